@@ -10,6 +10,8 @@ includes a formatted summary of every operation.
 from __future__ import annotations
 
 import argparse
+import json
+import re
 import subprocess
 import sys
 import time
@@ -21,7 +23,18 @@ import os
 REPO_ROOT = Path(__file__).resolve().parent.parent
 RUST_PROJECT = REPO_ROOT / "benchmark" / "rust_benchmark"
 RUST_BIN = RUST_PROJECT / "target" / "release" / "cross_lang_rust_tool"
+# Passed as --state-dir so the Rust tool does not depend on its working directory
+RUST_STATE_DIR = RUST_PROJECT / "tmp"
 ZIG_BIN = REPO_ROOT / "zig-out" / "bin" / "cross-lang-zig-tool"
+# Set by --profile: the Rust tool writes a flamegraph per command here
+RUST_PROFILE_DIR: Optional[Path] = None
+# Set by --mode: "cold" drops binaries and inputs from the page cache before
+# each timed command, "warm" runs each verification once untimed first. Unset,
+# commands are timed in whatever state the caches are in.
+MODE: Optional[str] = None
+# Set by --allocator: the Rust tool's global allocator, built in as a feature
+# unless it is the system one
+ALLOCATOR = "system"
 
 TMP_DIR = Path("/tmp")
 DEFAULT_SEED = "4242424242424242424242424242424242424242424242424242424242424242"
@@ -60,6 +73,7 @@ class OperationResult:
     duration: float
     stdout: str = ""
     stderr: str = ""
+    peak_rss_bytes: Optional[int] = None
 
 
 @dataclass
@@ -104,6 +118,28 @@ def parse_args() -> argparse.Namespace:
         action="store_true",
         help="(Deprecated: SSZ is now always used) Kept for backward compatibility.",
     )
+    parser.add_argument(
+        "--profile",
+        action="store_true",
+        help="Build the Rust tool with pprof and write a flamegraph SVG per Rust command to benchmark/rust_benchmark/tmp/profiles.",
+    )
+    parser.add_argument(
+        "--mode",
+        choices=("cold", "warm"),
+        default=None,
+        help="cold: every timed command starts with its binary and inputs dropped from the page cache; warm: each verification is run once untimed before the timed run. Without --mode the caches are left alone. The summary names the mode.",
+    )
+    parser.add_argument(
+        "--allocator",
+        choices=("system", "mimalloc", "jemalloc"),
+        default="system",
+        help="Global allocator to build the Rust tool with. The summary names it.",
+    )
+    parser.add_argument(
+        "--formats",
+        action="store_true",
+        help="After the scenarios, print the Rust tool's size and encode/decode time of each artifact in JSON, bincode, SSZ and the Zig binary format per lifetime.",
+    )
     args = parser.parse_args()
 
     if args.lifetimes is None:
@@ -208,8 +244,12 @@ def ensure_rust_binary() -> None:
     if RUST_BIN.exists():
         print(f"Removing existing Rust binary: {RUST_BIN}")
         RUST_BIN.unlink()
+    enabled = ["profile"] if RUST_PROFILE_DIR else []
+    if ALLOCATOR != "system":
+        enabled.append(ALLOCATOR)
+    features = ["--features", ",".join(enabled)] if enabled else []
     result = run_command(
-        ["cargo", "build", "--release", "--bin", "cross_lang_rust_tool"],
+        ["cargo", "build", "--release", "--bin", "cross_lang_rust_tool", *features],
         cwd=RUST_PROJECT,
         timeout=600,
     )
@@ -250,23 +290,80 @@ def prepare_tmp_files(paths: Dict[str, Path]) -> None:
             path.unlink()
 
 
+def rust_profile_args(name: str) -> list[str]:
+    """`--profile=<svg>` for a Rust command when profiling, else nothing."""
+    if RUST_PROFILE_DIR is None:
+        return []
+    return [f"--profile={RUST_PROFILE_DIR / f'{name}.svg'}"]
+
+
+def drop_page_cache(*paths: Path) -> None:
+    """Evict `paths` from the page cache so the next read goes to disk (Linux only)."""
+    if not hasattr(os, "posix_fadvise"):
+        return
+    for path in paths:
+        try:
+            fd = os.open(path, os.O_RDONLY)
+        except OSError:
+            continue
+        try:
+            os.posix_fadvise(fd, 0, 0, os.POSIX_FADV_DONTNEED)
+        finally:
+            os.close(fd)
+
+
+def settle_caches(cmd: list[str], inputs: list[Path], cwd: Path) -> None:
+    """Put the caches in the state --mode asks for before timing `cmd`."""
+    if MODE is None:
+        return
+    if MODE == "cold":
+        drop_page_cache(Path(cmd[0]), *inputs)
+    else:
+        print("(warm-up run, untimed)")
+        run_command(cmd, cwd=cwd)
+
+
 def command_duration(start: float) -> float:
     return time.perf_counter() - start
 
 
+def print_rust_keygen_phases(state_dir: Path) -> None:
+    """Print the per-phase keygen timings the Rust tool keeps in rust_key.json."""
+    try:
+        metadata = json.loads((state_dir / "rust_key.json").read_text())
+    except (OSError, ValueError):
+        return
+    phases = metadata.get("keygen_phases_ms")
+    if phases:
+        breakdown = ", ".join(f"{name.removesuffix('_ms')} {ms / 1000:.3f}s" for name, ms in phases.items())
+        print(f"Rust keygen phases: {breakdown}")
+
+
+def parse_peak_rss(blob: str) -> Optional[int]:
+    """Bytes from the Rust tool's `PEAK_RSS: 812.4 MiB (851861504 bytes)` line."""
+    match = re.search(r"PEAK_RSS: .*\((\d+) bytes\)", blob or "")
+    return int(match.group(1)) if match else None
+
+
+def format_rss(bytes_: Optional[int]) -> str:
+    return f"{bytes_ / (1024 * 1024):.1f} MiB" if bytes_ is not None else "n/a"
+
+
 def run_rust_sign(cfg: ScenarioConfig, paths: Dict[str, Path]) -> OperationResult:
     print(f"\n-- Rust key generation & signing ({cfg.lifetime}) --")
     
     # Setup tmp directory in project root
-    tmp_dir = RUST_PROJECT / "tmp"
+    tmp_dir = RUST_STATE_DIR
     tmp_dir.mkdir(exist_ok=True)
     
     # Save active epochs to file for the tool to read
     (tmp_dir / "rust_active_epochs.txt").write_text(str(cfg.num_active_epochs))
     
     # Generate keypair first
+    if MODE == "cold":
+        drop_page_cache(RUST_BIN)
     start = time.perf_counter()
-    keygen_cmd = [str(RUST_BIN), "keygen", cfg.seed_hex, cfg.lifetime, "--ssz"]
+    keygen_cmd = [str(RUST_BIN), "--state-dir", str(tmp_dir), *rust_profile_args(f"keygen_{cfg.tag}"), "keygen", cfg.seed_hex, cfg.lifetime, "--ssz"]
     keygen_result = run_command(
         keygen_cmd,
         cwd=RUST_PROJECT,
@@ -274,14 +371,22 @@ def run_rust_sign(cfg: ScenarioConfig, paths: Dict[str, Path]) -> OperationResul
     if keygen_result.returncode != 0:
         return OperationResult(False, command_duration(start), keygen_result.stdout, keygen_result.stderr)
     
+    print_rust_keygen_phases(tmp_dir)
+
     # Sign message
-    sign_cmd = [str(RUST_BIN), "sign", cfg.message, str(cfg.epoch), "--ssz"]
+    sign_cmd = [str(RUST_BIN), "--state-dir", str(tmp_dir), *rust_profile_args(f"sign_{cfg.tag}"), "sign", cfg.message, str(cfg.epoch), "--ssz"]
     sign_result = run_command(
         sign_cmd,
         cwd=RUST_PROJECT,
     )
     duration = command_duration(start)
     success = sign_result.returncode == 0
+    # Keygen and sign are separate processes; the larger peak is the one CI must fit
+    peaks = [
+        peak
+        for peak in (parse_peak_rss(keygen_result.stderr), parse_peak_rss(sign_result.stderr))
+        if peak is not None
+    ]
     
     # Copy files to /tmp with expected names
     if success:
@@ -293,7 +398,9 @@ def run_rust_sign(cfg: ScenarioConfig, paths: Dict[str, Path]) -> OperationResul
         print(f"Rust public key saved to: {paths['rust_pk']}")
         print(f"Rust signature saved to : {paths['rust_sig']}")
     
-    return OperationResult(success, duration, sign_result.stdout, sign_result.stderr)
+    return OperationResult(
+        success, duration, sign_result.stdout, sign_result.stderr, max(peaks, default=None)
+    )
 
 
 def run_zig_sign(cfg: ScenarioConfig, paths: Dict[str, Path], timeout_2_32: int) -> OperationResult:
@@ -315,6 +422,8 @@ def run_zig_sign(cfg: ScenarioConfig, paths: Dict[str, Path], timeout_2_32: int)
     (tmp_dir / "zig_active_epochs.txt").write_text(str(cfg.num_active_epochs))
     
     # Generate keypair first
+    if MODE == "cold":
+        drop_page_cache(ZIG_BIN)
     start = time.perf_counter()
     keygen_cmd = [str(ZIG_BIN), "keygen", cfg.seed_hex, cfg.lifetime, "--ssz"]
     keygen_result = run_command(
@@ -368,7 +477,6 @@ def run_zig_verify(
     tmp_dir.mkdir(exist_ok=True)
     (tmp_dir / "zig_lifetime.txt").write_text(cfg.lifetime)
     
-    start = time.perf_counter()
     verify_cmd = [
         str(ZIG_BIN),
         "verify",
@@ -378,6 +486,8 @@ def run_zig_verify(
         str(cfg.epoch),
         "--ssz",
     ]
+    settle_caches(verify_cmd, [pk_path, sig_path], REPO_ROOT)
+    start = time.perf_counter()
     result = run_command(
         verify_cmd,
         cwd=REPO_ROOT,
@@ -396,16 +506,22 @@ def run_rust_verify(
 ) -> OperationResult:
     print(f"\n-- {label} ({cfg.lifetime}) --")
     
-    start = time.perf_counter()
     verify_cmd = [
         str(RUST_BIN),
+        "--state-dir",
+        str(RUST_STATE_DIR),
+        *rust_profile_args(f"verify_{cfg.tag}_{sig_path.stem}"),
         "verify",
         str(sig_path),
         str(pk_path),
         cfg.message,
         str(cfg.epoch),
+        "--lifetime",
+        cfg.lifetime,
         "--ssz",
     ]
+    settle_caches(verify_cmd, [pk_path, sig_path], RUST_PROJECT)
+    start = time.perf_counter()
     result = run_command(
         verify_cmd,
         cwd=RUST_PROJECT,
@@ -441,11 +557,37 @@ def run_scenario(cfg: ScenarioConfig, timeout_2_32: int) -> tuple[Dict[str, Oper
     return results, paths
 
 
+def run_rust_formats(cfg: ScenarioConfig) -> bool:
+    """Print the format-comparison table for one lifetime."""
+    print(f"\n-- Wire formats ({cfg.lifetime}) --")
+    result = run_command(
+        [
+            str(RUST_BIN),
+            *rust_profile_args(f"formats_{cfg.tag}"),
+            "bench",
+            "formats",
+            "--lifetime",
+            cfg.lifetime,
+            "--seed",
+            cfg.seed_hex,
+        ],
+        cwd=RUST_PROJECT,
+        timeout=600,
+    )
+    return result.returncode == 0
+
+
 def print_summary(
     scenarios: list[ScenarioConfig],
     all_results: Dict[str, tuple[Dict[str, OperationResult], Dict[str, Path]]],
 ) -> bool:
-    print("\n=== Summary ===")
+    conditions = [f"{ALLOCATOR} allocator"]
+    if MODE is not None:
+        conditions.insert(0, {
+            "cold": "cold: page cache dropped before each command",
+            "warm": "warm: verifications after an untimed run",
+        }[MODE])
+    print(f"\n=== Summary ({'; '.join(conditions)}) ===")
     overall_success = True
     for cfg in scenarios:
         results, paths = all_results[cfg.lifetime]
@@ -454,14 +596,22 @@ def print_summary(
             result = results[key]
             status = "PASS" if result.success else "FAIL"
             overall_success &= result.success
-            print(f"  {SUMMARY_LABELS[key]:<30} {status:>4}  ({result.duration:.3f}s)")
+            line = f"  {SUMMARY_LABELS[key]:<30} {status:>4}  ({result.duration:.3f}s)"
+            if result.peak_rss_bytes is not None:
+                line += f"  peak RSS {format_rss(result.peak_rss_bytes)}"
+            print(line)
         print(f"  Rust public key: {paths['rust_pk']}")
         print(f"  Zig public key : {paths['zig_pk']}")
     return overall_success
 
 
 def main() -> int:
+    global ALLOCATOR, MODE, RUST_PROFILE_DIR
     args = parse_args()
+    MODE = args.mode
+    ALLOCATOR = args.allocator
+    if args.profile:
+        RUST_PROFILE_DIR = RUST_STATE_DIR / "profiles"
     scenarios = build_scenarios(args.lifetime_values, args.seed_hex)
 
     try:
@@ -484,6 +634,11 @@ def main() -> int:
 
     overall_success &= print_summary(scenarios, scenario_results)
 
+    if args.formats:
+        print("\n=== Wire formats ===")
+        for cfg in scenarios:
+            overall_success &= run_rust_formats(cfg)
+
     if overall_success:
         print("\n✅ Cross-language signing and verification complete.")
         return 0
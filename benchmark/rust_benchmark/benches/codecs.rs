@@ -0,0 +1,194 @@
+//! Codec microbenchmarks
+//!
+//! The cross-language benchmark times whole keygen, sign and verify runs, so
+//! a codec that gets twice as slow disappears into the totals. These time
+//! the conversions on their own, on a 2^8 signature and public key:
+//!
+//! ```sh
+//! cargo bench --bench codecs
+//! cargo bench --bench codecs -- ssz    # one group
+//! ```
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rust_benchmark::compat::binfmt::{self, Endian, Repr};
+use rust_benchmark::compat::json::{self, JsonStyle};
+use rust_benchmark::compat::ssz_codec;
+use rust_benchmark::field::{
+    canonical_to_montgomery, canonical_to_montgomery_slice, montgomery_to_canonical,
+    montgomery_to_canonical_slice, KOALABEAR_PRIME,
+};
+use rust_benchmark::lifetime::SchemeMetadata;
+use rust_benchmark::upstream::{
+    SIGTopLevelTargetSumLifetime8Dim64Base8 as Scheme, SignatureScheme,
+};
+use ssz::Encode;
+
+type PublicKey = <Scheme as SignatureScheme>::PublicKey;
+type Signature = <Scheme as SignatureScheme>::Signature;
+
+const MESSAGE: [u8; 32] = *b"codec microbenchmark message....";
+
+fn artifacts() -> (PublicKey, Signature) {
+    let mut rng = StdRng::from_seed([0x42; 32]);
+    let (pk, sk) = Scheme::key_gen(&mut rng, 0, 256);
+    let signature = Scheme::sign(&sk, 3, &MESSAGE).expect("signing failed");
+    (pk, signature)
+}
+
+fn field(c: &mut Criterion) {
+    let mut rng = StdRng::from_seed([7; 32]);
+    let canonical: Vec<u32> = (0..4096)
+        .map(|_| rng.random_range(0..KOALABEAR_PRIME as u32))
+        .collect();
+    let montgomery: Vec<u32> = canonical
+        .iter()
+        .map(|&x| canonical_to_montgomery(x))
+        .collect();
+
+    let mut group = c.benchmark_group("field");
+    group.throughput(Throughput::Elements(canonical.len() as u64));
+    group.bench_function("canonical_to_montgomery", |b| {
+        b.iter(|| {
+            black_box(&canonical)
+                .iter()
+                .map(|&x| canonical_to_montgomery(x))
+                .fold(0u32, u32::wrapping_add)
+        })
+    });
+    group.bench_function("montgomery_to_canonical", |b| {
+        b.iter(|| {
+            black_box(&montgomery)
+                .iter()
+                .map(|&x| montgomery_to_canonical(x))
+                .fold(0u32, u32::wrapping_add)
+        })
+    });
+    group.bench_function("canonical_to_montgomery_slice", |b| {
+        b.iter_batched_ref(
+            || canonical.clone(),
+            |words| canonical_to_montgomery_slice(words),
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("montgomery_to_canonical_slice", |b| {
+        b.iter_batched_ref(
+            || montgomery.clone(),
+            |words| montgomery_to_canonical_slice(words),
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+fn binary(c: &mut Criterion) {
+    let (_, signature) = artifacts();
+    let meta = Scheme::METADATA;
+    let value = json::signature_to_json(&signature, meta).unwrap();
+    let mut bytes = Vec::new();
+    binfmt::write_signature(&mut bytes, &value, meta, Repr::Montgomery, Endian::Little).unwrap();
+    let path = std::env::temp_dir().join(format!("hashzig_codecs_{}.bin", std::process::id()));
+
+    let mut group = c.benchmark_group("binary");
+    group.throughput(Throughput::Bytes(bytes.len() as u64));
+    group.bench_function("write_signature", |b| {
+        let mut out = Vec::with_capacity(bytes.len());
+        b.iter(|| {
+            out.clear();
+            binfmt::write_signature(
+                &mut out,
+                black_box(&value),
+                meta,
+                Repr::Montgomery,
+                Endian::Little,
+            )
+            .unwrap();
+        })
+    });
+    group.bench_function("read_signature", |b| {
+        b.iter(|| binfmt::read_signature(&mut black_box(bytes.as_slice()), meta).unwrap())
+    });
+    group.bench_function("write_signature_binary", |b| {
+        b.iter(|| {
+            binfmt::write_signature_binary(
+                black_box(&value),
+                &path,
+                meta,
+                Repr::Montgomery,
+                Endian::Little,
+            )
+            .unwrap()
+        })
+    });
+    group.bench_function("read_signature_binary", |b| {
+        b.iter(|| binfmt::read_signature_binary(&path, meta).unwrap())
+    });
+    group.finish();
+    let _ = std::fs::remove_file(&path);
+}
+
+fn json(c: &mut Criterion) {
+    let (_, signature) = artifacts();
+    let meta = Scheme::METADATA;
+    let value = json::signature_to_json(&signature, meta).unwrap();
+    let mut hex_value = value.clone();
+    json::to_hex_field_elements(&mut hex_value);
+
+    let mut group = c.benchmark_group("json");
+    group.bench_function("signature_to_json", |b| {
+        b.iter(|| json::signature_to_json(black_box(&signature), meta).unwrap())
+    });
+    group.bench_function("signature_from_json", |b| {
+        b.iter_batched(
+            || value.clone(),
+            |value| json::signature_from_json::<Signature>(value, meta).unwrap(),
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("to_hex_field_elements", |b| {
+        b.iter_batched(
+            || value.clone(),
+            |mut value| json::to_hex_field_elements(&mut value),
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("from_hex_field_elements", |b| {
+        b.iter_batched(
+            || hex_value.clone(),
+            |mut value| json::from_hex_field_elements(&mut value).unwrap(),
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("json_bytes_canonical", |b| {
+        b.iter(|| json::json_bytes(black_box(&value), JsonStyle::Canonical).unwrap())
+    });
+    group.finish();
+}
+
+fn ssz(c: &mut Criterion) {
+    let (pk, signature) = artifacts();
+    let meta = Scheme::METADATA;
+    let pk_bytes = pk.as_ssz_bytes();
+    let sig_bytes = signature.as_ssz_bytes();
+
+    let mut group = c.benchmark_group("ssz");
+    group.bench_function("encode_public_key", |b| {
+        b.iter(|| black_box(&pk).as_ssz_bytes())
+    });
+    group.bench_function("decode_public_key", |b| {
+        b.iter(|| ssz_codec::decode_public_key::<Scheme>(black_box(&pk_bytes), meta).unwrap())
+    });
+    group.throughput(Throughput::Bytes(sig_bytes.len() as u64));
+    group.bench_function("encode_signature", |b| {
+        b.iter(|| black_box(&signature).as_ssz_bytes())
+    });
+    group.bench_function("decode_signature", |b| {
+        b.iter(|| ssz_codec::decode_signature::<Scheme>(black_box(&sig_bytes), meta).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, field, binary, json, ssz);
+criterion_main!(benches);
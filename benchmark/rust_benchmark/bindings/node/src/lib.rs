@@ -0,0 +1,91 @@
+//! Node.js bindings for verifying Zig-produced signatures
+//!
+//! ```ts
+//! import { verify, convertSignature } from "hashzig-compat-node";
+//! const ok = verify(fs.readFileSync("zig_pk.json", "utf8"),
+//!                   fs.readFileSync("zig_sig.bin"), Buffer.from("hello"), 0, "2^8");
+//! ```
+//!
+//! Public keys are the trimmed serde JSON the tools write and signatures use
+//! the Zig binary layout, both decoded with `rust_benchmark::compat::codec`.
+
+use std::error::Error;
+
+use napi::bindgen_prelude::Buffer;
+use napi_derive::napi;
+
+use rust_benchmark::compat::codec;
+use rust_benchmark::config::Format;
+use rust_benchmark::lifetime::{LifetimeTag, SchemeMetadata};
+use rust_benchmark::upstream::{
+    SIGTopLevelTargetSumLifetime18Dim64Base8, SIGTopLevelTargetSumLifetime32Dim64Base8,
+    SIGTopLevelTargetSumLifetime8Dim64Base8,
+};
+
+fn to_napi_err(e: Box<dyn Error>) -> napi::Error {
+    napi::Error::from_reason(e.to_string())
+}
+
+fn verify_for_scheme<S: SchemeMetadata>(
+    pk_json: &str,
+    sig_bin: &[u8],
+    message: &[u8; 32],
+    epoch: u32,
+) -> Result<bool, Box<dyn Error>> {
+    let pk = codec::decode_public_key::<S>(pk_json.as_bytes(), Format::Json)?;
+    let sig = codec::decode_signature::<S>(sig_bin, Format::ZigBinary)?;
+    Ok(S::verify(&pk, epoch, message, &sig))
+}
+
+/// Verify a Zig binary signature against a JSON public key. Throws if either
+/// cannot be decoded for `lifetime` ("2^8", "2^18" or "2^32").
+#[napi]
+pub fn verify(
+    pk_json: String,
+    sig_bin: Buffer,
+    message: Buffer,
+    epoch: u32,
+    lifetime: String,
+) -> napi::Result<bool> {
+    let lifetime = LifetimeTag::parse(Some(&lifetime)).map_err(to_napi_err)?;
+    let message = codec::message_from_bytes(&message).map_err(to_napi_err)?;
+    match lifetime {
+        LifetimeTag::Pow8 => verify_for_scheme::<SIGTopLevelTargetSumLifetime8Dim64Base8>(
+            &pk_json, &sig_bin, &message, epoch,
+        ),
+        LifetimeTag::Pow18 => verify_for_scheme::<SIGTopLevelTargetSumLifetime18Dim64Base8>(
+            &pk_json, &sig_bin, &message, epoch,
+        ),
+        LifetimeTag::Pow32 => verify_for_scheme::<SIGTopLevelTargetSumLifetime32Dim64Base8>(
+            &pk_json, &sig_bin, &message, epoch,
+        ),
+    }
+    .map_err(to_napi_err)
+}
+
+/// Re-encode a signature between "json" (padded bincode), "ssz" and "bin"
+/// (Zig layout).
+#[napi]
+pub fn convert_signature(
+    signature: Buffer,
+    from: String,
+    to: String,
+    lifetime: String,
+) -> napi::Result<Buffer> {
+    let lifetime = LifetimeTag::parse(Some(&lifetime)).map_err(to_napi_err)?;
+    let from = Format::parse(&from).map_err(to_napi_err)?;
+    let to = Format::parse(&to).map_err(to_napi_err)?;
+    let bytes = match lifetime {
+        LifetimeTag::Pow8 => codec::convert_signature::<SIGTopLevelTargetSumLifetime8Dim64Base8>(
+            &signature, from, to,
+        ),
+        LifetimeTag::Pow18 => codec::convert_signature::<SIGTopLevelTargetSumLifetime18Dim64Base8>(
+            &signature, from, to,
+        ),
+        LifetimeTag::Pow32 => codec::convert_signature::<SIGTopLevelTargetSumLifetime32Dim64Base8>(
+            &signature, from, to,
+        ),
+    }
+    .map_err(to_napi_err)?;
+    Ok(bytes.into())
+}
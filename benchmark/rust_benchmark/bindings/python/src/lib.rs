@@ -0,0 +1,277 @@
+//! Python bindings for the Rust side of the cross-language tests
+//!
+//! Exposes keygen/sign/verify/convert on in-memory bytes so the Python
+//! orchestration does not have to shell out to `cross_lang_rust_tool` and
+//! exchange keys through `tmp/`. Encodings are the ones the CLIs write; see
+//! `rust_benchmark::compat::codec`.
+//!
+//! ```python
+//! import hashzig_compat_py as hz
+//! keys = hz.keygen("2^8", seed=bytes(32))
+//! sig = hz.sign(keys["secret_key"], b"hello", 0, "2^8")
+//! assert hz.verify(keys["public_key"], sig, b"hello", 0, "2^8")
+//! zig_sig = hz.convert(sig, "json", "bin", "2^8")
+//! ```
+
+// pyo3 0.22's `#[pyfunction]` expansion trips this lint on `PyResult` returns.
+#![allow(clippy::useless_conversion)]
+
+use std::error::Error;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict};
+use rand::{rngs::StdRng, SeedableRng};
+
+use rust_benchmark::compat::codec;
+use rust_benchmark::compat::json::signature_to_json;
+use rust_benchmark::config::{Format, SchemeConfig};
+use rust_benchmark::lifetime::{LifetimeTag, SchemeMetadata};
+use rust_benchmark::signer::prepare_epoch;
+use rust_benchmark::upstream::{
+    SIGTopLevelTargetSumLifetime18Dim64Base8, SIGTopLevelTargetSumLifetime32Dim64Base8,
+    SIGTopLevelTargetSumLifetime8Dim64Base8,
+};
+
+type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+fn to_py_err(e: Box<dyn Error>) -> PyErr {
+    PyValueError::new_err(e.to_string())
+}
+
+fn keygen_for_scheme<S: SchemeMetadata>(config: &SchemeConfig) -> Result<(Vec<u8>, Vec<u8>)> {
+    let mut rng = StdRng::from_seed(config.seed().ok_or("keygen requires a seed")?);
+    let (pk, sk) = S::key_gen(
+        &mut rng,
+        config.activation_epoch(),
+        config.num_active_epochs(),
+    );
+    Ok((
+        codec::encode_public_key::<S>(&pk, config.format())?,
+        codec::encode_secret_key::<S>(&sk, config.format())?,
+    ))
+}
+
+fn sign_for_scheme<S: SchemeMetadata>(
+    sk: &[u8],
+    message: &[u8; 32],
+    epoch: u32,
+    key_format: Format,
+    sig_format: Format,
+) -> Result<Vec<u8>> {
+    let mut sk = codec::decode_secret_key::<S>(sk, key_format)?;
+    if !prepare_epoch(&mut sk, u64::from(epoch)) {
+        return Err(format!("epoch {epoch} cannot be prepared").into());
+    }
+    let signature = S::sign(&sk, epoch, message)
+        .map_err(|e| format!("failed to sign message at epoch {epoch}: {e:?}"))?;
+    codec::encode_signature::<S>(&signature, sig_format)
+}
+
+fn verify_for_scheme<S: SchemeMetadata>(
+    pk: &[u8],
+    sig: &[u8],
+    message: &[u8; 32],
+    epoch: u32,
+    key_format: Format,
+    sig_format: Format,
+) -> Result<bool> {
+    let pk = codec::decode_public_key::<S>(pk, key_format)?;
+    let sig = codec::decode_signature::<S>(sig, sig_format)?;
+    Ok(S::verify(&pk, epoch, message, &sig))
+}
+
+fn signature_json_for_scheme<S: SchemeMetadata>(sig: &[u8], format: Format) -> Result<String> {
+    let sig = codec::decode_signature::<S>(sig, format)?;
+    Ok(signature_to_json(&sig, S::METADATA)?.to_string())
+}
+
+/// Generate a key pair. Returns a dict with `public_key` and `secret_key`
+/// bytes in `format` ("json" or "ssz"). A random seed is used if none is given.
+#[pyfunction]
+#[pyo3(signature = (lifetime="2^8", seed=None, activation_epoch=0, num_active_epochs=256, format="json"))]
+fn keygen<'py>(
+    py: Python<'py>,
+    lifetime: &str,
+    seed: Option<&[u8]>,
+    activation_epoch: usize,
+    num_active_epochs: usize,
+    format: &str,
+) -> PyResult<Bound<'py, PyDict>> {
+    let seed: [u8; 32] = match seed {
+        Some(seed) => seed
+            .try_into()
+            .map_err(|_| PyValueError::new_err("seed must be exactly 32 bytes"))?,
+        None => {
+            let mut seed = [0u8; 32];
+            getrandom::getrandom(&mut seed).map_err(|e| {
+                PyValueError::new_err(format!("failed to generate random seed: {e}"))
+            })?;
+            seed
+        }
+    };
+    let config = SchemeConfig::new()
+        .lifetime(LifetimeTag::parse(Some(lifetime)).map_err(to_py_err)?)
+        .seed(seed)
+        .activation(activation_epoch, num_active_epochs)
+        .format(Format::parse(format).map_err(to_py_err)?)
+        .build()
+        .map_err(to_py_err)?;
+    let (pk, sk) = py
+        .allow_threads(|| {
+            match config.lifetime() {
+                LifetimeTag::Pow8 => {
+                    keygen_for_scheme::<SIGTopLevelTargetSumLifetime8Dim64Base8>(&config)
+                }
+                LifetimeTag::Pow18 => {
+                    keygen_for_scheme::<SIGTopLevelTargetSumLifetime18Dim64Base8>(&config)
+                }
+                LifetimeTag::Pow32 => {
+                    keygen_for_scheme::<SIGTopLevelTargetSumLifetime32Dim64Base8>(&config)
+                }
+            }
+            .map_err(|e| e.to_string())
+        })
+        .map_err(PyValueError::new_err)?;
+    let dict = PyDict::new_bound(py);
+    dict.set_item("lifetime", config.lifetime().as_str())?;
+    dict.set_item("public_key", PyBytes::new_bound(py, &pk))?;
+    dict.set_item("secret_key", PyBytes::new_bound(py, &sk))?;
+    Ok(dict)
+}
+
+/// Sign `message` (at most 32 bytes, zero-padded) at `epoch`. The key is
+/// advanced to `epoch` in memory; the caller's bytes are not updated.
+#[pyfunction]
+#[pyo3(signature = (secret_key, message, epoch, lifetime="2^8", key_format="json", sig_format="json"))]
+fn sign<'py>(
+    py: Python<'py>,
+    secret_key: &[u8],
+    message: &[u8],
+    epoch: u32,
+    lifetime: &str,
+    key_format: &str,
+    sig_format: &str,
+) -> PyResult<Bound<'py, PyBytes>> {
+    let lifetime = LifetimeTag::parse(Some(lifetime)).map_err(to_py_err)?;
+    let key_format = Format::parse(key_format).map_err(to_py_err)?;
+    let sig_format = Format::parse(sig_format).map_err(to_py_err)?;
+    let message = codec::message_from_bytes(message).map_err(to_py_err)?;
+    let signature = py
+        .allow_threads(|| {
+            match lifetime {
+                LifetimeTag::Pow8 => sign_for_scheme::<SIGTopLevelTargetSumLifetime8Dim64Base8>(
+                    secret_key, &message, epoch, key_format, sig_format,
+                ),
+                LifetimeTag::Pow18 => sign_for_scheme::<SIGTopLevelTargetSumLifetime18Dim64Base8>(
+                    secret_key, &message, epoch, key_format, sig_format,
+                ),
+                LifetimeTag::Pow32 => sign_for_scheme::<SIGTopLevelTargetSumLifetime32Dim64Base8>(
+                    secret_key, &message, epoch, key_format, sig_format,
+                ),
+            }
+            .map_err(|e| e.to_string())
+        })
+        .map_err(PyValueError::new_err)?;
+    Ok(PyBytes::new_bound(py, &signature))
+}
+
+/// Verify a signature. Raises `ValueError` if the inputs cannot be decoded.
+#[pyfunction]
+#[pyo3(signature = (public_key, signature, message, epoch, lifetime="2^8", key_format="json", sig_format="json"))]
+#[allow(clippy::too_many_arguments)]
+fn verify(
+    py: Python<'_>,
+    public_key: &[u8],
+    signature: &[u8],
+    message: &[u8],
+    epoch: u32,
+    lifetime: &str,
+    key_format: &str,
+    sig_format: &str,
+) -> PyResult<bool> {
+    let lifetime = LifetimeTag::parse(Some(lifetime)).map_err(to_py_err)?;
+    let key_format = Format::parse(key_format).map_err(to_py_err)?;
+    let sig_format = Format::parse(sig_format).map_err(to_py_err)?;
+    let message = codec::message_from_bytes(message).map_err(to_py_err)?;
+    py.allow_threads(|| {
+        match lifetime {
+            LifetimeTag::Pow8 => verify_for_scheme::<SIGTopLevelTargetSumLifetime8Dim64Base8>(
+                public_key, signature, &message, epoch, key_format, sig_format,
+            ),
+            LifetimeTag::Pow18 => verify_for_scheme::<SIGTopLevelTargetSumLifetime18Dim64Base8>(
+                public_key, signature, &message, epoch, key_format, sig_format,
+            ),
+            LifetimeTag::Pow32 => verify_for_scheme::<SIGTopLevelTargetSumLifetime32Dim64Base8>(
+                public_key, signature, &message, epoch, key_format, sig_format,
+            ),
+        }
+        .map_err(|e| e.to_string())
+    })
+    .map_err(PyValueError::new_err)
+}
+
+/// Re-encode a signature, e.g. from "json" (bincode) to "bin" (Zig layout).
+#[pyfunction]
+#[pyo3(signature = (signature, from_format, to_format, lifetime="2^8"))]
+fn convert<'py>(
+    py: Python<'py>,
+    signature: &[u8],
+    from_format: &str,
+    to_format: &str,
+    lifetime: &str,
+) -> PyResult<Bound<'py, PyBytes>> {
+    let lifetime = LifetimeTag::parse(Some(lifetime)).map_err(to_py_err)?;
+    let from = Format::parse(from_format).map_err(to_py_err)?;
+    let to = Format::parse(to_format).map_err(to_py_err)?;
+    let bytes = match lifetime {
+        LifetimeTag::Pow8 => {
+            codec::convert_signature::<SIGTopLevelTargetSumLifetime8Dim64Base8>(signature, from, to)
+        }
+        LifetimeTag::Pow18 => codec::convert_signature::<SIGTopLevelTargetSumLifetime18Dim64Base8>(
+            signature, from, to,
+        ),
+        LifetimeTag::Pow32 => codec::convert_signature::<SIGTopLevelTargetSumLifetime32Dim64Base8>(
+            signature, from, to,
+        ),
+    }
+    .map_err(to_py_err)?;
+    Ok(PyBytes::new_bound(py, &bytes))
+}
+
+/// Decode a signature into the JSON shape the Zig side uses, as a dict.
+#[pyfunction]
+#[pyo3(signature = (signature, format="json", lifetime="2^8"))]
+fn signature_to_dict<'py>(
+    py: Python<'py>,
+    signature: &[u8],
+    format: &str,
+    lifetime: &str,
+) -> PyResult<Bound<'py, PyAny>> {
+    let lifetime = LifetimeTag::parse(Some(lifetime)).map_err(to_py_err)?;
+    let format = Format::parse(format).map_err(to_py_err)?;
+    let json = match lifetime {
+        LifetimeTag::Pow8 => {
+            signature_json_for_scheme::<SIGTopLevelTargetSumLifetime8Dim64Base8>(signature, format)
+        }
+        LifetimeTag::Pow18 => {
+            signature_json_for_scheme::<SIGTopLevelTargetSumLifetime18Dim64Base8>(signature, format)
+        }
+        LifetimeTag::Pow32 => {
+            signature_json_for_scheme::<SIGTopLevelTargetSumLifetime32Dim64Base8>(signature, format)
+        }
+    }
+    .map_err(to_py_err)?;
+    py.import_bound("json")?.call_method1("loads", (json,))
+}
+
+#[pymodule]
+fn hashzig_compat_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("BACKEND", rust_benchmark::upstream::BACKEND_NAME)?;
+    m.add_function(wrap_pyfunction!(keygen, m)?)?;
+    m.add_function(wrap_pyfunction!(sign, m)?)?;
+    m.add_function(wrap_pyfunction!(verify, m)?)?;
+    m.add_function(wrap_pyfunction!(convert, m)?)?;
+    m.add_function(wrap_pyfunction!(signature_to_dict, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,49 @@
+//! Build-time steps for optional features.
+//!
+//! - `zig-ffi` links the Zig static library. Build it first with
+//!   `zig build c-api` from the repository root. The library is looked up in
+//!   `HASH_ZIG_LIB_DIR`, defaulting to `zig-out/lib`.
+//! - `grpc` generates the tonic service from `proto/hashsig.proto` using the
+//!   vendored `protoc`, so no system protobuf install is needed.
+//! - `protobuf` generates the prost messages in `proto/interchange.proto`,
+//!   also with the vendored `protoc`.
+
+use std::env;
+use std::path::PathBuf;
+
+fn main() {
+    println!("cargo:rerun-if-env-changed=HASH_ZIG_LIB_DIR");
+    if env::var_os("CARGO_FEATURE_ZIG_FFI").is_some() {
+        link_zig();
+    }
+    #[cfg(feature = "grpc")]
+    compile_protos();
+    #[cfg(feature = "protobuf")]
+    compile_interchange();
+}
+
+fn link_zig() {
+    let lib_dir = match env::var_os("HASH_ZIG_LIB_DIR") {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap()).join("../../zig-out/lib"),
+    };
+    println!("cargo:rustc-link-search=native={}", lib_dir.display());
+    println!("cargo:rustc-link-lib=static=hash-zig-c");
+}
+
+#[cfg(feature = "grpc")]
+fn compile_protos() {
+    let protoc = protoc_bin_vendored::protoc_bin_path().expect("vendored protoc");
+    env::set_var("PROTOC", protoc);
+    println!("cargo:rerun-if-changed=proto/hashsig.proto");
+    tonic_build::compile_protos("proto/hashsig.proto").expect("compile proto/hashsig.proto");
+}
+
+#[cfg(feature = "protobuf")]
+fn compile_interchange() {
+    let protoc = protoc_bin_vendored::protoc_bin_path().expect("vendored protoc");
+    env::set_var("PROTOC", protoc);
+    println!("cargo:rerun-if-changed=proto/interchange.proto");
+    prost_build::compile_protos(&["proto/interchange.proto"], &["proto"])
+        .expect("compile proto/interchange.proto");
+}
@@ -0,0 +1,41 @@
+/*
+ * C ABI exported by librust_benchmark when built with `--features capi`.
+ * See src/capi.rs for the Rust side. Keys and signatures are SSZ-encoded;
+ * messages are exactly 32 bytes. log_lifetime is one of 8, 18 or 32.
+ */
+#ifndef HASHZIG_RUST_H
+#define HASHZIG_RUST_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#define HASHZIG_OK 0
+#define HASHZIG_ERR_NULL_POINTER -1
+#define HASHZIG_ERR_LIFETIME -2
+#define HASHZIG_ERR_CONFIG -3
+#define HASHZIG_ERR_BUFFER_TOO_SMALL -4
+#define HASHZIG_ERR_DECODE -5
+#define HASHZIG_ERR_SIGN -6
+#define HASHZIG_ERR_PANIC -7
+/* -8 is the Zig library's internal error (benchmark/zig_benchmark/src/c_api.zig). */
+/* The epoch lies outside the secret key's activation interval. */
+#define HASHZIG_ERR_EPOCH -9
+
+/* On HASHZIG_ERR_BUFFER_TOO_SMALL the required sizes are still written to
+ * *pk_len / *sk_len. */
+int32_t hashzig_keygen_into_buffer(uint32_t log_lifetime, const uint8_t seed[32],
+                                   uint64_t activation_epoch, uint64_t num_active_epochs,
+                                   uint8_t *pk_out, size_t pk_cap, size_t *pk_len,
+                                   uint8_t *sk_out, size_t sk_cap, size_t *sk_len);
+
+/* The key is advanced to `epoch` in memory before signing. */
+int32_t hashzig_sign(uint32_t log_lifetime, const uint8_t *sk, size_t sk_len,
+                     uint32_t epoch, const uint8_t message[32],
+                     uint8_t *sig_out, size_t sig_cap, size_t *sig_len);
+
+/* Returns 1 if the signature is valid, 0 if not, negative on error. */
+int32_t hashzig_verify(uint32_t log_lifetime, const uint8_t *pk, size_t pk_len,
+                       uint32_t epoch, const uint8_t message[32],
+                       const uint8_t *sig, size_t sig_len);
+
+#endif /* HASHZIG_RUST_H */
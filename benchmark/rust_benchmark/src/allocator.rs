@@ -0,0 +1,24 @@
+//! Which global allocator this build uses
+//!
+//! Keygen at the larger lifetimes and the JSON codecs allocate heavily, so
+//! the allocator is one of the things a timing comparison has to hold fixed.
+//! The `mimalloc` and `jemalloc` features swap it in for every binary linking
+//! this crate; with both on, mimalloc wins so `--all-features` still builds.
+//! [`NAME`] goes into each command's `--json` document as `allocator`.
+
+#[cfg(feature = "mimalloc")]
+#[global_allocator]
+static GLOBAL: mimalloc::MiMalloc = mimalloc::MiMalloc;
+
+#[cfg(all(feature = "jemalloc", not(feature = "mimalloc")))]
+#[global_allocator]
+static GLOBAL: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
+
+/// `mimalloc`, `jemalloc` or `system`.
+pub const NAME: &str = if cfg!(feature = "mimalloc") {
+    "mimalloc"
+} else if cfg!(feature = "jemalloc") {
+    "jemalloc"
+} else {
+    "system"
+};
@@ -0,0 +1,157 @@
+//! Consistency audit of a secret key against its public key
+//!
+//! A secret key whose PRF key, parameter or cached trees have been corrupted,
+//! or that was paired with the wrong public key file, only fails once someone
+//! tries to verify what it signed. `audit_key` catches this up front: for each
+//! audited epoch it signs a fixed message, which re-derives the one-time
+//! chain starts from the secret key and reads the Merkle path from its trees,
+//! and then verifies against the stored public key, which recomputes the leaf
+//! and walks the path up to the root.
+
+use std::error::Error;
+use std::ops::Range;
+
+use rand::seq::index;
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+
+use crate::signer::prepare_epoch;
+use crate::upstream::{SignatureScheme, SignatureSchemeSecretKey};
+
+/// Default number of epochs checked by a sampled audit.
+pub const DEFAULT_SAMPLES: usize = 16;
+
+const AUDIT_MESSAGE_PREFIX: &[u8; 16] = b"hash-zig audit  ";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditEpochs {
+    /// The first and last active epoch plus uniformly sampled ones, at most `n`
+    Sample(usize),
+    /// Every epoch in the activation interval
+    All,
+}
+
+impl AuditEpochs {
+    /// `all` or a sample count.
+    pub fn parse(raw: &str) -> Result<Self, Box<dyn Error>> {
+        match raw {
+            "all" => Ok(Self::All),
+            n => Ok(Self::Sample(n.parse().map_err(|_| {
+                format!("invalid audit epoch set '{n}', expected 'all' or a count")
+            })?)),
+        }
+    }
+
+    /// Ascending list of epochs to audit within `interval`.
+    pub fn select(self, interval: Range<u64>, seed: [u8; 32]) -> Vec<u32> {
+        let len = (interval.end - interval.start) as usize;
+        let mut epochs: Vec<u64> = match self {
+            AuditEpochs::All => interval.collect(),
+            AuditEpochs::Sample(n) if n >= len => interval.collect(),
+            AuditEpochs::Sample(n) => {
+                let mut rng = ChaCha20Rng::from_seed(seed);
+                let mut picked: Vec<u64> = index::sample(&mut rng, len, n.saturating_sub(2))
+                    .into_iter()
+                    .map(|i| interval.start + i as u64)
+                    .collect();
+                if n > 0 {
+                    picked.push(interval.start);
+                }
+                if n > 1 {
+                    picked.push(interval.end - 1);
+                }
+                picked
+            }
+        };
+        epochs.sort_unstable();
+        epochs.dedup();
+        epochs.into_iter().map(|e| e as u32).collect()
+    }
+}
+
+/// Message signed at `epoch` during an audit.
+pub fn audit_message(epoch: u32) -> [u8; 32] {
+    let mut message = [0u8; 32];
+    message[..16].copy_from_slice(AUDIT_MESSAGE_PREFIX);
+    message[16..20].copy_from_slice(&epoch.to_le_bytes());
+    message
+}
+
+#[derive(Debug, Clone)]
+pub struct AuditFailure {
+    pub epoch: u32,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct AuditReport {
+    pub activation_interval: Range<u64>,
+    pub checked: Vec<u32>,
+    pub failures: Vec<AuditFailure>,
+}
+
+impl AuditReport {
+    pub fn is_consistent(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "activation_start": self.activation_interval.start,
+            "activation_end": self.activation_interval.end,
+            "checked": self.checked.len(),
+            "consistent": self.is_consistent(),
+            "failures": self.failures.iter().map(|f| serde_json::json!({
+                "epoch": f.epoch,
+                "reason": f.reason,
+            })).collect::<Vec<_>>(),
+        })
+    }
+}
+
+/// Sign and verify at each selected epoch, advancing `sk`'s preparation
+/// window as needed. The key is consumed in the sense that its prepared
+/// interval ends up past the last audited epoch; audit a freshly loaded copy.
+pub fn audit_key<S>(
+    pk: &S::PublicKey,
+    sk: &mut S::SecretKey,
+    epochs: AuditEpochs,
+    seed: [u8; 32],
+) -> AuditReport
+where
+    S: SignatureScheme,
+    S::SecretKey: SignatureSchemeSecretKey,
+{
+    let activation_interval = sk.get_activation_interval();
+    let checked = epochs.select(activation_interval.clone(), seed);
+    let mut failures = Vec::new();
+    for &epoch in &checked {
+        if !prepare_epoch(sk, epoch as u64) {
+            failures.push(AuditFailure {
+                epoch,
+                reason: format!(
+                    "secret key cannot prepare this epoch (prepared {:?})",
+                    sk.get_prepared_interval()
+                ),
+            });
+            continue;
+        }
+        let message = audit_message(epoch);
+        match S::sign(sk, epoch, &message) {
+            Ok(signature) if S::verify(pk, epoch, &message, &signature) => {}
+            Ok(_) => failures.push(AuditFailure {
+                epoch,
+                reason: "signature does not verify against the public key".to_string(),
+            }),
+            Err(e) => failures.push(AuditFailure {
+                epoch,
+                reason: format!("signing failed: {e:?}"),
+            }),
+        }
+    }
+    AuditReport {
+        activation_interval,
+        checked,
+        failures,
+    }
+}
@@ -0,0 +1,186 @@
+//! Comparing benchmark results with a stored baseline
+//!
+//! A codec or tree change that makes keygen 10% slower is easy to miss when
+//! runs are only looked at by eye. `bench ops` and `bench scaling` take an
+//! earlier result as a baseline and fail when a timing grew by more than a
+//! threshold. The baseline is a saved `OPS_RESULT:`/`SCALING_RESULT:`
+//! object or the whole `--json` document of such a run. Timings are
+//! matched by name: the median of each `bench ops` operation, and each
+//! `bench scaling` operation at each thread count. Timings only one side
+//! has are listed but never fail the run. A cold baseline is never compared
+//! with a warm run or the other way round.
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+use serde_json::{json, Value};
+
+/// A threshold such as `5%` or `0.05`, as a fraction.
+pub fn parse_threshold(raw: &str) -> Result<f64, Box<dyn Error>> {
+    let raw = raw.trim();
+    let fraction = match raw.strip_suffix('%') {
+        Some(percent) => percent.trim().parse::<f64>().map(|p| p / 100.0),
+        None => raw.parse::<f64>(),
+    }
+    .map_err(|_| format!("'{raw}' is not a threshold like 5% or 0.05"))?;
+    if !fraction.is_finite() || fraction < 0.0 {
+        return Err(format!("threshold '{raw}' must not be negative").into());
+    }
+    Ok(fraction)
+}
+
+/// The `bench ops` or `bench scaling` result within a `--json` document.
+fn result(report: &Value) -> &Value {
+    report
+        .get("ops")
+        .or_else(|| report.get("scaling"))
+        .unwrap_or(report)
+}
+
+/// `warm` or `cold`, the regime a result was measured in. Results from
+/// before the distinction are warm.
+pub fn regime(report: &Value) -> &str {
+    result(report)
+        .get("mode")
+        .and_then(Value::as_str)
+        .unwrap_or("warm")
+}
+
+/// Timings in milliseconds by name, from a `bench ops` or `bench scaling`
+/// result or a `--json` document holding one.
+pub fn timings(report: &Value) -> BTreeMap<String, f64> {
+    let report = result(report);
+    let mut timings = BTreeMap::new();
+    if let Some(metrics) = report.get("metrics").and_then(Value::as_object) {
+        for (name, summary) in metrics {
+            if let Some(ms) = summary.get("median_ms").and_then(Value::as_f64) {
+                timings.insert(name.clone(), ms);
+            }
+        }
+    }
+    for row in report
+        .get("rows")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        let Some(threads) = row.get("threads").and_then(Value::as_u64) else {
+            continue;
+        };
+        for op in ["keygen", "sign", "verify"] {
+            if let Some(ms) = row.get(format!("{op}_ms")).and_then(Value::as_f64) {
+                timings.insert(format!("{op}@{threads}"), ms);
+            }
+        }
+    }
+    timings
+}
+
+pub fn load(path: &Path) -> Result<Value, Box<dyn Error>> {
+    let bytes = fs::read(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    // The first OPS_RESULT:/SCALING_RESULT: line, if stdout was saved whole
+    let text = String::from_utf8_lossy(&bytes);
+    let json = text
+        .lines()
+        .find_map(|line| {
+            line.strip_prefix("OPS_RESULT:")
+                .or_else(|| line.strip_prefix("SCALING_RESULT:"))
+        })
+        .unwrap_or(&text);
+    serde_json::from_str(json).map_err(|e| format!("{}: {e}", path.display()).into())
+}
+
+/// One timing on both sides.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Delta {
+    pub name: String,
+    pub baseline_ms: f64,
+    pub current_ms: f64,
+}
+
+impl Delta {
+    /// Relative change, e.g. 0.08 for 8% slower.
+    pub fn change(&self) -> f64 {
+        self.current_ms / self.baseline_ms.max(f64::EPSILON) - 1.0
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Comparison {
+    pub max_regression: f64,
+    pub deltas: Vec<Delta>,
+    /// Timings only one side has
+    pub unmatched: Vec<String>,
+}
+
+impl Comparison {
+    pub fn regressions(&self) -> impl Iterator<Item = &Delta> {
+        self.deltas
+            .iter()
+            .filter(|delta| delta.change() > self.max_regression)
+    }
+
+    pub fn is_regression(&self) -> bool {
+        self.regressions().next().is_some()
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "max_regression": self.max_regression,
+            "regressed": self.is_regression(),
+            "deltas": self.deltas.iter().map(|delta| json!({
+                "metric": delta.name,
+                "baseline_ms": delta.baseline_ms,
+                "current_ms": delta.current_ms,
+                "change": delta.change(),
+                "regressed": delta.change() > self.max_regression,
+            })).collect::<Vec<_>>(),
+            "unmatched": self.unmatched,
+        })
+    }
+}
+
+/// Compare `current` with `baseline`. Both must be measured in the same
+/// regime and at least one timing must be on both sides, so comparing
+/// against the wrong kind of file is an error.
+pub fn compare(
+    baseline: &Value,
+    current: &Value,
+    max_regression: f64,
+) -> Result<Comparison, Box<dyn Error>> {
+    let (baseline_regime, current_regime) = (regime(baseline), regime(current));
+    if baseline_regime != current_regime {
+        return Err(format!(
+            "the baseline was measured {baseline_regime} and this run {current_regime}"
+        )
+        .into());
+    }
+    let baseline = timings(baseline);
+    let current = timings(current);
+    let deltas: Vec<Delta> = current
+        .iter()
+        .filter_map(|(name, &current_ms)| {
+            baseline.get(name).map(|&baseline_ms| Delta {
+                name: name.clone(),
+                baseline_ms,
+                current_ms,
+            })
+        })
+        .collect();
+    if deltas.is_empty() {
+        return Err("the baseline has none of this run's timings".into());
+    }
+    let unmatched = baseline
+        .keys()
+        .chain(current.keys())
+        .filter(|name| !(baseline.contains_key(*name) && current.contains_key(*name)))
+        .cloned()
+        .collect();
+    Ok(Comparison {
+        max_regression,
+        deltas,
+        unmatched,
+    })
+}
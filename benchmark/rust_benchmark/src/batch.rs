@@ -0,0 +1,155 @@
+//! Batch verification against one public key
+//!
+//! Consumers checking many signatures used to loop over the CLI's
+//! `verify_for_scheme`, paying file I/O and debug output per call. These
+//! functions take already-decoded signatures and verify them on the rayon
+//! pool; hash-based verification is independent per signature, so the work
+//! splits without coordination. They run on rayon's global pool, which
+//! `hashzig-tool -j` sizes; a library caller sharing that pool with other
+//! work can pass a [`Parallelism`] to `verify_batch_with` instead.
+//!
+//! With the `async-io` feature, `verify_batch_async` does the same from a
+//! tokio runtime: each verification runs on the blocking pool and at most
+//! `max_concurrency` run at once, so a large batch cannot starve the
+//! daemon's other requests of blocking threads.
+
+use std::error::Error;
+#[cfg(feature = "async-io")]
+use std::sync::Arc;
+
+use rayon::prelude::*;
+use rayon::ThreadPoolBuilder;
+
+use crate::upstream::SignatureScheme;
+
+/// One signature to check: `(epoch, message, signature)`.
+pub type BatchItem<S> = (u32, [u8; 32], <S as SignatureScheme>::Signature);
+
+/// How many threads a batch is verified on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Parallelism {
+    /// Rayon's global pool
+    #[default]
+    Global,
+    /// A pool of its own with this many threads, 0 for one per core
+    Threads(usize),
+}
+
+impl Parallelism {
+    /// `Global` for 0, else a pool of `threads`.
+    pub fn from_threads(threads: usize) -> Self {
+        match threads {
+            0 => Parallelism::Global,
+            threads => Parallelism::Threads(threads),
+        }
+    }
+
+    /// Run `work` with this parallelism: on the calling thread's pool, or on
+    /// a pool built for the call.
+    pub fn install<R, F>(self, work: F) -> Result<R, Box<dyn Error>>
+    where
+        R: Send,
+        F: FnOnce() -> R + Send,
+    {
+        match self {
+            Parallelism::Global => Ok(work()),
+            Parallelism::Threads(threads) => Ok(ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()?
+                .install(work)),
+        }
+    }
+
+    /// Threads `install` runs its work on.
+    pub fn threads(self) -> usize {
+        match self {
+            Parallelism::Global => rayon::current_num_threads(),
+            Parallelism::Threads(0) => std::thread::available_parallelism().map_or(1, |n| n.get()),
+            Parallelism::Threads(threads) => threads,
+        }
+    }
+}
+
+/// Verify every item, returning one result per item in input order.
+pub fn verify_batch<S>(pk: &S::PublicKey, items: &[BatchItem<S>]) -> Vec<bool>
+where
+    S: SignatureScheme,
+    S::PublicKey: Sync,
+    S::Signature: Sync,
+{
+    items
+        .par_iter()
+        .map(|(epoch, message, signature)| S::verify(pk, *epoch, message, signature))
+        .collect()
+}
+
+/// [`verify_batch`] with `parallelism` threads.
+pub fn verify_batch_with<S>(
+    pk: &S::PublicKey,
+    items: &[BatchItem<S>],
+    parallelism: Parallelism,
+) -> Result<Vec<bool>, Box<dyn Error>>
+where
+    S: SignatureScheme,
+    S::PublicKey: Sync,
+    S::Signature: Sync,
+{
+    parallelism.install(|| verify_batch::<S>(pk, items))
+}
+
+/// True only if every item verifies. Stops scheduling work after the first
+/// failure, so a bad batch is rejected without checking the rest.
+pub fn verify_batch_all<S>(pk: &S::PublicKey, items: &[BatchItem<S>]) -> bool
+where
+    S: SignatureScheme,
+    S::PublicKey: Sync,
+    S::Signature: Sync,
+{
+    items
+        .par_iter()
+        .all(|(epoch, message, signature)| S::verify(pk, *epoch, message, signature))
+}
+
+/// Verify every item on tokio's blocking pool with at most
+/// `max_concurrency` verifications in flight, returning one result per item
+/// in input order. A `max_concurrency` of 0 is treated as 1.
+#[cfg(feature = "async-io")]
+pub async fn verify_batch_async<S>(
+    pk: Arc<S::PublicKey>,
+    items: Vec<BatchItem<S>>,
+    max_concurrency: usize,
+) -> Vec<bool>
+where
+    S: SignatureScheme + 'static,
+    S::PublicKey: Send + Sync + 'static,
+    S::Signature: Send + 'static,
+{
+    use tokio::sync::Semaphore;
+    use tokio::task::JoinSet;
+
+    let permits = Arc::new(Semaphore::new(max_concurrency.max(1)));
+    let mut results = vec![false; items.len()];
+    let mut tasks = JoinSet::new();
+    for (index, (epoch, message, signature)) in items.into_iter().enumerate() {
+        // The semaphore is never closed, so acquiring only fails on a bug.
+        let permit = permits
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("batch semaphore closed");
+        let pk = Arc::clone(&pk);
+        tasks.spawn_blocking(move || {
+            let _permit = permit;
+            (index, S::verify(&pk, epoch, &message, &signature))
+        });
+    }
+    while let Some(joined) = tasks.join_next().await {
+        match joined {
+            Ok((index, valid)) => results[index] = valid,
+            Err(e) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+            // Tasks are only cancelled when the set is dropped, not here.
+            Err(_) => {}
+        }
+    }
+    results
+}
@@ -0,0 +1,427 @@
+//! Signing a manifest of messages with one loaded secret key
+//!
+//! `sign` loads the secret key again for every signature. A batch manifest
+//! lists the `(epoch, message)` pairs to sign instead, one per line:
+//!
+//! ```text
+//! # epoch message
+//! 0 block 1 attestation
+//! 7 0x00112233445566778899aabbccddeeff00112233445566778899aabbccddeeff
+//! ```
+//!
+//! The message is the rest of the line, zero-padded or truncated to 32 bytes
+//! as `sign` does with its argument, or exactly 32 bytes written as `0x` and
+//! 64 hex digits. `sign_batch` signs the pairs in epoch order through a
+//! `SigningSession`, so the prepared window only moves forward and no epoch
+//! is signed twice, and writes
+//!
+//! ```text
+//! <out>/sig_<epoch>.<ext>
+//! <out>/index.json
+//! ```
+//!
+//! The index records the lifetime, format and encoding and, per signature,
+//! the epoch, the message as hex, the file name, its length and the
+//! signature's SSZ hash tree root.
+//!
+//! `verify_batch_dir` goes the other way: it reads and decodes the
+//! signatures an index (or a manifest, through `manifest_files`) points at
+//! and verifies them with `batch::verify_batch_with`, both in parallel. It
+//! reports every file, so a missing or undecodable signature fails its own
+//! item rather than the whole batch.
+//!
+//! `generate_manifest` writes manifests for interop fuzzing. Each one starts
+//! with the `BOUNDARY_MESSAGES` (all-zero and all-0xff bytes, text of exactly
+//! 32 bytes, longer text that gets truncated, a multi-byte character cut at
+//! byte 32) and fills the remaining lines per `MessageStrategy`.
+
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+use rand::Rng;
+use rayon::prelude::*;
+use serde_json::{json, Value};
+
+use crate::batch::{self, BatchItem, Parallelism};
+use crate::cancel;
+use crate::compat::text_encoding::{self, Encoding};
+use crate::compat::{codec, hsz, ssz_root};
+use crate::config::Format;
+use crate::keystore::signature_extension;
+use crate::lifetime::SchemeMetadata;
+use crate::signer::SigningSession;
+
+/// Name of the index written next to the signatures.
+pub const INDEX_NAME: &str = "index.json";
+
+/// One line of a batch manifest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchEntry {
+    pub epoch: u32,
+    pub message: [u8; 32],
+}
+
+/// A message as written in a manifest: `0x` and 64 hex digits for raw bytes,
+/// anything else as text.
+pub fn parse_message(raw: &str) -> Result<[u8; 32], Box<dyn Error>> {
+    if let Some(digits) = raw.strip_prefix("0x") {
+        let bytes = hex::decode(digits).map_err(|e| format!("message {raw}: {e}"))?;
+        return bytes.try_into().map_err(|bytes: Vec<u8>| {
+            format!("message {raw} is {} bytes, expected 32", bytes.len()).into()
+        });
+    }
+    let mut message = [0u8; 32];
+    let len = raw.len().min(32);
+    message[..len].copy_from_slice(&raw.as_bytes()[..len]);
+    Ok(message)
+}
+
+/// Entries of a manifest, sorted by epoch. Blank lines and lines starting
+/// with `#` are skipped; an epoch listed twice is an error.
+pub fn parse_manifest(text: &str) -> Result<Vec<BatchEntry>, Box<dyn Error>> {
+    let mut entries: Vec<(usize, BatchEntry)> = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (epoch, message) = line
+            .split_once(char::is_whitespace)
+            .ok_or_else(|| format!("line {}: expected `<epoch> <message>`", i + 1))?;
+        let epoch = epoch
+            .parse()
+            .map_err(|e| format!("line {}: epoch '{epoch}': {e}", i + 1))?;
+        let message = parse_message(message.trim()).map_err(|e| format!("line {}: {e}", i + 1))?;
+        entries.push((i + 1, BatchEntry { epoch, message }));
+    }
+    if entries.is_empty() {
+        return Err("batch manifest holds no messages".into());
+    }
+    entries.sort_by_key(|(_, entry)| entry.epoch);
+    for pair in entries.windows(2) {
+        let ((first, a), (second, b)) = (&pair[0], &pair[1]);
+        if a.epoch == b.epoch {
+            return Err(format!(
+                "epoch {} is listed on lines {first} and {second}; an epoch can be signed only once",
+                a.epoch
+            )
+            .into());
+        }
+    }
+    Ok(entries.into_iter().map(|(_, entry)| entry).collect())
+}
+
+/// Manifest messages covering the encoding corner cases, as written in a
+/// manifest.
+pub const BOUNDARY_MESSAGES: [&str; 8] = [
+    "0x0000000000000000000000000000000000000000000000000000000000000000",
+    "0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+    "0x0000000000000000000000000000000000000000000000000000000000000001",
+    "0x8000000000000000000000000000000000000000000000000000000000000000",
+    // Exactly 32 bytes of text, then 33 and 64 that are truncated to 32
+    "boundary message of 32 bytes..!!",
+    "boundary message of 33 bytes...!!",
+    "boundary message of 64 bytes, the last 32 of which are dropped..",
+    // A two-byte character straddling byte 32
+    "boundary message cut mid-char: é",
+];
+
+/// How `generate_manifest` fills the lines after `BOUNDARY_MESSAGES`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageStrategy {
+    /// 32 random bytes as `0x` hex
+    Random,
+    /// Random printable ASCII text of 1 to 64 bytes
+    Ascii,
+    /// `BOUNDARY_MESSAGES` again, in order
+    Boundary,
+}
+
+impl MessageStrategy {
+    pub fn parse(raw: &str) -> Result<Self, String> {
+        match raw {
+            "random" => Ok(Self::Random),
+            "ascii" => Ok(Self::Ascii),
+            "boundary" => Ok(Self::Boundary),
+            other => Err(format!(
+                "unknown message strategy '{other}'. Must be one of: random, ascii, boundary"
+            )),
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Random => "random",
+            Self::Ascii => "ascii",
+            Self::Boundary => "boundary",
+        }
+    }
+
+    fn message<R: Rng>(self, index: usize, rng: &mut R) -> String {
+        match self {
+            Self::Random => format!("0x{}", hex::encode(rng.random::<[u8; 32]>())),
+            Self::Ascii => {
+                let len = rng.random_range(1..=64);
+                // A letter first and last, so the line is neither trimmed nor read as hex
+                (0..len)
+                    .map(|i| {
+                        if i == 0 || i == len - 1 {
+                            char::from(rng.random_range(b'a'..=b'z'))
+                        } else {
+                            char::from(rng.random_range(b' '..=b'~'))
+                        }
+                    })
+                    .collect()
+            }
+            Self::Boundary => BOUNDARY_MESSAGES[index % BOUNDARY_MESSAGES.len()].to_string(),
+        }
+    }
+}
+
+/// A manifest of `count` messages at epochs `first_epoch..first_epoch + count`:
+/// `BOUNDARY_MESSAGES` first, then messages from `strategy`.
+pub fn generate_manifest<R: Rng>(
+    count: u32,
+    first_epoch: u32,
+    strategy: MessageStrategy,
+    rng: &mut R,
+) -> Result<String, Box<dyn Error>> {
+    if count == 0 {
+        return Err("a manifest needs at least one message".into());
+    }
+    first_epoch
+        .checked_add(count - 1)
+        .ok_or("epochs of the generated manifest overflow u32")?;
+    let mut text = format!("# epoch message ({} strategy)\n", strategy.as_str());
+    for i in 0..count as usize {
+        let message = match BOUNDARY_MESSAGES.get(i) {
+            Some(message) => message.to_string(),
+            None => strategy.message(i, rng),
+        };
+        text.push_str(&format!("{} {message}\n", first_epoch + i as u32));
+    }
+    Ok(text)
+}
+
+/// Sign every entry with `secret_key` and write the signatures and
+/// `INDEX_NAME` to `out`, returning the index. Signatures are `.hsz`
+/// containers when `compress` is set. When [`cancel`]led, the index lists
+/// the signatures written so far.
+pub fn sign_batch<S: SchemeMetadata>(
+    secret_key: S::SecretKey,
+    entries: &[BatchEntry],
+    out: &Path,
+    format: Format,
+    encoding: Encoding,
+    compress: bool,
+) -> Result<Value, Box<dyn Error>> {
+    fs::create_dir_all(out)?;
+    let mut session = SigningSession::<S>::new(secret_key);
+    let mut signatures = Vec::with_capacity(entries.len());
+    let mut scratch = codec::Scratch::default();
+    let _scope = cancel::Scope::enter();
+    for entry in entries {
+        if cancel::is_cancelled() {
+            break;
+        }
+        let signature = session.sign(entry.epoch, &entry.message)?;
+        let bytes = codec::encode_signature_with::<S>(&signature, format, &mut scratch)?;
+        let mut path = out.join(format!(
+            "sig_{}.{}",
+            entry.epoch,
+            signature_extension(format)
+        ));
+        if compress {
+            path = hsz::compressed_path(path);
+        }
+        hsz::write(&path, &text_encoding::encode(bytes, encoding))?;
+        let root = ssz_root::signature_hash_tree_root::<S>(&signature)?;
+        signatures.push(json!({
+            "epoch": entry.epoch,
+            "message": hex::encode(entry.message),
+            "file": path.file_name().map(|name| name.to_string_lossy()),
+            "bytes": bytes.len(),
+            "signature_root": format!("0x{}", hex::encode(root)),
+        }));
+    }
+    let index = json!({
+        "lifetime": S::TAG.as_str(),
+        "format": format.to_string(),
+        "encoding": encoding.as_str(),
+        "signatures": signatures,
+    });
+    fs::write(out.join(INDEX_NAME), serde_json::to_string_pretty(&index)?)?;
+    Ok(index)
+}
+
+/// A signature file in a batch directory and the entry it was signed for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchFile {
+    pub entry: BatchEntry,
+    /// Relative to the batch directory.
+    pub file: String,
+}
+
+/// Signatures listed in an index written by `sign_batch`.
+pub fn index_files(index: &Value) -> Result<Vec<BatchFile>, Box<dyn Error>> {
+    let signatures = index
+        .get("signatures")
+        .and_then(Value::as_array)
+        .ok_or("batch index has no signatures array")?;
+    signatures
+        .iter()
+        .enumerate()
+        .map(|(i, record)| {
+            let field = |key: &str| {
+                record
+                    .get(key)
+                    .ok_or_else(|| format!("batch index /signatures/{i}: missing {key}"))
+            };
+            let epoch = field("epoch")?
+                .as_u64()
+                .and_then(|epoch| u32::try_from(epoch).ok())
+                .ok_or_else(|| format!("batch index /signatures/{i}: epoch is not a u32"))?;
+            let message = field("message")?
+                .as_str()
+                .ok_or_else(|| format!("batch index /signatures/{i}: message is not a string"))?;
+            let message = parse_message(&format!("0x{message}"))
+                .map_err(|e| format!("batch index /signatures/{i}: {e}"))?;
+            let file = field("file")?
+                .as_str()
+                .ok_or_else(|| format!("batch index /signatures/{i}: file is not a string"))?;
+            Ok(BatchFile {
+                entry: BatchEntry { epoch, message },
+                file: file.to_owned(),
+            })
+        })
+        .collect()
+}
+
+/// The files `sign_batch` writes for `entries` in `format`.
+pub fn manifest_files(entries: &[BatchEntry], format: Format) -> Vec<BatchFile> {
+    entries
+        .iter()
+        .map(|entry| BatchFile {
+            entry: entry.clone(),
+            file: format!("sig_{}.{}", entry.epoch, signature_extension(format)),
+        })
+        .collect()
+}
+
+/// What became of one file in `verify_batch_dir`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Valid,
+    Invalid,
+    /// The file is missing or does not decode.
+    Unreadable(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct BatchReport {
+    pub items: Vec<(BatchFile, Verdict)>,
+}
+
+impl BatchReport {
+    pub fn passed(&self) -> usize {
+        self.items
+            .iter()
+            .filter(|(_, verdict)| *verdict == Verdict::Valid)
+            .count()
+    }
+
+    pub fn failed(&self) -> usize {
+        self.items.len() - self.passed()
+    }
+
+    /// `{"total", "passed", "failed", "items": [{"epoch", "file", "valid", "error"?}]}`
+    pub fn to_json(&self) -> Value {
+        let items: Vec<Value> = self
+            .items
+            .iter()
+            .map(|(file, verdict)| {
+                let mut item = json!({
+                    "epoch": file.entry.epoch,
+                    "file": file.file,
+                    "valid": *verdict == Verdict::Valid,
+                });
+                if let Verdict::Unreadable(error) = verdict {
+                    item["error"] = error.as_str().into();
+                }
+                item
+            })
+            .collect();
+        json!({
+            "total": self.items.len(),
+            "passed": self.passed(),
+            "failed": self.failed(),
+            "items": items,
+        })
+    }
+}
+
+fn read_signature<S: SchemeMetadata>(
+    path: &Path,
+    format: Format,
+) -> Result<S::Signature, Box<dyn Error>> {
+    // `--compress` batches hold `sig_<epoch>.<ext>.hsz`
+    let compressed = hsz::compressed_path(path);
+    let path = if !path.exists() && compressed.exists() {
+        compressed.as_path()
+    } else {
+        path
+    };
+    let bytes = text_encoding::decode(&hsz::read(path)?)?;
+    codec::decode_signature::<S>(&bytes, format)
+}
+
+/// Verify every file under `dir` against `pk` and its entry. Files are read
+/// and decoded, then verified, on `parallelism` threads; the report keeps
+/// the order of `files`.
+pub fn verify_batch_dir<S>(
+    pk: &S::PublicKey,
+    dir: &Path,
+    files: &[BatchFile],
+    format: Format,
+    parallelism: Parallelism,
+) -> Result<BatchReport, Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::PublicKey: Sync,
+    S::Signature: Send + Sync,
+{
+    let decoded: Vec<Result<S::Signature, String>> = parallelism.install(|| {
+        files
+            .par_iter()
+            .map(|file| {
+                read_signature::<S>(&dir.join(&file.file), format).map_err(|e| e.to_string())
+            })
+            .collect()
+    })?;
+    let mut verdicts = Vec::with_capacity(files.len());
+    let mut items: Vec<BatchItem<S>> = Vec::new();
+    let mut readable = Vec::new();
+    for (i, (file, signature)) in files.iter().zip(decoded).enumerate() {
+        match signature {
+            Ok(signature) => {
+                items.push((file.entry.epoch, file.entry.message, signature));
+                readable.push(i);
+                verdicts.push(Verdict::Invalid);
+            }
+            Err(e) => verdicts.push(Verdict::Unreadable(e)),
+        }
+    }
+    for (i, valid) in
+        readable
+            .into_iter()
+            .zip(batch::verify_batch_with::<S>(pk, &items, parallelism)?)
+    {
+        if valid {
+            verdicts[i] = Verdict::Valid;
+        }
+    }
+    Ok(BatchReport {
+        items: files.iter().cloned().zip(verdicts).collect(),
+    })
+}
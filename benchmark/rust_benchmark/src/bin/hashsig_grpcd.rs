@@ -0,0 +1,33 @@
+//! gRPC server for the `hashsig.v1.HashSig` service
+//!
+//! Usage: `hashsig-grpcd [--listen 127.0.0.1:50051]`. See `proto/hashsig.proto`
+//! for the messages and `rust_benchmark::grpc` for the semantics.
+
+use std::error::Error;
+use std::net::SocketAddr;
+
+use clap::Parser;
+use tonic::transport::Server;
+
+use rust_benchmark::grpc::{HashSigServer, HashSigService};
+
+const DEFAULT_LISTEN: &str = "127.0.0.1:50051";
+
+/// gRPC server for the hashsig.v1.HashSig service
+#[derive(Parser)]
+#[command(name = "hashsig-grpcd", version)]
+struct Options {
+    #[arg(long, default_value = DEFAULT_LISTEN)]
+    listen: SocketAddr,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    let addr = Options::parse().listen;
+    eprintln!("✅ Serving hashsig.v1.HashSig on {addr}");
+    Server::builder()
+        .add_service(HashSigServer::new(HashSigService))
+        .serve(addr)
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,234 @@
+//! Remote signer daemon
+//!
+//! Loads a secret key once and serves signatures over HTTP, in the spirit of
+//! web3signer, so validators do not pay a key load (or key_gen) per message.
+//!
+//! Endpoints:
+//! - `GET  /upcheck`     200 `OK` while the daemon is serving
+//! - `GET  /public_key`  the public key in the configured format (hex)
+//! - `POST /sign`        body `{"epoch": 5, "message": "0x…"}` (at most 32
+//!   bytes of hex, zero-padded), returns `{"epoch": 5, "signature": "0x…"}`
+//!
+//! Epochs must strictly increase across `/sign` calls; a repeated or older
+//! epoch is answered with 412 so a one-time key is never used twice. The
+//! last signed epoch is written to the watermark file (`<sk>.watermark` by
+//! default) and synced before the signature is returned, and read back at
+//! startup, so a restart does not reset it.
+
+use std::error::Error;
+use std::sync::mpsc::{self, TrySendError};
+use std::thread;
+
+use clap::Parser;
+use serde_json::{json, Value};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use rust_benchmark::cli;
+use rust_benchmark::compat::{codec, hsz, key_envelope};
+use rust_benchmark::config::Format;
+use rust_benchmark::lifetime::{LifetimeTag, SchemeMetadata};
+use rust_benchmark::signer::{SigningSession, Watermark};
+use rust_benchmark::upstream::{
+    SIGTopLevelTargetSumLifetime18Dim64Base8, SIGTopLevelTargetSumLifetime32Dim64Base8,
+    SIGTopLevelTargetSumLifetime8Dim64Base8,
+};
+
+const DEFAULT_LISTEN: &str = "127.0.0.1:9000";
+
+/// Remote signer daemon serving signatures over HTTP
+#[derive(Parser)]
+#[command(
+    name = "hashsig-signerd",
+    version,
+    after_help = "Key files ending in .ssz are read as SSZ, anything else as JSON, unless the key's envelope names its format."
+)]
+struct Options {
+    /// Secret key file
+    #[arg(long = "sk")]
+    sk_path: String,
+    /// Public key file served by /public_key [default: none]
+    #[arg(long = "pk")]
+    pk_path: Option<String>,
+    /// 2^8, 2^18 or 2^32
+    #[arg(long, value_parser = cli::lifetime, default_value = "2^8")]
+    lifetime: LifetimeTag,
+    #[arg(long, default_value = DEFAULT_LISTEN)]
+    listen: String,
+    /// Signature encoding returned by /sign
+    #[arg(long, value_parser = cli::format, default_value = "ssz")]
+    format: Format,
+    /// File keeping the last signed epoch [default: <sk>.watermark]
+    #[arg(long)]
+    watermark: Option<String>,
+    /// Sign requests that may wait for the signer; more are answered with 503
+    #[arg(long, default_value_t = 64)]
+    max_pending: usize,
+}
+
+fn json_response(status: u16, body: Value) -> Response<std::io::Cursor<Vec<u8>>> {
+    let header = Header::from_bytes("Content-Type", "application/json").expect("static header");
+    Response::from_data(body.to_string().into_bytes())
+        .with_status_code(status)
+        .with_header(header)
+}
+
+fn error_response(status: u16, message: impl ToString) -> Response<std::io::Cursor<Vec<u8>>> {
+    json_response(status, json!({ "error": message.to_string() }))
+}
+
+fn parse_sign_request(request: &mut Request) -> Result<(u32, [u8; 32]), Box<dyn Error>> {
+    let mut body = String::new();
+    request.as_reader().read_to_string(&mut body)?;
+    let value: Value = serde_json::from_str(&body)?;
+    let epoch = value
+        .get("epoch")
+        .and_then(Value::as_u64)
+        .ok_or("missing or invalid 'epoch'")?;
+    let epoch = u32::try_from(epoch).map_err(|_| "epoch exceeds u32")?;
+    let message_hex = value
+        .get("message")
+        .and_then(Value::as_str)
+        .ok_or("missing or invalid 'message'")?;
+    let message = hex::decode(message_hex.trim_start_matches("0x"))?;
+    Ok((epoch, codec::message_from_bytes(&message)?))
+}
+
+fn serve<S>(options: &Options) -> Result<(), Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::SecretKey: Send,
+{
+    let sk_format = Format::from_key_path(&options.sk_path);
+    if let Some(envelope) = key_envelope::read_header(&options.sk_path)? {
+        envelope.check_lifetime(S::TAG)?;
+    }
+    let secret_key = codec::read_secret_key_file::<S, _>(&options.sk_path, sk_format)?;
+    let public_key_hex = match &options.pk_path {
+        Some(path) => {
+            let pk = codec::decode_public_key::<S>(&hsz::read(path)?, Format::from_key_path(path))?;
+            let pk_format = match options.format {
+                Format::ZigBinary => Format::Json,
+                format => format,
+            };
+            Some(format!(
+                "0x{}",
+                hex::encode(codec::encode_public_key::<S>(&pk, pk_format)?)
+            ))
+        }
+        None => None,
+    };
+    let watermark = match &options.watermark {
+        Some(path) => Watermark::new(path),
+        None => Watermark::next_to(&options.sk_path),
+    };
+    let watermark_path = watermark.path().display().to_string();
+    let mut session = SigningSession::<S>::with_watermark(secret_key, watermark)?;
+    let activation = session.activation_interval();
+    if let Some(last) = session.last_signed() {
+        if u64::from(last) + 1 >= activation.end {
+            return Err(format!(
+                "{watermark_path} records epoch {last}, which leaves no epoch of {activation:?} to sign"
+            )
+            .into());
+        }
+        eprintln!("Resuming after epoch {last} (from {watermark_path})");
+    }
+
+    let server = Server::http(&options.listen)
+        .map_err(|e| format!("failed to bind {}: {e}", options.listen))?;
+    eprintln!(
+        "✅ Serving lifetime {} key ({} epochs {:?}) on http://{}",
+        S::TAG,
+        activation.end - activation.start,
+        activation,
+        options.listen
+    );
+
+    // Signing and the watermark write happen on one signer thread, so
+    // /upcheck and /public_key are answered while a signature is in progress
+    let (queue, pending) = mpsc::sync_channel::<Request>(options.max_pending);
+    let format = options.format;
+    thread::scope(|scope| {
+        scope.spawn(move || {
+            // Requests are signed one at a time, so one set of buffers does
+            let mut scratch = codec::Scratch::default();
+            for mut request in pending {
+                let response = sign_response(&mut session, &mut request, format, &mut scratch);
+                respond(request, response);
+            }
+        });
+        for request in server.incoming_requests() {
+            let response = match (request.method(), request.url()) {
+                (Method::Get, "/upcheck") => Response::from_string("OK").with_status_code(200),
+                (Method::Get, "/public_key") => match &public_key_hex {
+                    Some(pk) => json_response(200, json!({ "public_key": pk })),
+                    None => error_response(404, "daemon was started without --pk"),
+                },
+                (Method::Post, "/sign") => match queue.try_send(request) {
+                    Ok(()) => continue,
+                    Err(TrySendError::Full(request)) => {
+                        respond(
+                            request,
+                            error_response(
+                                503,
+                                format!("{} sign requests already pending", options.max_pending),
+                            ),
+                        );
+                        continue;
+                    }
+                    Err(TrySendError::Disconnected(_)) => break,
+                },
+                _ => error_response(404, "not found"),
+            };
+            respond(request, response);
+        }
+        drop(queue);
+    });
+    Ok(())
+}
+
+fn sign_response<S: SchemeMetadata>(
+    session: &mut SigningSession<S>,
+    request: &mut Request,
+    format: Format,
+    scratch: &mut codec::Scratch,
+) -> Response<std::io::Cursor<Vec<u8>>> {
+    let (epoch, message) = match parse_sign_request(request) {
+        Ok(parsed) => parsed,
+        Err(e) => return error_response(400, e),
+    };
+    if session.is_reused(epoch) {
+        return error_response(
+            412,
+            format!(
+                "epoch {epoch} not after last signed epoch {:?}",
+                session.last_signed()
+            ),
+        );
+    }
+    match session
+        .sign(epoch, &message)
+        .and_then(|sig| codec::encode_signature_with::<S>(&sig, format, scratch).map(hex::encode))
+    {
+        Ok(signature) => json_response(
+            200,
+            json!({ "epoch": epoch, "signature": format!("0x{signature}") }),
+        ),
+        Err(e) => error_response(500, e),
+    }
+}
+
+fn respond(request: Request, response: Response<std::io::Cursor<Vec<u8>>>) {
+    if let Err(e) = request.respond(response) {
+        eprintln!("⚠️  failed to send response: {e}");
+    }
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let options = Options::parse();
+    match options.lifetime {
+        LifetimeTag::Pow8 => serve::<SIGTopLevelTargetSumLifetime8Dim64Base8>(&options),
+        LifetimeTag::Pow18 => serve::<SIGTopLevelTargetSumLifetime18Dim64Base8>(&options),
+        LifetimeTag::Pow32 => serve::<SIGTopLevelTargetSumLifetime32Dim64Base8>(&options),
+    }
+}
@@ -0,0 +1,299 @@
+//! Unix-socket verification daemon
+//!
+//! Batch interop tests verify thousands of signatures; spawning a CLI per
+//! signature pays process start-up plus JSON parsing and public-key
+//! deserialization every time. This daemon decodes each public key once,
+//! caches it by fingerprint (`rust_benchmark::pk_cache`), and answers one
+//! JSON request per line on each connection, streaming one JSON response per
+//! line back in order.
+//!
+//! Requests:
+//! - `{"id": 1, "op": "load", "public_key_path": "tmp/zig_pk.json"}`
+//!   → `{"id": 1, "fingerprint": "0x…"}`
+//! - `{"id": 2, "op": "verify", "fingerprint": "0x…", "epoch": 0,
+//!   "message": "hello", "signature_path": "tmp/zig_sig.bin"}`
+//!   → `{"id": 2, "valid": true}`
+//!
+//! `verify` also accepts `public_key_path` in place of `fingerprint` (the key
+//! is loaded and cached on first use), `signature` as hex in place of
+//! `signature_path`, and `message_hex` in place of the zero-padded UTF-8
+//! `message`. `signature_format` (`json`, `ssz`, `bin`) defaults to `ssz` for
+//! `.ssz` paths and the Zig binary layout otherwise. `fingerprint` may also be
+//! given as a multibase sha3-256 multihash (`compat::multihash`). Failures are
+//! reported as `{"id": …, "error": "…"}` without closing the connection.
+//!
+//! Connections are tokio tasks. Key and signature files are read through
+//! `compat::async_codec`, and decoding and verification run on the blocking
+//! pool, so a slow disk or a long verify never holds up the I/O workers.
+//! Each blocking thread keeps a `codec::Scratch`, so converting a binary
+//! signature to SSZ for decoding allocates nothing once the thread has seen
+//! one. At
+//! most `--max-concurrency` requests are handled at once across all
+//! connections; the rest wait for a slot.
+
+#[cfg(unix)]
+mod daemon {
+    use std::cell::RefCell;
+    use std::error::Error;
+    use std::fmt::Display;
+    use std::path::{Path, PathBuf};
+    use std::sync::Arc;
+
+    use clap::Parser;
+    use serde_json::{json, Value};
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::{UnixListener, UnixStream};
+    use tokio::sync::Semaphore;
+
+    use rust_benchmark::cli;
+    use rust_benchmark::compat::{async_codec, codec, ssz_codec};
+    use rust_benchmark::config::Format;
+    use rust_benchmark::lifetime::{LifetimeTag, SchemeMetadata};
+    use rust_benchmark::pk_cache::{CachedKey, Fingerprint, PublicKeyCache};
+    use rust_benchmark::upstream::{
+        SIGTopLevelTargetSumLifetime18Dim64Base8, SIGTopLevelTargetSumLifetime32Dim64Base8,
+        SIGTopLevelTargetSumLifetime8Dim64Base8,
+    };
+
+    const DEFAULT_SOCKET: &str = "tmp/hashsig-verifyd.sock";
+
+    thread_local! {
+        /// Decoding buffers of this blocking thread, reused across requests.
+        static SCRATCH: RefCell<codec::Scratch> = RefCell::default();
+    }
+
+    /// Unix-socket verification daemon with a public key cache
+    #[derive(Parser)]
+    #[command(name = "hashsig-verifyd", version)]
+    struct Options {
+        #[arg(long, default_value = DEFAULT_SOCKET)]
+        socket: PathBuf,
+        /// 2^8, 2^18 or 2^32
+        #[arg(long, value_parser = cli::lifetime, default_value = "2^8")]
+        lifetime: LifetimeTag,
+        /// Requests handled at once across all connections
+        #[arg(long, default_value_t = 64)]
+        max_concurrency: usize,
+    }
+
+    /// Errors cross await points as text; `Box<dyn Error>` is not `Send`.
+    fn text(e: impl Display) -> String {
+        e.to_string()
+    }
+
+    fn str_field<'a>(request: &'a Value, name: &str) -> Option<&'a str> {
+        request.get(name).and_then(Value::as_str)
+    }
+
+    fn message_of(request: &Value) -> Result<[u8; 32], Box<dyn Error>> {
+        if let Some(raw) = str_field(request, "message_hex") {
+            return codec::message_from_bytes(&hex::decode(raw.trim_start_matches("0x"))?);
+        }
+        let message = str_field(request, "message").ok_or("missing 'message'")?;
+        let mut bytes = [0u8; 32];
+        let len = message.len().min(32);
+        bytes[..len].copy_from_slice(&message.as_bytes()[..len]);
+        Ok(bytes)
+    }
+
+    /// The encoded signature of a request and its format, read from
+    /// `signature_path` or taken from `signature`.
+    async fn signature_of(request: &Value) -> Result<(Vec<u8>, Format), String> {
+        let explicit = str_field(request, "signature_format")
+            .map(Format::parse)
+            .transpose()
+            .map_err(text)?;
+        match (
+            str_field(request, "signature_path"),
+            str_field(request, "signature"),
+        ) {
+            (Some(path), _) => {
+                let default = if ssz_codec::is_ssz_path(path) {
+                    Format::Ssz
+                } else {
+                    Format::ZigBinary
+                };
+                let bytes = async_codec::read_file(Path::new(path))
+                    .await
+                    .map_err(text)?;
+                Ok((bytes, explicit.unwrap_or(default)))
+            }
+            (None, Some(raw)) => Ok((
+                hex::decode(raw.trim_start_matches("0x")).map_err(text)?,
+                explicit.unwrap_or(Format::ZigBinary),
+            )),
+            (None, None) => Err("missing 'signature_path' or 'signature'".into()),
+        }
+    }
+
+    async fn load_public_key<S>(
+        path: &str,
+        cache: &Arc<PublicKeyCache<S>>,
+    ) -> Result<CachedKey<S>, String>
+    where
+        S: SchemeMetadata + 'static,
+        S::PublicKey: Send + Sync,
+    {
+        let bytes = async_codec::read_file(Path::new(path))
+            .await
+            .map_err(text)?;
+        // A 2^32 key decodes long enough to stall the other connections
+        let (cache, path) = (Arc::clone(cache), path.to_owned());
+        tokio::task::spawn_blocking(move || cache.load_file_bytes(&path, &bytes).map_err(text))
+            .await
+            .map_err(|e| format!("worker failed: {e}"))?
+    }
+
+    async fn handle<S>(request: &Value, cache: &Arc<PublicKeyCache<S>>) -> Result<Value, String>
+    where
+        S: SchemeMetadata + 'static,
+        S::PublicKey: Send + Sync,
+    {
+        match str_field(request, "op").ok_or("missing 'op'")? {
+            "load" => {
+                let path =
+                    str_field(request, "public_key_path").ok_or("missing 'public_key_path'")?;
+                let (fingerprint, _) = load_public_key(path, cache).await?;
+                Ok(json!({ "fingerprint": fingerprint.to_string() }))
+            }
+            "verify" => {
+                let pk = match (
+                    str_field(request, "fingerprint"),
+                    str_field(request, "public_key_path"),
+                ) {
+                    (Some(raw), _) => {
+                        let fingerprint = Fingerprint::parse(raw).map_err(text)?;
+                        cache.get(&fingerprint).ok_or_else(|| {
+                            format!("unknown fingerprint {fingerprint}; send 'load' first")
+                        })?
+                    }
+                    (None, Some(path)) => load_public_key(path, cache).await?.1,
+                    (None, None) => return Err("missing 'fingerprint' or 'public_key_path'".into()),
+                };
+                let epoch = request
+                    .get("epoch")
+                    .and_then(Value::as_u64)
+                    .ok_or("missing or invalid 'epoch'")?;
+                let epoch = u32::try_from(epoch).map_err(|_| "epoch exceeds u32")?;
+                let message = message_of(request).map_err(text)?;
+                let (bytes, format) = signature_of(request).await?;
+                let valid = tokio::task::spawn_blocking(move || {
+                    SCRATCH
+                        .with_borrow_mut(|scratch| {
+                            codec::decode_signature_with::<S>(&bytes, format, scratch)
+                        })
+                        .map(|signature| S::verify(&pk, epoch, &message, &signature))
+                        .map_err(text)
+                })
+                .await
+                .map_err(|e| format!("worker failed: {e}"))??;
+                Ok(json!({ "valid": valid }))
+            }
+            other => Err(format!("unknown op '{other}'")),
+        }
+    }
+
+    async fn serve_connection<S>(
+        stream: UnixStream,
+        cache: Arc<PublicKeyCache<S>>,
+        limit: Arc<Semaphore>,
+    ) -> std::io::Result<()>
+    where
+        S: SchemeMetadata + 'static,
+        S::PublicKey: Send + Sync,
+    {
+        let (reader, mut writer) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        while let Some(line) = lines.next_line().await? {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let response = match serde_json::from_str::<Value>(&line) {
+                Ok(request) => {
+                    let id = request.get("id").cloned().unwrap_or(Value::Null);
+                    let result = {
+                        let _permit = limit.acquire().await.expect("the limit is never closed");
+                        handle(&request, &cache).await
+                    };
+                    match result {
+                        Ok(mut result) => {
+                            result["id"] = id;
+                            result
+                        }
+                        Err(e) => json!({ "id": id, "error": e }),
+                    }
+                }
+                Err(e) => json!({ "id": null, "error": format!("invalid request: {e}") }),
+            };
+            writer.write_all(format!("{response}\n").as_bytes()).await?;
+        }
+        Ok(())
+    }
+
+    async fn serve<S>(socket: &Path, max_concurrency: usize) -> Result<(), Box<dyn Error>>
+    where
+        S: SchemeMetadata + 'static,
+        S::PublicKey: Send + Sync,
+    {
+        if socket.exists() {
+            tokio::fs::remove_file(socket).await?;
+        }
+        if let Some(parent) = socket.parent().filter(|p| !p.as_os_str().is_empty()) {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let listener = UnixListener::bind(socket)?;
+        let cache = Arc::new(PublicKeyCache::<S>::new());
+        let limit = Arc::new(Semaphore::new(max_concurrency.max(1)));
+        eprintln!(
+            "✅ Verifying lifetime {} signatures on {} ({} requests at once)",
+            S::TAG,
+            socket.display(),
+            max_concurrency.max(1)
+        );
+        loop {
+            let stream = match listener.accept().await {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    eprintln!("⚠️  failed to accept connection: {e}");
+                    continue;
+                }
+            };
+            let cache = Arc::clone(&cache);
+            let limit = Arc::clone(&limit);
+            tokio::spawn(async move {
+                if let Err(e) = serve_connection(stream, cache, limit).await {
+                    eprintln!("⚠️  connection closed: {e}");
+                }
+            });
+        }
+    }
+
+    pub fn main() -> Result<(), Box<dyn Error>> {
+        let options = Options::parse();
+        let socket = options.socket.as_path();
+        let limit = options.max_concurrency;
+        let runtime = tokio::runtime::Runtime::new()?;
+        match options.lifetime {
+            LifetimeTag::Pow8 => runtime.block_on(
+                serve::<SIGTopLevelTargetSumLifetime8Dim64Base8>(socket, limit),
+            ),
+            LifetimeTag::Pow18 => runtime.block_on(
+                serve::<SIGTopLevelTargetSumLifetime18Dim64Base8>(socket, limit),
+            ),
+            LifetimeTag::Pow32 => runtime.block_on(
+                serve::<SIGTopLevelTargetSumLifetime32Dim64Base8>(socket, limit),
+            ),
+        }
+    }
+}
+
+#[cfg(unix)]
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    daemon::main()
+}
+
+#[cfg(not(unix))]
+fn main() {
+    eprintln!("hashsig-verifyd needs Unix domain sockets");
+    std::process::exit(1);
+}
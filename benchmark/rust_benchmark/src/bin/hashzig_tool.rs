@@ -0,0 +1,8 @@
+//! Rust tool for cross-language compatibility testing
+//!
+//! `keygen`, `sign`, `verify` and `convert` on a stored key pair, plus the
+//! `bench` and `debug` groups. See `rust_benchmark::tool` for the commands.
+
+fn main() {
+    rust_benchmark::tool::main();
+}
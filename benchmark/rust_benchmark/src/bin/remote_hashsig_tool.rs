@@ -0,0 +1,5 @@
+//! Old name for `hashzig-tool debug`, kept for the Zig remote tests
+
+fn main() {
+    rust_benchmark::tool::legacy_main(&["debug"], &[]);
+}
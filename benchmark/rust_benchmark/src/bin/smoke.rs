@@ -0,0 +1,5 @@
+//! Old name for `hashzig-tool debug smoke`
+
+fn main() {
+    rust_benchmark::tool::legacy_main(&["debug", "smoke"], &[]);
+}
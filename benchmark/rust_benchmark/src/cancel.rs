@@ -0,0 +1,144 @@
+//! Cooperative cancellation of long operations: ctrl-c and `--timeout`
+//!
+//! A 2^32 keygen or a verify-load sweep can run for hours. Killing it loses
+//! everything, including the rates already measured and the bottom trees
+//! already prepared. Instead, [`install`] turns ctrl-c and a deadline into a
+//! flag that long loops poll at their safe points, between two bottom trees
+//! or two signatures. On cancellation they save what they have and return
+//! [`Cancelled`]:
+//!
+//! ```ignore
+//! let _scope = cancel::Scope::enter();
+//! for entry in entries {
+//!     if cancel::is_cancelled() {
+//!         break; // write the partial index, then return cancel::check()
+//!     }
+//!     ...
+//! }
+//! ```
+//!
+//! Outside a [`Scope`] nothing polls the flag, so ctrl-c or the deadline
+//! ends the process at once, as it did before. A second ctrl-c always does.
+
+use std::error::Error;
+use std::fmt;
+use std::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+const RUNNING: u8 = 0;
+const INTERRUPTED: u8 = 1;
+const TIMED_OUT: u8 = 2;
+
+static STATE: AtomicU8 = AtomicU8::new(RUNNING);
+static SCOPES: AtomicUsize = AtomicUsize::new(0);
+
+/// How often [`interruptible`] looks at the flag.
+const POLL: Duration = Duration::from_millis(100);
+
+/// Why a long operation stopped early.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cancelled {
+    Interrupted,
+    TimedOut,
+}
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Cancelled::Interrupted => f.write_str("interrupted"),
+            Cancelled::TimedOut => f.write_str("timed out"),
+        }
+    }
+}
+
+impl Error for Cancelled {}
+
+/// Whether ctrl-c was pressed or the deadline passed.
+pub fn is_cancelled() -> bool {
+    STATE.load(Ordering::Relaxed) != RUNNING
+}
+
+/// `Err(Cancelled)` once cancelled; for `?` at a safe point.
+pub fn check() -> Result<(), Box<dyn Error>> {
+    match STATE.load(Ordering::Relaxed) {
+        RUNNING => Ok(()),
+        INTERRUPTED => Err(Cancelled::Interrupted.into()),
+        _ => Err(Cancelled::TimedOut.into()),
+    }
+}
+
+/// Marks the code that polls the flag. While one is alive, cancellation sets
+/// the flag instead of exiting.
+pub struct Scope(());
+
+impl Scope {
+    pub fn enter() -> Self {
+        SCOPES.fetch_add(1, Ordering::SeqCst);
+        Scope(())
+    }
+}
+
+impl Drop for Scope {
+    fn drop(&mut self) {
+        SCOPES.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Record `reason`, or exit with `exit_code` if nothing will notice it.
+fn cancel(reason: u8, exit_code: i32) {
+    let first = STATE
+        .compare_exchange(RUNNING, reason, Ordering::SeqCst, Ordering::SeqCst)
+        .is_ok();
+    if !first || SCOPES.load(Ordering::SeqCst) == 0 {
+        let reason = if reason == INTERRUPTED {
+            Cancelled::Interrupted
+        } else {
+            Cancelled::TimedOut
+        };
+        eprintln!("Error: {reason}");
+        std::process::exit(exit_code);
+    }
+    eprintln!("Stopping at the next safe point (ctrl-c again to quit now)...");
+}
+
+/// Handle ctrl-c and, with `timeout`, a deadline from now. Either one exits
+/// with `exit_code` unless a [`Scope`] is alive to stop cleanly.
+pub fn install(timeout: Option<Duration>, exit_code: i32) -> Result<(), Box<dyn Error>> {
+    ctrlc::set_handler(move || cancel(INTERRUPTED, exit_code))?;
+    if let Some(timeout) = timeout {
+        thread::spawn(move || {
+            thread::sleep(timeout);
+            cancel(TIMED_OUT, exit_code);
+        });
+    }
+    Ok(())
+}
+
+/// Run `work` on its own thread and wait for it, or return [`Cancelled`]
+/// as soon as the flag is set. For upstream calls with no safe points of
+/// their own, such as keygen: the abandoned thread ends with the process,
+/// and nothing it would have written is written.
+pub fn interruptible<T, F>(work: F) -> Result<T, Box<dyn Error>>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let _scope = Scope::enter();
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        // The receiver is gone only if we were cancelled
+        let _ = sender.send(work());
+    });
+    loop {
+        check()?;
+        match receiver.recv_timeout(POLL) {
+            Ok(value) => return Ok(value),
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+            Err(mpsc::RecvTimeoutError::Disconnected) => {
+                return Err("worker thread panicked".into())
+            }
+        }
+    }
+}
@@ -0,0 +1,246 @@
+//! C ABI for keygen, sign and verify
+//!
+//! Built into the `cdylib` when the `capi` feature is enabled so the Zig test
+//! harness can link against the Rust implementation directly instead of
+//! spawning `cross_lang_rust_tool` and scraping its output. The declarations
+//! are mirrored in `include/hashzig_rust.h`.
+//!
+//! Keys and signatures cross the boundary SSZ-encoded in caller-owned
+//! buffers. Every function returns `HASHZIG_OK` (or, for verify, 1/0) on
+//! success and one of the negative `HASHZIG_ERR_*` codes otherwise. When an
+//! output buffer is too small the required length is still written to the
+//! `*_len` out-pointer so the caller can retry.
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::slice;
+
+use rand::{rngs::StdRng, SeedableRng};
+use ssz::{Decode, Encode};
+
+use crate::config::SchemeConfig;
+use crate::lifetime::LifetimeTag;
+use crate::signer;
+use crate::upstream::{
+    SIGTopLevelTargetSumLifetime18Dim64Base8, SIGTopLevelTargetSumLifetime32Dim64Base8,
+    SIGTopLevelTargetSumLifetime8Dim64Base8, SignatureScheme,
+};
+
+pub const HASHZIG_OK: i32 = 0;
+pub const HASHZIG_ERR_NULL_POINTER: i32 = -1;
+pub const HASHZIG_ERR_LIFETIME: i32 = -2;
+pub const HASHZIG_ERR_CONFIG: i32 = -3;
+pub const HASHZIG_ERR_BUFFER_TOO_SMALL: i32 = -4;
+pub const HASHZIG_ERR_DECODE: i32 = -5;
+pub const HASHZIG_ERR_SIGN: i32 = -6;
+pub const HASHZIG_ERR_PANIC: i32 = -7;
+pub const HASHZIG_ERR_EPOCH: i32 = -9;
+
+fn lifetime_from_log(log_lifetime: u32) -> Result<LifetimeTag, i32> {
+    LifetimeTag::ALL
+        .into_iter()
+        .find(|tag| tag.log_lifetime() == log_lifetime)
+        .ok_or(HASHZIG_ERR_LIFETIME)
+}
+
+/// Copy `bytes` into the caller's buffer, always reporting the full length.
+unsafe fn write_out(
+    bytes: &[u8],
+    out: *mut u8,
+    cap: usize,
+    out_len: *mut usize,
+) -> Result<(), i32> {
+    *out_len = bytes.len();
+    if bytes.len() > cap {
+        return Err(HASHZIG_ERR_BUFFER_TOO_SMALL);
+    }
+    std::ptr::copy_nonoverlapping(bytes.as_ptr(), out, bytes.len());
+    Ok(())
+}
+
+/// Run `f`, mapping panics to `HASHZIG_ERR_PANIC` so they never unwind into C.
+fn guarded(f: impl FnOnce() -> Result<i32, i32>) -> i32 {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(code)) | Ok(Err(code)) => code,
+        Err(_) => HASHZIG_ERR_PANIC,
+    }
+}
+
+fn keygen_for_scheme<S>(config: &SchemeConfig) -> (Vec<u8>, Vec<u8>)
+where
+    S: SignatureScheme,
+    S::PublicKey: Encode,
+    S::SecretKey: Encode,
+{
+    let seed = config.seed().unwrap_or_default();
+    let mut rng = StdRng::from_seed(seed);
+    let (pk, sk) = S::key_gen(
+        &mut rng,
+        config.activation_epoch(),
+        config.num_active_epochs(),
+    );
+    (pk.as_ssz_bytes(), sk.as_ssz_bytes())
+}
+
+fn sign_for_scheme<S>(sk: &[u8], epoch: u32, message: &[u8; 32]) -> Result<Vec<u8>, i32>
+where
+    S: SignatureScheme,
+    S::SecretKey: Decode,
+    S::Signature: Encode,
+{
+    let mut sk = S::SecretKey::from_ssz_bytes(sk).map_err(|_| HASHZIG_ERR_DECODE)?;
+    if !signer::prepare_epoch(&mut sk, u64::from(epoch)) {
+        return Err(HASHZIG_ERR_EPOCH);
+    }
+    let signature = S::sign(&sk, epoch, message).map_err(|_| HASHZIG_ERR_SIGN)?;
+    Ok(signature.as_ssz_bytes())
+}
+
+fn verify_for_scheme<S>(pk: &[u8], epoch: u32, message: &[u8; 32], sig: &[u8]) -> Result<bool, i32>
+where
+    S: SignatureScheme,
+    S::PublicKey: Decode,
+    S::Signature: Decode,
+{
+    let pk = S::PublicKey::from_ssz_bytes(pk).map_err(|_| HASHZIG_ERR_DECODE)?;
+    let sig = S::Signature::from_ssz_bytes(sig).map_err(|_| HASHZIG_ERR_DECODE)?;
+    Ok(S::verify(&pk, epoch, message, &sig))
+}
+
+/// Generate a key pair from a 32-byte seed and write both halves as SSZ.
+///
+/// # Safety
+///
+/// `seed` must point to 32 readable bytes, `pk_out`/`sk_out` to `pk_cap`/
+/// `sk_cap` writable bytes, and `pk_len`/`sk_len` to writable `usize`s.
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn hashzig_keygen_into_buffer(
+    log_lifetime: u32,
+    seed: *const u8,
+    activation_epoch: u64,
+    num_active_epochs: u64,
+    pk_out: *mut u8,
+    pk_cap: usize,
+    pk_len: *mut usize,
+    sk_out: *mut u8,
+    sk_cap: usize,
+    sk_len: *mut usize,
+) -> i32 {
+    guarded(|| {
+        if seed.is_null()
+            || pk_out.is_null()
+            || pk_len.is_null()
+            || sk_out.is_null()
+            || sk_len.is_null()
+        {
+            return Err(HASHZIG_ERR_NULL_POINTER);
+        }
+        let mut seed_bytes = [0u8; 32];
+        seed_bytes.copy_from_slice(slice::from_raw_parts(seed, 32));
+        let config = SchemeConfig::new()
+            .lifetime(lifetime_from_log(log_lifetime)?)
+            .seed(seed_bytes)
+            .activation(activation_epoch as usize, num_active_epochs as usize)
+            .build()
+            .map_err(|_| HASHZIG_ERR_CONFIG)?;
+
+        let (pk, sk) = match config.lifetime() {
+            LifetimeTag::Pow8 => {
+                keygen_for_scheme::<SIGTopLevelTargetSumLifetime8Dim64Base8>(&config)
+            }
+            LifetimeTag::Pow18 => {
+                keygen_for_scheme::<SIGTopLevelTargetSumLifetime18Dim64Base8>(&config)
+            }
+            LifetimeTag::Pow32 => {
+                keygen_for_scheme::<SIGTopLevelTargetSumLifetime32Dim64Base8>(&config)
+            }
+        };
+        // Report both lengths even if the first buffer is too small.
+        let pk_result = write_out(&pk, pk_out, pk_cap, pk_len);
+        let sk_result = write_out(&sk, sk_out, sk_cap, sk_len);
+        pk_result.and(sk_result).map(|()| HASHZIG_OK)
+    })
+}
+
+/// Sign a 32-byte message at `epoch` with an SSZ-encoded secret key.
+///
+/// The decoded key is advanced to `epoch` first, as `sign` does; the caller's
+/// buffer is left as it was. An epoch outside the key's activation interval
+/// is `HASHZIG_ERR_EPOCH`.
+///
+/// # Safety
+///
+/// `sk` must point to `sk_len` readable bytes, `message` to 32 readable
+/// bytes, `sig_out` to `sig_cap` writable bytes and `sig_len` to a writable
+/// `usize`.
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn hashzig_sign(
+    log_lifetime: u32,
+    sk: *const u8,
+    sk_len: usize,
+    epoch: u32,
+    message: *const u8,
+    sig_out: *mut u8,
+    sig_cap: usize,
+    sig_len: *mut usize,
+) -> i32 {
+    guarded(|| {
+        if sk.is_null() || message.is_null() || sig_out.is_null() || sig_len.is_null() {
+            return Err(HASHZIG_ERR_NULL_POINTER);
+        }
+        let sk = slice::from_raw_parts(sk, sk_len);
+        let message = &*(message as *const [u8; 32]);
+        let signature = match lifetime_from_log(log_lifetime)? {
+            LifetimeTag::Pow8 => {
+                sign_for_scheme::<SIGTopLevelTargetSumLifetime8Dim64Base8>(sk, epoch, message)?
+            }
+            LifetimeTag::Pow18 => {
+                sign_for_scheme::<SIGTopLevelTargetSumLifetime18Dim64Base8>(sk, epoch, message)?
+            }
+            LifetimeTag::Pow32 => {
+                sign_for_scheme::<SIGTopLevelTargetSumLifetime32Dim64Base8>(sk, epoch, message)?
+            }
+        };
+        write_out(&signature, sig_out, sig_cap, sig_len).map(|()| HASHZIG_OK)
+    })
+}
+
+/// Verify an SSZ-encoded signature. Returns 1 if valid, 0 if invalid and a
+/// negative error code if the inputs could not be decoded.
+///
+/// # Safety
+///
+/// `pk` and `sig` must point to `pk_len` and `sig_len` readable bytes and
+/// `message` to 32 readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn hashzig_verify(
+    log_lifetime: u32,
+    pk: *const u8,
+    pk_len: usize,
+    epoch: u32,
+    message: *const u8,
+    sig: *const u8,
+    sig_len: usize,
+) -> i32 {
+    guarded(|| {
+        if pk.is_null() || message.is_null() || sig.is_null() {
+            return Err(HASHZIG_ERR_NULL_POINTER);
+        }
+        let pk = slice::from_raw_parts(pk, pk_len);
+        let sig = slice::from_raw_parts(sig, sig_len);
+        let message = &*(message as *const [u8; 32]);
+        let valid = match lifetime_from_log(log_lifetime)? {
+            LifetimeTag::Pow8 => verify_for_scheme::<SIGTopLevelTargetSumLifetime8Dim64Base8>(
+                pk, epoch, message, sig,
+            )?,
+            LifetimeTag::Pow18 => verify_for_scheme::<SIGTopLevelTargetSumLifetime18Dim64Base8>(
+                pk, epoch, message, sig,
+            )?,
+            LifetimeTag::Pow32 => verify_for_scheme::<SIGTopLevelTargetSumLifetime32Dim64Base8>(
+                pk, epoch, message, sig,
+            )?,
+        };
+        Ok(valid as i32)
+    })
+}
@@ -0,0 +1,127 @@
+//! Progress of a long `keygen`, so an interrupted run can resume
+//!
+//! A 2^32 keygen that has to reach a far epoch spends a short while in
+//! `key_gen` and then hours preparing bottom trees up to that epoch.
+//! `key_gen` is a single upstream call with nothing to save halfway, but
+//! preparation moves one bottom tree at a time. So keygen records what it
+//! was asked to do before it starts, marks the record once the key pair is
+//! stored, and saves the secret key every [`INTERVAL`] while preparing. A
+//! crash or preemption then loses at most that much work. The record is
+//! removed when keygen completes.
+//!
+//! `keygen --resume` reads it back. If the pair was never stored, it
+//! generates again from the recorded seed and window, so a run with a
+//! random seed resumes the same key. Otherwise it loads the stored key and
+//! prepares on from the last bottom tree saved.
+
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde_json::{json, Value};
+
+use crate::config::Format;
+use crate::lifetime::LifetimeTag;
+use crate::seed;
+
+/// How often a key being prepared is saved.
+pub const INTERVAL: Duration = Duration::from_secs(60);
+
+const FILE_NAME: &str = "rust_keygen_checkpoint.json";
+
+/// Where the checkpoint of a keygen into `dir` lives.
+pub fn path(dir: &Path) -> PathBuf {
+    dir.join(FILE_NAME)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeygenCheckpoint {
+    pub lifetime: LifetimeTag,
+    pub format: Format,
+    pub seed: [u8; 32],
+    pub activation_epoch: usize,
+    pub num_active_epochs: usize,
+    /// Epoch the prepared window is advanced to once the pair is stored
+    pub prepare_to: Option<u32>,
+    /// Whether the key pair has been stored
+    pub generated: bool,
+}
+
+impl KeygenCheckpoint {
+    pub fn to_json(&self) -> Value {
+        json!({
+            "lifetime": self.lifetime.as_str(),
+            "format": self.format.as_str(),
+            "seed": hex::encode(self.seed),
+            "activation_epoch": self.activation_epoch,
+            "num_active_epochs": self.num_active_epochs,
+            "prepare_to": self.prepare_to,
+            "generated": self.generated,
+        })
+    }
+
+    pub fn from_json(value: &Value) -> Result<Self, Box<dyn Error>> {
+        let field = |key: &str| value.get(key).ok_or_else(|| format!("no '{key}'"));
+        let string = |key: &str| {
+            field(key)?
+                .as_str()
+                .ok_or_else(|| format!("'{key}' is not a string"))
+        };
+        let number = |key: &str| {
+            field(key)?
+                .as_u64()
+                .ok_or_else(|| format!("'{key}' is not a number"))
+        };
+        let prepare_to = match field("prepare_to")? {
+            Value::Null => None,
+            epoch => Some(
+                epoch
+                    .as_u64()
+                    .and_then(|epoch| u32::try_from(epoch).ok())
+                    .ok_or("'prepare_to' is not an epoch")?,
+            ),
+        };
+        Ok(KeygenCheckpoint {
+            lifetime: LifetimeTag::parse(Some(string("lifetime")?))?,
+            format: Format::parse(string("format")?)?,
+            seed: seed::parse(string("seed")?)?,
+            activation_epoch: number("activation_epoch")? as usize,
+            num_active_epochs: number("num_active_epochs")? as usize,
+            prepare_to,
+            generated: field("generated")?
+                .as_bool()
+                .ok_or("'generated' is not a boolean")?,
+        })
+    }
+
+    /// The checkpoint at `path`, or `None` if there is none.
+    pub fn load(path: &Path) -> Result<Option<Self>, Box<dyn Error>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let value: Value = serde_json::from_slice(&fs::read(path)?)?;
+        Self::from_json(&value)
+            .map(Some)
+            .map_err(|e| format!("{}: {e}", path.display()).into())
+    }
+
+    /// Written next to the old file and renamed over it, so a crash leaves
+    /// one checkpoint or the other.
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let partial = path.with_extension("json.partial");
+        fs::write(&partial, serde_json::to_string_pretty(&self.to_json())?)?;
+        fs::rename(&partial, path)?;
+        Ok(())
+    }
+
+    pub fn remove(path: &Path) -> Result<(), Box<dyn Error>> {
+        match fs::remove_file(path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
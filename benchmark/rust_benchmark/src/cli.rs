@@ -0,0 +1,75 @@
+//! Value parsers for the binaries' clap arguments
+//!
+//! The crate's `parse` functions return `Box<dyn Error>`, which clap cannot
+//! carry across threads, so each is wrapped here to return its message
+//! instead. Lifetimes accept everything `LifetimeTag::parse` does, formats
+//! everything `Format::parse` does, and so on, so scripts written against
+//! the hand-rolled parsers keep working.
+
+use crate::audit::AuditEpochs;
+use crate::baseline;
+use crate::compat::binfmt::{Endian, Repr};
+use crate::compat::multihash::Multibase;
+use crate::compat::text_encoding::Encoding;
+use crate::config::Format;
+use crate::gossip::GossipMix;
+use crate::hypertree::Epochs;
+use crate::lifetime::LifetimeTag;
+use crate::pk_cache::Fingerprint;
+use crate::regime::Regime;
+use crate::seed;
+
+pub fn lifetime(raw: &str) -> Result<LifetimeTag, String> {
+    LifetimeTag::parse(Some(raw)).map_err(|e| e.to_string())
+}
+
+pub fn format(raw: &str) -> Result<Format, String> {
+    Format::parse(raw).map_err(|e| e.to_string())
+}
+
+pub fn encoding(raw: &str) -> Result<Encoding, String> {
+    Encoding::parse(raw).map_err(|e| e.to_string())
+}
+
+pub fn repr(raw: &str) -> Result<Repr, String> {
+    Repr::parse(raw).map_err(|e| e.to_string())
+}
+
+pub fn endian(raw: &str) -> Result<Endian, String> {
+    Endian::parse(raw).map_err(|e| e.to_string())
+}
+
+pub fn multibase(raw: &str) -> Result<Multibase, String> {
+    Multibase::parse(raw).map_err(|e| e.to_string())
+}
+
+pub fn fingerprint(raw: &str) -> Result<Fingerprint, String> {
+    Fingerprint::parse(raw).map_err(|e| e.to_string())
+}
+
+/// A 32-byte seed as 64 hex digits, with or without `0x`; see [`seed::parse`].
+pub fn seed(raw: &str) -> Result<[u8; 32], String> {
+    seed::parse(raw).map_err(|e| e.to_string())
+}
+
+pub fn gossip_mix(raw: &str) -> Result<GossipMix, String> {
+    GossipMix::parse(raw).map_err(|e| e.to_string())
+}
+
+/// Comma-separated epochs and `start..end` ranges; see [`Epochs::parse`].
+pub fn epochs(raw: &str) -> Result<Epochs, String> {
+    Epochs::parse(raw).map_err(|e| e.to_string())
+}
+
+pub fn audit_epochs(raw: &str) -> Result<AuditEpochs, String> {
+    AuditEpochs::parse(raw).map_err(|e| e.to_string())
+}
+
+pub fn regime(raw: &str) -> Result<Regime, String> {
+    Regime::parse(raw).map_err(|e| e.to_string())
+}
+
+/// A fraction such as `5%` or `0.05`; see [`baseline::parse_threshold`].
+pub fn threshold(raw: &str) -> Result<f64, String> {
+    baseline::parse_threshold(raw).map_err(|e| e.to_string())
+}
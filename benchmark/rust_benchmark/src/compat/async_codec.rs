@@ -0,0 +1,187 @@
+//! Non-blocking reads and writes of encoded keys and signatures
+//!
+//! The daemons serve many connections from one tokio runtime, so they cannot
+//! use `std::fs` or blocking sockets without stalling every other request.
+//! These are the async counterparts of the file handling in the CLIs: bytes
+//! move through tokio, encoding and decoding go through `compat::codec`
+//! exactly as in the blocking path, including `.hsz` compression.
+//!
+//! Sockets carry one encoded value per frame: a big-endian u32 length
+//! followed by that many bytes. Frames above `MAX_FRAME_LEN` are rejected
+//! before anything is allocated.
+
+use std::error::Error;
+use std::io;
+use std::path::Path;
+
+use serde::{de::DeserializeOwned, Serialize};
+use ssz::{Decode, Encode};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::compat::{codec, hsz};
+use crate::config::Format;
+use crate::lifetime::SchemeMetadata;
+
+/// Largest frame accepted by [`read_frame`], so a corrupt length prefix
+/// cannot make the reader allocate gigabytes.
+pub const MAX_FRAME_LEN: usize = 64 * 1024 * 1024;
+
+/// The bytes of `path`, decompressed if it is an `.hsz` container: the
+/// async `hsz::read`.
+pub async fn read_file(path: &Path) -> Result<Vec<u8>, Box<dyn Error>> {
+    let bytes = tokio::fs::read(path)
+        .await
+        .map_err(|e| io::Error::new(e.kind(), format!("failed to read {}: {e}", path.display())))?;
+    hsz::decompress(&bytes)
+}
+
+async fn write_file(path: &Path, bytes: &[u8]) -> Result<(), Box<dyn Error>> {
+    let compressed;
+    let bytes = if hsz::is_hsz_path(path) {
+        compressed = hsz::compress(bytes)?;
+        &compressed
+    } else {
+        bytes
+    };
+    tokio::fs::write(path, bytes).await.map_err(|e| {
+        io::Error::new(e.kind(), format!("failed to write {}: {e}", path.display())).into()
+    })
+}
+
+pub async fn read_public_key<S, P>(path: P, format: Format) -> Result<S::PublicKey, Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::PublicKey: DeserializeOwned + Decode,
+    P: AsRef<Path>,
+{
+    codec::decode_public_key::<S>(&read_file(path.as_ref()).await?, format)
+}
+
+pub async fn write_public_key<S, P>(
+    pk: &S::PublicKey,
+    path: P,
+    format: Format,
+) -> Result<(), Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::PublicKey: Serialize + Encode,
+    P: AsRef<Path>,
+{
+    let bytes = codec::encode_public_key::<S>(pk, format)?;
+    write_file(path.as_ref(), &bytes).await
+}
+
+pub async fn read_secret_key<S, P>(path: P, format: Format) -> Result<S::SecretKey, Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::SecretKey: DeserializeOwned + Decode,
+    P: AsRef<Path>,
+{
+    codec::decode_secret_key::<S>(&read_file(path.as_ref()).await?, format)
+}
+
+pub async fn write_secret_key<S, P>(
+    sk: &S::SecretKey,
+    path: P,
+    format: Format,
+) -> Result<(), Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::SecretKey: Serialize + Encode,
+    P: AsRef<Path>,
+{
+    let bytes = codec::encode_secret_key::<S>(sk, format)?;
+    write_file(path.as_ref(), &bytes).await
+}
+
+pub async fn read_signature<S, P>(path: P, format: Format) -> Result<S::Signature, Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::Signature: DeserializeOwned + Decode,
+    P: AsRef<Path>,
+{
+    codec::decode_signature::<S>(&read_file(path.as_ref()).await?, format)
+}
+
+pub async fn write_signature<S, P>(
+    sig: &S::Signature,
+    path: P,
+    format: Format,
+) -> Result<(), Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::Signature: Serialize + Encode,
+    P: AsRef<Path>,
+{
+    let bytes = codec::encode_signature::<S>(sig, format)?;
+    write_file(path.as_ref(), &bytes).await
+}
+
+/// Read one length-prefixed frame. Returns `None` on a clean end of stream
+/// before the length prefix.
+pub async fn read_frame<R>(reader: &mut R) -> Result<Option<Vec<u8>>, Box<dyn Error>>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut len = [0u8; 4];
+    match reader.read_exact(&mut len).await {
+        Ok(_) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e.into()),
+    }
+    let len = u32::from_be_bytes(len) as usize;
+    if len > MAX_FRAME_LEN {
+        return Err(format!("frame of {len} bytes exceeds the {MAX_FRAME_LEN} byte limit").into());
+    }
+    let mut frame = vec![0u8; len];
+    reader.read_exact(&mut frame).await?;
+    Ok(Some(frame))
+}
+
+/// Write one length-prefixed frame and flush it.
+pub async fn write_frame<W>(writer: &mut W, frame: &[u8]) -> Result<(), Box<dyn Error>>
+where
+    W: AsyncWrite + Unpin,
+{
+    if frame.len() > MAX_FRAME_LEN {
+        return Err(format!(
+            "frame of {} bytes exceeds the {MAX_FRAME_LEN} byte limit",
+            frame.len()
+        )
+        .into());
+    }
+    writer
+        .write_all(&(frame.len() as u32).to_be_bytes())
+        .await?;
+    writer.write_all(frame).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+pub async fn read_signature_frame<S, R>(
+    reader: &mut R,
+    format: Format,
+) -> Result<Option<S::Signature>, Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::Signature: DeserializeOwned + Decode,
+    R: AsyncRead + Unpin,
+{
+    match read_frame(reader).await? {
+        Some(bytes) => codec::decode_signature::<S>(&bytes, format).map(Some),
+        None => Ok(None),
+    }
+}
+
+pub async fn write_signature_frame<S, W>(
+    writer: &mut W,
+    sig: &S::Signature,
+    format: Format,
+) -> Result<(), Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::Signature: Serialize + Encode,
+    W: AsyncWrite + Unpin,
+{
+    write_frame(writer, &codec::encode_signature::<S>(sig, format)?).await
+}
@@ -0,0 +1,1189 @@
+//! Montgomery binary format for signatures and public keys
+//!
+//! This is the layout `remote_hashsig_tool` exchanges with the Zig side.
+//! Signatures:
+//!
+//! ```text
+//! 4 bytes                     magic `HSIG`
+//! u8                          format version (1), | 0x80 if canonical,
+//!                             | 0x40 if big-endian
+//! u8                          log lifetime (8, 18 or 32)
+//! u8                          hash_len
+//! u8                          rand_len
+//! u64                         number of co-path nodes (N)
+//! N x hash_len x u32          co-path nodes
+//! rand_len x u32              rho
+//! u64                         number of chain hashes (M)
+//! M x hash_len x u32          chain hashes
+//! ```
+//!
+//! Public keys:
+//!
+//! ```text
+//! 4 bytes                     magic `HSPK`
+//! u8                          format version (1), | 0x80 if canonical,
+//!                             | 0x40 if big-endian
+//! u8                          log lifetime (8, 18 or 32)
+//! u8                          hash_len
+//! u8                          parameter_len
+//! hash_len x u32              root
+//! parameter_len x u32         parameter
+//! ```
+//!
+//! Field elements are stored in Montgomery form unless the writer chose
+//! `Repr::Canonical`, which sets the top bit of the version byte so a raw
+//! dump can be compared with the JSON artifacts. Counts and field elements
+//! are little-endian unless the writer chose `Endian::Big` for a big-endian
+//! consumer, which sets 0x40 in the version byte. Readers convert either
+//! way, and `convert_endian` rewrites a file in the other byte order.
+//! Decoded values are returned in the serde JSON shape (`path.nodes`, `rho`, `hashes` for signatures,
+//! `root`, `parameter` for keys) with canonical values, ready for
+//! `signature_from_json` or `serde_json::from_value`.
+//!
+//! The header lets a reader configured for one lifetime reject a file
+//! written for another before it touches the body. `read_signature_binary`
+//! also accepts files written with `compat::text_encoding::Encoding::Base64`.
+//!
+//! `SignatureView` parses a signature in place: it checks the header and
+//! section lengths of a `&[u8]` and hands out the field elements as
+//! iterators, or converts the whole body to SSZ in bulk so the upstream type
+//! is decoded without building a `serde_json::Value` first.
+//! `write_signature_ssz` goes the other way, from the upstream SSZ encoding.
+//! Both take the buffers they fill from the caller, so a daemon encoding or
+//! decoding one signature after another reuses the same allocations.
+
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use serde_json::Value;
+
+use crate::compat::lean_wire::Layout;
+use crate::compat::text_encoding;
+use crate::field::{
+    canonical_to_montgomery_slice, montgomery_to_canonical, montgomery_to_canonical_slice,
+};
+use crate::lifetime::{LifetimeError, LifetimeMetadata};
+
+pub const MAGIC: [u8; 4] = *b"HSIG";
+pub const PUBLIC_KEY_MAGIC: [u8; 4] = *b"HSPK";
+pub const FORMAT_VERSION: u8 = 1;
+/// Version-byte bit marking canonical field elements.
+pub const CANONICAL_FLAG: u8 = 0x80;
+/// Version-byte bit marking big-endian counts and field elements.
+pub const BIG_ENDIAN_FLAG: u8 = 0x40;
+pub const HEADER_LEN: usize = 8;
+
+/// How field elements are stored in the body.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Repr {
+    /// The representation both implementations use internally
+    #[default]
+    Montgomery,
+    /// `as_canonical_u32`, as in the JSON artifacts
+    Canonical,
+}
+
+impl Repr {
+    pub fn parse(raw: &str) -> Result<Self, Box<dyn Error>> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "montgomery" | "monty" => Ok(Self::Montgomery),
+            "canonical" => Ok(Self::Canonical),
+            other => Err(format!(
+                "unsupported representation '{other}'. Must be one of: montgomery, canonical"
+            )
+            .into()),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Repr::Montgomery => "montgomery",
+            Repr::Canonical => "canonical",
+        }
+    }
+
+    fn decode(self, raw: u32) -> u32 {
+        match self {
+            Repr::Montgomery => montgomery_to_canonical(raw),
+            Repr::Canonical => raw,
+        }
+    }
+
+    fn encode_slice(self, values: &mut [u32]) {
+        if self == Repr::Montgomery {
+            canonical_to_montgomery_slice(values);
+        }
+    }
+
+    fn decode_slice(self, values: &mut [u32]) {
+        if self == Repr::Montgomery {
+            montgomery_to_canonical_slice(values);
+        }
+    }
+}
+
+impl fmt::Display for Repr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Byte order of the counts and field elements in the body.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Endian {
+    /// What both implementations and SSZ use
+    #[default]
+    Little,
+    Big,
+}
+
+impl Endian {
+    pub fn parse(raw: &str) -> Result<Self, Box<dyn Error>> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "little" | "le" => Ok(Self::Little),
+            "big" | "be" => Ok(Self::Big),
+            other => {
+                Err(format!("unsupported byte order '{other}'. Must be one of: little, big").into())
+            }
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Endian::Little => "little",
+            Endian::Big => "big",
+        }
+    }
+
+    fn u32_bytes(self, value: u32) -> [u8; 4] {
+        match self {
+            Endian::Little => value.to_le_bytes(),
+            Endian::Big => value.to_be_bytes(),
+        }
+    }
+
+    fn u32_from(self, bytes: [u8; 4]) -> u32 {
+        match self {
+            Endian::Little => u32::from_le_bytes(bytes),
+            Endian::Big => u32::from_be_bytes(bytes),
+        }
+    }
+
+    fn u64_bytes(self, value: u64) -> [u8; 8] {
+        match self {
+            Endian::Little => value.to_le_bytes(),
+            Endian::Big => value.to_be_bytes(),
+        }
+    }
+
+    fn u64_from(self, bytes: [u8; 8]) -> u64 {
+        match self {
+            Endian::Little => u64::from_le_bytes(bytes),
+            Endian::Big => u64::from_be_bytes(bytes),
+        }
+    }
+}
+
+impl fmt::Display for Endian {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Whether `path` names a file in this format (`.bin`).
+pub fn is_binary_path<P: AsRef<Path>>(path: P) -> bool {
+    path.as_ref()
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("bin"))
+}
+
+#[derive(Clone, Copy)]
+enum Kind {
+    Signature,
+    PublicKey,
+}
+
+impl Kind {
+    fn magic(self) -> [u8; 4] {
+        match self {
+            Kind::Signature => MAGIC,
+            Kind::PublicKey => PUBLIC_KEY_MAGIC,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Kind::Signature => "signature",
+            Kind::PublicKey => "public key",
+        }
+    }
+
+    /// Name and value of the last header byte.
+    fn last_len(self, meta: LifetimeMetadata) -> (&'static str, usize) {
+        match self {
+            Kind::Signature => ("rand_len", meta.rand_len),
+            Kind::PublicKey => ("parameter_len", meta.parameter_len),
+        }
+    }
+}
+
+fn header_byte(value: usize, what: &str) -> Result<u8, Box<dyn Error>> {
+    u8::try_from(value).map_err(|_| format!("{what} {value} does not fit the header").into())
+}
+
+fn write_header<W: Write>(
+    writer: &mut W,
+    kind: Kind,
+    meta: LifetimeMetadata,
+    repr: Repr,
+    endian: Endian,
+) -> Result<(), Box<dyn Error>> {
+    let (last_name, last) = kind.last_len(meta);
+    let mut flags = match repr {
+        Repr::Montgomery => 0,
+        Repr::Canonical => CANONICAL_FLAG,
+    };
+    if endian == Endian::Big {
+        flags |= BIG_ENDIAN_FLAG;
+    }
+    writer.write_all(&kind.magic())?;
+    writer.write_all(&[
+        FORMAT_VERSION | flags,
+        header_byte(meta.log_lifetime as usize, "log lifetime")?,
+        header_byte(meta.hash_len, "hash_len")?,
+        header_byte(last, last_name)?,
+    ])?;
+    Ok(())
+}
+
+/// Check the header against the lifetime the caller expects and return the
+/// representation and byte order it declares.
+fn read_header<R: Read>(
+    reader: &mut R,
+    kind: Kind,
+    meta: LifetimeMetadata,
+) -> Result<(Repr, Endian), Box<dyn Error>> {
+    let name = kind.name();
+    let magic = kind.magic();
+    let mut header = [0u8; HEADER_LEN];
+    reader
+        .read_exact(&mut header)
+        .map_err(|e| format!("binary {name} header: {e}"))?;
+    if header[..4] != magic {
+        return Err(format!(
+            "not a binary {name}: magic {:02x?}, expected {:02x?} ({:?})",
+            &header[..4],
+            magic,
+            String::from_utf8_lossy(&magic)
+        )
+        .into());
+    }
+    let [version, log_lifetime, hash_len, last] = [header[4], header[5], header[6], header[7]];
+    let repr = if version & CANONICAL_FLAG != 0 {
+        Repr::Canonical
+    } else {
+        Repr::Montgomery
+    };
+    let endian = if version & BIG_ENDIAN_FLAG != 0 {
+        Endian::Big
+    } else {
+        Endian::Little
+    };
+    let version = version & !(CANONICAL_FLAG | BIG_ENDIAN_FLAG);
+    if version != FORMAT_VERSION {
+        return Err(format!(
+            "unsupported binary {name} version {version}, expected {FORMAT_VERSION}"
+        )
+        .into());
+    }
+    if u32::from(log_lifetime) != meta.log_lifetime {
+        return Err(LifetimeError(format!(
+            "{name} is for lifetime 2^{log_lifetime}, expected 2^{}",
+            meta.log_lifetime
+        ))
+        .into());
+    }
+    let (last_name, expected_last) = kind.last_len(meta);
+    if usize::from(hash_len) != meta.hash_len || usize::from(last) != expected_last {
+        return Err(format!(
+            "{name} has hash_len {hash_len} and {last_name} {last}, expected {} and {expected_last}",
+            meta.hash_len
+        )
+        .into());
+    }
+    Ok((repr, endian))
+}
+
+fn write_u64<W: Write>(writer: &mut W, value: u64, endian: Endian) -> Result<(), Box<dyn Error>> {
+    writer.write_all(&endian.u64_bytes(value))?;
+    Ok(())
+}
+
+fn write_u32<W: Write>(writer: &mut W, value: u32, endian: Endian) -> Result<(), Box<dyn Error>> {
+    writer.write_all(&endian.u32_bytes(value))?;
+    Ok(())
+}
+
+fn write_field_elements<W: Write>(
+    writer: &mut W,
+    entries: &[Value],
+    len: usize,
+    what: &str,
+    repr: Repr,
+    endian: Endian,
+) -> Result<(), Box<dyn Error>> {
+    if entries.len() < len {
+        return Err(format!(
+            "{what} length {} shorter than expected {len}",
+            entries.len()
+        )
+        .into());
+    }
+    // serde values are canonical
+    let mut words = entries
+        .iter()
+        .take(len)
+        .map(|entry| {
+            let num = entry
+                .as_u64()
+                .ok_or_else(|| format!("{what} entry is not an unsigned integer"))?;
+            u32::try_from(num).map_err(|_| format!("{what} entry exceeds u32"))
+        })
+        .collect::<Result<Vec<u32>, String>>()?;
+    repr.encode_slice(&mut words);
+    for word in words {
+        write_u32(writer, word, endian)?;
+    }
+    Ok(())
+}
+
+fn read_field_elements<R: Read>(
+    reader: &mut R,
+    len: usize,
+    repr: Repr,
+    endian: Endian,
+) -> Result<Value, Box<dyn Error>> {
+    let mut section = vec![0u8; len * 4];
+    reader.read_exact(&mut section)?;
+    // Back to canonical for serde deserialization
+    Ok(words_value(
+        canonical_section(&section, repr, endian).into_iter(),
+    ))
+}
+
+fn check_count(
+    raw: &[u8],
+    max: usize,
+    what: &str,
+    endian: Endian,
+) -> Result<usize, Box<dyn Error>> {
+    let count = endian.u64_from(raw.try_into().unwrap());
+    if count > max as u64 {
+        return Err(format!("{what} count {count} exceeds maximum {max}").into());
+    }
+    Ok(count as usize)
+}
+
+/// Split `len` bytes off the front of `bytes`.
+fn take<'a>(bytes: &mut &'a [u8], len: usize, what: &str) -> Result<&'a [u8], Box<dyn Error>> {
+    if bytes.len() < len {
+        return Err(format!(
+            "binary signature truncated in {what}: {} bytes left, {len} needed",
+            bytes.len()
+        )
+        .into());
+    }
+    let (head, rest) = bytes.split_at(len);
+    *bytes = rest;
+    Ok(head)
+}
+
+/// u32s of `section` as canonical values.
+fn canonical_words(section: &[u8], repr: Repr, endian: Endian) -> impl Iterator<Item = u32> + '_ {
+    section
+        .chunks_exact(4)
+        .map(move |word| repr.decode(endian.u32_from(word.try_into().unwrap())))
+}
+
+/// u32s of `section` as canonical values, converted in bulk.
+fn canonical_section(section: &[u8], repr: Repr, endian: Endian) -> Vec<u32> {
+    let mut words: Vec<u32> = section
+        .chunks_exact(4)
+        .map(|word| endian.u32_from(word.try_into().unwrap()))
+        .collect();
+    repr.decode_slice(&mut words);
+    words
+}
+
+/// Append `section` to `out` as canonical little-endian u32s, converting
+/// through `words`.
+fn extend_canonical(
+    out: &mut Vec<u8>,
+    section: &[u8],
+    repr: Repr,
+    endian: Endian,
+    words: &mut Vec<u32>,
+) {
+    if (repr, endian) == (Repr::Canonical, Endian::Little) {
+        out.extend_from_slice(section);
+        return;
+    }
+    words.clear();
+    words.extend(
+        section
+            .chunks_exact(4)
+            .map(|word| endian.u32_from(word.try_into().unwrap())),
+    );
+    repr.decode_slice(words);
+    out.extend(words.iter().flat_map(|word| word.to_le_bytes()));
+}
+
+/// Append canonical little-endian `section` to `out` in `repr` and `endian`,
+/// converting through `words`.
+fn extend_encoded(
+    out: &mut Vec<u8>,
+    section: &[u8],
+    repr: Repr,
+    endian: Endian,
+    words: &mut Vec<u32>,
+) {
+    if (repr, endian) == (Repr::Canonical, Endian::Little) {
+        out.extend_from_slice(section);
+        return;
+    }
+    words.clear();
+    words.extend(
+        section
+            .chunks_exact(4)
+            .map(|word| u32::from_le_bytes(word.try_into().unwrap())),
+    );
+    repr.encode_slice(words);
+    out.extend(words.iter().flat_map(|&word| endian.u32_bytes(word)));
+}
+
+fn words_value(words: impl Iterator<Item = u32>) -> Value {
+    Value::Array(words.map(Value::from).collect())
+}
+
+/// A binary signature borrowed from a byte slice.
+#[derive(Debug, Clone, Copy)]
+pub struct SignatureView<'a> {
+    repr: Repr,
+    endian: Endian,
+    node_bytes: usize,
+    nodes: &'a [u8],
+    rho: &'a [u8],
+    hashes: &'a [u8],
+}
+
+impl<'a> SignatureView<'a> {
+    /// Check the header against `meta` and split the body into sections.
+    /// `bytes` must hold exactly one signature; nothing is copied.
+    pub fn parse(bytes: &'a [u8], meta: LifetimeMetadata) -> Result<Self, Box<dyn Error>> {
+        let mut rest = bytes;
+        let (repr, endian) = read_header(&mut rest, Kind::Signature, meta)?;
+        let node_bytes = meta.hash_len * 4;
+
+        let path_len = check_count(
+            take(&mut rest, 8, "path length")?,
+            meta.log_lifetime as usize,
+            "path node",
+            endian,
+        )?;
+        let nodes = take(&mut rest, path_len * node_bytes, "co-path")?;
+        let rho = take(&mut rest, meta.rand_len * 4, "rho")?;
+        let hashes_len = check_count(
+            take(&mut rest, 8, "hash count")?,
+            meta.dimension,
+            "hash",
+            endian,
+        )?;
+        let hashes = take(&mut rest, hashes_len * node_bytes, "hashes")?;
+        if !rest.is_empty() {
+            return Err(format!(
+                "binary signature has {} trailing bytes after the chain hashes",
+                rest.len()
+            )
+            .into());
+        }
+        Ok(SignatureView {
+            repr,
+            endian,
+            node_bytes,
+            nodes,
+            rho,
+            hashes,
+        })
+    }
+
+    /// Representation declared by the header.
+    pub fn repr(&self) -> Repr {
+        self.repr
+    }
+
+    /// Byte order declared by the header.
+    pub fn endian(&self) -> Endian {
+        self.endian
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len() / self.node_bytes
+    }
+
+    pub fn hash_count(&self) -> usize {
+        self.hashes.len() / self.node_bytes
+    }
+
+    /// Co-path nodes, each as `hash_len` canonical values.
+    pub fn nodes(&self) -> impl Iterator<Item = impl Iterator<Item = u32> + 'a> + 'a {
+        let (repr, endian) = (self.repr, self.endian);
+        self.nodes
+            .chunks_exact(self.node_bytes)
+            .map(move |node| canonical_words(node, repr, endian))
+    }
+
+    /// rho as canonical values.
+    pub fn rho(&self) -> impl Iterator<Item = u32> + 'a {
+        canonical_words(self.rho, self.repr, self.endian)
+    }
+
+    /// Chain hashes, each as `hash_len` canonical values.
+    pub fn hashes(&self) -> impl Iterator<Item = impl Iterator<Item = u32> + 'a> + 'a {
+        let (repr, endian) = (self.repr, self.endian);
+        self.hashes
+            .chunks_exact(self.node_bytes)
+            .map(move |hash| canonical_words(hash, repr, endian))
+    }
+
+    /// The serde JSON shape returned by `read_signature`.
+    pub fn to_value(&self) -> Value {
+        let hash_len = self.node_bytes / 4;
+        let chunked = |section: &[u8]| {
+            let words = canonical_section(section, self.repr, self.endian);
+            Value::Array(
+                words
+                    .chunks_exact(hash_len)
+                    .map(|chunk| words_value(chunk.iter().copied()))
+                    .collect(),
+            )
+        };
+        let mut path_obj = serde_json::Map::new();
+        path_obj.insert("nodes".to_string(), chunked(self.nodes));
+
+        let mut sig_obj = serde_json::Map::new();
+        sig_obj.insert("path".to_string(), Value::Object(path_obj));
+        sig_obj.insert(
+            "rho".to_string(),
+            words_value(canonical_section(self.rho, self.repr, self.endian).into_iter()),
+        );
+        sig_obj.insert("hashes".to_string(), chunked(self.hashes));
+        Value::Object(sig_obj)
+    }
+
+    /// The SSZ encoding of the same signature (the container layout in
+    /// `compat::lean_wire`, without padding), for `Decode::from_ssz_bytes`.
+    /// Sections are converted in bulk; canonical little-endian files are
+    /// copied as is.
+    pub fn to_ssz_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        self.write_ssz_bytes(&mut out, &mut Vec::new());
+        out
+    }
+
+    /// `to_ssz_bytes` into `out`, which is cleared first, converting
+    /// sections through `words`. Neither allocates once it has grown to a
+    /// signature's size.
+    pub fn write_ssz_bytes(&self, out: &mut Vec<u8>, words: &mut Vec<u32>) {
+        const OFFSET: usize = 4;
+        let path_offset = OFFSET + self.rho.len() + OFFSET;
+        let hashes_offset = path_offset + OFFSET + self.nodes.len();
+        out.clear();
+        out.reserve(hashes_offset + self.hashes.len());
+        out.extend_from_slice(&(path_offset as u32).to_le_bytes());
+        extend_canonical(out, self.rho, self.repr, self.endian, words);
+        out.extend_from_slice(&(hashes_offset as u32).to_le_bytes());
+        out.extend_from_slice(&(OFFSET as u32).to_le_bytes());
+        extend_canonical(out, self.nodes, self.repr, self.endian, words);
+        extend_canonical(out, self.hashes, self.repr, self.endian, words);
+    }
+}
+
+/// Read one signature's bytes from `reader`, a section at a time.
+fn read_signature_bytes<R: Read>(
+    reader: &mut R,
+    meta: LifetimeMetadata,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    fn section<R: Read>(
+        reader: &mut R,
+        bytes: &mut Vec<u8>,
+        len: usize,
+        what: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        let start = bytes.len();
+        bytes.resize(start + len, 0);
+        reader
+            .read_exact(&mut bytes[start..])
+            .map_err(|e| format!("binary signature {what}: {e}").into())
+    }
+
+    let node_bytes = meta.hash_len * 4;
+    let mut bytes = Vec::new();
+    section(reader, &mut bytes, HEADER_LEN, "header")?;
+    let (_, endian) = read_header(&mut bytes.as_slice(), Kind::Signature, meta)?;
+    section(reader, &mut bytes, 8, "path length")?;
+    let path_len = check_count(
+        &bytes[HEADER_LEN..],
+        meta.log_lifetime as usize,
+        "path node",
+        endian,
+    )?;
+    section(
+        reader,
+        &mut bytes,
+        path_len * node_bytes + meta.rand_len * 4 + 8,
+        "co-path and rho",
+    )?;
+    let hashes_len = check_count(&bytes[bytes.len() - 8..], meta.dimension, "hash", endian)?;
+    section(reader, &mut bytes, hashes_len * node_bytes, "hashes")?;
+    Ok(bytes)
+}
+
+/// Encode a signature JSON value (as produced by `signature_to_json`).
+pub fn write_signature<W: Write>(
+    writer: &mut W,
+    value: &Value,
+    meta: LifetimeMetadata,
+    repr: Repr,
+    endian: Endian,
+) -> Result<(), Box<dyn Error>> {
+    let nodes_array = value
+        .get("path")
+        .and_then(|p| p.get("nodes"))
+        .and_then(|n| n.as_array())
+        .ok_or("signature JSON missing path.nodes")?;
+    let rho_array = value
+        .get("rho")
+        .and_then(|r| r.as_array())
+        .ok_or("signature JSON missing rho array")?;
+    let hashes_array = value
+        .get("hashes")
+        .and_then(|h| h.as_array())
+        .ok_or("signature JSON missing hashes array")?;
+
+    write_header(writer, Kind::Signature, meta, repr, endian)?;
+    write_u64(writer, u64::try_from(nodes_array.len())?, endian)?;
+    for node in nodes_array {
+        let node_arr = node.as_array().ok_or("path node is not an array")?;
+        write_field_elements(writer, node_arr, meta.hash_len, "path node", repr, endian)?;
+    }
+
+    write_field_elements(writer, rho_array, meta.rand_len, "rho", repr, endian)?;
+
+    write_u64(writer, u64::try_from(hashes_array.len())?, endian)?;
+    for domain in hashes_array {
+        let domain_arr = domain.as_array().ok_or("hash domain is not an array")?;
+        write_field_elements(
+            writer,
+            domain_arr,
+            meta.hash_len,
+            "hash domain",
+            repr,
+            endian,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Append the binary encoding of a signature given as its SSZ encoding (as
+/// `Encode::as_ssz_bytes` writes it) to `out`, converting sections through
+/// `words`. The SSZ container must hold a full co-path and every chain hash
+/// for `meta`; its field elements are taken as canonical.
+pub fn write_signature_ssz(
+    out: &mut Vec<u8>,
+    ssz: &[u8],
+    meta: LifetimeMetadata,
+    repr: Repr,
+    endian: Endian,
+    words: &mut Vec<u32>,
+) -> Result<(), Box<dyn Error>> {
+    let layout = Layout::of(meta);
+    if ssz.len() != layout.end {
+        return Err(format!(
+            "SSZ signature is {} bytes, expected {} for lifetime 2^{}",
+            ssz.len(),
+            layout.end,
+            meta.log_lifetime
+        )
+        .into());
+    }
+    let offset = |at: usize| u32::from_le_bytes(ssz[at..at + 4].try_into().unwrap()) as usize;
+    for (at, expected, what) in [
+        (0, layout.path, "path"),
+        (layout.hashes_offset, layout.hashes, "hashes"),
+        (layout.path, layout.nodes - layout.path, "path siblings"),
+    ] {
+        if offset(at) != expected {
+            return Err(format!(
+                "SSZ signature {what} offset is {}, expected {expected}",
+                offset(at)
+            )
+            .into());
+        }
+    }
+
+    write_header(out, Kind::Signature, meta, repr, endian)?;
+    write_u64(out, u64::from(meta.log_lifetime), endian)?;
+    extend_encoded(out, &ssz[layout.nodes..layout.hashes], repr, endian, words);
+    extend_encoded(
+        out,
+        &ssz[layout.rho..layout.hashes_offset],
+        repr,
+        endian,
+        words,
+    );
+    write_u64(out, u64::try_from(meta.dimension)?, endian)?;
+    extend_encoded(out, &ssz[layout.hashes..layout.end], repr, endian, words);
+    Ok(())
+}
+
+/// Decode a signature into its serde JSON shape with canonical values.
+///
+/// The header must name `meta`'s lifetime, hash and randomness lengths. The
+/// node and hash counts are bounded by the lifetime's tree depth and
+/// encoding dimension, so a file written for a different lifetime fails
+/// with an error instead of a huge allocation.
+pub fn read_signature<R: Read>(
+    reader: &mut R,
+    meta: LifetimeMetadata,
+) -> Result<Value, Box<dyn Error>> {
+    let bytes = read_signature_bytes(reader, meta)?;
+    Ok(SignatureView::parse(&bytes, meta)?.to_value())
+}
+
+pub fn write_signature_binary<P>(
+    value: &Value,
+    path: P,
+    meta: LifetimeMetadata,
+    repr: Repr,
+    endian: Endian,
+) -> Result<(), Box<dyn Error>>
+where
+    P: AsRef<Path>,
+{
+    let mut writer = BufWriter::new(File::create(path)?);
+    write_signature(&mut writer, value, meta, repr, endian)?;
+    writer.flush()?;
+    Ok(())
+}
+
+pub fn read_signature_binary<P>(path: P, meta: LifetimeMetadata) -> Result<Value, Box<dyn Error>>
+where
+    P: AsRef<Path>,
+{
+    let bytes = text_encoding::decode(&fs::read(path)?)?;
+    Ok(SignatureView::parse(&bytes, meta)?.to_value())
+}
+
+/// Encode a public key JSON value (`root`, `parameter`).
+pub fn write_public_key<W: Write>(
+    writer: &mut W,
+    value: &Value,
+    meta: LifetimeMetadata,
+    repr: Repr,
+    endian: Endian,
+) -> Result<(), Box<dyn Error>> {
+    let root = value
+        .get("root")
+        .and_then(|r| r.as_array())
+        .ok_or("public key JSON missing root array")?;
+    let parameter = value
+        .get("parameter")
+        .and_then(|p| p.as_array())
+        .ok_or("public key JSON missing parameter array")?;
+
+    write_header(writer, Kind::PublicKey, meta, repr, endian)?;
+    write_field_elements(writer, root, meta.hash_len, "root", repr, endian)?;
+    write_field_elements(
+        writer,
+        parameter,
+        meta.parameter_len,
+        "parameter",
+        repr,
+        endian,
+    )?;
+    Ok(())
+}
+
+/// Decode a public key into its serde JSON shape with canonical values.
+pub fn read_public_key<R: Read>(
+    reader: &mut R,
+    meta: LifetimeMetadata,
+) -> Result<Value, Box<dyn Error>> {
+    let (repr, endian) = read_header(reader, Kind::PublicKey, meta)?;
+    let root = read_field_elements(reader, meta.hash_len, repr, endian)?;
+    let parameter = read_field_elements(reader, meta.parameter_len, repr, endian)?;
+
+    let mut pk_obj = serde_json::Map::new();
+    pk_obj.insert("root".to_string(), root);
+    pk_obj.insert("parameter".to_string(), parameter);
+    Ok(Value::Object(pk_obj))
+}
+
+pub fn write_public_key_binary<P>(
+    value: &Value,
+    path: P,
+    meta: LifetimeMetadata,
+    repr: Repr,
+    endian: Endian,
+) -> Result<(), Box<dyn Error>>
+where
+    P: AsRef<Path>,
+{
+    let mut writer = BufWriter::new(File::create(path)?);
+    write_public_key(&mut writer, value, meta, repr, endian)?;
+    writer.flush()?;
+    Ok(())
+}
+
+pub fn read_public_key_binary<P>(path: P, meta: LifetimeMetadata) -> Result<Value, Box<dyn Error>>
+where
+    P: AsRef<Path>,
+{
+    let mut reader = BufReader::new(File::open(path)?);
+    read_public_key(&mut reader, meta)
+}
+
+/// Rewrite a binary signature or public key (told apart by the magic) in
+/// `endian` byte order, keeping its representation. Files already in that
+/// order come back unchanged.
+pub fn convert_endian(
+    bytes: &[u8],
+    meta: LifetimeMetadata,
+    endian: Endian,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut out = Vec::with_capacity(bytes.len());
+    if bytes.starts_with(&PUBLIC_KEY_MAGIC) {
+        let (repr, _) = read_header(&mut &bytes[..], Kind::PublicKey, meta)?;
+        let value = read_public_key(&mut &bytes[..], meta)?;
+        write_public_key(&mut out, &value, meta, repr, endian)?;
+    } else {
+        let view = SignatureView::parse(bytes, meta)?;
+        write_signature(&mut out, &view.to_value(), meta, view.repr(), endian)?;
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lifetime::LifetimeTag;
+    use serde_json::json;
+
+    fn sample_signature(meta: LifetimeMetadata) -> Value {
+        let element = |i: usize| Value::from((i as u64 * 7919 + 1) % crate::field::KOALABEAR_PRIME);
+        let domain = |seed: usize| {
+            Value::Array((0..meta.hash_len).map(|j| element(seed * 31 + j)).collect())
+        };
+        json!({
+            "path": { "nodes": (0..meta.log_lifetime as usize).map(domain).collect::<Vec<_>>() },
+            "rho": (0..meta.rand_len).map(|i| element(1000 + i)).collect::<Vec<_>>(),
+            "hashes": (0..meta.dimension).map(|i| domain(500 + i)).collect::<Vec<_>>(),
+        })
+    }
+
+    fn encode(value: &Value, meta: LifetimeMetadata) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        write_signature(&mut bytes, value, meta, Repr::Montgomery, Endian::Little).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn round_trip_every_lifetime() {
+        for tag in LifetimeTag::ALL {
+            let meta = tag.metadata();
+            let sig = sample_signature(meta);
+            let bytes = encode(&sig, meta);
+            let expected_len = HEADER_LEN
+                + 8
+                + meta.log_lifetime as usize * meta.hash_len * 4
+                + meta.rand_len * 4
+                + 8
+                + meta.dimension * meta.hash_len * 4;
+            assert_eq!(bytes.len(), expected_len, "{tag}");
+            let decoded = read_signature(&mut bytes.as_slice(), meta).unwrap();
+            assert_eq!(decoded, sig, "{tag}");
+        }
+    }
+
+    #[test]
+    fn truncated_input_is_rejected() {
+        let meta = LifetimeTag::Pow8.metadata();
+        let bytes = encode(&sample_signature(meta), meta);
+        for cut in [0, 7, HEADER_LEN, HEADER_LEN + 8, 100, bytes.len() - 1] {
+            assert!(
+                read_signature(&mut &bytes[..cut], meta).is_err(),
+                "cut at {cut}"
+            );
+        }
+    }
+
+    #[test]
+    fn mismatched_hash_len_is_rejected() {
+        let written = LifetimeTag::Pow8.metadata();
+        let bytes = encode(&sample_signature(written), written);
+        let read_as = LifetimeMetadata {
+            hash_len: written.hash_len - 1,
+            ..written
+        };
+        assert!(read_signature(&mut bytes.as_slice(), read_as).is_err());
+    }
+
+    #[test]
+    fn other_lifetime_is_named_in_the_error() {
+        let written = LifetimeTag::Pow32.metadata();
+        let bytes = encode(&sample_signature(written), written);
+        let err = read_signature(&mut bytes.as_slice(), LifetimeTag::Pow8.metadata()).unwrap_err();
+        assert!(
+            err.to_string().contains("lifetime 2^32, expected 2^8"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn bad_magic_and_version_are_rejected() {
+        let meta = LifetimeTag::Pow8.metadata();
+        let bytes = encode(&sample_signature(meta), meta);
+        assert_eq!(&bytes[..4], b"HSIG");
+
+        let mut headerless = bytes[HEADER_LEN..].to_vec();
+        headerless.resize(bytes.len(), 0);
+        let err = read_signature(&mut headerless.as_slice(), meta).unwrap_err();
+        assert!(err.to_string().contains("magic"), "{err}");
+
+        let mut future = bytes.clone();
+        future[4] = FORMAT_VERSION + 1;
+        let err = read_signature(&mut future.as_slice(), meta).unwrap_err();
+        assert!(err.to_string().contains("version"), "{err}");
+    }
+
+    #[test]
+    fn public_key_round_trip_every_lifetime() {
+        for tag in LifetimeTag::ALL {
+            let meta = tag.metadata();
+            let element = |i: usize| Value::from(i as u64 * 104_729 + 3);
+            let pk = json!({
+                "root": (0..meta.hash_len).map(element).collect::<Vec<_>>(),
+                "parameter": (0..meta.parameter_len).map(|i| element(100 + i)).collect::<Vec<_>>(),
+            });
+            let mut bytes = Vec::new();
+            write_public_key(&mut bytes, &pk, meta, Repr::Montgomery, Endian::Little).unwrap();
+            assert_eq!(&bytes[..4], b"HSPK", "{tag}");
+            assert_eq!(
+                bytes.len(),
+                HEADER_LEN + (meta.hash_len + meta.parameter_len) * 4,
+                "{tag}"
+            );
+            assert_eq!(
+                read_public_key(&mut bytes.as_slice(), meta).unwrap(),
+                pk,
+                "{tag}"
+            );
+
+            // A signature reader must not accept a key, and vice versa.
+            assert!(
+                read_signature(&mut bytes.as_slice(), meta).is_err(),
+                "{tag}"
+            );
+            let sig_bytes = encode(&sample_signature(meta), meta);
+            assert!(
+                read_public_key(&mut sig_bytes.as_slice(), meta).is_err(),
+                "{tag}"
+            );
+        }
+    }
+
+    #[test]
+    fn short_arrays_are_rejected_on_write() {
+        let meta = LifetimeTag::Pow8.metadata();
+        let mut sig = sample_signature(meta);
+        sig["rho"].as_array_mut().unwrap().pop();
+        assert!(write_signature(
+            &mut Vec::new(),
+            &sig,
+            meta,
+            Repr::Montgomery,
+            Endian::Little
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn canonical_files_are_flagged_and_converted_on_read() {
+        let meta = LifetimeTag::Pow8.metadata();
+        let sig = sample_signature(meta);
+        let montgomery = encode(&sig, meta);
+        let mut canonical = Vec::new();
+        write_signature(&mut canonical, &sig, meta, Repr::Canonical, Endian::Little).unwrap();
+
+        assert_eq!(montgomery[4], FORMAT_VERSION);
+        assert_eq!(canonical[4], FORMAT_VERSION | CANONICAL_FLAG);
+        assert_eq!(canonical.len(), montgomery.len());
+        assert_ne!(canonical, montgomery);
+        // rho starts right after the header and the co-path
+        let rho_at = HEADER_LEN + 8 + meta.log_lifetime as usize * meta.hash_len * 4;
+        assert_eq!(
+            u64::from(u32::from_le_bytes(
+                canonical[rho_at..rho_at + 4].try_into().unwrap()
+            )),
+            sig["rho"][0].as_u64().unwrap()
+        );
+        assert_eq!(
+            read_signature(&mut canonical.as_slice(), meta).unwrap(),
+            sig
+        );
+    }
+
+    #[test]
+    fn views_borrow_the_same_signature() {
+        for tag in LifetimeTag::ALL {
+            let meta = tag.metadata();
+            let sig = sample_signature(meta);
+            for repr in [Repr::Montgomery, Repr::Canonical] {
+                let mut bytes = Vec::new();
+                write_signature(&mut bytes, &sig, meta, repr, Endian::Little).unwrap();
+                let view = SignatureView::parse(&bytes, meta).unwrap();
+                assert_eq!(view.repr(), repr);
+                assert_eq!(view.node_count(), meta.log_lifetime as usize);
+                assert_eq!(view.hash_count(), meta.dimension);
+                assert_eq!(view.to_value(), sig, "{tag} {repr}");
+                assert_eq!(
+                    view.rho().map(u64::from).collect::<Vec<_>>(),
+                    sig["rho"]
+                        .as_array()
+                        .unwrap()
+                        .iter()
+                        .map(|v| v.as_u64().unwrap())
+                        .collect::<Vec<_>>()
+                );
+
+                // The SSZ container is the wire format without its padding
+                let wire = crate::compat::lean_wire::encode_signature(&sig, meta).unwrap();
+                let ssz = view.to_ssz_bytes();
+                assert_eq!(ssz, wire[..ssz.len()], "{tag} {repr}");
+            }
+        }
+    }
+
+    #[test]
+    fn ssz_encoding_matches_the_json_path_with_reused_buffers() {
+        let (mut out, mut ssz, mut words) = (Vec::new(), Vec::new(), Vec::new());
+        for tag in LifetimeTag::ALL {
+            let meta = tag.metadata();
+            let sig = sample_signature(meta);
+            for (repr, endian) in [
+                (Repr::Montgomery, Endian::Little),
+                (Repr::Canonical, Endian::Little),
+                (Repr::Montgomery, Endian::Big),
+            ] {
+                let mut expected = Vec::new();
+                write_signature(&mut expected, &sig, meta, repr, endian).unwrap();
+                SignatureView::parse(&expected, meta)
+                    .unwrap()
+                    .write_ssz_bytes(&mut ssz, &mut words);
+                assert_eq!(
+                    ssz,
+                    SignatureView::parse(&expected, meta)
+                        .unwrap()
+                        .to_ssz_bytes()
+                );
+
+                out.clear();
+                write_signature_ssz(&mut out, &ssz, meta, repr, endian, &mut words).unwrap();
+                assert_eq!(out, expected, "{tag} {repr} {endian}");
+            }
+        }
+        let meta = LifetimeTag::Pow8.metadata();
+        let err = write_signature_ssz(
+            &mut out,
+            &ssz,
+            meta,
+            Repr::Montgomery,
+            Endian::Little,
+            &mut words,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("expected"), "{err}");
+    }
+
+    #[test]
+    fn views_reject_trailing_bytes() {
+        let meta = LifetimeTag::Pow8.metadata();
+        let mut bytes = encode(&sample_signature(meta), meta);
+        bytes.push(0);
+        let err = SignatureView::parse(&bytes, meta).unwrap_err();
+        assert!(err.to_string().contains("trailing"), "{err}");
+        // A reader stops after the signature
+        let mut reader = bytes.as_slice();
+        assert!(read_signature(&mut reader, meta).is_ok());
+        assert_eq!(reader, [0]);
+    }
+
+    #[test]
+    fn parses_repr_names() {
+        assert_eq!(Repr::parse("Canonical").unwrap(), Repr::Canonical);
+        assert_eq!(Repr::parse("montgomery").unwrap(), Repr::Montgomery);
+        assert!(Repr::parse("hex").is_err());
+    }
+
+    #[test]
+    fn big_endian_files_are_flagged_and_converted() {
+        for tag in LifetimeTag::ALL {
+            let meta = tag.metadata();
+            let sig = sample_signature(meta);
+            for repr in [Repr::Montgomery, Repr::Canonical] {
+                let mut little = Vec::new();
+                write_signature(&mut little, &sig, meta, repr, Endian::Little).unwrap();
+                let mut big = Vec::new();
+                write_signature(&mut big, &sig, meta, repr, Endian::Big).unwrap();
+
+                assert_eq!(big[4] & BIG_ENDIAN_FLAG, BIG_ENDIAN_FLAG);
+                assert_eq!(big.len(), little.len());
+                // The co-path count is the first body field
+                assert_eq!(
+                    u64::from_be_bytes(big[HEADER_LEN..HEADER_LEN + 8].try_into().unwrap()),
+                    u64::from(meta.log_lifetime)
+                );
+                let view = SignatureView::parse(&big, meta).unwrap();
+                assert_eq!(view.endian(), Endian::Big);
+                assert_eq!(view.to_value(), sig, "{tag} {repr}");
+                assert_eq!(
+                    view.to_ssz_bytes(),
+                    SignatureView::parse(&little, meta).unwrap().to_ssz_bytes()
+                );
+                assert_eq!(read_signature(&mut big.as_slice(), meta).unwrap(), sig);
+
+                assert_eq!(convert_endian(&big, meta, Endian::Little).unwrap(), little);
+                assert_eq!(convert_endian(&little, meta, Endian::Big).unwrap(), big);
+                assert_eq!(convert_endian(&big, meta, Endian::Big).unwrap(), big);
+            }
+
+            let pk = json!({
+                "root": vec![7u32; meta.hash_len],
+                "parameter": vec![9u32; meta.parameter_len],
+            });
+            let mut little = Vec::new();
+            write_public_key(&mut little, &pk, meta, Repr::Canonical, Endian::Little).unwrap();
+            let big = convert_endian(&little, meta, Endian::Big).unwrap();
+            assert_eq!(big[4], FORMAT_VERSION | CANONICAL_FLAG | BIG_ENDIAN_FLAG);
+            assert_eq!(&big[HEADER_LEN..HEADER_LEN + 4], [0, 0, 0, 7]);
+            assert_eq!(read_public_key(&mut big.as_slice(), meta).unwrap(), pk);
+        }
+        assert_eq!(Endian::parse("BE").unwrap(), Endian::Big);
+        assert!(Endian::parse("middle").is_err());
+    }
+}
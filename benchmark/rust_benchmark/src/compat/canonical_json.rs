@@ -0,0 +1,69 @@
+//! Canonical JSON writer
+//!
+//! The Zig side byte-compares JSON artifacts, but serde_json only promises
+//! valid JSON: key order depends on its `preserve_order` feature and pretty
+//! printing on its version. The canonical form fixes every choice:
+//!
+//! - object keys sorted by their UTF-8 bytes,
+//! - no whitespace between tokens,
+//! - integers in plain decimal, without sign for non-negative values,
+//!   exponent or fraction,
+//! - strings escaped as serde_json escapes them.
+//!
+//! Floats have no canonical form here and are rejected; keys and signatures
+//! only contain integers.
+
+use std::error::Error;
+use std::io::Write;
+
+use serde_json::Value;
+
+/// Write `value` in canonical form.
+pub fn write<W: Write>(writer: &mut W, value: &Value) -> Result<(), Box<dyn Error>> {
+    match value {
+        Value::Null => writer.write_all(b"null")?,
+        Value::Bool(b) => writer.write_all(if *b { b"true" } else { b"false" })?,
+        Value::Number(n) => {
+            if let Some(u) = n.as_u64() {
+                write!(writer, "{u}")?;
+            } else if let Some(i) = n.as_i64() {
+                write!(writer, "{i}")?;
+            } else {
+                return Err(format!("canonical JSON has no form for non-integer {n}").into());
+            }
+        }
+        Value::String(s) => serde_json::to_writer(&mut *writer, s)?,
+        Value::Array(items) => {
+            writer.write_all(b"[")?;
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    writer.write_all(b",")?;
+                }
+                write(writer, item)?;
+            }
+            writer.write_all(b"]")?;
+        }
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.as_bytes().cmp(b.as_bytes()));
+            writer.write_all(b"{")?;
+            for (i, (key, item)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    writer.write_all(b",")?;
+                }
+                serde_json::to_writer(&mut *writer, key)?;
+                writer.write_all(b":")?;
+                write(writer, item)?;
+            }
+            writer.write_all(b"}")?;
+        }
+    }
+    Ok(())
+}
+
+/// `value` in canonical form.
+pub fn to_vec(value: &Value) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut bytes = Vec::new();
+    write(&mut bytes, value)?;
+    Ok(bytes)
+}
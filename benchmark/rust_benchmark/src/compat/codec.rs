@@ -0,0 +1,363 @@
+//! In-memory encoding of keys and signatures by `Format`
+//!
+//! The CLIs read and write these encodings through files under `tmp/`;
+//! language bindings need the same bytes without touching the filesystem.
+//!
+//! | Format       | keys                       | signatures                       |
+//! |--------------|----------------------------|----------------------------------|
+//! | `Json`       | serde JSON (pk trimmed)    | `compat::lean_wire`, 3116 bytes  |
+//! | `Ssz`        | SSZ                        | SSZ                              |
+//! | `ZigBinary`  | pk `compat::binfmt`, no sk | `compat::binfmt` layout          |
+//! | `Cbor`       | serde CBOR (pk trimmed)    | serde CBOR                       |
+//! | `Msgpack`    | MessagePack (pk trimmed)   | MessagePack                      |
+//! | `Bincode`    | pk as `Json`, sk binary    | as `Json`                        |
+//!
+//! CBOR and MessagePack carry the same field names and nesting as the JSON
+//! keys, so a consumer can map between the three without knowing the scheme.
+//! `Bincode` secret keys use the `compat::sk_binary` layout.
+//!
+//! `read_secret_key` decodes a secret key straight from a reader, so a 2^32
+//! key is never held in memory as both its encoding and its decoded form.
+//!
+//! `encode_signature_with` and `decode_signature_with` work in a caller's
+//! [`Scratch`], so a daemon or batch handling one signature after another
+//! stops allocating for the SSZ and binary encodings once the buffers have
+//! grown to a signature's size.
+
+use std::error::Error;
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use ssz::{Decode, Encode};
+
+use crate::compat::binfmt::{
+    read_public_key, write_public_key, write_signature_ssz, Endian, Repr, SignatureView,
+};
+use crate::compat::json::{
+    from_hex_field_elements, signature_from_json, signature_to_json, trim_public_key_value,
+};
+use crate::compat::json_schema::validate_public_key;
+use crate::compat::{hsz, key_envelope, lean_wire, sk_binary, ssz_codec};
+use crate::config::Format;
+use crate::lifetime::SchemeMetadata;
+
+/// Zero-pad `message` to the 32 bytes the schemes sign; longer messages
+/// are rejected rather than silently truncated.
+pub fn message_from_bytes(message: &[u8]) -> Result<[u8; 32], Box<dyn Error>> {
+    if message.len() > 32 {
+        return Err(format!(
+            "message is {} bytes, at most 32 are supported",
+            message.len()
+        )
+        .into());
+    }
+    let mut bytes = [0u8; 32];
+    bytes[..message.len()].copy_from_slice(message);
+    Ok(bytes)
+}
+
+fn unsupported(what: &str, format: Format) -> Box<dyn Error> {
+    format!("{what} cannot be encoded as {format}").into()
+}
+
+fn to_cbor<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut bytes = Vec::new();
+    ciborium::into_writer(value, &mut bytes)?;
+    Ok(bytes)
+}
+
+fn from_cbor<T: DeserializeOwned>(what: &str, bytes: &[u8]) -> Result<T, Box<dyn Error>> {
+    ciborium::from_reader(bytes)
+        .map_err(|e| format!("failed to decode {what} from CBOR: {e}").into())
+}
+
+fn to_msgpack<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, Box<dyn Error>> {
+    Ok(rmp_serde::to_vec_named(value)?)
+}
+
+fn from_msgpack<T: DeserializeOwned>(what: &str, bytes: &[u8]) -> Result<T, Box<dyn Error>> {
+    rmp_serde::from_slice(bytes)
+        .map_err(|e| format!("failed to decode {what} from MessagePack: {e}").into())
+}
+
+/// serde value of a public key, trimmed to the scheme's lengths.
+fn public_key_value<S>(pk: &S::PublicKey) -> Result<Value, Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::PublicKey: Serialize,
+{
+    let mut value = serde_json::to_value(pk)?;
+    trim_public_key_value(&mut value, S::METADATA);
+    Ok(value)
+}
+
+fn public_key_from_value<S>(mut value: Value) -> Result<S::PublicKey, Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::PublicKey: DeserializeOwned,
+{
+    validate_public_key(&value, S::METADATA).map_err(|e| format!("public key {e}"))?;
+    from_hex_field_elements(&mut value)?;
+    trim_public_key_value(&mut value, S::METADATA);
+    Ok(serde_json::from_value(value)?)
+}
+
+pub fn encode_public_key<S>(pk: &S::PublicKey, format: Format) -> Result<Vec<u8>, Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::PublicKey: Serialize + Encode,
+{
+    match format {
+        Format::Json | Format::Bincode => {
+            Ok(serde_json::to_vec_pretty(&public_key_value::<S>(pk)?)?)
+        }
+        Format::Ssz => Ok(pk.as_ssz_bytes()),
+        Format::ZigBinary => {
+            let mut bytes = Vec::new();
+            write_public_key(
+                &mut bytes,
+                &public_key_value::<S>(pk)?,
+                S::METADATA,
+                Repr::Montgomery,
+                Endian::Little,
+            )?;
+            Ok(bytes)
+        }
+        Format::Cbor => to_cbor(&public_key_value::<S>(pk)?),
+        Format::Msgpack => to_msgpack(&public_key_value::<S>(pk)?),
+    }
+}
+
+pub fn decode_public_key<S>(bytes: &[u8], format: Format) -> Result<S::PublicKey, Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::PublicKey: DeserializeOwned + Decode,
+{
+    match format {
+        Format::Json | Format::Bincode => {
+            public_key_from_value::<S>(serde_json::from_slice(bytes)?)
+        }
+        Format::Ssz => ssz_codec::decode_public_key::<S>(bytes, S::METADATA),
+        Format::ZigBinary => {
+            public_key_from_value::<S>(read_public_key(&mut &bytes[..], S::METADATA)?)
+        }
+        Format::Cbor => public_key_from_value::<S>(from_cbor("public key", bytes)?),
+        Format::Msgpack => public_key_from_value::<S>(from_msgpack("public key", bytes)?),
+    }
+}
+
+pub fn encode_secret_key<S>(sk: &S::SecretKey, format: Format) -> Result<Vec<u8>, Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::SecretKey: Serialize + Encode,
+{
+    match format {
+        Format::Json => Ok(serde_json::to_vec_pretty(sk)?),
+        Format::Ssz => Ok(sk.as_ssz_bytes()),
+        Format::ZigBinary => Err(unsupported("secret key", format)),
+        Format::Cbor => to_cbor(sk),
+        Format::Msgpack => to_msgpack(sk),
+        Format::Bincode => sk_binary::encode_secret_key::<S>(sk),
+    }
+}
+
+pub fn decode_secret_key<S>(bytes: &[u8], format: Format) -> Result<S::SecretKey, Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::SecretKey: DeserializeOwned + Decode,
+{
+    match format {
+        Format::Json => Ok(serde_json::from_slice(bytes)?),
+        Format::Ssz => S::SecretKey::from_ssz_bytes(bytes)
+            .map_err(|e| format!("failed to decode secret key from SSZ: {e:?}").into()),
+        Format::ZigBinary => Err(unsupported("secret key", format)),
+        Format::Cbor => from_cbor("secret key", bytes),
+        Format::Msgpack => from_msgpack("secret key", bytes),
+        Format::Bincode => sk_binary::decode_secret_key::<S>(bytes),
+    }
+}
+
+/// Decode a secret key from `reader` without buffering the whole encoding.
+/// SSZ needs the complete byte string and is read into memory first.
+pub fn read_secret_key<S, R>(reader: R, format: Format) -> Result<S::SecretKey, Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::SecretKey: DeserializeOwned + Decode,
+    R: Read,
+{
+    let mut reader = BufReader::new(reader);
+    match format {
+        Format::Json => serde_json::from_reader(reader)
+            .map_err(|e| format!("failed to decode secret key from JSON: {e}").into()),
+        Format::Ssz => {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes)?;
+            decode_secret_key::<S>(&bytes, format)
+        }
+        Format::ZigBinary => Err(unsupported("secret key", format)),
+        Format::Cbor => ciborium::from_reader(reader)
+            .map_err(|e| format!("failed to decode secret key from CBOR: {e}").into()),
+        Format::Msgpack => rmp_serde::from_read(reader)
+            .map_err(|e| format!("failed to decode secret key from MessagePack: {e}").into()),
+        Format::Bincode => sk_binary::read_secret_key::<S, _>(&mut reader),
+    }
+}
+
+/// `read_secret_key` from a file, decompressing `.hsz` containers on the fly.
+/// An enveloped key is decoded in the format its envelope names rather than
+/// `format`; its lifetime is left for the caller to check.
+pub fn read_secret_key_file<S, P>(path: P, format: Format) -> Result<S::SecretKey, Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::SecretKey: DeserializeOwned + Decode,
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    key_envelope::open(path)
+        .and_then(|(envelope, reader)| {
+            read_secret_key::<S, _>(reader, envelope.map_or(format, |e| e.format))
+        })
+        .map_err(|e| hsz::with_context(&path.display().to_string(), e))
+}
+
+/// Buffers kept by a caller across `encode_signature_with` and
+/// `decode_signature_with` calls.
+#[derive(Debug, Default)]
+pub struct Scratch {
+    bytes: Vec<u8>,
+    ssz: Vec<u8>,
+    words: Vec<u32>,
+}
+
+pub fn encode_signature<S>(sig: &S::Signature, format: Format) -> Result<Vec<u8>, Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::Signature: Serialize + Encode,
+{
+    match format {
+        Format::Json | Format::Bincode => {
+            lean_wire::encode_signature(&signature_to_json(sig, S::METADATA)?, S::METADATA)
+        }
+        Format::Ssz | Format::ZigBinary => {
+            let mut scratch = Scratch::default();
+            encode_signature_with::<S>(sig, format, &mut scratch)?;
+            Ok(scratch.bytes)
+        }
+        Format::Cbor => to_cbor(sig),
+        Format::Msgpack => to_msgpack(sig),
+    }
+}
+
+/// `encode_signature` into `scratch`, borrowing the encoding from it until
+/// the next call. `Ssz` and `ZigBinary` reuse its buffers (the binary
+/// encoding is converted from SSZ, without a `serde_json::Value`); the other
+/// formats allocate as `encode_signature` does.
+pub fn encode_signature_with<'a, S>(
+    sig: &S::Signature,
+    format: Format,
+    scratch: &'a mut Scratch,
+) -> Result<&'a [u8], Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::Signature: Serialize + Encode,
+{
+    let Scratch { bytes, ssz, words } = scratch;
+    bytes.clear();
+    match format {
+        Format::Ssz => sig.ssz_append(bytes),
+        Format::ZigBinary => {
+            ssz.clear();
+            sig.ssz_append(ssz);
+            write_signature_ssz(
+                bytes,
+                ssz,
+                S::METADATA,
+                Repr::Montgomery,
+                Endian::Little,
+                words,
+            )?;
+        }
+        _ => *bytes = encode_signature::<S>(sig, format)?,
+    }
+    Ok(bytes)
+}
+
+pub fn decode_signature<S>(bytes: &[u8], format: Format) -> Result<S::Signature, Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::Signature: DeserializeOwned + Decode,
+{
+    match format {
+        Format::Json | Format::Bincode => signature_from_json(
+            lean_wire::decode_signature(bytes, S::METADATA)?,
+            S::METADATA,
+        ),
+        Format::Ssz => ssz_codec::decode_signature::<S>(bytes, S::METADATA),
+        Format::ZigBinary => decode_signature_with::<S>(bytes, format, &mut Scratch::default()),
+        Format::Cbor => from_cbor("signature", bytes),
+        Format::Msgpack => from_msgpack("signature", bytes),
+    }
+}
+
+/// `decode_signature` using `scratch` for the intermediate buffers.
+/// `ZigBinary` is converted straight to SSZ in them, without a
+/// `serde_json::Value` in between; the other formats decode in place.
+pub fn decode_signature_with<S>(
+    bytes: &[u8],
+    format: Format,
+    scratch: &mut Scratch,
+) -> Result<S::Signature, Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::Signature: DeserializeOwned + Decode,
+{
+    match format {
+        Format::ZigBinary => {
+            SignatureView::parse(bytes, S::METADATA)?
+                .write_ssz_bytes(&mut scratch.ssz, &mut scratch.words);
+            ssz_codec::decode_signature::<S>(&scratch.ssz, S::METADATA)
+        }
+        _ => decode_signature::<S>(bytes, format),
+    }
+}
+
+/// Re-encode a signature from one format into another.
+pub fn convert_signature<S>(
+    bytes: &[u8],
+    from: Format,
+    to: Format,
+) -> Result<Vec<u8>, Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::Signature: Serialize + DeserializeOwned + Encode + Decode,
+{
+    encode_signature::<S>(&decode_signature::<S>(bytes, from)?, to)
+}
+
+/// Re-encode a public key from one format into another.
+pub fn convert_public_key<S>(
+    bytes: &[u8],
+    from: Format,
+    to: Format,
+) -> Result<Vec<u8>, Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::PublicKey: Serialize + DeserializeOwned + Encode + Decode,
+{
+    encode_public_key::<S>(&decode_public_key::<S>(bytes, from)?, to)
+}
+
+/// Re-encode a secret key from one format into another.
+pub fn convert_secret_key<S>(
+    bytes: &[u8],
+    from: Format,
+    to: Format,
+) -> Result<Vec<u8>, Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::SecretKey: Serialize + DeserializeOwned + Encode + Decode,
+{
+    encode_secret_key::<S>(&decode_secret_key::<S>(bytes, from)?, to)
+}
@@ -0,0 +1,163 @@
+//! Field-level differences between two keys or signatures
+//!
+//! A Rust-vs-Zig mismatch usually shows up as two files that do not compare
+//! equal, in different formats and with field elements in different forms.
+//! Decoding both into the scheme's types normalizes the format and the
+//! Montgomery/canonical question; `diff_values` then walks their serde
+//! values and names each differing leaf the way a reader would look it up,
+//! e.g. `root[3]` or `path.co_path[5][2]`.
+//!
+//! Public keys are compared with the root trimmed to `hash_len`, signatures
+//! with the trimming `compat::json` applies, so padding that only one
+//! encoding keeps is not reported.
+
+use std::error::Error;
+use std::fmt;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::compat::json::{trim_public_key_value, trim_signature_value};
+use crate::field::canonical_to_montgomery;
+use crate::lifetime::SchemeMetadata;
+
+/// One leaf that differs, with both sides' values (`Null` where one side has
+/// no such field or element).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldDiff {
+    pub location: String,
+    pub left: Value,
+    pub right: Value,
+}
+
+/// How field elements are shown in a report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ElementForm {
+    /// As serde writes them
+    #[default]
+    Canonical,
+    /// 0x-prefixed Montgomery hex, as the Zig tools print them
+    Montgomery,
+}
+
+impl ElementForm {
+    fn show(self, value: &Value) -> String {
+        match (self, value.as_u64().and_then(|n| u32::try_from(n).ok())) {
+            (ElementForm::Montgomery, Some(n)) => format!("0x{:08x}", canonical_to_montgomery(n)),
+            _ => value.to_string(),
+        }
+    }
+}
+
+impl FieldDiff {
+    pub fn describe(&self, form: ElementForm) -> String {
+        format!(
+            "{}: {} != {}",
+            self.location,
+            form.show(&self.left),
+            form.show(&self.right)
+        )
+    }
+}
+
+impl fmt::Display for FieldDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.describe(ElementForm::Canonical))
+    }
+}
+
+fn walk(location: &str, left: &Value, right: &Value, out: &mut Vec<FieldDiff>) {
+    match (left, right) {
+        (Value::Object(l), Value::Object(r)) => {
+            for (key, value) in l {
+                let child = if location.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{location}.{key}")
+                };
+                walk(&child, value, r.get(key).unwrap_or(&Value::Null), out);
+            }
+            for (key, value) in r.iter().filter(|(key, _)| !l.contains_key(*key)) {
+                out.push(FieldDiff {
+                    location: format!("{location}.{key}"),
+                    left: Value::Null,
+                    right: value.clone(),
+                });
+            }
+        }
+        (Value::Array(l), Value::Array(r)) => {
+            for i in 0..l.len().max(r.len()) {
+                walk(
+                    &format!("{location}[{i}]"),
+                    l.get(i).unwrap_or(&Value::Null),
+                    r.get(i).unwrap_or(&Value::Null),
+                    out,
+                );
+            }
+        }
+        (l, r) if l != r => out.push(FieldDiff {
+            location: location.to_string(),
+            left: l.clone(),
+            right: r.clone(),
+        }),
+        _ => {}
+    }
+}
+
+/// Differing leaves of two serde values, in document order.
+pub fn diff_values(left: &Value, right: &Value) -> Vec<FieldDiff> {
+    let mut out = Vec::new();
+    walk("", left, right, &mut out);
+    out
+}
+
+/// `public_key` as `public_key_diff` compares it, for callers that keep it
+/// around to diff against a later key.
+pub fn public_key_value<S>(public_key: &S::PublicKey) -> Result<Value, Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::PublicKey: Serialize,
+{
+    let mut value = serde_json::to_value(public_key)?;
+    trim_public_key_value(&mut value, S::METADATA);
+    Ok(value)
+}
+
+/// `signature` as `signature_diff` compares it.
+pub fn signature_value<S>(signature: &S::Signature) -> Result<Value, Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::Signature: Serialize,
+{
+    let mut value = serde_json::to_value(signature)?;
+    trim_signature_value(&mut value, S::METADATA);
+    Ok(value)
+}
+
+pub fn public_key_diff<S>(
+    left: &S::PublicKey,
+    right: &S::PublicKey,
+) -> Result<Vec<FieldDiff>, Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::PublicKey: Serialize,
+{
+    Ok(diff_values(
+        &public_key_value::<S>(left)?,
+        &public_key_value::<S>(right)?,
+    ))
+}
+
+pub fn signature_diff<S>(
+    left: &S::Signature,
+    right: &S::Signature,
+) -> Result<Vec<FieldDiff>, Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::Signature: Serialize,
+{
+    Ok(diff_values(
+        &signature_value::<S>(left)?,
+        &signature_value::<S>(right)?,
+    ))
+}
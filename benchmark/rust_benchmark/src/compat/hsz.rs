@@ -0,0 +1,179 @@
+//! zstd-compressed `.hsz` containers
+//!
+//! 2^32 secret keys and signature batches are large in the CI artifact
+//! cache. An `.hsz` file wraps any other key or signature file:
+//!
+//! ```text
+//! 4 bytes                     magic `HSZC`
+//! u8                          format version (1)
+//! 3 bytes                     reserved, zero
+//! ...                         zstd stream of the wrapped file
+//! ```
+//!
+//! The wrapped file keeps its own name, so `rust_sk.bincode.hsz` holds a
+//! binary secret key and `Format::from_key_path` looks through the `.hsz`.
+//! `open`/`read` recognise the magic whatever the file is called; `create`
+//! and `write` compress when the path ends in `.hsz`.
+
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+use crate::lifetime::LifetimeError;
+
+pub const MAGIC: [u8; 4] = *b"HSZC";
+pub const FORMAT_VERSION: u8 = 1;
+pub const HEADER_LEN: usize = 8;
+pub const EXTENSION: &str = "hsz";
+
+const LEVEL: i32 = zstd::DEFAULT_COMPRESSION_LEVEL;
+
+pub fn is_hsz_path<P: AsRef<Path>>(path: P) -> bool {
+    path.as_ref()
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case(EXTENSION))
+}
+
+/// `path` without a trailing `.hsz`.
+pub fn inner_path(path: &Path) -> PathBuf {
+    if is_hsz_path(path) {
+        path.with_extension("")
+    } else {
+        path.to_path_buf()
+    }
+}
+
+/// `path` with `.hsz` appended.
+pub fn compressed_path<P: AsRef<Path>>(path: P) -> PathBuf {
+    let mut name = path.as_ref().as_os_str().to_owned();
+    name.push(".");
+    name.push(EXTENSION);
+    PathBuf::from(name)
+}
+
+/// Whether `bytes` start with the container header.
+pub fn is_compressed(bytes: &[u8]) -> bool {
+    bytes.starts_with(&MAGIC)
+}
+
+fn check_header(header: &[u8; HEADER_LEN]) -> Result<(), Box<dyn Error>> {
+    if header[4] != FORMAT_VERSION {
+        return Err(format!(
+            "unsupported .hsz version {}, expected {FORMAT_VERSION}",
+            header[4]
+        )
+        .into());
+    }
+    Ok(())
+}
+
+fn write_header<W: Write>(writer: &mut W) -> io::Result<()> {
+    writer.write_all(&MAGIC)?;
+    writer.write_all(&[FORMAT_VERSION, 0, 0, 0])
+}
+
+pub fn compress(data: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut bytes = Vec::new();
+    write_header(&mut bytes)?;
+    zstd::stream::copy_encode(data, &mut bytes, LEVEL)?;
+    Ok(bytes)
+}
+
+/// Unwrap a container; other bytes are returned unchanged.
+pub fn decompress(bytes: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+    if !is_compressed(bytes) {
+        return Ok(bytes.to_vec());
+    }
+    let header: &[u8; HEADER_LEN] = bytes
+        .get(..HEADER_LEN)
+        .and_then(|h| h.try_into().ok())
+        .ok_or("truncated .hsz header")?;
+    check_header(header)?;
+    Ok(zstd::stream::decode_all(&bytes[HEADER_LEN..])?)
+}
+
+/// Open a file for reading, decompressing it if it is a container.
+pub fn open<P: AsRef<Path>>(path: P) -> Result<Box<dyn Read>, Box<dyn Error>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    if !is_compressed(reader.fill_buf()?) {
+        return Ok(Box::new(reader));
+    }
+    let mut header = [0u8; HEADER_LEN];
+    reader.read_exact(&mut header)?;
+    check_header(&header)?;
+    Ok(Box::new(zstd::stream::Decoder::with_buffer(reader)?))
+}
+
+pub fn read<P: AsRef<Path>>(path: P) -> Result<Vec<u8>, Box<dyn Error>> {
+    let path = path.as_ref();
+    let mut bytes = Vec::new();
+    open(path)
+        .and_then(|mut reader| Ok(reader.read_to_end(&mut bytes)?))
+        .map_err(|e| with_context(&format!("failed to read {}", path.display()), e))?;
+    Ok(bytes)
+}
+
+/// Prefix `error` with `context`. I/O and lifetime errors keep their type,
+/// so callers can still tell them apart from a file that does not decode.
+pub fn with_context(context: &str, error: Box<dyn Error>) -> Box<dyn Error> {
+    let message = format!("{context}: {error}");
+    if let Some(io) = error.downcast_ref::<io::Error>() {
+        io::Error::new(io.kind(), message).into()
+    } else if error.is::<LifetimeError>() {
+        LifetimeError(message).into()
+    } else {
+        message.into()
+    }
+}
+
+/// A file being written, compressed when its path ends in `.hsz`. Call
+/// `finish` to flush the zstd frame.
+pub enum Writer {
+    Plain(BufWriter<File>),
+    Compressed(zstd::stream::Encoder<'static, BufWriter<File>>),
+}
+
+impl Writer {
+    pub fn finish(self) -> Result<(), Box<dyn Error>> {
+        match self {
+            Writer::Plain(mut writer) => writer.flush()?,
+            Writer::Compressed(encoder) => encoder.finish()?.flush()?,
+        }
+        Ok(())
+    }
+}
+
+impl Write for Writer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Writer::Plain(writer) => writer.write(buf),
+            Writer::Compressed(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Writer::Plain(writer) => writer.flush(),
+            Writer::Compressed(encoder) => encoder.flush(),
+        }
+    }
+}
+
+pub fn create<P: AsRef<Path>>(path: P) -> Result<Writer, Box<dyn Error>> {
+    let compress = is_hsz_path(&path);
+    let mut writer = BufWriter::new(File::create(path)?);
+    if !compress {
+        return Ok(Writer::Plain(writer));
+    }
+    write_header(&mut writer)?;
+    Ok(Writer::Compressed(zstd::stream::Encoder::new(
+        writer, LEVEL,
+    )?))
+}
+
+pub fn write<P: AsRef<Path>>(path: P, data: &[u8]) -> Result<(), Box<dyn Error>> {
+    let mut writer = create(path)?;
+    writer.write_all(data)?;
+    writer.finish()
+}
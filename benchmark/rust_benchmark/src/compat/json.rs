@@ -0,0 +1,262 @@
+//! JSON shape of public keys and signatures exchanged with the Zig side
+//!
+//! serde emits the upstream structs with canonical field elements and
+//! untrimmed arrays; the Zig side expects `hash_len`/`rand_len` entries and
+//! calls the co-path `nodes`. These helpers translate between the two.
+//!
+//! Files are pretty-printed by default; `JsonStyle::Canonical` writes the
+//! byte-stable form from `compat::canonical_json` for byte comparisons.
+//! `JsonStyle::Hex` writes every field element as a `"0x…"` string of its
+//! Montgomery value, the shape the Zig side prints and parses. The readers
+//! here accept either form, and check the shape with `compat::json_schema`
+//! before converting so a malformed file is reported by JSON pointer.
+
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::Path;
+
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+use crate::compat::canonical_json;
+use crate::compat::json_schema::{validate_public_key, validate_signature};
+use crate::field::{
+    canonical_to_montgomery, canonical_to_montgomery_slice, montgomery_to_canonical,
+    montgomery_to_canonical_slice,
+};
+use crate::lifetime::LifetimeMetadata;
+
+/// How JSON artifacts are laid out on disk.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum JsonStyle {
+    /// serde_json's pretty printer
+    #[default]
+    Pretty,
+    /// Sorted keys, no whitespace, plain integers
+    Canonical,
+    /// Pretty-printed, field elements as `0x` Montgomery hex strings
+    Hex,
+}
+
+/// Serialize `value` in `style`.
+pub fn json_bytes(value: &Value, style: JsonStyle) -> Result<Vec<u8>, Box<dyn Error>> {
+    match style {
+        JsonStyle::Pretty => Ok(serde_json::to_vec_pretty(value)?),
+        JsonStyle::Canonical => canonical_json::to_vec(value),
+        JsonStyle::Hex => {
+            let mut value = value.clone();
+            to_hex_field_elements(&mut value);
+            Ok(serde_json::to_vec_pretty(&value)?)
+        }
+    }
+}
+
+/// Replace every canonical field element in `value` with the `"0x…"` hex
+/// string of its Montgomery form, as Zig's `deserializeFieldElement` expects.
+pub fn to_hex_field_elements(value: &mut Value) {
+    match value {
+        Value::Array(items) => {
+            // Arrays of field elements are converted in one bulk pass
+            match items
+                .iter()
+                .map(|item| item.as_u64().and_then(|u| u32::try_from(u).ok()))
+                .collect::<Option<Vec<u32>>>()
+            {
+                Some(mut words) if !words.is_empty() => {
+                    canonical_to_montgomery_slice(&mut words);
+                    for (item, word) in items.iter_mut().zip(words) {
+                        *item = Value::String(format!("0x{word:x}"));
+                    }
+                }
+                _ => items.iter_mut().for_each(to_hex_field_elements),
+            }
+        }
+        Value::Object(obj) => obj.values_mut().for_each(to_hex_field_elements),
+        Value::Number(n) => {
+            if let Some(canonical) = n.as_u64().and_then(|u| u32::try_from(u).ok()) {
+                *value = Value::String(format!("0x{:x}", canonical_to_montgomery(canonical)));
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Inverse of `to_hex_field_elements`; plain numbers are left as they are.
+pub fn from_hex_field_elements(value: &mut Value) -> Result<(), Box<dyn Error>> {
+    match value {
+        Value::Array(items) => {
+            // Arrays of hex elements are converted in one bulk pass
+            let hex: Option<Vec<&str>> = items
+                .iter()
+                .map(|item| item.as_str().filter(|s| hex_digits(s).is_some()))
+                .collect();
+            match hex {
+                Some(strings) if !strings.is_empty() => {
+                    let mut words = strings
+                        .iter()
+                        .map(|s| parse_hex_element(s))
+                        .collect::<Result<Vec<u32>, _>>()?;
+                    montgomery_to_canonical_slice(&mut words);
+                    for (item, word) in items.iter_mut().zip(words) {
+                        *item = Value::Number(word.into());
+                    }
+                    Ok(())
+                }
+                _ => items.iter_mut().try_for_each(from_hex_field_elements),
+            }
+        }
+        Value::Object(obj) => obj.values_mut().try_for_each(from_hex_field_elements),
+        Value::String(s) => {
+            if hex_digits(s).is_none() {
+                return Ok(());
+            }
+            let montgomery = parse_hex_element(s)?;
+            *value = Value::Number(montgomery_to_canonical(montgomery).into());
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+fn hex_digits(s: &str) -> Option<&str> {
+    s.strip_prefix("0x").or_else(|| s.strip_prefix("0X"))
+}
+
+/// A `0x`-prefixed Montgomery element.
+fn parse_hex_element(s: &str) -> Result<u32, String> {
+    u32::from_str_radix(hex_digits(s).unwrap_or(s), 16)
+        .map_err(|e| format!("invalid hex field element {s:?}: {e}"))
+}
+
+pub fn serialize_public_key_to_file<P, K>(
+    pk: &K,
+    path: P,
+    meta: LifetimeMetadata,
+    style: JsonStyle,
+) -> Result<(), Box<dyn Error>>
+where
+    P: AsRef<Path>,
+    K: Serialize,
+{
+    let mut pk_value = serde_json::to_value(pk)?;
+    trim_public_key_value(&mut pk_value, meta);
+    // JSON serialization uses canonical form (matching Rust's serde default)
+    let file = File::create(path)?;
+    let mut writer = BufWriter::new(file);
+    writer.write_all(&json_bytes(&pk_value, style)?)?;
+    writer.flush()?;
+    Ok(())
+}
+
+pub fn deserialize_public_key_from_file<P, PK>(
+    path: P,
+    meta: LifetimeMetadata,
+) -> Result<PK, Box<dyn Error>>
+where
+    P: AsRef<Path>,
+    PK: for<'de> DeserializeOwned,
+{
+    let file = File::open(path)?;
+    let reader = BufReader::new(file);
+    let mut pk_value: serde_json::Value = serde_json::from_reader(reader)?;
+    validate_public_key(&pk_value, meta).map_err(|e| format!("public key {e}"))?;
+    from_hex_field_elements(&mut pk_value)?;
+    trim_public_key_value(&mut pk_value, meta);
+    // JSON deserialization uses canonical form (matching Rust's serde default)
+    let pk = serde_json::from_value(pk_value)?;
+    Ok(pk)
+}
+
+pub fn trim_public_key_value(value: &mut Value, meta: LifetimeMetadata) {
+    if let Some(obj) = value.as_object_mut() {
+        if let Some(Value::Array(root)) = obj.get_mut("root") {
+            if root.len() > meta.hash_len {
+                root.truncate(meta.hash_len);
+            }
+        }
+    }
+}
+
+pub fn signature_to_json<S>(signature: &S, meta: LifetimeMetadata) -> Result<Value, Box<dyn Error>>
+where
+    S: Serialize,
+{
+    let mut value = serde_json::to_value(signature)?;
+    trim_signature_value(&mut value, meta);
+    if let Some(obj) = value.as_object_mut() {
+        if let Some(path_val) = obj.get_mut("path") {
+            if let Some(path_obj) = path_val.as_object_mut() {
+                if let Some(co_path) = path_obj.remove("co_path") {
+                    path_obj.insert("nodes".to_string(), co_path);
+                }
+            }
+        }
+    }
+    // JSON serialization uses canonical form (matching Rust's serde default)
+    Ok(value)
+}
+
+/// `signature_to_json`, serialized in `style`.
+pub fn signature_to_json_bytes<S>(
+    signature: &S,
+    meta: LifetimeMetadata,
+    style: JsonStyle,
+) -> Result<Vec<u8>, Box<dyn Error>>
+where
+    S: Serialize,
+{
+    json_bytes(&signature_to_json(signature, meta)?, style)
+}
+
+pub fn signature_from_json<S>(mut value: Value, meta: LifetimeMetadata) -> Result<S, Box<dyn Error>>
+where
+    S: for<'de> DeserializeOwned,
+{
+    validate_signature(&value, meta).map_err(|e| format!("signature {e}"))?;
+    from_hex_field_elements(&mut value)?;
+    trim_signature_value(&mut value, meta);
+    if let Some(obj) = value.as_object_mut() {
+        if let Some(path_val) = obj.get_mut("path") {
+            if let Some(path_obj) = path_val.as_object_mut() {
+                if let Some(nodes) = path_obj.remove("nodes") {
+                    path_obj.insert("co_path".to_string(), nodes);
+                }
+            }
+        }
+    }
+    // JSON deserialization uses canonical form (matching Rust's serde default)
+    Ok(serde_json::from_value(value)?)
+}
+
+pub fn trim_signature_value(value: &mut Value, meta: LifetimeMetadata) {
+    if let Some(obj) = value.as_object_mut() {
+        if let Some(path_val) = obj.get_mut("path") {
+            if let Some(path_obj) = path_val.as_object_mut() {
+                if let Some(Value::Array(nodes)) = path_obj.get_mut("nodes") {
+                    for node in nodes.iter_mut() {
+                        if let Value::Array(ref mut node_arr) = node {
+                            if node_arr.len() > meta.hash_len {
+                                node_arr.truncate(meta.hash_len);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        if let Some(Value::Array(hashes)) = obj.get_mut("hashes") {
+            for domain in hashes.iter_mut() {
+                if let Value::Array(ref mut arr) = domain {
+                    if arr.len() > meta.hash_len {
+                        arr.truncate(meta.hash_len);
+                    }
+                }
+            }
+        }
+        if let Some(Value::Array(rho)) = obj.get_mut("rho") {
+            if rho.len() > meta.rand_len {
+                rho.truncate(meta.rand_len);
+            }
+        }
+    }
+}
@@ -0,0 +1,272 @@
+//! Shape checks for JSON public keys and signatures
+//!
+//! A key or signature with one element too few otherwise surfaces as a serde
+//! error from deep inside the upstream types, after `compat::json` has
+//! renamed and trimmed the arrays. These checks run on the value as read and
+//! name the offending JSON pointer with the expected and actual lengths:
+//!
+//! ```text
+//! /root                 at least hash_len elements (extra ones are trimmed)
+//! /parameter            parameter_len elements
+//! /rho                  at least rand_len elements
+//! /path/nodes           log_lifetime nodes (`co_path` in serde's naming)
+//! /path/nodes/i         at least hash_len elements
+//! /hashes               dimension hashes
+//! /hashes/i             at least hash_len elements
+//! ```
+//!
+//! Every element must be a canonical field element, either a number or a
+//! `"0x…"` Montgomery hex string (`JsonStyle::Hex`).
+
+use std::error::Error;
+use std::fmt;
+
+use serde_json::Value;
+
+use crate::field::KOALABEAR_PRIME;
+use crate::lifetime::LifetimeMetadata;
+
+/// Where a JSON artifact departs from the expected shape.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaError {
+    /// RFC 6901 pointer to the offending value
+    pub pointer: String,
+    pub message: String,
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pointer = if self.pointer.is_empty() {
+            "/"
+        } else {
+            &self.pointer
+        };
+        write!(f, "{pointer}: {}", self.message)
+    }
+}
+
+impl Error for SchemaError {}
+
+fn fail<T>(pointer: &str, message: String) -> Result<T, SchemaError> {
+    Err(SchemaError {
+        pointer: pointer.to_string(),
+        message,
+    })
+}
+
+/// How many elements an array may hold.
+#[derive(Debug, Clone, Copy)]
+enum Len {
+    Exactly(usize),
+    /// Longer arrays are trimmed by `compat::json`
+    AtLeast(usize),
+}
+
+fn get<'a>(value: &'a Value, pointer: &str, name: &str) -> Result<&'a Value, SchemaError> {
+    if !value.is_object() {
+        return fail(
+            pointer,
+            format!("expected an object, found {}", kind(value)),
+        );
+    }
+    match value.get(name) {
+        Some(field) => Ok(field),
+        None => fail(&format!("{pointer}/{name}"), "missing".to_string()),
+    }
+}
+
+fn kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+fn array<'a>(value: &'a Value, pointer: &str, len: Len) -> Result<&'a [Value], SchemaError> {
+    let Some(items) = value.as_array() else {
+        return fail(pointer, format!("expected an array, found {}", kind(value)));
+    };
+    match len {
+        Len::Exactly(n) if items.len() != n => fail(
+            pointer,
+            format!("expected {n} elements, found {}", items.len()),
+        ),
+        Len::AtLeast(n) if items.len() < n => fail(
+            pointer,
+            format!("expected at least {n} elements, found {}", items.len()),
+        ),
+        _ => Ok(items),
+    }
+}
+
+fn field_element(value: &Value, pointer: &str) -> Result<(), SchemaError> {
+    let raw = match value {
+        Value::Number(n) => n.as_u64(),
+        Value::String(s) => s
+            .strip_prefix("0x")
+            .or_else(|| s.strip_prefix("0X"))
+            .and_then(|digits| u64::from_str_radix(digits, 16).ok()),
+        _ => None,
+    };
+    match raw {
+        Some(n) if n < KOALABEAR_PRIME => Ok(()),
+        Some(n) => fail(
+            pointer,
+            format!("{n} is not below the field modulus {KOALABEAR_PRIME}"),
+        ),
+        None => fail(
+            pointer,
+            format!("expected a field element (number or 0x hex), found {value}"),
+        ),
+    }
+}
+
+fn field_elements(value: &Value, pointer: &str, len: Len) -> Result<(), SchemaError> {
+    for (i, item) in array(value, pointer, len)?.iter().enumerate() {
+        field_element(item, &format!("{pointer}/{i}"))?;
+    }
+    Ok(())
+}
+
+fn domains(value: &Value, pointer: &str, count: usize, hash_len: usize) -> Result<(), SchemaError> {
+    for (i, domain) in array(value, pointer, Len::Exactly(count))?
+        .iter()
+        .enumerate()
+    {
+        field_elements(domain, &format!("{pointer}/{i}"), Len::AtLeast(hash_len))?;
+    }
+    Ok(())
+}
+
+/// Check a public key value (`root`, `parameter`) against `meta`.
+pub fn validate_public_key(value: &Value, meta: LifetimeMetadata) -> Result<(), SchemaError> {
+    field_elements(
+        get(value, "", "root")?,
+        "/root",
+        Len::AtLeast(meta.hash_len),
+    )?;
+    field_elements(
+        get(value, "", "parameter")?,
+        "/parameter",
+        Len::Exactly(meta.parameter_len),
+    )
+}
+
+/// Check a signature value (`path.nodes` or `path.co_path`, `rho`,
+/// `hashes`) against `meta`.
+pub fn validate_signature(value: &Value, meta: LifetimeMetadata) -> Result<(), SchemaError> {
+    let path = get(value, "", "path")?;
+    let name = if path.get("co_path").is_some() {
+        "co_path"
+    } else {
+        "nodes"
+    };
+    domains(
+        get(path, "/path", name)?,
+        &format!("/path/{name}"),
+        meta.log_lifetime as usize,
+        meta.hash_len,
+    )?;
+    field_elements(get(value, "", "rho")?, "/rho", Len::AtLeast(meta.rand_len))?;
+    domains(
+        get(value, "", "hashes")?,
+        "/hashes",
+        meta.dimension,
+        meta.hash_len,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lifetime::LifetimeTag;
+    use serde_json::json;
+
+    fn signature(meta: LifetimeMetadata) -> Value {
+        let domain = |_: usize| vec![1u32; meta.hash_len];
+        json!({
+            "path": { "nodes": (0..meta.log_lifetime as usize).map(domain).collect::<Vec<_>>() },
+            "rho": vec![2u32; meta.rand_len],
+            "hashes": (0..meta.dimension).map(domain).collect::<Vec<_>>(),
+        })
+    }
+
+    fn public_key(meta: LifetimeMetadata) -> Value {
+        json!({ "root": vec![3u32; meta.hash_len], "parameter": vec![4u32; meta.parameter_len] })
+    }
+
+    #[test]
+    fn accepts_well_formed_values() {
+        for tag in LifetimeTag::ALL {
+            let meta = tag.metadata();
+            validate_signature(&signature(meta), meta).unwrap();
+            validate_public_key(&public_key(meta), meta).unwrap();
+
+            // serde's naming, hex elements and untrimmed arrays
+            let mut sig = signature(meta);
+            let nodes = sig["path"]
+                .as_object_mut()
+                .unwrap()
+                .remove("nodes")
+                .unwrap();
+            sig["path"]["co_path"] = nodes;
+            sig["rho"] = json!(vec!["0x1"; meta.rand_len + 1]);
+            validate_signature(&sig, meta).unwrap();
+        }
+    }
+
+    #[test]
+    fn reports_pointer_and_lengths() {
+        let meta = LifetimeTag::Pow18.metadata();
+
+        let mut sig = signature(meta);
+        sig["hashes"][17].as_array_mut().unwrap().pop();
+        let err = validate_signature(&sig, meta).unwrap_err();
+        assert_eq!(err.pointer, "/hashes/17");
+        assert_eq!(
+            err.to_string(),
+            "/hashes/17: expected at least 7 elements, found 6"
+        );
+
+        let mut sig = signature(meta);
+        sig["path"]["nodes"].as_array_mut().unwrap().pop();
+        let err = validate_signature(&sig, meta).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "/path/nodes: expected 18 elements, found 17"
+        );
+
+        let mut pk = public_key(meta);
+        pk["parameter"].as_array_mut().unwrap().push(json!(0));
+        let err = validate_public_key(&pk, meta).unwrap_err();
+        assert_eq!(err.to_string(), "/parameter: expected 5 elements, found 6");
+
+        let mut pk = public_key(meta);
+        pk.as_object_mut().unwrap().remove("root");
+        assert_eq!(validate_public_key(&pk, meta).unwrap_err().pointer, "/root");
+    }
+
+    #[test]
+    fn rejects_bad_elements() {
+        let meta = LifetimeTag::Pow8.metadata();
+        let mut sig = signature(meta);
+        sig["rho"][3] = json!(KOALABEAR_PRIME);
+        assert_eq!(
+            validate_signature(&sig, meta).unwrap_err().pointer,
+            "/rho/3"
+        );
+
+        sig["rho"][3] = json!("12");
+        assert_eq!(
+            validate_signature(&sig, meta).unwrap_err().pointer,
+            "/rho/3"
+        );
+
+        let err = validate_public_key(&json!([]), meta).unwrap_err();
+        assert_eq!(err.to_string(), "/: expected an object, found an array");
+    }
+}
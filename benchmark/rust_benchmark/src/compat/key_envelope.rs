@@ -0,0 +1,207 @@
+//! Scheme identity stored in front of a secret key
+//!
+//! A key file copied out of its store used to lose the `_lifetime.txt` next
+//! to it, and was then decoded for whatever lifetime the new directory
+//! claimed. An enveloped key names its own scheme:
+//!
+//! ```text
+//! 4 bytes                     magic `HSKE`
+//! u8                          envelope version (1)
+//! u8                          log lifetime
+//! u8                          format: 0 json, 1 ssz, 2 cbor, 3 msgpack, 4 bincode
+//! u8                          reserved, zero
+//! u16 little-endian           dimension
+//! u16 little-endian           base
+//! 4 bytes                     reserved, zero
+//! ...                         the key in that format
+//! ```
+//!
+//! `FileKeyStore` envelopes the secret keys it writes. Public keys stay bare
+//! because the Zig tools read them as they are. The envelope sits inside an
+//! `.hsz` container, so `open` and `read` look through both; bytes without
+//! the magic are returned unchanged with no envelope.
+
+use std::error::Error;
+use std::io::{Cursor, Read};
+use std::path::Path;
+
+use crate::compat::hsz;
+use crate::config::Format;
+use crate::lifetime::{LifetimeError, LifetimeTag};
+
+pub const MAGIC: [u8; 4] = *b"HSKE";
+pub const FORMAT_VERSION: u8 = 1;
+pub const HEADER_LEN: usize = 16;
+
+fn format_code(format: Format) -> Result<u8, Box<dyn Error>> {
+    match format {
+        Format::Json => Ok(0),
+        Format::Ssz => Ok(1),
+        Format::Cbor => Ok(2),
+        Format::Msgpack => Ok(3),
+        Format::Bincode => Ok(4),
+        Format::ZigBinary => Err("keys cannot be stored in the Zig binary layout".into()),
+    }
+}
+
+fn format_of(code: u8) -> Result<Format, Box<dyn Error>> {
+    match code {
+        0 => Ok(Format::Json),
+        1 => Ok(Format::Ssz),
+        2 => Ok(Format::Cbor),
+        3 => Ok(Format::Msgpack),
+        4 => Ok(Format::Bincode),
+        other => Err(format!("unknown key format code {other} in envelope").into()),
+    }
+}
+
+/// Which scheme and encoding an enveloped key is for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyEnvelope {
+    pub lifetime: LifetimeTag,
+    pub dimension: u16,
+    pub base: u16,
+    pub format: Format,
+}
+
+impl KeyEnvelope {
+    /// Envelope for a `lifetime` key in `format`, with dimension and base
+    /// from the scheme registry.
+    pub fn new(lifetime: LifetimeTag, format: Format) -> Result<Self, Box<dyn Error>> {
+        format_code(format)?;
+        let metadata = lifetime.metadata();
+        Ok(KeyEnvelope {
+            lifetime,
+            dimension: metadata.dimension as u16,
+            base: metadata.base as u16,
+            format,
+        })
+    }
+
+    pub fn to_header(&self) -> [u8; HEADER_LEN] {
+        let mut header = [0u8; HEADER_LEN];
+        header[..4].copy_from_slice(&MAGIC);
+        header[4] = FORMAT_VERSION;
+        header[5] = self.lifetime.log_lifetime() as u8;
+        header[6] = format_code(self.format).expect("checked by KeyEnvelope::new");
+        header[8..10].copy_from_slice(&self.dimension.to_le_bytes());
+        header[10..12].copy_from_slice(&self.base.to_le_bytes());
+        header
+    }
+
+    /// Parse a header, rejecting lifetimes this build has no scheme for and
+    /// a dimension or base that differs from the registered scheme's.
+    pub fn from_header(header: &[u8; HEADER_LEN]) -> Result<Self, Box<dyn Error>> {
+        if header[..4] != MAGIC {
+            return Err("not a key envelope".into());
+        }
+        if header[4] != FORMAT_VERSION {
+            return Err(format!(
+                "unsupported key envelope version {}, expected {FORMAT_VERSION}",
+                header[4]
+            )
+            .into());
+        }
+        let lifetime = LifetimeTag::ALL
+            .into_iter()
+            .find(|tag| tag.log_lifetime() == u32::from(header[5]))
+            .ok_or_else(|| {
+                LifetimeError(format!(
+                    "key envelope names unsupported lifetime 2^{}",
+                    header[5]
+                ))
+            })?;
+        let envelope = KeyEnvelope {
+            lifetime,
+            dimension: u16::from_le_bytes([header[8], header[9]]),
+            base: u16::from_le_bytes([header[10], header[11]]),
+            format: format_of(header[6])?,
+        };
+        let expected = KeyEnvelope::new(lifetime, envelope.format)?;
+        if (envelope.dimension, envelope.base) != (expected.dimension, expected.base) {
+            return Err(LifetimeError(format!(
+                "key envelope names lifetime {lifetime} with dimension {} and base {}, but that scheme has dimension {} and base {}",
+                envelope.dimension, envelope.base, expected.dimension, expected.base
+            ))
+            .into());
+        }
+        Ok(envelope)
+    }
+
+    /// Error unless the key is for `lifetime`.
+    pub fn check_lifetime(&self, lifetime: LifetimeTag) -> Result<(), Box<dyn Error>> {
+        if self.lifetime == lifetime {
+            Ok(())
+        } else {
+            Err(LifetimeError(format!(
+                "key is for lifetime {}, not {lifetime}",
+                self.lifetime
+            ))
+            .into())
+        }
+    }
+}
+
+/// Whether `bytes` start with an envelope header.
+pub fn is_enveloped(bytes: &[u8]) -> bool {
+    bytes.starts_with(&MAGIC)
+}
+
+/// `key` with `envelope`'s header in front.
+pub fn wrap(envelope: &KeyEnvelope, key: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(HEADER_LEN + key.len());
+    bytes.extend_from_slice(&envelope.to_header());
+    bytes.extend_from_slice(key);
+    bytes
+}
+
+/// Split off the envelope, if there is one.
+pub fn unwrap(bytes: &[u8]) -> Result<(Option<KeyEnvelope>, &[u8]), Box<dyn Error>> {
+    if !is_enveloped(bytes) {
+        return Ok((None, bytes));
+    }
+    let header: &[u8; HEADER_LEN] = bytes
+        .get(..HEADER_LEN)
+        .and_then(|h| h.try_into().ok())
+        .ok_or("truncated key envelope")?;
+    Ok((
+        Some(KeyEnvelope::from_header(header)?),
+        &bytes[HEADER_LEN..],
+    ))
+}
+
+/// Open a key file, decompressing it if needed, and read its envelope. The
+/// reader starts at the key itself.
+pub fn open<P: AsRef<Path>>(
+    path: P,
+) -> Result<(Option<KeyEnvelope>, Box<dyn Read>), Box<dyn Error>> {
+    let mut reader = hsz::open(path)?;
+    // A zstd decoder may hand out fewer bytes per read than asked for
+    let mut prefix = Vec::with_capacity(HEADER_LEN);
+    (&mut reader)
+        .take(HEADER_LEN as u64)
+        .read_to_end(&mut prefix)?;
+    if is_enveloped(&prefix) && prefix.len() == HEADER_LEN {
+        let header: &[u8; HEADER_LEN] = prefix.as_slice().try_into().expect("length checked");
+        return Ok((Some(KeyEnvelope::from_header(header)?), reader));
+    }
+    if is_enveloped(&prefix) {
+        return Err("truncated key envelope".into());
+    }
+    Ok((None, Box::new(Cursor::new(prefix).chain(reader))))
+}
+
+/// The envelope of a key file, without reading the key.
+pub fn read_header<P: AsRef<Path>>(path: P) -> Result<Option<KeyEnvelope>, Box<dyn Error>> {
+    let path = path.as_ref();
+    open(path)
+        .map(|(envelope, _)| envelope)
+        .map_err(|e| hsz::with_context(&format!("failed to read {}", path.display()), e))
+}
+
+/// A whole key file, decompressed, with its envelope split off.
+pub fn read<P: AsRef<Path>>(path: P) -> Result<(Option<KeyEnvelope>, Vec<u8>), Box<dyn Error>> {
+    let bytes = hsz::read(path)?;
+    let (envelope, key) = unwrap(&bytes)?;
+    Ok((envelope, key.to_vec()))
+}
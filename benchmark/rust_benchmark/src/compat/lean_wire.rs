@@ -0,0 +1,354 @@
+//! The fixed 3116-byte leanSignature wire format
+//!
+//! leanSpec carries signatures as `Bytes3116`: the SSZ encoding of the
+//! signature container followed by zero padding. Every lifetime has a fixed
+//! co-path depth and encoding dimension, so every field sits at an offset
+//! known in advance:
+//!
+//! ```text
+//! offset                 field
+//! 0                      offset(path) u32 = 8 + 4 * rand_len
+//! 4                      rho: rand_len x u32
+//! 4 + 4 * rand_len       offset(hashes) u32
+//! 8 + 4 * rand_len       path: offset(siblings) u32 = 4
+//!                        | log_lifetime x hash_len x u32 co-path nodes
+//! offset(hashes)         hashes: dimension x hash_len x u32
+//! end                    zero padding up to 3116 bytes
+//! ```
+//!
+//! Field elements are little-endian canonical u32s. The layout is written
+//! field by field instead of zero-padding a bincode dump, so it does not move
+//! when bincode or the upstream field order does. Decoding is strict: the
+//! input must be exactly 3116 bytes, every offset must be the one above, every
+//! value must be below the field modulus and the padding must be zero.
+//!
+//! Values are in the serde JSON shape of `compat::binfmt` (`path.nodes`,
+//! `rho`, `hashes`), for `signature_to_json` and `signature_from_json`.
+
+use std::error::Error;
+
+use serde_json::Value;
+
+use crate::field::KOALABEAR_PRIME;
+use crate::lifetime::LifetimeMetadata;
+
+/// Size of every signature on the wire.
+pub const SIGNATURE_LEN: usize = 3116;
+
+const FIELD_ELEMENT_BYTES: usize = 4;
+const OFFSET_BYTES: usize = 4;
+
+/// Byte offsets of each field for one lifetime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Layout {
+    pub rho: usize,
+    pub hashes_offset: usize,
+    pub path: usize,
+    pub nodes: usize,
+    pub hashes: usize,
+    /// End of the encoded signature; zero padding follows.
+    pub end: usize,
+}
+
+impl Layout {
+    pub fn of(meta: LifetimeMetadata) -> Self {
+        let node_bytes = meta.hash_len * FIELD_ELEMENT_BYTES;
+        let rho = OFFSET_BYTES;
+        let hashes_offset = rho + meta.rand_len * FIELD_ELEMENT_BYTES;
+        let path = hashes_offset + OFFSET_BYTES;
+        let nodes = path + OFFSET_BYTES;
+        let hashes = nodes + meta.log_lifetime as usize * node_bytes;
+        let end = hashes + meta.dimension * node_bytes;
+        Layout {
+            rho,
+            hashes_offset,
+            path,
+            nodes,
+            hashes,
+            end,
+        }
+    }
+}
+
+fn put_u32(out: &mut [u8], at: usize, value: u32) {
+    out[at..at + 4].copy_from_slice(&value.to_le_bytes());
+}
+
+fn get_u32(bytes: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+}
+
+fn put_offset(out: &mut [u8], at: usize, value: usize) -> Result<(), Box<dyn Error>> {
+    put_u32(out, at, u32::try_from(value)?);
+    Ok(())
+}
+
+fn put_field_elements(
+    out: &mut [u8],
+    at: usize,
+    value: &Value,
+    len: usize,
+    what: &str,
+) -> Result<(), Box<dyn Error>> {
+    let entries = value
+        .as_array()
+        .ok_or_else(|| format!("{what} is not an array"))?;
+    if entries.len() < len {
+        return Err(format!("{what} has {} elements, expected {len}", entries.len()).into());
+    }
+    for (i, entry) in entries[..len].iter().enumerate() {
+        let fe = entry
+            .as_u64()
+            .filter(|&n| n < KOALABEAR_PRIME)
+            .ok_or_else(|| format!("{what} holds {entry}, not a canonical field element"))?;
+        put_u32(out, at + i * FIELD_ELEMENT_BYTES, fe as u32);
+    }
+    Ok(())
+}
+
+fn put_domains(
+    out: &mut [u8],
+    at: usize,
+    value: Option<&Value>,
+    count: usize,
+    meta: LifetimeMetadata,
+    what: &str,
+) -> Result<(), Box<dyn Error>> {
+    let domains = value
+        .and_then(Value::as_array)
+        .ok_or_else(|| format!("signature JSON missing {what} array"))?;
+    if domains.len() != count {
+        return Err(format!(
+            "signature has {} {what}, the lifetime 2^{} wire format needs {count}",
+            domains.len(),
+            meta.log_lifetime
+        )
+        .into());
+    }
+    let node_bytes = meta.hash_len * FIELD_ELEMENT_BYTES;
+    for (i, domain) in domains.iter().enumerate() {
+        put_field_elements(out, at + i * node_bytes, domain, meta.hash_len, what)?;
+    }
+    Ok(())
+}
+
+/// Encode a signature JSON value (as produced by `signature_to_json`).
+pub fn encode_signature(value: &Value, meta: LifetimeMetadata) -> Result<Vec<u8>, Box<dyn Error>> {
+    let layout = Layout::of(meta);
+    if layout.end > SIGNATURE_LEN {
+        return Err(format!(
+            "lifetime 2^{} signatures take {} bytes, more than {SIGNATURE_LEN}",
+            meta.log_lifetime, layout.end
+        )
+        .into());
+    }
+    let mut out = vec![0u8; SIGNATURE_LEN];
+    put_offset(&mut out, 0, layout.path)?;
+    let rho = value.get("rho").ok_or("signature JSON missing rho array")?;
+    put_field_elements(&mut out, layout.rho, rho, meta.rand_len, "rho")?;
+    put_offset(&mut out, layout.hashes_offset, layout.hashes)?;
+    put_offset(&mut out, layout.path, OFFSET_BYTES)?;
+    let nodes = value.get("path").and_then(|p| p.get("nodes"));
+    put_domains(
+        &mut out,
+        layout.nodes,
+        nodes,
+        meta.log_lifetime as usize,
+        meta,
+        "path nodes",
+    )?;
+    put_domains(
+        &mut out,
+        layout.hashes,
+        value.get("hashes"),
+        meta.dimension,
+        meta,
+        "hashes",
+    )?;
+    Ok(out)
+}
+
+fn check_offset(
+    bytes: &[u8],
+    at: usize,
+    expected: usize,
+    what: &str,
+) -> Result<(), Box<dyn Error>> {
+    let found = get_u32(bytes, at) as usize;
+    if found != expected {
+        return Err(format!("{what} offset is {found}, expected {expected}").into());
+    }
+    Ok(())
+}
+
+fn field_elements(
+    bytes: &[u8],
+    at: usize,
+    len: usize,
+    what: &str,
+) -> Result<Value, Box<dyn Error>> {
+    (0..len)
+        .map(|i| {
+            let offset = at + i * FIELD_ELEMENT_BYTES;
+            let fe = get_u32(bytes, offset);
+            if u64::from(fe) >= KOALABEAR_PRIME {
+                return Err(format!(
+                    "{what} value {fe:#x} at byte {offset} is not a canonical field element"
+                )
+                .into());
+            }
+            Ok(Value::from(fe))
+        })
+        .collect::<Result<Vec<_>, Box<dyn Error>>>()
+        .map(Value::Array)
+}
+
+fn domains(
+    bytes: &[u8],
+    at: usize,
+    count: usize,
+    meta: LifetimeMetadata,
+    what: &str,
+) -> Result<Value, Box<dyn Error>> {
+    let node_bytes = meta.hash_len * FIELD_ELEMENT_BYTES;
+    (0..count)
+        .map(|i| field_elements(bytes, at + i * node_bytes, meta.hash_len, what))
+        .collect::<Result<Vec<_>, _>>()
+        .map(Value::Array)
+}
+
+/// Decode a wire signature into its serde JSON shape with canonical values.
+pub fn decode_signature(bytes: &[u8], meta: LifetimeMetadata) -> Result<Value, Box<dyn Error>> {
+    if bytes.len() != SIGNATURE_LEN {
+        return Err(format!(
+            "wire signature is {} bytes, expected exactly {SIGNATURE_LEN}",
+            bytes.len()
+        )
+        .into());
+    }
+    let layout = Layout::of(meta);
+    if layout.end > SIGNATURE_LEN {
+        return Err(format!(
+            "lifetime 2^{} signatures take {} bytes, more than {SIGNATURE_LEN}",
+            meta.log_lifetime, layout.end
+        )
+        .into());
+    }
+    check_offset(bytes, 0, layout.path, "path")?;
+    check_offset(bytes, layout.hashes_offset, layout.hashes, "hashes")?;
+    check_offset(bytes, layout.path, OFFSET_BYTES, "path siblings")?;
+    if let Some(i) = bytes[layout.end..].iter().position(|&b| b != 0) {
+        return Err(format!(
+            "non-zero padding at byte {} (signature for lifetime 2^{} ends at {})",
+            layout.end + i,
+            meta.log_lifetime,
+            layout.end
+        )
+        .into());
+    }
+
+    let mut path_obj = serde_json::Map::new();
+    path_obj.insert(
+        "nodes".to_string(),
+        domains(
+            bytes,
+            layout.nodes,
+            meta.log_lifetime as usize,
+            meta,
+            "path node",
+        )?,
+    );
+
+    let mut sig_obj = serde_json::Map::new();
+    sig_obj.insert("path".to_string(), Value::Object(path_obj));
+    sig_obj.insert(
+        "rho".to_string(),
+        field_elements(bytes, layout.rho, meta.rand_len, "rho")?,
+    );
+    sig_obj.insert(
+        "hashes".to_string(),
+        domains(bytes, layout.hashes, meta.dimension, meta, "hash")?,
+    );
+    Ok(Value::Object(sig_obj))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lifetime::LifetimeTag;
+
+    fn sample_signature(meta: LifetimeMetadata) -> Value {
+        let element = |i: usize| Value::from((i as u64 * 7919 + 1) % KOALABEAR_PRIME);
+        let domain =
+            |base: usize| Value::Array((0..meta.hash_len).map(|j| element(base + j)).collect());
+        serde_json::json!({
+            "path": {
+                "nodes": (0..meta.log_lifetime as usize).map(|i| domain(i * 100)).collect::<Vec<_>>(),
+            },
+            "rho": (0..meta.rand_len).map(|i| element(10_000 + i)).collect::<Vec<_>>(),
+            "hashes": (0..meta.dimension).map(|i| domain(20_000 + i * 100)).collect::<Vec<_>>(),
+        })
+    }
+
+    #[test]
+    fn round_trips_every_lifetime_at_fixed_offsets() {
+        for tag in LifetimeTag::ALL {
+            let meta = tag.metadata();
+            let layout = Layout::of(meta);
+            let value = sample_signature(meta);
+            let bytes = encode_signature(&value, meta).unwrap();
+            assert_eq!(bytes.len(), SIGNATURE_LEN);
+            assert_eq!(get_u32(&bytes, 0) as usize, layout.path, "{tag}");
+            assert_eq!(get_u32(&bytes, layout.rho), 10_000 * 7919 + 1, "{tag}");
+            assert_eq!(get_u32(&bytes, layout.hashes), 20_000 * 7919 + 1, "{tag}");
+            assert_eq!(decode_signature(&bytes, meta).unwrap(), value, "{tag}");
+        }
+    }
+
+    #[test]
+    fn lifetime_2_32_layout() {
+        let layout = Layout::of(LifetimeTag::Pow32.metadata());
+        assert_eq!(
+            (layout.path, layout.nodes, layout.hashes, layout.end),
+            (36, 40, 1064, 3112)
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        let meta = LifetimeTag::Pow8.metadata();
+        let layout = Layout::of(meta);
+        let bytes = encode_signature(&sample_signature(meta), meta).unwrap();
+
+        assert!(decode_signature(&bytes[..SIGNATURE_LEN - 1], meta).is_err());
+        let mut longer = bytes.clone();
+        longer.push(0);
+        assert!(decode_signature(&longer, meta).is_err());
+
+        let mut bad = bytes.clone();
+        bad[layout.end] = 1;
+        assert!(decode_signature(&bad, meta)
+            .unwrap_err()
+            .to_string()
+            .contains("padding"));
+
+        let mut bad = bytes.clone();
+        put_u32(&mut bad, layout.hashes_offset, layout.hashes as u32 + 4);
+        assert!(decode_signature(&bad, meta).is_err());
+
+        let mut bad = bytes.clone();
+        put_u32(&mut bad, layout.rho, KOALABEAR_PRIME as u32);
+        assert!(decode_signature(&bad, meta).is_err());
+
+        // A 2^8 signature is not a 2^32 one
+        assert!(decode_signature(&bytes, LifetimeTag::Pow32.metadata()).is_err());
+    }
+
+    #[test]
+    fn encode_requires_exact_counts() {
+        let meta = LifetimeTag::Pow8.metadata();
+        let mut value = sample_signature(meta);
+        value["hashes"].as_array_mut().unwrap().pop();
+        assert!(encode_signature(&value, meta).is_err());
+    }
+}
@@ -0,0 +1,22 @@
+//! Interchange formats shared with the Zig implementation
+
+#[cfg(feature = "async-io")]
+pub mod async_codec;
+pub mod binfmt;
+pub mod canonical_json;
+pub mod codec;
+pub mod field_diff;
+pub mod hsz;
+pub mod json;
+pub mod json_schema;
+pub mod key_envelope;
+pub mod lean_wire;
+pub mod multihash;
+pub mod pem;
+pub mod pk_info;
+#[cfg(feature = "protobuf")]
+pub mod protobuf;
+pub mod sk_binary;
+pub mod ssz_codec;
+pub mod ssz_root;
+pub mod text_encoding;
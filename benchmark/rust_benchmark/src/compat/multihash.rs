@@ -0,0 +1,294 @@
+//! Multihash/multibase form of public key fingerprints
+//!
+//! Content-addressed stores name keys by self-describing digests rather than
+//! bare hex. A fingerprint (see `compat::pk_info`) is wrapped as a multihash,
+//! the varint code for sha3-256 (0x16) and the digest length (32) followed by
+//! the digest, and then written in one of the multibase alphabets with its
+//! one-character prefix, e.g. `z` and base58btc.
+//!
+//! Only sha3-256 digests are accepted when parsing: anything else cannot be a
+//! fingerprint this crate produced.
+
+use std::error::Error;
+use std::fmt;
+
+use base64::Engine;
+
+/// Multihash code of sha3-256.
+pub const SHA3_256_CODE: u8 = 0x16;
+/// Digest length of sha3-256.
+pub const SHA3_256_LEN: u8 = 32;
+
+const BASE32_ALPHABET: &[u8; 32] = b"abcdefghijklmnopqrstuvwxyz234567";
+const BASE58_ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+const BASE64URL: base64::engine::GeneralPurpose = base64::engine::general_purpose::URL_SAFE_NO_PAD;
+
+/// Multibase alphabets a digest can be written in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Multibase {
+    /// `f`, lowercase hex
+    Base16,
+    /// `b`, lowercase RFC 4648 base32 without padding
+    Base32,
+    /// `z`, the Bitcoin base58 alphabet
+    #[default]
+    Base58Btc,
+    /// `u`, URL-safe base64 without padding
+    Base64Url,
+}
+
+impl Multibase {
+    pub const ALL: [Multibase; 4] = [
+        Multibase::Base16,
+        Multibase::Base32,
+        Multibase::Base58Btc,
+        Multibase::Base64Url,
+    ];
+
+    pub fn parse(raw: &str) -> Result<Self, Box<dyn Error>> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "base16" | "hex" => Ok(Self::Base16),
+            "base32" => Ok(Self::Base32),
+            "base58btc" | "base58" => Ok(Self::Base58Btc),
+            "base64url" => Ok(Self::Base64Url),
+            other => Err(format!(
+                "unsupported multibase '{other}'. Must be one of: base16, base32, base58btc, base64url"
+            )
+            .into()),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Multibase::Base16 => "base16",
+            Multibase::Base32 => "base32",
+            Multibase::Base58Btc => "base58btc",
+            Multibase::Base64Url => "base64url",
+        }
+    }
+
+    pub fn prefix(&self) -> char {
+        match self {
+            Multibase::Base16 => 'f',
+            Multibase::Base32 => 'b',
+            Multibase::Base58Btc => 'z',
+            Multibase::Base64Url => 'u',
+        }
+    }
+
+    fn from_prefix(prefix: char) -> Option<Self> {
+        // Uppercase `F`/`B` are the same alphabets with capitals
+        let prefix = match prefix {
+            'F' | 'B' => prefix.to_ascii_lowercase(),
+            _ => prefix,
+        };
+        Self::ALL.into_iter().find(|base| base.prefix() == prefix)
+    }
+
+    fn encode(&self, bytes: &[u8]) -> String {
+        match self {
+            Multibase::Base16 => hex::encode(bytes),
+            Multibase::Base32 => base32_encode(bytes),
+            Multibase::Base58Btc => base58_encode(bytes),
+            Multibase::Base64Url => BASE64URL.encode(bytes),
+        }
+    }
+
+    fn decode(&self, text: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+        match self {
+            Multibase::Base16 => Ok(hex::decode(text)?),
+            Multibase::Base32 => base32_decode(&text.to_ascii_lowercase()),
+            Multibase::Base58Btc => base58_decode(text),
+            Multibase::Base64Url => Ok(BASE64URL.decode(text)?),
+        }
+    }
+}
+
+impl fmt::Display for Multibase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+fn base32_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(5) * 8);
+    let (mut buffer, mut bits) = (0u32, 0u32);
+    for &byte in bytes {
+        buffer = (buffer << 8) | u32::from(byte);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(BASE32_ALPHABET[((buffer >> bits) & 31) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        out.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 31) as usize] as char);
+    }
+    out
+}
+
+fn base32_decode(text: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut out = Vec::with_capacity(text.len() * 5 / 8);
+    let (mut buffer, mut bits) = (0u32, 0u32);
+    for c in text.bytes() {
+        let value = BASE32_ALPHABET
+            .iter()
+            .position(|&a| a == c)
+            .ok_or_else(|| format!("invalid base32 character '{}'", c as char))?;
+        buffer = (buffer << 5) | value as u32;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+    }
+    Ok(out)
+}
+
+fn base58_encode(bytes: &[u8]) -> String {
+    // Base-58 digits, least significant first
+    let mut digits: Vec<u8> = Vec::new();
+    for &byte in bytes {
+        let mut carry = u32::from(byte);
+        for digit in digits.iter_mut() {
+            carry += u32::from(*digit) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+    let zeros = bytes.iter().take_while(|&&b| b == 0).count();
+    std::iter::repeat_n('1', zeros)
+        .chain(
+            digits
+                .iter()
+                .rev()
+                .map(|&d| BASE58_ALPHABET[d as usize] as char),
+        )
+        .collect()
+}
+
+fn base58_decode(text: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    // Bytes, least significant first
+    let mut bytes: Vec<u8> = Vec::new();
+    for c in text.bytes() {
+        let mut carry = BASE58_ALPHABET
+            .iter()
+            .position(|&a| a == c)
+            .ok_or_else(|| format!("invalid base58 character '{}'", c as char))?
+            as u32;
+        for byte in bytes.iter_mut() {
+            carry += u32::from(*byte) * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+    let zeros = text.bytes().take_while(|&c| c == b'1').count();
+    Ok(std::iter::repeat_n(0, zeros)
+        .chain(bytes.into_iter().rev())
+        .collect())
+}
+
+/// The multihash bytes of a sha3-256 digest.
+pub fn sha3_256_multihash(digest: &[u8; 32]) -> Vec<u8> {
+    let mut bytes = vec![SHA3_256_CODE, SHA3_256_LEN];
+    bytes.extend_from_slice(digest);
+    bytes
+}
+
+/// A sha3-256 digest as a multibase multihash string.
+pub fn encode_sha3_256(digest: &[u8; 32], base: Multibase) -> String {
+    format!(
+        "{}{}",
+        base.prefix(),
+        base.encode(&sha3_256_multihash(digest))
+    )
+}
+
+/// Whether `text` starts with a multibase prefix this module reads.
+pub fn is_multibase(text: &str) -> bool {
+    text.chars()
+        .next()
+        .and_then(Multibase::from_prefix)
+        .is_some()
+}
+
+/// Parse `encode_sha3_256` output back into the digest.
+pub fn decode_sha3_256(text: &str) -> Result<[u8; 32], Box<dyn Error>> {
+    let text = text.trim();
+    let mut chars = text.chars();
+    let prefix = chars.next().ok_or("empty multibase string")?;
+    let base = Multibase::from_prefix(prefix)
+        .ok_or_else(|| format!("unsupported multibase prefix '{prefix}'"))?;
+    let bytes = base.decode(chars.as_str())?;
+    match bytes.as_slice() {
+        [SHA3_256_CODE, SHA3_256_LEN, digest @ ..] if digest.len() == SHA3_256_LEN as usize => {
+            Ok(digest.try_into().unwrap())
+        }
+        [SHA3_256_CODE, ..] => Err(format!(
+            "sha3-256 multihash is {} bytes, expected {}",
+            bytes.len(),
+            2 + SHA3_256_LEN as usize
+        )
+        .into()),
+        [code, ..] => Err(format!("multihash code 0x{code:02x} is not sha3-256 (0x16)").into()),
+        [] => Err("empty multihash".into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digest() -> [u8; 32] {
+        std::array::from_fn(|i| (i * 37 + 5) as u8)
+    }
+
+    #[test]
+    fn round_trips_in_every_base() {
+        for base in Multibase::ALL {
+            let text = encode_sha3_256(&digest(), base);
+            assert!(text.starts_with(base.prefix()));
+            assert!(is_multibase(&text));
+            assert_eq!(decode_sha3_256(&text).unwrap(), digest(), "{base}");
+        }
+    }
+
+    #[test]
+    fn matches_reference_encodings() {
+        // sha3-256 of the empty string, as printed by the multihash CLI
+        let empty: [u8; 32] =
+            hex::decode("a7ffc6f8bf1ed76651c14756a061d662f580ff4de43b49fa82d80a4b80f8434a")
+                .unwrap()
+                .try_into()
+                .unwrap();
+        assert_eq!(
+            encode_sha3_256(&empty, Multibase::Base16),
+            "f1620a7ffc6f8bf1ed76651c14756a061d662f580ff4de43b49fa82d80a4b80f8434a"
+        );
+        assert_eq!(base58_encode(b"hello world"), "StV1DL6CwTryKyV");
+        assert_eq!(base58_decode("StV1DL6CwTryKyV").unwrap(), b"hello world");
+        assert_eq!(base58_encode(&[0, 0, 1]), "112");
+        assert_eq!(base32_encode(b"foobar"), "mzxw6ytboi");
+        assert_eq!(base32_decode("mzxw6ytboi").unwrap(), b"foobar");
+    }
+
+    #[test]
+    fn rejects_other_digests() {
+        let mut bytes = sha3_256_multihash(&digest());
+        bytes[0] = 0x12; // sha2-256
+        assert!(decode_sha3_256(&format!("f{}", hex::encode(&bytes))).is_err());
+        bytes[0] = SHA3_256_CODE;
+        bytes.pop();
+        assert!(decode_sha3_256(&format!("f{}", hex::encode(&bytes))).is_err());
+        assert!(decode_sha3_256("x1234").is_err());
+        assert!(Multibase::parse("base36").is_err());
+    }
+}
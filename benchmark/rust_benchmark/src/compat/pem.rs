@@ -0,0 +1,260 @@
+//! DER and PEM wrapping for public keys
+//!
+//! Operators keep hash-sig public keys next to their other key material, and
+//! tooling around that material expects `SubjectPublicKeyInfo`-shaped DER
+//! with PEM armor:
+//!
+//! ```text
+//! SEQUENCE {
+//!   SEQUENCE { OBJECT IDENTIFIER 1.3.6.1.4.1.32473.1.<log_lifetime> }
+//!   BIT STRING <SSZ public key>
+//! }
+//! ```
+//!
+//! The OID sits under 1.3.6.1.4.1.32473, the enterprise number RFC 5612
+//! reserves for documentation, until the project registers its own. Its last
+//! arc is the log lifetime, so a wrapped key says which scheme it is for.
+//! PEM uses the label `HASHSIG PUBLIC KEY`.
+
+use std::error::Error;
+
+use ssz::{Decode, Encode};
+
+use crate::compat::ssz_codec;
+use crate::lifetime::{LifetimeError, LifetimeTag, SchemeMetadata};
+
+pub const PEM_LABEL: &str = "HASHSIG PUBLIC KEY";
+
+/// OID arcs shared by all lifetimes; the log lifetime is appended.
+pub const OID_PREFIX: [u64; 8] = [1, 3, 6, 1, 4, 1, 32473, 1];
+
+const TAG_SEQUENCE: u8 = 0x30;
+const TAG_OID: u8 = 0x06;
+const TAG_BIT_STRING: u8 = 0x03;
+
+const PEM_LINE_LEN: usize = 64;
+
+fn pem_begin() -> String {
+    format!("-----BEGIN {PEM_LABEL}-----")
+}
+
+fn pem_end() -> String {
+    format!("-----END {PEM_LABEL}-----")
+}
+
+fn push_len(out: &mut Vec<u8>, len: usize) {
+    if len < 0x80 {
+        out.push(len as u8);
+    } else {
+        let bytes = len.to_be_bytes();
+        let skip = bytes.iter().take_while(|&&b| b == 0).count();
+        out.push(0x80 | (bytes.len() - skip) as u8);
+        out.extend_from_slice(&bytes[skip..]);
+    }
+}
+
+fn tlv(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut out = vec![tag];
+    push_len(&mut out, content.len());
+    out.extend_from_slice(content);
+    out
+}
+
+fn encode_oid(arcs: &[u64]) -> Vec<u8> {
+    let mut out = vec![(arcs[0] * 40 + arcs[1]) as u8];
+    for &arc in &arcs[2..] {
+        let mut base128 = vec![(arc & 0x7f) as u8];
+        let mut rest = arc >> 7;
+        while rest > 0 {
+            base128.push(0x80 | (rest & 0x7f) as u8);
+            rest >>= 7;
+        }
+        out.extend(base128.iter().rev());
+    }
+    out
+}
+
+fn decode_oid(bytes: &[u8]) -> Result<Vec<u64>, Box<dyn Error>> {
+    let (&first, rest) = bytes.split_first().ok_or("empty OID")?;
+    let mut arcs = vec![u64::from(first / 40), u64::from(first % 40)];
+    let mut arc = 0u64;
+    for (i, &byte) in rest.iter().enumerate() {
+        if arc > u64::MAX >> 7 {
+            return Err("OID arc out of range".into());
+        }
+        arc = (arc << 7) | u64::from(byte & 0x7f);
+        if byte & 0x80 == 0 {
+            arcs.push(arc);
+            arc = 0;
+        } else if i == rest.len() - 1 {
+            return Err("truncated OID".into());
+        }
+    }
+    Ok(arcs)
+}
+
+/// Content of a DER element and the bytes after it.
+type Tlv<'a> = (&'a [u8], &'a [u8]);
+
+/// Split one DER element with the given tag off the front of `input`.
+fn read_tlv(input: &[u8], tag: u8) -> Result<Tlv<'_>, Box<dyn Error>> {
+    let (&actual, rest) = input.split_first().ok_or("unexpected end of DER")?;
+    if actual != tag {
+        return Err(format!("expected DER tag {tag:#04x}, found {actual:#04x}").into());
+    }
+    let (&first, rest) = rest.split_first().ok_or("unexpected end of DER")?;
+    let (len, rest) = if first < 0x80 {
+        (first as usize, rest)
+    } else {
+        let n = (first & 0x7f) as usize;
+        if n == 0 || n > std::mem::size_of::<usize>() || rest.len() < n {
+            return Err("invalid DER length".into());
+        }
+        let len = rest[..n]
+            .iter()
+            .fold(0usize, |acc, &b| (acc << 8) | b as usize);
+        (len, &rest[n..])
+    };
+    if rest.len() < len {
+        return Err("DER element longer than input".into());
+    }
+    Ok(rest.split_at(len))
+}
+
+/// OID for keys of the given lifetime.
+pub fn lifetime_oid(tag: LifetimeTag) -> Vec<u64> {
+    let mut arcs = OID_PREFIX.to_vec();
+    arcs.push(u64::from(tag.log_lifetime()));
+    arcs
+}
+
+/// Wrap SSZ public key bytes for `tag` in DER.
+pub fn wrap_der(tag: LifetimeTag, ssz_key: &[u8]) -> Vec<u8> {
+    let algorithm = tlv(TAG_SEQUENCE, &tlv(TAG_OID, &encode_oid(&lifetime_oid(tag))));
+    let mut bits = vec![0u8]; // no unused bits
+    bits.extend_from_slice(ssz_key);
+    let mut content = algorithm;
+    content.extend(tlv(TAG_BIT_STRING, &bits));
+    tlv(TAG_SEQUENCE, &content)
+}
+
+/// Lifetime and SSZ public key bytes of a DER-wrapped key.
+pub fn unwrap_der(der: &[u8]) -> Result<(LifetimeTag, &[u8]), Box<dyn Error>> {
+    let (spki, trailing) = read_tlv(der, TAG_SEQUENCE)?;
+    if !trailing.is_empty() {
+        return Err("trailing bytes after public key DER".into());
+    }
+    let (algorithm, rest) = read_tlv(spki, TAG_SEQUENCE)?;
+    let (oid, _params) = read_tlv(algorithm, TAG_OID)?;
+    let arcs = decode_oid(oid)?;
+    let tag = match arcs.split_last() {
+        Some((&log_lifetime, prefix)) if prefix == OID_PREFIX => LifetimeTag::ALL
+            .into_iter()
+            .find(|tag| u64::from(tag.log_lifetime()) == log_lifetime)
+            .ok_or_else(|| {
+                LifetimeError(format!(
+                    "unsupported log lifetime {log_lifetime} in key OID"
+                ))
+            })?,
+        _ => return Err(format!("not a hash-sig public key (OID {arcs:?})").into()),
+    };
+    let (bits, rest) = read_tlv(rest, TAG_BIT_STRING)?;
+    if !rest.is_empty() {
+        return Err("trailing fields in public key DER".into());
+    }
+    match bits.split_first() {
+        Some((0, key)) => Ok((tag, key)),
+        _ => Err("public key BIT STRING must have no unused bits".into()),
+    }
+}
+
+/// Armor DER bytes as `HASHSIG PUBLIC KEY` PEM.
+pub fn der_to_pem(der: &[u8]) -> String {
+    use base64::Engine;
+    let body = base64::engine::general_purpose::STANDARD.encode(der);
+    let mut pem = pem_begin();
+    pem.push('\n');
+    for line in body.as_bytes().chunks(PEM_LINE_LEN) {
+        pem.push_str(std::str::from_utf8(line).expect("base64 is ASCII"));
+        pem.push('\n');
+    }
+    pem.push_str(&pem_end());
+    pem.push('\n');
+    pem
+}
+
+/// DER bytes inside `HASHSIG PUBLIC KEY` PEM armor.
+pub fn pem_to_der(pem: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    use base64::Engine;
+    let begin = pem_begin();
+    let end = pem_end();
+    let start = pem
+        .find(&begin)
+        .ok_or_else(|| format!("missing '{begin}'"))?
+        + begin.len();
+    let stop = pem[start..]
+        .find(&end)
+        .ok_or_else(|| format!("missing '{end}'"))?
+        + start;
+    let body: String = pem[start..stop]
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace())
+        .collect();
+    Ok(base64::engine::general_purpose::STANDARD.decode(body)?)
+}
+
+/// Whether `bytes` hold a PEM- or DER-wrapped public key rather than one of
+/// the plain `compat::codec` encodings.
+pub fn is_wrapped(bytes: &[u8]) -> bool {
+    let pem_begin = pem_begin();
+    bytes
+        .windows(pem_begin.len())
+        .any(|w| w == pem_begin.as_bytes())
+        || unwrap_der(bytes).is_ok()
+}
+
+fn der_of(bytes: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+    match std::str::from_utf8(bytes) {
+        Ok(text) if text.contains(&pem_begin()) => pem_to_der(text),
+        _ => Ok(bytes.to_vec()),
+    }
+}
+
+/// Lifetime named by a PEM- or DER-wrapped key.
+pub fn wrapped_lifetime(bytes: &[u8]) -> Result<LifetimeTag, Box<dyn Error>> {
+    Ok(unwrap_der(&der_of(bytes)?)?.0)
+}
+
+pub fn encode_der<S>(pk: &S::PublicKey) -> Vec<u8>
+where
+    S: SchemeMetadata,
+    S::PublicKey: Encode,
+{
+    wrap_der(S::TAG, &pk.as_ssz_bytes())
+}
+
+pub fn encode_pem<S>(pk: &S::PublicKey) -> String
+where
+    S: SchemeMetadata,
+    S::PublicKey: Encode,
+{
+    der_to_pem(&encode_der::<S>(pk))
+}
+
+/// Decode a PEM- or DER-wrapped key, rejecting keys for another lifetime.
+pub fn decode_public_key<S>(bytes: &[u8]) -> Result<S::PublicKey, Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::PublicKey: Decode,
+{
+    let der = der_of(bytes)?;
+    let (tag, ssz_key) = unwrap_der(&der)?;
+    if tag != S::TAG {
+        return Err(LifetimeError(format!(
+            "public key is for lifetime {tag}, expected {}",
+            S::TAG
+        ))
+        .into());
+    }
+    ssz_codec::decode_public_key::<S>(ssz_key, S::METADATA)
+}
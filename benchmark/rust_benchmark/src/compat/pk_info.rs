@@ -0,0 +1,180 @@
+//! Public key root and fingerprint, whatever the encoding
+//!
+//! A public key is the Merkle root plus the hash parameter. Comparing keys
+//! across implementations, or reporting which key a benchmark produced, only
+//! needs those field elements, yet each encoding lays them out differently:
+//! named arrays in JSON, a fixed little-endian layout in SSZ, and serde's
+//! tuple layout in bincode. `PublicKeyInfo` extracts them once so callers do
+//! not slice raw bytes and hope the root comes first.
+//!
+//! The fingerprint is the SHA3-256 of `root_bytes()`, the same digest
+//! `keygen_bench.zig` prints as `PUBLIC_SHA3`. `root_from_bytes` reads just
+//! the root from any `Format` the codec writes, checking the encoded length
+//! first, for callers that only need the fingerprint.
+
+use std::error::Error;
+
+use serde::Serialize;
+use serde_json::Value;
+use sha3::{Digest, Sha3_256};
+
+use crate::compat::binfmt::{self, HEADER_LEN};
+use crate::compat::json::from_hex_field_elements;
+use crate::compat::multihash::{self, Multibase};
+use crate::config::Format;
+use crate::lifetime::{LifetimeMetadata, SchemeMetadata};
+
+const FIELD_ELEMENT_BYTES: usize = 4;
+
+/// Canonical field elements of a public key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublicKeyInfo {
+    pub root: Vec<u32>,
+    pub parameter: Vec<u32>,
+}
+
+fn field_elements(value: &Value, name: &str, len: usize) -> Result<Vec<u32>, Box<dyn Error>> {
+    let array = value
+        .get(name)
+        .and_then(Value::as_array)
+        .ok_or_else(|| format!("public key has no '{name}' array"))?;
+    if array.len() < len {
+        return Err(format!("'{name}' has {} elements, expected {len}", array.len()).into());
+    }
+    array[..len]
+        .iter()
+        .map(|v| {
+            v.as_u64()
+                .and_then(|n| u32::try_from(n).ok())
+                .ok_or_else(|| format!("'{name}' holds a non-u32 value {v}").into())
+        })
+        .collect()
+}
+
+fn ssz_len(meta: LifetimeMetadata) -> usize {
+    (meta.hash_len + meta.parameter_len) * FIELD_ELEMENT_BYTES
+}
+
+fn check_len(
+    bytes: &[u8],
+    expected: usize,
+    what: &str,
+    meta: LifetimeMetadata,
+) -> Result<(), Box<dyn Error>> {
+    if bytes.len() != expected {
+        return Err(format!(
+            "{what} public key is {} bytes, expected {expected} for lifetime 2^{}",
+            bytes.len(),
+            meta.log_lifetime
+        )
+        .into());
+    }
+    Ok(())
+}
+
+fn le_words(bytes: &[u8]) -> Vec<u32> {
+    bytes
+        .chunks_exact(FIELD_ELEMENT_BYTES)
+        .map(|c| u32::from_le_bytes(c.try_into().unwrap()))
+        .collect()
+}
+
+/// The root of an encoded public key, without decoding the parameter or the
+/// scheme's key type.
+///
+/// Fixed-size encodings (SSZ, `compat::binfmt`) must have exactly the
+/// lifetime's length; self-describing ones (JSON, CBOR, MessagePack) must
+/// hold a `root` array of at least `hash_len` elements, as trimmed keys do.
+pub fn root_from_bytes(
+    bytes: &[u8],
+    format: Format,
+    meta: LifetimeMetadata,
+) -> Result<Vec<u32>, Box<dyn Error>> {
+    let mut value: Value = match format {
+        Format::Ssz => {
+            check_len(bytes, ssz_len(meta), "SSZ", meta)?;
+            return Ok(le_words(&bytes[..meta.hash_len * FIELD_ELEMENT_BYTES]));
+        }
+        Format::ZigBinary => {
+            check_len(bytes, HEADER_LEN + ssz_len(meta), "binary", meta)?;
+            binfmt::read_public_key(&mut &bytes[..], meta)?
+        }
+        Format::Json | Format::Bincode => serde_json::from_slice(bytes)?,
+        Format::Cbor => ciborium::from_reader(bytes)
+            .map_err(|e| format!("failed to decode public key from CBOR: {e}"))?,
+        Format::Msgpack => rmp_serde::from_slice(bytes)
+            .map_err(|e| format!("failed to decode public key from MessagePack: {e}"))?,
+    };
+    from_hex_field_elements(&mut value)?;
+    field_elements(&value, "root", meta.hash_len)
+}
+
+/// SHA3-256 of a root's little-endian bytes; see `PublicKeyInfo::fingerprint`.
+pub fn root_fingerprint(root: &[u32]) -> [u8; 32] {
+    let bytes: Vec<u8> = root.iter().flat_map(|fe| fe.to_le_bytes()).collect();
+    Sha3_256::digest(bytes).into()
+}
+
+impl PublicKeyInfo {
+    /// From the serde JSON value of a key (trimmed or not).
+    pub fn from_json_value(value: &Value, meta: LifetimeMetadata) -> Result<Self, Box<dyn Error>> {
+        Ok(PublicKeyInfo {
+            root: field_elements(value, "root", meta.hash_len)?,
+            parameter: field_elements(value, "parameter", meta.parameter_len)?,
+        })
+    }
+
+    /// From a JSON key file's contents.
+    pub fn from_json_bytes(bytes: &[u8], meta: LifetimeMetadata) -> Result<Self, Box<dyn Error>> {
+        Self::from_json_value(&serde_json::from_slice(bytes)?, meta)
+    }
+
+    /// From the SSZ layout: `hash_len` root elements then `parameter_len`
+    /// parameter elements, each a little-endian u32.
+    pub fn from_ssz_bytes(bytes: &[u8], meta: LifetimeMetadata) -> Result<Self, Box<dyn Error>> {
+        check_len(bytes, ssz_len(meta), "SSZ", meta)?;
+        let root_len = meta.hash_len * FIELD_ELEMENT_BYTES;
+        Ok(PublicKeyInfo {
+            root: le_words(&bytes[..root_len]),
+            parameter: le_words(&bytes[root_len..]),
+        })
+    }
+
+    /// From a decoded upstream key.
+    pub fn from_public_key<S>(pk: &S::PublicKey) -> Result<Self, Box<dyn Error>>
+    where
+        S: SchemeMetadata,
+        S::PublicKey: Serialize,
+    {
+        Self::from_json_value(&serde_json::to_value(pk)?, S::METADATA)
+    }
+
+    /// From a bincode-serialized key. bincode carries no field names, so the
+    /// bytes are decoded as the scheme's key type first.
+    pub fn from_bincode<S>(bytes: &[u8]) -> Result<Self, Box<dyn Error>>
+    where
+        S: SchemeMetadata,
+        S::PublicKey: Serialize,
+    {
+        Self::from_public_key::<S>(&bincode::deserialize::<S::PublicKey>(bytes)?)
+    }
+
+    /// The root as little-endian u32s, as in the SSZ encoding.
+    pub fn root_bytes(&self) -> Vec<u8> {
+        self.root.iter().flat_map(|fe| fe.to_le_bytes()).collect()
+    }
+
+    /// SHA3-256 of `root_bytes()`.
+    pub fn fingerprint(&self) -> [u8; 32] {
+        root_fingerprint(&self.root)
+    }
+
+    pub fn fingerprint_hex(&self) -> String {
+        hex::encode(self.fingerprint())
+    }
+
+    /// The fingerprint as a sha3-256 multihash in `base`.
+    pub fn fingerprint_multibase(&self, base: Multibase) -> String {
+        multihash::encode_sha3_256(&self.fingerprint(), base)
+    }
+}
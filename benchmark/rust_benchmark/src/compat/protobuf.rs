@@ -0,0 +1,188 @@
+//! Protobuf encoding of keys and signatures
+//!
+//! `proto/interchange.proto` describes public keys and signatures field by
+//! field, so consumers in other languages can parse artifacts with generated
+//! protobuf code instead of the ad-hoc binary layout. Conversions go through
+//! the same serde JSON shape as `compat::json`, with canonical field
+//! elements, and check every length against the scheme's metadata.
+
+use std::error::Error;
+
+use prost::Message;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{json, Value};
+
+use crate::compat::json::{signature_from_json, signature_to_json};
+use crate::compat::pk_info::PublicKeyInfo;
+use crate::lifetime::{LifetimeError, LifetimeMetadata, SchemeMetadata};
+
+pub mod proto {
+    include!(concat!(env!("OUT_DIR"), "/hashsig.interchange.v1.rs"));
+}
+
+use proto::{Digest, PublicKey, Signature};
+
+fn check_lifetime(log_lifetime: u32, meta: LifetimeMetadata) -> Result<(), Box<dyn Error>> {
+    if log_lifetime != meta.log_lifetime {
+        return Err(LifetimeError(format!(
+            "message is for lifetime 2^{log_lifetime}, expected 2^{}",
+            meta.log_lifetime
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+fn check_len(what: &str, actual: usize, expected: usize) -> Result<(), Box<dyn Error>> {
+    if actual != expected {
+        return Err(format!("{what} has {actual} elements, expected {expected}").into());
+    }
+    Ok(())
+}
+
+fn u32_array(value: &Value, what: &str) -> Result<Vec<u32>, Box<dyn Error>> {
+    value
+        .as_array()
+        .ok_or_else(|| format!("{what} is not an array"))?
+        .iter()
+        .map(|v| {
+            v.as_u64()
+                .and_then(|n| u32::try_from(n).ok())
+                .ok_or_else(|| format!("{what} holds a non-u32 value {v}").into())
+        })
+        .collect()
+}
+
+fn digests(value: Option<&Value>, what: &str) -> Result<Vec<Digest>, Box<dyn Error>> {
+    value
+        .and_then(Value::as_array)
+        .ok_or_else(|| format!("signature JSON missing {what}"))?
+        .iter()
+        .map(|node| {
+            Ok(Digest {
+                elements: u32_array(node, what)?,
+            })
+        })
+        .collect()
+}
+
+fn check_max(what: &str, actual: usize, max: usize) -> Result<(), Box<dyn Error>> {
+    if actual > max {
+        return Err(format!("{what} has {actual} entries, at most {max} expected").into());
+    }
+    Ok(())
+}
+
+fn check_digests(
+    what: &str,
+    digests: &[Digest],
+    meta: LifetimeMetadata,
+) -> Result<(), Box<dyn Error>> {
+    digests
+        .iter()
+        .try_for_each(|digest| check_len(what, digest.elements.len(), meta.hash_len))
+}
+
+pub fn public_key_to_proto<S>(pk: &S::PublicKey) -> Result<PublicKey, Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::PublicKey: Serialize,
+{
+    let info = PublicKeyInfo::from_public_key::<S>(pk)?;
+    Ok(PublicKey {
+        log_lifetime: S::METADATA.log_lifetime,
+        root: info.root,
+        parameter: info.parameter,
+    })
+}
+
+pub fn public_key_from_proto<S>(message: &PublicKey) -> Result<S::PublicKey, Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::PublicKey: DeserializeOwned,
+{
+    let meta = S::METADATA;
+    check_lifetime(message.log_lifetime, meta)?;
+    check_len("root", message.root.len(), meta.hash_len)?;
+    check_len("parameter", message.parameter.len(), meta.parameter_len)?;
+    Ok(serde_json::from_value(json!({
+        "root": message.root,
+        "parameter": message.parameter,
+    }))?)
+}
+
+pub fn signature_to_proto<S>(sig: &S::Signature) -> Result<Signature, Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::Signature: Serialize,
+{
+    let value = signature_to_json(sig, S::METADATA)?;
+    Ok(Signature {
+        log_lifetime: S::METADATA.log_lifetime,
+        path_nodes: digests(value.get("path").and_then(|p| p.get("nodes")), "path.nodes")?,
+        rho: u32_array(value.get("rho").ok_or("signature JSON missing rho")?, "rho")?,
+        hashes: digests(value.get("hashes"), "hashes")?,
+    })
+}
+
+pub fn signature_from_proto<S>(message: &Signature) -> Result<S::Signature, Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::Signature: DeserializeOwned,
+{
+    let meta = S::METADATA;
+    check_lifetime(message.log_lifetime, meta)?;
+    // Counts are bounded the same way as in `compat::binfmt`.
+    check_max(
+        "path_nodes",
+        message.path_nodes.len(),
+        meta.log_lifetime as usize,
+    )?;
+    check_digests("path node", &message.path_nodes, meta)?;
+    check_len("rho", message.rho.len(), meta.rand_len)?;
+    check_max("hashes", message.hashes.len(), meta.dimension)?;
+    check_digests("hash", &message.hashes, meta)?;
+    let elements = |digests: &[Digest]| -> Vec<Vec<u32>> {
+        digests.iter().map(|d| d.elements.clone()).collect()
+    };
+    signature_from_json(
+        json!({
+            "path": { "nodes": elements(&message.path_nodes) },
+            "rho": message.rho,
+            "hashes": elements(&message.hashes),
+        }),
+        meta,
+    )
+}
+
+pub fn encode_public_key<S>(pk: &S::PublicKey) -> Result<Vec<u8>, Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::PublicKey: Serialize,
+{
+    Ok(public_key_to_proto::<S>(pk)?.encode_to_vec())
+}
+
+pub fn decode_public_key<S>(bytes: &[u8]) -> Result<S::PublicKey, Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::PublicKey: DeserializeOwned,
+{
+    public_key_from_proto::<S>(&PublicKey::decode(bytes)?)
+}
+
+pub fn encode_signature<S>(sig: &S::Signature) -> Result<Vec<u8>, Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::Signature: Serialize,
+{
+    Ok(signature_to_proto::<S>(sig)?.encode_to_vec())
+}
+
+pub fn decode_signature<S>(bytes: &[u8]) -> Result<S::Signature, Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::Signature: DeserializeOwned,
+{
+    signature_from_proto::<S>(&Signature::decode(bytes)?)
+}
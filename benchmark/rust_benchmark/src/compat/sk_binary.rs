@@ -0,0 +1,121 @@
+//! Compact binary secret keys
+//!
+//! Pretty-printed JSON secret keys run to hundreds of megabytes for 2^18
+//! and most of a sign run went into parsing them. This layout is an 8-byte
+//! header followed by the key's bincode encoding:
+//!
+//! ```text
+//! 4 bytes                     magic `HSSK`
+//! u8                          format version (1)
+//! u8                          log lifetime (8, 18 or 32)
+//! 2 bytes                     reserved, zero
+//! ...                         bincode secret key
+//! ```
+//!
+//! The header matches `compat::binfmt`, so a key for another lifetime is
+//! rejected before the body is parsed. Reading and writing go through
+//! `Read`/`Write`, so files are streamed rather than buffered whole, through
+//! zstd for `.hsz` paths.
+
+use std::error::Error;
+use std::io::{Read, Write};
+use std::path::Path;
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::compat::{hsz, key_envelope};
+use crate::lifetime::{LifetimeError, SchemeMetadata};
+
+pub const MAGIC: [u8; 4] = *b"HSSK";
+pub const FORMAT_VERSION: u8 = 1;
+pub const HEADER_LEN: usize = 8;
+
+pub fn write_secret_key<S, W>(writer: &mut W, sk: &S::SecretKey) -> Result<(), Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::SecretKey: Serialize,
+    W: Write,
+{
+    writer.write_all(&MAGIC)?;
+    writer.write_all(&[FORMAT_VERSION, S::METADATA.log_lifetime as u8, 0, 0])?;
+    bincode::serialize_into(writer, sk)?;
+    Ok(())
+}
+
+/// Read a key written by `write_secret_key`, rejecting keys for another
+/// lifetime.
+pub fn read_secret_key<S, R>(reader: &mut R) -> Result<S::SecretKey, Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::SecretKey: DeserializeOwned,
+    R: Read,
+{
+    let mut header = [0u8; HEADER_LEN];
+    reader
+        .read_exact(&mut header)
+        .map_err(|e| format!("binary secret key header: {e}"))?;
+    if header[..4] != MAGIC {
+        return Err(format!(
+            "not a binary secret key: magic {:02x?}, expected {:02x?} (\"HSSK\")",
+            &header[..4],
+            MAGIC
+        )
+        .into());
+    }
+    if header[4] != FORMAT_VERSION {
+        return Err(format!(
+            "unsupported binary secret key version {}, expected {FORMAT_VERSION}",
+            header[4]
+        )
+        .into());
+    }
+    if u32::from(header[5]) != S::METADATA.log_lifetime {
+        return Err(LifetimeError(format!(
+            "secret key is for lifetime 2^{}, expected 2^{}",
+            header[5],
+            S::METADATA.log_lifetime
+        ))
+        .into());
+    }
+    bincode::deserialize_from(reader)
+        .map_err(|e| format!("failed to decode binary secret key: {e}").into())
+}
+
+pub fn encode_secret_key<S>(sk: &S::SecretKey) -> Result<Vec<u8>, Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::SecretKey: Serialize,
+{
+    let mut bytes = Vec::new();
+    write_secret_key::<S, _>(&mut bytes, sk)?;
+    Ok(bytes)
+}
+
+pub fn decode_secret_key<S>(bytes: &[u8]) -> Result<S::SecretKey, Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::SecretKey: DeserializeOwned,
+{
+    read_secret_key::<S, _>(&mut &bytes[..])
+}
+
+pub fn write_secret_key_file<S, P>(path: P, sk: &S::SecretKey) -> Result<(), Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::SecretKey: Serialize,
+    P: AsRef<Path>,
+{
+    let mut writer = hsz::create(path)?;
+    write_secret_key::<S, _>(&mut writer, sk)?;
+    writer.finish()
+}
+
+pub fn read_secret_key_file<S, P>(path: P) -> Result<S::SecretKey, Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::SecretKey: DeserializeOwned,
+    P: AsRef<Path>,
+{
+    let (_, mut reader) = key_envelope::open(path)?;
+    read_secret_key::<S, _>(&mut reader)
+}
@@ -0,0 +1,113 @@
+//! SSZ containers for public keys and signatures
+//!
+//! The byte layout comes from the upstream `Encode`/`Decode` derives; this
+//! module adds the per-lifetime size checks so a key or signature encoded
+//! for a different instantiation is rejected with a readable error.
+//!
+//! ```text
+//! PublicKey  (fixed)     root: hash_len x u32 | parameter: parameter_len x u32
+//! Signature  (variable)  offset(path) u32 | rho: rand_len x u32 | offset(hashes) u32
+//!                        | path: co_path nodes | hashes
+//! ```
+
+use std::error::Error;
+use std::path::Path;
+
+use ssz::{Decode, DecodeError, Encode};
+
+use crate::compat::{hsz, text_encoding};
+use crate::lifetime::LifetimeMetadata;
+use crate::upstream::SignatureScheme;
+
+const FIELD_ELEMENT_BYTES: usize = 4;
+const OFFSET_BYTES: usize = 4;
+
+pub fn public_key_len(meta: LifetimeMetadata) -> usize {
+    (meta.hash_len + meta.parameter_len) * FIELD_ELEMENT_BYTES
+}
+
+pub fn signature_fixed_len(meta: LifetimeMetadata) -> usize {
+    OFFSET_BYTES + meta.rand_len * FIELD_ELEMENT_BYTES + OFFSET_BYTES
+}
+
+/// True when `path` carries the `.ssz` extension used for SSZ artifacts,
+/// possibly followed by `.hsz`.
+pub fn is_ssz_path<P: AsRef<Path>>(path: P) -> bool {
+    hsz::inner_path(path.as_ref())
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("ssz"))
+}
+
+fn decode_error(what: &str, err: DecodeError) -> Box<dyn Error> {
+    format!("failed to decode {what} from SSZ: {err:?}").into()
+}
+
+pub fn decode_public_key<S>(
+    bytes: &[u8],
+    meta: LifetimeMetadata,
+) -> Result<S::PublicKey, Box<dyn Error>>
+where
+    S: SignatureScheme,
+    S::PublicKey: Decode,
+{
+    let expected = public_key_len(meta);
+    if bytes.len() != expected {
+        return Err(format!(
+            "SSZ public key is {} bytes, expected {expected} for lifetime 2^{}",
+            bytes.len(),
+            meta.log_lifetime
+        )
+        .into());
+    }
+    S::PublicKey::from_ssz_bytes(bytes).map_err(|e| decode_error("public key", e))
+}
+
+pub fn decode_signature<S>(
+    bytes: &[u8],
+    meta: LifetimeMetadata,
+) -> Result<S::Signature, Box<dyn Error>>
+where
+    S: SignatureScheme,
+    S::Signature: Decode,
+{
+    let fixed = signature_fixed_len(meta);
+    if bytes.len() < fixed {
+        return Err(format!(
+            "SSZ signature is {} bytes, shorter than the {fixed}-byte fixed part for lifetime 2^{}",
+            bytes.len(),
+            meta.log_lifetime
+        )
+        .into());
+    }
+    S::Signature::from_ssz_bytes(bytes).map_err(|e| decode_error("signature", e))
+}
+
+pub fn read_public_key_ssz<S, P>(
+    path: P,
+    meta: LifetimeMetadata,
+) -> Result<S::PublicKey, Box<dyn Error>>
+where
+    S: SignatureScheme,
+    S::PublicKey: Decode,
+    P: AsRef<Path>,
+{
+    decode_public_key::<S>(&hsz::read(path)?, meta)
+}
+
+pub fn read_signature_ssz<S, P>(
+    path: P,
+    meta: LifetimeMetadata,
+) -> Result<S::Signature, Box<dyn Error>>
+where
+    S: SignatureScheme,
+    S::Signature: Decode,
+    P: AsRef<Path>,
+{
+    decode_signature::<S>(&text_encoding::decode(&hsz::read(path)?)?, meta)
+}
+
+pub fn write_ssz<T: Encode, P: AsRef<Path>>(value: &T, path: P) -> Result<usize, Box<dyn Error>> {
+    let bytes = value.as_ssz_bytes();
+    hsz::write(path, &bytes)?;
+    Ok(bytes.len())
+}
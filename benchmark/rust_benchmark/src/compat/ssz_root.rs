@@ -0,0 +1,207 @@
+//! SSZ `hash_tree_root` of public keys and signatures
+//!
+//! Lean consensus commits to keys and signatures by their SSZ merkle root,
+//! not their byte encoding. The upstream types only derive `Encode`, so the
+//! roots are computed from the SSZ bytes with the `tree_hash` merkleization
+//! helpers, using this schema (`Fp` is a `uint32`):
+//!
+//! ```text
+//! PublicKey  { root: Vector[Fp, hash_len], parameter: Vector[Fp, parameter_len] }
+//! Signature  { path: { co_path: List[Digest, log_lifetime] },
+//!              rho: Vector[Fp, rand_len],
+//!              hashes: List[Digest, dimension] }
+//! Digest     = Vector[Fp, hash_len]
+//! ```
+//!
+//! The list limits are the bounds `compat::binfmt` already enforces. They
+//! feed into the root, so the Zig side must merkleize with the same limits.
+
+use std::error::Error;
+
+use ssz::Encode;
+use tree_hash::{merkle_root, mix_in_length};
+
+use crate::compat::ssz_codec::{public_key_len, signature_fixed_len};
+use crate::lifetime::{LifetimeMetadata, SchemeMetadata};
+
+const FIELD_ELEMENT_BYTES: usize = 4;
+const OFFSET_BYTES: usize = 4;
+
+type Root = [u8; 32];
+
+/// Root of a vector of basic values: its bytes packed into chunks.
+fn vector_root(bytes: &[u8]) -> Root {
+    merkle_root(bytes, 0).0
+}
+
+/// Root of a container from its field roots.
+fn container_root(fields: &[Root]) -> Root {
+    merkle_root(&fields.concat(), 0).0
+}
+
+/// Root of `List[Digest, limit]` from the concatenated digests.
+fn digest_list_root(
+    bytes: &[u8],
+    hash_len: usize,
+    limit: usize,
+    what: &str,
+) -> Result<Root, Box<dyn Error>> {
+    let digest_len = hash_len * FIELD_ELEMENT_BYTES;
+    if !bytes.len().is_multiple_of(digest_len) {
+        return Err(format!(
+            "{what} is {} bytes, not a multiple of {digest_len}",
+            bytes.len()
+        )
+        .into());
+    }
+    let count = bytes.len() / digest_len;
+    if count > limit {
+        return Err(format!("{what} has {count} entries, at most {limit} expected").into());
+    }
+    let roots: Vec<Root> = bytes.chunks(digest_len).map(vector_root).collect();
+    Ok(mix_in_length(&merkle_root(&roots.concat(), limit), count).0)
+}
+
+fn read_offset(bytes: &[u8], at: usize) -> usize {
+    let mut buf = [0u8; OFFSET_BYTES];
+    buf.copy_from_slice(&bytes[at..at + OFFSET_BYTES]);
+    u32::from_le_bytes(buf) as usize
+}
+
+/// `hash_tree_root` of an SSZ-encoded public key.
+pub fn public_key_root(bytes: &[u8], meta: LifetimeMetadata) -> Result<Root, Box<dyn Error>> {
+    let expected = public_key_len(meta);
+    if bytes.len() != expected {
+        return Err(format!(
+            "SSZ public key is {} bytes, expected {expected} for lifetime 2^{}",
+            bytes.len(),
+            meta.log_lifetime
+        )
+        .into());
+    }
+    let (root, parameter) = bytes.split_at(meta.hash_len * FIELD_ELEMENT_BYTES);
+    Ok(container_root(&[vector_root(root), vector_root(parameter)]))
+}
+
+/// `hash_tree_root` of an SSZ-encoded signature.
+pub fn signature_root(bytes: &[u8], meta: LifetimeMetadata) -> Result<Root, Box<dyn Error>> {
+    let fixed = signature_fixed_len(meta);
+    if bytes.len() < fixed {
+        return Err(format!(
+            "SSZ signature is {} bytes, shorter than the {fixed}-byte fixed part",
+            bytes.len()
+        )
+        .into());
+    }
+    let path_offset = read_offset(bytes, 0);
+    let rho = &bytes[OFFSET_BYTES..fixed - OFFSET_BYTES];
+    let hashes_offset = read_offset(bytes, fixed - OFFSET_BYTES);
+    if path_offset != fixed || hashes_offset < path_offset || hashes_offset > bytes.len() {
+        return Err(format!(
+            "SSZ signature offsets {path_offset} and {hashes_offset} do not fit {} bytes",
+            bytes.len()
+        )
+        .into());
+    }
+    let path = &bytes[path_offset..hashes_offset];
+    let hashes = &bytes[hashes_offset..];
+
+    // `path` is a container whose only field is the co-path list.
+    if path.len() < OFFSET_BYTES || read_offset(path, 0) != OFFSET_BYTES {
+        return Err("SSZ signature path is not a single-list container".into());
+    }
+    let co_path = digest_list_root(
+        &path[OFFSET_BYTES..],
+        meta.hash_len,
+        meta.log_lifetime as usize,
+        "co-path",
+    )?;
+    let hashes = digest_list_root(hashes, meta.hash_len, meta.dimension, "hashes")?;
+    Ok(container_root(&[
+        container_root(&[co_path]),
+        vector_root(rho),
+        hashes,
+    ]))
+}
+
+pub fn public_key_hash_tree_root<S>(pk: &S::PublicKey) -> Result<Root, Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::PublicKey: Encode,
+{
+    public_key_root(&pk.as_ssz_bytes(), S::METADATA)
+}
+
+pub fn signature_hash_tree_root<S>(sig: &S::Signature) -> Result<Root, Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::Signature: Encode,
+{
+    signature_root(&sig.as_ssz_bytes(), S::METADATA)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lifetime::LifetimeTag;
+
+    fn signature_bytes(meta: LifetimeMetadata, co_path: usize, hashes: usize) -> Vec<u8> {
+        let digest = vec![0x11u8; meta.hash_len * FIELD_ELEMENT_BYTES];
+        let fixed = signature_fixed_len(meta);
+        let path_len = OFFSET_BYTES + co_path * digest.len();
+        let mut bytes = (fixed as u32).to_le_bytes().to_vec();
+        bytes.extend(vec![0x22u8; meta.rand_len * FIELD_ELEMENT_BYTES]);
+        bytes.extend(((fixed + path_len) as u32).to_le_bytes());
+        bytes.extend((OFFSET_BYTES as u32).to_le_bytes());
+        for _ in 0..co_path {
+            bytes.extend(&digest);
+        }
+        for _ in 0..hashes {
+            bytes.extend(&digest);
+        }
+        bytes
+    }
+
+    #[test]
+    fn zero_public_key_root_is_hash_of_two_zero_chunks() {
+        for tag in LifetimeTag::ALL {
+            let meta = tag.metadata();
+            let root = public_key_root(&vec![0; public_key_len(meta)], meta).unwrap();
+            assert_eq!(
+                hex::encode(root),
+                "f5a5fd42d16a20302798ef6ed309979b43003d2320d9f0e8ea9831a92759fb4b",
+                "{tag}"
+            );
+        }
+    }
+
+    #[test]
+    fn signature_root_depends_on_every_field() {
+        let meta = LifetimeTag::Pow8.metadata();
+        let bytes = signature_bytes(meta, meta.log_lifetime as usize, meta.dimension);
+        let root = signature_root(&bytes, meta).unwrap();
+        for at in [
+            OFFSET_BYTES,
+            bytes.len() - 1,
+            signature_fixed_len(meta) + OFFSET_BYTES,
+        ] {
+            let mut changed = bytes.clone();
+            changed[at] ^= 1;
+            assert_ne!(signature_root(&changed, meta).unwrap(), root, "byte {at}");
+        }
+    }
+
+    #[test]
+    fn malformed_signatures_are_rejected() {
+        let meta = LifetimeTag::Pow8.metadata();
+        let too_long = signature_bytes(meta, meta.log_lifetime as usize + 1, 1);
+        assert!(signature_root(&too_long, meta).is_err());
+
+        let mut bad_offset = signature_bytes(meta, 1, 1);
+        bad_offset[0] ^= 1;
+        assert!(signature_root(&bad_offset, meta).is_err());
+
+        let truncated = signature_bytes(meta, 1, 1);
+        assert!(signature_root(&truncated[..truncated.len() - 1], meta).is_err());
+    }
+}
@@ -0,0 +1,118 @@
+//! Text encodings for binary signatures
+//!
+//! YAML and JSON test manifests embed signatures as text. `Encoding::Base64`
+//! writes the raw signature bytes as standard base64 followed by a newline;
+//! `decode` reverses it and passes raw files through, so verify paths accept
+//! either without a flag.
+//!
+//! Raw signatures are never mistaken for base64: the `compat::binfmt` header
+//! carries a version byte of 1 or 0x81 and SSZ/bincode signatures start with
+//! little-endian lengths, both outside the base64 alphabet.
+
+use std::error::Error;
+use std::fmt;
+
+use base64::Engine;
+
+const ENGINE: base64::engine::GeneralPurpose = base64::engine::general_purpose::STANDARD;
+
+/// How binary signature files are written.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Encoding {
+    /// The bytes as they are
+    #[default]
+    Raw,
+    /// Standard base64 with padding, one line
+    Base64,
+}
+
+impl Encoding {
+    pub fn parse(raw: &str) -> Result<Self, Box<dyn Error>> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "raw" | "binary" => Ok(Self::Raw),
+            "base64" => Ok(Self::Base64),
+            other => {
+                Err(format!("unsupported encoding '{other}'. Must be one of: raw, base64").into())
+            }
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Encoding::Raw => "raw",
+            Encoding::Base64 => "base64",
+        }
+    }
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+pub fn encode(bytes: &[u8], encoding: Encoding) -> Vec<u8> {
+    match encoding {
+        Encoding::Raw => bytes.to_vec(),
+        Encoding::Base64 => {
+            let mut text = ENGINE.encode(bytes).into_bytes();
+            text.push(b'\n');
+            text
+        }
+    }
+}
+
+fn is_base64_text(text: &[u8]) -> bool {
+    !text.is_empty()
+        && text.len().is_multiple_of(4)
+        && text
+            .iter()
+            .all(|&b| b.is_ascii_alphanumeric() || matches!(b, b'+' | b'/' | b'='))
+}
+
+/// Whether `bytes` look like `encode(_, Encoding::Base64)` output.
+pub fn is_base64(bytes: &[u8]) -> bool {
+    is_base64_text(bytes.trim_ascii())
+}
+
+/// Decode base64 text; anything else is returned unchanged.
+pub fn decode(bytes: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+    let text = bytes.trim_ascii();
+    if !is_base64_text(text) {
+        return Ok(bytes.to_vec());
+    }
+    ENGINE
+        .decode(text)
+        .map_err(|e| format!("invalid base64 signature: {e}").into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compat::binfmt;
+
+    #[test]
+    fn base64_round_trips() {
+        let bytes: Vec<u8> = (0..=255).collect();
+        let text = encode(&bytes, Encoding::Base64);
+        assert!(text.ends_with(b"\n"));
+        assert!(is_base64(&text));
+        assert_eq!(decode(&text).unwrap(), bytes);
+    }
+
+    #[test]
+    fn raw_bytes_pass_through() {
+        let mut header = binfmt::MAGIC.to_vec();
+        header.extend([binfmt::FORMAT_VERSION, 8, 7, 6]);
+        assert!(!is_base64(&header));
+        assert_eq!(decode(&header).unwrap(), header);
+        assert_eq!(encode(&header, Encoding::Raw), header);
+    }
+
+    #[test]
+    fn parses_names() {
+        assert_eq!(Encoding::parse("Base64").unwrap(), Encoding::Base64);
+        assert_eq!(Encoding::parse("raw").unwrap(), Encoding::Raw);
+        assert!(Encoding::parse("hex").is_err());
+    }
+}
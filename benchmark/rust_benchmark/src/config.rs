@@ -0,0 +1,202 @@
+//! Validated configuration for sign/verify pipelines
+//!
+//! ```ignore
+//! let config = SchemeConfig::new()
+//!     .lifetime(LifetimeTag::Pow18)
+//!     .seed(seed)
+//!     .activation(0, 256)
+//!     .format(Format::Ssz)
+//!     .build()?;
+//! ```
+//!
+//! The CLIs translate their arguments into this builder so lifetime, seed,
+//! activation window and artifact format are checked in one place.
+
+use std::error::Error;
+use std::fmt;
+use std::path::Path;
+
+use crate::lifetime::LifetimeTag;
+
+/// On-disk encoding of keys and signatures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Format {
+    /// serde JSON (keys) and the 3116-byte `compat::lean_wire` layout (signatures)
+    Json,
+    Ssz,
+    /// Little-endian Montgomery layout from `compat::binfmt`
+    ZigBinary,
+    /// serde CBOR for keys and signatures
+    Cbor,
+    /// serde MessagePack (named fields) for keys and signatures
+    Msgpack,
+    /// `compat::sk_binary` secret keys; public keys and signatures as `Json`
+    Bincode,
+}
+
+impl Format {
+    pub fn parse(raw: &str) -> Result<Self, Box<dyn Error>> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "json" => Ok(Self::Json),
+            "ssz" => Ok(Self::Ssz),
+            "bin" | "zig" | "zig-binary" => Ok(Self::ZigBinary),
+            "cbor" => Ok(Self::Cbor),
+            "msgpack" | "messagepack" => Ok(Self::Msgpack),
+            "bincode" => Ok(Self::Bincode),
+            other => Err(format!(
+                "unsupported format '{other}'. Must be one of: json, ssz, bin, cbor, msgpack, bincode"
+            )
+            .into()),
+        }
+    }
+
+    /// Format of a key file: `.ssz` is SSZ, `.cbor` CBOR, `.msgpack`
+    /// MessagePack, `.bincode` a binary secret key, anything else JSON. A
+    /// trailing `.hsz` is skipped. Signature files are not covered since `.bin` is used for both
+    /// bincode and the Zig layout.
+    pub fn from_key_path<P: AsRef<Path>>(path: P) -> Self {
+        let path = &crate::compat::hsz::inner_path(path.as_ref());
+        if crate::compat::ssz_codec::is_ssz_path(path) {
+            Format::Ssz
+        } else if path.extension().is_some_and(|ext| ext == "cbor") {
+            Format::Cbor
+        } else if path.extension().is_some_and(|ext| ext == "msgpack") {
+            Format::Msgpack
+        } else if path.extension().is_some_and(|ext| ext == "bincode") {
+            Format::Bincode
+        } else {
+            Format::Json
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Format::Json => "json",
+            Format::Ssz => "ssz",
+            Format::ZigBinary => "bin",
+            Format::Cbor => "cbor",
+            Format::Msgpack => "msgpack",
+            Format::Bincode => "bincode",
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SchemeConfigBuilder {
+    lifetime: LifetimeTag,
+    seed: Option<[u8; 32]>,
+    activation_epoch: usize,
+    num_active_epochs: usize,
+    format: Format,
+}
+
+impl Default for SchemeConfigBuilder {
+    fn default() -> Self {
+        Self {
+            lifetime: LifetimeTag::Pow8,
+            seed: None,
+            activation_epoch: 0,
+            num_active_epochs: 256,
+            format: Format::Json,
+        }
+    }
+}
+
+impl SchemeConfigBuilder {
+    pub fn lifetime(mut self, lifetime: LifetimeTag) -> Self {
+        self.lifetime = lifetime;
+        self
+    }
+
+    pub fn seed(mut self, seed: [u8; 32]) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn activation(mut self, activation_epoch: usize, num_active_epochs: usize) -> Self {
+        self.activation_epoch = activation_epoch;
+        self.num_active_epochs = num_active_epochs;
+        self
+    }
+
+    pub fn format(mut self, format: Format) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Check the activation window against the lifetime and freeze the config.
+    pub fn build(self) -> Result<SchemeConfig, Box<dyn Error>> {
+        let lifetime = self.lifetime.metadata().lifetime();
+        if self.num_active_epochs == 0 {
+            return Err("num_active_epochs must be at least 1".into());
+        }
+        let end = self.activation_epoch as u64 + self.num_active_epochs as u64;
+        if end > lifetime {
+            return Err(format!(
+                "activation window {}..{} exceeds lifetime {} ({} epochs)",
+                self.activation_epoch, end, self.lifetime, lifetime
+            )
+            .into());
+        }
+        Ok(SchemeConfig {
+            lifetime: self.lifetime,
+            seed: self.seed,
+            activation_epoch: self.activation_epoch,
+            num_active_epochs: self.num_active_epochs,
+            format: self.format,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemeConfig {
+    lifetime: LifetimeTag,
+    seed: Option<[u8; 32]>,
+    activation_epoch: usize,
+    num_active_epochs: usize,
+    format: Format,
+}
+
+impl SchemeConfig {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> SchemeConfigBuilder {
+        SchemeConfigBuilder::default()
+    }
+
+    pub fn lifetime(&self) -> LifetimeTag {
+        self.lifetime
+    }
+
+    pub fn seed(&self) -> Option<[u8; 32]> {
+        self.seed
+    }
+
+    pub fn activation_epoch(&self) -> usize {
+        self.activation_epoch
+    }
+
+    pub fn num_active_epochs(&self) -> usize {
+        self.num_active_epochs
+    }
+
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    /// Error unless `epoch` lies inside the activation window.
+    pub fn check_epoch(&self, epoch: u32) -> Result<(), Box<dyn Error>> {
+        let start = self.activation_epoch as u64;
+        let end = start + self.num_active_epochs as u64;
+        if (start..end).contains(&(epoch as u64)) {
+            Ok(())
+        } else {
+            Err(format!("epoch {epoch} outside activation window {start}..{end}").into())
+        }
+    }
+}
@@ -0,0 +1,97 @@
+//! Opt-in tracing for sign/verify internals
+//!
+//! Cross-language debugging needs intermediate values (randomness, hash
+//! chain heads, Poseidon states) from both sides, but printing them on every
+//! call swamps benchmark output. Sign/verify code takes a `DebugSink` and
+//! records named events with JSON payloads; the sink decides whether they go
+//! nowhere, to stderr, or to a JSON-lines file that a comparison script can
+//! load. Callers check `enabled()` before computing anything expensive.
+
+use std::env;
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use serde_json::{json, Value};
+
+/// Environment variable read by [`from_env`].
+pub const DEBUG_ENV: &str = "HASHSIG_DEBUG";
+
+/// Stderr prefix; `benchmark.py` filters lines carrying it out of its logs.
+pub const STDERR_PREFIX: &str = "RUST_VERIFY_DEBUG:";
+
+pub trait DebugSink {
+    /// Whether events are recorded at all.
+    fn enabled(&self) -> bool {
+        true
+    }
+
+    fn record(&mut self, event: &str, data: Value);
+}
+
+/// Drops everything.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NullSink;
+
+impl DebugSink for NullSink {
+    fn enabled(&self) -> bool {
+        false
+    }
+
+    fn record(&mut self, _event: &str, _data: Value) {}
+}
+
+/// One `RUST_VERIFY_DEBUG: <event> <json>` line per event on stderr.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct StderrSink;
+
+impl DebugSink for StderrSink {
+    fn record(&mut self, event: &str, data: Value) {
+        eprintln!("{STDERR_PREFIX} {event} {data}");
+    }
+}
+
+/// Appends `{"event": …, "data": …}` lines to a file.
+pub struct JsonFileSink {
+    writer: BufWriter<File>,
+}
+
+impl JsonFileSink {
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        Ok(JsonFileSink {
+            writer: BufWriter::new(File::create(path)?),
+        })
+    }
+}
+
+impl DebugSink for JsonFileSink {
+    fn record(&mut self, event: &str, data: Value) {
+        // Tracing must never fail the operation being traced.
+        let _ = writeln!(self.writer, "{}", json!({ "event": event, "data": data }));
+        let _ = self.writer.flush();
+    }
+}
+
+/// Build a sink from `off`, `stderr` or `json:<path>`.
+pub fn from_spec(spec: &str) -> Result<Box<dyn DebugSink>, Box<dyn Error>> {
+    match spec.trim() {
+        "" | "off" | "0" | "null" => Ok(Box::new(NullSink)),
+        "stderr" | "1" => Ok(Box::new(StderrSink)),
+        other => match other.strip_prefix("json:") {
+            Some(path) => Ok(Box::new(JsonFileSink::create(path)?)),
+            None => Err(format!(
+                "unsupported debug sink '{other}'. Must be one of: off, stderr, json:<path>"
+            )
+            .into()),
+        },
+    }
+}
+
+/// Sink selected by `HASHSIG_DEBUG`, or [`NullSink`] when it is unset.
+pub fn from_env() -> Result<Box<dyn DebugSink>, Box<dyn Error>> {
+    match env::var(DEBUG_ENV) {
+        Ok(spec) => from_spec(&spec),
+        Err(_) => Ok(Box::new(NullSink)),
+    }
+}
@@ -0,0 +1,84 @@
+//! KoalaBear field helpers
+//!
+//! The binary interchange format stores field elements in Montgomery form,
+//! while serde (and therefore the JSON artifacts) uses canonical form.
+//!
+//! A signature holds a few thousand field elements, so the codecs convert
+//! whole slices with [`montgomery_to_canonical_slice`] and
+//! [`canonical_to_montgomery_slice`]. Those avoid the `%` and the branches
+//! of the one-element functions (to Montgomery form is a multiplication by
+//! R^2 followed by a reduction), which lets LLVM vectorize the loops.
+
+pub const KOALABEAR_PRIME: u64 = 0x7f000001; // 2^31 - 2^24 + 1
+pub const KOALABEAR_MONTY_BITS: u32 = 32;
+
+/// Convert canonical to Montgomery form.
+pub fn canonical_to_montgomery(canonical: u32) -> u32 {
+    // to_monty: (((x as u64) << MONTY_BITS) % PRIME) as u32
+    let shifted = (canonical as u64) << KOALABEAR_MONTY_BITS;
+    (shifted % KOALABEAR_PRIME) as u32
+}
+
+/// Convert Montgomery to canonical form.
+pub fn montgomery_to_canonical(montgomery: u32) -> u32 {
+    // from_monty: monty_reduce(x as u64)
+    monty_reduce(montgomery as u64)
+}
+
+/// R^2 mod PRIME with R = 2^32; `monty_reduce(x * R2)` is `x` in Montgomery form.
+const MONTY_R2: u64 = (1u64 << 62) % KOALABEAR_PRIME * 4 % KOALABEAR_PRIME;
+
+/// Convert every element of `values` from Montgomery to canonical form.
+pub fn montgomery_to_canonical_slice(values: &mut [u32]) {
+    for value in values {
+        *value = monty_reduce_branchless(*value as u64);
+    }
+}
+
+/// Convert every element of `values` from canonical to Montgomery form.
+pub fn canonical_to_montgomery_slice(values: &mut [u32]) {
+    for value in values {
+        *value = monty_reduce_branchless(*value as u64 * MONTY_R2);
+    }
+}
+
+/// `monty_reduce` with selects instead of branches, for `x < PRIME * 2^32`.
+#[inline(always)]
+fn monty_reduce_branchless(x: u64) -> u32 {
+    const MONTY_MU: u32 = 0x81000001;
+    let t = (x as u32).wrapping_mul(MONTY_MU);
+    let u = t as u64 * KOALABEAR_PRIME;
+    let (x_sub_u, overflow) = x.overflowing_sub(u);
+    let result = (x_sub_u >> KOALABEAR_MONTY_BITS) as u32;
+    // (x - u) / 2^32 lies in (-PRIME, PRIME), so one correction is enough
+    result.wrapping_add(if overflow { KOALABEAR_PRIME as u32 } else { 0 })
+}
+
+// Montgomery reduction - converts Montgomery form to canonical
+// Algorithm: montgomery_reduce(x) = ((x - ((x * MU) & MASK) * P) >> 32) mod P
+fn monty_reduce(x: u64) -> u32 {
+    const MONTY_MU: u64 = 0x81000001; // Modular inverse of PRIME mod 2^32
+    const MONTY_MASK: u64 = 0xffffffff;
+
+    // t = (x * MU) mod 2^32
+    let t = (x.wrapping_mul(MONTY_MU)) & MONTY_MASK;
+
+    // u = t * P
+    let u = t.wrapping_mul(KOALABEAR_PRIME);
+
+    // result = (x - u) >> 32, handling underflow
+    let (x_sub_u, overflow) = x.overflowing_sub(u);
+    let mut result = (x_sub_u >> KOALABEAR_MONTY_BITS) as u32;
+
+    // If underflow occurred, add PRIME back
+    if overflow {
+        result = result.wrapping_add(KOALABEAR_PRIME as u32);
+    }
+
+    // Ensure result is in range [0, PRIME)
+    if result >= KOALABEAR_PRIME as u32 {
+        result -= KOALABEAR_PRIME as u32;
+    }
+
+    result
+}
@@ -0,0 +1,203 @@
+//! Encode and decode cost of each wire format
+//!
+//! `size` answers how many bytes a format spends; the consensus spec's
+//! wire-format decision also needs how long each takes to produce and to
+//! parse. [`measure`] generates a key pair and a signature, then encodes and
+//! decodes each artifact in every format of [`FORMATS`] `iterations` times
+//! and keeps the medians. JSON, SSZ and the Zig binary format go through
+//! `compat::codec` as the tool's own files do (JSON signatures are the
+//! leanSignature wire layout). Bincode is the upstream types' serde
+//! encoding, as the gossip corpus uses for signatures and `sk_binary` for
+//! secret keys. The Zig binary format has no secret key layout, so that
+//! combination is left out.
+
+use std::error::Error;
+use std::time::{Duration, Instant};
+
+use rand::{rngs::StdRng, SeedableRng};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{json, Value};
+use ssz::{Decode, Encode};
+
+use crate::cancel;
+use crate::compat::codec;
+use crate::config::Format;
+use crate::lifetime::SchemeMetadata;
+use crate::stats::Summary;
+use crate::upstream::SignatureSchemeSecretKey;
+
+/// Formats compared, in table order.
+pub const FORMATS: [Format; 4] = [
+    Format::Json,
+    Format::Bincode,
+    Format::Ssz,
+    Format::ZigBinary,
+];
+
+/// Active epochs of the key the artifacts come from.
+pub const NUM_ACTIVE_EPOCHS: usize = 256;
+
+/// One artifact in one format.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FormatTiming {
+    /// `pk`, `sig` or `sk`
+    pub artifact: &'static str,
+    pub format: Format,
+    pub bytes: usize,
+    /// Median of the encodes
+    pub encode: Duration,
+    /// Median of the decodes
+    pub decode: Duration,
+}
+
+impl FormatTiming {
+    pub fn to_json(&self) -> Value {
+        json!({
+            "artifact": self.artifact,
+            "format": self.format.as_str(),
+            "bytes": self.bytes,
+            "encode_us": self.encode.as_secs_f64() * 1e6,
+            "decode_us": self.decode.as_secs_f64() * 1e6,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FormatReport {
+    pub iterations: usize,
+    pub rows: Vec<FormatTiming>,
+}
+
+impl FormatReport {
+    pub fn to_json(&self) -> Value {
+        json!({
+            "iterations": self.iterations,
+            "rows": self.rows.iter().map(FormatTiming::to_json).collect::<Vec<_>>(),
+        })
+    }
+
+    /// Fixed-width table, one row per artifact and format.
+    pub fn table(&self) -> String {
+        let mut out = format!(
+            "{:<8} {:<8} {:>10} {:>12} {:>12}\n",
+            "artifact", "format", "bytes", "encode_us", "decode_us"
+        );
+        for row in &self.rows {
+            out.push_str(&format!(
+                "{:<8} {:<8} {:>10} {:>12.1} {:>12.1}\n",
+                row.artifact,
+                row.format.as_str(),
+                row.bytes,
+                row.encode.as_secs_f64() * 1e6,
+                row.decode.as_secs_f64() * 1e6
+            ));
+        }
+        out
+    }
+}
+
+/// Median time of `iterations` calls of `work`, and the last call's result.
+fn median<T>(
+    iterations: usize,
+    mut work: impl FnMut() -> Result<T, Box<dyn Error>>,
+) -> Result<(Duration, T), Box<dyn Error>> {
+    let mut samples = Vec::with_capacity(iterations);
+    let mut last = None;
+    for _ in 0..iterations.max(1) {
+        let start = Instant::now();
+        let result = work()?;
+        samples.push(start.elapsed());
+        last = Some(result);
+    }
+    let median = Summary::of(&samples).map_or(Duration::ZERO, |summary| summary.median);
+    Ok((median, last.expect("at least one iteration")))
+}
+
+/// Time encoding `value` and decoding the result back in `format`.
+fn time_artifact<T>(
+    artifact: &'static str,
+    format: Format,
+    iterations: usize,
+    encode: impl Fn(Format) -> Result<Vec<u8>, Box<dyn Error>>,
+    decode: impl Fn(&[u8], Format) -> Result<T, Box<dyn Error>>,
+) -> Result<FormatTiming, Box<dyn Error>> {
+    let (encode, bytes) = median(iterations, || encode(format))
+        .map_err(|e| format!("encoding {artifact} as {format}: {e}"))?;
+    let (decode, _) = median(iterations, || decode(&bytes, format))
+        .map_err(|e| format!("decoding {artifact} from {format}: {e}"))?;
+    Ok(FormatTiming {
+        artifact,
+        format,
+        bytes: bytes.len(),
+        encode,
+        decode,
+    })
+}
+
+/// Generate a key pair from `seed`, sign one message at the first prepared
+/// epoch and time every artifact in every format. When [`cancel`]led, the
+/// report holds the rows measured so far.
+pub fn measure<S>(seed: [u8; 32], iterations: usize) -> Result<FormatReport, Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::PublicKey: Serialize + DeserializeOwned + Encode + Decode,
+    S::SecretKey: SignatureSchemeSecretKey + Serialize + DeserializeOwned + Encode + Decode,
+    S::Signature: Serialize + DeserializeOwned + Encode + Decode,
+{
+    let mut rng = StdRng::from_seed(seed);
+    let (pk, sk) = S::key_gen(&mut rng, 0, NUM_ACTIVE_EPOCHS);
+    let epoch = sk.get_prepared_interval().start as u32;
+    let sig = S::sign(&sk, epoch, &[0u8; 32])
+        .map_err(|e| format!("failed to sign message at epoch {epoch}: {e:?}"))?;
+
+    let mut report = FormatReport {
+        iterations,
+        rows: Vec::new(),
+    };
+    let _scope = cancel::Scope::enter();
+    for format in FORMATS {
+        if cancel::is_cancelled() {
+            break;
+        }
+        report.rows.push(time_artifact(
+            "pk",
+            format,
+            iterations,
+            |format| match format {
+                Format::Bincode => Ok(bincode::serialize(&pk)?),
+                format => codec::encode_public_key::<S>(&pk, format),
+            },
+            |bytes, format| match format {
+                Format::Bincode => Ok(bincode::deserialize::<S::PublicKey>(bytes)?),
+                format => codec::decode_public_key::<S>(bytes, format),
+            },
+        )?);
+        report.rows.push(time_artifact(
+            "sig",
+            format,
+            iterations,
+            |format| match format {
+                Format::Bincode => Ok(bincode::serialize(&sig)?),
+                format => codec::encode_signature::<S>(&sig, format),
+            },
+            |bytes, format| match format {
+                Format::Bincode => Ok(bincode::deserialize::<S::Signature>(bytes)?),
+                format => codec::decode_signature::<S>(bytes, format),
+            },
+        )?);
+        if format != Format::ZigBinary {
+            report.rows.push(time_artifact(
+                "sk",
+                format,
+                iterations,
+                |format| codec::encode_secret_key::<S>(&sk, format),
+                |bytes, format| codec::decode_secret_key::<S>(bytes, format),
+            )?);
+        }
+    }
+    // Grouped by artifact, as the size tables are
+    report
+        .rows
+        .sort_by_key(|row| ["pk", "sig", "sk"].iter().position(|a| *a == row.artifact));
+    Ok(report)
+}
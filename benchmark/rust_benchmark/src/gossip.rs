@@ -0,0 +1,295 @@
+//! Seeded adversarial corpora for gossip-style verification
+//!
+//! A gossip validator sees a mix of fresh signatures, replays of signatures it
+//! already accepted, signatures presented for the wrong epoch, and garbage
+//! bytes. `generate_corpus` produces such a stream deterministically from a
+//! seed and `simulate` runs it through a validator with a seen-cache,
+//! reporting throughput and how often the accept/reject decision was right.
+
+use std::collections::HashSet;
+use std::error::Error;
+use std::ops::Range;
+use std::time::{Duration, Instant};
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+use crate::upstream::{SignatureScheme, SignatureSchemeSecretKey};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GossipKind {
+    Valid,
+    StaleEpoch,
+    Malformed,
+    Duplicate,
+}
+
+impl GossipKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GossipKind::Valid => "valid",
+            GossipKind::StaleEpoch => "stale",
+            GossipKind::Malformed => "malformed",
+            GossipKind::Duplicate => "duplicate",
+        }
+    }
+}
+
+/// Relative weights of each item kind in a generated corpus.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GossipMix {
+    pub valid: f64,
+    pub stale_epoch: f64,
+    pub malformed: f64,
+    pub duplicate: f64,
+}
+
+impl Default for GossipMix {
+    fn default() -> Self {
+        Self {
+            valid: 0.7,
+            stale_epoch: 0.1,
+            malformed: 0.1,
+            duplicate: 0.1,
+        }
+    }
+}
+
+impl GossipMix {
+    /// Parse `valid=0.7,stale=0.1,malformed=0.1,duplicate=0.1`. Omitted kinds
+    /// get weight zero; weights need not sum to one.
+    pub fn parse(spec: &str) -> Result<Self, Box<dyn Error>> {
+        let mut mix = Self {
+            valid: 0.0,
+            stale_epoch: 0.0,
+            malformed: 0.0,
+            duplicate: 0.0,
+        };
+        for part in spec.split(',').filter(|p| !p.trim().is_empty()) {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| format!("invalid mix entry '{part}', expected kind=weight"))?;
+            let weight: f64 = value.trim().parse()?;
+            if !(weight >= 0.0 && weight.is_finite()) {
+                return Err(format!("invalid weight for '{key}': {weight}").into());
+            }
+            match key.trim() {
+                "valid" => mix.valid = weight,
+                "stale" => mix.stale_epoch = weight,
+                "malformed" => mix.malformed = weight,
+                "duplicate" => mix.duplicate = weight,
+                other => return Err(format!("unknown gossip item kind '{other}'").into()),
+            }
+        }
+        if mix.total() == 0.0 {
+            return Err("gossip mix must have at least one non-zero weight".into());
+        }
+        Ok(mix)
+    }
+
+    fn total(&self) -> f64 {
+        self.valid + self.stale_epoch + self.malformed + self.duplicate
+    }
+
+    fn pick<R: Rng>(&self, rng: &mut R) -> GossipKind {
+        let mut r = rng.random::<f64>() * self.total();
+        for (kind, weight) in [
+            (GossipKind::Valid, self.valid),
+            (GossipKind::StaleEpoch, self.stale_epoch),
+            (GossipKind::Malformed, self.malformed),
+        ] {
+            if r < weight {
+                return kind;
+            }
+            r -= weight;
+        }
+        GossipKind::Duplicate
+    }
+}
+
+/// One gossip message: a claimed epoch, message and encoded signature.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GossipItem {
+    pub kind: GossipKind,
+    pub epoch: u32,
+    pub message: [u8; 32],
+    /// bincode-encoded signature (possibly corrupted)
+    pub signature: Vec<u8>,
+}
+
+impl GossipItem {
+    /// Whether a correct validator should accept this item.
+    pub fn should_accept(&self) -> bool {
+        self.kind == GossipKind::Valid
+    }
+}
+
+fn sign_at<S>(
+    sk: &mut S::SecretKey,
+    epoch: u32,
+    message: &[u8; 32],
+) -> Result<Vec<u8>, Box<dyn Error>>
+where
+    S: SignatureScheme,
+    S::SecretKey: SignatureSchemeSecretKey,
+    S::Signature: serde::Serialize,
+{
+    while !sk.get_prepared_interval().contains(&(epoch as u64)) {
+        sk.advance_preparation();
+    }
+    let signature = S::sign(sk, epoch, message)
+        .map_err(|e| format!("failed to sign message at epoch {epoch}: {e:?}"))?;
+    Ok(bincode::serialize(&signature)?)
+}
+
+/// Generate `count` items signed by `sk` at epochs drawn from `epochs`.
+///
+/// Epochs are visited in increasing order so preparation only moves forward.
+pub fn generate_corpus<S>(
+    sk: &mut S::SecretKey,
+    seed: [u8; 32],
+    count: usize,
+    epochs: Range<u32>,
+    mix: &GossipMix,
+) -> Result<Vec<GossipItem>, Box<dyn Error>>
+where
+    S: SignatureScheme,
+    S::SecretKey: SignatureSchemeSecretKey,
+    S::Signature: serde::Serialize,
+{
+    if epochs.end.saturating_sub(epochs.start) < 2 {
+        return Err("gossip corpus needs at least two epochs for stale-epoch items".into());
+    }
+    let mut rng = ChaCha20Rng::from_seed(seed);
+    let mut plan: Vec<(GossipKind, u32)> = (0..count)
+        .map(|_| (mix.pick(&mut rng), rng.random_range(epochs.clone())))
+        .collect();
+    plan.sort_by_key(|(_, epoch)| *epoch);
+
+    let mut items: Vec<GossipItem> = Vec::with_capacity(count);
+    let mut valid_indices = Vec::new();
+    for (kind, epoch) in plan {
+        let mut message = [0u8; 32];
+        rng.fill(&mut message);
+        let item = match kind {
+            GossipKind::Duplicate if !valid_indices.is_empty() => {
+                let original: &GossipItem =
+                    &items[valid_indices[rng.random_range(0..valid_indices.len())]];
+                GossipItem {
+                    kind,
+                    ..original.clone()
+                }
+            }
+            GossipKind::Valid | GossipKind::Duplicate => {
+                valid_indices.push(items.len());
+                GossipItem {
+                    kind: GossipKind::Valid,
+                    epoch,
+                    message,
+                    signature: sign_at::<S>(sk, epoch, &message)?,
+                }
+            }
+            GossipKind::StaleEpoch => {
+                let claimed = if epoch > epochs.start {
+                    epoch - 1
+                } else {
+                    epoch + 1
+                };
+                GossipItem {
+                    kind,
+                    epoch: claimed,
+                    message,
+                    signature: sign_at::<S>(sk, epoch, &message)?,
+                }
+            }
+            GossipKind::Malformed => {
+                let mut signature = sign_at::<S>(sk, epoch, &message)?;
+                if rng.random_bool(0.5) {
+                    let keep = rng.random_range(0..signature.len());
+                    signature.truncate(keep);
+                } else {
+                    let pos = rng.random_range(0..signature.len());
+                    signature[pos] ^= 1 << rng.random_range(0..8);
+                }
+                GossipItem {
+                    kind,
+                    epoch,
+                    message,
+                    signature,
+                }
+            }
+        };
+        items.push(item);
+    }
+    Ok(items)
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct GossipReport {
+    pub total: usize,
+    pub accepted: usize,
+    pub rejected: usize,
+    pub false_accepts: usize,
+    pub false_rejects: usize,
+    pub elapsed: Duration,
+}
+
+impl GossipReport {
+    pub fn throughput(&self) -> f64 {
+        self.total as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+
+    pub fn accuracy(&self) -> f64 {
+        if self.total == 0 {
+            return 1.0;
+        }
+        (self.total - self.false_accepts - self.false_rejects) as f64 / self.total as f64
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "total": self.total,
+            "accepted": self.accepted,
+            "rejected": self.rejected,
+            "false_accepts": self.false_accepts,
+            "false_rejects": self.false_rejects,
+            "elapsed_seconds": self.elapsed.as_secs_f64(),
+            "throughput_per_second": self.throughput(),
+            "accuracy": self.accuracy(),
+        })
+    }
+}
+
+/// Run the corpus through a validator that drops replays and then verifies.
+pub fn simulate<S>(pk: &S::PublicKey, items: &[GossipItem]) -> GossipReport
+where
+    S: SignatureScheme,
+    S::Signature: serde::de::DeserializeOwned,
+{
+    let mut seen: HashSet<(u32, [u8; 32], &[u8])> = HashSet::with_capacity(items.len());
+    let mut report = GossipReport {
+        total: items.len(),
+        ..Default::default()
+    };
+    let start = Instant::now();
+    for item in items {
+        let accepted = seen.insert((item.epoch, item.message, item.signature.as_slice()))
+            && bincode::deserialize::<S::Signature>(&item.signature)
+                .map(|signature| S::verify(pk, item.epoch, &item.message, &signature))
+                .unwrap_or(false);
+        match (accepted, item.should_accept()) {
+            (true, true) => report.accepted += 1,
+            (true, false) => {
+                report.accepted += 1;
+                report.false_accepts += 1;
+            }
+            (false, true) => {
+                report.rejected += 1;
+                report.false_rejects += 1;
+            }
+            (false, false) => report.rejected += 1,
+        }
+    }
+    report.elapsed = start.elapsed();
+    report
+}
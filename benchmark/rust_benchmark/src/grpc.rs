@@ -0,0 +1,236 @@
+//! gRPC service over the reference implementation
+//!
+//! Serves `hashsig.v1.HashSig` (see `proto/hashsig.proto`) so Zig, Go and
+//! Python clients in the CI farm can drive keygen, sign and verify through one
+//! interface instead of each shelling out to the CLIs. Keys and signatures
+//! are SSZ bytes tagged with their lifetime; the service keeps no state, so a
+//! secret key is sent with every `Sign` call.
+//!
+//! Scheme operations run on tokio's blocking pool: a single 2^18 verify is
+//! milliseconds of hashing and would otherwise stall the async workers.
+
+// `tonic::Status` is large, but it is the error type the generated trait
+// requires, so boxing it here would only move the conversion around.
+#![allow(clippy::result_large_err)]
+
+use std::error::Error;
+
+use rand::{rngs::StdRng, SeedableRng};
+use tonic::{Request, Response, Status};
+
+use crate::compat::codec;
+use crate::config::{Format, SchemeConfig};
+use crate::lifetime::{LifetimeTag, SchemeMetadata};
+use crate::signer::prepare_epoch;
+use crate::upstream::{
+    SIGTopLevelTargetSumLifetime18Dim64Base8, SIGTopLevelTargetSumLifetime32Dim64Base8,
+    SIGTopLevelTargetSumLifetime8Dim64Base8, SignatureSchemeSecretKey,
+};
+
+pub mod proto {
+    tonic::include_proto!("hashsig.v1");
+}
+
+use proto::hash_sig_server::HashSig;
+pub use proto::hash_sig_server::HashSigServer;
+use proto::{
+    KeyGenRequest, KeyGenResponse, PublicKey, SecretKey, SignRequest, SignResponse, Signature,
+    VerifyRequest, VerifyResponse,
+};
+
+fn lifetime_from_log(log_lifetime: u32) -> Result<LifetimeTag, Status> {
+    LifetimeTag::ALL
+        .into_iter()
+        .find(|tag| tag.log_lifetime() == log_lifetime)
+        .ok_or_else(|| {
+            Status::invalid_argument(format!(
+                "unsupported log_lifetime {log_lifetime}; expected 8, 18 or 32"
+            ))
+        })
+}
+
+fn invalid(e: Box<dyn Error>) -> Status {
+    Status::invalid_argument(e.to_string())
+}
+
+/// Run a scheme operation on the blocking pool.
+async fn blocking<T: Send + 'static>(
+    f: impl FnOnce() -> Result<T, Status> + Send + 'static,
+) -> Result<T, Status> {
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| Status::internal(format!("worker failed: {e}")))?
+}
+
+pub fn keygen_for_scheme<S: SchemeMetadata>(
+    config: &SchemeConfig,
+) -> Result<(Vec<u8>, Vec<u8>), Box<dyn Error>> {
+    let mut rng = StdRng::from_seed(config.seed().ok_or("keygen requires a seed")?);
+    let (pk, sk) = S::key_gen(
+        &mut rng,
+        config.activation_epoch(),
+        config.num_active_epochs(),
+    );
+    Ok((
+        codec::encode_public_key::<S>(&pk, Format::Ssz)?,
+        codec::encode_secret_key::<S>(&sk, Format::Ssz)?,
+    ))
+}
+
+pub fn sign_for_scheme<S: SchemeMetadata>(
+    sk: &[u8],
+    epoch: u32,
+    message: &[u8; 32],
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut sk = codec::decode_secret_key::<S>(sk, Format::Ssz)?;
+    let activation = sk.get_activation_interval();
+    if !activation.contains(&(epoch as u64)) {
+        return Err(format!("epoch {epoch} outside activation interval {activation:?}").into());
+    }
+    if !prepare_epoch(&mut sk, epoch as u64) {
+        return Err(format!("epoch {epoch} cannot be prepared").into());
+    }
+    let signature = S::sign(&sk, epoch, message)
+        .map_err(|e| format!("failed to sign message at epoch {epoch}: {e:?}"))?;
+    codec::encode_signature::<S>(&signature, Format::Ssz)
+}
+
+pub fn verify_for_scheme<S: SchemeMetadata>(
+    pk: &[u8],
+    epoch: u32,
+    message: &[u8; 32],
+    sig: &[u8],
+) -> Result<bool, Box<dyn Error>> {
+    let pk = codec::decode_public_key::<S>(pk, Format::Ssz)?;
+    let sig = codec::decode_signature::<S>(sig, Format::Ssz)?;
+    Ok(S::verify(&pk, epoch, message, &sig))
+}
+
+fn keygen(request: KeyGenRequest) -> Result<KeyGenResponse, Status> {
+    let lifetime = lifetime_from_log(request.log_lifetime)?;
+    let seed: [u8; 32] = request
+        .seed
+        .as_slice()
+        .try_into()
+        .map_err(|_| Status::invalid_argument("seed must be 32 bytes"))?;
+    let config = SchemeConfig::new()
+        .lifetime(lifetime)
+        .seed(seed)
+        .activation(
+            request.activation_epoch as usize,
+            request.num_active_epochs as usize,
+        )
+        .format(Format::Ssz)
+        .build()
+        .map_err(invalid)?;
+    let (pk, sk) = match lifetime {
+        LifetimeTag::Pow8 => keygen_for_scheme::<SIGTopLevelTargetSumLifetime8Dim64Base8>(&config),
+        LifetimeTag::Pow18 => {
+            keygen_for_scheme::<SIGTopLevelTargetSumLifetime18Dim64Base8>(&config)
+        }
+        LifetimeTag::Pow32 => {
+            keygen_for_scheme::<SIGTopLevelTargetSumLifetime32Dim64Base8>(&config)
+        }
+    }
+    .map_err(|e| Status::internal(e.to_string()))?;
+    Ok(KeyGenResponse {
+        public_key: Some(PublicKey {
+            log_lifetime: request.log_lifetime,
+            ssz: pk,
+        }),
+        secret_key: Some(SecretKey {
+            log_lifetime: request.log_lifetime,
+            ssz: sk,
+        }),
+    })
+}
+
+fn sign(request: SignRequest) -> Result<SignResponse, Status> {
+    let sk = request
+        .secret_key
+        .ok_or_else(|| Status::invalid_argument("missing secret_key"))?;
+    let epoch = request
+        .epoch
+        .ok_or_else(|| Status::invalid_argument("missing epoch"))?
+        .value;
+    let message = codec::message_from_bytes(&request.message).map_err(invalid)?;
+    let sig = match lifetime_from_log(sk.log_lifetime)? {
+        LifetimeTag::Pow8 => {
+            sign_for_scheme::<SIGTopLevelTargetSumLifetime8Dim64Base8>(&sk.ssz, epoch, &message)
+        }
+        LifetimeTag::Pow18 => {
+            sign_for_scheme::<SIGTopLevelTargetSumLifetime18Dim64Base8>(&sk.ssz, epoch, &message)
+        }
+        LifetimeTag::Pow32 => {
+            sign_for_scheme::<SIGTopLevelTargetSumLifetime32Dim64Base8>(&sk.ssz, epoch, &message)
+        }
+    }
+    .map_err(|e| Status::failed_precondition(e.to_string()))?;
+    Ok(SignResponse {
+        signature: Some(Signature {
+            log_lifetime: sk.log_lifetime,
+            ssz: sig,
+        }),
+    })
+}
+
+fn verify(request: VerifyRequest) -> Result<VerifyResponse, Status> {
+    let pk = request
+        .public_key
+        .ok_or_else(|| Status::invalid_argument("missing public_key"))?;
+    let sig = request
+        .signature
+        .ok_or_else(|| Status::invalid_argument("missing signature"))?;
+    let epoch = request
+        .epoch
+        .ok_or_else(|| Status::invalid_argument("missing epoch"))?
+        .value;
+    if pk.log_lifetime != sig.log_lifetime {
+        return Err(Status::invalid_argument(format!(
+            "public key is for 2^{} but signature is for 2^{}",
+            pk.log_lifetime, sig.log_lifetime
+        )));
+    }
+    let message = codec::message_from_bytes(&request.message).map_err(invalid)?;
+    let valid = match lifetime_from_log(pk.log_lifetime)? {
+        LifetimeTag::Pow8 => verify_for_scheme::<SIGTopLevelTargetSumLifetime8Dim64Base8>(
+            &pk.ssz, epoch, &message, &sig.ssz,
+        ),
+        LifetimeTag::Pow18 => verify_for_scheme::<SIGTopLevelTargetSumLifetime18Dim64Base8>(
+            &pk.ssz, epoch, &message, &sig.ssz,
+        ),
+        LifetimeTag::Pow32 => verify_for_scheme::<SIGTopLevelTargetSumLifetime32Dim64Base8>(
+            &pk.ssz, epoch, &message, &sig.ssz,
+        ),
+    }
+    .map_err(invalid)?;
+    Ok(VerifyResponse { valid })
+}
+
+/// Stateless `HashSig` implementation.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct HashSigService;
+
+#[tonic::async_trait]
+impl HashSig for HashSigService {
+    async fn key_gen(
+        &self,
+        request: Request<KeyGenRequest>,
+    ) -> Result<Response<KeyGenResponse>, Status> {
+        let request = request.into_inner();
+        blocking(move || keygen(request)).await.map(Response::new)
+    }
+
+    async fn sign(&self, request: Request<SignRequest>) -> Result<Response<SignResponse>, Status> {
+        let request = request.into_inner();
+        blocking(move || sign(request)).await.map(Response::new)
+    }
+
+    async fn verify(
+        &self,
+        request: Request<VerifyRequest>,
+    ) -> Result<Response<VerifyResponse>, Status> {
+        let request = request.into_inner();
+        blocking(move || verify(request)).await.map(Response::new)
+    }
+}
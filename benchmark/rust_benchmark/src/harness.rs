@@ -0,0 +1,211 @@
+//! Rust side of a cross-language compatibility run, as a library
+//!
+//! `benchmark.py` drives compatibility checks by running
+//! `cross_lang_rust_tool keygen`/`sign`, handing the files to
+//! `cross-lang-zig-tool verify` and grepping the output. `CompatHarness`
+//! does the Rust half in-process: it generates a key from the configured
+//! seed, signs, encodes the artifacts, and passes them to a peer callback
+//! (typically a closure that runs the Zig tool). Whatever the peer hands
+//! back (its verdict, and optionally its own public key and signature) is
+//! checked against the Rust implementation, so an integration test only has
+//! to supply the closure.
+//!
+//! ```ignore
+//! let mut harness = CompatHarness::<Scheme>::new(config)?;
+//! let report = harness.run(0, &message, |artifacts| {
+//!     let (pk, sig) = artifacts.write_to("tmp")?;
+//!     Ok(PeerResponse::verdict(run_zig_verify(&pk, &sig)?))
+//! })?;
+//! assert!(report.is_consistent());
+//! ```
+
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use rand::{rngs::StdRng, SeedableRng};
+use serde::{de::DeserializeOwned, Serialize};
+use ssz::{Decode, Encode};
+
+use crate::compat::codec;
+use crate::config::{Format, SchemeConfig};
+use crate::keystore::{FileKeyStore, DEFAULT_KEY_NAME};
+use crate::lifetime::SchemeMetadata;
+use crate::signer::prepare_epoch;
+use crate::upstream::SignatureSchemeSecretKey;
+
+/// Encoded Rust artifacts handed to the peer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Artifacts {
+    pub format: Format,
+    pub epoch: u32,
+    pub message: [u8; 32],
+    pub public_key: Vec<u8>,
+    pub signature: Vec<u8>,
+}
+
+impl Artifacts {
+    /// Write the public key and signature where `cross_lang_rust_tool` would
+    /// (`<dir>/rust_pk.<ext>`, `<dir>/rust_sig.<ext>`) and return both paths.
+    pub fn write_to<P: AsRef<Path>>(&self, dir: P) -> Result<(PathBuf, PathBuf), Box<dyn Error>> {
+        let store = FileKeyStore::new(dir);
+        fs::create_dir_all(store.dir())?;
+        let pk_path = store.public_key_path(DEFAULT_KEY_NAME, self.format)?;
+        let sig_path = store.signature_path(DEFAULT_KEY_NAME, self.format);
+        fs::write(&pk_path, &self.public_key)?;
+        fs::write(&sig_path, &self.signature)?;
+        Ok((pk_path, sig_path))
+    }
+}
+
+/// What the peer reports back. Artifacts are encoded in the same format as
+/// the ones it received.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PeerResponse {
+    /// Whether the peer accepted the Rust signature.
+    pub verified_rust: bool,
+    /// Public key the peer derived from the same seed, if it generated one.
+    pub public_key: Option<Vec<u8>>,
+    /// Signature the peer produced over the same epoch and message.
+    pub signature: Option<Vec<u8>>,
+}
+
+impl PeerResponse {
+    /// A response carrying only the peer's verdict on the Rust signature.
+    pub fn verdict(verified_rust: bool) -> Self {
+        PeerResponse {
+            verified_rust,
+            ..Default::default()
+        }
+    }
+}
+
+/// Outcome of one harness run. Checks the peer gave no input for are `None`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CompatReport {
+    pub peer_verifies_rust: bool,
+    pub public_keys_match: Option<bool>,
+    pub signatures_match: Option<bool>,
+    pub rust_verifies_peer: Option<bool>,
+}
+
+impl CompatReport {
+    pub fn is_consistent(&self) -> bool {
+        self.peer_verifies_rust
+            && self.public_keys_match != Some(false)
+            && self.signatures_match != Some(false)
+            && self.rust_verifies_peer != Some(false)
+    }
+}
+
+/// Rust key pair generated from a `SchemeConfig` seed, reused across runs.
+pub struct CompatHarness<S: SchemeMetadata> {
+    config: SchemeConfig,
+    public_key: S::PublicKey,
+    secret_key: S::SecretKey,
+}
+
+impl<S> CompatHarness<S>
+where
+    S: SchemeMetadata,
+    S::PublicKey: Serialize + DeserializeOwned + Encode + Decode,
+    S::Signature: Serialize + DeserializeOwned + Encode + Decode,
+{
+    /// Generate the Rust key pair. The config must carry a seed so the peer
+    /// can derive the same key.
+    pub fn new(config: SchemeConfig) -> Result<Self, Box<dyn Error>> {
+        let (public_key, secret_key) = Self::key_gen(&config)?;
+        Ok(CompatHarness {
+            config,
+            public_key,
+            secret_key,
+        })
+    }
+
+    fn key_gen(config: &SchemeConfig) -> Result<(S::PublicKey, S::SecretKey), Box<dyn Error>> {
+        let seed = config.seed().ok_or("CompatHarness requires a seed")?;
+        let mut rng = StdRng::from_seed(seed);
+        Ok(S::key_gen(
+            &mut rng,
+            config.activation_epoch(),
+            config.num_active_epochs(),
+        ))
+    }
+
+    pub fn config(&self) -> &SchemeConfig {
+        &self.config
+    }
+
+    pub fn public_key(&self) -> &S::PublicKey {
+        &self.public_key
+    }
+
+    /// Sign `message` at `epoch` and encode key and signature in the
+    /// configured format. Epochs may come in any order: one behind the
+    /// prepared window regenerates the key from the seed.
+    pub fn artifacts(
+        &mut self,
+        epoch: u32,
+        message: &[u8; 32],
+    ) -> Result<Artifacts, Box<dyn Error>> {
+        self.config.check_epoch(epoch)?;
+        if (epoch as u64) < self.secret_key.get_prepared_interval().start {
+            self.secret_key = Self::key_gen(&self.config)?.1;
+        }
+        if !prepare_epoch(&mut self.secret_key, epoch as u64) {
+            return Err(format!("epoch {epoch} cannot be prepared").into());
+        }
+        let signature = S::sign(&self.secret_key, epoch, message)
+            .map_err(|e| format!("failed to sign message at epoch {epoch}: {e:?}"))?;
+        let format = self.config.format();
+        Ok(Artifacts {
+            format,
+            epoch,
+            message: *message,
+            public_key: codec::encode_public_key::<S>(&self.public_key, format)?,
+            signature: codec::encode_signature::<S>(&signature, format)?,
+        })
+    }
+
+    /// Sign, hand the artifacts to `peer`, and check its response against
+    /// the Rust implementation.
+    pub fn run<F>(
+        &mut self,
+        epoch: u32,
+        message: &[u8; 32],
+        peer: F,
+    ) -> Result<CompatReport, Box<dyn Error>>
+    where
+        F: FnOnce(&Artifacts) -> Result<PeerResponse, Box<dyn Error>>,
+    {
+        let artifacts = self.artifacts(epoch, message)?;
+        let response = peer(&artifacts)?;
+        let format = artifacts.format;
+
+        // Re-encode the peer's key so JSON whitespace and field order do not
+        // count as a mismatch.
+        let public_keys_match = match &response.public_key {
+            Some(bytes) => {
+                let public_key = codec::decode_public_key::<S>(bytes, format)?;
+                Some(codec::encode_public_key::<S>(&public_key, format)? == artifacts.public_key)
+            }
+            None => None,
+        };
+        let rust_verifies_peer = match &response.signature {
+            Some(bytes) => {
+                let signature = codec::decode_signature::<S>(bytes, format)?;
+                Some(S::verify(&self.public_key, epoch, message, &signature))
+            }
+            None => None,
+        };
+        Ok(CompatReport {
+            peer_verifies_rust: response.verified_rust,
+            public_keys_match,
+            signatures_match: response
+                .signature
+                .as_ref()
+                .map(|sig| *sig == artifacts.signature),
+            rust_verifies_peer,
+        })
+    }
+}
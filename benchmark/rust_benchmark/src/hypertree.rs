@@ -0,0 +1,120 @@
+//! Activation windows that cover only the epochs a run needs
+//!
+//! The top-level schemes are a two-level hypertree. The lifetime L is split
+//! into √L bottom trees of √L epochs each, under a top tree over their
+//! roots. `key_gen` builds every bottom tree in the activation window to get
+//! its root. The window is rounded out to whole bottom trees, at least two.
+//! A key therefore costs what its window holds, not what the lifetime
+//! holds: a 2^32 key active for epochs 0..256 builds two trees of 65536
+//! leaves. Flat key generation, a window from 0 and then advancing to a far
+//! epoch, is what made 2^32 impractical to test end to end.
+//!
+//! [`plan`] picks the smallest window `key_gen` accepts that still holds
+//! every requested epoch. A 2^32 sign/verify round trip at epoch
+//! 3_000_000_000 then builds two bottom trees. Epochs that lie far apart
+//! still need every tree between them; [`Plan::bottom_trees`] says how many
+//! before any work starts.
+
+use std::error::Error;
+use std::ops::Range;
+
+use serde_json::{json, Value};
+
+use crate::lifetime::LifetimeTag;
+
+/// Epochs per bottom tree: 2^(log lifetime / 2).
+pub fn bottom_tree_len(lifetime: LifetimeTag) -> u64 {
+    1 << (lifetime.log_lifetime() / 2)
+}
+
+/// An activation window aligned to bottom trees.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Plan {
+    pub lifetime: LifetimeTag,
+    pub activation: Range<u64>,
+}
+
+impl Plan {
+    /// Bottom trees `key_gen` builds for this window.
+    pub fn bottom_trees(&self) -> u64 {
+        (self.activation.end - self.activation.start) / bottom_tree_len(self.lifetime)
+    }
+
+    pub fn activation_epoch(&self) -> usize {
+        self.activation.start as usize
+    }
+
+    pub fn num_active_epochs(&self) -> usize {
+        (self.activation.end - self.activation.start) as usize
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "activation_start": self.activation.start,
+            "activation_end": self.activation.end,
+            "bottom_trees": self.bottom_trees(),
+            "bottom_tree_len": bottom_tree_len(self.lifetime),
+        })
+    }
+}
+
+/// The smallest window holding every epoch of `spans`: whole bottom trees,
+/// at least two, as the upstream key generation rounds it anyway.
+pub fn plan(lifetime: LifetimeTag, spans: &[Range<u64>]) -> Result<Plan, Box<dyn Error>> {
+    let spans = spans.iter().filter(|span| !span.is_empty());
+    let first = spans
+        .clone()
+        .map(|span| span.start)
+        .min()
+        .ok_or("no epochs to plan a window for")?;
+    let last = spans.map(|span| span.end - 1).max().unwrap_or(first);
+    lifetime.check_epoch(last)?;
+    let len = bottom_tree_len(lifetime);
+    let trees = lifetime.metadata().lifetime() / len;
+    let mut start = first / len;
+    let mut end = last / len + 1;
+    if end - start < 2 {
+        if end < trees {
+            end = start + 2;
+        } else {
+            start = end - 2;
+        }
+    }
+    Ok(Plan {
+        lifetime,
+        activation: start * len..end * len,
+    })
+}
+
+/// Epochs as given on the command line: comma-separated epochs and
+/// `start..end` ranges (end exclusive), e.g. `5,9,1000..2000`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Epochs(pub Vec<Range<u64>>);
+
+impl Epochs {
+    pub fn parse(spec: &str) -> Result<Self, Box<dyn Error>> {
+        let number = |raw: &str| {
+            raw.trim()
+                .parse::<u64>()
+                .map_err(|e| format!("invalid epoch '{}': {e}", raw.trim()))
+        };
+        let mut spans = Vec::new();
+        for part in spec.split(',').filter(|part| !part.trim().is_empty()) {
+            let span = match part.split_once("..") {
+                Some((start, end)) => number(start)?..number(end)?,
+                None => {
+                    let epoch = number(part)?;
+                    epoch..epoch + 1
+                }
+            };
+            if span.is_empty() {
+                return Err(format!("empty epoch range '{}'", part.trim()).into());
+            }
+            spans.push(span);
+        }
+        if spans.is_empty() {
+            return Err("no epochs given".into());
+        }
+        Ok(Epochs(spans))
+    }
+}
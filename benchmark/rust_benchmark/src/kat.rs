@@ -0,0 +1,447 @@
+//! Known-answer test bundles for the Zig fixtures
+//!
+//! The Zig tests should pin byte-exact encodings produced by the Rust
+//! implementation rather than round-trip their own output. `generate` derives
+//! a key pair from each seed (`StdRng::from_seed`, as `keygen` does), signs a
+//! message at each requested epoch and writes every artifact in every format
+//! of `size_report::FORMATS`:
+//!
+//! ```text
+//! <out>/manifest.json
+//! <out>/<seed prefix>/pk.{json,ssz,bin,cbor,msgpack}
+//! <out>/<seed prefix>/sig_<epoch>.{wire,ssz,bin,cbor,msgpack}
+//! ```
+//!
+//! JSON signatures are the 3116-byte leanSignature wire format and get the
+//! `.wire` extension. The manifest lists, per seed, the public key's
+//! `PUBLIC_SHA3` fingerprint, its multihash and SSZ hash tree root, and per
+//! epoch the message, the signature's hash tree root and the chunks the
+//! message encodes to (one per chain, with their sum), plus the SHA3-256 of
+//! every file so fixtures can be checked without re-running keygen. The
+//! chunks localize an encoder bug that would otherwise only show as a
+//! different signature.
+//!
+//! `check_bundle` goes the other way: it re-derives each vector from the
+//! recorded seed and epochs and byte-compares every file and manifest entry,
+//! reporting the first differing byte of each file that changed.
+
+use std::error::Error;
+use std::fmt::Debug;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use rand::{rngs::StdRng, SeedableRng};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{json, Map, Value};
+use sha3::{Digest, Sha3_256};
+use ssz::Encode;
+
+use crate::compat::codec;
+use crate::compat::multihash::Multibase;
+use crate::compat::pk_info::PublicKeyInfo;
+use crate::compat::ssz_root;
+use crate::config::Format;
+use crate::lifetime::{LifetimeTag, SchemeMetadata};
+use crate::seed;
+use crate::signer::prepare_epoch;
+use crate::size_report::FORMATS;
+use crate::upstream::{
+    GeneralizedXMSSSignatureScheme, IncomparableEncoding, Pseudorandom, TweakableHash,
+};
+
+/// Name of the index written next to the per-seed directories.
+pub const MANIFEST_NAME: &str = "manifest.json";
+
+const KAT_MESSAGE_PREFIX: &[u8; 16] = b"hash-zig kat    ";
+
+/// Seeds from a seeds file: one seed per line as `seed::parse` reads it,
+/// blank lines and `#` comments skipped.
+pub fn parse_seeds(text: &str) -> Result<Vec<[u8; 32]>, Box<dyn Error>> {
+    let mut seeds = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+        let seed = seed::parse(line).map_err(|e| format!("seed on line {}: {e}", i + 1))?;
+        seeds.push(seed);
+    }
+    if seeds.is_empty() {
+        return Err("seeds file holds no seeds".into());
+    }
+    Ok(seeds)
+}
+
+/// Comma-separated epochs, e.g. `0,1,255`, sorted and deduplicated.
+pub fn parse_epochs(raw: &str) -> Result<Vec<u32>, Box<dyn Error>> {
+    let mut epochs = raw
+        .split(',')
+        .map(|epoch| {
+            epoch
+                .trim()
+                .parse()
+                .map_err(|_| format!("invalid epoch '{}'", epoch.trim()).into())
+        })
+        .collect::<Result<Vec<u32>, Box<dyn Error>>>()?;
+    epochs.sort_unstable();
+    epochs.dedup();
+    Ok(epochs)
+}
+
+/// Message signed at `epoch` for the key derived from `seed`.
+pub fn kat_message(seed: &[u8; 32], epoch: u32) -> [u8; 32] {
+    let mut hasher = Sha3_256::new();
+    hasher.update(KAT_MESSAGE_PREFIX);
+    hasher.update(seed);
+    hasher.update(epoch.to_le_bytes());
+    hasher.finalize().into()
+}
+
+/// The message encoding of an instantiation, evaluated outside of signing.
+pub trait MessageEncoding: SchemeMetadata {
+    /// Chunks `message` encodes to at `epoch` under the public key's
+    /// parameter and the signature's randomness: the chain positions the
+    /// signature reveals.
+    fn chunks(
+        public_key: &Self::PublicKey,
+        signature: &Self::Signature,
+        epoch: u32,
+        message: &[u8; 32],
+    ) -> Result<Vec<u8>, Box<dyn Error>>;
+}
+
+impl<PRF, IE, TH, const LOG_LIFETIME: usize> MessageEncoding
+    for GeneralizedXMSSSignatureScheme<PRF, IE, TH, LOG_LIFETIME>
+where
+    Self: SchemeMetadata,
+    Self::PublicKey: Serialize,
+    Self::Signature: Serialize,
+    PRF: Pseudorandom,
+    IE: IncomparableEncoding,
+    IE::Parameter: DeserializeOwned,
+    IE::Randomness: DeserializeOwned,
+    IE::Error: Debug,
+    TH: TweakableHash,
+{
+    fn chunks(
+        public_key: &Self::PublicKey,
+        signature: &Self::Signature,
+        epoch: u32,
+        message: &[u8; 32],
+    ) -> Result<Vec<u8>, Box<dyn Error>> {
+        // The fields are private upstream; serde is the way in
+        let parameter: IE::Parameter =
+            serde_json::from_value(serde_json::to_value(public_key)?["parameter"].take())?;
+        let rho: IE::Randomness =
+            serde_json::from_value(serde_json::to_value(signature)?["rho"].take())?;
+        IE::encode(&parameter, message, &rho, epoch)
+            .map_err(|e| format!("message does not encode at epoch {epoch}: {e:?}").into())
+    }
+}
+
+fn signature_extension(format: Format) -> &'static str {
+    match format {
+        Format::Json | Format::Bincode => "wire",
+        format => format.as_str(),
+    }
+}
+
+fn public_key_extension(format: Format) -> &'static str {
+    match format {
+        Format::Bincode => "json",
+        format => format.as_str(),
+    }
+}
+
+/// Artifacts of one seed: its manifest entry and the files it lists, by path
+/// relative to the bundle directory.
+struct Vector {
+    entry: Value,
+    files: Vec<(String, Vec<u8>)>,
+}
+
+impl Vector {
+    /// Record `bytes` as `prefix/name` and describe the file for the manifest.
+    fn add_file(&mut self, prefix: &str, name: String, bytes: Vec<u8>) -> Value {
+        let file = format!("{prefix}/{name}");
+        let entry = json!({
+            "file": file,
+            "len": bytes.len(),
+            "sha3_256": hex::encode(Sha3_256::digest(&bytes)),
+        });
+        self.files.push((file, bytes));
+        entry
+    }
+}
+
+/// Derive the key pair from `seed`, sign at `epochs` and encode everything.
+fn derive<S>(seed: [u8; 32], epochs: &[u32]) -> Result<Vector, Box<dyn Error>>
+where
+    S: MessageEncoding,
+    S::PublicKey: Serialize + Encode,
+    S::Signature: Serialize + Encode,
+{
+    let last = *epochs.last().ok_or("no epochs to sign at")?;
+    S::TAG.check_epoch(u64::from(last))?;
+    let seed_hex = hex::encode(seed);
+    let prefix = &seed_hex[..16];
+    let mut vector = Vector {
+        entry: Value::Null,
+        files: Vec::new(),
+    };
+
+    let num_active_epochs = last as usize + 1;
+    let mut rng = StdRng::from_seed(seed);
+    let (public_key, mut secret_key) = S::key_gen(&mut rng, 0, num_active_epochs);
+
+    let mut pk_files = Map::new();
+    for format in FORMATS {
+        let bytes = codec::encode_public_key::<S>(&public_key, format)?;
+        let name = format!("pk.{}", public_key_extension(format));
+        pk_files.insert(format.as_str().into(), vector.add_file(prefix, name, bytes));
+    }
+    let info = PublicKeyInfo::from_public_key::<S>(&public_key)?;
+
+    let mut signatures = Vec::with_capacity(epochs.len());
+    for &epoch in epochs {
+        let message = kat_message(&seed, epoch);
+        if !prepare_epoch(&mut secret_key, u64::from(epoch)) {
+            return Err(format!("epoch {epoch} cannot be prepared").into());
+        }
+        let signature = S::sign(&secret_key, epoch, &message)
+            .map_err(|e| format!("failed to sign at epoch {epoch}: {e:?}"))?;
+        if !S::verify(&public_key, epoch, &message, &signature) {
+            return Err(format!("signature at epoch {epoch} does not verify").into());
+        }
+        let chunks = S::chunks(&public_key, &signature, epoch, &message)?;
+        let mut sig_files = Map::new();
+        for format in FORMATS {
+            let bytes = codec::encode_signature::<S>(&signature, format)?;
+            let name = format!("sig_{epoch}.{}", signature_extension(format));
+            sig_files.insert(format.as_str().into(), vector.add_file(prefix, name, bytes));
+        }
+        signatures.push(json!({
+            "epoch": epoch,
+            "message": hex::encode(message),
+            "hash_tree_root": hex::encode(ssz_root::signature_hash_tree_root::<S>(&signature)?),
+            "chunk_sum": chunks.iter().map(|&chunk| u32::from(chunk)).sum::<u32>(),
+            "chunks": chunks,
+            "files": sig_files,
+        }));
+    }
+
+    vector.entry = json!({
+        "seed": seed_hex,
+        "activation_epoch": 0,
+        "num_active_epochs": num_active_epochs,
+        "public_key": {
+            "fingerprint": info.fingerprint_hex(),
+            "multihash": info.fingerprint_multibase(Multibase::default()),
+            "hash_tree_root": hex::encode(ssz_root::public_key_hash_tree_root::<S>(&public_key)?),
+            "files": pk_files,
+        },
+        "signatures": signatures,
+    });
+    Ok(vector)
+}
+
+/// Generate the vector for one seed under `out`, returning its manifest entry.
+pub fn generate<S>(seed: [u8; 32], epochs: &[u32], out: &Path) -> Result<Value, Box<dyn Error>>
+where
+    S: MessageEncoding,
+    S::PublicKey: Serialize + Encode,
+    S::Signature: Serialize + Encode,
+{
+    let vector = derive::<S>(seed, epochs)?;
+    for (file, bytes) in &vector.files {
+        let path = out.join(file);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, bytes)?;
+    }
+    Ok(vector.entry)
+}
+
+/// Generate one vector per seed and write the manifest, returning its path.
+pub fn generate_bundle<S>(
+    seeds: &[[u8; 32]],
+    epochs: &[u32],
+    out: &Path,
+) -> Result<PathBuf, Box<dyn Error>>
+where
+    S: MessageEncoding,
+    S::PublicKey: Serialize + Encode,
+    S::Signature: Serialize + Encode,
+{
+    let vectors = seeds
+        .iter()
+        .map(|&seed| generate::<S>(seed, epochs, out))
+        .collect::<Result<Vec<_>, _>>()?;
+    let manifest = json!({
+        "lifetime": S::TAG.as_str(),
+        "rng": "StdRng::from_seed",
+        "formats": FORMATS.map(|format| format.as_str()),
+        "vectors": vectors,
+    });
+    let path = out.join(MANIFEST_NAME);
+    fs::write(&path, serde_json::to_vec_pretty(&manifest)?)?;
+    Ok(path)
+}
+
+/// Read `MANIFEST_NAME` from a bundle directory.
+pub fn read_manifest(dir: &Path) -> Result<Value, Box<dyn Error>> {
+    let path = dir.join(MANIFEST_NAME);
+    let bytes = fs::read(&path)
+        .map_err(|e| io::Error::new(e.kind(), format!("{}: {e}", path.display())))?;
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
+/// Lifetime a manifest was generated for.
+pub fn manifest_lifetime(manifest: &Value) -> Result<LifetimeTag, Box<dyn Error>> {
+    let raw = manifest["lifetime"]
+        .as_str()
+        .ok_or("manifest has no lifetime")?;
+    LifetimeTag::parse(Some(raw))
+}
+
+/// One difference between a bundle and what the recorded seeds reproduce.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    /// File relative to the bundle, or `manifest.json` with a JSON pointer
+    pub location: String,
+    pub detail: String,
+}
+
+/// Outcome of `check_bundle`.
+#[derive(Debug, Clone, Default)]
+pub struct KatCheck {
+    pub vectors: usize,
+    pub files: usize,
+    pub mismatches: Vec<Mismatch>,
+}
+
+impl KatCheck {
+    pub fn passed(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+
+    /// One line per mismatch.
+    pub fn report(&self) -> String {
+        self.mismatches
+            .iter()
+            .map(|m| format!("{}: {}\n", m.location, m.detail))
+            .collect()
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "vectors": self.vectors,
+            "files": self.files,
+            "passed": self.passed(),
+            "mismatches": self.mismatches.iter().map(|m| json!({
+                "location": m.location,
+                "detail": m.detail,
+            })).collect::<Vec<_>>(),
+        })
+    }
+}
+
+/// Where two files first differ, or how their lengths do.
+fn compare_bytes(expected: &[u8], actual: &[u8]) -> Option<String> {
+    match expected.iter().zip(actual).position(|(e, a)| e != a) {
+        Some(i) => Some(format!(
+            "byte {i} is 0x{:02x}, expected 0x{:02x} ({} bytes, expected {})",
+            actual[i],
+            expected[i],
+            actual.len(),
+            expected.len()
+        )),
+        None if expected.len() != actual.len() => Some(format!(
+            "{} bytes, expected {} (common prefix matches)",
+            actual.len(),
+            expected.len()
+        )),
+        None => None,
+    }
+}
+
+/// Leaf differences between the recorded and re-derived manifest entries.
+fn compare_values(pointer: &str, expected: &Value, actual: &Value, out: &mut Vec<Mismatch>) {
+    match (expected, actual) {
+        (Value::Object(e), Value::Object(a)) => {
+            for (key, value) in e {
+                let path = format!("{pointer}/{key}");
+                compare_values(&path, value, a.get(key).unwrap_or(&Value::Null), out);
+            }
+            for key in a.keys().filter(|key| !e.contains_key(*key)) {
+                out.push(Mismatch {
+                    location: format!("{MANIFEST_NAME}{pointer}/{key}"),
+                    detail: "not produced by the generator".into(),
+                });
+            }
+        }
+        (Value::Array(e), Value::Array(a)) if e.len() == a.len() => {
+            for (i, (e, a)) in e.iter().zip(a).enumerate() {
+                compare_values(&format!("{pointer}/{i}"), e, a, out);
+            }
+        }
+        (e, a) if e != a => out.push(Mismatch {
+            location: format!("{MANIFEST_NAME}{pointer}"),
+            detail: format!("recorded {a}, expected {e}"),
+        }),
+        _ => {}
+    }
+}
+
+/// Re-derive every vector of the bundle in `dir` from its seed and epochs and
+/// compare the manifest entries and files byte for byte.
+pub fn check_bundle<S>(dir: &Path, manifest: &Value) -> Result<KatCheck, Box<dyn Error>>
+where
+    S: MessageEncoding,
+    S::PublicKey: Serialize + Encode,
+    S::Signature: Serialize + Encode,
+{
+    let vectors = manifest["vectors"]
+        .as_array()
+        .ok_or("manifest has no vectors")?;
+    let mut check = KatCheck {
+        vectors: vectors.len(),
+        ..KatCheck::default()
+    };
+    for (i, recorded) in vectors.iter().enumerate() {
+        let seed = seed::parse(recorded["seed"].as_str().unwrap_or_default())
+            .map_err(|e| format!("vector {i}: {e}"))?;
+        let epochs = recorded["signatures"]
+            .as_array()
+            .map(|sigs| {
+                sigs.iter()
+                    .filter_map(|sig| sig["epoch"].as_u64())
+                    .map(|epoch| epoch as u32)
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        let vector = derive::<S>(seed, &epochs)?;
+        compare_values(
+            &format!("/vectors/{i}"),
+            &vector.entry,
+            recorded,
+            &mut check.mismatches,
+        );
+        for (file, expected) in &vector.files {
+            check.files += 1;
+            let detail = match fs::read(dir.join(file)) {
+                Ok(actual) => compare_bytes(expected, &actual),
+                Err(e) => Some(e.to_string()),
+            };
+            if let Some(detail) = detail {
+                check.mismatches.push(Mismatch {
+                    location: file.clone(),
+                    detail,
+                });
+            }
+        }
+    }
+    Ok(check)
+}
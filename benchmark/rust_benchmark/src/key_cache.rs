@@ -0,0 +1,146 @@
+//! Generated key pairs cached on disk by seed and activation window
+//!
+//! `debug sign` (the old `remote_hashsig_tool sign`) derives its key pair
+//! from a seed on every call, and the Zig interop suite calls it once per
+//! signature. At 2^18 that is tens of seconds of `key_gen` per call for the
+//! same handful of seeds. A `KeyCache` keeps each pair it generates under a
+//! name derived from everything `key_gen` depends on:
+//!
+//! ```text
+//! sha3-256("hashzig-key-cache-v1" || backend || log lifetime || seed
+//!          || activation_epoch u64 LE || num_active_epochs u64 LE)
+//! ```
+//!
+//! and hands it back on later calls. Entries are `FileKeyStore` pairs in
+//! SSZ, named `key_<first 16 bytes of the digest in hex>`. The secret key is
+//! stored as generated, before any preparation advance, so a cached pair
+//! signs every epoch of its window. An entry that no longer decodes is
+//! generated again and overwritten.
+
+use std::env;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+use rand::{rngs::StdRng, SeedableRng};
+use sha3::{Digest, Sha3_256};
+
+use crate::compat::codec;
+use crate::config::Format;
+use crate::keystore::{FileKeyStore, KeyMetadata, SecretKeyStore, StoredKey};
+use crate::lifetime::SchemeMetadata;
+use crate::upstream::BACKEND_NAME;
+
+/// Overrides the cache directory.
+pub const DIR_ENV: &str = "HASHZIG_KEY_CACHE";
+
+const DOMAIN: &[u8] = b"hashzig-key-cache-v1";
+
+/// Where the cache lives by default: `$HASHZIG_KEY_CACHE`, else
+/// `$XDG_CACHE_HOME/hashzig/keys`, else `~/.cache/hashzig/keys`. `None`
+/// when none of these variables is set.
+pub fn default_dir() -> Option<PathBuf> {
+    let from = |name: &str| env::var_os(name).filter(|value| !value.is_empty());
+    from(DIR_ENV).map(PathBuf::from).or_else(|| {
+        from("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| from("HOME").map(|home| Path::new(&home).join(".cache")))
+            .map(|dir| dir.join("hashzig").join("keys"))
+    })
+}
+
+/// How a key pair was obtained.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lookup {
+    Hit,
+    Miss,
+}
+
+impl Lookup {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Lookup::Hit => "hit",
+            Lookup::Miss => "miss",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct KeyCache {
+    store: FileKeyStore,
+}
+
+impl KeyCache {
+    pub fn new<P: AsRef<Path>>(dir: P) -> Self {
+        KeyCache {
+            store: FileKeyStore::new(dir),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        self.store.dir()
+    }
+
+    /// Name of the entry for a `S` key pair generated from `seed` for
+    /// `num_active_epochs` epochs starting at `activation_epoch`.
+    pub fn entry_name<S: SchemeMetadata>(
+        seed: &[u8; 32],
+        activation_epoch: usize,
+        num_active_epochs: usize,
+    ) -> String {
+        let mut hasher = Sha3_256::new();
+        hasher.update(DOMAIN);
+        hasher.update(BACKEND_NAME.as_bytes());
+        hasher.update([S::TAG.log_lifetime() as u8]);
+        hasher.update(seed);
+        hasher.update((activation_epoch as u64).to_le_bytes());
+        hasher.update((num_active_epochs as u64).to_le_bytes());
+        format!("key_{}", hex::encode(&hasher.finalize()[..16]))
+    }
+
+    /// The cached pair named `name`, or `None` if there is none or it does
+    /// not decode.
+    fn load<S: SchemeMetadata>(&self, name: &str) -> Option<(S::PublicKey, S::SecretKey)> {
+        let stored = self.store.load(name).ok()?;
+        if stored.metadata.lifetime != S::TAG {
+            return None;
+        }
+        let format = stored.metadata.format;
+        let public_key = codec::decode_public_key::<S>(&stored.public_key, format).ok()?;
+        let secret_key = codec::decode_secret_key::<S>(&stored.secret_key, format).ok()?;
+        Some((public_key, secret_key))
+    }
+
+    fn save<S: SchemeMetadata>(
+        &self,
+        name: &str,
+        public_key: &S::PublicKey,
+        secret_key: &S::SecretKey,
+    ) -> Result<(), Box<dyn Error>> {
+        let key = StoredKey {
+            metadata: KeyMetadata {
+                lifetime: S::TAG,
+                format: Format::Ssz,
+            },
+            secret_key: codec::encode_secret_key::<S>(secret_key, Format::Ssz)?,
+            public_key: codec::encode_public_key::<S>(public_key, Format::Ssz)?,
+        };
+        self.store.save(name, &key)
+    }
+
+    /// `S::key_gen` seeded with `seed`, or the pair it returned last time.
+    pub fn key_gen<S: SchemeMetadata>(
+        &self,
+        seed: [u8; 32],
+        activation_epoch: usize,
+        num_active_epochs: usize,
+    ) -> Result<(S::PublicKey, S::SecretKey, Lookup), Box<dyn Error>> {
+        let name = Self::entry_name::<S>(&seed, activation_epoch, num_active_epochs);
+        if let Some((public_key, secret_key)) = self.load::<S>(&name) {
+            return Ok((public_key, secret_key, Lookup::Hit));
+        }
+        let mut rng = StdRng::from_seed(seed);
+        let (public_key, secret_key) = S::key_gen(&mut rng, activation_epoch, num_active_epochs);
+        self.save::<S>(&name, &public_key, &secret_key)?;
+        Ok((public_key, secret_key, Lookup::Miss))
+    }
+}
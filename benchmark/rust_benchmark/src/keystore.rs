@@ -0,0 +1,409 @@
+//! Named secret-key storage
+//!
+//! The CLIs used to read and write `tmp/rust_sk.json` / `tmp/rust_sk.ssz`
+//! relative to the working directory and keep the lifetime in a separate
+//! text file. A `SecretKeyStore` keeps a key pair under a name together with
+//! the lifetime and format it was encoded for, so sign and verify only need
+//! the store and the key name.
+//!
+//! `FileKeyStore` lays keys out as `<dir>/<name>_sk.<ext>` and
+//! `<dir>/<name>_pk.<ext>` with metadata in `<dir>/<name>_key.json`. With the
+//! default directory `tmp` and name `rust` these are the paths
+//! `benchmark.py` already expects. Secret keys are written inside a
+//! `compat::key_envelope`, and when one has an envelope its lifetime and
+//! format win over the metadata file, so a key copied in from elsewhere is
+//! still read for its own scheme. Key pairs written before the metadata file
+//! existed are still found through `<dir>/<name>_lifetime.txt`. The metadata
+//! file also records when and by which build a pair was generated, which
+//! `key-info` reports as the key's provenance.
+//!
+//! A store built with `compressed(true)` writes keys and signatures as
+//! `.hsz` containers (`compat::hsz`). Loading finds either variant.
+
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde_json::json;
+
+use crate::compat::hsz;
+use crate::compat::key_envelope::{self, KeyEnvelope};
+use crate::config::Format;
+use crate::lifetime::LifetimeTag;
+use crate::upstream::BACKEND_NAME;
+
+/// What a stored key pair was generated and encoded for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyMetadata {
+    pub lifetime: LifetimeTag,
+    /// Any format but `ZigBinary`, which has no key encoding.
+    pub format: Format,
+}
+
+impl From<KeyEnvelope> for KeyMetadata {
+    fn from(envelope: KeyEnvelope) -> Self {
+        KeyMetadata {
+            lifetime: envelope.lifetime,
+            format: envelope.format,
+        }
+    }
+}
+
+/// An encoded key pair and its metadata.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredKey {
+    pub metadata: KeyMetadata,
+    pub secret_key: Vec<u8>,
+    pub public_key: Vec<u8>,
+}
+
+pub trait SecretKeyStore {
+    fn save(&self, name: &str, key: &StoredKey) -> Result<(), Box<dyn Error>>;
+
+    fn load(&self, name: &str) -> Result<StoredKey, Box<dyn Error>>;
+
+    /// Replace the named secret key, e.g. after advancing its preparation,
+    /// keeping the public key. `secret_key` is encoded as `metadata` says.
+    fn replace_secret_key(
+        &self,
+        name: &str,
+        metadata: KeyMetadata,
+        secret_key: &[u8],
+    ) -> Result<(), Box<dyn Error>>;
+
+    /// Metadata of the named key without reading the key material.
+    fn metadata(&self, name: &str) -> Result<KeyMetadata, Box<dyn Error>>;
+
+    /// Names of all stored keys, sorted.
+    fn list(&self) -> Result<Vec<String>, Box<dyn Error>>;
+}
+
+fn key_extension(format: Format) -> Result<&'static str, Box<dyn Error>> {
+    match format {
+        Format::Json => Ok("json"),
+        Format::Bincode => Ok("bincode"),
+        Format::Ssz => Ok("ssz"),
+        Format::Cbor => Ok("cbor"),
+        Format::Msgpack => Ok("msgpack"),
+        Format::ZigBinary => Err("keys cannot be stored in the Zig binary layout".into()),
+    }
+}
+
+/// Extension of signature files in `format`. Key pairs stored as JSON or
+/// bincode sign into 3116-byte wire signatures, saved as `.bin`.
+pub fn signature_extension(format: Format) -> &'static str {
+    match format {
+        Format::Ssz => "ssz",
+        Format::Cbor => "cbor",
+        Format::Msgpack => "msgpack",
+        Format::Json | Format::Bincode | Format::ZigBinary => "bin",
+    }
+}
+
+/// Name of the pair a `<name>_sk.<ext>` or `<name>_pk.<ext>` file belongs
+/// to, compressed or not.
+pub fn key_name_of(path: &Path) -> Option<String> {
+    let path = hsz::inner_path(path);
+    let stem = path.file_stem()?.to_str()?;
+    stem.strip_suffix("_sk")
+        .or_else(|| stem.strip_suffix("_pk"))
+        .map(str::to_string)
+}
+
+pub const DEFAULT_KEY_DIR: &str = "tmp";
+pub const DEFAULT_KEY_NAME: &str = "rust";
+
+const METADATA_SUFFIX: &str = "_key.json";
+const LEGACY_LIFETIME_SUFFIX: &str = "_lifetime.txt";
+
+#[derive(Debug, Clone)]
+pub struct FileKeyStore {
+    dir: PathBuf,
+    signature_dir: Option<PathBuf>,
+    compress: bool,
+}
+
+impl FileKeyStore {
+    pub fn new<P: AsRef<Path>>(dir: P) -> Self {
+        FileKeyStore {
+            dir: dir.as_ref().to_path_buf(),
+            signature_dir: None,
+            compress: false,
+        }
+    }
+
+    /// Put signatures in `dir` instead of next to the keys.
+    pub fn signature_dir<P: AsRef<Path>>(mut self, dir: P) -> Self {
+        self.signature_dir = Some(dir.as_ref().to_path_buf());
+        self
+    }
+
+    /// Write keys and signatures as `.hsz` containers.
+    pub fn compressed(mut self, compress: bool) -> Self {
+        self.compress = compress;
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Whether keys and signatures are written as `.hsz` containers.
+    pub fn is_compressed(&self) -> bool {
+        self.compress
+    }
+
+    fn file_path(&self, dir: &Path, file_name: String) -> PathBuf {
+        let path = dir.join(file_name);
+        if self.compress {
+            hsz::compressed_path(path)
+        } else {
+            path
+        }
+    }
+
+    /// `path` as written, or its compressed/uncompressed counterpart if only
+    /// that one exists.
+    fn existing(path: PathBuf) -> PathBuf {
+        if path.exists() {
+            return path;
+        }
+        let other = if hsz::is_hsz_path(&path) {
+            hsz::inner_path(&path)
+        } else {
+            hsz::compressed_path(&path)
+        };
+        if other.exists() {
+            other
+        } else {
+            path
+        }
+    }
+
+    pub fn secret_key_path(&self, name: &str, format: Format) -> Result<PathBuf, Box<dyn Error>> {
+        Ok(self.file_path(&self.dir, format!("{name}_sk.{}", key_extension(format)?)))
+    }
+
+    /// Path of the named secret key as stored, compressed or not, for
+    /// streaming it with `codec::read_secret_key_file` instead of `load`.
+    pub fn stored_secret_key_path(&self, name: &str) -> Result<PathBuf, Box<dyn Error>> {
+        let format = self.recorded_metadata(name)?.format;
+        Ok(Self::existing(self.secret_key_path(name, format)?))
+    }
+
+    /// Path of the named public key as stored, compressed or not.
+    pub fn stored_public_key_path(&self, name: &str) -> Result<PathBuf, Box<dyn Error>> {
+        let format = self.recorded_metadata(name)?.format;
+        Ok(Self::existing(self.public_key_path(name, format)?))
+    }
+
+    /// `Bincode` pairs keep their public key as JSON.
+    pub fn public_key_path(&self, name: &str, format: Format) -> Result<PathBuf, Box<dyn Error>> {
+        let format = match format {
+            Format::Bincode => Format::Json,
+            format => format,
+        };
+        Ok(self.file_path(&self.dir, format!("{name}_pk.{}", key_extension(format)?)))
+    }
+
+    /// Where a signature made with the named key is written: `.ssz` for SSZ,
+    /// `.cbor` for CBOR, `.msgpack` for MessagePack, `.bin` for bincode
+    /// (`Json` and `Bincode`) and the Zig layout.
+    pub fn signature_path(&self, name: &str, format: Format) -> PathBuf {
+        let ext = signature_extension(format);
+        let dir = self.signature_dir.as_deref().unwrap_or(&self.dir);
+        self.file_path(dir, format!("{name}_sig.{ext}"))
+    }
+
+    fn metadata_path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{name}{METADATA_SUFFIX}"))
+    }
+
+    /// The named pair's metadata file as written (lifetime, format and, for
+    /// pairs generated since it was added, `created_at`, `tool` and
+    /// `backend`), or `None` for pairs without one.
+    pub fn provenance(&self, name: &str) -> Result<Option<serde_json::Value>, Box<dyn Error>> {
+        let path = self.metadata_path(name);
+        if !path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_slice(&fs::read(path)?)?))
+    }
+
+    /// Add `field` to the named pair's metadata file, for facts learned after
+    /// it was saved, such as how long generating it took.
+    pub fn annotate(
+        &self,
+        name: &str,
+        field: &str,
+        value: serde_json::Value,
+    ) -> Result<(), Box<dyn Error>> {
+        let path = self.metadata_path(name);
+        let mut metadata: serde_json::Value = serde_json::from_slice(&fs::read(&path)?)?;
+        metadata
+            .as_object_mut()
+            .ok_or_else(|| format!("{} is not a JSON object", path.display()))?
+            .insert(field.to_owned(), value);
+        fs::write(&path, serde_json::to_string_pretty(&metadata)?)?;
+        Ok(())
+    }
+
+    fn legacy_lifetime_path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{name}{LEGACY_LIFETIME_SUFFIX}"))
+    }
+
+    /// Metadata for pairs written by older tools: lifetime from the text
+    /// file (default 2^8), format from whichever secret key file exists.
+    fn legacy_metadata(&self, name: &str) -> Result<KeyMetadata, Box<dyn Error>> {
+        let lifetime = fs::read_to_string(self.legacy_lifetime_path(name))
+            .unwrap_or_else(|_| "2^8".to_string());
+        let formats = [
+            Format::Ssz,
+            Format::Json,
+            Format::Cbor,
+            Format::Msgpack,
+            Format::Bincode,
+        ];
+        let mut format = None;
+        for candidate in formats {
+            if Self::existing(self.secret_key_path(name, candidate)?).exists() {
+                format = Some(candidate);
+                break;
+            }
+        }
+        let format =
+            format.ok_or_else(|| format!("no key named '{name}' in {}", self.dir.display()))?;
+        Ok(KeyMetadata {
+            lifetime: LifetimeTag::parse(Some(lifetime.trim()))?,
+            format,
+        })
+    }
+
+    /// Metadata from the metadata file, or for older pairs the lifetime file.
+    fn recorded_metadata(&self, name: &str) -> Result<KeyMetadata, Box<dyn Error>> {
+        let path = self.metadata_path(name);
+        if !path.exists() {
+            return self.legacy_metadata(name);
+        }
+        let value: serde_json::Value = serde_json::from_slice(&fs::read(&path)?)?;
+        let field = |key: &str| {
+            value
+                .get(key)
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| format!("{} has no '{key}'", path.display()))
+        };
+        Ok(KeyMetadata {
+            lifetime: LifetimeTag::parse(Some(field("lifetime")?))?,
+            format: Format::parse(field("format")?)?,
+        })
+    }
+}
+
+impl Default for FileKeyStore {
+    fn default() -> Self {
+        FileKeyStore::new(DEFAULT_KEY_DIR)
+    }
+}
+
+impl SecretKeyStore for FileKeyStore {
+    fn save(&self, name: &str, key: &StoredKey) -> Result<(), Box<dyn Error>> {
+        let KeyMetadata { lifetime, format } = key.metadata;
+        fs::create_dir_all(&self.dir)?;
+        let envelope = KeyEnvelope::new(lifetime, format)?;
+        hsz::write(
+            self.secret_key_path(name, format)?,
+            &key_envelope::wrap(&envelope, &key.secret_key),
+        )?;
+        hsz::write(self.public_key_path(name, format)?, &key.public_key)?;
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        let metadata = json!({
+            "lifetime": lifetime.as_str(),
+            "format": format.as_str(),
+            "created_at": created_at,
+            "tool": format!("hashzig-tool {}", env!("CARGO_PKG_VERSION")),
+            "backend": BACKEND_NAME,
+        });
+        fs::write(
+            self.metadata_path(name),
+            serde_json::to_string_pretty(&metadata)?,
+        )?;
+        // A lifetime file left by an older tool would contradict the new key
+        match fs::remove_file(self.legacy_lifetime_path(name)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn load(&self, name: &str) -> Result<StoredKey, Box<dyn Error>> {
+        let recorded = self.recorded_metadata(name)?;
+        let (envelope, secret_key) =
+            key_envelope::read(Self::existing(self.secret_key_path(name, recorded.format)?))?;
+        let metadata = envelope.map_or(recorded, KeyMetadata::from);
+        Ok(StoredKey {
+            metadata,
+            secret_key,
+            public_key: hsz::read(Self::existing(self.public_key_path(name, recorded.format)?))?,
+        })
+    }
+
+    /// Written next to the old file and renamed over it, so an interrupted
+    /// write leaves the old key in place.
+    fn replace_secret_key(
+        &self,
+        name: &str,
+        metadata: KeyMetadata,
+        secret_key: &[u8],
+    ) -> Result<(), Box<dyn Error>> {
+        let path = self.stored_secret_key_path(name)?;
+        let envelope = KeyEnvelope::new(metadata.lifetime, metadata.format)?;
+        let data = key_envelope::wrap(&envelope, secret_key);
+        let data = if hsz::is_hsz_path(&path) {
+            hsz::compress(&data)?
+        } else {
+            data
+        };
+        let mut partial = path.clone().into_os_string();
+        partial.push(".partial");
+        fs::write(&partial, data)?;
+        fs::rename(&partial, &path)?;
+        Ok(())
+    }
+
+    /// From the secret key's envelope when it has one.
+    fn metadata(&self, name: &str) -> Result<KeyMetadata, Box<dyn Error>> {
+        let recorded = self.recorded_metadata(name)?;
+        let envelope = key_envelope::read_header(Self::existing(
+            self.secret_key_path(name, recorded.format)?,
+        ))?;
+        Ok(envelope.map_or(recorded, KeyMetadata::from))
+    }
+
+    fn list(&self) -> Result<Vec<String>, Box<dyn Error>> {
+        let mut names = Vec::new();
+        if !self.dir.exists() {
+            return Ok(names);
+        }
+        for entry in fs::read_dir(&self.dir)? {
+            let file_name = entry?.file_name();
+            let file_name = file_name.to_string_lossy();
+            let file_name = file_name.strip_suffix(".hsz").unwrap_or(&file_name);
+            let name = file_name
+                .strip_suffix(METADATA_SUFFIX)
+                .or_else(|| file_name.strip_suffix("_sk.json"))
+                .or_else(|| file_name.strip_suffix("_sk.ssz"))
+                .or_else(|| file_name.strip_suffix("_sk.cbor"))
+                .or_else(|| file_name.strip_suffix("_sk.msgpack"))
+                .or_else(|| file_name.strip_suffix("_sk.bincode"));
+            if let Some(name) = name {
+                names.push(name.to_string());
+            }
+        }
+        names.sort();
+        names.dedup();
+        Ok(names)
+    }
+}
@@ -0,0 +1,51 @@
+//! Shared code for the Rust side of the cross-language benchmark tools
+//!
+//! The binaries in this crate (`hashzig-tool`, its shims under the old names,
+//! and the daemons) link against this library so scheme metadata and
+//! interchange helpers are defined exactly once.
+
+pub mod allocator;
+pub mod audit;
+pub mod baseline;
+pub mod batch;
+pub mod batch_sign;
+pub mod cancel;
+#[cfg(feature = "capi")]
+pub mod capi;
+pub mod checkpoint;
+pub mod cli;
+pub mod compat;
+pub mod config;
+pub mod debug_sink;
+pub mod field;
+pub mod format_bench;
+pub mod gossip;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+pub mod harness;
+pub mod hypertree;
+pub mod kat;
+pub mod key_cache;
+pub mod keystore;
+pub mod lifetime;
+pub mod memory;
+pub mod multi_seed;
+pub mod packed_poseidon;
+pub mod phases;
+pub mod pk_cache;
+pub mod poseidon;
+pub mod regime;
+#[cfg(feature = "rustcrypto")]
+pub mod rustcrypto;
+pub mod scaling;
+pub mod seed;
+pub mod sign_throughput;
+pub mod signer;
+pub mod size_report;
+pub mod stats;
+pub mod tool;
+pub mod upstream;
+pub mod verify_load;
+pub mod verify_throughput;
+#[cfg(feature = "zig-ffi")]
+pub mod zig_ffi;
@@ -0,0 +1,204 @@
+//! Lifetime tags and the per-scheme metadata registry
+//!
+//! The interchange code trims field-element arrays to the lengths used by each
+//! instantiation. Those lengths are read off the instantiation's type
+//! parameters through [`UpstreamShape`] (the encoding's dimension and base,
+//! the sizes of the tweakable hash's domain and parameter and of the
+//! encoding's randomness), and the lifetime from `SignatureScheme::LIFETIME`.
+//! Nothing is typed in by hand, so an upstream change of lengths moves the
+//! registry with it, and adding a lifetime without registering it is a
+//! compile error rather than a silently wrong trim.
+
+use std::error::Error;
+use std::fmt;
+
+use std::mem::size_of;
+
+use p3_koala_bear::KoalaBear;
+
+use crate::upstream::{
+    GeneralizedXMSSSignatureScheme, IncomparableEncoding, Pseudorandom,
+    SIGTopLevelTargetSumLifetime18Dim64Base8, SIGTopLevelTargetSumLifetime32Dim64Base8,
+    SIGTopLevelTargetSumLifetime8Dim64Base8, SignatureScheme, TweakableHash,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LifetimeTag {
+    Pow8,
+    Pow18,
+    Pow32,
+}
+
+impl LifetimeTag {
+    pub const ALL: [LifetimeTag; 3] = [LifetimeTag::Pow8, LifetimeTag::Pow18, LifetimeTag::Pow32];
+
+    /// Parse a lifetime as accepted on the command line (`2^8`, `256`,
+    /// `lifetime_2_8`, ...). A missing value defaults to 2^8.
+    pub fn parse(raw: Option<&str>) -> Result<Self, Box<dyn Error>> {
+        let provided = raw.unwrap_or("2^8");
+        let cleaned = provided.trim().to_ascii_lowercase();
+        match cleaned.as_str() {
+            "2^8" | "256" | "lifetime_2_8" => Ok(Self::Pow8),
+            "2^18" | "262144" | "lifetime_2_18" => Ok(Self::Pow18),
+            "2^32" | "4294967296" | "lifetime_2_32" => Ok(Self::Pow32),
+            other => Err(LifetimeError(format!(
+                "unsupported lifetime '{other}'. Must be one of: 2^8, 2^18, 2^32"
+            ))
+            .into()),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LifetimeTag::Pow8 => "2^8",
+            LifetimeTag::Pow18 => "2^18",
+            LifetimeTag::Pow32 => "2^32",
+        }
+    }
+
+    pub const fn log_lifetime(&self) -> u32 {
+        match self {
+            LifetimeTag::Pow8 => 8,
+            LifetimeTag::Pow18 => 18,
+            LifetimeTag::Pow32 => 32,
+        }
+    }
+
+    /// Error unless `epoch` is below 2^log_lifetime.
+    pub fn check_epoch(&self, epoch: u64) -> Result<(), Box<dyn Error>> {
+        if epoch >> self.log_lifetime() == 0 {
+            Ok(())
+        } else {
+            Err(format!("epoch {epoch} is outside lifetime {self}").into())
+        }
+    }
+
+    pub fn metadata(&self) -> LifetimeMetadata {
+        match self {
+            LifetimeTag::Pow8 => SIGTopLevelTargetSumLifetime8Dim64Base8::METADATA,
+            LifetimeTag::Pow18 => SIGTopLevelTargetSumLifetime18Dim64Base8::METADATA,
+            LifetimeTag::Pow32 => SIGTopLevelTargetSumLifetime32Dim64Base8::METADATA,
+        }
+    }
+}
+
+impl fmt::Display for LifetimeTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A lifetime that is not one of [`LifetimeTag::ALL`], or that does not match
+/// the lifetime an artifact was made for. Kept distinct from decode errors so
+/// callers (the tool's exit codes) can tell the two apart.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LifetimeError(pub String);
+
+impl fmt::Display for LifetimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Error for LifetimeError {}
+
+/// Shape of the field-element arrays produced by one instantiation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LifetimeMetadata {
+    pub log_lifetime: u32,
+    pub dimension: usize,
+    pub base: usize,
+    pub parameter_len: usize,
+    pub rand_len: usize,
+    pub hash_len: usize,
+}
+
+impl LifetimeMetadata {
+    pub const fn lifetime(&self) -> u64 {
+        1u64 << self.log_lifetime
+    }
+}
+
+/// Array lengths fixed by the type parameters of an upstream instantiation.
+pub trait UpstreamShape {
+    const DIMENSION: usize;
+    const BASE: usize;
+    const PARAMETER_LEN: usize;
+    const RAND_LEN: usize;
+    const HASH_LEN: usize;
+}
+
+/// Field elements in a `[KoalaBear; N]`.
+const fn field_elements<T>() -> usize {
+    size_of::<T>() / size_of::<KoalaBear>()
+}
+
+impl<PRF, IE, TH, const LOG_LIFETIME: usize> UpstreamShape
+    for GeneralizedXMSSSignatureScheme<PRF, IE, TH, LOG_LIFETIME>
+where
+    PRF: Pseudorandom,
+    IE: IncomparableEncoding,
+    TH: TweakableHash,
+{
+    const DIMENSION: usize = IE::DIMENSION;
+    const BASE: usize = IE::BASE;
+    const PARAMETER_LEN: usize = field_elements::<TH::Parameter>();
+    const RAND_LEN: usize = field_elements::<IE::Randomness>();
+    const HASH_LEN: usize = field_elements::<TH::Domain>();
+}
+
+/// Registry entry tying an upstream instantiation to its tag and metadata.
+pub trait SchemeMetadata: SignatureScheme {
+    const TAG: LifetimeTag;
+    const METADATA: LifetimeMetadata;
+}
+
+macro_rules! register_scheme {
+    ($scheme:ty => $tag:expr) => {
+        impl SchemeMetadata for $scheme {
+            const TAG: LifetimeTag = $tag;
+            const METADATA: LifetimeMetadata = LifetimeMetadata {
+                log_lifetime: <$scheme as SignatureScheme>::LIFETIME.trailing_zeros(),
+                dimension: <$scheme as UpstreamShape>::DIMENSION,
+                base: <$scheme as UpstreamShape>::BASE,
+                parameter_len: <$scheme as UpstreamShape>::PARAMETER_LEN,
+                rand_len: <$scheme as UpstreamShape>::RAND_LEN,
+                hash_len: <$scheme as UpstreamShape>::HASH_LEN,
+            };
+        }
+
+        const _: () = assert!(
+            <$scheme as SignatureScheme>::LIFETIME == 1u64 << $tag.log_lifetime(),
+            "scheme registered under a tag with a different lifetime"
+        );
+    };
+}
+
+register_scheme!(SIGTopLevelTargetSumLifetime8Dim64Base8 => LifetimeTag::Pow8);
+register_scheme!(SIGTopLevelTargetSumLifetime18Dim64Base8 => LifetimeTag::Pow18);
+register_scheme!(SIGTopLevelTargetSumLifetime32Dim64Base8 => LifetimeTag::Pow32);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The Zig implementation hard-codes these lengths; an upstream change
+    /// that moves them breaks the interchange formats.
+    #[test]
+    fn derived_lengths_match_the_zig_parameters() {
+        for (tag, rand_len, hash_len) in [
+            (LifetimeTag::Pow8, 7, 8),
+            (LifetimeTag::Pow18, 6, 7),
+            (LifetimeTag::Pow32, 7, 8),
+        ] {
+            let meta = tag.metadata();
+            assert_eq!((meta.dimension, meta.base), (64, 8), "{tag}");
+            assert_eq!(meta.parameter_len, 5, "{tag}");
+            assert_eq!(
+                (meta.rand_len, meta.hash_len),
+                (rand_len, hash_len),
+                "{tag}"
+            );
+        }
+    }
+}
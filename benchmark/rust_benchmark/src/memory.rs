@@ -0,0 +1,31 @@
+//! Peak resident memory of this process
+//!
+//! Memory, not time, is what keeps 2^18 and 2^32 keygen off small CI
+//! machines. Each keygen or sign is a process of its own, so the process's
+//! high-water mark is that operation's peak. Linux keeps it as `VmHWM` in
+//! `/proc/self/status`; elsewhere there is no such file and no figure.
+
+use std::fs;
+
+/// Peak resident set size in bytes, if the platform reports it.
+pub fn peak_rss_bytes() -> Option<u64> {
+    parse_vm_hwm(&fs::read_to_string("/proc/self/status").ok()?)
+}
+
+/// The `VmHWM` line of a `/proc/<pid>/status` file, e.g.
+/// `VmHWM:	  123456 kB`, in bytes.
+pub fn parse_vm_hwm(status: &str) -> Option<u64> {
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    let kib: u64 = line["VmHWM:".len()..]
+        .trim()
+        .strip_suffix("kB")?
+        .trim()
+        .parse()
+        .ok()?;
+    Some(kib * 1024)
+}
+
+/// e.g. `812.4 MiB`
+pub fn describe(bytes: u64) -> String {
+    format!("{:.1} MiB", bytes as f64 / (1024.0 * 1024.0))
+}
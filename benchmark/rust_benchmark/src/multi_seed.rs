@@ -0,0 +1,201 @@
+//! The keygen/sign/verify pipeline over many seeds at once
+//!
+//! One seed's timings say little about the spread across keys, and a key
+//! that comes out different for the same seed is a bug no timing shows.
+//! [`run`] takes the seeds of a seeds file, runs the pipeline `runs` times
+//! per seed with several seeds in flight on a bounded rayon pool, and keeps
+//! every run's timings and public key fingerprint (the `PUBLIC_SHA3`
+//! digest). [`MultiSeedReport`] aggregates the timings over all runs and
+//! flags any seed whose fingerprint changed between its runs.
+
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::time::{Duration, Instant};
+
+use rand::{rngs::StdRng, SeedableRng};
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::batch::Parallelism;
+use crate::cancel;
+use crate::compat::pk_info::PublicKeyInfo;
+use crate::lifetime::SchemeMetadata;
+use crate::stats::Summary;
+use crate::upstream::SignatureSchemeSecretKey;
+
+/// Active epochs of each generated key.
+pub const NUM_ACTIVE_EPOCHS: usize = 256;
+
+const MESSAGE: [u8; 32] = *b"hash-zig multi-seed bench input!";
+
+/// One keygen, sign and verify.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PipelineRun {
+    pub keygen: Duration,
+    pub sign: Duration,
+    pub verify: Duration,
+    /// `PUBLIC_SHA3` of the generated public key, hex
+    pub fingerprint: String,
+    pub verified: bool,
+}
+
+/// Every run of one seed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeedResult {
+    pub seed: [u8; 32],
+    pub runs: Vec<PipelineRun>,
+}
+
+impl SeedResult {
+    /// Distinct fingerprints over the runs, sorted.
+    pub fn fingerprints(&self) -> Vec<&str> {
+        let distinct: BTreeSet<&str> = self.runs.iter().map(|r| r.fingerprint.as_str()).collect();
+        distinct.into_iter().collect()
+    }
+
+    /// Every run produced the same public key.
+    pub fn is_deterministic(&self) -> bool {
+        self.fingerprints().len() <= 1
+    }
+
+    pub fn failures(&self) -> usize {
+        self.runs.iter().filter(|run| !run.verified).count()
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "seed": hex::encode(self.seed),
+            "runs": self.runs.len(),
+            "fingerprints": self.fingerprints(),
+            "deterministic": self.is_deterministic(),
+            "failures": self.failures(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MultiSeedReport {
+    /// Seeds in flight at once
+    pub concurrency: usize,
+    pub runs_per_seed: usize,
+    pub seeds: Vec<SeedResult>,
+}
+
+impl MultiSeedReport {
+    /// Seeds whose public key changed between runs.
+    pub fn nondeterministic(&self) -> impl Iterator<Item = &SeedResult> {
+        self.seeds.iter().filter(|seed| !seed.is_deterministic())
+    }
+
+    /// Runs whose signature did not verify, over all seeds.
+    pub fn failures(&self) -> usize {
+        self.seeds.iter().map(SeedResult::failures).sum()
+    }
+
+    /// Keygen, sign and verify timings over every run of every seed.
+    pub fn summaries(&self) -> [(&'static str, Option<Summary>); 3] {
+        let runs: Vec<&PipelineRun> = self.seeds.iter().flat_map(|s| &s.runs).collect();
+        let summary = |time: fn(&PipelineRun) -> Duration| {
+            Summary::of(&runs.iter().map(|&run| time(run)).collect::<Vec<_>>())
+        };
+        [
+            ("keygen", summary(|run| run.keygen)),
+            ("sign", summary(|run| run.sign)),
+            ("verify", summary(|run| run.verify)),
+        ]
+    }
+
+    pub fn to_json(&self) -> Value {
+        let metrics: serde_json::Map<String, Value> = self
+            .summaries()
+            .into_iter()
+            .filter_map(|(name, summary)| Some((name.to_string(), summary?.to_json())))
+            .collect();
+        json!({
+            "concurrency": self.concurrency,
+            "runs_per_seed": self.runs_per_seed,
+            "metrics": metrics,
+            "failures": self.failures(),
+            "nondeterministic": self
+                .nondeterministic()
+                .map(|seed| hex::encode(seed.seed))
+                .collect::<Vec<_>>(),
+            "seeds": self.seeds.iter().map(SeedResult::to_json).collect::<Vec<_>>(),
+        })
+    }
+}
+
+fn pipeline<S>(seed: [u8; 32]) -> Result<PipelineRun, Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::SecretKey: SignatureSchemeSecretKey,
+    S::PublicKey: Serialize,
+{
+    let start = Instant::now();
+    let mut rng = StdRng::from_seed(seed);
+    let (public_key, secret_key) = S::key_gen(&mut rng, 0, NUM_ACTIVE_EPOCHS);
+    let keygen = start.elapsed();
+    let fingerprint = PublicKeyInfo::from_public_key::<S>(&public_key)?.fingerprint_hex();
+
+    let epoch = secret_key.get_prepared_interval().start as u32;
+    let start = Instant::now();
+    let signature = S::sign(&secret_key, epoch, &MESSAGE)
+        .map_err(|e| format!("failed to sign message at epoch {epoch}: {e:?}"))?;
+    let sign = start.elapsed();
+
+    let start = Instant::now();
+    let verified = S::verify(&public_key, epoch, &MESSAGE, &signature);
+    Ok(PipelineRun {
+        keygen,
+        sign,
+        verify: start.elapsed(),
+        fingerprint,
+        verified,
+    })
+}
+
+/// Run the pipeline `runs` times for each of `seeds`, `concurrency` seeds
+/// at a time (0 for rayon's global pool). When [`cancel`]led, seeds not
+/// started yet are left out and started ones keep the runs they finished.
+pub fn run<S>(
+    seeds: &[[u8; 32]],
+    runs: usize,
+    concurrency: usize,
+) -> Result<MultiSeedReport, Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::SecretKey: SignatureSchemeSecretKey,
+    S::PublicKey: Serialize,
+{
+    let parallelism = Parallelism::from_threads(concurrency);
+    let _scope = cancel::Scope::enter();
+    let results = parallelism.install(|| {
+        seeds
+            .par_iter()
+            .map(|&seed| -> Result<SeedResult, String> {
+                let mut result = SeedResult {
+                    seed,
+                    runs: Vec::with_capacity(runs),
+                };
+                for _ in 0..runs {
+                    if cancel::is_cancelled() {
+                        break;
+                    }
+                    let run = pipeline::<S>(seed)
+                        .map_err(|e| format!("seed {}: {e}", hex::encode(seed)))?;
+                    result.runs.push(run);
+                }
+                Ok(result)
+            })
+            .collect::<Result<Vec<_>, String>>()
+    })??;
+    Ok(MultiSeedReport {
+        concurrency: parallelism.threads(),
+        runs_per_seed: runs,
+        seeds: results
+            .into_iter()
+            .filter(|seed| !seed.runs.is_empty())
+            .collect(),
+    })
+}
@@ -0,0 +1,119 @@
+//! The width-24 Poseidon2 permutation over packed KoalaBear lanes
+//!
+//! Comparing Poseidon states with Zig state by state means thousands of
+//! permutations, and the tracer has always run them one state at a time.
+//! `p3_field` packs `Packing::WIDTH` KoalaBear elements into one value: 8
+//! with AVX2, 16 with AVX-512, 4 with NEON, and 1 without SIMD. The
+//! Poseidon2 layers have vectorized implementations for those values, so
+//! [`permute_packed`] runs that many states per call. [`compare`] runs both
+//! paths over the same states, so the packed results are only trusted once
+//! they match the scalar ones.
+
+use std::array;
+use std::time::{Duration, Instant};
+
+use p3_field::{Field, PackedValue, PrimeCharacteristicRing, PrimeField32};
+use p3_koala_bear::KoalaBear;
+use p3_symmetric::Permutation;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde_json::{json, Value};
+
+pub const WIDTH: usize = 24;
+
+pub type State = [KoalaBear; WIDTH];
+
+type Packed = <KoalaBear as Field>::Packing;
+
+/// States [`permute_packed`] permutes per call.
+pub fn lanes() -> usize {
+    Packed::WIDTH
+}
+
+pub fn canonical(state: &State) -> Vec<u32> {
+    state.iter().map(PrimeField32::as_canonical_u32).collect()
+}
+
+/// `count` pseudo-random states drawn from `seed`.
+pub fn random_states(seed: [u8; 32], count: usize) -> Vec<State> {
+    let mut rng = StdRng::from_seed(seed);
+    (0..count)
+        .map(|_| array::from_fn(|_| KoalaBear::from_u32(rng.random_range(0..KoalaBear::ORDER_U32))))
+        .collect()
+}
+
+/// Each state permuted on its own.
+pub fn permute_scalar<P: Permutation<State>>(perm: &P, states: &[State]) -> Vec<State> {
+    states.iter().map(|state| perm.permute(*state)).collect()
+}
+
+/// Each state permuted in a lane of a packed state, [`lanes`] at a time.
+pub fn permute_packed<P: Permutation<[Packed; WIDTH]>>(perm: &P, states: &[State]) -> Vec<State> {
+    let mut permuted = Vec::with_capacity(states.len());
+    for chunk in states.chunks(lanes()) {
+        // A short last chunk fills its spare lanes with its first state
+        let mut packed: [Packed; WIDTH] =
+            array::from_fn(|i| Packed::from_fn(|lane| chunk.get(lane).unwrap_or(&chunk[0])[i]));
+        perm.permute_mut(&mut packed);
+        permuted
+            .extend((0..chunk.len()).map(|lane| array::from_fn(|i| packed[i].as_slice()[lane])));
+    }
+    permuted
+}
+
+/// Both paths over the same states.
+#[derive(Debug, Clone)]
+pub struct Comparison {
+    pub permutations: usize,
+    pub lanes: usize,
+    pub scalar: Duration,
+    pub packed: Duration,
+    /// Indices of the states the two paths permuted differently
+    pub mismatches: Vec<usize>,
+}
+
+impl Comparison {
+    pub fn is_equal(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "permutations": self.permutations,
+            "lanes": self.lanes,
+            "scalar_ms": self.scalar.as_secs_f64() * 1000.0,
+            "simd_ms": self.packed.as_secs_f64() * 1000.0,
+            "speedup": self.scalar.as_secs_f64() / self.packed.as_secs_f64().max(f64::EPSILON),
+            "equal": self.is_equal(),
+            "first_mismatch": self.mismatches.first(),
+        })
+    }
+}
+
+/// Permute `states` both ways and return the scalar results with how the
+/// two compared.
+pub fn compare<P>(perm: &P, states: &[State]) -> (Vec<State>, Comparison)
+where
+    P: Permutation<State> + Permutation<[Packed; WIDTH]>,
+{
+    let start = Instant::now();
+    let scalar = permute_scalar(perm, states);
+    let scalar_time = start.elapsed();
+    let start = Instant::now();
+    let packed = permute_packed(perm, states);
+    let packed_time = start.elapsed();
+    let mismatches = scalar
+        .iter()
+        .zip(&packed)
+        .enumerate()
+        .filter(|(_, (scalar, packed))| scalar != packed)
+        .map(|(i, _)| i)
+        .collect();
+    let comparison = Comparison {
+        permutations: states.len(),
+        lanes: lanes(),
+        scalar: scalar_time,
+        packed: packed_time,
+        mismatches,
+    };
+    (scalar, comparison)
+}
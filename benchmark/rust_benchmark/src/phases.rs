@@ -0,0 +1,82 @@
+//! Where the time of a keygen, sign or verify goes
+//!
+//! The benchmark compares whole keygen, sign and verify runs, which says the
+//! Zig side is slower but not where. [`Phases`] times the steps of one run
+//! separately, in the order they ran:
+//!
+//! | phase       | what it covers                                           |
+//! |-------------|----------------------------------------------------------|
+//! | `key_gen`   | upstream `key_gen`: the leaves and trees of every bottom tree in the activation window, and the top tree |
+//! | `prepare`   | advancing the prepared window, one bottom tree per step |
+//! | `load`      | reading and decoding a stored secret key                 |
+//! | `sign`      | upstream `sign`                                          |
+//! | `decode`    | reading and decoding a public key and signature          |
+//! | `verify`    | upstream `verify`                                        |
+//! | `serialize` | encoding keys or signatures in the chosen format         |
+//! | `write`     | writing them to disk                                     |
+//!
+//! Leaf generation and tree construction happen inside the one upstream
+//! `key_gen` call and cannot be timed apart from outside it.
+
+use std::time::{Duration, Instant};
+
+use serde_json::{Map, Value};
+
+#[derive(Debug, Clone, Default)]
+pub struct Phases {
+    entries: Vec<(&'static str, Duration)>,
+}
+
+impl Phases {
+    pub fn new() -> Self {
+        Phases::default()
+    }
+
+    /// Run `work` and add its time to `phase`.
+    pub fn time<T>(&mut self, phase: &'static str, work: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let value = work();
+        self.add(phase, start.elapsed());
+        value
+    }
+
+    /// Add `elapsed` to `phase`, which is listed where it first ran.
+    pub fn add(&mut self, phase: &'static str, elapsed: Duration) {
+        match self.entries.iter_mut().find(|(name, _)| *name == phase) {
+            Some((_, total)) => *total += elapsed,
+            None => self.entries.push((phase, elapsed)),
+        }
+    }
+
+    pub fn get(&self, phase: &str) -> Option<Duration> {
+        self.entries
+            .iter()
+            .find(|(name, _)| *name == phase)
+            .map(|(_, elapsed)| *elapsed)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Milliseconds per phase, e.g. `{"key_gen_ms": 812.4, "serialize_ms": 3.1}`.
+    pub fn to_json(&self) -> Value {
+        let mut map = Map::new();
+        for (name, elapsed) in &self.entries {
+            map.insert(
+                format!("{name}_ms"),
+                (elapsed.as_secs_f64() * 1000.0).into(),
+            );
+        }
+        Value::Object(map)
+    }
+
+    /// One line for people, e.g. `key_gen 0.812s, serialize 0.003s`.
+    pub fn describe(&self) -> String {
+        self.entries
+            .iter()
+            .map(|(name, elapsed)| format!("{name} {:.3}s", elapsed.as_secs_f64()))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
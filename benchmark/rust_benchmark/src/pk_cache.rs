@@ -0,0 +1,128 @@
+//! Deserialized public keys keyed by fingerprint
+//!
+//! Interop runs verify thousands of signatures against a handful of keys.
+//! Parsing the JSON (or SSZ) key and rebuilding the upstream type dominates
+//! short verifies, so long-lived verifiers decode each key once and refer to
+//! it by its `PublicKeyInfo` fingerprint afterwards. The fingerprint depends
+//! only on the root, so a key loaded from JSON and from SSZ shares one entry.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+
+use ssz::Encode;
+
+use crate::compat::pk_info::PublicKeyInfo;
+use crate::compat::{codec, hsz, multihash, pem};
+use crate::config::Format;
+use crate::lifetime::SchemeMetadata;
+use crate::upstream::SignatureScheme;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Fingerprint(pub [u8; 32]);
+
+impl Fingerprint {
+    pub fn of<S>(pk: &S::PublicKey) -> Self
+    where
+        S: SchemeMetadata,
+        S::PublicKey: Encode,
+    {
+        let info = PublicKeyInfo::from_ssz_bytes(&pk.as_ssz_bytes(), S::METADATA)
+            .expect("SSZ public key has the registered length");
+        Fingerprint(info.fingerprint())
+    }
+
+    /// Hex (optionally `0x`-prefixed) or a multibase sha3-256 multihash.
+    pub fn parse(raw: &str) -> Result<Self, Box<dyn Error>> {
+        let raw = raw.trim();
+        let hex_digits = raw.trim_start_matches("0x");
+        if hex_digits.len() != 64 && multihash::is_multibase(raw) {
+            return Ok(Fingerprint(multihash::decode_sha3_256(raw)?));
+        }
+        let bytes = hex::decode(hex_digits)?;
+        let bytes: [u8; 32] = bytes
+            .try_into()
+            .map_err(|_| "fingerprint must be 32 bytes of hex")?;
+        Ok(Fingerprint(bytes))
+    }
+}
+
+impl fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0x{}", hex::encode(self.0))
+    }
+}
+
+/// A cached key together with its fingerprint.
+pub type CachedKey<S> = (Fingerprint, Arc<<S as SignatureScheme>::PublicKey>);
+
+/// Thread-safe cache of decoded public keys for one scheme.
+pub struct PublicKeyCache<S: SchemeMetadata> {
+    keys: RwLock<HashMap<Fingerprint, Arc<S::PublicKey>>>,
+}
+
+impl<S: SchemeMetadata> Default for PublicKeyCache<S> {
+    fn default() -> Self {
+        Self {
+            keys: RwLock::new(HashMap::new()),
+        }
+    }
+}
+
+impl<S> PublicKeyCache<S>
+where
+    S: SchemeMetadata,
+    S::PublicKey: Encode,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, fingerprint: &Fingerprint) -> Option<Arc<S::PublicKey>> {
+        self.keys.read().unwrap().get(fingerprint).cloned()
+    }
+
+    /// Cache `pk`, returning its fingerprint and the shared entry (the
+    /// existing one if the key was already cached).
+    pub fn insert(&self, pk: S::PublicKey) -> CachedKey<S> {
+        let fingerprint = Fingerprint::of::<S>(&pk);
+        let mut keys = self.keys.write().unwrap();
+        let entry = keys.entry(fingerprint).or_insert_with(|| Arc::new(pk));
+        (fingerprint, Arc::clone(entry))
+    }
+
+    /// Decode `bytes` in `format` and cache the key.
+    pub fn load_bytes(&self, bytes: &[u8], format: Format) -> Result<CachedKey<S>, Box<dyn Error>> {
+        Ok(self.insert(codec::decode_public_key::<S>(bytes, format)?))
+    }
+
+    /// Read a key file (PEM/DER-wrapped, SSZ for `.ssz`, JSON otherwise,
+    /// optionally in an `.hsz` container) and cache the key.
+    pub fn load_file<P: AsRef<Path>>(&self, path: P) -> Result<CachedKey<S>, Box<dyn Error>> {
+        let path = path.as_ref();
+        self.load_file_bytes(path, &hsz::read(path)?)
+    }
+
+    /// [`load_file`](Self::load_file) for a file already read, so async
+    /// callers can do the reading themselves. `path` picks the format.
+    pub fn load_file_bytes<P: AsRef<Path>>(
+        &self,
+        path: P,
+        bytes: &[u8],
+    ) -> Result<CachedKey<S>, Box<dyn Error>> {
+        if pem::is_wrapped(bytes) {
+            return Ok(self.insert(pem::decode_public_key::<S>(bytes)?));
+        }
+        self.load_bytes(bytes, Format::from_key_path(path.as_ref()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.read().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
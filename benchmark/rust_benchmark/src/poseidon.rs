@@ -0,0 +1,25 @@
+//! The Poseidon2 permutations, built once per process
+//!
+//! Building a Poseidon2 instance expands its round constants, which is
+//! cheap once but showed up in batch profiles when the tracer and
+//! `compare-poseidon` built one per call. These are built on first use from
+//! the same upstream constructors the schemes use, and shared afterwards.
+
+use std::sync::LazyLock;
+
+use p3_koala_bear::Poseidon2KoalaBear;
+
+use crate::upstream::backend;
+
+static POSEIDON2_16: LazyLock<Poseidon2KoalaBear<16>> = LazyLock::new(backend::poseidon2_16);
+static POSEIDON2_24: LazyLock<Poseidon2KoalaBear<24>> = LazyLock::new(backend::poseidon2_24);
+
+/// The width-16 permutation (chain and tree hashing).
+pub fn poseidon2_16() -> &'static Poseidon2KoalaBear<16> {
+    &POSEIDON2_16
+}
+
+/// The width-24 permutation (message hashing and leaf compression).
+pub fn poseidon2_24() -> &'static Poseidon2KoalaBear<24> {
+    &POSEIDON2_24
+}
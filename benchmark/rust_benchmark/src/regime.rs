@@ -0,0 +1,72 @@
+//! Warm and cold measurement regimes
+//!
+//! A keygen timed right after another in the same process runs from warm
+//! caches: the binary's pages, the allocator's arenas and the CPU caches are
+//! all primed. The same keygen as the first act of a fresh process, with
+//! the binary just read from disk, is what a validator restart pays. The
+//! published Rust-vs-Zig numbers used to mix the two. A [`Regime`] names
+//! which one a run measured: `Warm` repeats the operation in process after
+//! warmup runs, `Cold` runs every iteration in a fresh process after
+//! [`drop_page_cache`] evicts the binary. Results carry the regime, and
+//! baselines only compare within one.
+
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Regime {
+    /// Repeated in one process after warmup
+    #[default]
+    Warm,
+    /// One fresh process per iteration, page cache dropped first
+    Cold,
+}
+
+impl Regime {
+    pub fn parse(raw: &str) -> Result<Self, Box<dyn Error>> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "warm" => Ok(Self::Warm),
+            "cold" => Ok(Self::Cold),
+            other => Err(format!("unknown mode '{other}'. Must be one of: warm, cold").into()),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Regime::Warm => "warm",
+            Regime::Cold => "cold",
+        }
+    }
+}
+
+impl fmt::Display for Regime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Ask the kernel to drop `path`'s cached pages, so the next process to
+/// read it goes to disk. Pages still mapped by a running process stay.
+#[cfg(target_os = "linux")]
+pub fn drop_page_cache(path: &Path) -> io::Result<()> {
+    use std::os::fd::AsRawFd;
+
+    let file = std::fs::File::open(path)?;
+    // SAFETY: the descriptor is open for the whole call; offset and length 0
+    // cover the file.
+    let code = unsafe { libc::posix_fadvise(file.as_raw_fd(), 0, 0, libc::POSIX_FADV_DONTNEED) };
+    match code {
+        0 => Ok(()),
+        code => Err(io::Error::from_raw_os_error(code)),
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn drop_page_cache(_path: &Path) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "dropping the page cache needs posix_fadvise (Linux only)",
+    ))
+}
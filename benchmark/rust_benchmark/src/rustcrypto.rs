@@ -0,0 +1,120 @@
+//! RustCrypto `signature` trait adapters
+//!
+//! Hash-based signatures are stateful: every signature is bound to an epoch,
+//! which the generic `Signer`/`Verifier` traits have no room for. The adapters
+//! here carry the epoch alongside the key so existing code that is generic
+//! over those traits can drive any of the wrapped schemes.
+//!
+//! Messages follow the convention of the CLI tools: up to 32 bytes, zero
+//! padded. Longer messages are rejected rather than truncated, so hash them
+//! first if needed.
+
+use std::fmt;
+
+use signature::{Error, Signer, Verifier};
+
+use crate::upstream::{SignatureScheme, SignatureSchemeSecretKey};
+
+const MESSAGE_LENGTH: usize = 32;
+
+fn message_block(msg: &[u8]) -> Result<[u8; MESSAGE_LENGTH], Error> {
+    if msg.len() > MESSAGE_LENGTH {
+        return Err(Error::new());
+    }
+    let mut block = [0u8; MESSAGE_LENGTH];
+    block[..msg.len()].copy_from_slice(msg);
+    Ok(block)
+}
+
+/// A scheme signature together with the epoch it was produced for.
+pub struct HashSigSignature<S: SignatureScheme> {
+    pub epoch: u32,
+    pub signature: S::Signature,
+}
+
+impl<S: SignatureScheme> Clone for HashSigSignature<S>
+where
+    S::Signature: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            epoch: self.epoch,
+            signature: self.signature.clone(),
+        }
+    }
+}
+
+impl<S: SignatureScheme> fmt::Debug for HashSigSignature<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HashSigSignature")
+            .field("epoch", &self.epoch)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Signs at a fixed epoch with a borrowed secret key.
+///
+/// The epoch must already be inside the key's prepared interval; advancing
+/// preparation needs `&mut` access and stays the caller's responsibility.
+pub struct EpochSigner<'a, S: SignatureScheme> {
+    secret_key: &'a S::SecretKey,
+    epoch: u32,
+}
+
+impl<'a, S: SignatureScheme> EpochSigner<'a, S> {
+    pub fn new(secret_key: &'a S::SecretKey, epoch: u32) -> Self {
+        Self { secret_key, epoch }
+    }
+
+    pub fn epoch(&self) -> u32 {
+        self.epoch
+    }
+}
+
+impl<S> Signer<HashSigSignature<S>> for EpochSigner<'_, S>
+where
+    S: SignatureScheme,
+    S::SecretKey: SignatureSchemeSecretKey,
+{
+    fn try_sign(&self, msg: &[u8]) -> Result<HashSigSignature<S>, Error> {
+        if !self
+            .secret_key
+            .get_prepared_interval()
+            .contains(&(self.epoch as u64))
+        {
+            return Err(Error::new());
+        }
+        let block = message_block(msg)?;
+        let signature = S::sign(self.secret_key, self.epoch, &block).map_err(Error::from_source)?;
+        Ok(HashSigSignature {
+            epoch: self.epoch,
+            signature,
+        })
+    }
+}
+
+/// Verifies signatures for a fixed epoch against a borrowed public key.
+pub struct EpochVerifier<'a, S: SignatureScheme> {
+    public_key: &'a S::PublicKey,
+    epoch: u32,
+}
+
+impl<'a, S: SignatureScheme> EpochVerifier<'a, S> {
+    pub fn new(public_key: &'a S::PublicKey, epoch: u32) -> Self {
+        Self { public_key, epoch }
+    }
+}
+
+impl<S: SignatureScheme> Verifier<HashSigSignature<S>> for EpochVerifier<'_, S> {
+    fn verify(&self, msg: &[u8], signature: &HashSigSignature<S>) -> Result<(), Error> {
+        if signature.epoch != self.epoch {
+            return Err(Error::new());
+        }
+        let block = message_block(msg)?;
+        if S::verify(self.public_key, self.epoch, &block, &signature.signature) {
+            Ok(())
+        } else {
+            Err(Error::new())
+        }
+    }
+}
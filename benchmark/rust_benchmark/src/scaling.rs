@@ -0,0 +1,157 @@
+//! How keygen, sign and verify scale with the number of threads
+//!
+//! The Zig side is studied by rerunning its benchmark at 1, 2, 4, ... threads
+//! and comparing each run with the first. [`measure`] does one such run on a
+//! rayon pool of its own: a keygen, a batch of signatures signed in
+//! parallel, and the batch verified in parallel. [`ScalingReport`] puts the
+//! runs side by side with each operation's speedup over the first run and
+//! its efficiency, the speedup divided by the growth in threads. Upstream
+//! keygen parallelizes internally; one sign or verify does not, so those two
+//! scale through the batch.
+
+use std::error::Error;
+use std::time::{Duration, Instant};
+
+use rand::{rngs::StdRng, SeedableRng};
+use rayon::prelude::*;
+use serde_json::{json, Value};
+
+use crate::batch::{self, BatchItem, Parallelism};
+use crate::upstream::{SignatureScheme, SignatureSchemeSecretKey};
+
+/// Active epochs of the key each run generates.
+pub const NUM_ACTIVE_EPOCHS: usize = 256;
+
+/// Timings of one run.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScalingRow {
+    pub threads: usize,
+    pub keygen: Duration,
+    pub sign: Duration,
+    pub verify: Duration,
+}
+
+impl ScalingRow {
+    fn operations(&self) -> [(&'static str, Duration); 3] {
+        [
+            ("keygen", self.keygen),
+            ("sign", self.sign),
+            ("verify", self.verify),
+        ]
+    }
+}
+
+/// Message signed at position `index` of a batch.
+fn message(index: usize) -> [u8; 32] {
+    let mut message = [0u8; 32];
+    message[..24].copy_from_slice(b"hash-zig scaling message");
+    message[24..].copy_from_slice(&(index as u64).to_le_bytes());
+    message
+}
+
+/// Keygen from `seed`, sign `signatures` messages and verify them, all on a
+/// pool of `threads` threads (0 for one per core).
+pub fn measure<S>(
+    seed: [u8; 32],
+    threads: usize,
+    signatures: usize,
+) -> Result<ScalingRow, Box<dyn Error>>
+where
+    S: SignatureScheme,
+    S::SecretKey: SignatureSchemeSecretKey + Sync,
+    S::PublicKey: Sync,
+    S::Signature: Send + Sync,
+{
+    let parallelism = Parallelism::Threads(threads);
+    let row = parallelism.install(|| -> Result<ScalingRow, String> {
+        let start = Instant::now();
+        let mut rng = StdRng::from_seed(seed);
+        let (public_key, secret_key) = S::key_gen(&mut rng, 0, NUM_ACTIVE_EPOCHS);
+        let keygen = start.elapsed();
+
+        // Epochs of the prepared window, round robin, so nothing is advanced
+        let prepared = secret_key.get_prepared_interval();
+        let epochs: Vec<u32> = prepared
+            .cycle()
+            .take(signatures)
+            .map(|epoch| epoch as u32)
+            .collect();
+        let start = Instant::now();
+        let items = epochs
+            .par_iter()
+            .enumerate()
+            .map(|(index, &epoch)| {
+                let message = message(index);
+                S::sign(&secret_key, epoch, &message)
+                    .map(|signature| (epoch, message, signature))
+                    .map_err(|e| format!("failed to sign message at epoch {epoch}: {e:?}"))
+            })
+            .collect::<Result<Vec<BatchItem<S>>, String>>()?;
+        let sign = start.elapsed();
+
+        let start = Instant::now();
+        let valid = batch::verify_batch::<S>(&public_key, &items);
+        let verify = start.elapsed();
+        if let Some(index) = valid.iter().position(|valid| !valid) {
+            return Err(format!(
+                "signature {index} (epoch {}) did not verify",
+                items[index].0
+            ));
+        }
+        Ok(ScalingRow {
+            threads: parallelism.threads(),
+            keygen,
+            sign,
+            verify,
+        })
+    })??;
+    Ok(row)
+}
+
+/// Runs at several thread counts, compared with the first.
+#[derive(Debug, Clone, Default)]
+pub struct ScalingReport {
+    pub signatures: usize,
+    pub rows: Vec<ScalingRow>,
+}
+
+impl ScalingReport {
+    /// `row`'s speedup over the first run for each operation, and that
+    /// speedup per thread added.
+    pub fn scaling(&self, row: &ScalingRow) -> [(&'static str, f64, f64); 3] {
+        let base = self.rows.first().unwrap_or(row);
+        let growth = row.threads as f64 / base.threads as f64;
+        let mut scaling = [("", 0.0, 0.0); 3];
+        for (slot, ((name, elapsed), (_, base_elapsed))) in scaling
+            .iter_mut()
+            .zip(row.operations().into_iter().zip(base.operations()))
+        {
+            let speedup = base_elapsed.as_secs_f64() / elapsed.as_secs_f64().max(f64::EPSILON);
+            *slot = (name, speedup, speedup / growth);
+        }
+        scaling
+    }
+
+    pub fn to_json(&self) -> Value {
+        let rows: Vec<Value> = self
+            .rows
+            .iter()
+            .map(|row| {
+                let mut value = json!({ "threads": row.threads });
+                for ((name, elapsed), (_, speedup, efficiency)) in
+                    row.operations().into_iter().zip(self.scaling(row))
+                {
+                    value[format!("{name}_ms")] = json!(elapsed.as_secs_f64() * 1000.0);
+                    value[format!("{name}_speedup")] = json!(speedup);
+                    value[format!("{name}_efficiency")] = json!(efficiency);
+                }
+                value
+            })
+            .collect();
+        json!({
+            "signatures": self.signatures,
+            "num_active_epochs": NUM_ACTIVE_EPOCHS,
+            "rows": rows,
+        })
+    }
+}
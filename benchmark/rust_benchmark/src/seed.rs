@@ -0,0 +1,64 @@
+//! Seeds for key generation and seeded corpora
+//!
+//! Every seed the tools accept follows one policy: exactly 32 bytes, written
+//! as 64 hex digits with an optional `0x`. Shorter or longer hex is an error
+//! rather than being zero-padded or cut off. A seed file holds the same hex
+//! (surrounding whitespace and `#` comments allowed) or the 32 raw bytes, so
+//! `head -c 32 /dev/urandom > seed` works too.
+
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+pub const SEED_LEN: usize = 32;
+
+/// A seed written as 64 hex digits, with or without `0x`.
+pub fn parse(raw: &str) -> Result<[u8; SEED_LEN], Box<dyn Error>> {
+    let raw = raw.trim();
+    let digits = raw
+        .strip_prefix("0x")
+        .or_else(|| raw.strip_prefix("0X"))
+        .unwrap_or(raw);
+    if digits.len() != 2 * SEED_LEN {
+        return Err(format!(
+            "seed must be {SEED_LEN} bytes ({} hex digits), got {} digits",
+            2 * SEED_LEN,
+            digits.len()
+        )
+        .into());
+    }
+    let bytes = hex::decode(digits).map_err(|e| format!("invalid seed hex: {e}"))?;
+    Ok(bytes.try_into().expect("length checked"))
+}
+
+/// The seed in a seed file: hex text, or exactly `SEED_LEN` raw bytes.
+pub fn read_file<P: AsRef<Path>>(path: P) -> Result<[u8; SEED_LEN], Box<dyn Error>> {
+    let path = path.as_ref();
+    let bytes = fs::read(path)?;
+    let hex: Option<String> = std::str::from_utf8(&bytes).ok().map(|text| {
+        text.lines()
+            .map(|line| line.split('#').next().unwrap_or_default().trim())
+            .collect()
+    });
+    match hex {
+        // 32 raw bytes can happen to be valid UTF-8; 64 hex digits cannot be 32 bytes
+        Some(hex) if hex.len() == 2 * SEED_LEN || bytes.len() != SEED_LEN => {
+            parse(&hex).map_err(|e| format!("seed file {}: {e}", path.display()).into())
+        }
+        _ => bytes.try_into().map_err(|bytes: Vec<u8>| {
+            format!(
+                "seed file {} holds neither hex nor {SEED_LEN} raw bytes ({} bytes)",
+                path.display(),
+                bytes.len()
+            )
+            .into()
+        }),
+    }
+}
+
+/// A fresh seed from the operating system.
+pub fn random() -> Result<[u8; SEED_LEN], Box<dyn Error>> {
+    let mut seed = [0u8; SEED_LEN];
+    getrandom::getrandom(&mut seed).map_err(|e| format!("failed to generate random seed: {e}"))?;
+    Ok(seed)
+}
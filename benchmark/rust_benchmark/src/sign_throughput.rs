@@ -0,0 +1,144 @@
+//! Steady-state signing cost over many epochs
+//!
+//! The benchmark signs once, at epoch 0, right after keygen, where the
+//! prepared window already covers the epoch. A validator signs at a new
+//! epoch every slot, and every so often the window has to move on by a
+//! bottom tree first. [`measure`] signs `count` messages at distinct epochs
+//! `0, stride, 2 * stride, ...` from one key and times the signing and the
+//! preparation advances separately, so the steady-state rate can be set
+//! against the Zig implementation's.
+
+use std::error::Error;
+use std::time::{Duration, Instant};
+
+use rand::{rngs::StdRng, SeedableRng};
+use serde_json::{json, Value};
+
+use crate::cancel;
+use crate::signer::{prepare_epoch, PreparationState};
+use crate::upstream::{SignatureScheme, SignatureSchemeSecretKey};
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ThroughputReport {
+    pub stride: u32,
+    /// Epochs signed, in order
+    pub epochs: Vec<u32>,
+    pub keygen: Duration,
+    /// Total time in `sign`
+    pub signing: Duration,
+    /// Total time moving the prepared window
+    pub advancing: Duration,
+    /// Bottom trees the prepared window moved by
+    pub advances: u64,
+}
+
+impl ThroughputReport {
+    pub fn signatures(&self) -> usize {
+        self.epochs.len()
+    }
+
+    /// Signatures per second counting signing alone.
+    pub fn signing_rate(&self) -> f64 {
+        self.signatures() as f64 / self.signing.as_secs_f64().max(f64::EPSILON)
+    }
+
+    /// Signatures per second counting the advances too.
+    pub fn steady_state_rate(&self) -> f64 {
+        self.signatures() as f64
+            / (self.signing + self.advancing)
+                .as_secs_f64()
+                .max(f64::EPSILON)
+    }
+
+    /// Share of the steady-state time spent advancing.
+    pub fn advance_overhead(&self) -> f64 {
+        let total = (self.signing + self.advancing).as_secs_f64();
+        if total == 0.0 {
+            0.0
+        } else {
+            self.advancing.as_secs_f64() / total
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        let ms = |d: Duration| d.as_secs_f64() * 1e3;
+        json!({
+            "signatures": self.signatures(),
+            "stride": self.stride,
+            "first_epoch": self.epochs.first(),
+            "last_epoch": self.epochs.last(),
+            "keygen_ms": ms(self.keygen),
+            "signing_ms": ms(self.signing),
+            "advancing_ms": ms(self.advancing),
+            "advances": self.advances,
+            "mean_sign_ms": ms(self.signing) / self.signatures().max(1) as f64,
+            "signatures_per_sec": self.signing_rate(),
+            "steady_state_signatures_per_sec": self.steady_state_rate(),
+            "advance_overhead": self.advance_overhead(),
+        })
+    }
+}
+
+/// Generate a key from `seed` active for the epochs to be signed, then sign
+/// `count` messages at epochs `0, stride, ...`, stopping early on
+/// cancellation.
+pub fn measure<S>(
+    seed: [u8; 32],
+    count: u32,
+    stride: u32,
+) -> Result<ThroughputReport, Box<dyn Error>>
+where
+    S: SignatureScheme,
+    S::SecretKey: SignatureSchemeSecretKey,
+{
+    let stride = stride.max(1);
+    let span = u64::from(count.saturating_sub(1)) * u64::from(stride) + 1;
+    let lifetime = S::LIFETIME;
+    if span > lifetime {
+        return Err(format!(
+            "{count} epochs {stride} apart reach epoch {}, past the lifetime of {lifetime}",
+            span - 1
+        )
+        .into());
+    }
+    let mut report = ThroughputReport {
+        stride,
+        ..ThroughputReport::default()
+    };
+    let start = Instant::now();
+    let mut rng = StdRng::from_seed(seed);
+    let (_, mut secret_key) = S::key_gen(&mut rng, 0, span as usize);
+    report.keygen = start.elapsed();
+
+    let _scope = cancel::Scope::enter();
+    for i in 0..count {
+        let epoch = i * stride;
+        let message = {
+            let mut message = [0u8; 32];
+            message[..4].copy_from_slice(&epoch.to_le_bytes());
+            message
+        };
+        report.advances += PreparationState::of(&secret_key)
+            .advances_needed(u64::from(epoch))
+            .unwrap_or(0);
+        let start = Instant::now();
+        if !prepare_epoch(&mut secret_key, u64::from(epoch)) {
+            return Err(format!(
+                "epoch {epoch} cannot be prepared (prepared {:?})",
+                secret_key.get_prepared_interval()
+            )
+            .into());
+        }
+        report.advancing += start.elapsed();
+
+        let start = Instant::now();
+        S::sign(&secret_key, epoch, &message)
+            .map_err(|e| format!("failed to sign message at epoch {epoch}: {e:?}"))?;
+        report.signing += start.elapsed();
+        report.epochs.push(epoch);
+        if cancel::is_cancelled() {
+            break;
+        }
+    }
+    Ok(report)
+}
@@ -0,0 +1,294 @@
+//! Long-lived signing state
+//!
+//! The one-shot CLIs load (or regenerate) a secret key for every signature.
+//! A `SigningSession` keeps the key in memory, moves its prepared window
+//! forward on demand, and refuses to sign an epoch at or below the last one
+//! it signed: signing two different messages for the same epoch breaks the
+//! one-time signature underneath. With a [`Watermark`] the last signed epoch
+//! is also kept on disk, so the refusal survives a restart.
+//!
+//! `sign_range` covers the bulk case: signing a run of consecutive epochs for
+//! an interop corpus, moving the prepared window only when the next epoch
+//! falls outside it.
+//!
+//! `PreparationState` reports where a key's prepared window stands, so an
+//! operator can tell whether signing an epoch will first have to advance it.
+
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::Write;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use serde_json::{json, Value};
+
+use crate::upstream::{SignatureScheme, SignatureSchemeSecretKey};
+
+/// Advance `sk` until `epoch` is prepared. Returns false if the epoch lies
+/// behind the prepared window or the window stops moving.
+pub fn prepare_epoch<K: SignatureSchemeSecretKey>(sk: &mut K, epoch: u64) -> bool {
+    loop {
+        let prepared = sk.get_prepared_interval();
+        if prepared.contains(&epoch) {
+            return true;
+        }
+        if prepared.start > epoch {
+            return false;
+        }
+        sk.advance_preparation();
+        if sk.get_prepared_interval() == prepared {
+            return false;
+        }
+    }
+}
+
+/// A secret key's activation interval and the part of it that is prepared.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreparationState {
+    pub activation: Range<u64>,
+    pub prepared: Range<u64>,
+}
+
+impl PreparationState {
+    pub fn of<K: SignatureSchemeSecretKey>(sk: &K) -> Self {
+        Self {
+            activation: sk.get_activation_interval(),
+            prepared: sk.get_prepared_interval(),
+        }
+    }
+
+    /// Epochs from the start of the prepared window to the end of the
+    /// activation interval, i.e. those the key can still sign.
+    pub fn epochs_remaining(&self) -> u64 {
+        self.activation.end.saturating_sub(self.prepared.start)
+    }
+
+    /// `advance_preparation` calls needed before `epoch` can be signed: 0 if
+    /// it is prepared, `None` if it is outside the activation interval or
+    /// behind the prepared window. Each call moves the window by one bottom
+    /// tree, half its length.
+    pub fn advances_needed(&self, epoch: u64) -> Option<u64> {
+        if !self.activation.contains(&epoch) || epoch < self.prepared.start {
+            return None;
+        }
+        if self.prepared.contains(&epoch) {
+            return Some(0);
+        }
+        let step = ((self.prepared.end - self.prepared.start) / 2).max(1);
+        Some((epoch - self.prepared.end) / step + 1)
+    }
+
+    /// With `epoch`, also whether and how far the window has to move for it.
+    pub fn to_json(&self, epoch: Option<u64>) -> Value {
+        let mut value = json!({
+            "activation_start": self.activation.start,
+            "activation_end": self.activation.end,
+            "prepared_start": self.prepared.start,
+            "prepared_end": self.prepared.end,
+            "epochs_remaining": self.epochs_remaining(),
+        });
+        if let Some(epoch) = epoch {
+            value["epoch"] = json!(epoch);
+            value["advances_needed"] = json!(self.advances_needed(epoch));
+        }
+        value
+    }
+}
+
+/// The last epoch a key signed, in a file next to the key: the epoch in
+/// decimal on one line. A missing file means nothing was signed.
+pub struct Watermark {
+    path: PathBuf,
+}
+
+impl Watermark {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// `<sk>.watermark` for the secret key at `sk_path`.
+    pub fn next_to(sk_path: impl AsRef<Path>) -> Self {
+        let mut path = sk_path.as_ref().as_os_str().to_owned();
+        path.push(".watermark");
+        Self::new(path)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn load(&self) -> Result<Option<u32>, Box<dyn Error>> {
+        match fs::read_to_string(&self.path) {
+            Ok(text) => text
+                .trim()
+                .parse()
+                .map(Some)
+                .map_err(|e| format!("{}: not an epoch: {e}", self.path.display()).into()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(format!("{}: {e}", self.path.display()).into()),
+        }
+    }
+
+    /// Written to a `.partial` file, synced and renamed over the old one,
+    /// then the directory is synced: once this returns, a crash still
+    /// leaves `epoch` recorded.
+    pub fn store(&self, epoch: u32) -> Result<(), Box<dyn Error>> {
+        let mut partial = self.path.clone().into_os_string();
+        partial.push(".partial");
+        let mut file = File::create(&partial)?;
+        writeln!(file, "{epoch}")?;
+        file.sync_all()?;
+        fs::rename(&partial, &self.path)?;
+        #[cfg(unix)]
+        {
+            let dir = match self.path.parent() {
+                Some(dir) if !dir.as_os_str().is_empty() => dir,
+                _ => Path::new("."),
+            };
+            File::open(dir)?.sync_all()?;
+        }
+        Ok(())
+    }
+}
+
+pub struct SigningSession<S: SignatureScheme> {
+    secret_key: S::SecretKey,
+    last_signed: Option<u32>,
+    watermark: Option<Watermark>,
+}
+
+impl<S> SigningSession<S>
+where
+    S: SignatureScheme,
+    S::SecretKey: SignatureSchemeSecretKey,
+{
+    pub fn new(secret_key: S::SecretKey) -> Self {
+        Self {
+            secret_key,
+            last_signed: None,
+            watermark: None,
+        }
+    }
+
+    /// Resume from the epoch recorded in `watermark`, and record every
+    /// signed epoch there before the signature is returned.
+    pub fn with_watermark(
+        secret_key: S::SecretKey,
+        watermark: Watermark,
+    ) -> Result<Self, Box<dyn Error>> {
+        Ok(Self {
+            secret_key,
+            last_signed: watermark.load()?,
+            watermark: Some(watermark),
+        })
+    }
+
+    pub fn activation_interval(&self) -> Range<u64> {
+        self.secret_key.get_activation_interval()
+    }
+
+    /// Highest epoch signed in this session, or recorded in its watermark.
+    pub fn last_signed(&self) -> Option<u32> {
+        self.last_signed
+    }
+
+    /// Whether `epoch` would be refused because it is not after `last_signed`.
+    pub fn is_reused(&self, epoch: u32) -> bool {
+        self.last_signed.is_some_and(|last| epoch <= last)
+    }
+
+    pub fn sign(&mut self, epoch: u32, message: &[u8; 32]) -> Result<S::Signature, Box<dyn Error>> {
+        if self.is_reused(epoch) {
+            return Err(format!(
+                "refusing to sign epoch {epoch}: already signed up to epoch {}",
+                self.last_signed.unwrap_or_default()
+            )
+            .into());
+        }
+        let activation = self.activation_interval();
+        if !activation.contains(&(epoch as u64)) {
+            return Err(format!("epoch {epoch} outside activation interval {activation:?}").into());
+        }
+        if !prepare_epoch(&mut self.secret_key, epoch as u64) {
+            return Err(format!(
+                "epoch {epoch} cannot be prepared (prepared {:?})",
+                self.secret_key.get_prepared_interval()
+            )
+            .into());
+        }
+        let signature = S::sign(&self.secret_key, epoch, message)
+            .map_err(|e| format!("failed to sign message at epoch {epoch}: {e:?}"))?;
+        if let Some(watermark) = &self.watermark {
+            watermark.store(epoch).map_err(|e| {
+                format!(
+                    "failed to record epoch {epoch} in {}: {e}",
+                    watermark.path().display()
+                )
+            })?;
+        }
+        self.last_signed = Some(epoch);
+        Ok(signature)
+    }
+}
+
+/// Lazy iterator returned by [`sign_range`].
+pub struct SignRange<'a, S: SignatureScheme, I> {
+    secret_key: &'a mut S::SecretKey,
+    epochs: Range<u32>,
+    messages: I,
+}
+
+impl<S, I> Iterator for SignRange<'_, S, I>
+where
+    S: SignatureScheme,
+    S::SecretKey: SignatureSchemeSecretKey,
+    I: Iterator<Item = [u8; 32]>,
+{
+    type Item = Result<(u32, S::Signature), Box<dyn Error>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let epoch = self.epochs.next()?;
+        let message = self.messages.next()?;
+        if !prepare_epoch(self.secret_key, epoch as u64) {
+            let prepared = self.secret_key.get_prepared_interval();
+            // Later epochs cannot be prepared either; end the iteration.
+            self.epochs = epoch..epoch;
+            return Some(Err(format!(
+                "epoch {epoch} cannot be prepared (prepared {prepared:?})"
+            )
+            .into()));
+        }
+        Some(
+            S::sign(self.secret_key, epoch, &message)
+                .map(|signature| (epoch, signature))
+                .map_err(|e| format!("failed to sign message at epoch {epoch}: {e:?}").into()),
+        )
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (_, upper) = self.messages.size_hint();
+        let epochs = self.epochs.len();
+        (0, Some(upper.map_or(epochs, |m| m.min(epochs))))
+    }
+}
+
+/// Sign `messages` at consecutive epochs from `epochs`, one signature per
+/// pair, stopping when either runs out. Signatures are produced as the
+/// iterator is consumed; the prepared window is advanced in place, so `sk`
+/// ends up positioned after the last epoch signed.
+pub fn sign_range<S, I>(
+    sk: &mut S::SecretKey,
+    epochs: Range<u32>,
+    messages: I,
+) -> SignRange<'_, S, I::IntoIter>
+where
+    S: SignatureScheme,
+    S::SecretKey: SignatureSchemeSecretKey,
+    I: IntoIterator<Item = [u8; 32]>,
+{
+    SignRange {
+        secret_key: sk,
+        epochs,
+        messages: messages.into_iter(),
+    }
+}
@@ -0,0 +1,192 @@
+//! Byte budget of signatures and public keys per encoding
+//!
+//! Choosing a wire format for the consensus spec comes down to what each
+//! encoding spends beyond the field elements themselves. `signature_sizes`
+//! and `public_key_sizes` encode one artifact in every `Format` and split the
+//! total into its components (co-path nodes, rho and hashes; root and
+//! parameter). A component is the bytes encoding that field's value: four per
+//! field element in the binary layouts, the value's own CBOR or MessagePack
+//! encoding otherwise. Everything else (headers, offsets, lengths, field
+//! names, padding) is overhead.
+//!
+//! `Format::Bincode` shares its signature and public key encodings with
+//! `Format::Json` and is not listed separately.
+
+use std::error::Error;
+
+use serde::Serialize;
+use serde_json::Value;
+use ssz::Encode;
+
+use crate::compat::codec;
+use crate::compat::json::trim_public_key_value;
+use crate::config::Format;
+use crate::lifetime::{LifetimeMetadata, SchemeMetadata};
+
+const FIELD_ELEMENT_BYTES: usize = 4;
+
+/// Formats compared by a report.
+pub const FORMATS: [Format; 5] = [
+    Format::Json,
+    Format::Ssz,
+    Format::ZigBinary,
+    Format::Cbor,
+    Format::Msgpack,
+];
+
+/// Sizes of one artifact in one format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SizeReport {
+    pub format: Format,
+    /// Component names and their bytes, in encoding order
+    pub components: Vec<(&'static str, usize)>,
+    pub total: usize,
+}
+
+impl SizeReport {
+    /// Bytes not attributed to any component.
+    pub fn overhead(&self) -> usize {
+        self.total - self.components.iter().map(|(_, n)| n).sum::<usize>()
+    }
+
+    pub fn to_json(&self) -> Value {
+        let components: serde_json::Map<String, Value> = self
+            .components
+            .iter()
+            .map(|(name, bytes)| (name.to_string(), Value::from(*bytes)))
+            .collect();
+        serde_json::json!({
+            "format": self.format.to_string(),
+            "components": components,
+            "overhead": self.overhead(),
+            "total": self.total,
+        })
+    }
+}
+
+fn value_len(value: &Value, format: Format) -> Result<usize, Box<dyn Error>> {
+    Ok(match format {
+        Format::Cbor => {
+            let mut bytes = Vec::new();
+            ciborium::into_writer(value, &mut bytes)?;
+            bytes.len()
+        }
+        Format::Msgpack => rmp_serde::to_vec_named(value)?.len(),
+        _ => unreachable!("{format} components are counted in field elements"),
+    })
+}
+
+fn field<'a>(value: &'a Value, path: &[&str]) -> Result<&'a Value, Box<dyn Error>> {
+    path.iter()
+        .try_fold(value, |v, name| v.get(name))
+        .ok_or_else(|| format!("serialized artifact has no '{}'", path.join(".")).into())
+}
+
+/// Split `total` into `components`, each given as its serde path and its
+/// length in field elements.
+fn report(
+    format: Format,
+    total: usize,
+    value: &Value,
+    components: &[(&'static str, &[&str], usize)],
+) -> Result<SizeReport, Box<dyn Error>> {
+    let components = components
+        .iter()
+        .map(|&(name, path, elements)| {
+            let len = match format {
+                Format::Cbor | Format::Msgpack => value_len(field(value, path)?, format)?,
+                _ => elements * FIELD_ELEMENT_BYTES,
+            };
+            Ok((name, len))
+        })
+        .collect::<Result<_, Box<dyn Error>>>()?;
+    Ok(SizeReport {
+        format,
+        components,
+        total,
+    })
+}
+
+pub fn signature_sizes<S>(sig: &S::Signature) -> Result<Vec<SizeReport>, Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::Signature: Serialize + Encode,
+{
+    let meta: LifetimeMetadata = S::METADATA;
+    // CBOR and MessagePack serialize the upstream struct as is
+    let value = serde_json::to_value(sig)?;
+    let nodes = value
+        .pointer("/path/co_path")
+        .and_then(Value::as_array)
+        .map_or(meta.log_lifetime as usize, Vec::len);
+    let hashes = value
+        .get("hashes")
+        .and_then(Value::as_array)
+        .map_or(meta.dimension, Vec::len);
+    FORMATS
+        .into_iter()
+        .map(|format| {
+            let total = codec::encode_signature::<S>(sig, format)?.len();
+            report(
+                format,
+                total,
+                &value,
+                &[
+                    ("co_path", &["path", "co_path"], nodes * meta.hash_len),
+                    ("rho", &["rho"], meta.rand_len),
+                    ("hashes", &["hashes"], hashes * meta.hash_len),
+                ],
+            )
+        })
+        .collect()
+}
+
+pub fn public_key_sizes<S>(pk: &S::PublicKey) -> Result<Vec<SizeReport>, Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::PublicKey: Serialize + Encode,
+{
+    let meta: LifetimeMetadata = S::METADATA;
+    // The codec trims keys before CBOR and MessagePack
+    let mut value = serde_json::to_value(pk)?;
+    trim_public_key_value(&mut value, meta);
+    FORMATS
+        .into_iter()
+        .map(|format| {
+            let total = codec::encode_public_key::<S>(pk, format)?.len();
+            report(
+                format,
+                total,
+                &value,
+                &[
+                    ("root", &["root"], meta.hash_len),
+                    ("parameter", &["parameter"], meta.parameter_len),
+                ],
+            )
+        })
+        .collect()
+}
+
+/// Render reports as a fixed-width table, one row per format.
+pub fn format_table(reports: &[SizeReport]) -> String {
+    let Some(first) = reports.first() else {
+        return String::new();
+    };
+    let mut out = format!("{:<10}", "format");
+    for (name, _) in &first.components {
+        out.push_str(&format!(" {name:>10}"));
+    }
+    out.push_str(&format!(" {:>10} {:>10}\n", "overhead", "total"));
+    for report in reports {
+        out.push_str(&format!("{:<10}", report.format.as_str()));
+        for (_, len) in &report.components {
+            out.push_str(&format!(" {len:>10}"));
+        }
+        out.push_str(&format!(
+            " {:>10} {:>10}\n",
+            report.overhead(),
+            report.total
+        ));
+    }
+    out
+}
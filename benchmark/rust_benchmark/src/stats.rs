@@ -0,0 +1,94 @@
+//! Summaries of repeated timings
+//!
+//! One keygen on a shared CI runner can be off by tens of percent from the
+//! next. Timing an operation several times and summarizing the samples
+//! gives figures two runs can be compared by, with the spread to show
+//! whether a difference is real.
+
+use std::time::Duration;
+
+use serde_json::{json, Value};
+
+/// Nearest-rank percentile of an ascending slice.
+pub fn percentile(sorted: &[Duration], p: f64) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Mean, spread and percentiles of a set of samples, kept in the order
+/// they were taken.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Summary {
+    pub samples: Vec<Duration>,
+    pub mean: Duration,
+    pub median: Duration,
+    /// Sample standard deviation; zero for a single sample
+    pub stddev: Duration,
+    pub p95: Duration,
+    pub min: Duration,
+    pub max: Duration,
+}
+
+impl Summary {
+    /// `None` for no samples.
+    pub fn of(samples: &[Duration]) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+        let mut sorted = samples.to_vec();
+        sorted.sort();
+        let secs: Vec<f64> = samples.iter().map(Duration::as_secs_f64).collect();
+        let mean = secs.iter().sum::<f64>() / secs.len() as f64;
+        let variance = if secs.len() > 1 {
+            secs.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / (secs.len() - 1) as f64
+        } else {
+            0.0
+        };
+        // The middle sample, or the mean of the two middle ones
+        let mid = sorted.len() / 2;
+        let median = if sorted.len() % 2 == 0 {
+            (sorted[mid - 1] + sorted[mid]) / 2
+        } else {
+            sorted[mid]
+        };
+        Some(Summary {
+            samples: samples.to_vec(),
+            mean: Duration::from_secs_f64(mean),
+            median,
+            stddev: Duration::from_secs_f64(variance.sqrt()),
+            p95: percentile(&sorted, 95.0),
+            min: sorted[0],
+            max: sorted[sorted.len() - 1],
+        })
+    }
+
+    pub fn to_json(&self) -> Value {
+        let ms = |d: Duration| d.as_secs_f64() * 1e3;
+        json!({
+            "n": self.samples.len(),
+            "mean_ms": ms(self.mean),
+            "median_ms": ms(self.median),
+            "stddev_ms": ms(self.stddev),
+            "p95_ms": ms(self.p95),
+            "min_ms": ms(self.min),
+            "max_ms": ms(self.max),
+            "samples_ms": self.samples.iter().map(|&d| ms(d)).collect::<Vec<_>>(),
+        })
+    }
+
+    /// One line for people, e.g. `mean 812.400ms ± 12.100ms, median ...`.
+    pub fn describe(&self) -> String {
+        let ms = |d: Duration| d.as_secs_f64() * 1e3;
+        format!(
+            "mean {:.3}ms ± {:.3}ms, median {:.3}ms, p95 {:.3}ms ({} runs)",
+            ms(self.mean),
+            ms(self.stddev),
+            ms(self.median),
+            ms(self.p95),
+            self.samples.len()
+        )
+    }
+}
@@ -0,0 +1,660 @@
+//! The `bench` group: repeated keygen/sign/verify, the pipeline over many
+//! seeds, signing and verification throughput, wire-format cost, gossip
+//! simulation, verification under load and thread scaling
+
+use std::error::Error;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use rand::{rngs::StdRng, SeedableRng};
+
+use super::exit::{self, Failure};
+use super::output::{progress, Output};
+use super::BaselineArgs;
+use crate::baseline;
+use crate::cancel;
+use crate::format_bench;
+use crate::gossip::{self, GossipMix};
+use crate::lifetime::LifetimeTag;
+use crate::multi_seed;
+use crate::regime::{self, Regime};
+use crate::scaling::{self, ScalingReport};
+use crate::sign_throughput;
+use crate::stats::Summary;
+use crate::upstream::{
+    SIGTopLevelTargetSumLifetime18Dim64Base8, SIGTopLevelTargetSumLifetime32Dim64Base8,
+    SIGTopLevelTargetSumLifetime8Dim64Base8, SignatureScheme, SignatureSchemeSecretKey,
+};
+use crate::verify_load::{self, VerifyCorpus};
+use crate::verify_throughput;
+
+/// Compare `result` with `--baseline`, if given, print the change in each
+/// timing and fail the run if one regressed past `--max-regression`.
+fn check_baseline(
+    result: &serde_json::Value,
+    args: &BaselineArgs,
+    stdout: &mut Output,
+) -> Result<(), Box<dyn Error>> {
+    let Some(path) = &args.baseline else {
+        return Ok(());
+    };
+    let stored = baseline::load(path).map_err(exit::tag(Failure::Parse))?;
+    let comparison = baseline::compare(&stored, result, args.max_regression)
+        .map_err(exit::tag(Failure::Usage))?;
+    progress!("Against {}:", path.display());
+    progress!(
+        "  {:<12} {:>12} {:>12} {:>8}",
+        "metric",
+        "baseline",
+        "current",
+        "change"
+    );
+    for delta in &comparison.deltas {
+        progress!(
+            "  {:<12} {:>10.3}ms {:>10.3}ms {:>+7.1}%{}",
+            delta.name,
+            delta.baseline_ms,
+            delta.current_ms,
+            delta.change() * 100.0,
+            if delta.change() > comparison.max_regression {
+                "  regressed"
+            } else {
+                ""
+            }
+        );
+    }
+    if !comparison.unmatched.is_empty() {
+        progress!("  only on one side: {}", comparison.unmatched.join(", "));
+    }
+    let regressed = comparison.regressions().count();
+    if regressed > 0 {
+        progress!(
+            "❌ {} of {} timings regressed by more than {:.1}%",
+            regressed,
+            comparison.deltas.len(),
+            comparison.max_regression * 100.0
+        );
+        stdout.fail();
+    } else {
+        progress!(
+            "✅ No timing regressed by more than {:.1}%",
+            comparison.max_regression * 100.0
+        );
+    }
+    stdout.set("baseline", comparison.to_json());
+    Ok(())
+}
+
+/// Timings of keygen, sign and verify, one sample per measured iteration.
+type OpsSamples = [(&'static str, Vec<Duration>); 3];
+
+fn ops_for_scheme<S>(
+    seed: [u8; 32],
+    iterations: usize,
+    warmup: usize,
+) -> Result<OpsSamples, Box<dyn Error>>
+where
+    S: SignatureScheme,
+{
+    const NUM_ACTIVE_EPOCHS: usize = 256;
+    const MESSAGE: [u8; 32] = *b"hash-zig repeated bench message!";
+    let mut samples: OpsSamples = [
+        ("keygen", Vec::new()),
+        ("sign", Vec::new()),
+        ("verify", Vec::new()),
+    ];
+    for i in 0..warmup + iterations {
+        let mut times = [Duration::ZERO; 3];
+        let start = Instant::now();
+        let mut rng = StdRng::from_seed(seed);
+        let (public_key, secret_key) = S::key_gen(&mut rng, 0, NUM_ACTIVE_EPOCHS);
+        times[0] = start.elapsed();
+        let epoch = secret_key.get_prepared_interval().start as u32;
+        let start = Instant::now();
+        let signature = S::sign(&secret_key, epoch, &MESSAGE)
+            .map_err(|e| format!("failed to sign message at epoch {epoch}: {e:?}"))?;
+        times[1] = start.elapsed();
+        let start = Instant::now();
+        let valid = S::verify(&public_key, epoch, &MESSAGE, &signature);
+        times[2] = start.elapsed();
+        if !valid {
+            return Err(format!("iteration {i}: signature did not verify").into());
+        }
+        if i < warmup {
+            progress!("  warmup {}/{} done", i + 1, warmup);
+        } else {
+            for ((_, samples), time) in samples.iter_mut().zip(times) {
+                samples.push(time);
+            }
+            progress!("  iteration {}/{} done", i + 1 - warmup, iterations);
+        }
+        if cancel::is_cancelled() {
+            break;
+        }
+    }
+    Ok(samples)
+}
+
+/// Run each iteration as `bench ops --iterations 1 --warmup 0` in a fresh
+/// process of this binary, with its pages dropped from the page cache first.
+fn ops_cold(
+    lifetime: LifetimeTag,
+    iterations: usize,
+    seed: [u8; 32],
+) -> Result<OpsSamples, Box<dyn Error>> {
+    let exe = std::env::current_exe()?;
+    let mut samples: OpsSamples = [
+        ("keygen", Vec::new()),
+        ("sign", Vec::new()),
+        ("verify", Vec::new()),
+    ];
+    let _scope = cancel::Scope::enter();
+    for i in 1..=iterations {
+        if let Err(e) = regime::drop_page_cache(&exe) {
+            if i == 1 {
+                progress!("⚠️  {} stays in the page cache: {e}", exe.display());
+            }
+        }
+        let child = Command::new(&exe)
+            .args(["--json", "--quiet", "bench", "ops", "--iterations", "1"])
+            .args(["--warmup", "0", "--lifetime", lifetime.as_str()])
+            .args(["--seed", &hex::encode(seed)])
+            .stderr(Stdio::inherit())
+            .output()?;
+        // The child saw the ctrl-c too and has no result
+        if cancel::is_cancelled() {
+            break;
+        }
+        let document: serde_json::Value = serde_json::from_slice(&child.stdout)
+            .map_err(|e| format!("iteration {i}: no result from the child process: {e}"))?;
+        if !child.status.success() {
+            return Err(format!(
+                "iteration {i}: {}",
+                document["error"]
+                    .as_str()
+                    .unwrap_or("the child process failed")
+            )
+            .into());
+        }
+        for (name, samples) in samples.iter_mut() {
+            let ms = document["ops"]["metrics"][*name]["samples_ms"][0]
+                .as_f64()
+                .ok_or_else(|| format!("iteration {i}: the child process timed no {name}"))?;
+            samples.push(Duration::from_secs_f64(ms / 1e3));
+        }
+        progress!("  iteration {}/{} done in a fresh process", i, iterations);
+    }
+    Ok(samples)
+}
+
+pub fn ops_command(
+    lifetime: LifetimeTag,
+    iterations: usize,
+    warmup: usize,
+    seed: [u8; 32],
+    mode: Regime,
+    baseline: &BaselineArgs,
+    stdout: &mut Output,
+) -> Result<(), Box<dyn Error>> {
+    if iterations == 0 {
+        return Err(exit::tag(Failure::Usage)("--iterations must be at least 1"));
+    }
+    // Cold iterations are first runs by definition
+    let warmup = match mode {
+        Regime::Warm => warmup,
+        Regime::Cold => 0,
+    };
+    progress!(
+        "Timing keygen, sign and verify {} times after {} warmup runs (lifetime {}, {})...",
+        iterations,
+        warmup,
+        lifetime,
+        mode
+    );
+    let samples = match (mode, lifetime) {
+        (Regime::Cold, _) => ops_cold(lifetime, iterations, seed)?,
+        (Regime::Warm, LifetimeTag::Pow8) => {
+            ops_for_scheme::<SIGTopLevelTargetSumLifetime8Dim64Base8>(seed, iterations, warmup)?
+        }
+        (Regime::Warm, LifetimeTag::Pow18) => {
+            ops_for_scheme::<SIGTopLevelTargetSumLifetime18Dim64Base8>(seed, iterations, warmup)?
+        }
+        (Regime::Warm, LifetimeTag::Pow32) => {
+            ops_for_scheme::<SIGTopLevelTargetSumLifetime32Dim64Base8>(seed, iterations, warmup)?
+        }
+    };
+    // Iterations finished before a ctrl-c or --timeout are still summarized
+    let mut metrics = serde_json::Map::new();
+    for (name, samples) in &samples {
+        if let Some(summary) = Summary::of(samples) {
+            progress!("  {:<7} {}", name, summary.describe());
+            metrics.insert(name.to_string(), summary.to_json());
+        }
+    }
+    let json = serde_json::json!({
+        "mode": mode.as_str(),
+        "iterations": iterations,
+        "warmup": warmup,
+        "metrics": metrics,
+    });
+    stdout.line(format!("OPS_RESULT:{}", json));
+    stdout.set("lifetime", lifetime.as_str());
+    stdout.set("seed", hex::encode(seed));
+    check_baseline(&json, baseline, stdout)?;
+    stdout.set("ops", json);
+    cancel::check()
+}
+
+pub fn multi_seed_command(
+    seeds: &[[u8; 32]],
+    runs: usize,
+    concurrency: usize,
+    lifetime: LifetimeTag,
+    stdout: &mut Output,
+) -> Result<(), Box<dyn Error>> {
+    if runs == 0 {
+        return Err(exit::tag(Failure::Usage)("--runs must be at least 1"));
+    }
+    progress!(
+        "Running keygen, sign and verify {} times for each of {} seeds (lifetime {})...",
+        runs,
+        seeds.len(),
+        lifetime
+    );
+    let report = match lifetime {
+        LifetimeTag::Pow8 => {
+            multi_seed::run::<SIGTopLevelTargetSumLifetime8Dim64Base8>(seeds, runs, concurrency)?
+        }
+        LifetimeTag::Pow18 => {
+            multi_seed::run::<SIGTopLevelTargetSumLifetime18Dim64Base8>(seeds, runs, concurrency)?
+        }
+        LifetimeTag::Pow32 => {
+            multi_seed::run::<SIGTopLevelTargetSumLifetime32Dim64Base8>(seeds, runs, concurrency)?
+        }
+    };
+    progress!(
+        "{} seeds, {} at a time:",
+        report.seeds.len(),
+        report.concurrency
+    );
+    for (name, summary) in report.summaries() {
+        if let Some(summary) = summary {
+            progress!("  {:<7} {}", name, summary.describe());
+        }
+    }
+    for seed in report.nondeterministic() {
+        progress!(
+            "❌ seed {} gave {} different public keys: {}",
+            hex::encode(seed.seed),
+            seed.fingerprints().len(),
+            seed.fingerprints().join(", ")
+        );
+    }
+    if report.failures() > 0 {
+        progress!("❌ {} signatures did not verify", report.failures());
+    }
+    let json = report.to_json();
+    stdout.line(format!("MULTI_SEED_RESULT:{}", json));
+    stdout.set("lifetime", lifetime.as_str());
+    stdout.set("multi_seed", json);
+    if report.nondeterministic().next().is_some() || report.failures() > 0 {
+        stdout.fail();
+    }
+    cancel::check()
+}
+
+pub fn sign_throughput_command(
+    lifetime: LifetimeTag,
+    count: u32,
+    stride: u32,
+    seed: [u8; 32],
+    stdout: &mut Output,
+) -> Result<(), Box<dyn Error>> {
+    progress!(
+        "Signing {} messages at epochs {} apart from one key (lifetime {})...",
+        count,
+        stride.max(1),
+        lifetime
+    );
+    let report = match lifetime {
+        LifetimeTag::Pow8 => {
+            sign_throughput::measure::<SIGTopLevelTargetSumLifetime8Dim64Base8>(seed, count, stride)
+        }
+        LifetimeTag::Pow18 => sign_throughput::measure::<SIGTopLevelTargetSumLifetime18Dim64Base8>(
+            seed, count, stride,
+        ),
+        LifetimeTag::Pow32 => sign_throughput::measure::<SIGTopLevelTargetSumLifetime32Dim64Base8>(
+            seed, count, stride,
+        ),
+    }
+    .map_err(exit::tag(Failure::Usage))?;
+    progress!(
+        "✅ {} signatures: {:.1}/s signing alone, {:.1}/s with {} advances ({:.1}% of the time)",
+        report.signatures(),
+        report.signing_rate(),
+        report.steady_state_rate(),
+        report.advances,
+        report.advance_overhead() * 100.0
+    );
+    let json = report.to_json();
+    stdout.line(format!("SIGN_THROUGHPUT_RESULT:{}", json));
+    stdout.set("lifetime", lifetime.as_str());
+    stdout.set("seed", hex::encode(seed));
+    stdout.set("sign_throughput", json);
+    cancel::check()
+}
+
+pub fn verify_throughput_command(
+    lifetime: LifetimeTag,
+    count: usize,
+    parallel: bool,
+    seed: [u8; 32],
+    stdout: &mut Output,
+) -> Result<(), Box<dyn Error>> {
+    if count == 0 {
+        return Err(exit::tag(Failure::Usage)("--count must be at least 1"));
+    }
+    progress!(
+        "Verifying {} signatures serially{} (lifetime {})...",
+        count,
+        if parallel { " and in parallel" } else { "" },
+        lifetime
+    );
+    let report =
+        match lifetime {
+            LifetimeTag::Pow8 => verify_throughput::measure::<
+                SIGTopLevelTargetSumLifetime8Dim64Base8,
+            >(seed, count, parallel)?,
+            LifetimeTag::Pow18 => verify_throughput::measure::<
+                SIGTopLevelTargetSumLifetime18Dim64Base8,
+            >(seed, count, parallel)?,
+            LifetimeTag::Pow32 => verify_throughput::measure::<
+                SIGTopLevelTargetSumLifetime32Dim64Base8,
+            >(seed, count, parallel)?,
+        };
+    progress!(
+        "  serial   {:>10.1} signatures/s ({} in {:.3}s)",
+        report.serial.rate(),
+        report.serial.verified,
+        report.serial.elapsed.as_secs_f64()
+    );
+    if let (Some(run), Some(speedup)) = (report.parallel, report.speedup()) {
+        progress!(
+            "  parallel {:>10.1} signatures/s on {} threads ({:.2}x)",
+            run.rate(),
+            run.threads,
+            speedup
+        );
+    }
+    let json = report.to_json();
+    stdout.line(format!("VERIFY_THROUGHPUT_RESULT:{}", json));
+    stdout.set("lifetime", lifetime.as_str());
+    stdout.set("seed", hex::encode(seed));
+    stdout.set("verify_throughput", json);
+    cancel::check()
+}
+
+pub fn formats_command(
+    lifetimes: &[LifetimeTag],
+    iterations: usize,
+    seed: [u8; 32],
+    stdout: &mut Output,
+) -> Result<(), Box<dyn Error>> {
+    if iterations == 0 {
+        return Err(exit::tag(Failure::Usage)("--iterations must be at least 1"));
+    }
+    let mut results = Vec::new();
+    for &lifetime in lifetimes {
+        progress!(
+            "Encoding and decoding each artifact {} times per format (lifetime {})...",
+            iterations,
+            lifetime
+        );
+        let report = match lifetime {
+            LifetimeTag::Pow8 => {
+                format_bench::measure::<SIGTopLevelTargetSumLifetime8Dim64Base8>(seed, iterations)?
+            }
+            LifetimeTag::Pow18 => {
+                format_bench::measure::<SIGTopLevelTargetSumLifetime18Dim64Base8>(seed, iterations)?
+            }
+            LifetimeTag::Pow32 => {
+                format_bench::measure::<SIGTopLevelTargetSumLifetime32Dim64Base8>(seed, iterations)?
+            }
+        };
+        stdout.line(format!("lifetime {}:", lifetime));
+        stdout.print(report.table());
+        let mut json = report.to_json();
+        json["lifetime"] = serde_json::Value::from(lifetime.as_str());
+        stdout.line(format!("FORMATS_RESULT:{}", json));
+        results.push(json);
+        if cancel::is_cancelled() {
+            break;
+        }
+    }
+    stdout.set("seed", hex::encode(seed));
+    stdout.set("formats", results);
+    cancel::check()
+}
+
+fn simulate_gossip_for_scheme<S>(
+    count: usize,
+    seed: [u8; 32],
+    mix: &GossipMix,
+) -> Result<gossip::GossipReport, Box<dyn Error>>
+where
+    S: SignatureScheme,
+{
+    const NUM_ACTIVE_EPOCHS: u32 = 256;
+    let mut rng = StdRng::from_seed(seed);
+    let (public_key, mut secret_key) = S::key_gen(&mut rng, 0, NUM_ACTIVE_EPOCHS as usize);
+    let items =
+        gossip::generate_corpus::<S>(&mut secret_key, seed, count, 0..NUM_ACTIVE_EPOCHS, mix)?;
+    for kind in [
+        gossip::GossipKind::Valid,
+        gossip::GossipKind::StaleEpoch,
+        gossip::GossipKind::Malformed,
+        gossip::GossipKind::Duplicate,
+    ] {
+        let n = items.iter().filter(|item| item.kind == kind).count();
+        progress!("  {:<10} {}", kind.as_str(), n);
+    }
+    Ok(gossip::simulate::<S>(&public_key, &items))
+}
+
+pub fn simulate_gossip_command(
+    count: usize,
+    seed: [u8; 32],
+    lifetime: LifetimeTag,
+    mix: &GossipMix,
+    stdout: &mut Output,
+) -> Result<(), Box<dyn Error>> {
+    progress!(
+        "Generating gossip corpus of {} items (lifetime {})...",
+        count,
+        lifetime
+    );
+    let report =
+        match lifetime {
+            LifetimeTag::Pow8 => simulate_gossip_for_scheme::<
+                SIGTopLevelTargetSumLifetime8Dim64Base8,
+            >(count, seed, mix)?,
+            LifetimeTag::Pow18 => simulate_gossip_for_scheme::<
+                SIGTopLevelTargetSumLifetime18Dim64Base8,
+            >(count, seed, mix)?,
+            LifetimeTag::Pow32 => simulate_gossip_for_scheme::<
+                SIGTopLevelTargetSumLifetime32Dim64Base8,
+            >(count, seed, mix)?,
+        };
+    progress!(
+        "✅ Verified {} items in {:.3}s ({:.1} items/s)",
+        report.total,
+        report.elapsed.as_secs_f64(),
+        report.throughput()
+    );
+    progress!(
+        "   accepted {}, rejected {}, false accepts {}, false rejects {} (accuracy {:.4})",
+        report.accepted,
+        report.rejected,
+        report.false_accepts,
+        report.false_rejects,
+        report.accuracy()
+    );
+    stdout.line(format!("GOSSIP_RESULT:{}", report.to_json()));
+    stdout.set("lifetime", lifetime.as_str());
+    stdout.set("seed", hex::encode(seed));
+    stdout.set("gossip", report.to_json());
+    Ok(())
+}
+
+fn verify_load_for_scheme<S>(
+    jobs: usize,
+    threads: usize,
+    slo: std::time::Duration,
+) -> Result<verify_load::LoadReport, Box<dyn Error>>
+where
+    S: SignatureScheme + 'static,
+    S::PublicKey: Send + Sync,
+    S::Signature: Send + Sync,
+{
+    let corpus = VerifyCorpus::<S>::generate([0x42; 32])?;
+    verify_load::sweep(corpus, threads, jobs, slo)
+}
+
+fn verify_load_lifetime(
+    jobs: usize,
+    lifetime: LifetimeTag,
+    threads: usize,
+    slo: std::time::Duration,
+    stdout: &Output,
+) -> Result<serde_json::Value, Box<dyn Error>> {
+    progress!(
+        "Measuring verification latency under load (lifetime {}, {} jobs per rate)...",
+        lifetime,
+        jobs
+    );
+    let report = match lifetime {
+        LifetimeTag::Pow8 => {
+            verify_load_for_scheme::<SIGTopLevelTargetSumLifetime8Dim64Base8>(jobs, threads, slo)?
+        }
+        LifetimeTag::Pow18 => {
+            verify_load_for_scheme::<SIGTopLevelTargetSumLifetime18Dim64Base8>(jobs, threads, slo)?
+        }
+        LifetimeTag::Pow32 => {
+            verify_load_for_scheme::<SIGTopLevelTargetSumLifetime32Dim64Base8>(jobs, threads, slo)?
+        }
+    };
+    for rate in &report.rates {
+        progress!(
+            "  {:>10.1}/s offered  {:>10.1}/s achieved  p50 {:>8.3}ms  p99 {:>8.3}ms  {}",
+            rate.offered_rate,
+            rate.achieved_rate,
+            rate.p50.as_secs_f64() * 1e3,
+            rate.p99.as_secs_f64() * 1e3,
+            if rate.meets_slo(report.slo) {
+                "ok"
+            } else {
+                "over SLO"
+            }
+        );
+    }
+    match report.max_sustainable_rate() {
+        Some(rate) => progress!(
+            "✅ {} threads sustain {:.1} verifications/s within {}ms p99",
+            report.threads,
+            rate,
+            report.slo.as_millis()
+        ),
+        None => progress!(
+            "❌ no tested rate met the {}ms p99 SLO",
+            report.slo.as_millis()
+        ),
+    }
+    let mut json = report.to_json();
+    json["lifetime"] = serde_json::Value::from(lifetime.as_str());
+    stdout.line(format!("VERIFY_LOAD_RESULT:{}", json));
+    Ok(json)
+}
+
+pub fn verify_load_command(
+    jobs: usize,
+    lifetimes: &[LifetimeTag],
+    threads: usize,
+    slo: std::time::Duration,
+    stdout: &mut Output,
+) -> Result<(), Box<dyn Error>> {
+    let mut results = Vec::new();
+    for &lifetime in lifetimes {
+        results.push(verify_load_lifetime(jobs, lifetime, threads, slo, stdout)?);
+        if cancel::is_cancelled() {
+            break;
+        }
+    }
+    // Whatever was measured before a ctrl-c or --timeout is still reported
+    stdout.set("verify_load", results);
+    cancel::check()
+}
+
+pub fn scaling_command(
+    threads: &[usize],
+    lifetime: LifetimeTag,
+    signatures: usize,
+    seed: [u8; 32],
+    baseline: &BaselineArgs,
+    stdout: &mut Output,
+) -> Result<(), Box<dyn Error>> {
+    progress!(
+        "Measuring thread scaling (lifetime {}, {} signatures per run)...",
+        lifetime,
+        signatures
+    );
+    progress!(
+        "  {:>7}  {:>10} {:>7}  {:>10} {:>7}  {:>10} {:>7}",
+        "threads",
+        "keygen",
+        "speedup",
+        "sign",
+        "speedup",
+        "verify",
+        "speedup"
+    );
+    let mut report = ScalingReport {
+        signatures,
+        rows: Vec::new(),
+    };
+    for &n in threads {
+        let row = match lifetime {
+            LifetimeTag::Pow8 => {
+                scaling::measure::<SIGTopLevelTargetSumLifetime8Dim64Base8>(seed, n, signatures)?
+            }
+            LifetimeTag::Pow18 => {
+                scaling::measure::<SIGTopLevelTargetSumLifetime18Dim64Base8>(seed, n, signatures)?
+            }
+            LifetimeTag::Pow32 => {
+                scaling::measure::<SIGTopLevelTargetSumLifetime32Dim64Base8>(seed, n, signatures)?
+            }
+        };
+        report.rows.push(row);
+        let [keygen, sign, verify] = report.scaling(&row);
+        progress!(
+            "  {:>7}  {:>8.3}ms {:>6.2}x  {:>8.3}ms {:>6.2}x  {:>8.3}ms {:>6.2}x",
+            row.threads,
+            row.keygen.as_secs_f64() * 1e3,
+            keygen.1,
+            row.sign.as_secs_f64() * 1e3,
+            sign.1,
+            row.verify.as_secs_f64() * 1e3,
+            verify.1
+        );
+        if cancel::is_cancelled() {
+            break;
+        }
+    }
+    // Thread counts run before a ctrl-c or --timeout are still reported
+    let json = report.to_json();
+    stdout.line(format!("SCALING_RESULT:{}", json));
+    stdout.set("lifetime", lifetime.as_str());
+    stdout.set("seed", hex::encode(seed));
+    check_baseline(&json, baseline, stdout)?;
+    stdout.set("scaling", json);
+    cancel::check()
+}
@@ -0,0 +1,215 @@
+//! `convert` between interchange formats and `size` reports
+
+use std::error::Error;
+
+use super::exit::{self, Failure};
+use super::output::{progress, Output};
+use crate::compat::codec;
+use crate::compat::hsz;
+use crate::compat::key_envelope::{self, KeyEnvelope};
+use crate::compat::text_encoding;
+use crate::config::Format;
+use crate::lifetime::{LifetimeTag, SchemeMetadata};
+use crate::size_report;
+use crate::upstream::{
+    SIGTopLevelTargetSumLifetime18Dim64Base8, SIGTopLevelTargetSumLifetime32Dim64Base8,
+    SIGTopLevelTargetSumLifetime8Dim64Base8,
+};
+
+/// What `convert` and `size` translate.
+#[derive(Debug, Clone, Copy)]
+pub enum Artifact {
+    Signature,
+    PublicKey,
+    SecretKey,
+}
+
+impl Artifact {
+    pub fn parse(raw: &str) -> Result<Self, String> {
+        match raw {
+            "sig" | "signature" => Ok(Self::Signature),
+            "pk" | "public-key" => Ok(Self::PublicKey),
+            "sk" | "secret-key" => Ok(Self::SecretKey),
+            other => Err(format!(
+                "unknown artifact kind '{other}'. Must be one of: sig, pk, sk"
+            )),
+        }
+    }
+
+    /// Guess from names like `rust_sk.json` or `zig_pk.ssz`; anything else is a signature.
+    pub fn from_path(path: &str) -> Self {
+        let path = hsz::inner_path(std::path::Path::new(path));
+        match path.file_stem().and_then(|stem| stem.to_str()) {
+            Some(stem) if stem.ends_with("sk") => Self::SecretKey,
+            Some(stem) if stem.ends_with("pk") => Self::PublicKey,
+            _ => Self::Signature,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Signature => "signature",
+            Self::PublicKey => "public key",
+            Self::SecretKey => "secret key",
+        }
+    }
+}
+
+fn convert_for_scheme<S: SchemeMetadata>(
+    kind: Artifact,
+    input: &str,
+    from: Format,
+    to: Format,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    let bytes = hsz::read(input)?;
+    let parse = exit::tag(Failure::Parse);
+    match kind {
+        Artifact::Signature => {
+            let signature = text_encoding::decode(&bytes)
+                .and_then(|bytes| codec::decode_signature::<S>(&bytes, from))
+                .map_err(parse)?;
+            codec::encode_signature::<S>(&signature, to)
+        }
+        Artifact::PublicKey => {
+            let public_key = codec::decode_public_key::<S>(&bytes, from).map_err(parse)?;
+            codec::encode_public_key::<S>(&public_key, to)
+        }
+        Artifact::SecretKey => {
+            // An enveloped key stays enveloped, with the new format recorded
+            let (envelope, bytes) = key_envelope::unwrap(&bytes)?;
+            if let Some(envelope) = envelope {
+                envelope.check_lifetime(S::TAG)?;
+            }
+            let from = envelope.map_or(from, |e| e.format);
+            let secret_key = codec::decode_secret_key::<S>(bytes, from).map_err(parse)?;
+            let encoded = codec::encode_secret_key::<S>(&secret_key, to)?;
+            match envelope {
+                Some(_) => Ok(key_envelope::wrap(&KeyEnvelope::new(S::TAG, to)?, &encoded)),
+                None => Ok(encoded),
+            }
+        }
+    }
+}
+
+/// With `dry_run`, the input is decoded and re-encoded in memory but
+/// `output` is not written.
+#[allow(clippy::too_many_arguments)]
+pub fn convert_command(
+    kind: Artifact,
+    input: &str,
+    output: &str,
+    from: Format,
+    to: Format,
+    lifetime: LifetimeTag,
+    dry_run: bool,
+    stdout: &mut Output,
+) -> Result<(), Box<dyn Error>> {
+    let bytes = match lifetime {
+        LifetimeTag::Pow8 => {
+            convert_for_scheme::<SIGTopLevelTargetSumLifetime8Dim64Base8>(kind, input, from, to)?
+        }
+        LifetimeTag::Pow18 => {
+            convert_for_scheme::<SIGTopLevelTargetSumLifetime18Dim64Base8>(kind, input, from, to)?
+        }
+        LifetimeTag::Pow32 => {
+            convert_for_scheme::<SIGTopLevelTargetSumLifetime32Dim64Base8>(kind, input, from, to)?
+        }
+    };
+    if dry_run {
+        stdout.line(format!(
+            "would convert {} {} ({}) to {} ({}, {} bytes)",
+            kind.as_str(),
+            input,
+            from,
+            output,
+            to,
+            bytes.len()
+        ));
+        stdout.set("dry_run", true);
+    } else {
+        hsz::write(output, &bytes)?;
+        progress!(
+            "✅ Converted {} {} ({}) to {} ({}, {} bytes)",
+            kind.as_str(),
+            input,
+            from,
+            output,
+            to,
+            bytes.len()
+        );
+    }
+    stdout.set("kind", kind.as_str());
+    stdout.set("input", input);
+    stdout.set("from", from.to_string());
+    stdout.set("output", output);
+    stdout.set("to", to.to_string());
+    stdout.set("bytes", bytes.len());
+    Ok(())
+}
+
+fn size_for_scheme<S: SchemeMetadata>(
+    kind: Artifact,
+    input: &str,
+    from: Format,
+) -> Result<Vec<size_report::SizeReport>, Box<dyn Error>> {
+    let bytes = hsz::read(input)?;
+    let parse = exit::tag(Failure::Parse);
+    match kind {
+        Artifact::Signature => {
+            let signature = text_encoding::decode(&bytes)
+                .and_then(|bytes| codec::decode_signature::<S>(&bytes, from))
+                .map_err(parse)?;
+            size_report::signature_sizes::<S>(&signature)
+        }
+        Artifact::PublicKey => {
+            let public_key = codec::decode_public_key::<S>(&bytes, from).map_err(parse)?;
+            size_report::public_key_sizes::<S>(&public_key)
+        }
+        Artifact::SecretKey => {
+            Err("size reports cover signatures and public keys, not secret keys".into())
+        }
+    }
+}
+
+pub fn size_command(
+    kind: Artifact,
+    input: &str,
+    from: Format,
+    lifetime: LifetimeTag,
+    stdout: &mut Output,
+) -> Result<(), Box<dyn Error>> {
+    let reports = match lifetime {
+        LifetimeTag::Pow8 => {
+            size_for_scheme::<SIGTopLevelTargetSumLifetime8Dim64Base8>(kind, input, from)?
+        }
+        LifetimeTag::Pow18 => {
+            size_for_scheme::<SIGTopLevelTargetSumLifetime18Dim64Base8>(kind, input, from)?
+        }
+        LifetimeTag::Pow32 => {
+            size_for_scheme::<SIGTopLevelTargetSumLifetime32Dim64Base8>(kind, input, from)?
+        }
+    };
+    let meta = lifetime.metadata();
+    stdout.line(format!(
+        "{} {} ({}), lifetime {}: hash_len {}, rand_len {}, parameter_len {}, dimension {}",
+        kind.as_str(),
+        input,
+        from,
+        lifetime,
+        meta.hash_len,
+        meta.rand_len,
+        meta.parameter_len,
+        meta.dimension
+    ));
+    stdout.print(size_report::format_table(&reports));
+    stdout
+        .line("json signatures are the 3116-byte leanSignature wire format; bincode matches json");
+    stdout.set("kind", kind.as_str());
+    stdout.set("input", input);
+    stdout.set("lifetime", lifetime.as_str());
+    stdout.set(
+        "reports",
+        reports.iter().map(|r| r.to_json()).collect::<Vec<_>>(),
+    );
+    Ok(())
+}
@@ -0,0 +1,117 @@
+//! `diff` between two public keys or two signatures, field by field
+
+use std::error::Error;
+
+use serde_json::json;
+
+use super::convert::Artifact;
+use super::exit::{self, Failure};
+use super::output::{progress, Output};
+use crate::compat::codec;
+use crate::compat::field_diff::{self, ElementForm, FieldDiff};
+use crate::compat::hsz;
+use crate::compat::pem;
+use crate::compat::text_encoding;
+use crate::config::Format;
+use crate::lifetime::{LifetimeTag, SchemeMetadata};
+use crate::upstream::{
+    SIGTopLevelTargetSumLifetime18Dim64Base8, SIGTopLevelTargetSumLifetime32Dim64Base8,
+    SIGTopLevelTargetSumLifetime8Dim64Base8,
+};
+
+fn read_public_key<S: SchemeMetadata>(
+    path: &str,
+    format: Format,
+) -> Result<S::PublicKey, Box<dyn Error>> {
+    let bytes = hsz::read(path)?;
+    if pem::is_wrapped(&bytes) {
+        pem::decode_public_key::<S>(&bytes)
+    } else {
+        codec::decode_public_key::<S>(&bytes, format)
+    }
+    .map_err(|e| exit::tag(Failure::Parse)(hsz::with_context(path, e)))
+}
+
+fn read_signature<S: SchemeMetadata>(
+    path: &str,
+    format: Format,
+) -> Result<S::Signature, Box<dyn Error>> {
+    text_encoding::decode(&hsz::read(path)?)
+        .and_then(|bytes| codec::decode_signature::<S>(&bytes, format))
+        .map_err(|e| exit::tag(Failure::Parse)(hsz::with_context(path, e)))
+}
+
+fn diff_for_scheme<S: SchemeMetadata>(
+    kind: Artifact,
+    left: (&str, Format),
+    right: (&str, Format),
+) -> Result<Vec<FieldDiff>, Box<dyn Error>> {
+    match kind {
+        Artifact::PublicKey => field_diff::public_key_diff::<S>(
+            &read_public_key::<S>(left.0, left.1)?,
+            &read_public_key::<S>(right.0, right.1)?,
+        ),
+        Artifact::Signature => field_diff::signature_diff::<S>(
+            &read_signature::<S>(left.0, left.1)?,
+            &read_signature::<S>(right.0, right.1)?,
+        ),
+        Artifact::SecretKey => Err(exit::tag(Failure::Usage)(
+            "diff compares public keys or signatures, not secret keys",
+        )),
+    }
+}
+
+/// Decode `left` and `right`, each in its own format, and print every field
+/// element that differs. Differences make the command fail like a rejected
+/// signature.
+pub fn diff_command(
+    kind: Artifact,
+    left: (&str, Format),
+    right: (&str, Format),
+    lifetime: LifetimeTag,
+    form: ElementForm,
+    stdout: &mut Output,
+) -> Result<(), Box<dyn Error>> {
+    let diffs = match lifetime {
+        LifetimeTag::Pow8 => {
+            diff_for_scheme::<SIGTopLevelTargetSumLifetime8Dim64Base8>(kind, left, right)?
+        }
+        LifetimeTag::Pow18 => {
+            diff_for_scheme::<SIGTopLevelTargetSumLifetime18Dim64Base8>(kind, left, right)?
+        }
+        LifetimeTag::Pow32 => {
+            diff_for_scheme::<SIGTopLevelTargetSumLifetime32Dim64Base8>(kind, left, right)?
+        }
+    };
+    for diff in &diffs {
+        stdout.line(diff.describe(form));
+    }
+    stdout.line(format!("DIFF_RESULT:{}", diffs.len()));
+    stdout.set("kind", kind.as_str());
+    stdout.set("lifetime", lifetime.as_str());
+    stdout.set(
+        "differences",
+        diffs
+            .iter()
+            .map(|d| json!({ "location": d.location, "left": d.left, "right": d.right }))
+            .collect::<Vec<_>>(),
+    );
+    if diffs.is_empty() {
+        progress!(
+            "✅ {} {} and {} are identical",
+            kind.as_str(),
+            left.0,
+            right.0
+        );
+    } else {
+        progress!(
+            "❌ {} {} and {} differ in {} field(s)",
+            kind.as_str(),
+            left.0,
+            right.0,
+            diffs.len()
+        );
+        stdout.fail();
+    }
+    Ok(())
+}
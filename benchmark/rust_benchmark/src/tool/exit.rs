@@ -0,0 +1,140 @@
+//! Exit codes: what a failed command tells the script that ran it
+//!
+//! | code | kind                   | when                                               |
+//! |------|------------------------|----------------------------------------------------|
+//! | 0    |                        | success                                            |
+//! | 1    | `check_failed`         | a signature that does not verify, a KAT mismatch   |
+//! | 2    | `usage`                | bad arguments (clap's code), an epoch out of range |
+//! | 3    | `parse`                | an input file that cannot be decoded               |
+//! | 4    | `unsupported_lifetime` | a file for another or an unknown lifetime          |
+//! | 5    | `io`                   | a file that cannot be read or written              |
+//! | 6    | `other`                | anything else                                      |
+//! | 7    | `cancelled`            | ctrl-c or `--timeout` stopped a long operation     |
+//!
+//! Errors stay `Box<dyn Error>`; [`Failure::of`] recovers the kind from the
+//! error chain (`io::Error`, [`LifetimeError`], ...), and [`tag`] marks the
+//! errors that carry no type of their own, such as a decoder's message.
+
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+use crate::cancel::Cancelled;
+use crate::compat::json_schema::SchemaError;
+use crate::lifetime::LifetimeError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Failure {
+    CheckFailed,
+    Usage,
+    Parse,
+    UnsupportedLifetime,
+    Io,
+    Other,
+    Cancelled,
+}
+
+impl Failure {
+    pub const ALL: [Failure; 7] = [
+        Failure::CheckFailed,
+        Failure::Usage,
+        Failure::Parse,
+        Failure::UnsupportedLifetime,
+        Failure::Io,
+        Failure::Other,
+        Failure::Cancelled,
+    ];
+
+    pub const fn code(self) -> i32 {
+        match self {
+            Failure::CheckFailed => 1,
+            Failure::Usage => 2,
+            Failure::Parse => 3,
+            Failure::UnsupportedLifetime => 4,
+            Failure::Io => 5,
+            Failure::Other => 6,
+            Failure::Cancelled => 7,
+        }
+    }
+
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Failure::CheckFailed => "check_failed",
+            Failure::Usage => "usage",
+            Failure::Parse => "parse",
+            Failure::UnsupportedLifetime => "unsupported_lifetime",
+            Failure::Io => "io",
+            Failure::Other => "other",
+            Failure::Cancelled => "cancelled",
+        }
+    }
+
+    /// The kind of the first error in `error`'s source chain that has one.
+    pub fn of(error: &(dyn Error + 'static)) -> Self {
+        let mut current = Some(error);
+        while let Some(e) = current {
+            if let Some(tagged) = e.downcast_ref::<Tagged>() {
+                return tagged.failure;
+            }
+            if e.is::<Cancelled>() {
+                return Failure::Cancelled;
+            }
+            if e.is::<LifetimeError>() {
+                return Failure::UnsupportedLifetime;
+            }
+            if e.is::<io::Error>() {
+                return Failure::Io;
+            }
+            if let Some(json) = e.downcast_ref::<serde_json::Error>() {
+                return if json.is_io() {
+                    Failure::Io
+                } else {
+                    Failure::Parse
+                };
+            }
+            if e.is::<SchemaError>() {
+                return Failure::Parse;
+            }
+            current = e.source();
+        }
+        Failure::Other
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// An error marked with the kind it exits with. Displays as the error itself.
+#[derive(Debug)]
+struct Tagged {
+    failure: Failure,
+    error: Box<dyn Error>,
+}
+
+impl fmt::Display for Tagged {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+impl Error for Tagged {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.error.source()
+    }
+}
+
+/// For `map_err`: mark an error as `failure`, unless its chain already says
+/// what it is. An I/O error while reading an input stays [`Failure::Io`].
+pub fn tag<E: Into<Box<dyn Error>>>(failure: Failure) -> impl FnOnce(E) -> Box<dyn Error> {
+    move |error| {
+        let error = error.into();
+        if Failure::of(&*error) == Failure::Other {
+            Box::new(Tagged { failure, error })
+        } else {
+            error
+        }
+    }
+}
@@ -0,0 +1,170 @@
+//! `key-info`: what a key file is for and where it came from
+
+use std::error::Error;
+use std::path::Path;
+
+use serde_json::{json, Value};
+
+use super::convert::Artifact;
+use super::exit::{self, Failure};
+use super::output::Output;
+use crate::compat::codec;
+use crate::compat::hsz;
+use crate::compat::key_envelope;
+use crate::compat::multihash::Multibase;
+use crate::compat::pem;
+use crate::compat::pk_info::PublicKeyInfo;
+use crate::compat::ssz_root;
+use crate::config::Format;
+use crate::keystore::{self, FileKeyStore};
+use crate::lifetime::{LifetimeTag, SchemeMetadata};
+use crate::signer::PreparationState;
+use crate::upstream::{
+    SIGTopLevelTargetSumLifetime18Dim64Base8, SIGTopLevelTargetSumLifetime32Dim64Base8,
+    SIGTopLevelTargetSumLifetime8Dim64Base8,
+};
+
+fn public_key_fields<S: SchemeMetadata>(
+    bytes: &[u8],
+    format: Format,
+) -> Result<Vec<(&'static str, Value)>, Box<dyn Error>> {
+    let public_key = if pem::is_wrapped(bytes) {
+        pem::decode_public_key::<S>(bytes)
+    } else {
+        codec::decode_public_key::<S>(bytes, format)
+    }
+    .map_err(exit::tag(Failure::Parse))?;
+    let info = PublicKeyInfo::from_public_key::<S>(&public_key)?;
+    let root = hex::encode(ssz_root::public_key_hash_tree_root::<S>(&public_key)?);
+    Ok(vec![
+        (
+            "root",
+            json!(format!("0x{}", hex::encode(info.root_bytes()))),
+        ),
+        ("root_elements", json!(info.root)),
+        ("parameter", json!(info.parameter)),
+        ("public_sha3", json!(info.fingerprint_hex())),
+        (
+            "public_multihash",
+            json!(info.fingerprint_multibase(Multibase::Base58Btc)),
+        ),
+        ("public_key_root", json!(format!("0x{root}"))),
+    ])
+}
+
+fn secret_key_fields<S: SchemeMetadata>(
+    bytes: &[u8],
+    format: Format,
+) -> Result<Vec<(&'static str, Value)>, Box<dyn Error>> {
+    let secret_key =
+        codec::decode_secret_key::<S>(bytes, format).map_err(exit::tag(Failure::Parse))?;
+    let state = PreparationState::of(&secret_key);
+    // The root lives in the private top tree; the parameter is a plain field
+    let parameter = serde_json::to_value(&secret_key)?
+        .get("parameter")
+        .cloned()
+        .unwrap_or(Value::Null);
+    Ok(vec![
+        ("parameter", parameter),
+        (
+            "activation_interval",
+            json!([state.activation.start, state.activation.end]),
+        ),
+        (
+            "prepared_interval",
+            json!([state.prepared.start, state.prepared.end]),
+        ),
+        ("epochs_remaining", json!(state.epochs_remaining())),
+    ])
+}
+
+fn fields_for_scheme<S: SchemeMetadata>(
+    kind: Artifact,
+    bytes: &[u8],
+    format: Format,
+) -> Result<Vec<(&'static str, Value)>, Box<dyn Error>> {
+    match kind {
+        Artifact::PublicKey => public_key_fields::<S>(bytes, format),
+        Artifact::SecretKey => secret_key_fields::<S>(bytes, format),
+        Artifact::Signature => Err(exit::tag(Failure::Usage)(
+            "key-info reads public and secret keys; pass --kind pk or sk for files not named *_pk.* or *_sk.*",
+        )),
+    }
+}
+
+/// Print the scheme parameters of the key in `input` and the provenance its
+/// store recorded. The lifetime comes from the key itself when it names one
+/// (an enveloped secret key, a PEM/DER public key), else from `lifetime`,
+/// without which such a key is refused.
+pub fn key_info_command(
+    kind: Artifact,
+    input: &str,
+    from: Format,
+    lifetime: Option<LifetimeTag>,
+    stdout: &mut Output,
+) -> Result<(), Box<dyn Error>> {
+    let bytes = hsz::read(input)?;
+    let (envelope, bytes) = key_envelope::unwrap(&bytes)?;
+    let (lifetime, format) = match envelope {
+        Some(envelope) => (envelope.lifetime, envelope.format),
+        None if pem::is_wrapped(bytes) => (
+            pem::wrapped_lifetime(bytes).map_err(exit::tag(Failure::Parse))?,
+            Format::Ssz,
+        ),
+        None => (
+            lifetime.ok_or_else(|| super::unknown_lifetime(input))?,
+            from,
+        ),
+    };
+    let fields = match lifetime {
+        LifetimeTag::Pow8 => {
+            fields_for_scheme::<SIGTopLevelTargetSumLifetime8Dim64Base8>(kind, bytes, format)?
+        }
+        LifetimeTag::Pow18 => {
+            fields_for_scheme::<SIGTopLevelTargetSumLifetime18Dim64Base8>(kind, bytes, format)?
+        }
+        LifetimeTag::Pow32 => {
+            fields_for_scheme::<SIGTopLevelTargetSumLifetime32Dim64Base8>(kind, bytes, format)?
+        }
+    };
+    let meta = lifetime.metadata();
+    let mut lines = vec![
+        ("kind", json!(kind.as_str())),
+        ("format", json!(format.as_str())),
+        ("lifetime", json!(lifetime.as_str())),
+        (
+            "lifetime_source",
+            json!(lifetime_source(envelope.is_some(), bytes)),
+        ),
+        ("dimension", json!(meta.dimension)),
+        ("base", json!(meta.base)),
+        ("hash_len", json!(meta.hash_len)),
+        ("rand_len", json!(meta.rand_len)),
+        ("parameter_len", json!(meta.parameter_len)),
+    ];
+    lines.extend(fields);
+    let path = Path::new(input);
+    if let (Some(dir), Some(name)) = (path.parent(), keystore::key_name_of(path)) {
+        if let Some(provenance) = FileKeyStore::new(dir).provenance(&name)? {
+            lines.push(("provenance", provenance));
+        }
+    }
+    for (name, value) in lines {
+        match &value {
+            Value::String(text) => stdout.line(format!("{name}: {text}")),
+            other => stdout.line(format!("{name}: {other}")),
+        }
+        stdout.set(name, value);
+    }
+    Ok(())
+}
+
+fn lifetime_source(enveloped: bool, bytes: &[u8]) -> &'static str {
+    if enveloped {
+        "envelope"
+    } else if pem::is_wrapped(bytes) {
+        "pem"
+    } else {
+        "given"
+    }
+}
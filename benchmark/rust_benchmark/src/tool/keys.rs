@@ -0,0 +1,1095 @@
+//! `keygen`, `sign`, `verify` and their batch forms on the stored key pair, `epoch-info` and `advance`, plus `audit-key` and `export-pem`
+
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use rand::{rngs::StdRng, SeedableRng};
+use serde_json::{json, Value};
+use ssz::{Decode, DecodeError};
+
+use super::exit::{self, Failure};
+use super::output::{progress, Output};
+use super::stdio;
+use super::KEY_NAME;
+use crate::audit::{self, AuditEpochs};
+use crate::batch::Parallelism;
+use crate::batch_sign::{self, BatchEntry, BatchFile, BatchReport, Verdict};
+use crate::cancel;
+use crate::checkpoint::{self, KeygenCheckpoint};
+use crate::compat::codec;
+use crate::compat::hsz;
+use crate::compat::json::{json_bytes, JsonStyle};
+use crate::compat::key_envelope;
+use crate::compat::multihash::Multibase;
+use crate::compat::pem;
+use crate::compat::pk_info::PublicKeyInfo;
+use crate::compat::sk_binary;
+use crate::compat::ssz_codec;
+use crate::compat::ssz_root;
+use crate::compat::text_encoding::{self, Encoding};
+use crate::config::{Format, SchemeConfig};
+use crate::keystore::{FileKeyStore, KeyMetadata, SecretKeyStore, StoredKey};
+use crate::lifetime::{LifetimeTag, SchemeMetadata};
+use crate::memory;
+use crate::phases::Phases;
+use crate::pk_cache::Fingerprint;
+use crate::signer::PreparationState;
+use crate::upstream::{
+    SIGTopLevelTargetSumLifetime18Dim64Base8, SIGTopLevelTargetSumLifetime32Dim64Base8,
+    SIGTopLevelTargetSumLifetime8Dim64Base8, SignatureSchemeSecretKey,
+};
+
+/// Print the time each phase took on stderr and add it to the JSON document.
+fn report_phases(phases: &Phases, stdout: &mut Output) {
+    progress!("PHASES: {}", phases.describe());
+    stdout.set("phases", phases.to_json());
+}
+
+/// Print the process's peak resident memory on stderr and add it to the JSON
+/// document, where the platform reports it.
+fn report_peak_rss(stdout: &mut Output) -> Option<u64> {
+    let bytes = memory::peak_rss_bytes()?;
+    progress!("PEAK_RSS: {} ({} bytes)", memory::describe(bytes), bytes);
+    stdout.set("peak_rss_bytes", bytes);
+    Some(bytes)
+}
+
+/// Print the public key fingerprint in the same `PUBLIC_SHA3:` form as
+/// keygen_bench.zig so runs can be matched by key, and with `--multibase`
+/// as a multihash for content-addressed stores. With `--quiet` only the hex
+/// digest is printed, on stdout.
+fn print_public_digest<S: SchemeMetadata>(
+    public_key: &S::PublicKey,
+    multibase: Option<Multibase>,
+    stdout: &mut Output,
+) -> Result<(), Box<dyn Error>> {
+    let info = PublicKeyInfo::from_public_key::<S>(public_key)?;
+    if stdout.is_quiet() {
+        stdout.line(info.fingerprint_hex());
+    }
+    progress!("PUBLIC_SHA3: {}", info.fingerprint_hex());
+    stdout.set("public_sha3", info.fingerprint_hex());
+    if let Some(base) = multibase {
+        progress!("PUBLIC_MULTIHASH: {}", info.fingerprint_multibase(base));
+        stdout.set("public_multihash", info.fingerprint_multibase(base));
+    }
+    let root = hex::encode(ssz_root::public_key_hash_tree_root::<S>(public_key)?);
+    progress!("PUBLIC_KEY_ROOT: 0x{}", root);
+    stdout.set("public_key_root", format!("0x{root}"));
+    Ok(())
+}
+
+/// Generate the key pair `config` describes and store it.
+fn generate_and_store<S>(
+    config: &SchemeConfig,
+    json_style: JsonStyle,
+    multibase: Option<Multibase>,
+    store: &FileKeyStore,
+    phases: &mut Phases,
+    stdout: &mut Output,
+) -> Result<(S::SecretKey, KeyMetadata), Box<dyn Error>>
+where
+    S: SchemeMetadata + 'static,
+    S::PublicKey: Send,
+    S::SecretKey: Send,
+{
+    let seed = config.seed().ok_or("keygen requires a seed")?;
+    let format = config.format();
+    let mut rng = StdRng::from_seed(seed);
+    let (activation_epoch, num_active_epochs) =
+        (config.activation_epoch(), config.num_active_epochs());
+    // Key generation has no safe point inside; cancelling it writes nothing
+    let (public_key, secret_key) = phases.time("key_gen", || {
+        cancel::interruptible(move || S::key_gen(&mut rng, activation_epoch, num_active_epochs))
+    })?;
+    print_public_digest::<S>(&public_key, multibase, stdout)?;
+
+    let key = phases.time("serialize", || -> Result<_, Box<dyn Error>> {
+        Ok(StoredKey {
+            metadata: KeyMetadata {
+                lifetime: S::TAG,
+                format,
+            },
+            secret_key: codec::encode_secret_key::<S>(&secret_key, format)?,
+            // Untrimmed serde JSON, as earlier versions of this tool wrote it
+            public_key: match format {
+                Format::Json | Format::Bincode => {
+                    json_bytes(&serde_json::to_value(&public_key)?, json_style)?
+                }
+                _ => codec::encode_public_key::<S>(&public_key, format)?,
+            },
+        })
+    })?;
+    phases.time("write", || store.save(KEY_NAME, &key))?;
+    let sk_path = store.secret_key_path(KEY_NAME, format)?;
+    let pk_path = store.public_key_path(KEY_NAME, format)?;
+    progress!(
+        "✅ Secret key saved to {} ({} bytes)",
+        sk_path.display(),
+        key.secret_key.len()
+    );
+    progress!(
+        "✅ Public key saved to {} ({} bytes)",
+        pk_path.display(),
+        key.public_key.len()
+    );
+    stdout.set("lifetime", S::TAG.as_str());
+    stdout.set("format", format.to_string());
+    stdout.set("secret_key_path", sk_path.display().to_string());
+    stdout.set("secret_key_len", key.secret_key.len());
+    stdout.set("public_key_path", pk_path.display().to_string());
+    stdout.set("public_key_len", key.public_key.len());
+    Ok((secret_key, key.metadata))
+}
+
+/// The pair an interrupted keygen stored, to prepare it further.
+fn load_generated<S: SchemeMetadata>(
+    checkpoint: &KeygenCheckpoint,
+    multibase: Option<Multibase>,
+    store: &FileKeyStore,
+    stdout: &mut Output,
+) -> Result<(S::SecretKey, KeyMetadata), Box<dyn Error>> {
+    let key = store.metadata(KEY_NAME)?;
+    if key.lifetime != checkpoint.lifetime || key.format != checkpoint.format {
+        return Err(format!(
+            "the stored key ({}, {}) is not the one the checkpoint describes ({}, {})",
+            key.lifetime, key.format, checkpoint.lifetime, checkpoint.format
+        )
+        .into());
+    }
+    let public_key = codec::decode_public_key::<S>(
+        &hsz::read(store.stored_public_key_path(KEY_NAME)?)?,
+        key.format,
+    )
+    .map_err(exit::tag(Failure::Parse))?;
+    print_public_digest::<S>(&public_key, multibase, stdout)?;
+    let secret_key =
+        codec::read_secret_key_file::<S, _>(store.stored_secret_key_path(KEY_NAME)?, key.format)
+            .map_err(exit::tag(Failure::Parse))?;
+    stdout.set("lifetime", S::TAG.as_str());
+    stdout.set("format", key.format.to_string());
+    Ok((secret_key, key))
+}
+
+/// Generate and store the pair, unless `checkpoint` says that was done,
+/// then prepare it up to `checkpoint.prepare_to`, saving it as it goes.
+fn keygen_for_scheme<S>(
+    config: &SchemeConfig,
+    json_style: JsonStyle,
+    multibase: Option<Multibase>,
+    checkpoint: &mut KeygenCheckpoint,
+    store: &FileKeyStore,
+    stdout: &mut Output,
+) -> Result<(), Box<dyn Error>>
+where
+    S: SchemeMetadata + 'static,
+    S::PublicKey: Send,
+    S::SecretKey: Send,
+{
+    let mut phases = Phases::new();
+    let generated_here = !checkpoint.generated;
+    let (mut secret_key, key) = if checkpoint.generated {
+        phases.time("load", || {
+            load_generated::<S>(checkpoint, multibase, store, stdout)
+        })?
+    } else {
+        generate_and_store::<S>(config, json_style, multibase, store, &mut phases, stdout)?
+    };
+    if let Some(epoch) = checkpoint.prepare_to {
+        checkpoint.generated = true;
+        checkpoint.save(&checkpoint::path(store.dir()))?;
+        let state = advance_and_store::<S>(&mut secret_key, epoch, &key, store, &mut phases)?;
+        stdout.set("prepared", state.to_json(None));
+    }
+    report_phases(&phases, stdout);
+    let peak_rss = report_peak_rss(stdout);
+    // Kept with the key, so a benchmark can compare it with the Zig keygen later
+    if generated_here {
+        store.annotate(KEY_NAME, "keygen_phases_ms", phases.to_json())?;
+        store.annotate(KEY_NAME, "keygen_peak_rss_bytes", peak_rss.into())?;
+    }
+    Ok(())
+}
+
+fn run_keygen(
+    config: &SchemeConfig,
+    json_style: JsonStyle,
+    multibase: Option<Multibase>,
+    mut checkpoint: KeygenCheckpoint,
+    store: &FileKeyStore,
+    stdout: &mut Output,
+) -> Result<(), Box<dyn Error>> {
+    let path = checkpoint::path(store.dir());
+    checkpoint.save(&path)?;
+    let checkpoint = &mut checkpoint;
+    match config.lifetime() {
+        LifetimeTag::Pow8 => keygen_for_scheme::<SIGTopLevelTargetSumLifetime8Dim64Base8>(
+            config, json_style, multibase, checkpoint, store, stdout,
+        )?,
+        LifetimeTag::Pow18 => keygen_for_scheme::<SIGTopLevelTargetSumLifetime18Dim64Base8>(
+            config, json_style, multibase, checkpoint, store, stdout,
+        )?,
+        LifetimeTag::Pow32 => keygen_for_scheme::<SIGTopLevelTargetSumLifetime32Dim64Base8>(
+            config, json_style, multibase, checkpoint, store, stdout,
+        )?,
+    }
+    KeygenCheckpoint::remove(&path)?;
+    progress!("Keypair generated successfully!");
+    Ok(())
+}
+
+/// Generate a key pair into `store` and, with `prepare_to`, advance its
+/// prepared window to that epoch. Progress is checkpointed for `--resume`.
+pub fn keygen_command(
+    config: &SchemeConfig,
+    json_style: JsonStyle,
+    multibase: Option<Multibase>,
+    prepare_to: Option<u32>,
+    store: &FileKeyStore,
+    stdout: &mut Output,
+) -> Result<(), Box<dyn Error>> {
+    if let Some(epoch) = prepare_to {
+        config
+            .check_epoch(epoch)
+            .map_err(exit::tag(Failure::Usage))?;
+    }
+    let checkpoint = KeygenCheckpoint {
+        lifetime: config.lifetime(),
+        format: config.format(),
+        seed: config.seed().ok_or("keygen requires a seed")?,
+        activation_epoch: config.activation_epoch(),
+        num_active_epochs: config.num_active_epochs(),
+        prepare_to,
+        generated: false,
+    };
+    progress!("Generating keypair with lifetime {}...", config.lifetime());
+    run_keygen(config, json_style, multibase, checkpoint, store, stdout)
+}
+
+/// Finish the keygen whose checkpoint is in `store`, with the seed, window
+/// and format it recorded.
+pub fn resume_keygen_command(
+    json_style: JsonStyle,
+    multibase: Option<Multibase>,
+    store: &FileKeyStore,
+    stdout: &mut Output,
+) -> Result<(), Box<dyn Error>> {
+    let checkpoint = KeygenCheckpoint::load(&checkpoint::path(store.dir()))?.ok_or_else(|| {
+        exit::tag(Failure::Usage)(format!(
+            "no interrupted keygen to resume in {}",
+            store.dir().display()
+        ))
+    })?;
+    let config = SchemeConfig::new()
+        .lifetime(checkpoint.lifetime)
+        .seed(checkpoint.seed)
+        .activation(checkpoint.activation_epoch, checkpoint.num_active_epochs)
+        .format(checkpoint.format)
+        .build()?;
+    if checkpoint.generated {
+        progress!(
+            "Resuming keygen with lifetime {}: preparing the stored key...",
+            checkpoint.lifetime
+        );
+    } else {
+        progress!(
+            "Resuming keygen with lifetime {}: generating again from the recorded seed...",
+            checkpoint.lifetime
+        );
+    }
+    stdout.set("resumed", true);
+    run_keygen(&config, json_style, multibase, checkpoint, store, stdout)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn sign_for_scheme<S: SchemeMetadata>(
+    key: &KeyMetadata,
+    msg_bytes: &[u8; 32],
+    epoch: u32,
+    sig_format: Format,
+    encoding: Encoding,
+    out: Option<&str>,
+    store: &FileKeyStore,
+    stdout: &mut Output,
+) -> Result<(), Box<dyn Error>> {
+    let mut phases = Phases::new();
+    // Streamed, so a 2^32 key is never held as text and struct at once
+    let sk_path = store.stored_secret_key_path(KEY_NAME)?;
+    let mut secret_key = phases
+        .time("load", || {
+            codec::read_secret_key_file::<S, _>(sk_path, key.format)
+        })
+        .map_err(exit::tag(Failure::Parse))?;
+    let state = advance_and_store::<S>(&mut secret_key, epoch, key, store, &mut phases)?;
+    stdout.set("prepared_start", state.prepared.start);
+    stdout.set("prepared_end", state.prepared.end);
+    let signature = phases
+        .time("sign", || S::sign(&secret_key, epoch, msg_bytes))
+        .map_err(|e| format!("failed to sign message at epoch {epoch}: {e:?}"))?;
+    // SSZ, or the fixed 3116-byte leanSignature wire format
+    let sig_bytes = phases.time("serialize", || {
+        codec::encode_signature::<S>(&signature, sig_format)
+    })?;
+    let sig_file = text_encoding::encode(&sig_bytes, encoding);
+    let write_started = Instant::now();
+    let sig_path = match out {
+        Some(path) if stdio::is_dash(path) => {
+            stdio::write_stdout(&sig_file, encoding == Encoding::Raw)?;
+            PathBuf::from(path)
+        }
+        Some(path) => {
+            hsz::write(path, &sig_file)?;
+            PathBuf::from(path)
+        }
+        None => {
+            let path = store.signature_path(KEY_NAME, sig_format);
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            hsz::write(&path, &sig_file)?;
+            path
+        }
+    };
+    phases.add("write", write_started.elapsed());
+    progress!(
+        "✅ Signature saved to {} ({} bytes, {})",
+        sig_path.display(),
+        sig_bytes.len(),
+        encoding
+    );
+    let root = hex::encode(ssz_root::signature_hash_tree_root::<S>(&signature)?);
+    progress!("SIGNATURE_ROOT: 0x{}", root);
+    stdout.set("signature_path", sig_path.display().to_string());
+    stdout.set("signature_len", sig_bytes.len());
+    stdout.set("encoding", encoding.to_string());
+    stdout.set("signature_root", format!("0x{root}"));
+    report_phases(&phases, stdout);
+    report_peak_rss(stdout);
+    Ok(())
+}
+
+/// Decode the stored secret key and check that `epoch` can be signed with
+/// it, leaving the key as it is.
+fn sign_plan_for_scheme<S: SchemeMetadata>(
+    key: &KeyMetadata,
+    epoch: u32,
+    store: &FileKeyStore,
+) -> Result<(PreparationState, u64), Box<dyn Error>> {
+    let secret_key =
+        codec::read_secret_key_file::<S, _>(store.stored_secret_key_path(KEY_NAME)?, key.format)
+            .map_err(exit::tag(Failure::Parse))?;
+    let state = PreparationState::of(&secret_key);
+    let advances = advances_needed(&state, epoch)?;
+    Ok((state, advances))
+}
+
+/// With `dry_run`, the key, message and epoch are checked and the plan is
+/// printed, but nothing is prepared, signed or written.
+#[allow(clippy::too_many_arguments)]
+pub fn sign_command(
+    message: &str,
+    epoch: u32,
+    config: &SchemeConfig,
+    encoding: Encoding,
+    out: Option<&str>,
+    dry_run: bool,
+    store: &FileKeyStore,
+    stdout: &mut Output,
+) -> Result<(), Box<dyn Error>> {
+    progress!("Signing message: '{}' (epoch: {})", message, epoch);
+
+    // A message piped in means the signature is piped out
+    let out = out.or(stdio::is_dash(message).then_some(stdio::DASH));
+    if out.is_some_and(stdio::is_dash) && stdout.is_json() {
+        return Err(exit::tag(Failure::Usage)(
+            "--json needs stdout for its document; write the signature with --out <file>",
+        ));
+    }
+    let msg_bytes = stdio::message(message)?;
+
+    let key = store.metadata(KEY_NAME)?;
+    let lifetime = config.lifetime();
+    lifetime
+        .check_epoch(u64::from(epoch))
+        .map_err(exit::tag(Failure::Usage))?;
+    stdout.set("lifetime", lifetime.as_str());
+    stdout.set("epoch", epoch);
+    let sig_format = config.format();
+    if dry_run {
+        let (state, advances) = match lifetime {
+            LifetimeTag::Pow8 => {
+                sign_plan_for_scheme::<SIGTopLevelTargetSumLifetime8Dim64Base8>(&key, epoch, store)?
+            }
+            LifetimeTag::Pow18 => sign_plan_for_scheme::<SIGTopLevelTargetSumLifetime18Dim64Base8>(
+                &key, epoch, store,
+            )?,
+            LifetimeTag::Pow32 => sign_plan_for_scheme::<SIGTopLevelTargetSumLifetime32Dim64Base8>(
+                &key, epoch, store,
+            )?,
+        };
+        let sig_path = match out {
+            Some(path) => path.to_string(),
+            None => store
+                .signature_path(KEY_NAME, sig_format)
+                .display()
+                .to_string(),
+        };
+        stdout.line(format!(
+            "would advance the prepared window {:?} by {advances} bottom tree(s)",
+            state.prepared
+        ));
+        stdout.line(format!(
+            "would sign 0x{} at epoch {epoch} with the {lifetime} key",
+            hex::encode(msg_bytes)
+        ));
+        stdout.line(format!(
+            "would write a {sig_format} signature ({encoding}) to {sig_path}"
+        ));
+        stdout.set("dry_run", true);
+        stdout.set("message", format!("0x{}", hex::encode(msg_bytes)));
+        stdout.set("prepared", state.to_json(Some(u64::from(epoch))));
+        stdout.set("signature_path", sig_path);
+        stdout.set("encoding", encoding.to_string());
+        return Ok(());
+    }
+    match lifetime {
+        LifetimeTag::Pow8 => sign_for_scheme::<SIGTopLevelTargetSumLifetime8Dim64Base8>(
+            &key, &msg_bytes, epoch, sig_format, encoding, out, store, stdout,
+        )?,
+        LifetimeTag::Pow18 => sign_for_scheme::<SIGTopLevelTargetSumLifetime18Dim64Base8>(
+            &key, &msg_bytes, epoch, sig_format, encoding, out, store, stdout,
+        )?,
+        LifetimeTag::Pow32 => sign_for_scheme::<SIGTopLevelTargetSumLifetime32Dim64Base8>(
+            &key, &msg_bytes, epoch, sig_format, encoding, out, store, stdout,
+        )?,
+    }
+
+    progress!("Message signed successfully!");
+    Ok(())
+}
+
+/// Preparation advances before `epoch` can be signed, or a usage error if it
+/// never can.
+fn advances_needed(state: &PreparationState, epoch: u32) -> Result<u64, Box<dyn Error>> {
+    state.advances_needed(u64::from(epoch)).ok_or_else(|| {
+        exit::tag(Failure::Usage)(format!(
+            "epoch {epoch} cannot be prepared: activation interval {:?}, prepared {:?}",
+            state.activation, state.prepared
+        ))
+    })
+}
+
+/// Move the prepared window of `secret_key` until `epoch` is in it and, if it
+/// moved, store the key, so the next run does not repeat the work. The key is
+/// also stored every `checkpoint::INTERVAL` on the way, and when cancelled
+/// between two bottom trees, as far as it got.
+fn advance_and_store<S: SchemeMetadata>(
+    secret_key: &mut S::SecretKey,
+    epoch: u32,
+    key: &KeyMetadata,
+    store: &FileKeyStore,
+    phases: &mut Phases,
+) -> Result<PreparationState, Box<dyn Error>> {
+    let before = PreparationState::of(secret_key);
+    let advances = advances_needed(&before, epoch)?;
+    if advances == 0 {
+        return Ok(before);
+    }
+    progress!(
+        "Advancing the prepared window {:?} to epoch {epoch} ({advances} bottom tree(s))...",
+        before.prepared
+    );
+    let scope = cancel::Scope::enter();
+    let mut done = 0;
+    let mut saved = Instant::now();
+    while done < advances && !cancel::is_cancelled() {
+        phases.time("prepare", || secret_key.advance_preparation());
+        done += 1;
+        if done < advances && saved.elapsed() >= checkpoint::INTERVAL {
+            let encoded = phases.time("serialize", || {
+                codec::encode_secret_key::<S>(secret_key, key.format)
+            })?;
+            phases.time("write", || {
+                store.replace_secret_key(KEY_NAME, *key, &encoded)
+            })?;
+            progress!(
+                "Checkpoint: secret key saved with prepared interval {:?} ({done} of {advances} bottom tree(s))",
+                secret_key.get_prepared_interval()
+            );
+            saved = Instant::now();
+        }
+    }
+    drop(scope);
+    if done == advances
+        && !secret_key
+            .get_prepared_interval()
+            .contains(&u64::from(epoch))
+    {
+        return Err(format!(
+            "epoch {epoch} cannot be prepared (prepared {:?})",
+            secret_key.get_prepared_interval()
+        )
+        .into());
+    }
+    let after = PreparationState::of(secret_key);
+    if done > 0 {
+        let encoded = phases.time("serialize", || {
+            codec::encode_secret_key::<S>(secret_key, key.format)
+        })?;
+        phases.time("write", || {
+            store.replace_secret_key(KEY_NAME, *key, &encoded)
+        })?;
+        progress!(
+            "✅ Secret key saved with prepared interval {:?} ({done} of {advances} bottom tree(s))",
+            after.prepared
+        );
+    }
+    cancel::check()?;
+    Ok(after)
+}
+
+/// Advance the stored key's prepared window to `epoch` and save it.
+pub fn advance_command(
+    epoch: u32,
+    store: &FileKeyStore,
+    stdout: &mut Output,
+) -> Result<(), Box<dyn Error>> {
+    let key = store.metadata(KEY_NAME)?;
+    key.lifetime
+        .check_epoch(u64::from(epoch))
+        .map_err(exit::tag(Failure::Usage))?;
+    let state = match key.lifetime {
+        LifetimeTag::Pow8 => {
+            advance_for_scheme::<SIGTopLevelTargetSumLifetime8Dim64Base8>(&key, epoch, store)?
+        }
+        LifetimeTag::Pow18 => {
+            advance_for_scheme::<SIGTopLevelTargetSumLifetime18Dim64Base8>(&key, epoch, store)?
+        }
+        LifetimeTag::Pow32 => {
+            advance_for_scheme::<SIGTopLevelTargetSumLifetime32Dim64Base8>(&key, epoch, store)?
+        }
+    };
+    let info = state.to_json(Some(u64::from(epoch)));
+    stdout.line(format!("EPOCH_INFO:{info}"));
+    stdout.set("lifetime", key.lifetime.as_str());
+    stdout.set("epoch_info", info);
+    Ok(())
+}
+
+fn advance_for_scheme<S: SchemeMetadata>(
+    key: &KeyMetadata,
+    epoch: u32,
+    store: &FileKeyStore,
+) -> Result<PreparationState, Box<dyn Error>> {
+    let mut secret_key =
+        codec::read_secret_key_file::<S, _>(store.stored_secret_key_path(KEY_NAME)?, key.format)
+            .map_err(exit::tag(Failure::Parse))?;
+    advance_and_store::<S>(&mut secret_key, epoch, key, store, &mut Phases::new())
+}
+
+fn epoch_info_for_scheme<S: SchemeMetadata>(
+    key: &KeyMetadata,
+    store: &FileKeyStore,
+) -> Result<PreparationState, Box<dyn Error>> {
+    let secret_key =
+        codec::read_secret_key_file::<S, _>(store.stored_secret_key_path(KEY_NAME)?, key.format)
+            .map_err(exit::tag(Failure::Parse))?;
+    Ok(PreparationState::of(&secret_key))
+}
+
+/// Print the stored key's activation interval, prepared window and epochs
+/// remaining, and with `epoch` how many preparation advances signing it needs.
+pub fn epoch_info_command(
+    epoch: Option<u32>,
+    store: &FileKeyStore,
+    stdout: &mut Output,
+) -> Result<(), Box<dyn Error>> {
+    let key = store.metadata(KEY_NAME)?;
+    if let Some(epoch) = epoch {
+        key.lifetime
+            .check_epoch(u64::from(epoch))
+            .map_err(exit::tag(Failure::Usage))?;
+    }
+    let state = match key.lifetime {
+        LifetimeTag::Pow8 => {
+            epoch_info_for_scheme::<SIGTopLevelTargetSumLifetime8Dim64Base8>(&key, store)?
+        }
+        LifetimeTag::Pow18 => {
+            epoch_info_for_scheme::<SIGTopLevelTargetSumLifetime18Dim64Base8>(&key, store)?
+        }
+        LifetimeTag::Pow32 => {
+            epoch_info_for_scheme::<SIGTopLevelTargetSumLifetime32Dim64Base8>(&key, store)?
+        }
+    };
+    progress!("  Lifetime: {}", key.lifetime);
+    progress!("  Activation interval: {:?}", state.activation);
+    progress!("  Prepared interval: {:?}", state.prepared);
+    progress!("  Epochs remaining: {}", state.epochs_remaining());
+    let epoch = epoch.map(u64::from);
+    if let Some(epoch) = epoch {
+        match state.advances_needed(epoch) {
+            Some(0) => progress!("  Epoch {epoch} is prepared"),
+            Some(n) => progress!("  Epoch {epoch} needs {n} preparation advance(s)"),
+            None => progress!("  Epoch {epoch} cannot be signed with this key"),
+        }
+    }
+    let info = state.to_json(epoch);
+    stdout.line(format!("EPOCH_INFO:{info}"));
+    stdout.set("lifetime", key.lifetime.as_str());
+    stdout.set("epoch_info", info);
+    Ok(())
+}
+
+fn batch_sign_for_scheme<S: SchemeMetadata>(
+    key: &KeyMetadata,
+    entries: &[BatchEntry],
+    out: &Path,
+    encoding: Encoding,
+    store: &FileKeyStore,
+) -> Result<Value, Box<dyn Error>> {
+    let secret_key =
+        codec::read_secret_key_file::<S, _>(store.stored_secret_key_path(KEY_NAME)?, key.format)
+            .map_err(exit::tag(Failure::Parse))?;
+    batch_sign::sign_batch::<S>(
+        secret_key,
+        entries,
+        out,
+        key.format,
+        encoding,
+        store.is_compressed(),
+    )
+}
+
+/// Sign every `(epoch, message)` pair of the manifest at `manifest` (`-` for
+/// stdin) with the stored key, loaded once.
+pub fn batch_sign_command(
+    manifest: &str,
+    out: &Path,
+    encoding: Encoding,
+    store: &FileKeyStore,
+    stdout: &mut Output,
+) -> Result<(), Box<dyn Error>> {
+    let text = String::from_utf8(stdio::read(manifest)?).map_err(exit::tag(Failure::Parse))?;
+    let entries = batch_sign::parse_manifest(&text).map_err(exit::tag(Failure::Parse))?;
+    let key = store.metadata(KEY_NAME)?;
+    for entry in &entries {
+        key.lifetime
+            .check_epoch(u64::from(entry.epoch))
+            .map_err(exit::tag(Failure::Usage))?;
+    }
+    progress!(
+        "Signing {} messages from {} (epochs {}..={})...",
+        entries.len(),
+        manifest,
+        entries[0].epoch,
+        entries[entries.len() - 1].epoch
+    );
+    let index = match key.lifetime {
+        LifetimeTag::Pow8 => batch_sign_for_scheme::<SIGTopLevelTargetSumLifetime8Dim64Base8>(
+            &key, &entries, out, encoding, store,
+        )?,
+        LifetimeTag::Pow18 => batch_sign_for_scheme::<SIGTopLevelTargetSumLifetime18Dim64Base8>(
+            &key, &entries, out, encoding, store,
+        )?,
+        LifetimeTag::Pow32 => batch_sign_for_scheme::<SIGTopLevelTargetSumLifetime32Dim64Base8>(
+            &key, &entries, out, encoding, store,
+        )?,
+    };
+    let index_path = out.join(batch_sign::INDEX_NAME);
+    // Fewer than the manifest lists if cancelled
+    let signed = index["signatures"].as_array().map_or(0, Vec::len);
+    progress!(
+        "✅ {} of {} signatures saved to {}, index {}",
+        signed,
+        entries.len(),
+        out.display(),
+        index_path.display()
+    );
+    stdout.line(format!("BATCH_SIGN_RESULT:{signed}"));
+    stdout.set("out", out.display().to_string());
+    stdout.set("index_path", index_path.display().to_string());
+    stdout.set("index", index);
+    cancel::check()
+}
+
+fn batch_verify_for_scheme<S>(
+    pk_path: &str,
+    pk_bytes: &[u8],
+    dir: &Path,
+    files: &[BatchFile],
+    format: Format,
+    parallelism: Parallelism,
+) -> Result<BatchReport, Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::PublicKey: Sync,
+    S::Signature: Send + Sync,
+{
+    let public_key = if pem::is_wrapped(pk_bytes) {
+        pem::decode_public_key::<S>(pk_bytes)
+    } else {
+        codec::decode_public_key::<S>(pk_bytes, Format::from_key_path(pk_path))
+    }
+    .map_err(exit::tag(Failure::Parse))?;
+    batch_sign::verify_batch_dir::<S>(&public_key, dir, files, format, parallelism)
+}
+
+/// Verify the signatures in `dir` listed by `manifest`: a `batch-sign`
+/// index (the default, `<dir>/index.json`) or a `batch-sign` manifest, whose
+/// files are looked up as `sig_<epoch>.<ext>` in `format`. Lifetime and
+/// format come from the index when it has them, else from a PEM key or
+/// `lifetime` and `format`; with no lifetime from any of them the batch is
+/// refused. Signatures are decoded and verified on
+/// `threads` threads, 0 for the global pool.
+pub fn batch_verify_command(
+    pk_path: &str,
+    dir: &Path,
+    manifest: Option<&Path>,
+    lifetime: Option<LifetimeTag>,
+    format: Format,
+    threads: usize,
+    stdout: &mut Output,
+) -> Result<(), Box<dyn Error>> {
+    let manifest = manifest.map_or_else(|| dir.join(batch_sign::INDEX_NAME), Path::to_path_buf);
+    let manifest_bytes = hsz::read(&manifest)?;
+    let pk_bytes = hsz::read(pk_path)?;
+    let mut lifetime = if pem::is_wrapped(&pk_bytes) {
+        Some(pem::wrapped_lifetime(&pk_bytes).map_err(exit::tag(Failure::Parse))?)
+    } else {
+        lifetime
+    };
+    let mut format = format;
+    let files = match serde_json::from_slice::<Value>(&manifest_bytes) {
+        Ok(index) => {
+            if let Some(tag) = index.get("lifetime").and_then(Value::as_str) {
+                lifetime = Some(LifetimeTag::parse(Some(tag))?);
+            }
+            if let Some(name) = index.get("format").and_then(Value::as_str) {
+                format = Format::parse(name).map_err(exit::tag(Failure::Parse))?;
+            }
+            batch_sign::index_files(&index)
+        }
+        Err(_) => std::str::from_utf8(&manifest_bytes)
+            .map_err(Box::<dyn Error>::from)
+            .and_then(batch_sign::parse_manifest)
+            .map(|entries| batch_sign::manifest_files(&entries, format)),
+    }
+    .map_err(exit::tag(Failure::Parse))?;
+    let lifetime = lifetime.ok_or_else(|| super::unknown_lifetime(pk_path))?;
+    let parallelism = Parallelism::from_threads(threads);
+    progress!(
+        "Verifying {} signatures in {} against {} (lifetime {}, {}, {} threads)...",
+        files.len(),
+        dir.display(),
+        pk_path,
+        lifetime,
+        format,
+        parallelism.threads()
+    );
+    let report =
+        match lifetime {
+            LifetimeTag::Pow8 => {
+                batch_verify_for_scheme::<SIGTopLevelTargetSumLifetime8Dim64Base8>(
+                    pk_path,
+                    &pk_bytes,
+                    dir,
+                    &files,
+                    format,
+                    parallelism,
+                )?
+            }
+            LifetimeTag::Pow18 => batch_verify_for_scheme::<
+                SIGTopLevelTargetSumLifetime18Dim64Base8,
+            >(
+                pk_path, &pk_bytes, dir, &files, format, parallelism
+            )?,
+            LifetimeTag::Pow32 => batch_verify_for_scheme::<
+                SIGTopLevelTargetSumLifetime32Dim64Base8,
+            >(
+                pk_path, &pk_bytes, dir, &files, format, parallelism
+            )?,
+        };
+    for (file, verdict) in &report.items {
+        let epoch = file.entry.epoch;
+        match verdict {
+            Verdict::Valid => stdout.line(format!("PASS {} (epoch {epoch})", file.file)),
+            Verdict::Invalid => stdout.line(format!("FAIL {} (epoch {epoch})", file.file)),
+            Verdict::Unreadable(e) => {
+                stdout.line(format!("FAIL {} (epoch {epoch}): {e}", file.file))
+            }
+        }
+    }
+    let summary = report.to_json();
+    stdout.line(format!(
+        "BATCH_VERIFY_RESULT:{}",
+        json!({ "total": summary["total"], "passed": summary["passed"], "failed": summary["failed"] })
+    ));
+    stdout.set("lifetime", lifetime.as_str());
+    stdout.set("format", format.to_string());
+    stdout.set("threads", parallelism.threads());
+    stdout.set("report", summary);
+    if report.failed() == 0 {
+        progress!("✅ All {} signatures verified", report.items.len());
+    } else {
+        progress!(
+            "❌ {} of {} signatures failed",
+            report.failed(),
+            report.items.len()
+        );
+        stdout.fail();
+    }
+    Ok(())
+}
+
+/// Decode the signature at `sig_path` and the public key in `pk_bytes`,
+/// checking the key against `expect_key`.
+pub(super) fn load_for_verify<S: SchemeMetadata>(
+    sig_path: &str,
+    pk_path: &str,
+    pk_bytes: &[u8],
+    expect_key: Option<Fingerprint>,
+    format: Format,
+    stdout: &mut Output,
+) -> Result<(S::PublicKey, S::Signature), Box<dyn Error>> {
+    // JSON keys come with 3116-byte wire signatures, raw or base64
+    let sig_bytes = stdio::read(sig_path)?;
+    let signature = text_encoding::decode(&sig_bytes)
+        .and_then(|bytes| codec::decode_signature::<S>(&bytes, format))
+        .map_err(exit::tag(Failure::Parse))?;
+    let public_key = if pem::is_wrapped(pk_bytes) {
+        pem::decode_public_key::<S>(pk_bytes)
+    } else {
+        codec::decode_public_key::<S>(pk_bytes, format)
+    }
+    .map_err(exit::tag(Failure::Parse))?;
+    if let Some(expected) = expect_key {
+        let actual = Fingerprint::of::<S>(&public_key);
+        if actual != expected {
+            return Err(format!(
+                "public key {} has digest {}, expected {}",
+                pk_path, actual, expected
+            )
+            .into());
+        }
+        progress!("  Key digest: {} (matches)", actual);
+        stdout.set("key_digest", actual.to_string());
+    }
+    Ok((public_key, signature))
+}
+
+/// The verdict, or `None` with `dry_run`, where both files are only decoded.
+#[allow(clippy::too_many_arguments)]
+fn verify_for_scheme<S: SchemeMetadata>(
+    sig_path: &str,
+    pk_path: &str,
+    pk_bytes: &[u8],
+    msg_bytes: &[u8; 32],
+    epoch: u32,
+    expect_key: Option<Fingerprint>,
+    format: Format,
+    dry_run: bool,
+    stdout: &mut Output,
+) -> Result<Option<bool>, Box<dyn Error>> {
+    let mut phases = Phases::new();
+    let (public_key, signature) = phases.time("decode", || {
+        load_for_verify::<S>(sig_path, pk_path, pk_bytes, expect_key, format, stdout)
+    })?;
+    if dry_run {
+        return Ok(None);
+    }
+    let valid = phases.time("verify", || {
+        S::verify(&public_key, epoch, msg_bytes, &signature)
+    });
+    report_phases(&phases, stdout);
+    Ok(Some(valid))
+}
+
+/// `pk_bytes` is the already-read contents of `pk_path`, which is `-` for stdin.
+/// With `dry_run`, the signature and key are decoded but not checked against
+/// each other.
+#[allow(clippy::too_many_arguments)]
+pub fn verify_command(
+    sig_path: &str,
+    pk_path: &str,
+    pk_bytes: &[u8],
+    message: &str,
+    epoch: u32,
+    expect_key: Option<Fingerprint>,
+    config: &SchemeConfig,
+    dry_run: bool,
+    stdout: &mut Output,
+) -> Result<(), Box<dyn Error>> {
+    let lifetime = config.lifetime();
+    let format = config.format();
+    progress!("Verifying signature from Zig...");
+    progress!("  Signature: {}", sig_path);
+    progress!("  Public key: {}", pk_path);
+    progress!("  Message: '{}'", message);
+    progress!("  Epoch: {}", epoch);
+
+    let msg_bytes = stdio::message(message)?;
+
+    let verdict = match lifetime {
+        LifetimeTag::Pow8 => verify_for_scheme::<SIGTopLevelTargetSumLifetime8Dim64Base8>(
+            sig_path, pk_path, pk_bytes, &msg_bytes, epoch, expect_key, format, dry_run, stdout,
+        )?,
+        LifetimeTag::Pow18 => verify_for_scheme::<SIGTopLevelTargetSumLifetime18Dim64Base8>(
+            sig_path, pk_path, pk_bytes, &msg_bytes, epoch, expect_key, format, dry_run, stdout,
+        )?,
+        LifetimeTag::Pow32 => verify_for_scheme::<SIGTopLevelTargetSumLifetime32Dim64Base8>(
+            sig_path, pk_path, pk_bytes, &msg_bytes, epoch, expect_key, format, dry_run, stdout,
+        )?,
+    };
+
+    stdout.set("lifetime", lifetime.as_str());
+    stdout.set("epoch", epoch);
+    let Some(is_valid) = verdict else {
+        stdout.line(format!(
+            "would verify {sig_path} ({format}) against {pk_path} for 0x{} at epoch {epoch}, lifetime {lifetime}",
+            hex::encode(msg_bytes)
+        ));
+        stdout.set("dry_run", true);
+        stdout.set("message", format!("0x{}", hex::encode(msg_bytes)));
+        return Ok(());
+    };
+    stdout.set("valid", is_valid);
+    if stdout.is_quiet() {
+        stdout.line(is_valid);
+    }
+    if is_valid {
+        progress!("✅ Signature verification PASSED!");
+    } else {
+        progress!("❌ Signature verification FAILED!");
+        stdout.fail();
+    }
+    Ok(())
+}
+
+fn export_pem_for_scheme<S: SchemeMetadata>(
+    pk_path: &std::path::Path,
+) -> Result<String, Box<dyn Error>> {
+    let public_key =
+        codec::decode_public_key::<S>(&hsz::read(pk_path)?, Format::from_key_path(pk_path))
+            .map_err(exit::tag(Failure::Parse))?;
+    Ok(pem::encode_pem::<S>(&public_key))
+}
+
+pub fn export_pem_command(
+    pk_path: &std::path::Path,
+    out: Option<&str>,
+    lifetime: LifetimeTag,
+    stdout: &mut Output,
+) -> Result<(), Box<dyn Error>> {
+    let pem = match lifetime {
+        LifetimeTag::Pow8 => {
+            export_pem_for_scheme::<SIGTopLevelTargetSumLifetime8Dim64Base8>(pk_path)?
+        }
+        LifetimeTag::Pow18 => {
+            export_pem_for_scheme::<SIGTopLevelTargetSumLifetime18Dim64Base8>(pk_path)?
+        }
+        LifetimeTag::Pow32 => {
+            export_pem_for_scheme::<SIGTopLevelTargetSumLifetime32Dim64Base8>(pk_path)?
+        }
+    };
+    match out {
+        Some(path) => {
+            fs::write(path, &pem)?;
+            progress!("✅ PEM public key saved to {}", path);
+            stdout.set("path", path);
+        }
+        None => {
+            stdout.print(&pem);
+            stdout.set("pem", pem);
+        }
+    }
+    Ok(())
+}
+
+/// Load a key from `.ssz` or serde JSON, chosen by extension, optionally in
+/// an `.hsz` container and a key envelope.
+fn load_key<T>(path: &str) -> Result<T, Box<dyn Error>>
+where
+    T: Decode + serde::de::DeserializeOwned,
+{
+    if ssz_codec::is_ssz_path(path) {
+        let (_, bytes) = key_envelope::read(path)?;
+        T::from_ssz_bytes(&bytes).map_err(|e: DecodeError| {
+            exit::tag(Failure::Parse)(format!("Failed to decode {} from SSZ: {:?}", path, e))
+        })
+    } else {
+        // Streamed: a 2^32 JSON secret key is too large to buffer as text
+        let (_, reader) = key_envelope::open(path)?;
+        Ok(serde_json::from_reader(std::io::BufReader::new(reader))?)
+    }
+}
+
+fn audit_key_for_scheme<S>(
+    sk_path: &str,
+    pk_path: &str,
+    epochs: AuditEpochs,
+) -> Result<audit::AuditReport, Box<dyn Error>>
+where
+    S: SchemeMetadata,
+{
+    let public_key: S::PublicKey = load_key(pk_path)?;
+    let mut secret_key: S::SecretKey = if Format::from_key_path(sk_path) == Format::Bincode {
+        sk_binary::read_secret_key_file::<S, _>(sk_path).map_err(exit::tag(Failure::Parse))?
+    } else {
+        load_key(sk_path)?
+    };
+    Ok(audit::audit_key::<S>(
+        &public_key,
+        &mut secret_key,
+        epochs,
+        [0x42; 32],
+    ))
+}
+
+pub fn audit_key_command(
+    sk_path: &str,
+    pk_path: &str,
+    epochs: AuditEpochs,
+    config: &SchemeConfig,
+    stdout: &mut Output,
+) -> Result<(), Box<dyn Error>> {
+    let lifetime = config.lifetime();
+    progress!(
+        "Auditing secret key {} against public key {} (lifetime {})...",
+        sk_path,
+        pk_path,
+        lifetime
+    );
+    let report = match lifetime {
+        LifetimeTag::Pow8 => audit_key_for_scheme::<SIGTopLevelTargetSumLifetime8Dim64Base8>(
+            sk_path, pk_path, epochs,
+        )?,
+        LifetimeTag::Pow18 => audit_key_for_scheme::<SIGTopLevelTargetSumLifetime18Dim64Base8>(
+            sk_path, pk_path, epochs,
+        )?,
+        LifetimeTag::Pow32 => audit_key_for_scheme::<SIGTopLevelTargetSumLifetime32Dim64Base8>(
+            sk_path, pk_path, epochs,
+        )?,
+    };
+    stdout.line(format!("AUDIT_RESULT:{}", report.to_json()));
+    stdout.set("audit", report.to_json());
+    if report.is_consistent() {
+        progress!(
+            "✅ {} epochs in {:?} sign and verify against the stored public key",
+            report.checked.len(),
+            report.activation_interval
+        );
+    } else {
+        for failure in &report.failures {
+            progress!("   epoch {}: {}", failure.epoch, failure.reason);
+        }
+        progress!(
+            "❌ Key audit FAILED at {} of {} epochs",
+            report.failures.len(),
+            report.checked.len()
+        );
+        stdout.fail();
+    }
+    Ok(())
+}
@@ -0,0 +1,1130 @@
+//! The `hashzig-tool` command line
+//!
+//! One binary for everything the Rust side of the benchmark does from a shell:
+//! `keygen`, `sign`, `verify`, `batch-sign`, `batch-verify`, `epoch-info`,
+//! `advance` and `convert` on the stored key pair, plus `size`, `diff`,
+//! `key-info`, `audit-key`, `export-pem`, an in-memory `repl`, the
+//! known-answer vectors and `gen-messages` fuzzing manifests, the `bench`
+//! group (repeated timings, many seeds at once, signing and verification
+//! throughput, wire-format cost, gossip simulation, verification under load,
+//! thread scaling) and the `debug` group (the interop smoke test and the
+//! binary-format commands the Zig remote tests drive). The old binary names (`cross_lang_rust_tool`,
+//! `remote_hashsig_tool`, `smoke`) are shims over [`legacy_main`] that keep
+//! their original argument syntax. Exit codes are listed in [`exit`].
+
+mod bench;
+mod convert;
+mod diff;
+pub mod exit;
+mod info;
+mod keys;
+mod output;
+mod profile;
+mod remote;
+mod repl;
+mod smoke;
+mod stdio;
+mod vectors;
+mod watch;
+
+use std::error::Error;
+use std::ffi::OsString;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use clap::{ArgMatches, Args, CommandFactory, FromArgMatches, Parser, Subcommand};
+
+use crate::allocator;
+use crate::audit::{self, AuditEpochs};
+use crate::batch_sign::MessageStrategy;
+use crate::cancel;
+use crate::cli;
+use crate::compat::field_diff::ElementForm;
+use crate::compat::json::JsonStyle;
+use crate::compat::key_envelope;
+use crate::compat::multihash::Multibase;
+use crate::compat::pem;
+use crate::compat::text_encoding::Encoding;
+use crate::config::{Format, SchemeConfig};
+use crate::gossip::GossipMix;
+use crate::hypertree::{self, Epochs};
+use crate::kat;
+use crate::keystore::{self, FileKeyStore, SecretKeyStore};
+use crate::lifetime::{LifetimeError, LifetimeTag};
+use crate::pk_cache::Fingerprint;
+use crate::regime::Regime;
+use crate::seed;
+use crate::verify_load;
+use convert::Artifact;
+use exit::Failure;
+use output::{progress, Output};
+
+/// Name of the key pair this tool generates and signs with.
+const KEY_NAME: &str = keystore::DEFAULT_KEY_NAME;
+
+/// Lifetime of the stored key pair if `input` is one of its files: its
+/// public key, secret key or signature.
+fn stored_lifetime(store: &FileKeyStore, input: &str) -> Option<LifetimeTag> {
+    let metadata = store.metadata(KEY_NAME).ok()?;
+    let input = fs::canonicalize(input).ok()?;
+    let own = [
+        store.stored_public_key_path(KEY_NAME).ok(),
+        store.stored_secret_key_path(KEY_NAME).ok(),
+        Some(store.signature_path(KEY_NAME, metadata.format)),
+    ];
+    own.into_iter()
+        .flatten()
+        .filter_map(|path| fs::canonicalize(path).ok())
+        .any(|path| path == input)
+        .then_some(metadata.lifetime)
+}
+
+/// The refusal for a file that does not name its lifetime when none was
+/// passed: reading it as some default could verify under the wrong scheme.
+fn unknown_lifetime(input: &str) -> Box<dyn Error> {
+    exit::tag(Failure::Usage)(format!(
+        "{input} does not record its lifetime; pass --lifetime"
+    ))
+}
+
+/// `requested`, else the stored pair's lifetime for one of its own files.
+fn bare_lifetime(
+    store: &FileKeyStore,
+    requested: Option<LifetimeTag>,
+    input: &str,
+) -> Result<LifetimeTag, Box<dyn Error>> {
+    requested
+        .or_else(|| stored_lifetime(store, input))
+        .ok_or_else(|| unknown_lifetime(input))
+}
+
+/// The lifetime to use for a key that names `own`: `requested` if it agrees
+/// or `force` is set, an error if it does not, `own` if none was requested.
+fn key_lifetime(
+    own: LifetimeTag,
+    requested: Option<LifetimeTag>,
+    force: bool,
+    key: &str,
+) -> Result<LifetimeTag, Box<dyn Error>> {
+    match requested {
+        Some(requested) if requested != own && !force => Err(LifetimeError(format!(
+            "{key} is for lifetime {own}, not {requested}; pass --force-lifetime to use {requested} anyway"
+        ))
+        .into()),
+        Some(requested) => Ok(requested),
+        None => Ok(own),
+    }
+}
+
+/// Where a command's seed comes from; [`crate::seed`] has the accepted forms.
+/// Commands that took the seed as a positional argument keep it, as another
+/// spelling of `--seed`.
+#[derive(Args, Debug, Default)]
+pub struct SeedArgs {
+    /// 32-byte seed as 64 hex digits
+    #[arg(
+        long = "seed",
+        value_parser = cli::seed,
+        conflicts_with_all = ["seed_file", "random_seed"]
+    )]
+    seed_flag: Option<[u8; 32]>,
+    /// File holding the seed as 64 hex digits or 32 raw bytes
+    #[arg(long, conflicts_with = "random_seed")]
+    seed_file: Option<PathBuf>,
+    /// Use a fresh random seed, printed to stderr so the run can be repeated with --seed
+    #[arg(long)]
+    random_seed: bool,
+}
+
+impl SeedArgs {
+    /// The seed given by `--seed`, `--seed-file` or `positional`, else
+    /// `default`, else a random one, which is printed.
+    fn resolve(
+        &self,
+        positional: Option<[u8; 32]>,
+        default: Option<[u8; 32]>,
+    ) -> Result<[u8; 32], Box<dyn Error>> {
+        let given = match &self.seed_file {
+            _ if self.random_seed => None,
+            Some(path) => Some(seed::read_file(path).map_err(exit::tag(Failure::Parse))?),
+            None => self.seed_flag.or(positional).or(default),
+        };
+        match given {
+            Some(seed) => Ok(seed),
+            None => {
+                let seed = seed::random()?;
+                eprintln!("Random seed: {}", hex::encode(seed));
+                Ok(seed)
+            }
+        }
+    }
+}
+
+/// A stored result a bench run is checked against; see [`crate::baseline`].
+#[derive(Args, Debug)]
+pub struct BaselineArgs {
+    /// Earlier result of the same bench command (its --json output or result line) to compare with; a timing slower by more than --max-regression fails the run
+    #[arg(long, value_name = "JSON")]
+    baseline: Option<PathBuf>,
+    /// Slowdown over the baseline that counts as a regression, e.g. 5% or 0.05
+    #[arg(long, value_parser = cli::threshold, default_value = "5%")]
+    max_regression: f64,
+}
+
+/// Keygen, sign, verify and convert hash-based signatures for cross-language testing
+#[derive(Parser)]
+#[command(name = "hashzig-tool", version)]
+pub struct Cli {
+    /// Directory holding the rust_sk/rust_pk key pair, its metadata and rust_active_epochs.txt; relative to the working directory
+    #[arg(
+        long,
+        global = true,
+        visible_alias = "state-dir",
+        env = "HASHZIG_STATE_DIR",
+        default_value = keystore::DEFAULT_KEY_DIR
+    )]
+    key_dir: PathBuf,
+    /// Directory sign writes rust_sig.* to [default: the key directory]
+    #[arg(long, global = true, env = "HASHZIG_OUT_DIR")]
+    out_dir: Option<PathBuf>,
+    /// Write keys and signatures as zstd-compressed .hsz files (reading detects them either way)
+    #[arg(long, global = true)]
+    compress: bool,
+    /// Key and signature encoding for keygen/sign/verify [default: json, or ssz with --ssz]; bincode keeps the secret key in a compact binary file
+    #[arg(long, global = true, value_parser = cli::format, conflicts_with = "ssz")]
+    format: Option<Format>,
+    /// Use SSZ serialization instead of JSON/bincode
+    #[arg(long, global = true)]
+    ssz: bool,
+    /// Print a single JSON document on stdout (result, timings, artifact paths, error) instead of the usual lines
+    #[arg(long, global = true)]
+    json: bool,
+    /// Print only the essential result (keygen: the PUBLIC_SHA3 digest; verify: true or false) and errors
+    #[arg(short, long, global = true)]
+    quiet: bool,
+    /// Worker threads for parallel verification (batch-verify, bench verify-load), 0 for one per core; reported as `jobs` with --json
+    #[arg(
+        short = 'j',
+        long = "jobs",
+        global = true,
+        env = "HASHZIG_JOBS",
+        default_value_t = 0
+    )]
+    worker_threads: usize,
+    /// Stop keygen, sign, advance, batch-sign and the bench commands at their next safe point after this many seconds, saving what they have (exit code 7, as for ctrl-c)
+    #[arg(long, global = true, env = "HASHZIG_TIMEOUT", value_name = "SECONDS")]
+    timeout: Option<u64>,
+    /// Sample the command with pprof and write a flamegraph SVG when it finishes (--profile=<SVG>, default flamegraph.svg); needs --features profile
+    #[arg(
+        long,
+        global = true,
+        value_name = "SVG",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "flamegraph.svg"
+    )]
+    profile: Option<PathBuf>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+impl Cli {
+    fn store(&self) -> FileKeyStore {
+        let store = FileKeyStore::new(&self.key_dir).compressed(self.compress);
+        match &self.out_dir {
+            Some(dir) => store.signature_dir(dir),
+            None => store,
+        }
+    }
+
+    fn format(&self) -> Result<Format, Box<dyn Error>> {
+        let format = match self.format {
+            Some(format) => format,
+            None if self.ssz => Format::Ssz,
+            None => Format::Json,
+        };
+        if format == Format::ZigBinary {
+            return Err("the Zig binary layout is only supported by the debug commands".into());
+        }
+        Ok(format)
+    }
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Generate a keypair into the key directory
+    Keygen {
+        /// Same as --seed [default: random]
+        #[arg(
+            value_parser = cli::seed,
+            conflicts_with_all = ["seed_flag", "seed_file", "random_seed"]
+        )]
+        seed: Option<[u8; 32]>,
+        #[command(flatten)]
+        seed_args: SeedArgs,
+        /// 2^8, 2^18 or 2^32
+        #[arg(value_parser = cli::lifetime, default_value = "2^8")]
+        lifetime: LifetimeTag,
+        /// Write JSON public keys as canonical JSON (sorted keys, no whitespace)
+        #[arg(long, conflicts_with = "hex_json")]
+        canonical: bool,
+        /// Write JSON public keys with field elements as 0x-prefixed Montgomery hex strings, as the Zig tool prints them
+        #[arg(long)]
+        hex_json: bool,
+        /// Also print the PUBLIC_SHA3 digest as a multibase sha3-256 multihash (PUBLIC_MULTIHASH): base16, base32, base58btc or base64url
+        #[arg(long, value_parser = cli::multibase)]
+        multibase: Option<Multibase>,
+        /// Activate only the bottom trees holding these epochs, e.g. 5,1000..2000 (end exclusive), instead of 0..rust_active_epochs.txt; makes 2^32 keys for far epochs quick
+        #[arg(long, value_parser = cli::epochs)]
+        epochs: Option<Epochs>,
+        /// After generating, advance the prepared window to this epoch, saving the key every minute on the way
+        #[arg(long)]
+        prepare_to: Option<u32>,
+        /// Finish the keygen interrupted in the key directory, with the seed, lifetime, window and format it recorded
+        #[arg(
+            long,
+            conflicts_with_all = ["seed", "seed_flag", "seed_file", "random_seed", "lifetime", "epochs", "prepare_to"]
+        )]
+        resume: bool,
+    },
+    /// Sign a message with the stored key, saving <out-dir>/rust_sig.bin (or .ssz, .cbor, .msgpack); a key advanced to reach the epoch is saved too
+    Sign {
+        /// Message text, or - to read it from stdin
+        message: String,
+        epoch: u32,
+        /// Write the signature as raw bytes or base64 text (verify decodes base64 automatically)
+        #[arg(long, value_parser = cli::encoding, default_value = "raw")]
+        encoding: Encoding,
+        /// Signature file, or - for stdout [default: rust_sig.* in --out-dir, stdout when the message is read from stdin]
+        #[arg(long)]
+        out: Option<String>,
+        /// Fail unless the secret key is for this lifetime [default: the one its envelope names]
+        #[arg(long, value_parser = cli::lifetime)]
+        lifetime: Option<LifetimeTag>,
+        /// Sign for --lifetime even if the key names another one
+        #[arg(long, requires = "lifetime")]
+        force_lifetime: bool,
+        /// Check the key, message and epoch and print what would be done, without signing
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Show the stored secret key's activation interval, prepared window and epochs remaining
+    EpochInfo {
+        /// Also report how many preparation advances signing this epoch needs
+        #[arg(long)]
+        epoch: Option<u32>,
+    },
+    /// Advance the stored secret key's prepared window to an epoch and save it, so later sign calls start there
+    Advance {
+        #[arg(long)]
+        to_epoch: u32,
+    },
+    /// Sign every "<epoch> <message>" line of a manifest with the stored key, loaded once, writing <out>/sig_<epoch>.* and <out>/index.json
+    BatchSign {
+        /// Manifest file, or - for stdin
+        manifest: String,
+        out: PathBuf,
+        /// Write the signatures as raw bytes or base64 text
+        #[arg(long, value_parser = cli::encoding, default_value = "raw")]
+        encoding: Encoding,
+    },
+    /// Generate a batch-sign manifest for interop fuzzing, starting with the message-encoding corner cases
+    GenMessages {
+        #[arg(long, default_value_t = 64)]
+        count: u32,
+        /// random (32-byte hex), ascii (text of 1 to 64 bytes) or boundary (the corner cases only)
+        #[arg(long, value_parser = MessageStrategy::parse, default_value = "random")]
+        strategy: MessageStrategy,
+        #[arg(long, default_value_t = 0)]
+        first_epoch: u32,
+        #[command(flatten)]
+        seed: SeedArgs,
+        /// [default: stdout]
+        #[arg(short, long)]
+        out: Option<String>,
+    },
+    /// Verify a Zig signature
+    Verify {
+        /// Signature file, or - for stdin
+        signature: String,
+        /// Public key file, or - for stdin
+        public_key: String,
+        /// Message text, or - to read it from stdin
+        message: String,
+        epoch: u32,
+        /// Fail unless the public key's PUBLIC_SHA3 digest matches (hex or multihash)
+        #[arg(long, value_parser = cli::fingerprint)]
+        expect_key: Option<Fingerprint>,
+        /// Lifetime of a bare public key; a PEM/DER key must agree [default: the stored key's, for its own files]
+        #[arg(long, value_parser = cli::lifetime)]
+        lifetime: Option<LifetimeTag>,
+        /// Verify for --lifetime even if a PEM/DER key names another one
+        #[arg(long, requires = "lifetime")]
+        force_lifetime: bool,
+        /// Verify again whenever either file changes, listing the fields that changed, until interrupted
+        #[arg(long)]
+        watch: bool,
+        /// Decode the signature and public key and check the epoch, without verifying
+        #[arg(long, conflicts_with = "watch")]
+        dry_run: bool,
+    },
+    /// Verify a directory of signatures in parallel against one public key, reporting each file and the totals
+    BatchVerify {
+        /// Public key (JSON, SSZ, CBOR, MessagePack, PEM or DER)
+        #[arg(long)]
+        pk: String,
+        /// Directory holding the signatures
+        #[arg(long)]
+        dir: PathBuf,
+        /// batch-sign index or manifest [default: <dir>/index.json]
+        #[arg(long)]
+        manifest: Option<PathBuf>,
+        /// Used when neither the index nor a PEM key names the lifetime [default: the stored key's, for its own files]
+        #[arg(long, value_parser = cli::lifetime)]
+        lifetime: Option<LifetimeTag>,
+        /// Threads decoding and verifying the signatures [default: --jobs]
+        #[arg(long, default_value_t = 0)]
+        threads: usize,
+    },
+    /// Re-encode a signature or key (formats as --format, plus bin for the Zig binary layout)
+    Convert {
+        input: String,
+        output: String,
+        /// [default: the stored key's, for its own files]
+        #[arg(value_parser = cli::lifetime)]
+        lifetime: Option<LifetimeTag>,
+        /// [default: from the input file name]
+        #[arg(long, value_parser = cli::format)]
+        from: Option<Format>,
+        /// [default: from the output file name]
+        #[arg(long, value_parser = cli::format)]
+        to: Option<Format>,
+        /// sig, pk or sk [default: from the input file name]
+        #[arg(long, value_parser = Artifact::parse)]
+        kind: Option<Artifact>,
+        /// Decode and re-encode the input without writing the output
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Field-by-field differences between two public keys or two signatures, each in any format
+    Diff {
+        left: String,
+        right: String,
+        /// [default: the stored key's, for its own files]
+        #[arg(long, value_parser = cli::lifetime)]
+        lifetime: Option<LifetimeTag>,
+        /// [default: from the left file name]
+        #[arg(long, value_parser = cli::format)]
+        left_format: Option<Format>,
+        /// [default: from the right file name]
+        #[arg(long, value_parser = cli::format)]
+        right_format: Option<Format>,
+        /// sig or pk [default: from the left file name]
+        #[arg(long, value_parser = Artifact::parse)]
+        kind: Option<Artifact>,
+        /// Print differing field elements in Montgomery form, as the Zig tools do
+        #[arg(long)]
+        montgomery: bool,
+    },
+    /// Byte budget per component (co_path, rho, hashes or root, parameter) in every format
+    Size {
+        input: String,
+        /// [default: the stored key's, for its own files]
+        #[arg(long, value_parser = cli::lifetime)]
+        lifetime: Option<LifetimeTag>,
+        /// [default: from the input file name]
+        #[arg(long, value_parser = cli::format)]
+        from: Option<Format>,
+        /// sig or pk [default: from the input file name]
+        #[arg(long, value_parser = Artifact::parse)]
+        kind: Option<Artifact>,
+    },
+    /// Scheme parameters, root, parameter, fingerprint and recorded provenance of a public or secret key
+    KeyInfo {
+        input: String,
+        /// Used when the key does not name its lifetime [default: the stored key's, for its own files]
+        #[arg(long, value_parser = cli::lifetime)]
+        lifetime: Option<LifetimeTag>,
+        /// [default: from the input file name]
+        #[arg(long, value_parser = cli::format)]
+        from: Option<Format>,
+        /// pk or sk [default: from the input file name]
+        #[arg(long, value_parser = Artifact::parse)]
+        kind: Option<Artifact>,
+    },
+    /// Check a secret key against its public key by signing and verifying at sampled epochs
+    AuditKey {
+        sk: String,
+        /// [default: the secret key path with _sk. replaced by _pk.]
+        pk: Option<String>,
+        /// A sample count, or `all`
+        #[arg(value_parser = cli::audit_epochs)]
+        samples: Option<AuditEpochs>,
+    },
+    /// Wrap the stored (or given) public key as HASHSIG PUBLIC KEY PEM
+    ExportPem {
+        pk: Option<PathBuf>,
+        /// [default: stdout]
+        out: Option<String>,
+    },
+    /// Keep one key pair in memory and read sign/verify/trace/dump commands from stdin; `help` lists them
+    Repl {
+        #[arg(long, value_parser = cli::lifetime, default_value = "2^8")]
+        lifetime: LifetimeTag,
+        #[arg(long, default_value_t = 256)]
+        active_epochs: usize,
+        #[command(flatten)]
+        seed: SeedArgs,
+    },
+    /// Known-answer vectors: keys and signatures in every format plus a manifest of digests
+    GenKat {
+        /// One 64-hex-digit seed per line
+        #[arg(long)]
+        seeds: PathBuf,
+        #[arg(long, value_parser = cli::lifetime, default_value = "2^8")]
+        lifetime: LifetimeTag,
+        #[arg(long, value_delimiter = ',', default_value = "0,1,255")]
+        epochs: Vec<u32>,
+        #[arg(short, long, default_value = "kat")]
+        out: PathBuf,
+    },
+    /// Re-derive a gen-kat bundle from its seeds and byte-compare every file and manifest entry
+    CheckKat { dir: PathBuf },
+    /// Throughput and latency measurements
+    #[command(subcommand)]
+    Bench(BenchCommand),
+    /// Smoke test and the binary-format sign/verify used by the Zig remote tests
+    #[command(
+        subcommand,
+        after_help = "verify reads SSZ instead of JSON/binary for any input path ending in .ssz\nsign and verify use the binary public key layout for pk paths ending in .bin"
+    )]
+    Debug(DebugCommand),
+}
+
+#[derive(Subcommand)]
+enum BenchCommand {
+    /// Keygen, sign and verify repeatedly, with mean, median, stddev and p95 per operation
+    Ops {
+        /// Measured runs
+        #[arg(long, default_value_t = 10)]
+        iterations: usize,
+        /// Unmeasured runs first, to warm caches and the allocator
+        #[arg(long, default_value_t = 1)]
+        warmup: usize,
+        /// warm: repeat in this process after the warmup runs; cold: one fresh process per iteration with the binary dropped from the page cache, no warmup
+        #[arg(long, value_parser = cli::regime, default_value = "warm")]
+        mode: Regime,
+        #[arg(long, value_parser = cli::lifetime, default_value = "2^8")]
+        lifetime: LifetimeTag,
+        #[command(flatten)]
+        seed_args: SeedArgs,
+        #[command(flatten)]
+        baseline: BaselineArgs,
+    },
+    /// Keygen, sign and verify for every seed of a seeds file, several seeds at once, flagging seeds whose public key changes between runs
+    MultiSeed {
+        /// One seed per line, as for gen-kat
+        #[arg(long)]
+        seeds: PathBuf,
+        /// Pipeline runs per seed; two or more check determinism
+        #[arg(long, default_value_t = 2)]
+        runs: usize,
+        /// Seeds in flight at once [default: --jobs]
+        #[arg(long, default_value_t = 0)]
+        concurrency: usize,
+        #[arg(long, value_parser = cli::lifetime, default_value = "2^8")]
+        lifetime: LifetimeTag,
+    },
+    /// Sign many messages at distinct epochs from one key: signatures per second and the cost of advancing the prepared window
+    SignThroughput {
+        /// Messages to sign, one per epoch
+        #[arg(long, default_value_t = 256)]
+        count: u32,
+        /// Epochs between two signatures
+        #[arg(long, default_value_t = 1)]
+        stride: u32,
+        #[arg(long, value_parser = cli::lifetime, default_value = "2^8")]
+        lifetime: LifetimeTag,
+        #[command(flatten)]
+        seed_args: SeedArgs,
+    },
+    /// Verified signatures per second, serially and with --parallel also on the -j pool
+    Verify {
+        /// Signatures to verify
+        #[arg(long, default_value_t = 256)]
+        count: usize,
+        /// Also verify the batch split across the worker pool
+        #[arg(long)]
+        parallel: bool,
+        #[arg(long, value_parser = cli::lifetime, default_value = "2^8")]
+        lifetime: LifetimeTag,
+        #[command(flatten)]
+        seed_args: SeedArgs,
+    },
+    /// Encode and decode time and byte size of each artifact in JSON, bincode, SSZ and the Zig binary format
+    Formats {
+        /// A lifetime, or `all`
+        #[arg(long, default_value = "2^8")]
+        lifetime: String,
+        /// Encodes and decodes per artifact and format; the median is reported
+        #[arg(long, default_value_t = 100)]
+        iterations: usize,
+        #[command(flatten)]
+        seed_args: SeedArgs,
+    },
+    /// Verify a seeded adversarial gossip corpus
+    SimulateGossip {
+        count: usize,
+        /// Same as --seed [default: random]
+        #[arg(
+            value_parser = cli::seed,
+            conflicts_with_all = ["seed_flag", "seed_file", "random_seed"]
+        )]
+        seed: Option<[u8; 32]>,
+        #[command(flatten)]
+        seed_args: SeedArgs,
+        #[arg(value_parser = cli::lifetime, default_value = "2^8")]
+        lifetime: LifetimeTag,
+        /// e.g. valid=0.7,stale=0.1,malformed=0.1,duplicate=0.1
+        #[arg(value_parser = cli::gossip_mix)]
+        mix: Option<GossipMix>,
+    },
+    /// Verification latency under load (p50/p99, max rate within SLO)
+    VerifyLoad {
+        jobs: usize,
+        /// A lifetime, or `all`
+        #[arg(default_value = "2^8")]
+        lifetime: String,
+        /// Worker threads [default: --jobs]
+        #[arg(default_value_t = 0)]
+        threads: usize,
+        /// p99 latency target in milliseconds
+        slo_ms: Option<u64>,
+    },
+    /// Keygen, sign and verify at each thread count, with speedup and efficiency
+    Scaling {
+        /// Thread counts to run at, compared with the first
+        #[arg(long, value_delimiter = ',', default_value = "1,2,4,8,16")]
+        threads: Vec<usize>,
+        #[arg(long, value_parser = cli::lifetime, default_value = "2^8")]
+        lifetime: LifetimeTag,
+        /// Messages signed and verified per run
+        #[arg(long, default_value_t = 256)]
+        signatures: usize,
+        #[command(flatten)]
+        seed_args: SeedArgs,
+        #[command(flatten)]
+        baseline: BaselineArgs,
+    },
+}
+
+#[derive(Subcommand)]
+enum DebugCommand {
+    /// Interop smoke test: keygen, sign, verify and format round trips at 2^8
+    Smoke,
+    #[command(flatten)]
+    Remote(remote::Command),
+}
+
+/// Size rayon's global pool to `threads`, or one per core for 0, and return
+/// the size it got.
+fn configure_worker_pool(threads: usize) -> Result<usize, Box<dyn Error>> {
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build_global()?;
+    Ok(rayon::current_num_threads())
+}
+
+fn run(cli: Cli, stdout: &mut Output) -> Result<(), Box<dyn Error>> {
+    let store = cli.store();
+    let format = cli.format()?;
+    stdout.set("jobs", configure_worker_pool(cli.worker_threads)?);
+    stdout.set("allocator", allocator::NAME);
+    cancel::install(
+        cli.timeout.map(Duration::from_secs),
+        Failure::Cancelled.code(),
+    )?;
+
+    match cli.command {
+        Command::Keygen {
+            seed,
+            seed_args,
+            lifetime,
+            canonical,
+            hex_json,
+            multibase,
+            epochs,
+            prepare_to,
+            resume,
+        } => {
+            let json_style = if canonical {
+                JsonStyle::Canonical
+            } else if hex_json {
+                JsonStyle::Hex
+            } else {
+                JsonStyle::Pretty
+            };
+            if resume {
+                return keys::resume_keygen_command(json_style, multibase, &store, stdout);
+            }
+            let (activation_epoch, num_active_epochs) = match epochs {
+                Some(epochs) => {
+                    let plan =
+                        hypertree::plan(lifetime, &epochs.0).map_err(exit::tag(Failure::Usage))?;
+                    progress!(
+                        "Activating epochs {}..{} ({} bottom trees of {})",
+                        plan.activation.start,
+                        plan.activation.end,
+                        plan.bottom_trees(),
+                        hypertree::bottom_tree_len(lifetime)
+                    );
+                    stdout.set("hypertree", plan.to_json());
+                    (plan.activation_epoch(), plan.num_active_epochs())
+                }
+                // Read active epochs from file (default to 256 if not found)
+                None => (
+                    0,
+                    fs::read_to_string(store.dir().join("rust_active_epochs.txt"))
+                        .ok()
+                        .and_then(|s| s.trim().parse().ok())
+                        .unwrap_or(256),
+                ),
+            };
+            let config = SchemeConfig::new()
+                .lifetime(lifetime)
+                .seed(seed_args.resolve(seed, None)?)
+                .activation(activation_epoch, num_active_epochs)
+                .format(format)
+                .build()?;
+            keys::keygen_command(&config, json_style, multibase, prepare_to, &store, stdout)?;
+        }
+        Command::Sign {
+            message,
+            epoch,
+            encoding,
+            out,
+            lifetime,
+            force_lifetime,
+            dry_run,
+        } => {
+            let key = store.metadata(KEY_NAME)?;
+            let lifetime = key_lifetime(
+                key.lifetime,
+                lifetime,
+                force_lifetime,
+                "the stored secret key",
+            )?;
+            let config = SchemeConfig::new()
+                .lifetime(lifetime)
+                .format(format)
+                .build()?;
+            keys::sign_command(
+                &message,
+                epoch,
+                &config,
+                encoding,
+                out.as_deref(),
+                dry_run,
+                &store,
+                stdout,
+            )?;
+        }
+        Command::EpochInfo { epoch } => keys::epoch_info_command(epoch, &store, stdout)?,
+        Command::Advance { to_epoch } => keys::advance_command(to_epoch, &store, stdout)?,
+        Command::BatchSign {
+            manifest,
+            out,
+            encoding,
+        } => {
+            keys::batch_sign_command(&manifest, &out, encoding, &store, stdout)?;
+        }
+        Command::GenMessages {
+            count,
+            strategy,
+            first_epoch,
+            seed,
+            out,
+        } => {
+            vectors::gen_messages_command(
+                count,
+                first_epoch,
+                strategy,
+                seed.resolve(None, None)?,
+                out.as_deref(),
+                stdout,
+            )?;
+        }
+        Command::BatchVerify {
+            pk,
+            dir,
+            manifest,
+            lifetime,
+            threads,
+        } => {
+            let lifetime = lifetime.or_else(|| stored_lifetime(&store, &pk));
+            keys::batch_verify_command(
+                &pk,
+                &dir,
+                manifest.as_deref(),
+                lifetime,
+                format,
+                threads,
+                stdout,
+            )?;
+        }
+        Command::Verify {
+            signature,
+            public_key,
+            message,
+            epoch,
+            expect_key,
+            lifetime,
+            force_lifetime,
+            watch,
+            dry_run,
+        } => {
+            stdio::check_single_stdin(&[&signature, &public_key, &message])?;
+            let pk_bytes = stdio::read(&public_key)?;
+            // A PEM/DER key names its own lifetime
+            let lifetime = if pem::is_wrapped(&pk_bytes) {
+                let own = pem::wrapped_lifetime(&pk_bytes).map_err(exit::tag(Failure::Parse))?;
+                key_lifetime(own, lifetime, force_lifetime, &public_key)?
+            } else {
+                bare_lifetime(&store, lifetime, &public_key)?
+            };
+            lifetime
+                .check_epoch(u64::from(epoch))
+                .map_err(exit::tag(Failure::Usage))?;
+            let config = SchemeConfig::new()
+                .lifetime(lifetime)
+                .format(format)
+                .build()?;
+            if watch {
+                watch::verify_watch_command(
+                    &signature,
+                    &public_key,
+                    &message,
+                    epoch,
+                    expect_key,
+                    &config,
+                    stdout,
+                )?;
+            } else {
+                keys::verify_command(
+                    &signature,
+                    &public_key,
+                    &pk_bytes,
+                    &message,
+                    epoch,
+                    expect_key,
+                    &config,
+                    dry_run,
+                    stdout,
+                )?;
+            }
+        }
+        Command::Convert {
+            input,
+            output,
+            lifetime,
+            from,
+            to,
+            kind,
+            dry_run,
+        } => {
+            // Formats default to what the file names say; `.bin` means the wire format `sign` writes
+            let from = from.unwrap_or_else(|| Format::from_key_path(&input));
+            let to = to.unwrap_or_else(|| Format::from_key_path(&output));
+            let kind = kind.unwrap_or_else(|| Artifact::from_path(&input));
+            let envelope = match kind {
+                Artifact::SecretKey if lifetime.is_none() => key_envelope::read_header(&input)?,
+                _ => None,
+            };
+            let lifetime = match envelope {
+                Some(envelope) => envelope.lifetime,
+                None => bare_lifetime(&store, lifetime, &input)?,
+            };
+            convert::convert_command(kind, &input, &output, from, to, lifetime, dry_run, stdout)?;
+        }
+        Command::Diff {
+            left,
+            right,
+            lifetime,
+            left_format,
+            right_format,
+            kind,
+            montgomery,
+        } => {
+            let left_format = left_format.unwrap_or_else(|| Format::from_key_path(&left));
+            let right_format = right_format.unwrap_or_else(|| Format::from_key_path(&right));
+            let kind = kind.unwrap_or_else(|| Artifact::from_path(&left));
+            let lifetime = lifetime
+                .or_else(|| stored_lifetime(&store, &left))
+                .or_else(|| stored_lifetime(&store, &right))
+                .ok_or_else(|| unknown_lifetime(&left))?;
+            let form = if montgomery {
+                ElementForm::Montgomery
+            } else {
+                ElementForm::Canonical
+            };
+            diff::diff_command(
+                kind,
+                (&left, left_format),
+                (&right, right_format),
+                lifetime,
+                form,
+                stdout,
+            )?;
+        }
+        Command::Size {
+            input,
+            lifetime,
+            from,
+            kind,
+        } => {
+            let from = from.unwrap_or_else(|| Format::from_key_path(&input));
+            let kind = kind.unwrap_or_else(|| Artifact::from_path(&input));
+            let lifetime = bare_lifetime(&store, lifetime, &input)?;
+            convert::size_command(kind, &input, from, lifetime, stdout)?;
+        }
+        Command::KeyInfo {
+            input,
+            lifetime,
+            from,
+            kind,
+        } => {
+            let from = from.unwrap_or_else(|| Format::from_key_path(&input));
+            let kind = kind.unwrap_or_else(|| Artifact::from_path(&input));
+            let lifetime = lifetime.or_else(|| stored_lifetime(&store, &input));
+            info::key_info_command(kind, &input, from, lifetime, stdout)?;
+        }
+        Command::AuditKey { sk, pk, samples } => {
+            let pk = pk.unwrap_or_else(|| sk.replace("_sk.", "_pk."));
+            let epochs = samples.unwrap_or(AuditEpochs::Sample(audit::DEFAULT_SAMPLES));
+            let lifetime = match key_envelope::read_header(&sk)? {
+                Some(envelope) => envelope.lifetime,
+                None => bare_lifetime(&store, None, &sk)?,
+            };
+            let config = SchemeConfig::new().lifetime(lifetime).build()?;
+            keys::audit_key_command(&sk, &pk, epochs, &config, stdout)?;
+        }
+        Command::ExportPem { pk, out } => {
+            let metadata = store.metadata(KEY_NAME);
+            let pk_path = match pk {
+                Some(path) => path,
+                None => store.public_key_path(
+                    KEY_NAME,
+                    metadata.as_ref().map(|m| m.format).unwrap_or(format),
+                )?,
+            };
+            let lifetime = metadata.map(|m| m.lifetime).unwrap_or(LifetimeTag::Pow8);
+            keys::export_pem_command(&pk_path, out.as_deref(), lifetime, stdout)?;
+        }
+        Command::Repl {
+            lifetime,
+            active_epochs,
+            seed,
+        } => repl::repl_command(lifetime, seed.resolve(None, None)?, active_epochs, stdout)?,
+        Command::GenKat {
+            seeds,
+            lifetime,
+            mut epochs,
+            out,
+        } => {
+            epochs.sort_unstable();
+            epochs.dedup();
+            let seeds = kat::parse_seeds(&fs::read_to_string(&seeds)?)
+                .map_err(exit::tag(Failure::Parse))?;
+            vectors::gen_kat_command(&seeds, &epochs, &out, lifetime, stdout)?;
+        }
+        Command::CheckKat { dir } => vectors::check_kat_command(&dir, stdout)?,
+        Command::Bench(BenchCommand::Ops {
+            iterations,
+            warmup,
+            mode,
+            lifetime,
+            seed_args,
+            baseline,
+        }) => {
+            bench::ops_command(
+                lifetime,
+                iterations,
+                warmup,
+                seed_args.resolve(None, None)?,
+                mode,
+                &baseline,
+                stdout,
+            )?;
+        }
+        Command::Bench(BenchCommand::MultiSeed {
+            seeds,
+            runs,
+            concurrency,
+            lifetime,
+        }) => {
+            let seeds = kat::parse_seeds(&fs::read_to_string(&seeds)?)
+                .map_err(exit::tag(Failure::Parse))?;
+            bench::multi_seed_command(&seeds, runs, concurrency, lifetime, stdout)?;
+        }
+        Command::Bench(BenchCommand::SignThroughput {
+            count,
+            stride,
+            lifetime,
+            seed_args,
+        }) => {
+            bench::sign_throughput_command(
+                lifetime,
+                count,
+                stride,
+                seed_args.resolve(None, None)?,
+                stdout,
+            )?;
+        }
+        Command::Bench(BenchCommand::Verify {
+            count,
+            parallel,
+            lifetime,
+            seed_args,
+        }) => {
+            bench::verify_throughput_command(
+                lifetime,
+                count,
+                parallel,
+                seed_args.resolve(None, None)?,
+                stdout,
+            )?;
+        }
+        Command::Bench(BenchCommand::Formats {
+            lifetime,
+            iterations,
+            seed_args,
+        }) => {
+            let lifetimes = match lifetime.as_str() {
+                "all" => LifetimeTag::ALL.to_vec(),
+                other => vec![LifetimeTag::parse(Some(other))?],
+            };
+            bench::formats_command(
+                &lifetimes,
+                iterations,
+                seed_args.resolve(None, None)?,
+                stdout,
+            )?;
+        }
+        Command::Bench(BenchCommand::SimulateGossip {
+            count,
+            seed,
+            seed_args,
+            lifetime,
+            mix,
+        }) => {
+            bench::simulate_gossip_command(
+                count,
+                seed_args.resolve(seed, None)?,
+                lifetime,
+                &mix.unwrap_or_default(),
+                stdout,
+            )?;
+        }
+        Command::Bench(BenchCommand::VerifyLoad {
+            jobs,
+            lifetime,
+            threads,
+            slo_ms,
+        }) => {
+            let lifetimes = match lifetime.as_str() {
+                "all" => LifetimeTag::ALL.to_vec(),
+                other => vec![LifetimeTag::parse(Some(other))?],
+            };
+            let slo = slo_ms.map_or(verify_load::DEFAULT_SLO, Duration::from_millis);
+            let threads = if threads == 0 {
+                cli.worker_threads
+            } else {
+                threads
+            };
+            bench::verify_load_command(jobs, &lifetimes, threads, slo, stdout)?;
+        }
+        Command::Bench(BenchCommand::Scaling {
+            threads,
+            lifetime,
+            signatures,
+            seed_args,
+            baseline,
+        }) => {
+            bench::scaling_command(
+                &threads,
+                lifetime,
+                signatures,
+                seed_args.resolve(None, None)?,
+                &baseline,
+                stdout,
+            )?;
+        }
+        Command::Debug(DebugCommand::Smoke) => smoke::run(stdout),
+        Command::Debug(DebugCommand::Remote(command)) => remote::run(command, stdout)?,
+    }
+
+    Ok(())
+}
+
+/// Subcommand path of `matches`, e.g. `bench verify-load`.
+fn command_name(matches: &ArgMatches) -> String {
+    let mut names = Vec::new();
+    let mut matches = matches;
+    while let Some((name, sub)) = matches.subcommand() {
+        names.push(name);
+        matches = sub;
+    }
+    names.join(" ")
+}
+
+/// Parse `args` (program name first), run the command and report it, exiting
+/// with the code [`exit::Failure`] assigns to an error or a failed check.
+fn run_args(args: Vec<OsString>) {
+    let matches = Cli::command().get_matches_from(args);
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    let mut stdout = Output::new(cli.json).quiet(cli.quiet);
+    let started = Instant::now();
+    let result = match cli.profile.clone() {
+        Some(svg) => profile::profiled(&svg, &mut stdout, |stdout| run(cli, stdout)),
+        None => run(cli, &mut stdout),
+    };
+    let code = stdout.finish(&command_name(&matches), started.elapsed(), result);
+    if code != 0 {
+        std::process::exit(code);
+    }
+}
+
+/// Entry point of the `hashzig-tool` binary.
+pub fn main() {
+    run_args(std::env::args_os().collect());
+}
+
+/// Rewrite the arguments of an old binary name as `hashzig-tool` arguments:
+/// `group` is inserted before the first argument naming one of `commands`,
+/// or right after the program name when `commands` is empty. Arguments
+/// without such a command are left alone.
+pub fn legacy_args<I, T>(args: I, group: &[&str], commands: &[&str]) -> Vec<OsString>
+where
+    I: IntoIterator<Item = T>,
+    T: Into<OsString>,
+{
+    let mut args: Vec<OsString> = args.into_iter().map(Into::into).collect();
+    let at = if commands.is_empty() {
+        Some(args.len().min(1))
+    } else {
+        args.iter()
+            .skip(1)
+            .position(|arg| arg.to_str().is_some_and(|arg| commands.contains(&arg)))
+            .map(|i| i + 1)
+    };
+    if let Some(at) = at {
+        args.splice(at..at, group.iter().map(OsString::from));
+    }
+    args
+}
+
+/// Entry point of the old binary names; see [`legacy_args`].
+pub fn legacy_main(group: &[&str], commands: &[&str]) {
+    run_args(legacy_args(std::env::args_os(), group, commands));
+}
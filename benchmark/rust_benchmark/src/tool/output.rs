@@ -0,0 +1,124 @@
+//! Command results on stdout: the usual lines, or one JSON document with `--json`
+
+use std::error::Error;
+use std::fmt::Display;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use serde_json::{Map, Value};
+
+use super::exit::Failure;
+
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Whether `--quiet` suppresses progress messages.
+pub fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+/// A progress message on stderr, unless `--quiet` is set. Errors are always
+/// reported; see [`Output::finish`].
+macro_rules! progress {
+    ($($arg:tt)*) => {
+        if !$crate::tool::output::is_quiet() {
+            eprintln!($($arg)*);
+        }
+    };
+}
+pub(crate) use progress;
+
+/// Collects what a command reports. Without `--json`, [`Output::line`] and
+/// [`Output::print`] go straight to stdout and the recorded fields are
+/// dropped; with it, stdout holds nothing but the document [`Output::finish`]
+/// writes. Progress messages stay on stderr either way.
+pub struct Output {
+    json: bool,
+    ok: bool,
+    fields: Map<String, Value>,
+}
+
+impl Output {
+    pub fn new(json: bool) -> Self {
+        Self {
+            json,
+            ok: true,
+            fields: Map::new(),
+        }
+    }
+
+    /// Suppress progress messages, leaving stdout to the result alone.
+    pub fn quiet(self, quiet: bool) -> Self {
+        QUIET.store(quiet, Ordering::Relaxed);
+        self
+    }
+
+    pub fn is_json(&self) -> bool {
+        self.json
+    }
+
+    pub fn is_quiet(&self) -> bool {
+        is_quiet()
+    }
+
+    /// Print `text` and a newline, unless in JSON mode.
+    pub fn line(&self, text: impl Display) {
+        if !self.json {
+            println!("{text}");
+        }
+    }
+
+    /// Print `text` as is, unless in JSON mode.
+    pub fn print(&self, text: impl Display) {
+        if !self.json {
+            print!("{text}");
+        }
+    }
+
+    /// Record `value` under `key` in the JSON document.
+    pub fn set(&mut self, key: &str, value: impl Into<Value>) {
+        self.fields.insert(key.to_owned(), value.into());
+    }
+
+    /// Mark the command as failed (a signature that does not verify, a
+    /// mismatching vector) without it being an error: [`Failure::CheckFailed`].
+    pub fn fail(&mut self) {
+        self.ok = false;
+    }
+
+    /// Report the outcome of `command` and return the exit code: the JSON
+    /// document in JSON mode, otherwise just the error on stderr.
+    pub fn finish(
+        self,
+        command: &str,
+        elapsed: Duration,
+        result: Result<(), Box<dyn Error>>,
+    ) -> i32 {
+        let failure = match &result {
+            Err(e) => Some(Failure::of(e.as_ref())),
+            Ok(()) if !self.ok => Some(Failure::CheckFailed),
+            Ok(()) => None,
+        };
+        if let Err(e) = &result {
+            if !self.json {
+                eprintln!("Error: {e}");
+            }
+        }
+        let code = failure.map_or(0, Failure::code);
+        if self.json {
+            let mut document = Map::new();
+            document.insert("command".into(), command.into());
+            document.insert("ok".into(), failure.is_none().into());
+            document.insert("elapsed_ms".into(), (elapsed.as_secs_f64() * 1e3).into());
+            document.insert("exit_code".into(), code.into());
+            if let Some(failure) = failure {
+                document.insert("failure".into(), failure.as_str().into());
+            }
+            if let Err(e) = &result {
+                document.insert("error".into(), e.to_string().into());
+            }
+            document.extend(self.fields);
+            println!("{}", Value::Object(document));
+        }
+        code
+    }
+}
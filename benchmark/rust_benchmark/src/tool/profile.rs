@@ -0,0 +1,66 @@
+//! `--profile`: a flamegraph of the command, next to its results
+//!
+//! Why one implementation is faster on some step used to mean adding timers
+//! by hand until the difference showed. With `--profile` the command runs
+//! under pprof's sampling profiler and the samples are written out as a
+//! flamegraph SVG once it finishes, whether it succeeded or not. Sampling
+//! needs the `profile` feature, which pulls in pprof (Unix only).
+
+use std::error::Error;
+use std::path::Path;
+
+#[cfg(not(feature = "profile"))]
+use super::exit::{self, Failure};
+#[cfg(feature = "profile")]
+use super::output::progress;
+use super::output::Output;
+
+/// Samples per second.
+#[cfg(feature = "profile")]
+const FREQUENCY: i32 = 997;
+
+/// Run `work` under the profiler and write the flamegraph to `svg`.
+#[cfg(feature = "profile")]
+pub fn profiled(
+    svg: &Path,
+    stdout: &mut Output,
+    work: impl FnOnce(&mut Output) -> Result<(), Box<dyn Error>>,
+) -> Result<(), Box<dyn Error>> {
+    let guard = pprof::ProfilerGuardBuilder::default()
+        .frequency(FREQUENCY)
+        .blocklist(&["libc", "libgcc", "pthread", "vdso"])
+        .build()?;
+    let result = work(stdout);
+    let written = guard
+        .report()
+        .build()
+        .map_err(Box::<dyn Error>::from)
+        .and_then(|report| {
+            if let Some(dir) = svg.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+                std::fs::create_dir_all(dir)?;
+            }
+            report.flamegraph(std::fs::File::create(svg)?)?;
+            Ok(())
+        });
+    match written {
+        Ok(()) => {
+            progress!("🔥 Flamegraph written to {}", svg.display());
+            stdout.set("flamegraph", svg.display().to_string());
+        }
+        // The command's own error matters more than the profile's
+        Err(e) if result.is_ok() => return Err(format!("{}: {e}", svg.display()).into()),
+        Err(e) => progress!("Could not write flamegraph to {}: {e}", svg.display()),
+    }
+    result
+}
+
+#[cfg(not(feature = "profile"))]
+pub fn profiled(
+    _svg: &Path,
+    _stdout: &mut Output,
+    _work: impl FnOnce(&mut Output) -> Result<(), Box<dyn Error>>,
+) -> Result<(), Box<dyn Error>> {
+    Err(exit::tag(Failure::Usage)(
+        "--profile needs a build with --features profile",
+    ))
+}
@@ -0,0 +1,958 @@
+//! Binary-format sign and verify for the Zig remote tests, and a JSON-RPC session over stdio
+
+use clap::Subcommand;
+use rand::{rngs::StdRng, SeedableRng};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{self, json, Value};
+use ssz::Decode;
+use std::error::Error;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use super::exit::{self, Failure};
+use super::output::{progress, Output};
+use super::SeedArgs;
+use crate::cli;
+use crate::compat::binfmt::{
+    convert_endian, is_binary_path, read_public_key_binary, write_public_key_binary,
+    write_signature, Endian, Repr, SignatureView,
+};
+use crate::compat::codec;
+use crate::compat::json::{
+    deserialize_public_key_from_file, serialize_public_key_to_file, signature_to_json,
+    trim_public_key_value, JsonStyle,
+};
+use crate::compat::ssz_codec::{self, is_ssz_path, read_public_key_ssz, read_signature_ssz};
+use crate::compat::text_encoding::{self, Encoding};
+use crate::config::{Format, SchemeConfig};
+use crate::debug_sink::{self, DebugSink};
+use crate::hypertree;
+use crate::key_cache::{self, KeyCache, Lookup};
+use crate::lifetime::{LifetimeMetadata, LifetimeTag, SchemeMetadata};
+use crate::seed;
+use crate::signer::prepare_epoch;
+use crate::upstream::{
+    SIGTopLevelTargetSumLifetime18Dim64Base8, SIGTopLevelTargetSumLifetime32Dim64Base8,
+    SIGTopLevelTargetSumLifetime8Dim64Base8, SignatureScheme, SignatureSchemeSecretKey,
+};
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Generate a key pair from a seed, sign at one epoch and write the public key and signature
+    Sign {
+        message: String,
+        pk_json: String,
+        sig_bin: String,
+        /// Same as --seed [default: 42 repeated]
+        #[arg(
+            value_parser = cli::seed,
+            conflicts_with_all = ["seed_flag", "seed_file", "random_seed"]
+        )]
+        seed: Option<[u8; 32]>,
+        #[command(flatten)]
+        seed_args: SeedArgs,
+        #[arg(default_value_t = 0)]
+        epoch: u32,
+        #[arg(default_value_t = 256)]
+        num_active_epochs: usize,
+        #[arg(default_value_t = 0)]
+        start_epoch: usize,
+        #[arg(value_parser = cli::lifetime, default_value = "2^8")]
+        lifetime: LifetimeTag,
+        /// Write pk_json as canonical JSON (sorted keys, no whitespace)
+        #[arg(long, conflicts_with = "hex_json")]
+        canonical: bool,
+        /// Write pk_json with field elements as 0x-prefixed Montgomery hex strings, as the Zig tool prints them
+        #[arg(long)]
+        hex_json: bool,
+        /// Write sig_bin as raw bytes or base64 text; verify decodes base64 automatically
+        #[arg(long, value_parser = cli::encoding, default_value = "raw")]
+        encoding: Encoding,
+        /// Store field elements in binary outputs in Montgomery or canonical form; the header records which and verify converts
+        #[arg(long, value_parser = cli::repr, default_value = "montgomery")]
+        repr: Repr,
+        /// Write counts and field elements in binary outputs little- or big-endian; the header records which and verify converts
+        #[arg(long, value_parser = cli::endian, default_value = "little")]
+        endian: Endian,
+        /// Generate the key pair even if the key cache ($HASHZIG_KEY_CACHE, else ~/.cache/hashzig/keys) has it, and do not store it
+        #[arg(long)]
+        no_cache: bool,
+        /// Activate only the two bottom trees around epoch, ignoring num_active_epochs and start_epoch; makes 2^32 signatures at far epochs quick
+        #[arg(long)]
+        hypertree: bool,
+    },
+    /// Verify a signature against a public key
+    Verify {
+        message: String,
+        pk_json: String,
+        sig_bin: String,
+        #[arg(default_value_t = 0)]
+        epoch: u32,
+        #[arg(value_parser = cli::lifetime, default_value = "2^8")]
+        lifetime: LifetimeTag,
+        /// With HASHSIG_DEBUG set, also trace the message-hash permutation on packed SIMD lanes and record whether it matches (needs --features debug-tools)
+        #[arg(long)]
+        simd: bool,
+    },
+    /// Permute seeded Poseidon2 states and, with --simd, compare the scalar and packed permutations (needs --features debug-tools)
+    ComparePoseidon {
+        #[arg(long, default_value_t = 4096)]
+        count: usize,
+        #[command(flatten)]
+        seed: SeedArgs,
+        /// Also permute on packed SIMD lanes, timing both and failing unless every state matches
+        #[arg(long)]
+        simd: bool,
+        /// Write each state as a {"input", "output"} JSON line, in canonical form, for the Zig side to replay
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+    /// Answer JSON-RPC keygen/sign/verify requests, one per line, keeping keys in memory
+    ServeStdio {
+        #[arg(value_parser = cli::lifetime, default_value = "2^8")]
+        lifetime: LifetimeTag,
+    },
+    /// Rewrite a binary signature or public key in the given byte order
+    ConvertEndian {
+        input: String,
+        output: String,
+        /// little or big
+        #[arg(value_parser = cli::endian)]
+        endian: Endian,
+        #[arg(value_parser = cli::lifetime, default_value = "2^8")]
+        lifetime: LifetimeTag,
+    },
+}
+
+/// Seed of `sign` and the JSON-RPC `keygen` when none is given.
+const DEFAULT_SEED: [u8; 32] = [0x42; 32];
+
+fn message_to_bytes(message: &str) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    let msg_bytes = message.as_bytes();
+    let copy_len = msg_bytes.len().min(32);
+    bytes[..copy_len].copy_from_slice(&msg_bytes[..copy_len]);
+    bytes
+}
+
+/// Sign with the key pair `config`'s seed derives, taken from `cache` when
+/// one is given; returns whether the cache had it.
+#[allow(clippy::too_many_arguments)]
+fn sign_for_scheme<S>(
+    message: String,
+    pk_json_out: String,
+    sig_bin_out: String,
+    epoch: u32,
+    config: &SchemeConfig,
+    json_style: JsonStyle,
+    encoding: Encoding,
+    repr: Repr,
+    endian: Endian,
+    cache: Option<&KeyCache>,
+    sink: &mut dyn DebugSink,
+) -> Result<Option<Lookup>, Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::PublicKey: Serialize + for<'de> DeserializeOwned,
+    S::SecretKey: SignatureSchemeSecretKey + Serialize + for<'de> DeserializeOwned,
+    S::Signature: Serialize + for<'de> DeserializeOwned,
+{
+    let meta = config.lifetime().metadata();
+    let seed = config.seed().ok_or("sign requires a seed")?;
+    let (activation_epoch, num_active_epochs) =
+        (config.activation_epoch(), config.num_active_epochs());
+    let (pk, mut sk, lookup) = match cache {
+        Some(cache) => {
+            let (pk, sk, lookup) = cache.key_gen::<S>(seed, activation_epoch, num_active_epochs)?;
+            progress!("Key cache {} in {}", lookup.as_str(), cache.dir().display());
+            (pk, sk, Some(lookup))
+        }
+        None => {
+            let mut rng = StdRng::from_seed(seed);
+            let (pk, sk) = S::key_gen(&mut rng, activation_epoch, num_active_epochs);
+            (pk, sk, None)
+        }
+    };
+
+    let msg_bytes = message_to_bytes(&message);
+    if !prepare_epoch(&mut sk, epoch as u64) {
+        return Err(format!("epoch {epoch} cannot be prepared").into());
+    }
+    if sink.enabled() {
+        let prepared = sk.get_prepared_interval();
+        sink.record(
+            "sign_start",
+            json!({ "epoch": epoch, "prepared": [prepared.start, prepared.end] }),
+        );
+    }
+
+    let signature = S::sign(&sk, epoch, &msg_bytes)
+        .map_err(|e| format!("failed to sign message at epoch {epoch}: {e:?}"))?;
+
+    if is_binary_path(&pk_json_out) {
+        write_public_key_binary(&serde_json::to_value(&pk)?, pk_json_out, meta, repr, endian)?;
+    } else {
+        serialize_public_key_to_file(&pk, pk_json_out, meta, json_style)?;
+    }
+    let sig_json = signature_to_json(&signature, meta)?;
+    if sink.enabled() {
+        let rho = sig_json.get("rho").and_then(|r| r.as_array());
+        sink.record(
+            "signed",
+            json!({ "epoch": epoch, "rho": rho.map(|r| field_elements(r, meta.rand_len)) }),
+        );
+    }
+    let mut sig_bytes = Vec::new();
+    write_signature(&mut sig_bytes, &sig_json, meta, repr, endian)?;
+    fs::write(sig_bin_out, text_encoding::encode(&sig_bytes, encoding))?;
+
+    Ok(lookup)
+}
+
+fn field_elements(values: &[Value], take: usize) -> Vec<u64> {
+    values.iter().take(take).filter_map(Value::as_u64).collect()
+}
+
+/// Trace the top-level Poseidon message hash step by step, mirroring Zig's
+/// applyTopLevelPoseidonMessageHash, so the two sides can be compared state
+/// by state.
+#[cfg(feature = "debug-tools")]
+pub(super) fn trace_message_hash(
+    sink: &mut dyn DebugSink,
+    meta: LifetimeMetadata,
+    pk_json: &Value,
+    sig_json: &Value,
+    msg_bytes: &[u8; 32],
+    epoch: u32,
+    simd: bool,
+) {
+    // Avoiding upstream scheme imports here to prevent triggering const generics compilation issues
+    use crate::packed_poseidon;
+    use crate::poseidon::poseidon2_24;
+    use crate::upstream::backend::symmetric::message_hash::poseidon::{
+        encode_epoch, encode_message,
+    };
+    use crate::upstream::backend::symmetric::tweak_hash::poseidon::poseidon_compress;
+    use p3_field::{PrimeCharacteristicRing, PrimeField32};
+    use p3_koala_bear::KoalaBear;
+    use p3_poseidon2::{ExternalLayer, InternalLayer};
+
+    let canonical = |state: &[KoalaBear]| -> Vec<u32> {
+        state
+            .iter()
+            .map(<KoalaBear as PrimeField32>::as_canonical_u32)
+            .collect()
+    };
+    let to_field = |values: Vec<u64>| -> Vec<KoalaBear> {
+        values
+            .into_iter()
+            .filter(|&u| u <= u32::MAX as u64)
+            .map(|u| KoalaBear::from_u32(u as u32))
+            .collect()
+    };
+
+    let Some(rho_array) = sig_json.get("rho").and_then(|r| r.as_array()) else {
+        sink.record(
+            "poseidon_skipped",
+            json!({ "reason": "no rho array in signature" }),
+        );
+        return;
+    };
+    let Some(param_array) = pk_json.get("parameter").and_then(|p| p.as_array()) else {
+        sink.record(
+            "poseidon_skipped",
+            json!({ "reason": "no parameter array in public key" }),
+        );
+        return;
+    };
+    let randomness = to_field(field_elements(rho_array, meta.rand_len));
+    let parameter = to_field(field_elements(param_array, meta.parameter_len));
+    if randomness.len() < meta.rand_len || parameter.len() < meta.parameter_len {
+        sink.record(
+            "poseidon_skipped",
+            json!({ "randomness_len": randomness.len(), "parameter_len": parameter.len() }),
+        );
+        return;
+    }
+
+    // randomness | parameter | epoch (2 fe) | message (9 fe, base-p) | iteration index, padded to 24
+    let message_fe: [KoalaBear; 9] = encode_message::<9>(msg_bytes);
+    let epoch_fe: [KoalaBear; 2] = encode_epoch::<2>(epoch);
+    if meta.rand_len + meta.parameter_len + epoch_fe.len() + message_fe.len() + 1 > 24 {
+        sink.record(
+            "poseidon_skipped",
+            json!({ "reason": "message hash input does not fit one 24-element state" }),
+        );
+        return;
+    }
+    let mut input = [KoalaBear::ZERO; 24];
+    for (slot, value) in input.iter_mut().zip(
+        randomness
+            .iter()
+            .chain(&parameter)
+            .chain(epoch_fe.iter())
+            .chain(message_fe.iter())
+            .chain(std::iter::once(&KoalaBear::ZERO)),
+    ) {
+        *slot = *value;
+    }
+    sink.record("poseidon_input", json!({ "state": canonical(&input) }));
+
+    // Step through the permutation: initial external rounds (with MDS light),
+    // internal rounds, terminal external rounds.
+    let perm = poseidon2_24();
+    let mut state = input;
+    perm.external_layer.permute_state_initial(&mut state);
+    sink.record(
+        "poseidon_state",
+        json!({ "stage": "EXT_INIT[3]", "state": canonical(&state) }),
+    );
+    perm.internal_layer.permute_state(&mut state);
+    sink.record(
+        "poseidon_state",
+        json!({ "stage": "INT[2]", "state": canonical(&state) }),
+    );
+    perm.external_layer.permute_state_terminal(&mut state);
+    sink.record(
+        "poseidon_state",
+        json!({ "stage": "EXT_FINAL[3]", "state": canonical(&state) }),
+    );
+    if simd {
+        let packed = packed_poseidon::permute_packed(perm, &[input])[0];
+        sink.record(
+            "poseidon_simd",
+            json!({
+                "lanes": packed_poseidon::lanes(),
+                "state": canonical(&packed),
+                "matches_scalar": packed == state,
+            }),
+        );
+    }
+
+    // poseidon_compress includes the feed-forward
+    let output = poseidon_compress::<KoalaBear, _, 24, 15>(perm, &input);
+    sink.record("poseidon_output", json!({ "output": canonical(&output) }));
+}
+
+fn verify_for_scheme<S>(
+    message: String,
+    pk_json_path: String,
+    sig_bin_path: String,
+    epoch: u32,
+    meta: LifetimeMetadata,
+    simd: bool,
+    sink: &mut dyn DebugSink,
+) -> Result<bool, Box<dyn Error>>
+where
+    S: SignatureScheme,
+    S::PublicKey: Serialize + for<'de> DeserializeOwned + Decode,
+    S::SecretKey: SignatureSchemeSecretKey + Serialize + for<'de> DeserializeOwned,
+    S::Signature: Serialize + for<'de> DeserializeOwned + Decode,
+{
+    sink.record(
+        "verify_start",
+        json!({ "epoch": epoch, "sig_path": sig_bin_path, "pk_path": pk_json_path }),
+    );
+    let pk: S::PublicKey = if is_ssz_path(&pk_json_path) {
+        read_public_key_ssz::<S, _>(&pk_json_path, meta)
+    } else if is_binary_path(&pk_json_path) {
+        read_public_key_binary(&pk_json_path, meta)
+            .and_then(|value| Ok(serde_json::from_value(value)?))
+    } else {
+        deserialize_public_key_from_file(&pk_json_path, meta)
+    }
+    .map_err(exit::tag(Failure::Parse))?;
+    // Straight to the upstream type: SSZ as it is, the binary layout through
+    // `SignatureView`'s bulk SSZ conversion
+    let ssz = is_ssz_path(&sig_bin_path);
+    let signature: S::Signature = if ssz {
+        read_signature_ssz::<S, _>(&sig_bin_path, meta)
+    } else {
+        fs::read(&sig_bin_path)
+            .map_err(Box::<dyn Error>::from)
+            .and_then(|raw| text_encoding::decode(&raw))
+            .and_then(|bytes| {
+                let ssz_bytes = SignatureView::parse(&bytes, meta)?.to_ssz_bytes();
+                ssz_codec::decode_signature::<S>(&ssz_bytes, meta)
+            })
+            .inspect_err(|e| {
+                sink.record("signature_decode_failed", json!({ "error": e.to_string() }));
+            })
+    }
+    .map_err(exit::tag(Failure::Parse))?;
+    let msg_bytes = message_to_bytes(&message);
+
+    // The serde shapes are only built for the tracer
+    if sink.enabled() {
+        let sig_json = signature_to_json(&signature, meta)?;
+        let rho = sig_json.get("rho").and_then(|r| r.as_array());
+        let first_hash = sig_json
+            .get("hashes")
+            .and_then(|h| h.as_array())
+            .and_then(|hashes| hashes.first())
+            .and_then(|h| h.as_array());
+        sink.record(
+            "signature_loaded",
+            json!({
+                "encoding": if ssz { "ssz" } else { "bin" },
+                "rho": rho.map(|r| field_elements(r, meta.rand_len)),
+                "hash0": first_hash.map(|h| field_elements(h, meta.hash_len)),
+            }),
+        );
+        #[cfg(feature = "debug-tools")]
+        {
+            let pk_json: Value = serde_json::to_value(&pk)?;
+            trace_message_hash(sink, meta, &pk_json, &sig_json, &msg_bytes, epoch, simd);
+        }
+    }
+    // Rejected by verify_command without the tracer
+    #[cfg(not(feature = "debug-tools"))]
+    let _ = simd;
+
+    let ok = S::verify(&pk, epoch, &msg_bytes, &signature);
+    sink.record("verify_result", json!({ "valid": ok }));
+    Ok(ok)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn sign_command(
+    message: String,
+    pk_json_out: String,
+    sig_bin_out: String,
+    seed: [u8; 32],
+    epoch: u32,
+    start_epoch: usize,
+    num_active_epochs: usize,
+    lifetime: LifetimeTag,
+    json_style: JsonStyle,
+    encoding: Encoding,
+    repr: Repr,
+    endian: Endian,
+    cache: Option<&KeyCache>,
+) -> Result<Option<Lookup>, Box<dyn Error>> {
+    let config = SchemeConfig::new()
+        .lifetime(lifetime)
+        .seed(seed)
+        .activation(start_epoch, num_active_epochs)
+        .format(Format::ZigBinary)
+        .build()?;
+    config
+        .check_epoch(epoch)
+        .map_err(exit::tag(Failure::Usage))?;
+    let mut sink = debug_sink::from_env()?;
+    let sink = sink.as_mut();
+    match lifetime {
+        LifetimeTag::Pow8 => sign_for_scheme::<SIGTopLevelTargetSumLifetime8Dim64Base8>(
+            message,
+            pk_json_out,
+            sig_bin_out,
+            epoch,
+            &config,
+            json_style,
+            encoding,
+            repr,
+            endian,
+            cache,
+            sink,
+        ),
+        LifetimeTag::Pow18 => sign_for_scheme::<SIGTopLevelTargetSumLifetime18Dim64Base8>(
+            message,
+            pk_json_out,
+            sig_bin_out,
+            epoch,
+            &config,
+            json_style,
+            encoding,
+            repr,
+            endian,
+            cache,
+            sink,
+        ),
+        LifetimeTag::Pow32 => sign_for_scheme::<SIGTopLevelTargetSumLifetime32Dim64Base8>(
+            message,
+            pk_json_out,
+            sig_bin_out,
+            epoch,
+            &config,
+            json_style,
+            encoding,
+            repr,
+            endian,
+            cache,
+            sink,
+        ),
+    }
+}
+
+fn convert_endian_command(
+    input: String,
+    output: String,
+    endian: Endian,
+    lifetime: LifetimeTag,
+) -> Result<(), Box<dyn Error>> {
+    let bytes = text_encoding::decode(&fs::read(&input)?).map_err(exit::tag(Failure::Parse))?;
+    fs::write(
+        &output,
+        convert_endian(&bytes, lifetime.metadata(), endian).map_err(exit::tag(Failure::Parse))?,
+    )?;
+    Ok(())
+}
+
+/// Permute `count` states drawn from `seed` with the scalar permutation
+/// and, with `simd`, on packed lanes too, failing unless the two agree.
+#[cfg(feature = "debug-tools")]
+fn compare_poseidon_command(
+    count: usize,
+    seed: [u8; 32],
+    simd: bool,
+    out: Option<&Path>,
+    stdout: &mut Output,
+) -> Result<(), Box<dyn Error>> {
+    use std::io::BufWriter;
+    use std::time::Instant;
+
+    use crate::packed_poseidon;
+    use crate::poseidon::poseidon2_24;
+
+    let perm = poseidon2_24();
+    let states = packed_poseidon::random_states(seed, count);
+    progress!("Permuting {count} Poseidon2 states...");
+    let (permuted, report) = if simd {
+        let (permuted, comparison) = packed_poseidon::compare(perm, &states);
+        if let Some(&index) = comparison.mismatches.first() {
+            progress!(
+                "❌ {} of {count} states differ between the scalar and SIMD permutations, first at {index}",
+                comparison.mismatches.len()
+            );
+            stdout.fail();
+        }
+        (permuted, comparison.to_json())
+    } else {
+        let started = Instant::now();
+        let permuted = packed_poseidon::permute_scalar(perm, &states);
+        let elapsed = started.elapsed().as_secs_f64() * 1000.0;
+        (
+            permuted,
+            json!({ "permutations": count, "scalar_ms": elapsed }),
+        )
+    };
+    stdout.line(format!("POSEIDON_COMPARE_RESULT:{report}"));
+    stdout.set("comparison", report);
+    if let Some(path) = out {
+        let mut file = BufWriter::new(fs::File::create(path)?);
+        for (input, output) in states.iter().zip(&permuted) {
+            let line = json!({
+                "input": packed_poseidon::canonical(input),
+                "output": packed_poseidon::canonical(output),
+            });
+            writeln!(file, "{line}")?;
+        }
+        file.flush()?;
+        stdout.set("out", path.display().to_string());
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "debug-tools"))]
+fn compare_poseidon_command(
+    _count: usize,
+    _seed: [u8; 32],
+    _simd: bool,
+    _out: Option<&Path>,
+    _stdout: &mut Output,
+) -> Result<(), Box<dyn Error>> {
+    Err(exit::tag(Failure::Usage)(
+        "compare-poseidon needs a build with --features debug-tools",
+    ))
+}
+
+fn verify_command(
+    message: String,
+    pk_json_path: String,
+    sig_bin_path: String,
+    epoch: u32,
+    lifetime: LifetimeTag,
+    simd: bool,
+    stdout: &mut Output,
+) -> Result<(), Box<dyn Error>> {
+    if simd && cfg!(not(feature = "debug-tools")) {
+        return Err(exit::tag(Failure::Usage)(
+            "--simd traces the message hash, which needs a build with --features debug-tools",
+        ));
+    }
+    let meta = lifetime.metadata();
+    let mut sink = debug_sink::from_env()?;
+    let sink = sink.as_mut();
+    let ok = match lifetime {
+        LifetimeTag::Pow8 => verify_for_scheme::<SIGTopLevelTargetSumLifetime8Dim64Base8>(
+            message,
+            pk_json_path,
+            sig_bin_path,
+            epoch,
+            meta,
+            simd,
+            sink,
+        )?,
+        LifetimeTag::Pow18 => verify_for_scheme::<SIGTopLevelTargetSumLifetime18Dim64Base8>(
+            message,
+            pk_json_path,
+            sig_bin_path,
+            epoch,
+            meta,
+            simd,
+            sink,
+        )?,
+        LifetimeTag::Pow32 => verify_for_scheme::<SIGTopLevelTargetSumLifetime32Dim64Base8>(
+            message,
+            pk_json_path,
+            sig_bin_path,
+            epoch,
+            meta,
+            simd,
+            sink,
+        )?,
+    };
+    stdout.line(format!("VERIFY_RESULT:{}", ok));
+    stdout.set("lifetime", lifetime.as_str());
+    stdout.set("epoch", epoch);
+    stdout.set("valid", ok);
+    Ok(())
+}
+
+// JSON-RPC 2.0 error codes
+const RPC_PARSE_ERROR: i64 = -32700;
+const RPC_METHOD_NOT_FOUND: i64 = -32601;
+const RPC_SERVER_ERROR: i64 = -32000;
+
+/// A key pair held by `serve-stdio`. The config is kept so the secret key can
+/// be regenerated when a request asks for an epoch behind its prepared window.
+struct StdioKey<S: SignatureScheme> {
+    config: SchemeConfig,
+    public_key: S::PublicKey,
+    secret_key: S::SecretKey,
+}
+
+fn rpc_param<'a>(params: &'a Value, name: &str) -> Option<&'a Value> {
+    params.get(name).filter(|v| !v.is_null())
+}
+
+fn rpc_u64(params: &Value, name: &str) -> Result<Option<u64>, Box<dyn Error>> {
+    rpc_param(params, name)
+        .map(|v| {
+            v.as_u64()
+                .ok_or_else(|| format!("'{name}' must be a non-negative integer").into())
+        })
+        .transpose()
+}
+
+fn rpc_str<'a>(params: &'a Value, name: &str) -> Result<&'a str, Box<dyn Error>> {
+    rpc_param(params, name)
+        .and_then(Value::as_str)
+        .ok_or_else(|| format!("missing or invalid '{name}'").into())
+}
+
+fn rpc_epoch(params: &Value) -> Result<u32, Box<dyn Error>> {
+    let epoch = rpc_u64(params, "epoch")?.unwrap_or(0);
+    Ok(u32::try_from(epoch).map_err(|_| "epoch exceeds u32")?)
+}
+
+fn rpc_key<'a, S: SignatureScheme>(
+    params: &Value,
+    keys: &'a mut [StdioKey<S>],
+) -> Result<&'a mut StdioKey<S>, Box<dyn Error>> {
+    let key_id = rpc_u64(params, "key_id")?.ok_or("missing 'key_id'")?;
+    keys.get_mut(key_id as usize)
+        .ok_or_else(|| format!("unknown key_id {key_id}").into())
+}
+
+fn rpc_keygen<S>(
+    params: &Value,
+    lifetime: LifetimeTag,
+    keys: &mut Vec<StdioKey<S>>,
+) -> Result<Value, Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::PublicKey: Serialize,
+{
+    let seed_hex = rpc_param(params, "seed_hex")
+        .map(|v| {
+            v.as_str()
+                .map(str::to_string)
+                .ok_or("'seed_hex' must be a string")
+        })
+        .transpose()?;
+    let config = SchemeConfig::new()
+        .lifetime(lifetime)
+        .seed(match seed_hex {
+            Some(hex) => seed::parse(&hex)?,
+            None => DEFAULT_SEED,
+        })
+        .activation(
+            rpc_u64(params, "start_epoch")?.unwrap_or(0) as usize,
+            rpc_u64(params, "num_active_epochs")?.unwrap_or(256) as usize,
+        )
+        .format(Format::ZigBinary)
+        .build()?;
+    let mut rng = StdRng::from_seed(config.seed().ok_or("keygen requires a seed")?);
+    let (public_key, secret_key) = S::key_gen(
+        &mut rng,
+        config.activation_epoch(),
+        config.num_active_epochs(),
+    );
+
+    let mut pk_value = serde_json::to_value(&public_key)?;
+    trim_public_key_value(&mut pk_value, S::METADATA);
+    let activation = secret_key.get_activation_interval();
+    keys.push(StdioKey {
+        config,
+        public_key,
+        secret_key,
+    });
+    Ok(json!({
+        "key_id": keys.len() - 1,
+        "public_key": pk_value,
+        "activation_interval": [activation.start, activation.end],
+    }))
+}
+
+fn rpc_sign<S>(params: &Value, keys: &mut [StdioKey<S>]) -> Result<Value, Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::Signature: Serialize,
+{
+    let epoch = rpc_epoch(params)?;
+    let message = message_to_bytes(rpc_str(params, "message")?);
+    let key = rpc_key(params, keys)?;
+    key.config.check_epoch(epoch)?;
+    if key.secret_key.get_prepared_interval().start > epoch as u64 {
+        // The prepared window only moves forward; start over from the seed.
+        let mut rng = StdRng::from_seed(key.config.seed().ok_or("key has no seed")?);
+        key.secret_key = S::key_gen(
+            &mut rng,
+            key.config.activation_epoch(),
+            key.config.num_active_epochs(),
+        )
+        .1;
+    }
+    if !prepare_epoch(&mut key.secret_key, epoch as u64) {
+        return Err(format!("epoch {epoch} cannot be prepared").into());
+    }
+    let signature = S::sign(&key.secret_key, epoch, &message)
+        .map_err(|e| format!("failed to sign message at epoch {epoch}: {e:?}"))?;
+    let sig_bin = codec::encode_signature::<S>(&signature, Format::ZigBinary)?;
+    Ok(json!({ "epoch": epoch, "signature": hex::encode(sig_bin) }))
+}
+
+fn rpc_verify<S>(params: &Value, keys: &mut [StdioKey<S>]) -> Result<Value, Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::PublicKey: for<'de> DeserializeOwned + Decode,
+    S::Signature: for<'de> DeserializeOwned + Decode,
+{
+    let epoch = rpc_epoch(params)?;
+    let message = message_to_bytes(rpc_str(params, "message")?);
+    let sig_bin = hex::decode(rpc_str(params, "signature")?.trim_start_matches("0x"))?;
+    let signature = codec::decode_signature::<S>(&sig_bin, Format::ZigBinary)?;
+    let valid = match rpc_param(params, "public_key") {
+        Some(pk_value) => {
+            let pk_json = serde_json::to_vec(pk_value)?;
+            let public_key = codec::decode_public_key::<S>(&pk_json, Format::Json)?;
+            S::verify(&public_key, epoch, &message, &signature)
+        }
+        None => S::verify(
+            &rpc_key(params, keys)?.public_key,
+            epoch,
+            &message,
+            &signature,
+        ),
+    };
+    Ok(json!({ "valid": valid }))
+}
+
+fn write_rpc(out: &mut impl Write, response: Value) -> io::Result<()> {
+    writeln!(out, "{response}")?;
+    out.flush()
+}
+
+fn write_rpc_error(out: &mut impl Write, id: Value, code: i64, message: String) -> io::Result<()> {
+    write_rpc(
+        out,
+        json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } }),
+    )
+}
+
+/// Answer JSON-RPC 2.0 requests read line by line from stdin until EOF or a
+/// `shutdown` call. Public keys are returned in the JSON file layout and
+/// signatures as hex of the Zig binary layout, matching what `sign` writes.
+fn serve_stdio<S>(lifetime: LifetimeTag) -> Result<(), Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::PublicKey: Serialize + for<'de> DeserializeOwned + Decode,
+    S::Signature: Serialize + for<'de> DeserializeOwned + Decode,
+{
+    let mut keys: Vec<StdioKey<S>> = Vec::new();
+    let mut stdout = io::stdout().lock();
+    eprintln!("RPC_READY: lifetime {lifetime}");
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let request: Value = match serde_json::from_str(&line) {
+            Ok(request) => request,
+            Err(e) => {
+                write_rpc_error(
+                    &mut stdout,
+                    Value::Null,
+                    RPC_PARSE_ERROR,
+                    format!("parse error: {e}"),
+                )?;
+                continue;
+            }
+        };
+        let id = request.get("id").cloned().unwrap_or(Value::Null);
+        let params = request.get("params").cloned().unwrap_or(Value::Null);
+        let method = request.get("method").and_then(Value::as_str).unwrap_or("");
+        let result = match method {
+            "keygen" => rpc_keygen::<S>(&params, lifetime, &mut keys),
+            "sign" => rpc_sign::<S>(&params, &mut keys),
+            "verify" => rpc_verify::<S>(&params, &mut keys),
+            "shutdown" => Ok(Value::Null),
+            other => {
+                write_rpc_error(
+                    &mut stdout,
+                    id,
+                    RPC_METHOD_NOT_FOUND,
+                    format!("unknown method '{other}'"),
+                )?;
+                continue;
+            }
+        };
+        match result {
+            Ok(result) => write_rpc(
+                &mut stdout,
+                json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            )?,
+            Err(e) => write_rpc_error(&mut stdout, id, RPC_SERVER_ERROR, e.to_string())?,
+        }
+        if method == "shutdown" {
+            break;
+        }
+    }
+    Ok(())
+}
+
+fn serve_stdio_command(lifetime: LifetimeTag) -> Result<(), Box<dyn Error>> {
+    match lifetime {
+        LifetimeTag::Pow8 => serve_stdio::<SIGTopLevelTargetSumLifetime8Dim64Base8>(lifetime),
+        LifetimeTag::Pow18 => serve_stdio::<SIGTopLevelTargetSumLifetime18Dim64Base8>(lifetime),
+        LifetimeTag::Pow32 => serve_stdio::<SIGTopLevelTargetSumLifetime32Dim64Base8>(lifetime),
+    }
+}
+
+pub fn run(command: Command, stdout: &mut Output) -> Result<(), Box<dyn Error>> {
+    match command {
+        Command::Sign {
+            message,
+            pk_json,
+            sig_bin,
+            seed,
+            seed_args,
+            epoch,
+            num_active_epochs,
+            start_epoch,
+            lifetime,
+            canonical,
+            hex_json,
+            encoding,
+            repr,
+            endian,
+            no_cache,
+            hypertree,
+        } => {
+            let json_style = if canonical {
+                JsonStyle::Canonical
+            } else if hex_json {
+                JsonStyle::Hex
+            } else {
+                JsonStyle::Pretty
+            };
+            stdout.set("public_key_path", pk_json.as_str());
+            stdout.set("signature_path", sig_bin.as_str());
+            stdout.set("lifetime", lifetime.as_str());
+            stdout.set("epoch", epoch);
+            let (start_epoch, num_active_epochs) = if hypertree {
+                let epoch = u64::from(epoch);
+                let plan = hypertree::plan(lifetime, &[epoch..epoch + 1])
+                    .map_err(exit::tag(Failure::Usage))?;
+                stdout.set("hypertree", plan.to_json());
+                (plan.activation_epoch(), plan.num_active_epochs())
+            } else {
+                (start_epoch, num_active_epochs)
+            };
+            let cache = match key_cache::default_dir() {
+                Some(dir) if !no_cache => Some(KeyCache::new(dir)),
+                _ => None,
+            };
+            let lookup = sign_command(
+                message,
+                pk_json,
+                sig_bin,
+                seed_args.resolve(seed, Some(DEFAULT_SEED))?,
+                epoch,
+                start_epoch,
+                num_active_epochs,
+                lifetime,
+                json_style,
+                encoding,
+                repr,
+                endian,
+                cache.as_ref(),
+            )?;
+            stdout.set("key_cache", lookup.map_or("off", Lookup::as_str));
+            Ok(())
+        }
+        Command::Verify {
+            message,
+            pk_json,
+            sig_bin,
+            epoch,
+            lifetime,
+            simd,
+        } => lifetime
+            .check_epoch(u64::from(epoch))
+            .map_err(exit::tag(Failure::Usage))
+            .and_then(|()| {
+                verify_command(message, pk_json, sig_bin, epoch, lifetime, simd, stdout)
+            }),
+        Command::ComparePoseidon {
+            count,
+            seed,
+            simd,
+            out,
+        } => compare_poseidon_command(
+            count,
+            seed.resolve(None, Some(DEFAULT_SEED))?,
+            simd,
+            out.as_deref(),
+            stdout,
+        ),
+        // Already one JSON-RPC response per line on stdout
+        Command::ServeStdio { .. } if stdout.is_json() => {
+            Err("serve-stdio speaks JSON-RPC on stdout and does not take --json".into())
+        }
+        Command::ServeStdio { lifetime } => serve_stdio_command(lifetime),
+        Command::ConvertEndian {
+            input,
+            output,
+            endian,
+            lifetime,
+        } => {
+            stdout.set("input", input.as_str());
+            stdout.set("output", output.as_str());
+            stdout.set("endian", endian.as_str());
+            convert_endian_command(input, output, endian, lifetime)
+        }
+    }
+}
@@ -0,0 +1,304 @@
+//! `repl`: one key pair in memory and a prompt to poke at it
+//!
+//! The cross-language debugging loop is sign, compare with Zig, change
+//! something, sign again. Running `keygen` for every step costs most of the
+//! time at 2^18, so the REPL generates the key once and keeps it, with every
+//! signature made in the session, until it exits:
+//!
+//! ```text
+//! hashzig> sign 5 "block 5"
+//! hashzig> verify last
+//! hashzig> trace poseidon
+//! hashzig> dump leaf 5
+//! ```
+//!
+//! Like `debug serve-stdio`, signing an epoch behind the prepared window
+//! regenerates the key from its seed, so epochs may be signed in any order
+//! and more than once. This is a debugging aid, not a signer.
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::io::{self, BufRead, IsTerminal, Write};
+
+use rand::{rngs::StdRng, SeedableRng};
+use serde_json::{json, Value};
+
+use super::exit::{self, Failure};
+use super::output::{progress, Output};
+use crate::compat::field_diff;
+use crate::compat::pk_info::PublicKeyInfo;
+use crate::compat::ssz_root;
+#[cfg(feature = "debug-tools")]
+use crate::debug_sink::DebugSink;
+use crate::lifetime::{LifetimeTag, SchemeMetadata};
+use crate::seed;
+use crate::signer::{prepare_epoch, PreparationState};
+use crate::upstream::{
+    SIGTopLevelTargetSumLifetime18Dim64Base8, SIGTopLevelTargetSumLifetime32Dim64Base8,
+    SIGTopLevelTargetSumLifetime8Dim64Base8, SignatureSchemeSecretKey,
+};
+
+const PROMPT: &str = "hashzig> ";
+
+const HELP: &str = "\
+keygen [seed]               generate a new key pair (default: the current seed)
+sign <epoch> <message>      sign and remember the signature; quote messages with spaces
+verify last|<epoch> [msg]   verify a remembered signature, against another message if given
+trace poseidon [last|<e>]   trace the message hash of a signature (needs --features debug-tools)
+dump pk|sig|leaf|path [e]   print the public key, a signature, its chain hashes or its Merkle path
+info                        lifetime, seed, fingerprint, prepared window and signed epochs
+help                        this text
+quit                        leave";
+
+/// Split `line` at whitespace, keeping double-quoted runs together.
+fn words(line: &str) -> Result<Vec<String>, Box<dyn Error>> {
+    let mut words = Vec::new();
+    let mut current: Option<String> = None;
+    let mut quoted = false;
+    for c in line.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                current.get_or_insert_with(String::new);
+            }
+            c if c.is_whitespace() && !quoted => words.extend(current.take()),
+            c => current.get_or_insert_with(String::new).push(c),
+        }
+    }
+    if quoted {
+        return Err("unterminated quote".into());
+    }
+    words.extend(current);
+    Ok(words)
+}
+
+/// Zero-padded or truncated to 32 bytes, as `sign` does.
+fn message_bytes(message: &str) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    let len = message.len().min(32);
+    bytes[..len].copy_from_slice(&message.as_bytes()[..len]);
+    bytes
+}
+
+/// Trace events as `<event> <json>` lines on stdout, next to the REPL output.
+#[cfg(feature = "debug-tools")]
+struct StdoutSink;
+
+#[cfg(feature = "debug-tools")]
+impl DebugSink for StdoutSink {
+    fn record(&mut self, event: &str, data: Value) {
+        println!("{event} {data}");
+    }
+}
+
+struct Session<S: SchemeMetadata> {
+    seed: [u8; 32],
+    num_active_epochs: usize,
+    public_key: S::PublicKey,
+    secret_key: S::SecretKey,
+    /// Message and signature per signed epoch
+    signatures: BTreeMap<u32, ([u8; 32], S::Signature)>,
+    last: Option<u32>,
+}
+
+impl<S: SchemeMetadata> Session<S> {
+    fn new(seed: [u8; 32], num_active_epochs: usize) -> Self {
+        let mut rng = StdRng::from_seed(seed);
+        let (public_key, secret_key) = S::key_gen(&mut rng, 0, num_active_epochs);
+        Session {
+            seed,
+            num_active_epochs,
+            public_key,
+            secret_key,
+            signatures: BTreeMap::new(),
+            last: None,
+        }
+    }
+
+    /// `last`, an epoch, or the last signature when `word` is absent.
+    fn epoch(&self, word: Option<&str>) -> Result<u32, Box<dyn Error>> {
+        match word {
+            None | Some("last") => self.last.ok_or_else(|| "nothing signed yet".into()),
+            Some(epoch) => {
+                let epoch = epoch.parse().map_err(|e| format!("epoch '{epoch}': {e}"))?;
+                if self.signatures.contains_key(&epoch) {
+                    Ok(epoch)
+                } else {
+                    Err(format!("no signature at epoch {epoch}; sign it first").into())
+                }
+            }
+        }
+    }
+
+    fn signature_value(&self, epoch: u32) -> Result<Value, Box<dyn Error>> {
+        field_diff::signature_value::<S>(&self.signatures[&epoch].1)
+    }
+
+    fn sign(&mut self, epoch: u32, message: &str) -> Result<(), Box<dyn Error>> {
+        let activation = self.secret_key.get_activation_interval();
+        if !activation.contains(&u64::from(epoch)) {
+            return Err(format!("epoch {epoch} outside activation interval {activation:?}").into());
+        }
+        if self.secret_key.get_prepared_interval().start > u64::from(epoch) {
+            // The prepared window only moves forward; start over from the seed
+            self.secret_key = Session::<S>::new(self.seed, self.num_active_epochs).secret_key;
+        }
+        if !prepare_epoch(&mut self.secret_key, u64::from(epoch)) {
+            return Err(format!("epoch {epoch} cannot be prepared").into());
+        }
+        let message = message_bytes(message);
+        let signature = S::sign(&self.secret_key, epoch, &message)
+            .map_err(|e| format!("failed to sign message at epoch {epoch}: {e:?}"))?;
+        let root = hex::encode(ssz_root::signature_hash_tree_root::<S>(&signature)?);
+        println!("SIGNATURE_ROOT: 0x{root} (epoch {epoch})");
+        self.signatures.insert(epoch, (message, signature));
+        self.last = Some(epoch);
+        Ok(())
+    }
+
+    fn verify(&self, epoch: Option<&str>, message: Option<&str>) -> Result<(), Box<dyn Error>> {
+        let epoch = self.epoch(epoch)?;
+        let (signed, signature) = &self.signatures[&epoch];
+        let message = message.map_or(*signed, message_bytes);
+        let valid = S::verify(&self.public_key, epoch, &message, signature);
+        println!("{} (epoch {epoch})", if valid { "PASS" } else { "FAIL" });
+        Ok(())
+    }
+
+    #[cfg(feature = "debug-tools")]
+    fn trace(&self, what: Option<&str>, epoch: Option<&str>) -> Result<(), Box<dyn Error>> {
+        if what != Some("poseidon") {
+            return Err("usage: trace poseidon [last|<epoch>]".into());
+        }
+        let epoch = self.epoch(epoch)?;
+        let pk_json = serde_json::to_value(&self.public_key)?;
+        let sig_json = self.signature_value(epoch)?;
+        super::remote::trace_message_hash(
+            &mut StdoutSink,
+            S::METADATA,
+            &pk_json,
+            &sig_json,
+            &self.signatures[&epoch].0,
+            epoch,
+            false,
+        );
+        Ok(())
+    }
+
+    #[cfg(not(feature = "debug-tools"))]
+    fn trace(&self, _what: Option<&str>, _epoch: Option<&str>) -> Result<(), Box<dyn Error>> {
+        Err("trace needs a build with --features debug-tools".into())
+    }
+
+    fn dump(&self, what: Option<&str>, epoch: Option<&str>) -> Result<(), Box<dyn Error>> {
+        let value = match what {
+            Some("pk") => field_diff::public_key_value::<S>(&self.public_key)?,
+            Some("sig") => self.signature_value(self.epoch(epoch)?)?,
+            // The one-time signature: one chain value per chunk of the message hash
+            Some("leaf") => self.signature_value(self.epoch(epoch)?)?["hashes"].take(),
+            Some("path") => self.signature_value(self.epoch(epoch)?)?["path"].take(),
+            _ => return Err("usage: dump pk|sig|leaf|path [last|<epoch>]".into()),
+        };
+        println!("{}", serde_json::to_string_pretty(&value)?);
+        Ok(())
+    }
+
+    fn info(&self) -> Result<(), Box<dyn Error>> {
+        let info = PublicKeyInfo::from_public_key::<S>(&self.public_key)?;
+        let state = PreparationState::of(&self.secret_key);
+        println!(
+            "{}",
+            json!({
+                "lifetime": S::TAG.as_str(),
+                "seed": hex::encode(self.seed),
+                "public_sha3": info.fingerprint_hex(),
+                "prepared": state.to_json(None),
+                "signed_epochs": self.signatures.keys().collect::<Vec<_>>(),
+            })
+        );
+        Ok(())
+    }
+
+    /// Run one command line; `Ok(false)` ends the session.
+    fn execute(&mut self, line: &str) -> Result<bool, Box<dyn Error>> {
+        let words = words(line)?;
+        let word = |i: usize| words.get(i).map(String::as_str);
+        match word(0) {
+            None => {}
+            Some("quit" | "exit") => return Ok(false),
+            Some("help") => println!("{HELP}"),
+            Some("keygen") => {
+                let seed = match word(1) {
+                    Some(hex) => seed::parse(hex)?,
+                    None => self.seed,
+                };
+                progress!("Generating keypair with lifetime {}...", S::TAG);
+                *self = Session::new(seed, self.num_active_epochs);
+                self.info()?;
+            }
+            Some("sign") => {
+                let epoch = word(1).ok_or("usage: sign <epoch> <message>")?;
+                let epoch = epoch.parse().map_err(|e| format!("epoch '{epoch}': {e}"))?;
+                self.sign(epoch, &words[2..].join(" "))?;
+            }
+            Some("verify") => self.verify(word(1), word(2))?,
+            Some("trace") => self.trace(word(1), word(2))?,
+            Some("dump") => self.dump(word(1), word(2))?,
+            Some("info") => self.info()?,
+            Some(other) => return Err(format!("unknown command '{other}'; try help").into()),
+        }
+        Ok(true)
+    }
+}
+
+fn repl_for_scheme<S: SchemeMetadata>(
+    seed: [u8; 32],
+    num_active_epochs: usize,
+) -> Result<(), Box<dyn Error>> {
+    progress!("Generating keypair with lifetime {}...", S::TAG);
+    let mut session = Session::<S>::new(seed, num_active_epochs);
+    session.info()?;
+    let interactive = io::stdin().is_terminal();
+    let mut lines = io::stdin().lock().lines();
+    loop {
+        if interactive {
+            print!("{PROMPT}");
+            io::stdout().flush()?;
+        }
+        let Some(line) = lines.next().transpose()? else {
+            break;
+        };
+        match session.execute(&line) {
+            Ok(true) => {}
+            Ok(false) => break,
+            Err(e) => println!("error: {e}"),
+        }
+    }
+    Ok(())
+}
+
+/// Read commands from stdin until `quit` or end of input. Errors in a
+/// command are printed and the session goes on.
+pub fn repl_command(
+    lifetime: LifetimeTag,
+    seed: [u8; 32],
+    num_active_epochs: usize,
+    stdout: &mut Output,
+) -> Result<(), Box<dyn Error>> {
+    if stdout.is_json() {
+        return Err(exit::tag(Failure::Usage)(
+            "repl prints as it goes and cannot write a --json document",
+        ));
+    }
+    match lifetime {
+        LifetimeTag::Pow8 => {
+            repl_for_scheme::<SIGTopLevelTargetSumLifetime8Dim64Base8>(seed, num_active_epochs)
+        }
+        LifetimeTag::Pow18 => {
+            repl_for_scheme::<SIGTopLevelTargetSumLifetime18Dim64Base8>(seed, num_active_epochs)
+        }
+        LifetimeTag::Pow32 => {
+            repl_for_scheme::<SIGTopLevelTargetSumLifetime32Dim64Base8>(seed, num_active_epochs)
+        }
+    }
+}
@@ -0,0 +1,146 @@
+//! Interop smoke test (`hashzig-tool debug smoke`)
+//!
+//! Takes no arguments. Generates a 2^8 key from a fixed seed, signs and
+//! verifies a fixed message, and round-trips the key and signature through
+//! every interchange format (JSON, SSZ, bincode, Zig binary). Prints one line
+//! per check followed by OK or FAIL; the exit code is non-zero on failure.
+//! This is the first thing to ask for in a bug report.
+
+use std::error::Error;
+
+use rand::{rngs::StdRng, SeedableRng};
+use serde_json::{json, Value};
+use ssz::{Decode, Encode};
+
+use super::output::Output;
+use crate::compat::binfmt::{read_signature, write_signature, Endian, Repr};
+use crate::compat::json::{signature_from_json, signature_to_json};
+use crate::lifetime::{LifetimeTag, SchemeMetadata};
+use crate::upstream::{SIGTopLevelTargetSumLifetime8Dim64Base8, SignatureScheme, BACKEND_NAME};
+
+type Scheme = SIGTopLevelTargetSumLifetime8Dim64Base8;
+type PublicKey = <Scheme as SignatureScheme>::PublicKey;
+type Signature = <Scheme as SignatureScheme>::Signature;
+
+const SEED: [u8; 32] = [0x42; 32];
+const MESSAGE: [u8; 32] = *b"hash-zig interop smoke test msg!";
+const EPOCH: u32 = 3;
+const NUM_ACTIVE_EPOCHS: usize = 256;
+
+struct Checks<'a> {
+    stdout: &'a mut Output,
+    results: Vec<Value>,
+    failures: usize,
+}
+
+impl Checks<'_> {
+    fn record(&mut self, name: &str, result: Result<(), String>) {
+        match result {
+            Ok(()) => {
+                self.stdout.line(format!("  [OK]   {name}"));
+                self.results.push(json!({ "name": name, "ok": true }));
+            }
+            Err(e) => {
+                self.failures += 1;
+                self.stdout.line(format!("  [FAIL] {name}: {e}"));
+                self.results
+                    .push(json!({ "name": name, "ok": false, "error": e }));
+            }
+        }
+    }
+
+    fn run(&mut self, name: &str, check: impl FnOnce() -> Result<(), Box<dyn Error>>) {
+        self.record(name, check().map_err(|e| e.to_string()));
+    }
+
+    /// Print the summary line and hand the results to the JSON document.
+    fn finish(self) {
+        if self.failures == 0 {
+            self.stdout.line("OK");
+        } else {
+            self.stdout
+                .line(format!("FAIL ({} check(s) failed)", self.failures));
+            self.stdout.fail();
+        }
+        self.stdout.set("checks", self.results);
+    }
+}
+
+fn expect_valid(pk: &PublicKey, sig: &Signature) -> Result<(), Box<dyn Error>> {
+    if Scheme::verify(pk, EPOCH, &MESSAGE, sig) {
+        Ok(())
+    } else {
+        Err("signature did not verify".into())
+    }
+}
+
+pub fn run(stdout: &mut Output) {
+    let meta = Scheme::METADATA;
+    stdout.line(format!(
+        "rust_benchmark {} ({} backend), lifetime {}",
+        env!("CARGO_PKG_VERSION"),
+        BACKEND_NAME,
+        LifetimeTag::Pow8
+    ));
+    stdout.set("version", env!("CARGO_PKG_VERSION"));
+    stdout.set("backend", BACKEND_NAME);
+    stdout.set("lifetime", LifetimeTag::Pow8.as_str());
+
+    let mut rng = StdRng::from_seed(SEED);
+    let (pk, sk) = Scheme::key_gen(&mut rng, 0, NUM_ACTIVE_EPOCHS);
+    let mut checks = Checks {
+        stdout,
+        results: Vec::new(),
+        failures: 0,
+    };
+
+    let signature = match Scheme::sign(&sk, EPOCH, &MESSAGE) {
+        Ok(sig) => sig,
+        Err(e) => {
+            checks.record("sign", Err(format!("{e:?}")));
+            checks.finish();
+            return;
+        }
+    };
+
+    checks.run("sign/verify", || expect_valid(&pk, &signature));
+    checks.run("reject wrong message", || {
+        let mut other = MESSAGE;
+        other[0] ^= 1;
+        if Scheme::verify(&pk, EPOCH, &other, &signature) {
+            Err("signature verified for a different message".into())
+        } else {
+            Ok(())
+        }
+    });
+    checks.run("JSON round-trip", || {
+        let pk: PublicKey = serde_json::from_str(&serde_json::to_string(&pk)?)?;
+        let sig: Signature = serde_json::from_str(&serde_json::to_string(&signature)?)?;
+        expect_valid(&pk, &sig)
+    });
+    checks.run("SSZ round-trip", || {
+        let pk = PublicKey::from_ssz_bytes(&pk.as_ssz_bytes()).map_err(|e| format!("{e:?}"))?;
+        let sig =
+            Signature::from_ssz_bytes(&signature.as_ssz_bytes()).map_err(|e| format!("{e:?}"))?;
+        expect_valid(&pk, &sig)
+    });
+    checks.run("bincode round-trip", || {
+        let sig: Signature = bincode::deserialize(&bincode::serialize(&signature)?)?;
+        expect_valid(&pk, &sig)
+    });
+    checks.run("Zig binary round-trip", || {
+        let mut bytes = Vec::new();
+        write_signature(
+            &mut bytes,
+            &signature_to_json(&signature, meta)?,
+            meta,
+            Repr::Montgomery,
+            Endian::Little,
+        )?;
+        let sig: Signature =
+            signature_from_json(read_signature(&mut bytes.as_slice(), meta)?, meta)?;
+        expect_valid(&pk, &sig)
+    });
+
+    checks.finish();
+}
@@ -0,0 +1,77 @@
+//! `-` in place of a file name: stdin for inputs, stdout for the signature
+
+use std::error::Error;
+use std::io::{self, IsTerminal, Read, Write};
+
+use super::exit::{self, Failure};
+use crate::compat::hsz;
+
+pub const DASH: &str = "-";
+
+pub fn is_dash(path: &str) -> bool {
+    path == DASH
+}
+
+/// Error if more than one of `inputs` is `-`: there is only one stdin.
+pub fn check_single_stdin(inputs: &[&str]) -> Result<(), Box<dyn Error>> {
+    if inputs.iter().filter(|input| is_dash(input)).count() > 1 {
+        return Err(exit::tag(Failure::Usage)(
+            "only one input can be read from stdin",
+        ));
+    }
+    Ok(())
+}
+
+fn read_stdin() -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut bytes = Vec::new();
+    io::stdin()
+        .lock()
+        .read_to_end(&mut bytes)
+        .map_err(|e| io::Error::new(e.kind(), format!("failed to read stdin: {e}")))?;
+    Ok(bytes)
+}
+
+/// The contents of `path`, or all of stdin for `-`. `.hsz` containers are
+/// unwrapped either way.
+pub fn read(path: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    if is_dash(path) {
+        hsz::decompress(&read_stdin()?)
+    } else {
+        hsz::read(path)
+    }
+}
+
+/// The 32-byte message to sign or verify: `message` itself, zero-padded or
+/// truncated, or stdin for `-`. A trailing newline on stdin is not part of
+/// the message, as with `zig-verify-signature --message-file`.
+pub fn message(message: &str) -> Result<[u8; 32], Box<dyn Error>> {
+    let stdin;
+    let raw = if is_dash(message) {
+        stdin = read_stdin()?;
+        let end = stdin
+            .iter()
+            .rposition(|&b| b != b'\n' && b != b'\r')
+            .map_or(0, |i| i + 1);
+        &stdin[..end]
+    } else {
+        message.as_bytes()
+    };
+    let mut bytes = [0u8; 32];
+    let len = raw.len().min(32);
+    bytes[..len].copy_from_slice(&raw[..len]);
+    Ok(bytes)
+}
+
+/// Write `bytes` to stdout as they are. Raw signatures are refused when
+/// stdout is a terminal.
+pub fn write_stdout(bytes: &[u8], binary: bool) -> Result<(), Box<dyn Error>> {
+    let mut stdout = io::stdout().lock();
+    if binary && stdout.is_terminal() {
+        return Err(exit::tag(Failure::Usage)(
+            "refusing to write a binary signature to a terminal; redirect stdout or use --encoding base64",
+        ));
+    }
+    stdout.write_all(bytes)?;
+    stdout.flush()?;
+    Ok(())
+}
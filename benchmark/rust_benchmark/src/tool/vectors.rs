@@ -0,0 +1,127 @@
+//! `gen-kat` and `check-kat` over [`crate::kat`] bundles, and `gen-messages`
+//! manifests for [`crate::batch_sign`]
+
+use std::error::Error;
+use std::fs;
+
+use rand::{rngs::StdRng, SeedableRng};
+
+use super::exit::{self, Failure};
+use super::output::{progress, Output};
+use crate::batch_sign::{self, MessageStrategy};
+use crate::kat;
+use crate::lifetime::LifetimeTag;
+use crate::upstream::{
+    SIGTopLevelTargetSumLifetime18Dim64Base8, SIGTopLevelTargetSumLifetime32Dim64Base8,
+    SIGTopLevelTargetSumLifetime8Dim64Base8,
+};
+
+pub fn gen_kat_command(
+    seeds: &[[u8; 32]],
+    epochs: &[u32],
+    out: &std::path::Path,
+    lifetime: LifetimeTag,
+    stdout: &mut Output,
+) -> Result<(), Box<dyn Error>> {
+    progress!(
+        "Generating known-answer vectors for {} seeds at epochs {:?} (lifetime {})...",
+        seeds.len(),
+        epochs,
+        lifetime
+    );
+    let manifest = match lifetime {
+        LifetimeTag::Pow8 => {
+            kat::generate_bundle::<SIGTopLevelTargetSumLifetime8Dim64Base8>(seeds, epochs, out)?
+        }
+        LifetimeTag::Pow18 => {
+            kat::generate_bundle::<SIGTopLevelTargetSumLifetime18Dim64Base8>(seeds, epochs, out)?
+        }
+        LifetimeTag::Pow32 => {
+            kat::generate_bundle::<SIGTopLevelTargetSumLifetime32Dim64Base8>(seeds, epochs, out)?
+        }
+    };
+    progress!(
+        "✅ Known-answer vectors written to {} (manifest {})",
+        out.display(),
+        manifest.display()
+    );
+    stdout.set("lifetime", lifetime.as_str());
+    stdout.set("seeds", seeds.len());
+    stdout.set("epochs", epochs);
+    stdout.set("out", out.display().to_string());
+    stdout.set("manifest", manifest.display().to_string());
+    Ok(())
+}
+
+pub fn check_kat_command(dir: &std::path::Path, stdout: &mut Output) -> Result<(), Box<dyn Error>> {
+    let manifest = kat::read_manifest(dir)?;
+    let lifetime = kat::manifest_lifetime(&manifest).map_err(exit::tag(Failure::Parse))?;
+    progress!(
+        "Checking known-answer vectors in {} (lifetime {})...",
+        dir.display(),
+        lifetime
+    );
+    let check = match lifetime {
+        LifetimeTag::Pow8 => {
+            kat::check_bundle::<SIGTopLevelTargetSumLifetime8Dim64Base8>(dir, &manifest)?
+        }
+        LifetimeTag::Pow18 => {
+            kat::check_bundle::<SIGTopLevelTargetSumLifetime18Dim64Base8>(dir, &manifest)?
+        }
+        LifetimeTag::Pow32 => {
+            kat::check_bundle::<SIGTopLevelTargetSumLifetime32Dim64Base8>(dir, &manifest)?
+        }
+    };
+    stdout.set("lifetime", lifetime.as_str());
+    stdout.set("check", check.to_json());
+    if check.passed() {
+        progress!("✅ {} vectors, {} files match", check.vectors, check.files);
+    } else {
+        stdout.print(check.report());
+        progress!(
+            "❌ {} mismatches across {} vectors, {} files",
+            check.mismatches.len(),
+            check.vectors,
+            check.files
+        );
+        stdout.fail();
+    }
+    Ok(())
+}
+
+/// Write a batch-sign manifest of `count` generated messages to `out`, or to
+/// stdout. The seed is recorded so a corpus that found a mismatch can be
+/// regenerated.
+pub fn gen_messages_command(
+    count: u32,
+    first_epoch: u32,
+    strategy: MessageStrategy,
+    seed: [u8; 32],
+    out: Option<&str>,
+    stdout: &mut Output,
+) -> Result<(), Box<dyn Error>> {
+    let mut rng = StdRng::from_seed(seed);
+    let manifest = batch_sign::generate_manifest(count, first_epoch, strategy, &mut rng)
+        .map_err(exit::tag(Failure::Usage))?;
+    let seed = hex::encode(seed);
+    progress!(
+        "Generated {count} messages ({} strategy, seed {seed})",
+        strategy.as_str()
+    );
+    stdout.set("count", count);
+    stdout.set("first_epoch", first_epoch);
+    stdout.set("strategy", strategy.as_str());
+    stdout.set("seed", seed);
+    match out {
+        Some(path) => {
+            fs::write(path, &manifest)?;
+            progress!("✅ Manifest saved to {path}");
+            stdout.set("path", path);
+        }
+        None => {
+            stdout.print(&manifest);
+            stdout.set("manifest", manifest);
+        }
+    }
+    Ok(())
+}
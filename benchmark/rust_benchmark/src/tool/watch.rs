@@ -0,0 +1,222 @@
+//! `verify --watch`: re-verify a signature whenever the Zig side rewrites it
+//!
+//! In the debugging loop with the Zig side, every `zig build` run rewrites
+//! the public key and signature. The watch is on the directories holding
+//! them rather than on the files, since a file replaced by a rename would
+//! drop a watch on the old one. After each burst of writes settles, the
+//! files are verified again and every field that changed since the previous
+//! run is listed, so a Zig fix shows up as the elements it moved.
+
+use std::error::Error;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::time::Duration;
+
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use serde_json::{json, Value};
+
+use super::exit::{self, Failure};
+use super::keys;
+use super::output::{progress, Output};
+use super::stdio;
+use crate::compat::field_diff::{self, FieldDiff};
+use crate::config::{Format, SchemeConfig};
+use crate::lifetime::{LifetimeTag, SchemeMetadata};
+use crate::pk_cache::Fingerprint;
+use crate::upstream::{
+    SIGTopLevelTargetSumLifetime18Dim64Base8, SIGTopLevelTargetSumLifetime32Dim64Base8,
+    SIGTopLevelTargetSumLifetime8Dim64Base8,
+};
+
+/// Quiet time after the last change before verifying again; the Zig build
+/// writes the public key and the signature separately.
+const SETTLE: Duration = Duration::from_millis(200);
+
+/// Changed fields listed per run; a wholly different key changes them all.
+const MAX_CHANGES: usize = 20;
+
+/// What one run decoded, compared against the next run.
+struct Run {
+    valid: bool,
+    public_key: Value,
+    signature: Value,
+}
+
+fn run_for_scheme<S: SchemeMetadata>(
+    sig_path: &str,
+    pk_path: &str,
+    msg_bytes: &[u8; 32],
+    epoch: u32,
+    expect_key: Option<Fingerprint>,
+    format: Format,
+    stdout: &mut Output,
+) -> Result<Run, Box<dyn Error>> {
+    let pk_bytes = stdio::read(pk_path)?;
+    let (public_key, signature) =
+        keys::load_for_verify::<S>(sig_path, pk_path, &pk_bytes, expect_key, format, stdout)?;
+    Ok(Run {
+        valid: S::verify(&public_key, epoch, msg_bytes, &signature),
+        public_key: field_diff::public_key_value::<S>(&public_key)?,
+        signature: field_diff::signature_value::<S>(&signature)?,
+    })
+}
+
+fn run(
+    sig_path: &str,
+    pk_path: &str,
+    msg_bytes: &[u8; 32],
+    epoch: u32,
+    expect_key: Option<Fingerprint>,
+    config: &SchemeConfig,
+    stdout: &mut Output,
+) -> Result<Run, Box<dyn Error>> {
+    let format = config.format();
+    match config.lifetime() {
+        LifetimeTag::Pow8 => run_for_scheme::<SIGTopLevelTargetSumLifetime8Dim64Base8>(
+            sig_path, pk_path, msg_bytes, epoch, expect_key, format, stdout,
+        ),
+        LifetimeTag::Pow18 => run_for_scheme::<SIGTopLevelTargetSumLifetime18Dim64Base8>(
+            sig_path, pk_path, msg_bytes, epoch, expect_key, format, stdout,
+        ),
+        LifetimeTag::Pow32 => run_for_scheme::<SIGTopLevelTargetSumLifetime32Dim64Base8>(
+            sig_path, pk_path, msg_bytes, epoch, expect_key, format, stdout,
+        ),
+    }
+}
+
+fn prefixed(prefix: &'static str, diffs: Vec<FieldDiff>) -> impl Iterator<Item = FieldDiff> {
+    diffs.into_iter().map(move |diff| FieldDiff {
+        location: format!("{prefix}.{}", diff.location),
+        ..diff
+    })
+}
+
+/// Fields of `current` that differ from `previous`, prefixed `pk.` or `sig.`.
+fn changes(previous: &Run, current: &Run) -> Vec<FieldDiff> {
+    prefixed(
+        "pk",
+        field_diff::diff_values(&previous.public_key, &current.public_key),
+    )
+    .chain(prefixed(
+        "sig",
+        field_diff::diff_values(&previous.signature, &current.signature),
+    ))
+    .collect()
+}
+
+/// Block until one of the `names` in the watched directories changes and no
+/// further event arrives for `SETTLE`.
+fn wait_for_change(
+    events: &Receiver<notify::Result<Event>>,
+    names: &[OsString],
+) -> Result<(), Box<dyn Error>> {
+    loop {
+        match events.recv().map_err(|_| "file watcher stopped")? {
+            // Reading the files ourselves shows up as access events
+            Ok(event) if matches!(event.kind, EventKind::Access(_)) => {}
+            Ok(event) => {
+                let ours = event.paths.iter().any(|path| {
+                    path.file_name()
+                        .is_some_and(|name| names.iter().any(|n| n == name))
+                });
+                if ours {
+                    break;
+                }
+            }
+            Err(e) => eprintln!("⚠️  File watcher error: {e}"),
+        }
+    }
+    while events.recv_timeout(SETTLE).is_ok() {}
+    Ok(())
+}
+
+/// Verify `sig_path` against `pk_path`, then again after every change to
+/// either file, until interrupted. A run whose files do not decode (the Zig
+/// build may be halfway through writing them) is reported and skipped.
+pub fn verify_watch_command(
+    sig_path: &str,
+    pk_path: &str,
+    message: &str,
+    epoch: u32,
+    expect_key: Option<Fingerprint>,
+    config: &SchemeConfig,
+    stdout: &mut Output,
+) -> Result<(), Box<dyn Error>> {
+    let usage = exit::tag(Failure::Usage);
+    if stdout.is_json() {
+        return Err(usage(
+            "verify --watch reports each run as it happens and cannot write a --json document",
+        ));
+    }
+    if stdio::is_dash(sig_path) || stdio::is_dash(pk_path) {
+        return Err(usage(
+            "verify --watch needs the signature and public key as files, not stdin",
+        ));
+    }
+    let msg_bytes = stdio::message(message)?;
+
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    let mut names: Vec<OsString> = Vec::new();
+    for path in [sig_path, pk_path].map(Path::new) {
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        if !dirs.contains(&dir) {
+            dirs.push(dir);
+        }
+        names.extend(path.file_name().map(OsString::from));
+    }
+    let (sender, events) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender)?;
+    for dir in &dirs {
+        watcher.watch(dir, RecursiveMode::NonRecursive)?;
+    }
+
+    let mut previous: Option<Run> = None;
+    for number in 1u64.. {
+        progress!("Run {number}: verifying {sig_path} against {pk_path} (epoch {epoch})...");
+        match run(
+            sig_path, pk_path, &msg_bytes, epoch, expect_key, config, stdout,
+        ) {
+            Ok(current) => {
+                let changed = previous
+                    .as_ref()
+                    .map(|previous| changes(previous, &current));
+                if let Some(changed) = &changed {
+                    for change in changed.iter().take(MAX_CHANGES) {
+                        stdout.line(format!("  {change}"));
+                    }
+                    if changed.len() > MAX_CHANGES {
+                        stdout.line(format!("  ... {} more", changed.len() - MAX_CHANGES));
+                    }
+                }
+                stdout.line(format!(
+                    "WATCH_RESULT:{}",
+                    json!({
+                        "run": number,
+                        "valid": current.valid,
+                        "changed": changed.map(|changed| changed.len()),
+                    })
+                ));
+                if current.valid {
+                    progress!("✅ Signature verification PASSED!");
+                } else {
+                    progress!("❌ Signature verification FAILED!");
+                }
+                previous = Some(current);
+            }
+            Err(e) => {
+                stdout.line(format!(
+                    "WATCH_RESULT:{}",
+                    json!({ "run": number, "error": e.to_string() })
+                ));
+                progress!("⚠️  {e}");
+            }
+        }
+        progress!("Watching for changes (Ctrl-C to stop)...");
+        wait_for_change(&events, &names)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,34 @@
+//! Single point of contact with the hash-based signature upstream
+//!
+//! `leansig` and `hashsig` expose the same module layout but distinct types, so
+//! mixing imports from both in one binary produces confusing trait mismatches.
+//! Everything in this crate imports scheme types and traits from here instead,
+//! and the `leansig` / `hashsig` features pick which crate backs them. When both
+//! features are enabled (as the upstream comparison test does) `leansig` wins.
+
+#[cfg(not(any(feature = "leansig", feature = "hashsig")))]
+compile_error!("enable the `leansig` or `hashsig` feature to select a signature upstream");
+
+#[cfg(feature = "leansig")]
+pub use leansig as backend;
+
+#[cfg(all(feature = "hashsig", not(feature = "leansig")))]
+pub use hashsig as backend;
+
+pub use backend::signature::generalized_xmss::instantiations_poseidon_top_level::lifetime_2_to_the_18::SIGTopLevelTargetSumLifetime18Dim64Base8;
+pub use backend::signature::generalized_xmss::instantiations_poseidon_top_level::lifetime_2_to_the_32::hashing_optimized::SIGTopLevelTargetSumLifetime32Dim64Base8;
+pub use backend::signature::generalized_xmss::instantiations_poseidon_top_level::lifetime_2_to_the_8::SIGTopLevelTargetSumLifetime8Dim64Base8;
+pub use backend::signature::{SignatureScheme, SignatureSchemeSecretKey};
+// The type parameters of an instantiation, for `lifetime::UpstreamShape`
+// and `kat::MessageEncoding`
+pub use backend::inc_encoding::IncomparableEncoding;
+pub use backend::signature::generalized_xmss::GeneralizedXMSSSignatureScheme;
+pub use backend::symmetric::prf::Pseudorandom;
+pub use backend::symmetric::tweak_hash::TweakableHash;
+
+/// Name of the crate backing this build, recorded in tool output.
+pub const BACKEND_NAME: &str = if cfg!(feature = "leansig") {
+    "leansig"
+} else {
+    "hashsig"
+};
@@ -0,0 +1,223 @@
+//! Verification latency under load
+//!
+//! Models a consensus client that must verify incoming signatures within a
+//! per-slot budget. Jobs arrive at a fixed rate and are handed to a rayon
+//! work-stealing pool; latency is measured from a job's scheduled arrival to
+//! the end of its verification, so it includes time spent queued behind other
+//! jobs. Sweeping the arrival rate gives p50/p99 latency per rate and the
+//! highest rate whose p99 stays inside the SLO.
+
+use std::error::Error;
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+
+use crate::cancel;
+use crate::stats::percentile;
+use crate::upstream::{SignatureScheme, SignatureSchemeSecretKey};
+
+/// Default latency budget: the attestation deadline within a 12 s slot.
+pub const DEFAULT_SLO: Duration = Duration::from_secs(4);
+
+/// Fractions of the estimated pool capacity tried by `sweep`.
+const RATE_FRACTIONS: [f64; 8] = [0.25, 0.5, 0.75, 0.9, 1.0, 1.1, 1.25, 1.5];
+
+/// Achieved throughput must reach this share of the offered rate for the
+/// pool to count as keeping up.
+const KEEP_UP_RATIO: f64 = 0.95;
+
+/// Distinct signatures cycled through by the job queue.
+const CORPUS_SIZE: usize = 32;
+
+/// A fixed set of valid (epoch, message, signature) triples under one key.
+pub struct VerifyCorpus<S: SignatureScheme> {
+    pub public_key: S::PublicKey,
+    pub entries: Vec<(u32, [u8; 32], S::Signature)>,
+}
+
+impl<S> VerifyCorpus<S>
+where
+    S: SignatureScheme,
+    S::SecretKey: SignatureSchemeSecretKey,
+{
+    /// Generate a key from `seed` and sign messages at its first prepared epochs.
+    pub fn generate(seed: [u8; 32]) -> Result<Self, Box<dyn Error>> {
+        let mut rng = ChaCha20Rng::from_seed(seed);
+        let (public_key, secret_key) = S::key_gen(&mut rng, 0, 256);
+        let prepared = secret_key.get_prepared_interval();
+        let mut entries = Vec::with_capacity(CORPUS_SIZE);
+        for epoch in prepared.take(CORPUS_SIZE) {
+            let epoch = epoch as u32;
+            let mut message = [0u8; 32];
+            rng.fill(&mut message);
+            let signature = S::sign(&secret_key, epoch, &message)
+                .map_err(|e| format!("failed to sign message at epoch {epoch}: {e:?}"))?;
+            entries.push((epoch, message, signature));
+        }
+        if entries.is_empty() {
+            return Err("secret key has no prepared epochs".into());
+        }
+        Ok(Self {
+            public_key,
+            entries,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RateResult {
+    pub offered_rate: f64,
+    pub achieved_rate: f64,
+    pub jobs: usize,
+    pub failures: usize,
+    pub p50: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+}
+
+impl RateResult {
+    pub fn meets_slo(&self, slo: Duration) -> bool {
+        self.failures == 0
+            && self.p99 <= slo
+            && self.achieved_rate >= self.offered_rate * KEEP_UP_RATIO
+    }
+
+    pub fn to_json(&self, slo: Duration) -> serde_json::Value {
+        serde_json::json!({
+            "offered_rate": self.offered_rate,
+            "achieved_rate": self.achieved_rate,
+            "jobs": self.jobs,
+            "failures": self.failures,
+            "p50_ms": self.p50.as_secs_f64() * 1e3,
+            "p99_ms": self.p99.as_secs_f64() * 1e3,
+            "max_ms": self.max.as_secs_f64() * 1e3,
+            "meets_slo": self.meets_slo(slo),
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LoadReport {
+    pub threads: usize,
+    pub slo: Duration,
+    /// Single-threaded mean verification time used to pick the rates
+    pub mean_verify: Duration,
+    pub rates: Vec<RateResult>,
+}
+
+impl LoadReport {
+    /// Highest offered rate whose p99 latency stayed inside the SLO.
+    pub fn max_sustainable_rate(&self) -> Option<f64> {
+        self.rates
+            .iter()
+            .filter(|r| r.meets_slo(self.slo))
+            .map(|r| r.offered_rate)
+            .reduce(f64::max)
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "threads": self.threads,
+            "slo_ms": self.slo.as_secs_f64() * 1e3,
+            "mean_verify_ms": self.mean_verify.as_secs_f64() * 1e3,
+            "max_sustainable_rate": self.max_sustainable_rate(),
+            "rates": self.rates.iter().map(|r| r.to_json(self.slo)).collect::<Vec<_>>(),
+        })
+    }
+}
+
+/// Offer `jobs` verifications to `pool` at `rate` jobs per second.
+pub fn run_at_rate<S>(
+    pool: &ThreadPool,
+    corpus: &Arc<VerifyCorpus<S>>,
+    rate: f64,
+    jobs: usize,
+) -> RateResult
+where
+    S: SignatureScheme + 'static,
+    S::PublicKey: Send + Sync,
+    S::Signature: Send + Sync,
+{
+    let (tx, rx) = mpsc::channel();
+    let interval = Duration::from_secs_f64(1.0 / rate);
+    let start = Instant::now();
+    for i in 0..jobs {
+        let arrival = start + interval * i as u32;
+        if let Some(wait) = arrival.checked_duration_since(Instant::now()) {
+            std::thread::sleep(wait);
+        }
+        let corpus = Arc::clone(corpus);
+        let tx = tx.clone();
+        pool.spawn(move || {
+            let (epoch, message, signature) = &corpus.entries[i % corpus.entries.len()];
+            let valid = S::verify(&corpus.public_key, *epoch, message, signature);
+            // The receiver outlives every job; a send error cannot happen.
+            let _ = tx.send((arrival.elapsed(), valid));
+        });
+    }
+    drop(tx);
+
+    let mut latencies = Vec::with_capacity(jobs);
+    let mut failures = 0;
+    for (latency, valid) in rx {
+        latencies.push(latency);
+        if !valid {
+            failures += 1;
+        }
+    }
+    let elapsed = start.elapsed();
+    latencies.sort_unstable();
+    RateResult {
+        offered_rate: rate,
+        achieved_rate: jobs as f64 / elapsed.as_secs_f64().max(f64::EPSILON),
+        jobs,
+        failures,
+        p50: percentile(&latencies, 50.0),
+        p99: percentile(&latencies, 99.0),
+        max: latencies.last().copied().unwrap_or_default(),
+    }
+}
+
+/// Estimate the pool's capacity and measure latency at rates around it. When
+/// [`cancel`]led, the report holds the rates measured so far.
+pub fn sweep<S>(
+    corpus: VerifyCorpus<S>,
+    threads: usize,
+    jobs: usize,
+    slo: Duration,
+) -> Result<LoadReport, Box<dyn Error>>
+where
+    S: SignatureScheme + 'static,
+    S::PublicKey: Send + Sync,
+    S::Signature: Send + Sync,
+{
+    let pool = ThreadPoolBuilder::new().num_threads(threads).build()?;
+    let threads = pool.current_num_threads();
+
+    let calibration = Instant::now();
+    for (epoch, message, signature) in &corpus.entries {
+        if !S::verify(&corpus.public_key, *epoch, message, signature) {
+            return Err(format!("corpus signature at epoch {epoch} does not verify").into());
+        }
+    }
+    let mean_verify = calibration.elapsed() / corpus.entries.len() as u32;
+    let capacity = threads as f64 / mean_verify.as_secs_f64().max(1e-9);
+
+    let corpus = Arc::new(corpus);
+    let _scope = cancel::Scope::enter();
+    let rates = RATE_FRACTIONS
+        .iter()
+        .take_while(|_| !cancel::is_cancelled())
+        .map(|fraction| run_at_rate(&pool, &corpus, capacity * fraction, jobs))
+        .collect();
+    Ok(LoadReport {
+        threads,
+        slo,
+        mean_verify,
+        rates,
+    })
+}
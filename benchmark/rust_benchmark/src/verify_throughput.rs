@@ -0,0 +1,152 @@
+//! Verified signatures per second, one at a time and in parallel
+//!
+//! A node checks signatures from many validators, so the rate it can verify
+//! them at matters more than one verification's latency. [`measure`] signs
+//! a batch of messages under one key and verifies the batch twice: one
+//! signature after another on the calling thread, and, if asked, split
+//! across rayon's global pool as `batch-verify` does. The rates go in the
+//! `--json` document next to the Zig implementation's.
+
+use std::error::Error;
+use std::time::{Duration, Instant};
+
+use rand::{rngs::StdRng, SeedableRng};
+use serde_json::{json, Value};
+
+use crate::batch::{self, BatchItem};
+use crate::cancel;
+use crate::upstream::{SignatureScheme, SignatureSchemeSecretKey};
+
+/// Active epochs of the key the batch is signed with.
+pub const NUM_ACTIVE_EPOCHS: usize = 256;
+
+/// One pass over the batch.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VerifyRun {
+    pub threads: usize,
+    /// Signatures verified before the pass finished or was cancelled
+    pub verified: usize,
+    pub elapsed: Duration,
+}
+
+impl VerifyRun {
+    pub fn rate(&self) -> f64 {
+        self.verified as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+
+    fn to_json(self) -> Value {
+        json!({
+            "threads": self.threads,
+            "verified": self.verified,
+            "elapsed_ms": self.elapsed.as_secs_f64() * 1e3,
+            "signatures_per_sec": self.rate(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerifyThroughputReport {
+    pub signatures: usize,
+    pub serial: VerifyRun,
+    pub parallel: Option<VerifyRun>,
+}
+
+impl VerifyThroughputReport {
+    /// Parallel rate over the serial rate.
+    pub fn speedup(&self) -> Option<f64> {
+        self.parallel
+            .map(|parallel| parallel.rate() / self.serial.rate().max(f64::EPSILON))
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "signatures": self.signatures,
+            "serial": self.serial.to_json(),
+            "parallel": self.parallel.map(VerifyRun::to_json),
+            "speedup": self.speedup(),
+        })
+    }
+}
+
+/// Message signed at position `index` of the batch.
+fn message(index: usize) -> [u8; 32] {
+    let mut message = [0u8; 32];
+    message[..24].copy_from_slice(b"hash-zig verify messages");
+    message[24..].copy_from_slice(&(index as u64).to_le_bytes());
+    message
+}
+
+/// Keygen from `seed`, sign `count` messages at the prepared epochs in turn,
+/// and time verifying them serially and, with `parallel`, on rayon's global
+/// pool. Every signature must verify. When [`cancel`]led, the serial pass
+/// reports the signatures it got through and the parallel pass is skipped.
+pub fn measure<S>(
+    seed: [u8; 32],
+    count: usize,
+    parallel: bool,
+) -> Result<VerifyThroughputReport, Box<dyn Error>>
+where
+    S: SignatureScheme,
+    S::SecretKey: SignatureSchemeSecretKey,
+    S::PublicKey: Sync,
+    S::Signature: Sync,
+{
+    let mut rng = StdRng::from_seed(seed);
+    let (public_key, secret_key) = S::key_gen(&mut rng, 0, NUM_ACTIVE_EPOCHS);
+    let items = secret_key
+        .get_prepared_interval()
+        .cycle()
+        .take(count)
+        .enumerate()
+        .map(|(index, epoch)| {
+            let epoch = epoch as u32;
+            let message = message(index);
+            S::sign(&secret_key, epoch, &message)
+                .map(|signature| (epoch, message, signature))
+                .map_err(|e| format!("failed to sign message at epoch {epoch}: {e:?}"))
+        })
+        .collect::<Result<Vec<BatchItem<S>>, String>>()?;
+
+    let _scope = cancel::Scope::enter();
+    let start = Instant::now();
+    let mut verified = 0;
+    for (epoch, message, signature) in &items {
+        if cancel::is_cancelled() {
+            break;
+        }
+        if !S::verify(&public_key, *epoch, message, signature) {
+            return Err(format!("signature {verified} (epoch {epoch}) did not verify").into());
+        }
+        verified += 1;
+    }
+    let serial = VerifyRun {
+        threads: 1,
+        verified,
+        elapsed: start.elapsed(),
+    };
+
+    let parallel = if parallel && !cancel::is_cancelled() {
+        let start = Instant::now();
+        let valid = batch::verify_batch::<S>(&public_key, &items);
+        let elapsed = start.elapsed();
+        if let Some(index) = valid.iter().position(|valid| !valid) {
+            return Err(format!(
+                "signature {index} (epoch {}) did not verify in parallel",
+                items[index].0
+            )
+            .into());
+        }
+        Some(VerifyRun {
+            threads: rayon::current_num_threads(),
+            verified: items.len(),
+            elapsed,
+        })
+    } else {
+        None
+    };
+    Ok(VerifyThroughputReport {
+        signatures: items.len(),
+        serial,
+        parallel,
+    })
+}
@@ -0,0 +1,200 @@
+//! In-process calls into the Zig implementation
+//!
+//! Links the `hash-zig-c` static library (`zig build c-api`, see
+//! `benchmark/zig_benchmark/src/c_api.zig`) so differential tests can run
+//! Rust and Zig keygen/sign/verify side by side without spawning
+//! `cross-lang-zig-tool`. Keys and signatures cross the boundary as SSZ.
+
+use std::error::Error;
+
+use rand::{rngs::StdRng, SeedableRng};
+use ssz::{Decode, Encode};
+
+use crate::compat::ssz_codec;
+use crate::lifetime::SchemeMetadata;
+use crate::signer::prepare_epoch;
+
+mod sys {
+    extern "C" {
+        pub fn hashzig_zig_keygen(
+            log_lifetime: u32,
+            seed: *const [u8; 32],
+            activation_epoch: u64,
+            num_active_epochs: u64,
+            pk_out: *mut u8,
+            pk_cap: usize,
+            pk_len: *mut usize,
+        ) -> i32;
+
+        pub fn hashzig_zig_sign(
+            log_lifetime: u32,
+            seed: *const [u8; 32],
+            activation_epoch: u64,
+            num_active_epochs: u64,
+            epoch: u32,
+            message: *const [u8; 32],
+            sig_out: *mut u8,
+            sig_cap: usize,
+            sig_len: *mut usize,
+        ) -> i32;
+
+        pub fn hashzig_zig_verify(
+            log_lifetime: u32,
+            pk: *const u8,
+            pk_len: usize,
+            epoch: u32,
+            message: *const [u8; 32],
+            sig: *const u8,
+            sig_len: usize,
+        ) -> i32;
+    }
+}
+
+/// Same value as `capi::HASHZIG_ERR_BUFFER_TOO_SMALL`.
+const ERR_BUFFER_TOO_SMALL: i32 = -4;
+
+/// Initial output buffer size; grown and retried if the Zig side needs more.
+const INITIAL_BUFFER: usize = 8192;
+
+fn zig_error(what: &str, code: i32) -> Box<dyn Error> {
+    format!("Zig {what} failed with code {code}").into()
+}
+
+/// Call `f(buf, cap, len)` until the output fits, returning the filled bytes.
+fn with_output_buffer(
+    what: &str,
+    mut f: impl FnMut(*mut u8, usize, *mut usize) -> i32,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut buf = vec![0u8; INITIAL_BUFFER];
+    loop {
+        let mut len = 0usize;
+        match f(buf.as_mut_ptr(), buf.len(), &mut len) {
+            0 => {
+                buf.truncate(len);
+                return Ok(buf);
+            }
+            ERR_BUFFER_TOO_SMALL if len > buf.len() => buf.resize(len, 0),
+            code => return Err(zig_error(what, code)),
+        }
+    }
+}
+
+/// SSZ public key generated by Zig from `seed`.
+pub fn zig_keygen<S: SchemeMetadata>(
+    seed: [u8; 32],
+    activation_epoch: usize,
+    num_active_epochs: usize,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    with_output_buffer("keygen", |out, cap, len| unsafe {
+        sys::hashzig_zig_keygen(
+            S::METADATA.log_lifetime,
+            &seed,
+            activation_epoch as u64,
+            num_active_epochs as u64,
+            out,
+            cap,
+            len,
+        )
+    })
+}
+
+/// SSZ signature produced by Zig with the key pair derived from `seed`,
+/// prepared up to `epoch` on the Zig side.
+pub fn zig_sign<S: SchemeMetadata>(
+    seed: [u8; 32],
+    activation_epoch: usize,
+    num_active_epochs: usize,
+    epoch: u32,
+    message: &[u8; 32],
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    with_output_buffer("sign", |out, cap, len| unsafe {
+        sys::hashzig_zig_sign(
+            S::METADATA.log_lifetime,
+            &seed,
+            activation_epoch as u64,
+            num_active_epochs as u64,
+            epoch,
+            message,
+            out,
+            cap,
+            len,
+        )
+    })
+}
+
+/// Verify SSZ-encoded artifacts with the Zig implementation.
+pub fn zig_verify<S: SchemeMetadata>(
+    pk: &[u8],
+    epoch: u32,
+    message: &[u8; 32],
+    sig: &[u8],
+) -> Result<bool, Box<dyn Error>> {
+    let code = unsafe {
+        sys::hashzig_zig_verify(
+            S::METADATA.log_lifetime,
+            pk.as_ptr(),
+            pk.len(),
+            epoch,
+            message,
+            sig.as_ptr(),
+            sig.len(),
+        )
+    };
+    match code {
+        0 => Ok(false),
+        1 => Ok(true),
+        code => Err(zig_error("verify", code)),
+    }
+}
+
+/// Outcome of one Rust-vs-Zig comparison.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Differential {
+    pub public_keys_match: bool,
+    pub signatures_match: bool,
+    pub rust_verifies_zig: bool,
+    pub zig_verifies_rust: bool,
+}
+
+impl Differential {
+    pub fn is_consistent(&self) -> bool {
+        self.public_keys_match
+            && self.signatures_match
+            && self.rust_verifies_zig
+            && self.zig_verifies_rust
+    }
+}
+
+/// Generate, sign and cross-verify with both implementations from one seed.
+pub fn compare<S>(
+    seed: [u8; 32],
+    num_active_epochs: usize,
+    epoch: u32,
+    message: &[u8; 32],
+) -> Result<Differential, Box<dyn Error>>
+where
+    S: SchemeMetadata,
+    S::PublicKey: Encode + Decode,
+    S::Signature: Encode + Decode,
+{
+    let mut rng = StdRng::from_seed(seed);
+    let (rust_pk, mut rust_sk) = S::key_gen(&mut rng, 0, num_active_epochs);
+    if !prepare_epoch(&mut rust_sk, u64::from(epoch)) {
+        return Err(format!("epoch {epoch} cannot be prepared").into());
+    }
+    let rust_sig = S::sign(&rust_sk, epoch, message)
+        .map_err(|e| format!("Rust signing failed at epoch {epoch}: {e:?}"))?;
+    let rust_pk_bytes = rust_pk.as_ssz_bytes();
+    let rust_sig_bytes = rust_sig.as_ssz_bytes();
+
+    let zig_pk_bytes = zig_keygen::<S>(seed, 0, num_active_epochs)?;
+    let zig_sig_bytes = zig_sign::<S>(seed, 0, num_active_epochs, epoch, message)?;
+    let zig_sig = ssz_codec::decode_signature::<S>(&zig_sig_bytes, S::METADATA)?;
+
+    Ok(Differential {
+        public_keys_match: rust_pk_bytes == zig_pk_bytes,
+        signatures_match: rust_sig_bytes == zig_sig_bytes,
+        rust_verifies_zig: S::verify(&rust_pk, epoch, message, &zig_sig),
+        zig_verifies_rust: zig_verify::<S>(&rust_pk_bytes, epoch, message, &rust_sig_bytes)?,
+    })
+}
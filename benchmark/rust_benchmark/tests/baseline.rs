@@ -0,0 +1,66 @@
+//! Bench results compared with a stored baseline
+
+use rust_benchmark::baseline;
+use serde_json::json;
+
+fn ops(keygen: f64, sign: f64) -> serde_json::Value {
+    json!({
+        "iterations": 5,
+        "warmup": 1,
+        "metrics": {
+            "keygen": { "median_ms": keygen, "mean_ms": 1.0 },
+            "sign": { "median_ms": sign },
+        },
+    })
+}
+
+#[test]
+fn regressions_past_the_threshold_fail() {
+    let threshold = baseline::parse_threshold("5%").unwrap();
+    assert_eq!(threshold, 0.05);
+    assert_eq!(baseline::parse_threshold("0.1").unwrap(), 0.1);
+    assert!(baseline::parse_threshold("-3%").is_err());
+    assert!(baseline::parse_threshold("fast").is_err());
+
+    // A --json document holds the result under `ops`
+    let stored = json!({ "command": "bench ops", "ops": ops(100.0, 10.0) });
+    let comparison = baseline::compare(&stored, &ops(104.0, 9.0), threshold).unwrap();
+    assert!(!comparison.is_regression());
+
+    let comparison = baseline::compare(&stored, &ops(110.0, 9.0), threshold).unwrap();
+    let regressed: Vec<_> = comparison.regressions().map(|d| d.name.as_str()).collect();
+    assert_eq!(regressed, ["keygen"]);
+    assert!((comparison.deltas[0].change() - 0.1).abs() < 1e-9);
+    assert_eq!(comparison.to_json()["regressed"], true);
+}
+
+#[test]
+fn scaling_rows_are_matched_by_thread_count() {
+    let stored = json!({ "rows": [
+        { "threads": 1, "keygen_ms": 100.0, "sign_ms": 50.0, "verify_ms": 20.0 },
+        { "threads": 4, "keygen_ms": 40.0, "sign_ms": 15.0, "verify_ms": 6.0 },
+    ]});
+    let current = json!({ "rows": [
+        { "threads": 1, "keygen_ms": 100.0, "sign_ms": 50.0, "verify_ms": 20.0 },
+        { "threads": 8, "keygen_ms": 30.0, "sign_ms": 9.0, "verify_ms": 4.0 },
+    ]});
+    let comparison = baseline::compare(&stored, &current, 0.05).unwrap();
+    assert_eq!(comparison.deltas.len(), 3);
+    assert!(!comparison.is_regression());
+    assert!(comparison.unmatched.contains(&"keygen@4".to_string()));
+    assert!(comparison.unmatched.contains(&"verify@8".to_string()));
+
+    assert!(baseline::compare(&json!({}), &current, 0.05).is_err());
+}
+
+#[test]
+fn cold_and_warm_runs_are_not_compared() {
+    let mut cold = ops(300.0, 30.0);
+    cold["mode"] = json!("cold");
+    // Results from before the mode was recorded are warm
+    assert_eq!(baseline::regime(&ops(100.0, 10.0)), "warm");
+    assert_eq!(baseline::regime(&json!({ "ops": cold.clone() })), "cold");
+
+    assert!(baseline::compare(&ops(100.0, 10.0), &cold, 0.05).is_err());
+    assert!(baseline::compare(&cold, &cold, 0.05).is_ok());
+}
@@ -0,0 +1,177 @@
+//! Batch manifests, signing them with one key and verifying the result
+
+use std::fs;
+
+use rand::{rngs::StdRng, SeedableRng};
+use rust_benchmark::batch::Parallelism;
+use rust_benchmark::batch_sign::{self, BatchEntry, BatchFile, MessageStrategy, Verdict};
+use rust_benchmark::compat::codec;
+use rust_benchmark::compat::text_encoding::Encoding;
+use rust_benchmark::config::Format;
+use rust_benchmark::upstream::{
+    SIGTopLevelTargetSumLifetime8Dim64Base8 as Scheme, SignatureScheme,
+};
+
+fn text_message(text: &str) -> [u8; 32] {
+    batch_sign::parse_message(text).unwrap()
+}
+
+#[test]
+fn parses_manifests_in_epoch_order() {
+    let hex = "ab".repeat(32);
+    let text = format!("# epoch message\n7 0x{hex}\n\n  2   block 2 attestation  \n");
+    assert_eq!(
+        batch_sign::parse_manifest(&text).unwrap(),
+        vec![
+            BatchEntry {
+                epoch: 2,
+                message: text_message("block 2 attestation"),
+            },
+            BatchEntry {
+                epoch: 7,
+                message: [0xab; 32],
+            },
+        ]
+    );
+    assert_eq!(&text_message("hi")[..3], b"hi\0");
+    assert_eq!(text_message(&"x".repeat(40)), [b'x'; 32]);
+}
+
+#[test]
+fn rejects_bad_manifests() {
+    for text in [
+        "",
+        "# only a comment\n",
+        "3\n",
+        "three message\n",
+        "1 0xabcd\n",
+        "1 first\n2 second\n1 again\n",
+    ] {
+        assert!(batch_sign::parse_manifest(text).is_err(), "{text:?}");
+    }
+    let err = batch_sign::parse_manifest("1 first\n2 second\n1 again\n").unwrap_err();
+    assert!(err.to_string().contains("lines 1 and 3"), "{err}");
+}
+
+#[test]
+fn generated_manifests_parse_and_cover_the_corner_cases() {
+    for strategy in [
+        MessageStrategy::Random,
+        MessageStrategy::Ascii,
+        MessageStrategy::Boundary,
+    ] {
+        let mut rng = StdRng::from_seed([7; 32]);
+        let text = batch_sign::generate_manifest(40, 5, strategy, &mut rng).unwrap();
+        let entries = batch_sign::parse_manifest(&text).unwrap();
+        assert_eq!(entries.len(), 40);
+        assert_eq!(entries[0].epoch, 5);
+        assert_eq!(entries[39].epoch, 44);
+        assert_eq!(entries[0].message, [0x00; 32]);
+        assert_eq!(entries[1].message, [0xff; 32]);
+        assert_eq!(
+            entries[4].message.as_slice(),
+            batch_sign::BOUNDARY_MESSAGES[4].as_bytes()
+        );
+        // Longer text is truncated to its first 32 bytes, as sign does
+        assert_eq!(
+            entries[5].message.as_slice(),
+            &batch_sign::BOUNDARY_MESSAGES[5].as_bytes()[..32]
+        );
+        assert_eq!(
+            entries[7].message.as_slice(),
+            &batch_sign::BOUNDARY_MESSAGES[7].as_bytes()[..32]
+        );
+        let mut again = StdRng::from_seed([7; 32]);
+        assert_eq!(
+            batch_sign::generate_manifest(40, 5, strategy, &mut again).unwrap(),
+            text
+        );
+    }
+    let mut rng = StdRng::from_seed([7; 32]);
+    assert!(batch_sign::generate_manifest(0, 0, MessageStrategy::Random, &mut rng).is_err());
+    assert!(batch_sign::generate_manifest(2, u32::MAX, MessageStrategy::Random, &mut rng).is_err());
+}
+
+#[test]
+fn signs_and_verifies_a_batch_with_one_key() {
+    let mut rng = StdRng::from_seed([0x42; 32]);
+    let (pk, sk) = Scheme::key_gen(&mut rng, 0, 256);
+    let entries =
+        batch_sign::parse_manifest("0 first message\n9 second message\n200 third message\n")
+            .unwrap();
+    let out = std::env::temp_dir().join(format!("hash-zig-batch-sign-{}", std::process::id()));
+
+    let index =
+        batch_sign::sign_batch::<Scheme>(sk, &entries, &out, Format::Json, Encoding::Raw, false)
+            .unwrap();
+    let written: serde_json::Value =
+        serde_json::from_slice(&fs::read(out.join(batch_sign::INDEX_NAME)).unwrap()).unwrap();
+    assert_eq!(written, index);
+    assert_eq!(index["lifetime"], "2^8");
+    let signatures = index["signatures"].as_array().unwrap();
+    assert_eq!(signatures.len(), entries.len());
+    for (entry, record) in entries.iter().zip(signatures) {
+        assert_eq!(record["epoch"], entry.epoch);
+        assert_eq!(record["file"], format!("sig_{}.bin", entry.epoch));
+        let bytes = fs::read(out.join(record["file"].as_str().unwrap())).unwrap();
+        assert_eq!(record["bytes"], bytes.len());
+        let signature = codec::decode_signature::<Scheme>(&bytes, Format::Json).unwrap();
+        assert!(Scheme::verify(&pk, entry.epoch, &entry.message, &signature));
+    }
+
+    let mut files = batch_sign::index_files(&index).unwrap();
+    assert_eq!(files, batch_sign::manifest_files(&entries, Format::Json));
+    files[1].entry.message = text_message("not what was signed");
+    files.push(BatchFile {
+        entry: entries[0].clone(),
+        file: "sig_missing.bin".into(),
+    });
+    let report = batch_sign::verify_batch_dir::<Scheme>(
+        &pk,
+        &out,
+        &files,
+        Format::Json,
+        Parallelism::Global,
+    )
+    .unwrap();
+    let verdicts: Vec<&Verdict> = report.items.iter().map(|(_, verdict)| verdict).collect();
+    assert_eq!(
+        verdicts[..3],
+        [&Verdict::Valid, &Verdict::Invalid, &Verdict::Valid]
+    );
+    assert!(matches!(verdicts[3], Verdict::Unreadable(_)));
+    assert_eq!((report.passed(), report.failed()), (2, 2));
+    // Same verdicts in the same order on a pool of its own
+    let single = batch_sign::verify_batch_dir::<Scheme>(
+        &pk,
+        &out,
+        &files,
+        Format::Json,
+        Parallelism::Threads(1),
+    )
+    .unwrap();
+    assert_eq!(single.items, report.items);
+    assert_eq!(report.to_json()["items"][3]["valid"], false);
+
+    fs::remove_dir_all(&out).unwrap();
+}
+
+#[test]
+fn reads_index_files() {
+    let index = serde_json::json!({
+        "signatures": [{ "epoch": 4, "message": "cd".repeat(32), "file": "sig_4.ssz" }],
+    });
+    assert_eq!(
+        batch_sign::index_files(&index).unwrap(),
+        vec![BatchFile {
+            entry: BatchEntry {
+                epoch: 4,
+                message: [0xcd; 32],
+            },
+            file: "sig_4.ssz".into(),
+        }]
+    );
+    assert!(batch_sign::index_files(&serde_json::json!({})).is_err());
+    let no_file = serde_json::json!({ "signatures": [{ "epoch": 4, "message": "cd".repeat(32) }] });
+    assert!(batch_sign::index_files(&no_file).is_err());
+}
@@ -0,0 +1,36 @@
+//! Byte-stable output of the canonical JSON writer
+
+use rust_benchmark::compat::canonical_json;
+use serde_json::json;
+
+#[test]
+fn sorts_keys_and_drops_whitespace() {
+    let value = json!({
+        "rho": [3, 1, 2],
+        "path": { "nodes": [[10, 20], [30, 40]] },
+        "hashes": [],
+        "label": "a\"b",
+        "neg": -5,
+    });
+    assert_eq!(
+        String::from_utf8(canonical_json::to_vec(&value).unwrap()).unwrap(),
+        r#"{"hashes":[],"label":"a\"b","neg":-5,"path":{"nodes":[[10,20],[30,40]]},"rho":[3,1,2]}"#
+    );
+}
+
+#[test]
+fn same_output_regardless_of_input_order() {
+    let a: serde_json::Value =
+        serde_json::from_str(r#"{"root": [1, 2], "parameter": [3]}"#).unwrap();
+    let b: serde_json::Value =
+        serde_json::from_str("{\n  \"parameter\": [3],\n  \"root\": [1,2]\n}").unwrap();
+    assert_eq!(
+        canonical_json::to_vec(&a).unwrap(),
+        canonical_json::to_vec(&b).unwrap()
+    );
+}
+
+#[test]
+fn rejects_floats() {
+    assert!(canonical_json::to_vec(&json!({ "x": 1.5 })).is_err());
+}
@@ -0,0 +1,85 @@
+//! The C ABI, called the way a C or Zig caller would
+//!
+//! Run with `cargo test --features capi --test capi`; without the feature
+//! this file compiles to nothing.
+
+#![cfg(feature = "capi")]
+
+use rust_benchmark::capi::{
+    hashzig_keygen_into_buffer, hashzig_sign, hashzig_verify, HASHZIG_ERR_EPOCH, HASHZIG_OK,
+};
+
+const SEED: [u8; 32] = [0x17; 32];
+const MESSAGE: [u8; 32] = *b"c abi sign past the first window";
+
+fn keygen(num_active_epochs: u64) -> (Vec<u8>, Vec<u8>) {
+    let (mut pk, mut sk) = (vec![0u8; 1 << 20], vec![0u8; 64 << 20]);
+    let (mut pk_len, mut sk_len) = (0, 0);
+    let code = unsafe {
+        hashzig_keygen_into_buffer(
+            8,
+            SEED.as_ptr(),
+            0,
+            num_active_epochs,
+            pk.as_mut_ptr(),
+            pk.len(),
+            &mut pk_len,
+            sk.as_mut_ptr(),
+            sk.len(),
+            &mut sk_len,
+        )
+    };
+    assert_eq!(code, HASHZIG_OK);
+    pk.truncate(pk_len);
+    sk.truncate(sk_len);
+    (pk, sk)
+}
+
+fn sign(sk: &[u8], epoch: u32) -> Result<Vec<u8>, i32> {
+    let mut sig = vec![0u8; 1 << 16];
+    let mut sig_len = 0;
+    let code = unsafe {
+        hashzig_sign(
+            8,
+            sk.as_ptr(),
+            sk.len(),
+            epoch,
+            MESSAGE.as_ptr(),
+            sig.as_mut_ptr(),
+            sig.len(),
+            &mut sig_len,
+        )
+    };
+    if code != HASHZIG_OK {
+        return Err(code);
+    }
+    sig.truncate(sig_len);
+    Ok(sig)
+}
+
+#[test]
+fn signs_past_the_first_prepared_window() {
+    let (pk, sk) = keygen(256);
+    // 2^8 keys start with two bottom trees of 16 epochs prepared
+    for epoch in [0, 31, 32, 200, 255] {
+        let sig = sign(&sk, epoch).unwrap_or_else(|code| panic!("epoch {epoch}: {code}"));
+        let valid = unsafe {
+            hashzig_verify(
+                8,
+                pk.as_ptr(),
+                pk.len(),
+                epoch,
+                MESSAGE.as_ptr(),
+                sig.as_ptr(),
+                sig.len(),
+            )
+        };
+        assert_eq!(valid, 1, "epoch {epoch}");
+    }
+}
+
+#[test]
+fn epochs_outside_the_activation_interval_are_refused() {
+    let (_, sk) = keygen(64);
+    assert_eq!(sign(&sk, 200), Err(HASHZIG_ERR_EPOCH));
+}
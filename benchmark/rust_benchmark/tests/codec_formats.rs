@@ -0,0 +1,181 @@
+//! Round trips through the self-describing codec formats
+//!
+//! MessagePack keys and signatures must carry exactly the structure of the
+//! JSON representation, so consumers can translate between the two without
+//! scheme knowledge. Binary secret keys must sign like the originals.
+
+use rand::{rngs::StdRng, SeedableRng};
+use rust_benchmark::compat::codec;
+use rust_benchmark::compat::pk_info::{self, PublicKeyInfo};
+use rust_benchmark::config::Format;
+use rust_benchmark::lifetime::SchemeMetadata;
+use rust_benchmark::upstream::{
+    SIGTopLevelTargetSumLifetime18Dim64Base8, SIGTopLevelTargetSumLifetime8Dim64Base8 as Scheme,
+    SignatureScheme,
+};
+use serde_json::Value;
+
+const SEED: [u8; 32] = [0x42; 32];
+const MESSAGE: [u8; 32] = *b"codec format round trip message!";
+const EPOCH: u32 = 3;
+
+fn artifacts() -> (
+    <Scheme as SignatureScheme>::PublicKey,
+    <Scheme as SignatureScheme>::Signature,
+) {
+    let mut rng = StdRng::from_seed(SEED);
+    let (pk, sk) = Scheme::key_gen(&mut rng, 0, 256);
+    let sig = Scheme::sign(&sk, EPOCH, &MESSAGE).expect("signing failed");
+    (pk, sig)
+}
+
+#[test]
+fn msgpack_public_key_matches_json() {
+    let (pk, _) = artifacts();
+    let json = codec::encode_public_key::<Scheme>(&pk, Format::Json).unwrap();
+    let msgpack = codec::encode_public_key::<Scheme>(&pk, Format::Msgpack).unwrap();
+
+    let json_value: Value = serde_json::from_slice(&json).unwrap();
+    let msgpack_value: Value = rmp_serde::from_slice(&msgpack).unwrap();
+    assert_eq!(msgpack_value, json_value);
+
+    let decoded = codec::decode_public_key::<Scheme>(&msgpack, Format::Msgpack).unwrap();
+    assert_eq!(
+        codec::encode_public_key::<Scheme>(&decoded, Format::Json).unwrap(),
+        json
+    );
+}
+
+#[test]
+fn msgpack_signature_matches_json() {
+    let (pk, sig) = artifacts();
+    let msgpack = codec::encode_signature::<Scheme>(&sig, Format::Msgpack).unwrap();
+
+    let msgpack_value: Value = rmp_serde::from_slice(&msgpack).unwrap();
+    assert_eq!(msgpack_value, serde_json::to_value(&sig).unwrap());
+
+    let decoded = codec::decode_signature::<Scheme>(&msgpack, Format::Msgpack).unwrap();
+    assert_eq!(
+        serde_json::to_value(&decoded).unwrap(),
+        serde_json::to_value(&sig).unwrap()
+    );
+    assert!(Scheme::verify(&pk, EPOCH, &MESSAGE, &decoded));
+}
+
+#[test]
+fn bincode_secret_key_signs_like_the_original() {
+    let mut rng = StdRng::from_seed(SEED);
+    let (pk, sk) = Scheme::key_gen(&mut rng, 0, 256);
+    let bytes = codec::encode_secret_key::<Scheme>(&sk, Format::Bincode).unwrap();
+    assert_eq!(&bytes[..4], b"HSSK");
+    assert!(
+        bytes.len()
+            < codec::encode_secret_key::<Scheme>(&sk, Format::Json)
+                .unwrap()
+                .len()
+    );
+
+    let decoded = codec::decode_secret_key::<Scheme>(&bytes, Format::Bincode).unwrap();
+    let sig = Scheme::sign(&decoded, EPOCH, &MESSAGE).expect("signing failed");
+    assert!(Scheme::verify(&pk, EPOCH, &MESSAGE, &sig));
+
+    let Err(err) = codec::decode_secret_key::<SIGTopLevelTargetSumLifetime18Dim64Base8>(
+        &bytes,
+        Format::Bincode,
+    ) else {
+        panic!("2^8 key decoded as 2^18");
+    };
+    assert!(
+        err.to_string().contains("lifetime 2^8, expected 2^18"),
+        "{err}"
+    );
+}
+
+#[test]
+fn streamed_secret_keys_match_buffered_decoding() {
+    let mut rng = StdRng::from_seed(SEED);
+    let (pk, sk) = Scheme::key_gen(&mut rng, 0, 256);
+    for format in [
+        Format::Json,
+        Format::Ssz,
+        Format::Cbor,
+        Format::Msgpack,
+        Format::Bincode,
+    ] {
+        let bytes = codec::encode_secret_key::<Scheme>(&sk, format).unwrap();
+        let streamed = codec::read_secret_key::<Scheme, _>(bytes.as_slice(), format).unwrap();
+        assert_eq!(
+            codec::encode_secret_key::<Scheme>(&streamed, format).unwrap(),
+            bytes,
+            "{format}"
+        );
+        let sig = Scheme::sign(&streamed, EPOCH, &MESSAGE).expect("signing failed");
+        assert!(Scheme::verify(&pk, EPOCH, &MESSAGE, &sig), "{format}");
+    }
+}
+
+#[test]
+fn conversions_round_trip_through_every_format() {
+    let (pk, sig) = artifacts();
+    let pk_json = codec::encode_public_key::<Scheme>(&pk, Format::Json).unwrap();
+    let sig_ssz = codec::encode_signature::<Scheme>(&sig, Format::Ssz).unwrap();
+    for format in [
+        Format::Ssz,
+        Format::ZigBinary,
+        Format::Cbor,
+        Format::Msgpack,
+        Format::Json,
+    ] {
+        let converted =
+            codec::convert_public_key::<Scheme>(&pk_json, Format::Json, format).unwrap();
+        assert_eq!(
+            codec::convert_public_key::<Scheme>(&converted, format, Format::Json).unwrap(),
+            pk_json,
+            "{format}"
+        );
+
+        let converted = codec::convert_signature::<Scheme>(&sig_ssz, Format::Ssz, format).unwrap();
+        assert_eq!(
+            codec::convert_signature::<Scheme>(&converted, format, Format::Ssz).unwrap(),
+            sig_ssz,
+            "{format}"
+        );
+    }
+}
+
+#[test]
+fn root_extraction_agrees_across_formats() {
+    let (pk, _) = artifacts();
+    let info = PublicKeyInfo::from_public_key::<Scheme>(&pk).unwrap();
+    for format in [
+        Format::Json,
+        Format::Ssz,
+        Format::ZigBinary,
+        Format::Cbor,
+        Format::Msgpack,
+        Format::Bincode,
+    ] {
+        let bytes = codec::encode_public_key::<Scheme>(&pk, format).unwrap();
+        let root = pk_info::root_from_bytes(&bytes, format, Scheme::METADATA).unwrap();
+        assert_eq!(root, info.root, "{format}");
+        assert_eq!(
+            pk_info::root_fingerprint(&root),
+            info.fingerprint(),
+            "{format}"
+        );
+    }
+
+    for format in [Format::Ssz, Format::ZigBinary] {
+        let mut bytes = codec::encode_public_key::<Scheme>(&pk, format).unwrap();
+        bytes.push(0);
+        assert!(
+            pk_info::root_from_bytes(&bytes, format, Scheme::METADATA).is_err(),
+            "{format}"
+        );
+        bytes.truncate(bytes.len() - 2);
+        assert!(
+            pk_info::root_from_bytes(&bytes, format, Scheme::METADATA).is_err(),
+            "{format}"
+        );
+    }
+}
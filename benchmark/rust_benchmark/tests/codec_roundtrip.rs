@@ -0,0 +1,164 @@
+//! encode → decode is the identity for every codec and lifetime
+//!
+//! Keys and signatures are built from random field elements rather than by
+//! keygen, so a 2^32 case costs no more than a 2^8 one and a failure shrinks
+//! towards all-zero elements. They are well formed, not valid: the codecs
+//! never check a signature against its key. A failure names the lifetime,
+//! the artifact and the format next to proptest's minimal input.
+
+use std::error::Error;
+
+use proptest::prelude::*;
+use rust_benchmark::compat::codec;
+use rust_benchmark::compat::json::{json_bytes, signature_from_json, signature_to_json, JsonStyle};
+use rust_benchmark::config::Format;
+use rust_benchmark::field::KOALABEAR_PRIME;
+use rust_benchmark::lifetime::{LifetimeMetadata, SchemeMetadata};
+use rust_benchmark::upstream::{
+    SIGTopLevelTargetSumLifetime18Dim64Base8, SIGTopLevelTargetSumLifetime32Dim64Base8,
+    SIGTopLevelTargetSumLifetime8Dim64Base8,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{json, Value};
+use ssz::{Decode, Encode};
+
+const FORMATS: [Format; 6] = [
+    Format::Json,
+    Format::Bincode,
+    Format::Ssz,
+    Format::Cbor,
+    Format::Msgpack,
+    Format::ZigBinary,
+];
+
+/// Field elements in a public key and a signature for `meta`.
+fn element_count(meta: LifetimeMetadata) -> usize {
+    meta.hash_len
+        + meta.parameter_len
+        + meta.log_lifetime as usize * meta.hash_len
+        + meta.rand_len
+        + meta.dimension * meta.hash_len
+}
+
+fn field_elements(meta: LifetimeMetadata) -> impl Strategy<Value = Vec<u32>> {
+    prop::collection::vec(0..KOALABEAR_PRIME as u32, element_count(meta))
+}
+
+/// Public key and signature JSON values holding `elements` in order.
+fn artifact_values(elements: &[u32], meta: LifetimeMetadata) -> (Value, Value) {
+    let mut elements = elements.iter().copied();
+    let mut take = |len: usize| -> Vec<u32> { elements.by_ref().take(len).collect() };
+    let pk = json!({
+        "root": take(meta.hash_len),
+        "parameter": take(meta.parameter_len),
+    });
+    let nodes: Vec<_> = (0..meta.log_lifetime)
+        .map(|_| take(meta.hash_len))
+        .collect();
+    let rho = take(meta.rand_len);
+    let hashes: Vec<_> = (0..meta.dimension).map(|_| take(meta.hash_len)).collect();
+    let sig = json!({ "path": { "nodes": nodes }, "rho": rho, "hashes": hashes });
+    (pk, sig)
+}
+
+fn check<T>(result: Result<T, Box<dyn Error>>, what: String) -> Result<T, TestCaseError> {
+    result.map_err(|e| TestCaseError::fail(format!("{what}: {e}")))
+}
+
+fn round_trips<S>(elements: &[u32]) -> Result<(), TestCaseError>
+where
+    S: SchemeMetadata,
+    S::PublicKey: Serialize + DeserializeOwned + Encode + Decode,
+    S::Signature: Serialize + DeserializeOwned + Encode + Decode,
+{
+    let meta = S::METADATA;
+    let tag = S::TAG;
+    let (pk_value, sig_value) = artifact_values(elements, meta);
+    let pk = check(
+        codec::decode_public_key::<S>(&serde_json::to_vec(&pk_value).unwrap(), Format::Json),
+        format!("{tag} public key from JSON"),
+    )?;
+    let sig: S::Signature = check(
+        signature_from_json(sig_value.clone(), meta),
+        format!("{tag} signature from JSON"),
+    )?;
+    let pk_json = |pk: &S::PublicKey, what: String| {
+        check(codec::encode_public_key::<S>(pk, Format::Json), what)
+            .map(|bytes| serde_json::from_slice::<Value>(&bytes).unwrap())
+    };
+    prop_assert_eq!(pk_json(&pk, format!("{tag} public key"))?, pk_value.clone());
+    prop_assert_eq!(
+        check(signature_to_json(&sig, meta), format!("{tag} signature"))?,
+        sig_value.clone()
+    );
+
+    for format in FORMATS {
+        let what = format!("{tag} public key as {format}");
+        let bytes = check(codec::encode_public_key::<S>(&pk, format), what.clone())?;
+        let decoded = check(codec::decode_public_key::<S>(&bytes, format), what.clone())?;
+        prop_assert_eq!(
+            pk_json(&decoded, what.clone())?,
+            pk_value.clone(),
+            "{}",
+            what
+        );
+
+        let what = format!("{tag} signature as {format}");
+        let bytes = check(codec::encode_signature::<S>(&sig, format), what.clone())?;
+        let decoded = check(codec::decode_signature::<S>(&bytes, format), what.clone())?;
+        prop_assert_eq!(
+            check(signature_to_json(&decoded, meta), what.clone())?,
+            sig_value.clone(),
+            "{}",
+            what
+        );
+    }
+
+    // Canonical JSON is a spelling of the same values
+    let what = format!("{tag} public key as canonical JSON");
+    let bytes = check(json_bytes(&pk_value, JsonStyle::Canonical), what.clone())?;
+    let decoded = check(
+        codec::decode_public_key::<S>(&bytes, Format::Json),
+        what.clone(),
+    )?;
+    prop_assert_eq!(pk_json(&decoded, what.clone())?, pk_value, "{}", what);
+
+    let what = format!("{tag} signature as canonical JSON");
+    let bytes = check(json_bytes(&sig_value, JsonStyle::Canonical), what.clone())?;
+    let decoded: S::Signature = check(
+        signature_from_json(serde_json::from_slice(&bytes).unwrap(), meta),
+        what.clone(),
+    )?;
+    prop_assert_eq!(
+        check(signature_to_json(&decoded, meta), what.clone())?,
+        sig_value,
+        "{}",
+        what
+    );
+    Ok(())
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    #[test]
+    fn lifetime_2_8_round_trips(
+        elements in field_elements(SIGTopLevelTargetSumLifetime8Dim64Base8::METADATA)
+    ) {
+        round_trips::<SIGTopLevelTargetSumLifetime8Dim64Base8>(&elements)?;
+    }
+
+    #[test]
+    fn lifetime_2_18_round_trips(
+        elements in field_elements(SIGTopLevelTargetSumLifetime18Dim64Base8::METADATA)
+    ) {
+        round_trips::<SIGTopLevelTargetSumLifetime18Dim64Base8>(&elements)?;
+    }
+
+    #[test]
+    fn lifetime_2_32_round_trips(
+        elements in field_elements(SIGTopLevelTargetSumLifetime32Dim64Base8::METADATA)
+    ) {
+        round_trips::<SIGTopLevelTargetSumLifetime32Dim64Base8>(&elements)?;
+    }
+}
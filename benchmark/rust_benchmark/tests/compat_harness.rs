@@ -0,0 +1,121 @@
+//! Cross-language checks through `CompatHarness`
+//!
+//! `rust_loopback` uses the Rust implementation as its own peer and runs
+//! with a plain `cargo test --test compat_harness`. `zig_cli_peer` drives
+//! `zig-out/bin/cross-lang-zig-tool` the way `benchmark.py` does; build it
+//! with `zig build` and run `cargo test --release --test compat_harness --
+//! --ignored`.
+
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use rand::{rngs::StdRng, SeedableRng};
+use rust_benchmark::compat::codec::{self, message_from_bytes};
+use rust_benchmark::config::{Format, SchemeConfig};
+use rust_benchmark::harness::{CompatHarness, PeerResponse};
+use rust_benchmark::lifetime::LifetimeTag;
+use rust_benchmark::upstream::{
+    SIGTopLevelTargetSumLifetime8Dim64Base8 as Scheme, SignatureScheme,
+};
+
+const SEED: [u8; 32] = [0x42; 32];
+const MESSAGE: &str = "compat harness message";
+const NUM_ACTIVE_EPOCHS: usize = 256;
+
+fn config(format: Format) -> SchemeConfig {
+    SchemeConfig::new()
+        .lifetime(LifetimeTag::Pow8)
+        .seed(SEED)
+        .activation(0, NUM_ACTIVE_EPOCHS)
+        .format(format)
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn rust_loopback() {
+    let message = message_from_bytes(MESSAGE.as_bytes()).unwrap();
+    for format in [Format::Json, Format::Ssz] {
+        let mut harness = CompatHarness::<Scheme>::new(config(format)).unwrap();
+        // Out of order on purpose: epoch 1 after 17 regenerates the key.
+        for epoch in [0u32, 17, 1] {
+            let report = harness
+                .run(epoch, &message, |artifacts| {
+                    let pk = codec::decode_public_key::<Scheme>(&artifacts.public_key, format)?;
+                    let sig = codec::decode_signature::<Scheme>(&artifacts.signature, format)?;
+                    let mut rng = StdRng::from_seed(SEED);
+                    let (peer_pk, peer_sk) = Scheme::key_gen(&mut rng, 0, NUM_ACTIVE_EPOCHS);
+                    let peer_sig = Scheme::sign(&peer_sk, epoch, &artifacts.message)
+                        .map_err(|e| format!("{e:?}"))?;
+                    Ok(PeerResponse {
+                        verified_rust: Scheme::verify(&pk, epoch, &artifacts.message, &sig),
+                        public_key: Some(codec::encode_public_key::<Scheme>(&peer_pk, format)?),
+                        signature: Some(codec::encode_signature::<Scheme>(&peer_sig, format)?),
+                    })
+                })
+                .unwrap();
+            assert!(report.is_consistent(), "{format} epoch {epoch}: {report:?}");
+        }
+    }
+}
+
+fn repo_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("../..")
+}
+
+fn run_zig(args: &[&str]) -> Result<String, Box<dyn Error>> {
+    let root = repo_root();
+    let output = Command::new(root.join("zig-out/bin/cross-lang-zig-tool"))
+        .args(args)
+        .current_dir(&root)
+        .output()?;
+    let text = format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    if !output.status.success() {
+        return Err(format!("cross-lang-zig-tool {args:?} failed:\n{text}").into());
+    }
+    Ok(text)
+}
+
+#[test]
+#[ignore = "needs `zig build` output in zig-out/bin"]
+fn zig_cli_peer() {
+    let message = message_from_bytes(MESSAGE.as_bytes()).unwrap();
+    let tmp = repo_root().join("tmp");
+    let seed_hex = hex::encode(SEED);
+    let mut harness = CompatHarness::<Scheme>::new(config(Format::Ssz)).unwrap();
+    for epoch in [0u32, 1, 17] {
+        let report = harness
+            .run(epoch, &message, |artifacts| {
+                let (pk_path, sig_path) = artifacts.write_to(&tmp)?;
+                fs::write(tmp.join("zig_lifetime.txt"), "2^8")?;
+                fs::write(
+                    tmp.join("zig_active_epochs.txt"),
+                    NUM_ACTIVE_EPOCHS.to_string(),
+                )?;
+                let epoch = epoch.to_string();
+                let verify = run_zig(&[
+                    "verify",
+                    sig_path.to_str().unwrap(),
+                    pk_path.to_str().unwrap(),
+                    MESSAGE,
+                    &epoch,
+                    "--ssz",
+                ]);
+                run_zig(&["keygen", &seed_hex, "2^8", "--ssz"])?;
+                run_zig(&["sign", MESSAGE, &epoch, "--ssz"])?;
+                Ok(PeerResponse {
+                    verified_rust: verify.is_ok_and(|out| out.contains("✅")),
+                    public_key: Some(fs::read(tmp.join("zig_pk.ssz"))?),
+                    signature: Some(fs::read(tmp.join("zig_sig.ssz"))?),
+                })
+            })
+            .unwrap();
+        assert!(report.is_consistent(), "epoch {epoch}: {report:?}");
+    }
+}
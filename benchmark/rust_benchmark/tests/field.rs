@@ -0,0 +1,150 @@
+//! Montgomery conversion: the round trip is the identity, bulk conversion
+//! agrees with the one-element functions, and both agree with KoalaBear
+//! arithmetic in `p3_koala_bear`
+
+use p3_field::{Field, PrimeCharacteristicRing, PrimeField32};
+use p3_koala_bear::KoalaBear;
+use proptest::prelude::*;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rust_benchmark::field::{
+    canonical_to_montgomery, canonical_to_montgomery_slice, montgomery_to_canonical,
+    montgomery_to_canonical_slice, KOALABEAR_PRIME,
+};
+
+#[test]
+fn slices_match_element_by_element() {
+    let prime = KOALABEAR_PRIME as u32;
+    let mut rng = StdRng::from_seed([3; 32]);
+    let mut canonical: Vec<u32> = vec![0, 1, 2, prime - 2, prime - 1];
+    canonical.extend((0..10_000).map(|_| rng.random_range(0..prime)));
+
+    let mut montgomery = canonical.clone();
+    canonical_to_montgomery_slice(&mut montgomery);
+    let expected: Vec<u32> = canonical
+        .iter()
+        .map(|&x| canonical_to_montgomery(x))
+        .collect();
+    assert_eq!(montgomery, expected);
+
+    montgomery_to_canonical_slice(&mut montgomery);
+    assert_eq!(montgomery, canonical);
+
+    // Raw words of a corrupt file are below 2^32, not below the prime
+    let mut raw: Vec<u32> = vec![prime, u32::MAX];
+    raw.extend((0..1000).map(|_| rng.random::<u32>()));
+    let expected: Vec<u32> = raw.iter().map(|&x| montgomery_to_canonical(x)).collect();
+    montgomery_to_canonical_slice(&mut raw);
+    assert_eq!(raw, expected);
+}
+
+const PRIME: u32 = KOALABEAR_PRIME as u32;
+
+/// Canonical values next to the edges of the field and of the word.
+const EDGES: [u32; 9] = [
+    0,
+    1,
+    2,
+    (1 << 24) - 1,
+    1 << 24,
+    (1 << 24) + 1,
+    PRIME / 2,
+    PRIME - 2,
+    PRIME - 1,
+];
+
+/// R = 2^32 as a field element; `x` in Montgomery form is `x * R`.
+fn monty_r() -> KoalaBear {
+    KoalaBear::from_u64(1 << 32)
+}
+
+/// A canonical value, drawn near the edges as often as anywhere else.
+fn residue() -> impl Strategy<Value = u32> {
+    prop_oneof![
+        0..PRIME,
+        0..64u32,
+        PRIME - 64..PRIME,
+        (1u32 << 24) - 32..(1 << 24) + 32,
+    ]
+}
+
+proptest! {
+    #[test]
+    fn canonical_round_trips(x in residue()) {
+        let montgomery = canonical_to_montgomery(x);
+        prop_assert!(montgomery < PRIME);
+        prop_assert_eq!(montgomery_to_canonical(montgomery), x);
+    }
+
+    #[test]
+    fn montgomery_round_trips(m in residue()) {
+        let canonical = montgomery_to_canonical(m);
+        prop_assert!(canonical < PRIME);
+        prop_assert_eq!(canonical_to_montgomery(canonical), m);
+    }
+
+    #[test]
+    fn to_montgomery_agrees_with_koala_bear(x in residue()) {
+        let expected = (KoalaBear::from_u32(x) * monty_r()).as_canonical_u32();
+        prop_assert_eq!(canonical_to_montgomery(x), expected);
+        let mut slice = [x];
+        canonical_to_montgomery_slice(&mut slice);
+        prop_assert_eq!(slice[0], expected);
+    }
+
+    /// Any word, as read from a file, decodes to `(m mod p) / R`.
+    #[test]
+    fn from_montgomery_agrees_with_koala_bear(m in any::<u32>()) {
+        let expected = (KoalaBear::from_u32(m) * monty_r().inverse()).as_canonical_u32();
+        prop_assert_eq!(montgomery_to_canonical(m), expected);
+        let mut slice = [m];
+        montgomery_to_canonical_slice(&mut slice);
+        prop_assert_eq!(slice[0], expected);
+    }
+}
+
+#[test]
+fn edge_values_round_trip() {
+    for x in EDGES {
+        let montgomery = canonical_to_montgomery(x);
+        assert_eq!(
+            montgomery,
+            (KoalaBear::from_u32(x) * monty_r()).as_canonical_u32(),
+            "{x:#x}"
+        );
+        assert_eq!(montgomery_to_canonical(montgomery), x, "{x:#x}");
+        assert_eq!(
+            canonical_to_montgomery(montgomery_to_canonical(x)),
+            x,
+            "{x:#x}"
+        );
+    }
+    // 1 in Montgomery form is R mod p, and R mod p is 2^32 - 2p
+    assert_eq!(canonical_to_montgomery(1), u32::MAX - 2 * PRIME + 1);
+    for raw in [PRIME, PRIME + 1, u32::MAX - 1, u32::MAX] {
+        assert_eq!(
+            montgomery_to_canonical(raw),
+            montgomery_to_canonical(raw - PRIME),
+            "{raw:#x}"
+        );
+    }
+}
+
+/// Every residue, in bulk. Takes a while in a debug build:
+/// `cargo test --release --test field -- --ignored`
+#[test]
+#[ignore]
+fn every_residue_round_trips() {
+    const CHUNK: u32 = 1 << 20;
+    let mut values = Vec::with_capacity(CHUNK as usize);
+    for start in (0..PRIME).step_by(CHUNK as usize) {
+        values.clear();
+        values.extend(start..PRIME.min(start + CHUNK));
+        canonical_to_montgomery_slice(&mut values);
+        assert!(values.iter().all(|&m| m < PRIME), "from {start:#x}");
+        montgomery_to_canonical_slice(&mut values);
+        assert!(
+            values.iter().copied().eq(start..PRIME.min(start + CHUNK)),
+            "from {start:#x}"
+        );
+    }
+}
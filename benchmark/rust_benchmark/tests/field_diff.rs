@@ -0,0 +1,41 @@
+//! Leaf naming and element forms of `compat::field_diff`
+
+use serde_json::json;
+
+use rust_benchmark::compat::field_diff::{diff_values, ElementForm, FieldDiff};
+use rust_benchmark::field::canonical_to_montgomery;
+
+#[test]
+fn differing_leaves_are_named_by_field_and_index() {
+    let left = json!({ "root": [1, 2, 3], "path": { "co_path": [[1, 2], [3, 4]] }, "rho": [9] });
+    let right =
+        json!({ "root": [1, 5, 3], "path": { "co_path": [[1, 2], [3, 7], [8]] }, "rho": [9] });
+    let diffs = diff_values(&left, &right);
+    let mut locations: Vec<&str> = diffs.iter().map(|d| d.location.as_str()).collect();
+    locations.sort_unstable();
+    assert_eq!(
+        locations,
+        ["path.co_path[1][1]", "path.co_path[2]", "root[1]"]
+    );
+    let appended = diffs.iter().find(|d| d.location == "path.co_path[2]");
+    assert_eq!(appended.unwrap().left, serde_json::Value::Null);
+    assert!(diff_values(&left, &left).is_empty());
+}
+
+#[test]
+fn elements_are_shown_in_the_requested_form() {
+    let diff = FieldDiff {
+        location: "root[0]".into(),
+        left: json!(1),
+        right: json!(2),
+    };
+    assert_eq!(diff.to_string(), "root[0]: 1 != 2");
+    assert_eq!(
+        diff.describe(ElementForm::Montgomery),
+        format!(
+            "root[0]: 0x{:08x} != 0x{:08x}",
+            canonical_to_montgomery(1),
+            canonical_to_montgomery(2)
+        )
+    );
+}
@@ -0,0 +1,59 @@
+//! Wire-format timing table
+
+use std::time::Duration;
+
+use rust_benchmark::config::Format;
+use rust_benchmark::format_bench::{self, FormatReport, FormatTiming};
+use rust_benchmark::upstream::SIGTopLevelTargetSumLifetime8Dim64Base8;
+
+#[test]
+fn every_artifact_is_timed_in_every_format_it_has() {
+    let report =
+        format_bench::measure::<SIGTopLevelTargetSumLifetime8Dim64Base8>([3u8; 32], 1).unwrap();
+    let rows: Vec<(&str, Format)> = report
+        .rows
+        .iter()
+        .map(|row| (row.artifact, row.format))
+        .collect();
+    assert_eq!(
+        rows,
+        [
+            ("pk", Format::Json),
+            ("pk", Format::Bincode),
+            ("pk", Format::Ssz),
+            ("pk", Format::ZigBinary),
+            ("sig", Format::Json),
+            ("sig", Format::Bincode),
+            ("sig", Format::Ssz),
+            ("sig", Format::ZigBinary),
+            ("sk", Format::Json),
+            ("sk", Format::Bincode),
+            ("sk", Format::Ssz),
+        ]
+    );
+    assert!(report.rows.iter().all(|row| row.bytes > 0));
+    // The leanSignature wire layout
+    assert_eq!(report.rows[4].bytes, 3116);
+}
+
+#[test]
+fn table_and_json_list_each_row() {
+    let report = FormatReport {
+        iterations: 10,
+        rows: vec![FormatTiming {
+            artifact: "sig",
+            format: Format::Ssz,
+            bytes: 3112,
+            encode: Duration::from_micros(12),
+            decode: Duration::from_micros(30),
+        }],
+    };
+    let table = report.table();
+    assert_eq!(table.lines().count(), 2);
+    assert!(table.lines().nth(1).unwrap().starts_with("sig      ssz"));
+
+    let json = report.to_json();
+    assert_eq!(json["iterations"], 10);
+    assert_eq!(json["rows"][0]["format"], "ssz");
+    assert_eq!(json["rows"][0]["bytes"], 3112);
+}
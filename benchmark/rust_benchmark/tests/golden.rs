@@ -0,0 +1,96 @@
+//! Committed golden fixtures, re-derived and byte-compared
+//!
+//! `tests/golden/<lifetime>/` holds a `gen-kat` bundle for the seeds in
+//! `tests/golden/seeds.txt`: every key and signature encoding plus the
+//! manifest with fingerprints and hash tree roots. Each test re-runs keygen
+//! and signing from the recorded seeds, so a change to how much randomness
+//! keygen draws, in what order, or to any encoding fails here with the first
+//! differing byte. When such a change is intended, regenerate the fixtures
+//! with `HASHZIG_BLESS=1 cargo test --release --test golden -- --include-ignored`
+//! and commit them with it. A bless run only writes; checking the new
+//! fixtures is the next run's job, so a bless never passes itself.
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use rust_benchmark::kat::{self, MessageEncoding};
+use rust_benchmark::upstream::{
+    SIGTopLevelTargetSumLifetime18Dim64Base8, SIGTopLevelTargetSumLifetime32Dim64Base8,
+    SIGTopLevelTargetSumLifetime8Dim64Base8,
+};
+use serde::Serialize;
+use ssz::Encode;
+
+const EPOCHS: [u32; 2] = [0, 3];
+const BLESS: &str = "HASHZIG_BLESS";
+
+fn golden_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden")
+}
+
+fn matches_golden<S>()
+where
+    S: MessageEncoding,
+    S::PublicKey: Serialize + Encode,
+    S::Signature: Serialize + Encode,
+{
+    let seeds =
+        kat::parse_seeds(&fs::read_to_string(golden_dir().join("seeds.txt")).unwrap()).unwrap();
+    let dir = golden_dir().join(S::TAG.as_str().replace('^', "_"));
+    if env::var_os(BLESS).is_some() {
+        let _ = fs::remove_dir_all(&dir);
+        kat::generate_bundle::<S>(&seeds, &EPOCHS, &dir).unwrap();
+        eprintln!(
+            "blessed {}; run again without {BLESS} to check it",
+            dir.display()
+        );
+        return;
+    }
+
+    let manifest = kat::read_manifest(&dir).unwrap_or_else(|e| {
+        panic!(
+            "no golden fixtures for {} ({e}); generate them with `{BLESS}=1 cargo test --release --test golden -- --include-ignored`",
+            S::TAG
+        )
+    });
+    assert_eq!(kat::manifest_lifetime(&manifest).unwrap(), S::TAG);
+    let recorded: Vec<_> = manifest["vectors"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|vector| vector["seed"].as_str().unwrap().to_owned())
+        .collect();
+    assert_eq!(
+        recorded,
+        seeds.iter().map(hex::encode).collect::<Vec<_>>(),
+        "fixtures are for other seeds than seeds.txt; bless them again"
+    );
+
+    let check = kat::check_bundle::<S>(&dir, &manifest).unwrap();
+    assert!(
+        check.passed(),
+        "{} of {} golden files for {} changed:\n{}",
+        check.mismatches.len(),
+        check.files,
+        S::TAG,
+        check.report()
+    );
+}
+
+#[test]
+fn lifetime_2_8_matches_golden() {
+    matches_golden::<SIGTopLevelTargetSumLifetime8Dim64Base8>();
+}
+
+#[test]
+fn lifetime_2_18_matches_golden() {
+    matches_golden::<SIGTopLevelTargetSumLifetime18Dim64Base8>();
+}
+
+/// Two bottom trees of 65536 leaves each: minutes without `--release`.
+#[test]
+#[ignore = "2^32 keygen; run with --release -- --include-ignored"]
+fn lifetime_2_32_matches_golden() {
+    matches_golden::<SIGTopLevelTargetSumLifetime32Dim64Base8>();
+}
@@ -0,0 +1,49 @@
+//! Zig-style hex field elements in JSON artifacts
+
+use rust_benchmark::compat::json::{
+    from_hex_field_elements, json_bytes, to_hex_field_elements, JsonStyle,
+};
+use rust_benchmark::field::canonical_to_montgomery;
+use serde_json::{json, Value};
+
+#[test]
+fn writes_montgomery_hex_strings() {
+    let value = json!({ "root": [0, 1], "parameter": [2130706432] });
+    let hex: Value = serde_json::from_slice(&json_bytes(&value, JsonStyle::Hex).unwrap()).unwrap();
+    assert_eq!(
+        hex,
+        json!({
+            "root": ["0x0", format!("0x{:x}", canonical_to_montgomery(1))],
+            "parameter": [format!("0x{:x}", canonical_to_montgomery(2130706432))],
+        })
+    );
+}
+
+#[test]
+fn hex_round_trips_to_canonical() {
+    let value = json!({
+        "path": { "nodes": [[5, 6], [7, 8]] },
+        "rho": [1, 2, 3],
+        "hashes": [[123456789, 42]],
+    });
+    let mut converted = value.clone();
+    to_hex_field_elements(&mut converted);
+    assert_ne!(converted, value);
+    from_hex_field_elements(&mut converted).unwrap();
+    assert_eq!(converted, value);
+}
+
+#[test]
+fn reads_mixed_and_uppercase_prefixes() {
+    let mut value = json!({ "root": [9, "0X0", "0xff"] });
+    from_hex_field_elements(&mut value).unwrap();
+    assert_eq!(value["root"][0], 9);
+    assert_eq!(value["root"][1], 0);
+    assert!(value["root"][2].is_u64());
+}
+
+#[test]
+fn rejects_malformed_hex() {
+    assert!(from_hex_field_elements(&mut json!(["0xnothex"])).is_err());
+    assert!(from_hex_field_elements(&mut json!(["0x100000000"])).is_err());
+}
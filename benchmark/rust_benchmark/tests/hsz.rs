@@ -0,0 +1,70 @@
+//! `.hsz` containers through the file helpers and the key store
+
+use std::fs;
+use std::path::PathBuf;
+
+use rust_benchmark::compat::hsz;
+use rust_benchmark::config::Format;
+use rust_benchmark::keystore::{FileKeyStore, KeyMetadata, SecretKeyStore, StoredKey};
+use rust_benchmark::lifetime::LifetimeTag;
+
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("hsz-test-{}-{name}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn files_round_trip_and_plain_files_pass_through() {
+    let dir = scratch_dir("files");
+    let data = b"{\"root\":[1,2,3]}".repeat(1000);
+
+    let compressed = dir.join("key.json.hsz");
+    hsz::write(&compressed, &data).unwrap();
+    let raw = fs::read(&compressed).unwrap();
+    assert!(hsz::is_compressed(&raw));
+    assert!(raw.len() < data.len() / 10);
+    assert_eq!(hsz::read(&compressed).unwrap(), data);
+    assert_eq!(hsz::decompress(&raw).unwrap(), data);
+
+    let plain = dir.join("key.json");
+    hsz::write(&plain, &data).unwrap();
+    assert_eq!(fs::read(&plain).unwrap(), data);
+    assert_eq!(hsz::read(&plain).unwrap(), data);
+}
+
+#[test]
+fn key_format_looks_through_hsz() {
+    assert_eq!(Format::from_key_path("tmp/rust_sk.ssz.hsz"), Format::Ssz);
+    assert_eq!(
+        Format::from_key_path("tmp/rust_sk.bincode.hsz"),
+        Format::Bincode
+    );
+    assert_eq!(Format::from_key_path("tmp/rust_pk.json.hsz"), Format::Json);
+}
+
+#[test]
+fn compressed_store_is_readable_by_a_plain_store() {
+    let dir = scratch_dir("store");
+    let key = StoredKey {
+        metadata: KeyMetadata {
+            lifetime: LifetimeTag::Pow18,
+            format: Format::Ssz,
+        },
+        secret_key: vec![7; 4096],
+        public_key: vec![9; 52],
+    };
+    let compressed = FileKeyStore::new(&dir).compressed(true);
+    compressed.save("rust", &key).unwrap();
+    assert!(dir.join("rust_sk.ssz.hsz").exists());
+    assert!(!dir.join("rust_sk.ssz").exists());
+
+    let plain = FileKeyStore::new(&dir);
+    assert_eq!(plain.load("rust").unwrap(), key);
+    assert_eq!(plain.list().unwrap(), vec!["rust".to_string()]);
+    assert_eq!(
+        plain.stored_secret_key_path("rust").unwrap(),
+        dir.join("rust_sk.ssz.hsz")
+    );
+}
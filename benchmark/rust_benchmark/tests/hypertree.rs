@@ -0,0 +1,57 @@
+//! Activation windows planned by `hypertree::plan`
+
+use rand::{rngs::StdRng, SeedableRng};
+use rust_benchmark::hypertree::{self, Epochs};
+use rust_benchmark::lifetime::LifetimeTag;
+use rust_benchmark::signer::prepare_epoch;
+use rust_benchmark::upstream::{SIGTopLevelTargetSumLifetime8Dim64Base8, SignatureScheme};
+
+#[test]
+fn windows_are_whole_bottom_trees_around_the_epochs() {
+    // 2^8: trees of 16 epochs, at least two of them
+    let plan = hypertree::plan(LifetimeTag::Pow8, &[200..201]).unwrap();
+    assert_eq!(plan.activation, 192..224);
+    assert_eq!(plan.bottom_trees(), 2);
+    // The last tree has no successor, so the window reaches back instead
+    let plan = hypertree::plan(LifetimeTag::Pow8, &[255..256]).unwrap();
+    assert_eq!(plan.activation, 224..256);
+
+    // 2^18: trees of 512; spans far apart keep every tree between them
+    let plan = hypertree::plan(LifetimeTag::Pow18, &[5..6, 1000..2000]).unwrap();
+    assert_eq!(plan.activation, 0..2048);
+    assert_eq!(plan.bottom_trees(), 4);
+
+    // 2^32: trees of 65536, two of them for one far epoch
+    let plan = hypertree::plan(LifetimeTag::Pow32, &[3_000_000_000..3_000_000_001]).unwrap();
+    assert_eq!(plan.activation, 2_999_975_936..3_000_107_008);
+    assert_eq!(plan.num_active_epochs(), 2 * 65536);
+    assert_eq!(plan.to_json()["bottom_tree_len"], 65536);
+
+    assert!(hypertree::plan(LifetimeTag::Pow8, &[256..257]).is_err());
+    assert!(hypertree::plan(LifetimeTag::Pow8, &[]).is_err());
+}
+
+#[test]
+fn epoch_lists_take_epochs_and_ranges() {
+    assert_eq!(
+        Epochs::parse("5, 9,1000..2000").unwrap(),
+        Epochs(vec![5..6, 9..10, 1000..2000])
+    );
+    assert!(Epochs::parse("").is_err());
+    assert!(Epochs::parse("7..7").is_err());
+    assert!(Epochs::parse("x").is_err());
+}
+
+#[test]
+fn a_planned_key_signs_and_verifies_its_epoch() {
+    type Scheme = SIGTopLevelTargetSumLifetime8Dim64Base8;
+    let epoch = 200u32;
+    let plan =
+        hypertree::plan(LifetimeTag::Pow8, &[u64::from(epoch)..u64::from(epoch) + 1]).unwrap();
+    let mut rng = StdRng::from_seed([0x42; 32]);
+    let (pk, mut sk) = Scheme::key_gen(&mut rng, plan.activation_epoch(), plan.num_active_epochs());
+    assert!(prepare_epoch(&mut sk, u64::from(epoch)));
+    let message = [7u8; 32];
+    let signature = Scheme::sign(&sk, epoch, &message).unwrap();
+    assert!(Scheme::verify(&pk, epoch, &message, &signature));
+}
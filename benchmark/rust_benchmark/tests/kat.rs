@@ -0,0 +1,124 @@
+//! Known-answer test bundles
+
+use rust_benchmark::compat::codec;
+use rust_benchmark::compat::pk_info::PublicKeyInfo;
+use rust_benchmark::config::Format;
+use rust_benchmark::kat;
+use rust_benchmark::upstream::{
+    SIGTopLevelTargetSumLifetime8Dim64Base8 as Scheme, SignatureScheme,
+};
+use sha3::{Digest, Sha3_256};
+
+#[test]
+fn parses_seeds_and_epochs() {
+    let text = format!(
+        "# fixtures\n{}\n\n{}  # second\n",
+        "00".repeat(32),
+        "ab".repeat(32)
+    );
+    assert_eq!(
+        kat::parse_seeds(&text).unwrap(),
+        vec![[0u8; 32], [0xab; 32]]
+    );
+    assert!(kat::parse_seeds("abcd\n").is_err());
+    assert!(kat::parse_seeds("# nothing\n").is_err());
+
+    assert_eq!(kat::parse_epochs("255, 0,1,0").unwrap(), vec![0, 1, 255]);
+    assert!(kat::parse_epochs("0,x").is_err());
+}
+
+#[test]
+fn bundle_files_match_the_manifest() {
+    let out = std::env::temp_dir().join(format!("hash-zig-kat-{}", std::process::id()));
+    let seed = [0x5a; 32];
+    let epochs = [0, 3];
+    let manifest_path = kat::generate_bundle::<Scheme>(&[seed], &epochs, &out).unwrap();
+    let manifest: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(manifest_path).unwrap()).unwrap();
+    let vector = &manifest["vectors"][0];
+    assert_eq!(vector["seed"], hex::encode(seed));
+
+    // Every listed file exists with the recorded digest
+    let read = |entry: &serde_json::Value| {
+        let bytes = std::fs::read(out.join(entry["file"].as_str().unwrap())).unwrap();
+        assert_eq!(entry["sha3_256"], hex::encode(Sha3_256::digest(&bytes)));
+        bytes
+    };
+
+    let pk_bytes = read(&vector["public_key"]["files"]["ssz"]);
+    let pk = codec::decode_public_key::<Scheme>(&pk_bytes, Format::Ssz).unwrap();
+    let info = PublicKeyInfo::from_public_key::<Scheme>(&pk).unwrap();
+    assert_eq!(vector["public_key"]["fingerprint"], info.fingerprint_hex());
+
+    let signatures = vector["signatures"].as_array().unwrap();
+    assert_eq!(signatures.len(), epochs.len());
+    for (case, epoch) in signatures.iter().zip(epochs) {
+        let message = kat::kat_message(&seed, epoch);
+        assert_eq!(case["message"], hex::encode(message));
+        let chunks: Vec<u64> = case["chunks"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|chunk| chunk.as_u64().unwrap())
+            .collect();
+        assert_eq!(chunks.len(), 64);
+        assert!(chunks.iter().all(|&chunk| chunk < 8));
+        assert_eq!(case["chunk_sum"], chunks.iter().sum::<u64>());
+        for (name, format) in [("json", Format::Json), ("bin", Format::ZigBinary)] {
+            let sig =
+                codec::decode_signature::<Scheme>(&read(&case["files"][name]), format).unwrap();
+            assert!(Scheme::verify(&pk, epoch, &message, &sig));
+        }
+    }
+    std::fs::remove_dir_all(&out).unwrap();
+}
+
+#[test]
+fn check_reports_tampered_files() {
+    let out = std::env::temp_dir().join(format!("hash-zig-kat-check-{}", std::process::id()));
+    kat::generate_bundle::<Scheme>(&[[0x11; 32]], &[2], &out).unwrap();
+    let manifest = kat::read_manifest(&out).unwrap();
+    assert_eq!(
+        kat::manifest_lifetime(&manifest).unwrap(),
+        rust_benchmark::lifetime::LifetimeTag::Pow8
+    );
+    let check = kat::check_bundle::<Scheme>(&out, &manifest).unwrap();
+    assert!(check.passed(), "{}", check.report());
+    assert_eq!(check.files, 2 * 5);
+
+    let sig = out.join(
+        manifest["vectors"][0]["signatures"][0]["files"]["ssz"]["file"]
+            .as_str()
+            .unwrap(),
+    );
+    let mut bytes = std::fs::read(&sig).unwrap();
+    bytes[10] ^= 1;
+    std::fs::write(&sig, &bytes).unwrap();
+    let mut manifest = manifest;
+    manifest["vectors"][0]["public_key"]["fingerprint"] = "00".into();
+    let chunk = &mut manifest["vectors"][0]["signatures"][0]["chunks"][5];
+    *chunk = (chunk.as_u64().unwrap() ^ 1).into();
+
+    let check = kat::check_bundle::<Scheme>(&out, &manifest).unwrap();
+    let locations: Vec<_> = check
+        .mismatches
+        .iter()
+        .map(|m| m.location.as_str())
+        .collect();
+    assert_eq!(
+        locations,
+        [
+            "manifest.json/vectors/0/public_key/fingerprint",
+            "manifest.json/vectors/0/signatures/0/chunks/5",
+            "1111111111111111/sig_2.ssz"
+        ]
+    );
+    assert!(check.mismatches[2].detail.starts_with("byte 10 "));
+    let json = check.to_json();
+    assert_eq!(json["passed"], false);
+    assert_eq!(
+        json["mismatches"][2]["location"],
+        "1111111111111111/sig_2.ssz"
+    );
+    std::fs::remove_dir_all(&out).unwrap();
+}
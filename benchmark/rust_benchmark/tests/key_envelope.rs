@@ -0,0 +1,132 @@
+//! Scheme identity carried by the secret keys the key store writes
+
+use std::fs;
+use std::path::PathBuf;
+
+use rust_benchmark::compat::key_envelope::{self, KeyEnvelope, HEADER_LEN};
+use rust_benchmark::config::Format;
+use rust_benchmark::keystore::{FileKeyStore, KeyMetadata, SecretKeyStore, StoredKey};
+use rust_benchmark::lifetime::{LifetimeError, LifetimeTag};
+
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("envelope-test-{}-{name}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn stored_key(lifetime: LifetimeTag, format: Format) -> StoredKey {
+    StoredKey {
+        metadata: KeyMetadata { lifetime, format },
+        secret_key: vec![7; 256],
+        public_key: vec![9; 52],
+    }
+}
+
+#[test]
+fn header_round_trips_and_bare_keys_pass_through() {
+    for lifetime in LifetimeTag::ALL {
+        let envelope = KeyEnvelope::new(lifetime, Format::Bincode).unwrap();
+        assert_eq!(envelope.dimension, 64);
+        assert_eq!(envelope.base, 8);
+        let wrapped = key_envelope::wrap(&envelope, b"key");
+        assert_eq!(wrapped.len(), HEADER_LEN + 3);
+        assert_eq!(
+            key_envelope::unwrap(&wrapped).unwrap(),
+            (Some(envelope), &b"key"[..])
+        );
+    }
+    assert_eq!(
+        key_envelope::unwrap(b"{\"json\":1}").unwrap(),
+        (None, &b"{\"json\":1}"[..])
+    );
+    assert!(KeyEnvelope::new(LifetimeTag::Pow8, Format::ZigBinary).is_err());
+}
+
+#[test]
+fn mismatched_scheme_parameters_are_rejected() {
+    let envelope = KeyEnvelope::new(LifetimeTag::Pow18, Format::Ssz).unwrap();
+    let mut header = envelope.to_header();
+    header[8] = 32;
+    let error = KeyEnvelope::from_header(&header).unwrap_err();
+    assert!(error.is::<LifetimeError>(), "{error}");
+    header = envelope.to_header();
+    header[5] = 20;
+    assert!(KeyEnvelope::from_header(&header)
+        .unwrap_err()
+        .is::<LifetimeError>());
+    let error = envelope.check_lifetime(LifetimeTag::Pow8).unwrap_err();
+    assert!(error.is::<LifetimeError>());
+}
+
+#[test]
+fn store_envelopes_secret_keys_only() {
+    let dir = scratch_dir("store");
+    let store = FileKeyStore::new(&dir);
+    let key = stored_key(LifetimeTag::Pow18, Format::Ssz);
+    store.save("rust", &key).unwrap();
+
+    let (envelope, secret_key) = key_envelope::read(dir.join("rust_sk.ssz")).unwrap();
+    assert_eq!(
+        envelope,
+        Some(KeyEnvelope::new(LifetimeTag::Pow18, Format::Ssz).unwrap())
+    );
+    assert_eq!(secret_key, key.secret_key);
+    assert_eq!(fs::read(dir.join("rust_pk.ssz")).unwrap(), key.public_key);
+    assert!(!dir.join("rust_lifetime.txt").exists());
+    assert_eq!(store.load("rust").unwrap(), key);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn a_copied_key_keeps_its_own_lifetime() {
+    let dir = scratch_dir("copy");
+    let store = FileKeyStore::new(&dir);
+    store
+        .save("rust", &stored_key(LifetimeTag::Pow8, Format::Ssz))
+        .unwrap();
+
+    let other = scratch_dir("copy-source");
+    FileKeyStore::new(&other)
+        .save("rust", &stored_key(LifetimeTag::Pow32, Format::Ssz))
+        .unwrap();
+    fs::copy(other.join("rust_sk.ssz"), dir.join("rust_sk.ssz")).unwrap();
+
+    assert_eq!(store.metadata("rust").unwrap().lifetime, LifetimeTag::Pow32);
+    fs::remove_dir_all(&dir).unwrap();
+    fs::remove_dir_all(&other).unwrap();
+}
+
+#[test]
+fn keys_without_an_envelope_use_the_recorded_metadata() {
+    let dir = scratch_dir("legacy");
+    fs::write(dir.join("rust_sk.json"), b"{}").unwrap();
+    fs::write(dir.join("rust_lifetime.txt"), b"2^18").unwrap();
+    let store = FileKeyStore::new(&dir);
+    assert_eq!(
+        store.metadata("rust").unwrap(),
+        KeyMetadata {
+            lifetime: LifetimeTag::Pow18,
+            format: Format::Json,
+        }
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn replacing_a_secret_key_keeps_its_envelope_and_public_key() {
+    let dir = scratch_dir("replace");
+    let store = FileKeyStore::new(&dir).compressed(true);
+    let key = stored_key(LifetimeTag::Pow8, Format::Bincode);
+    store.save("rust", &key).unwrap();
+    store
+        .replace_secret_key("rust", key.metadata, &[1, 2, 3])
+        .unwrap();
+
+    let loaded = FileKeyStore::new(&dir).load("rust").unwrap();
+    assert_eq!(loaded.metadata, key.metadata);
+    assert_eq!(loaded.secret_key, vec![1, 2, 3]);
+    assert_eq!(loaded.public_key, key.public_key);
+    assert!(!dir.join("rust_sk.bincode.hsz.partial").exists());
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,18 @@
+//! Peak resident memory read from `/proc/<pid>/status`
+
+use rust_benchmark::memory;
+
+#[test]
+fn vm_hwm_is_read_in_bytes() {
+    let status =
+        "Name:\thashzig-tool\nVmPeak:\t  900000 kB\nVmHWM:\t  123456 kB\nVmRSS:\t  100000 kB\n";
+    assert_eq!(memory::parse_vm_hwm(status), Some(123456 * 1024));
+    assert_eq!(memory::parse_vm_hwm("Name:\tx\n"), None);
+    assert_eq!(memory::describe(3 * 1024 * 1024 / 2), "1.5 MiB");
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn this_process_has_a_peak() {
+    assert!(memory::peak_rss_bytes().unwrap() > 0);
+}
@@ -0,0 +1,61 @@
+//! The pipeline over many seeds, with its determinism check
+
+use std::time::Duration;
+
+use rust_benchmark::multi_seed::{self, MultiSeedReport, PipelineRun, SeedResult};
+use rust_benchmark::upstream::SIGTopLevelTargetSumLifetime8Dim64Base8;
+
+#[test]
+fn every_seed_gives_one_key_per_run() {
+    let seeds = [[1u8; 32], [2u8; 32]];
+    let report = multi_seed::run::<SIGTopLevelTargetSumLifetime8Dim64Base8>(&seeds, 2, 2).unwrap();
+    assert_eq!(report.concurrency, 2);
+    assert_eq!(report.seeds.len(), 2);
+    assert!(report.seeds.iter().all(|seed| seed.runs.len() == 2));
+    assert_eq!(report.nondeterministic().count(), 0);
+    assert_eq!(report.failures(), 0);
+    // Different seeds, different keys
+    assert_ne!(
+        report.seeds[0].fingerprints(),
+        report.seeds[1].fingerprints()
+    );
+
+    let json = report.to_json();
+    assert_eq!(
+        json["metrics"]["keygen"]["samples_ms"]
+            .as_array()
+            .unwrap()
+            .len(),
+        4
+    );
+    assert_eq!(json["seeds"][0]["deterministic"], true);
+}
+
+#[test]
+fn a_changing_key_is_flagged() {
+    let run = |fingerprint: &str| PipelineRun {
+        keygen: Duration::from_millis(10),
+        sign: Duration::from_millis(2),
+        verify: Duration::from_millis(1),
+        fingerprint: fingerprint.to_string(),
+        verified: true,
+    };
+    let report = MultiSeedReport {
+        concurrency: 1,
+        runs_per_seed: 2,
+        seeds: vec![
+            SeedResult {
+                seed: [1u8; 32],
+                runs: vec![run("aa"), run("aa")],
+            },
+            SeedResult {
+                seed: [2u8; 32],
+                runs: vec![run("bb"), run("cc")],
+            },
+        ],
+    };
+    let flagged: Vec<_> = report.nondeterministic().map(|seed| seed.seed).collect();
+    assert_eq!(flagged, [[2u8; 32]]);
+    assert_eq!(report.seeds[1].fingerprints(), ["bb", "cc"]);
+    assert_eq!(report.to_json()["nondeterministic"][0], "02".repeat(32));
+}
@@ -0,0 +1,20 @@
+//! Scalar and packed Poseidon2 permutations agree
+//!
+//! Run with `cargo test --features debug-tools --test packed_poseidon`.
+
+#![cfg(feature = "debug-tools")]
+
+use rust_benchmark::packed_poseidon;
+use rust_benchmark::poseidon::poseidon2_24;
+
+#[test]
+fn packed_lanes_match_the_scalar_permutation() {
+    let perm = poseidon2_24();
+    // Not a multiple of any lane count, so the last chunk is short
+    let states = packed_poseidon::random_states([0x5a; 32], 3 * packed_poseidon::lanes() + 1);
+    let (scalar, comparison) = packed_poseidon::compare(perm, &states);
+    assert!(comparison.is_equal(), "{:?}", comparison.mismatches);
+    assert_eq!(comparison.permutations, states.len());
+    assert_eq!(scalar, packed_poseidon::permute_packed(perm, &states));
+    assert_ne!(scalar[0], states[0]);
+}
@@ -0,0 +1,45 @@
+//! DER/PEM wrapping of public keys, independent of the scheme types
+
+use rust_benchmark::compat::pem;
+use rust_benchmark::lifetime::LifetimeTag;
+
+#[test]
+fn der_round_trip_carries_lifetime() {
+    let key: Vec<u8> = (0..200u8).collect();
+    for tag in LifetimeTag::ALL {
+        let der = pem::wrap_der(tag, &key);
+        let (unwrapped_tag, unwrapped_key) = pem::unwrap_der(&der).unwrap();
+        assert_eq!(unwrapped_tag, tag);
+        assert_eq!(unwrapped_key, &key[..]);
+    }
+}
+
+#[test]
+fn pem_armor_round_trip() {
+    let der = pem::wrap_der(LifetimeTag::Pow18, &[7u8; 52]);
+    let armored = pem::der_to_pem(&der);
+    assert!(armored.starts_with("-----BEGIN HASHSIG PUBLIC KEY-----\n"));
+    assert!(armored.ends_with("-----END HASHSIG PUBLIC KEY-----\n"));
+    assert!(armored.lines().all(|line| line.len() <= 64));
+    assert_eq!(pem::pem_to_der(&armored).unwrap(), der);
+    assert!(pem::is_wrapped(armored.as_bytes()));
+    assert!(pem::is_wrapped(&der));
+    assert_eq!(
+        pem::wrapped_lifetime(armored.as_bytes()).unwrap(),
+        LifetimeTag::Pow18
+    );
+}
+
+#[test]
+fn rejects_foreign_and_truncated_der() {
+    let der = pem::wrap_der(LifetimeTag::Pow8, &[1u8; 52]);
+    assert!(pem::unwrap_der(&der[..der.len() - 1]).is_err());
+    assert!(!pem::is_wrapped(&[1u8; 52]));
+    assert!(!pem::is_wrapped(b"{\"root\": [1, 2, 3]}"));
+
+    // Same structure under OID 1.2.6.1.4.1.32473.1.8
+    let mut foreign = der.clone();
+    let oid_at = foreign.windows(3).position(|w| w == [0x2b, 6, 1]).unwrap();
+    foreign[oid_at] = 0x2a;
+    assert!(pem::unwrap_der(&foreign).is_err());
+}
@@ -0,0 +1,26 @@
+//! The process-wide Poseidon2 instances match freshly built ones
+
+use p3_field::PrimeCharacteristicRing;
+use p3_koala_bear::KoalaBear;
+use p3_symmetric::Permutation;
+use rust_benchmark::poseidon;
+use rust_benchmark::upstream::backend;
+
+#[test]
+fn cached_permutations_match_the_upstream_constructors() {
+    let state24: [KoalaBear; 24] = std::array::from_fn(|i| KoalaBear::from_u32(i as u32 * 7 + 1));
+    assert_eq!(
+        poseidon::poseidon2_24().permute(state24),
+        backend::poseidon2_24().permute(state24)
+    );
+    let state16: [KoalaBear; 16] = std::array::from_fn(|i| KoalaBear::from_u32(i as u32 * 3 + 2));
+    assert_eq!(
+        poseidon::poseidon2_16().permute(state16),
+        backend::poseidon2_16().permute(state16)
+    );
+    // Built once, then shared
+    assert!(std::ptr::eq(
+        poseidon::poseidon2_24(),
+        poseidon::poseidon2_24()
+    ));
+}
@@ -0,0 +1,24 @@
+//! Warm and cold measurement regimes
+
+use rust_benchmark::regime::{self, Regime};
+
+#[test]
+fn modes_parse_by_name() {
+    assert_eq!(Regime::parse("warm").unwrap(), Regime::Warm);
+    assert_eq!(Regime::parse(" Cold ").unwrap(), Regime::Cold);
+    assert_eq!(Regime::default(), Regime::Warm);
+    assert!(Regime::parse("lukewarm").is_err());
+    assert_eq!(Regime::Cold.to_string(), "cold");
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn dropping_the_page_cache_keeps_the_file() {
+    let path = std::env::temp_dir().join(format!("hash-zig-regime-{}", std::process::id()));
+    std::fs::write(&path, b"cached").unwrap();
+    regime::drop_page_cache(&path).unwrap();
+    assert_eq!(std::fs::read(&path).unwrap(), b"cached");
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(regime::drop_page_cache(&path).is_err());
+}
@@ -0,0 +1,42 @@
+//! Speedup and efficiency in the thread-scaling report
+
+use std::time::Duration;
+
+use rust_benchmark::scaling::{self, ScalingReport, ScalingRow};
+use rust_benchmark::upstream::SIGTopLevelTargetSumLifetime8Dim64Base8;
+
+fn row(threads: usize, keygen_ms: u64, sign_ms: u64, verify_ms: u64) -> ScalingRow {
+    ScalingRow {
+        threads,
+        keygen: Duration::from_millis(keygen_ms),
+        sign: Duration::from_millis(sign_ms),
+        verify: Duration::from_millis(verify_ms),
+    }
+}
+
+#[test]
+fn runs_are_compared_with_the_first() {
+    let report = ScalingReport {
+        signatures: 64,
+        rows: vec![row(1, 800, 400, 200), row(4, 400, 100, 80)],
+    };
+    let [keygen, sign, verify] = report.scaling(&report.rows[1]);
+    assert_eq!(keygen, ("keygen", 2.0, 0.5));
+    assert_eq!(sign, ("sign", 4.0, 1.0));
+    assert_eq!(verify, ("verify", 2.5, 0.625));
+
+    let json = report.to_json();
+    assert_eq!(json["signatures"], 64);
+    assert_eq!(json["rows"][0]["keygen_speedup"], 1.0);
+    assert_eq!(json["rows"][0]["verify_efficiency"], 1.0);
+    assert_eq!(json["rows"][1]["threads"], 4);
+    assert_eq!(json["rows"][1]["sign_ms"], 100.0);
+    assert_eq!(json["rows"][1]["sign_efficiency"], 1.0);
+}
+
+#[test]
+fn a_run_signs_and_verifies_on_its_own_pool() {
+    let row = scaling::measure::<SIGTopLevelTargetSumLifetime8Dim64Base8>([7; 32], 2, 8).unwrap();
+    assert_eq!(row.threads, 2);
+    assert!(row.sign > Duration::ZERO && row.verify > Duration::ZERO);
+}
@@ -0,0 +1,78 @@
+//! One seed policy for every command: 64 hex digits, or a seed file
+
+use std::fs;
+use std::process;
+
+use clap::Parser;
+use rust_benchmark::seed;
+use rust_benchmark::tool::Cli;
+
+#[test]
+fn parses_exactly_32_bytes_of_hex() {
+    let hex = "a5".repeat(32);
+    assert_eq!(seed::parse(&hex).unwrap(), [0xa5; 32]);
+    assert_eq!(seed::parse(&format!(" 0x{hex}\n")).unwrap(), [0xa5; 32]);
+    for bad in [
+        "a5".repeat(31),
+        "a5".repeat(33),
+        format!("{}a", "a5".repeat(31)),
+        "zz".repeat(32),
+        String::new(),
+    ] {
+        assert!(seed::parse(&bad).is_err(), "{bad}");
+    }
+}
+
+#[test]
+fn reads_hex_or_raw_seed_files() {
+    let dir = std::env::temp_dir().join(format!("hash-zig-seed-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+
+    let hex = dir.join("seed.hex");
+    fs::write(&hex, format!("# key for run 7\n{}\n", "07".repeat(32))).unwrap();
+    assert_eq!(seed::read_file(&hex).unwrap(), [0x07; 32]);
+
+    // 32 raw bytes, including ones that happen to be valid UTF-8
+    let raw = dir.join("seed.bin");
+    fs::write(&raw, [b'x'; 32]).unwrap();
+    assert_eq!(seed::read_file(&raw).unwrap(), [b'x'; 32]);
+    fs::write(&raw, [0xfe; 32]).unwrap();
+    assert_eq!(seed::read_file(&raw).unwrap(), [0xfe; 32]);
+
+    let short = dir.join("short.hex");
+    fs::write(&short, "abcd\n").unwrap();
+    assert!(seed::read_file(&short).is_err());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn seed_sources_are_exclusive() {
+    let hex = "42".repeat(32);
+    for line in [
+        format!("hashzig-tool keygen {hex} 2^8"),
+        format!("hashzig-tool keygen --seed {hex}"),
+        "hashzig-tool keygen --seed-file seed.hex".to_string(),
+        "hashzig-tool keygen --random-seed".to_string(),
+        format!("hashzig-tool bench simulate-gossip 10 --seed {hex}"),
+        "hashzig-tool gen-messages --random-seed".to_string(),
+        format!("hashzig-tool debug sign msg pk.json sig.bin {hex} 3"),
+        "hashzig-tool debug sign msg pk.json sig.bin --seed-file seed.hex".to_string(),
+    ] {
+        assert!(
+            Cli::try_parse_from(line.split_whitespace()).is_ok(),
+            "{line}"
+        );
+    }
+    for line in [
+        format!("hashzig-tool keygen {hex} --seed {hex}"),
+        format!("hashzig-tool keygen --seed {hex} --random-seed"),
+        "hashzig-tool keygen --seed-file seed.hex --random-seed".to_string(),
+        format!("hashzig-tool keygen {}", "42".repeat(31)),
+    ] {
+        assert!(
+            Cli::try_parse_from(line.split_whitespace()).is_err(),
+            "{line}"
+        );
+    }
+}
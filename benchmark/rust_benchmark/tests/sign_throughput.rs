@@ -0,0 +1,41 @@
+//! Signing at many epochs from one key
+
+use std::time::Duration;
+
+use rust_benchmark::sign_throughput::{self, ThroughputReport};
+use rust_benchmark::upstream::SIGTopLevelTargetSumLifetime8Dim64Base8;
+
+type Scheme = SIGTopLevelTargetSumLifetime8Dim64Base8;
+
+#[test]
+fn signing_past_the_prepared_window_advances_it() {
+    let report = sign_throughput::measure::<Scheme>([5u8; 32], 40, 1).unwrap();
+    assert_eq!(report.epochs, (0..40).collect::<Vec<_>>());
+    assert!(report.advances > 0);
+    assert!(report.steady_state_rate() <= report.signing_rate());
+
+    let json = report.to_json();
+    assert_eq!(json["signatures"], 40);
+    assert_eq!(json["first_epoch"], 0);
+    assert_eq!(json["last_epoch"], 39);
+}
+
+#[test]
+fn epochs_past_the_lifetime_are_rejected() {
+    assert!(sign_throughput::measure::<Scheme>([5u8; 32], 3, 128).is_err());
+}
+
+#[test]
+fn overhead_is_the_share_spent_advancing() {
+    let report = ThroughputReport {
+        stride: 1,
+        epochs: (0..4).collect(),
+        signing: Duration::from_millis(300),
+        advancing: Duration::from_millis(100),
+        advances: 2,
+        ..ThroughputReport::default()
+    };
+    assert_eq!(report.advance_overhead(), 0.25);
+    assert_eq!(report.steady_state_rate(), 10.0);
+    assert_eq!(ThroughputReport::default().advance_overhead(), 0.0);
+}
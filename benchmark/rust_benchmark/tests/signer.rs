@@ -0,0 +1,57 @@
+//! Prepared-window bookkeeping of `signer::PreparationState` and the
+//! on-disk watermark of `signer::SigningSession`
+
+use rand::{rngs::StdRng, SeedableRng};
+use rust_benchmark::signer::{PreparationState, SigningSession, Watermark};
+use rust_benchmark::upstream::{
+    SIGTopLevelTargetSumLifetime8Dim64Base8 as Scheme, SignatureScheme,
+};
+
+#[test]
+fn advances_are_counted_in_bottom_trees() {
+    // A 2^18 key active for 2^12 epochs from 1024, prepared over two 512-epoch trees
+    let state = PreparationState {
+        activation: 1024..5120,
+        prepared: 1024..2048,
+    };
+    assert_eq!(state.epochs_remaining(), 4096);
+    assert_eq!(state.advances_needed(1024), Some(0));
+    assert_eq!(state.advances_needed(2047), Some(0));
+    assert_eq!(state.advances_needed(2048), Some(1));
+    assert_eq!(state.advances_needed(2559), Some(1));
+    assert_eq!(state.advances_needed(2560), Some(2));
+    assert_eq!(state.advances_needed(1023), None);
+    assert_eq!(state.advances_needed(5120), None);
+
+    let info = state.to_json(Some(2560));
+    assert_eq!(info["prepared_end"], 2048);
+    assert_eq!(info["advances_needed"], 2);
+    assert!(state.to_json(None).get("epoch").is_none());
+}
+
+#[test]
+fn watermark_survives_a_new_session() {
+    let path = std::env::temp_dir().join(format!("hash-zig-watermark-{}", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    // The same seed gives the restarted signer the same key
+    let secret_key = || Scheme::key_gen(&mut StdRng::from_seed([7; 32]), 0, 256).1;
+    let message = [0x42; 32];
+
+    let mut session =
+        SigningSession::<Scheme>::with_watermark(secret_key(), Watermark::new(&path)).unwrap();
+    assert_eq!(session.last_signed(), None);
+    session.sign(3, &message).unwrap();
+    assert_eq!(Watermark::new(&path).load().unwrap(), Some(3));
+
+    let mut session =
+        SigningSession::<Scheme>::with_watermark(secret_key(), Watermark::new(&path)).unwrap();
+    assert_eq!(session.last_signed(), Some(3));
+    assert!(session.is_reused(3));
+    assert!(session.sign(3, &message).is_err());
+    session.sign(4, &message).unwrap();
+    assert_eq!(Watermark::new(&path).load().unwrap(), Some(4));
+
+    std::fs::write(&path, "four\n").unwrap();
+    assert!(Watermark::new(&path).load().is_err());
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,59 @@
+//! Per-component byte budgets of signatures and public keys
+
+use rand::{rngs::StdRng, SeedableRng};
+use rust_benchmark::compat::lean_wire;
+use rust_benchmark::config::Format;
+use rust_benchmark::lifetime::SchemeMetadata;
+use rust_benchmark::size_report::{self, SizeReport};
+use rust_benchmark::upstream::{
+    SIGTopLevelTargetSumLifetime8Dim64Base8 as Scheme, SignatureScheme,
+};
+
+fn row(reports: &[SizeReport], format: Format) -> &SizeReport {
+    reports.iter().find(|r| r.format == format).unwrap()
+}
+
+#[test]
+fn breakdowns_account_for_every_byte() {
+    let meta = Scheme::METADATA;
+    let mut rng = StdRng::from_seed([0x24; 32]);
+    let (pk, sk) = Scheme::key_gen(&mut rng, 0, 256);
+    let sig = Scheme::sign(&sk, 5, &[7u8; 32]).expect("signing failed");
+
+    let reports = size_report::signature_sizes::<Scheme>(&sig).unwrap();
+    assert_eq!(reports.len(), size_report::FORMATS.len());
+    let payload =
+        (meta.log_lifetime as usize + meta.dimension) * meta.hash_len * 4 + meta.rand_len * 4;
+    let ssz = row(&reports, Format::Ssz);
+    assert_eq!(ssz.total - ssz.overhead(), payload);
+    // offset(path), offset(hashes) and the path container's offset(siblings)
+    assert_eq!(ssz.overhead(), 12);
+    assert_eq!(row(&reports, Format::Json).total, lean_wire::SIGNATURE_LEN);
+    // header plus two u64 counts
+    assert_eq!(row(&reports, Format::ZigBinary).overhead(), 8 + 16);
+    for report in &reports {
+        assert_eq!(
+            report
+                .components
+                .iter()
+                .map(|(name, _)| *name)
+                .collect::<Vec<_>>(),
+            ["co_path", "rho", "hashes"]
+        );
+    }
+
+    let reports = size_report::public_key_sizes::<Scheme>(&pk).unwrap();
+    assert_eq!(row(&reports, Format::Ssz).overhead(), 0);
+    assert_eq!(row(&reports, Format::ZigBinary).overhead(), 8);
+    let table = size_report::format_table(&reports);
+    assert!(table.starts_with("format"));
+    assert_eq!(table.lines().count(), 1 + size_report::FORMATS.len());
+    let json = row(&reports, Format::Ssz).to_json();
+    assert_eq!(json["format"], "ssz");
+    assert_eq!(json["overhead"], 0);
+    assert_eq!(
+        json["total"],
+        json["components"]["root"].as_u64().unwrap()
+            + json["components"]["parameter"].as_u64().unwrap()
+    );
+}
@@ -0,0 +1,43 @@
+//! Summaries of repeated timings
+
+use std::time::Duration;
+
+use rust_benchmark::stats::{percentile, Summary};
+
+fn ms(values: &[u64]) -> Vec<Duration> {
+    values.iter().map(|&v| Duration::from_millis(v)).collect()
+}
+
+#[test]
+fn summary_of_samples() {
+    let summary = Summary::of(&ms(&[40, 10, 30, 20])).unwrap();
+    assert_eq!(summary.mean, Duration::from_millis(25));
+    assert_eq!(summary.median, Duration::from_millis(25));
+    assert_eq!(summary.p95, Duration::from_millis(40));
+    assert_eq!(summary.min, Duration::from_millis(10));
+    assert_eq!(summary.max, Duration::from_millis(40));
+    // Sample standard deviation: sqrt(500 / 3) ms
+    let stddev = summary.stddev.as_secs_f64() * 1e3;
+    assert!((stddev - (500.0f64 / 3.0).sqrt()).abs() < 1e-6, "{stddev}");
+
+    let json = summary.to_json();
+    assert_eq!(json["n"], 4);
+    // Raw samples stay in the order they were taken
+    assert_eq!(
+        json["samples_ms"],
+        serde_json::json!([40.0, 10.0, 30.0, 20.0])
+    );
+}
+
+#[test]
+fn single_and_missing_samples() {
+    let summary = Summary::of(&ms(&[7])).unwrap();
+    assert_eq!(summary.median, Duration::from_millis(7));
+    assert_eq!(summary.stddev, Duration::ZERO);
+    assert!(Summary::of(&[]).is_none());
+    assert_eq!(percentile(&[], 50.0), Duration::ZERO);
+    assert_eq!(
+        percentile(&ms(&[1, 2, 3, 4]), 50.0),
+        Duration::from_millis(2)
+    );
+}
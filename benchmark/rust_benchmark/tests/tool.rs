@@ -0,0 +1,671 @@
+//! The `hashzig-tool` command tree, the argument rewriting of its shims and
+//! its exit codes
+
+use std::ffi::OsString;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::{self, Command, Stdio};
+
+use clap::{CommandFactory, Parser};
+use rust_benchmark::checkpoint::{self, KeygenCheckpoint};
+use rust_benchmark::compat::{hsz, pem};
+use rust_benchmark::config::Format;
+use rust_benchmark::key_cache;
+use rust_benchmark::lifetime::LifetimeTag;
+use rust_benchmark::seed;
+use rust_benchmark::tool::exit::{self, Failure};
+use rust_benchmark::tool::{legacy_args, Cli};
+use rust_benchmark::upstream::SIGTopLevelTargetSumLifetime8Dim64Base8;
+
+fn args(line: &str) -> Vec<OsString> {
+    line.split_whitespace().map(OsString::from).collect()
+}
+
+#[test]
+fn command_tree_is_consistent() {
+    Cli::command().debug_assert();
+}
+
+#[test]
+fn legacy_commands_move_into_their_group() {
+    let bench = ["simulate-gossip", "verify-load"];
+    assert_eq!(
+        legacy_args(
+            args("cross_lang_rust_tool --ssz verify-load 100 all"),
+            &["bench"],
+            &bench
+        ),
+        args("cross_lang_rust_tool --ssz bench verify-load 100 all")
+    );
+    assert_eq!(
+        legacy_args(
+            args("cross_lang_rust_tool keygen 42 2^8 --ssz"),
+            &["bench"],
+            &bench
+        ),
+        args("cross_lang_rust_tool keygen 42 2^8 --ssz")
+    );
+    assert_eq!(
+        legacy_args(
+            args("remote_hashsig_tool verify msg pk.bin sig.bin 3"),
+            &["debug"],
+            &[]
+        ),
+        args("remote_hashsig_tool debug verify msg pk.bin sig.bin 3")
+    );
+    assert_eq!(
+        legacy_args(args("smoke"), &["debug", "smoke"], &[]),
+        args("smoke debug smoke")
+    );
+}
+
+#[test]
+fn legacy_invocations_parse() {
+    let bench = ["simulate-gossip", "verify-load"];
+    for line in [
+        "cross_lang_rust_tool keygen 4242424242424242424242424242424242424242424242424242424242424242 2^18 --ssz",
+        "cross_lang_rust_tool sign message 0 --ssz",
+        "cross_lang_rust_tool simulate-gossip 64",
+        "cross_lang_rust_tool --key-dir keys verify-load 100 all 4 20",
+    ] {
+        let argv = legacy_args(args(line), &["bench"], &bench);
+        assert!(Cli::try_parse_from(argv).is_ok(), "{line}");
+    }
+    let argv = legacy_args(
+        args("remote_hashsig_tool sign hello pk.bin sig.bin 4242424242424242424242424242424242424242424242424242424242424242 3 --endian big"),
+        &["debug"],
+        &[],
+    );
+    assert!(Cli::try_parse_from(argv).is_ok());
+    assert!(Cli::try_parse_from(legacy_args(args("smoke"), &["debug", "smoke"], &[])).is_ok());
+}
+
+#[test]
+fn json_flag_is_global() {
+    for line in [
+        "hashzig-tool --json verify sig.bin pk.json msg 0",
+        "hashzig-tool bench verify-load 10 --json",
+        "hashzig-tool debug smoke --json",
+    ] {
+        assert!(Cli::try_parse_from(args(line)).is_ok(), "{line}");
+    }
+}
+
+#[test]
+fn exit_codes_are_distinct_and_usage_matches_clap() {
+    let mut codes: Vec<i32> = Failure::ALL.iter().map(|f| f.code()).collect();
+    codes.dedup();
+    assert_eq!(codes, [1, 2, 3, 4, 5, 6, 7]);
+    let Err(usage) = Cli::try_parse_from(args("hashzig-tool verify")) else {
+        panic!("verify without arguments parsed");
+    };
+    assert_eq!(usage.exit_code(), Failure::Usage.code());
+}
+
+#[test]
+fn failures_are_classified_from_the_error_chain() {
+    let missing = hsz::read("/nonexistent/rust_pk.json").unwrap_err();
+    assert_eq!(Failure::of(missing.as_ref()), Failure::Io);
+
+    let lifetime = LifetimeTag::parse(Some("2^9")).unwrap_err();
+    assert_eq!(Failure::of(lifetime.as_ref()), Failure::UnsupportedLifetime);
+
+    let other_lifetime = pem::der_to_pem(&pem::wrap_der(LifetimeTag::Pow18, &[0; 52]));
+    let mismatch = pem::decode_public_key::<SIGTopLevelTargetSumLifetime8Dim64Base8>(
+        other_lifetime.as_bytes(),
+    )
+    .unwrap_err();
+    assert_eq!(Failure::of(mismatch.as_ref()), Failure::UnsupportedLifetime);
+
+    let json = serde_json::from_str::<serde_json::Value>("{").unwrap_err();
+    assert_eq!(Failure::of(&json), Failure::Parse);
+
+    let untyped: Box<dyn std::error::Error> = "no such thing".into();
+    assert_eq!(Failure::of(untyped.as_ref()), Failure::Other);
+}
+
+#[test]
+fn tag_does_not_override_a_known_failure() {
+    let parse = exit::tag(Failure::Parse)("bad magic");
+    assert_eq!(Failure::of(parse.as_ref()), Failure::Parse);
+    assert_eq!(parse.to_string(), "bad magic");
+
+    let missing = hsz::read("/nonexistent/rust_sig.ssz").unwrap_err();
+    let still_io = exit::tag(Failure::Parse)(missing);
+    assert_eq!(Failure::of(still_io.as_ref()), Failure::Io);
+}
+
+fn hashzig_tool(key_dir: &Path, line: &str, stdin: &[u8]) -> process::Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_hashzig-tool"))
+        .arg("--key-dir")
+        .arg(key_dir)
+        .args(line.split_whitespace())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(stdin).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn sign_and_verify_compose_in_a_pipeline() {
+    let dir = std::env::temp_dir().join(format!("hash-zig-pipeline-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let seed = "42".repeat(32);
+    assert!(hashzig_tool(&dir, &format!("keygen {seed} 2^8"), b"")
+        .status
+        .success());
+
+    // The message comes from stdin, so the signature goes to stdout
+    let signed = hashzig_tool(&dir, "sign - 3", b"piped-message\n");
+    assert!(
+        signed.status.success(),
+        "{}",
+        String::from_utf8_lossy(&signed.stderr)
+    );
+    assert_eq!(signed.stdout.len(), 3116);
+
+    let pk = dir.join("rust_pk.json");
+    let pk = pk.display();
+    let verified = hashzig_tool(
+        &dir,
+        &format!("verify - {pk} piped-message 3"),
+        &signed.stdout,
+    );
+    assert_eq!(verified.status.code(), Some(0));
+    let rejected = hashzig_tool(
+        &dir,
+        &format!("verify - {pk} other-message 3"),
+        &signed.stdout,
+    );
+    assert_eq!(rejected.status.code(), Some(Failure::CheckFailed.code()));
+    let truncated = hashzig_tool(
+        &dir,
+        &format!("verify - {pk} piped-message 3"),
+        &signed.stdout[..100],
+    );
+    assert_eq!(truncated.status.code(), Some(Failure::Parse.code()));
+    let two_stdins = hashzig_tool(&dir, "verify - - piped-message 3", b"");
+    assert_eq!(two_stdins.status.code(), Some(Failure::Usage.code()));
+
+    // The secret key names its own lifetime
+    let mismatch = hashzig_tool(&dir, "sign message 3 --lifetime 2^18", b"");
+    assert_eq!(
+        mismatch.status.code(),
+        Some(Failure::UnsupportedLifetime.code())
+    );
+    let agreed = hashzig_tool(&dir, "sign message 4 --lifetime 2^8 --out -", b"");
+    assert!(agreed.status.success());
+
+    // A copied bare public key no longer says which scheme it is for
+    let copied = dir.join("copied_pk.json");
+    fs::copy(dir.join("rust_pk.json"), &copied).unwrap();
+    let copied = copied.display();
+    let unnamed = hashzig_tool(
+        &dir,
+        &format!("verify - {copied} piped-message 3"),
+        &signed.stdout,
+    );
+    assert_eq!(unnamed.status.code(), Some(Failure::Usage.code()));
+    assert!(String::from_utf8_lossy(&unnamed.stderr).contains("pass --lifetime"));
+    let named = hashzig_tool(
+        &dir,
+        &format!("verify - {copied} piped-message 3 --lifetime 2^8"),
+        &signed.stdout,
+    );
+    assert_eq!(named.status.code(), Some(0));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn quiet_prints_only_the_digest_and_the_verdict() {
+    let dir = std::env::temp_dir().join(format!("hash-zig-quiet-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let seed = "51".repeat(32);
+    let keygen = hashzig_tool(&dir, &format!("keygen {seed} 2^8 --quiet"), b"");
+    assert!(keygen.status.success());
+    let digest = String::from_utf8(keygen.stdout).unwrap();
+    assert_eq!(digest.trim_end().len(), 64, "{digest}");
+    assert!(digest.trim_end().bytes().all(|b| b.is_ascii_hexdigit()));
+    assert!(keygen.stderr.is_empty());
+
+    let sig = dir.join("quiet.bin");
+    assert!(hashzig_tool(
+        &dir,
+        &format!("-q sign hello 1 --out {}", sig.display()),
+        b""
+    )
+    .status
+    .success());
+    let pk = dir.join("rust_pk.json");
+    let verify = |message: &str| {
+        hashzig_tool(
+            &dir,
+            &format!("verify {} {} {message} 1 -q", sig.display(), pk.display()),
+            b"",
+        )
+    };
+    let valid = verify("hello");
+    assert_eq!(valid.stdout, b"true\n");
+    assert!(valid.stderr.is_empty());
+    let invalid = verify("other");
+    assert_eq!(invalid.stdout, b"false\n");
+    assert_eq!(invalid.status.code(), Some(Failure::CheckFailed.code()));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn repl_keeps_the_key_between_commands() {
+    let dir = std::env::temp_dir().join(format!("hash-zig-repl-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let seed = "13".repeat(32);
+    let session = hashzig_tool(
+        &dir,
+        &format!("repl --seed {seed}"),
+        b"sign 5 \"block 5\"\nverify last\nverify 5 \"block 6\"\ndump leaf 5\nverify 9\nsign 2 earlier\nverify 2\nquit\nsign 7 never\n",
+    );
+    assert!(
+        session.status.success(),
+        "{}",
+        String::from_utf8_lossy(&session.stderr)
+    );
+    let stdout = String::from_utf8(session.stdout).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert!(lines.contains(&"PASS (epoch 5)"), "{stdout}");
+    assert!(lines.contains(&"FAIL (epoch 5)"), "{stdout}");
+    assert!(lines.contains(&"PASS (epoch 2)"), "{stdout}");
+    assert!(
+        stdout.contains("error: no signature at epoch 9"),
+        "{stdout}"
+    );
+    assert_eq!(stdout.matches("SIGNATURE_ROOT:").count(), 2, "{stdout}");
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn jobs_sizes_the_worker_pool_and_is_reported() {
+    let dir = std::env::temp_dir().join(format!("hash-zig-jobs-{}", process::id()));
+    let seed = "90".repeat(32);
+    let output = hashzig_tool(
+        &dir,
+        &format!("--json -j 3 gen-messages --count 1 --seed {seed}"),
+        b"",
+    );
+    assert!(output.status.success());
+    let document: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(document["jobs"], 3);
+}
+
+#[test]
+fn allocator_is_reported() {
+    let dir = std::env::temp_dir().join(format!("hash-zig-allocator-{}", process::id()));
+    let seed = "91".repeat(32);
+    let output = hashzig_tool(
+        &dir,
+        &format!("--json gen-messages --count 1 --seed {seed}"),
+        b"",
+    );
+    assert!(output.status.success());
+    let document: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(document["allocator"], rust_benchmark::allocator::NAME);
+}
+
+#[test]
+fn timeout_stops_keygen_without_writing_the_key() {
+    let dir = std::env::temp_dir().join(format!("hash-zig-timeout-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let seed = "77".repeat(32);
+    // A 2^18 keygen takes far longer than a second
+    let stopped = hashzig_tool(&dir, &format!("--json --timeout 1 keygen {seed} 2^18"), b"");
+    assert_eq!(stopped.status.code(), Some(Failure::Cancelled.code()));
+    let document: serde_json::Value = serde_json::from_slice(&stopped.stdout).unwrap();
+    assert_eq!(document["failure"], "cancelled");
+    assert_eq!(document["error"], "timed out");
+    assert!(!dir.join("rust_pk.json").exists());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn keygen_resumes_from_its_checkpoint() {
+    let dir = std::env::temp_dir().join(format!("hash-zig-resume-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let checkpoint_path = checkpoint::path(&dir);
+    let keygen = |line: &str| {
+        let output = hashzig_tool(&dir, &format!("--json keygen {line}"), b"");
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap()
+    };
+    let seed = "61".repeat(32);
+    let complete = keygen(&format!("{seed} 2^8 --prepare-to 100"));
+    assert_eq!(complete["prepared"]["prepared_end"], 112);
+    assert!(!checkpoint_path.exists());
+    // Timed per phase, and the timings kept with the key
+    for phase in ["key_gen_ms", "serialize_ms", "write_ms", "prepare_ms"] {
+        assert!(complete["phases"][phase].is_f64(), "{phase}");
+    }
+    let metadata: serde_json::Value =
+        serde_json::from_slice(&fs::read(dir.join("rust_key.json")).unwrap()).unwrap();
+    assert_eq!(
+        metadata["keygen_phases_ms"].as_object().unwrap().len(),
+        complete["phases"].as_object().unwrap().len()
+    );
+
+    // Interrupted after the pair was stored: only the preparation is left
+    let interrupted = KeygenCheckpoint {
+        lifetime: LifetimeTag::Pow8,
+        format: Format::Json,
+        seed: seed::parse(&seed).unwrap(),
+        activation_epoch: 0,
+        num_active_epochs: 256,
+        prepare_to: Some(100),
+        generated: true,
+    };
+    keygen(&seed);
+    interrupted.save(&checkpoint_path).unwrap();
+    let resumed = keygen("--resume");
+    assert_eq!(resumed["resumed"], true);
+    assert_eq!(resumed["public_sha3"], complete["public_sha3"]);
+    assert_eq!(resumed["prepared"]["prepared_end"], 112);
+    assert!(!checkpoint_path.exists());
+
+    // Interrupted inside key_gen: generated again from the recorded seed
+    KeygenCheckpoint {
+        generated: false,
+        ..interrupted
+    }
+    .save(&checkpoint_path)
+    .unwrap();
+    let regenerated = keygen("--resume");
+    assert_eq!(regenerated["public_sha3"], complete["public_sha3"]);
+    assert_eq!(regenerated["prepared"]["prepared_end"], 112);
+
+    let nothing = hashzig_tool(&dir, "keygen --resume", b"");
+    assert_eq!(nothing.status.code(), Some(Failure::Usage.code()));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn debug_sign_reuses_cached_keys() {
+    let dir = std::env::temp_dir().join(format!("hash-zig-key-cache-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let seed = "42".repeat(32);
+    let sign = |extra: &str| {
+        let output = Command::new(env!("CARGO_BIN_EXE_hashzig-tool"))
+            .env(key_cache::DIR_ENV, dir.join("cache"))
+            .current_dir(&dir)
+            .args(
+                format!("--json debug sign msg pk.json sig.bin {seed} 3 {extra}")
+                    .split_whitespace(),
+            )
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let document: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        (
+            document["key_cache"].clone(),
+            fs::read(dir.join("pk.json")).unwrap(),
+        )
+    };
+    let (first, pk) = sign("");
+    assert_eq!(first, "miss");
+    let (second, cached_pk) = sign("");
+    assert_eq!(second, "hit");
+    assert_eq!(cached_pk, pk);
+    let (bypassed, fresh_pk) = sign("--no-cache");
+    assert_eq!(bypassed, "off");
+    assert_eq!(fresh_pk, pk);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn state_dir_is_an_alias_of_key_dir() {
+    let matches = Cli::command().get_matches_from(args(
+        "hashzig-tool sign hello 0 --state-dir /work/state --out-dir /work/out",
+    ));
+    assert_eq!(
+        matches.get_one::<std::path::PathBuf>("key_dir").unwrap(),
+        Path::new("/work/state")
+    );
+    assert_eq!(
+        matches.get_one::<std::path::PathBuf>("out_dir").unwrap(),
+        Path::new("/work/out")
+    );
+}
+
+fn epoch_info(output: &process::Output) -> serde_json::Value {
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let line = stdout
+        .lines()
+        .find_map(|line| line.strip_prefix("EPOCH_INFO:"))
+        .unwrap_or_else(|| panic!("no EPOCH_INFO in {stdout}"));
+    serde_json::from_str(line).unwrap()
+}
+
+#[test]
+fn advance_persists_the_prepared_window() {
+    let dir = std::env::temp_dir().join(format!("hash-zig-advance-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let seed = "24".repeat(32);
+    assert!(hashzig_tool(&dir, &format!("keygen {seed} 2^8"), b"")
+        .status
+        .success());
+
+    let before = epoch_info(&hashzig_tool(&dir, "epoch-info --epoch 200", b""));
+    assert_eq!(before["prepared_start"], 0);
+    assert!(before["advances_needed"].as_u64().unwrap() > 0);
+
+    let advanced = hashzig_tool(&dir, "advance --to-epoch 200", b"");
+    assert!(
+        advanced.status.success(),
+        "{}",
+        String::from_utf8_lossy(&advanced.stderr)
+    );
+    let after = epoch_info(&hashzig_tool(&dir, "epoch-info --epoch 200", b""));
+    assert_eq!(after["advances_needed"], 0);
+    assert!(after["prepared_start"].as_u64().unwrap() > 0);
+
+    // Behind the stored window now
+    let stale = hashzig_tool(&dir, "sign message 3 --out -", b"");
+    assert_eq!(stale.status.code(), Some(Failure::Usage.code()));
+    let signed = hashzig_tool(&dir, "sign message 200 --out -", b"");
+    assert!(signed.status.success());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn dry_run_checks_inputs_without_touching_them() {
+    let dir = std::env::temp_dir().join(format!("hash-zig-dry-run-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let seed = "35".repeat(32);
+    assert!(hashzig_tool(&dir, &format!("keygen {seed} 2^8"), b"")
+        .status
+        .success());
+
+    let sig = dir.join("dry.bin");
+    let planned = hashzig_tool(
+        &dir,
+        &format!("--json sign message 200 --dry-run --out {}", sig.display()),
+        b"",
+    );
+    assert!(
+        planned.status.success(),
+        "{}",
+        String::from_utf8_lossy(&planned.stderr)
+    );
+    let document: serde_json::Value = serde_json::from_slice(&planned.stdout).unwrap();
+    assert_eq!(document["dry_run"], true);
+    assert!(document["prepared"]["advances_needed"].as_u64().unwrap() > 0);
+    assert!(!sig.exists());
+    // The stored key was not advanced
+    let info = epoch_info(&hashzig_tool(&dir, "epoch-info", b""));
+    assert_eq!(info["prepared_start"], 0);
+    let out_of_range = hashzig_tool(&dir, "sign message 256 --dry-run", b"");
+    assert_eq!(out_of_range.status.code(), Some(Failure::Usage.code()));
+
+    assert!(hashzig_tool(
+        &dir,
+        &format!("sign message 3 --out {}", sig.display()),
+        b""
+    )
+    .status
+    .success());
+    let pk = dir.join("rust_pk.json");
+    let checked = hashzig_tool(
+        &dir,
+        &format!(
+            "verify {} {} other 3 --dry-run",
+            sig.display(),
+            pk.display()
+        ),
+        b"",
+    );
+    assert_eq!(checked.status.code(), Some(0));
+    assert!(String::from_utf8_lossy(&checked.stdout).starts_with("would verify"));
+    let truncated = dir.join("truncated.bin");
+    fs::write(&truncated, &fs::read(&sig).unwrap()[..100]).unwrap();
+    let malformed = hashzig_tool(
+        &dir,
+        &format!(
+            "verify {} {} message 3 --dry-run",
+            truncated.display(),
+            pk.display()
+        ),
+        b"",
+    );
+    assert_eq!(malformed.status.code(), Some(Failure::Parse.code()));
+
+    let converted = dir.join("dry_pk.ssz");
+    let convert = hashzig_tool(
+        &dir,
+        &format!("convert {} {} --dry-run", pk.display(), converted.display()),
+        b"",
+    );
+    assert!(convert.status.success());
+    assert!(!converted.exists());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn key_info_reads_the_stored_pair() {
+    let dir = std::env::temp_dir().join(format!("hash-zig-key-info-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let seed = "66".repeat(32);
+    assert!(hashzig_tool(&dir, &format!("--ssz keygen {seed} 2^8"), b"")
+        .status
+        .success());
+
+    let pk = hashzig_tool(
+        &dir,
+        &format!("--json key-info {}", dir.join("rust_pk.ssz").display()),
+        b"",
+    );
+    assert!(
+        pk.status.success(),
+        "{}",
+        String::from_utf8_lossy(&pk.stderr)
+    );
+    let pk: serde_json::Value = serde_json::from_slice(&pk.stdout).unwrap();
+    assert_eq!(pk["lifetime"], "2^8");
+    assert_eq!(pk["dimension"], 64);
+    assert_eq!(pk["root_elements"].as_array().unwrap().len(), 8);
+    assert_eq!(pk["public_sha3"].as_str().unwrap().len(), 64);
+    assert_eq!(pk["provenance"]["format"], "ssz");
+
+    let sk = hashzig_tool(
+        &dir,
+        &format!("--json key-info {}", dir.join("rust_sk.ssz").display()),
+        b"",
+    );
+    let sk: serde_json::Value = serde_json::from_slice(&sk.stdout).unwrap();
+    assert_eq!(sk["lifetime_source"], "envelope");
+    assert_eq!(sk["activation_interval"], serde_json::json!([0, 256]));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn verify_watch_reruns_when_the_signature_is_rewritten() {
+    use std::io::{BufRead, BufReader};
+
+    let dir = std::env::temp_dir().join(format!("hash-zig-watch-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let seed = "77".repeat(32);
+    assert!(hashzig_tool(&dir, &format!("keygen {seed} 2^8"), b"")
+        .status
+        .success());
+    let sig = dir.join("sig.bin");
+    let signed = hashzig_tool(
+        &dir,
+        &format!("sign watched 5 --out {}", sig.display()),
+        b"",
+    );
+    assert!(signed.status.success());
+    let pk = dir.join("rust_pk.json");
+
+    let refused = hashzig_tool(
+        &dir,
+        &format!("verify - {} watched 5 --watch", pk.display()),
+        b"",
+    );
+    assert_eq!(refused.status.code(), Some(Failure::Usage.code()));
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_hashzig-tool"))
+        .arg("--key-dir")
+        .arg(&dir)
+        .args(["verify", "--watch"])
+        .arg(&sig)
+        .arg(&pk)
+        .args(["watched", "5"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    let mut lines = BufReader::new(child.stdout.take().unwrap()).lines();
+    let mut next_result = || -> serde_json::Value {
+        lines
+            .by_ref()
+            .map(Result::unwrap)
+            .filter_map(|line| line.strip_prefix("WATCH_RESULT:").map(str::to_owned))
+            .map(|line| serde_json::from_str::<serde_json::Value>(&line).unwrap())
+            // A run that caught the file half-written reports an error instead
+            .find(|result| result.get("valid").is_some())
+            .unwrap()
+    };
+    let first = next_result();
+    assert_eq!(first["valid"], true);
+    assert_eq!(first["changed"], serde_json::Value::Null);
+
+    // A signature for another epoch no longer verifies at epoch 5
+    let other = hashzig_tool(
+        &dir,
+        &format!("sign watched 6 --out {}", sig.display()),
+        b"",
+    );
+    assert!(other.status.success());
+    let second = next_result();
+    child.kill().unwrap();
+    child.wait().unwrap();
+    assert_eq!(second["valid"], false);
+    assert!(second["changed"].as_u64().unwrap() > 0);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,49 @@
+//! Compares artifacts produced by the `leansig` and `hashsig` upstreams
+//!
+//! Run with `cargo test --features hashsig --test upstream_compat`; without
+//! both upstreams enabled this file compiles to nothing.
+
+#![cfg(all(feature = "leansig", feature = "hashsig"))]
+
+use rand::{rngs::StdRng, SeedableRng};
+use serde_json::Value;
+
+const SEED: [u8; 32] = [0x42; 32];
+const MESSAGE: [u8; 32] = *b"upstream compatibility message!!";
+const NUM_ACTIVE_EPOCHS: usize = 256;
+
+fn leansig_artifacts(epoch: u32) -> (Value, Value) {
+    use leansig::signature::generalized_xmss::instantiations_poseidon_top_level::lifetime_2_to_the_8::SIGTopLevelTargetSumLifetime8Dim64Base8 as Scheme;
+    use leansig::signature::SignatureScheme;
+
+    let mut rng = StdRng::from_seed(SEED);
+    let (pk, sk) = Scheme::key_gen(&mut rng, 0, NUM_ACTIVE_EPOCHS);
+    let sig = Scheme::sign(&sk, epoch, &MESSAGE).expect("leansig signing failed");
+    (
+        serde_json::to_value(&pk).unwrap(),
+        serde_json::to_value(&sig).unwrap(),
+    )
+}
+
+fn hashsig_artifacts(epoch: u32) -> (Value, Value) {
+    use hashsig::signature::generalized_xmss::instantiations_poseidon_top_level::lifetime_2_to_the_8::SIGTopLevelTargetSumLifetime8Dim64Base8 as Scheme;
+    use hashsig::signature::SignatureScheme;
+
+    let mut rng = StdRng::from_seed(SEED);
+    let (pk, sk) = Scheme::key_gen(&mut rng, 0, NUM_ACTIVE_EPOCHS);
+    let sig = Scheme::sign(&sk, epoch, &MESSAGE).expect("hashsig signing failed");
+    (
+        serde_json::to_value(&pk).unwrap(),
+        serde_json::to_value(&sig).unwrap(),
+    )
+}
+
+#[test]
+fn same_seed_produces_identical_artifacts() {
+    for epoch in [0u32, 1, 17] {
+        let (lean_pk, lean_sig) = leansig_artifacts(epoch);
+        let (hash_pk, hash_sig) = hashsig_artifacts(epoch);
+        assert_eq!(lean_pk, hash_pk, "public keys diverge");
+        assert_eq!(lean_sig, hash_sig, "signatures diverge at epoch {epoch}");
+    }
+}
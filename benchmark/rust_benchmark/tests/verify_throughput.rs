@@ -0,0 +1,46 @@
+//! Serial and parallel verification rates
+
+use std::time::Duration;
+
+use rust_benchmark::upstream::SIGTopLevelTargetSumLifetime8Dim64Base8;
+use rust_benchmark::verify_throughput::{self, VerifyRun, VerifyThroughputReport};
+
+#[test]
+fn both_modes_verify_the_whole_batch() {
+    let report =
+        verify_throughput::measure::<SIGTopLevelTargetSumLifetime8Dim64Base8>([9u8; 32], 8, true)
+            .unwrap();
+    assert_eq!(report.signatures, 8);
+    assert_eq!(report.serial.verified, 8);
+    assert_eq!(report.serial.threads, 1);
+    assert_eq!(report.parallel.unwrap().verified, 8);
+
+    let json = report.to_json();
+    assert_eq!(json["serial"]["verified"], 8);
+    assert!(json["parallel"]["signatures_per_sec"].as_f64().unwrap() > 0.0);
+}
+
+#[test]
+fn parallel_is_optional() {
+    let report =
+        verify_throughput::measure::<SIGTopLevelTargetSumLifetime8Dim64Base8>([9u8; 32], 2, false)
+            .unwrap();
+    assert_eq!(report.parallel, None);
+    assert!(report.to_json()["parallel"].is_null());
+}
+
+#[test]
+fn speedup_compares_the_rates() {
+    let run = |threads, ms| VerifyRun {
+        threads,
+        verified: 100,
+        elapsed: Duration::from_millis(ms),
+    };
+    let report = VerifyThroughputReport {
+        signatures: 100,
+        serial: run(1, 400),
+        parallel: Some(run(4, 100)),
+    };
+    assert_eq!(report.serial.rate(), 250.0);
+    assert_eq!(report.speedup(), Some(4.0));
+}
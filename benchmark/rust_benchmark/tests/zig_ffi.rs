@@ -0,0 +1,30 @@
+//! Rust-vs-Zig differential tests through the in-process FFI bridge
+//!
+//! Run with `zig build c-api && cargo test --features zig-ffi --test zig_ffi`;
+//! without the feature this file compiles to nothing.
+
+#![cfg(feature = "zig-ffi")]
+
+use rust_benchmark::upstream::SIGTopLevelTargetSumLifetime8Dim64Base8 as Scheme;
+use rust_benchmark::zig_ffi::compare;
+
+const SEED: [u8; 32] = [0x42; 32];
+const MESSAGE: [u8; 32] = *b"zig ffi differential test msg!!!";
+const NUM_ACTIVE_EPOCHS: usize = 256;
+
+#[test]
+fn rust_and_zig_agree_for_lifetime_2_8() {
+    for epoch in [0u32, 1, 17, 255] {
+        let result = compare::<Scheme>(SEED, NUM_ACTIVE_EPOCHS, epoch, &MESSAGE).unwrap();
+        assert!(result.is_consistent(), "epoch {epoch}: {result:?}");
+    }
+}
+
+#[test]
+fn rust_and_zig_agree_past_the_first_prepared_window() {
+    // 2^8 keys start with epochs 0..32 prepared; both sides must advance
+    for epoch in [32u32, 100, 200] {
+        let result = compare::<Scheme>(SEED, NUM_ACTIVE_EPOCHS, epoch, &MESSAGE).unwrap();
+        assert!(result.is_consistent(), "epoch {epoch}: {result:?}");
+    }
+}
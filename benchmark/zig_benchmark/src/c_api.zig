@@ -0,0 +1,121 @@
+//! C ABI over the Zig implementation for in-process differential testing
+//!
+//! Built as the `hash-zig-c` static library (`zig build c-api`) and linked by
+//! the Rust crate's `zig-ffi` feature so Rust-vs-Zig comparisons run without
+//! spawning cross-lang-zig-tool. Keys and signatures are SSZ-encoded and
+//! messages are 32 bytes. Signing regenerates the key pair from its seed, the
+//! same way cross-lang-zig-tool reconstructs keys.
+//!
+//! Return codes follow benchmark/rust_benchmark/include/hashzig_rust.h, with
+//! ERR_INTERNAL (-8) for allocation and other Zig-side errors.
+
+const std = @import("std");
+const hash_zig = @import("hash-zig");
+const KeyLifetime = hash_zig.KeyLifetimeRustCompat;
+
+const OK: i32 = 0;
+const ERR_LIFETIME: i32 = -2;
+const ERR_BUFFER_TOO_SMALL: i32 = -4;
+const ERR_DECODE: i32 = -5;
+const ERR_SIGN: i32 = -6;
+const ERR_INTERNAL: i32 = -8;
+const ERR_EPOCH: i32 = -9;
+
+const allocator = std.heap.page_allocator;
+
+fn lifetimeFromLog(log_lifetime: u32) ?KeyLifetime {
+    return switch (log_lifetime) {
+        8 => .lifetime_2_8,
+        18 => .lifetime_2_18,
+        32 => .lifetime_2_32,
+        else => null,
+    };
+}
+
+/// Copy `bytes` into the caller's buffer, always reporting the full length.
+fn writeOut(bytes: []const u8, out: [*]u8, cap: usize, out_len: *usize) i32 {
+    out_len.* = bytes.len;
+    if (bytes.len > cap) return ERR_BUFFER_TOO_SMALL;
+    @memcpy(out[0..bytes.len], bytes);
+    return OK;
+}
+
+/// Generate a key pair from `seed` and write the SSZ public key.
+export fn hashzig_zig_keygen(
+    log_lifetime: u32,
+    seed: *const [32]u8,
+    activation_epoch: u64,
+    num_active_epochs: u64,
+    pk_out: [*]u8,
+    pk_cap: usize,
+    pk_len: *usize,
+) i32 {
+    const lifetime = lifetimeFromLog(log_lifetime) orelse return ERR_LIFETIME;
+    const scheme = hash_zig.GeneralizedXMSSSignatureScheme.initWithSeed(allocator, lifetime, seed.*) catch return ERR_INTERNAL;
+    defer scheme.deinit();
+    const keypair = scheme.keyGen(@intCast(activation_epoch), @intCast(num_active_epochs)) catch return ERR_INTERNAL;
+    defer keypair.secret_key.deinit();
+
+    const pk_bytes = keypair.public_key.toBytes(allocator) catch return ERR_INTERNAL;
+    defer allocator.free(pk_bytes);
+    return writeOut(pk_bytes, pk_out, pk_cap, pk_len);
+}
+
+/// Regenerate the key pair for `seed` and write the SSZ signature of
+/// `message` at `epoch`, advancing the prepared window to it first.
+export fn hashzig_zig_sign(
+    log_lifetime: u32,
+    seed: *const [32]u8,
+    activation_epoch: u64,
+    num_active_epochs: u64,
+    epoch: u32,
+    message: *const [32]u8,
+    sig_out: [*]u8,
+    sig_cap: usize,
+    sig_len: *usize,
+) i32 {
+    const lifetime = lifetimeFromLog(log_lifetime) orelse return ERR_LIFETIME;
+    const scheme = hash_zig.GeneralizedXMSSSignatureScheme.initWithSeed(allocator, lifetime, seed.*) catch return ERR_INTERNAL;
+    defer scheme.deinit();
+    const keypair = scheme.keyGen(@intCast(activation_epoch), @intCast(num_active_epochs)) catch return ERR_INTERNAL;
+    defer keypair.secret_key.deinit();
+
+    const log = scheme.lifetime_params.log_lifetime;
+    var prepared = keypair.secret_key.getPreparedInterval(log);
+    while (epoch >= prepared.end) {
+        keypair.secret_key.advancePreparation(scheme, log) catch return ERR_INTERNAL;
+        const next = keypair.secret_key.getPreparedInterval(log);
+        if (next.end == prepared.end) break;
+        prepared = next;
+    }
+    if (epoch < prepared.start or epoch >= prepared.end) return ERR_EPOCH;
+
+    const signature = scheme.sign(keypair.secret_key, epoch, message.*) catch return ERR_SIGN;
+    defer signature.deinit();
+    const sig_bytes = signature.toBytes(allocator) catch return ERR_INTERNAL;
+    defer allocator.free(sig_bytes);
+    return writeOut(sig_bytes, sig_out, sig_cap, sig_len);
+}
+
+/// Returns 1 if the SSZ signature verifies under the SSZ public key, 0 if
+/// not, and a negative code if either cannot be decoded.
+export fn hashzig_zig_verify(
+    log_lifetime: u32,
+    pk: [*]const u8,
+    pk_len: usize,
+    epoch: u32,
+    message: *const [32]u8,
+    sig: [*]const u8,
+    sig_len: usize,
+) i32 {
+    const lifetime = lifetimeFromLog(log_lifetime) orelse return ERR_LIFETIME;
+    const scheme = hash_zig.GeneralizedXMSSSignatureScheme.init(allocator, lifetime) catch return ERR_INTERNAL;
+    defer scheme.deinit();
+
+    const public_key = hash_zig.GeneralizedXMSSPublicKey.fromBytes(pk[0..pk_len], null) catch return ERR_DECODE;
+    const signature = hash_zig.GeneralizedXMSSSignature.fromBytes(sig[0..sig_len], allocator) catch return ERR_DECODE;
+    defer signature.deinit();
+
+    const valid = scheme.verify(&public_key, epoch, message.*, signature) catch return ERR_INTERNAL;
+    return @intFromBool(valid);
+}
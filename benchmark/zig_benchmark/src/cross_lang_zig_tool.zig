@@ -517,39 +517,11 @@ fn signCommand(allocator: Allocator, message: []const u8, epoch: u32, lifetime:
         try pk_file.writeAll(pk_json);
         std.debug.print("✅ Public key updated to tmp/zig_pk.json (from regenerated keypair)\n", .{});
 
-        // Serialize signature to bincode binary format (3116 bytes per leanSignature spec)
+        // Fixed 3116-byte leanSignature wire format, field by field
         // Reference: https://github.com/leanEthereum/leanSpec/blob/main/src/lean_spec/subspecs/containers/signature.py
-        // The leanSpec requires:
-        //   1. Signature container: exactly 3116 bytes (Bytes3116)
-        //   2. Signature data: bincode format at the beginning
-        //   3. Can be sliced to scheme.config.SIGNATURE_LEN_BYTES if needed
-        //   4. Format: XmssSignature.from_bytes (bincode deserialization)
-        // Import bincode functions from remote_hash_tool
         const remote_hash_tool = @import("remote_hash_tool.zig");
-        const rand_len = scheme.lifetime_params.rand_len_fe;
-        const hash_len = scheme.lifetime_params.hash_len_fe;
-        try remote_hash_tool.writeSignatureBincode("tmp/zig_sig.bin", signature, rand_len, hash_len);
-        
-        // Pad to exactly 3116 bytes as per leanSignature spec (Bytes3116 container)
-        const SIG_LEN: usize = 3116;
-        var sig_file = try std.fs.cwd().openFile("tmp/zig_sig.bin", .{ .mode = .read_write });
-        defer sig_file.close();
-        const current_size = try sig_file.getEndPos();
-        if (current_size > SIG_LEN) {
-            return error.SignatureTooLarge;
-        }
-        // Pad with zeros to reach 3116 bytes
-        try sig_file.seekTo(current_size);
-        const padding_needed = SIG_LEN - @as(usize, @intCast(current_size));
-        if (padding_needed > 0) {
-            const zeros = [_]u8{0} ** 1024;
-            var remaining = padding_needed;
-            while (remaining > 0) {
-                const to_write = @min(remaining, zeros.len);
-                try sig_file.writeAll(zeros[0..to_write]);
-                remaining -= to_write;
-            }
-        }
+        try remote_hash_tool.writeSignatureWire("tmp/zig_sig.bin", signature, scheme.lifetime_params);
+        const SIG_LEN = remote_hash_tool.wire_signature_len;
         std.debug.print("✅ Signature saved to tmp/zig_sig.bin ({} bytes)\n", .{SIG_LEN});
     }
 
@@ -593,16 +565,10 @@ fn verifyCommand(allocator: Allocator, sig_path: []const u8, pk_path: []const u8
         defer allocator.free(pk_bytes);
         public_key = try hash_zig.GeneralizedXMSSPublicKey.fromBytes(pk_bytes, null);
     } else {
-        // Load signature from binary format (bincode)
-        // Import bincode functions from remote_hash_tool
+        // Load signature from the fixed 3116-byte wire format
         const remote_hash_tool = @import("remote_hash_tool.zig");
         const rand_len = scheme.lifetime_params.rand_len_fe;
-        const max_path_len: usize = scheme.lifetime_params.final_layer;
-        const hash_len = scheme.lifetime_params.hash_len_fe;
-        const max_hashes: usize = scheme.lifetime_params.dimension;
-        
-        // The readSignatureBincode function reads from file path directly
-        signature = try remote_hash_tool.readSignatureBincode(sig_path, allocator, rand_len, max_path_len, hash_len, max_hashes);
+        signature = try remote_hash_tool.readSignatureWire(sig_path, allocator, scheme.lifetime_params);
         
         // Debug: print rho from signature right after reading (before verify)
         const rho_after_read = signature.getRho();
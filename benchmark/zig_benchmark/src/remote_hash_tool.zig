@@ -9,6 +9,108 @@ const Command = enum {
 
 const UsageError = error{InvalidArguments};
 const BincodeError = error{ LengthOverflow, InvalidRandLength, InvalidPathLength, InvalidHashesLength };
+const HeaderError = error{ BadMagic, UnsupportedVersion, LifetimeMismatch, LengthMismatch };
+const WireError = error{ WrongLength, BadOffset, NonCanonicalValue, NonZeroPadding, SignatureTooLarge, InvalidPathLength, InvalidHashesLength };
+
+/// Header in front of the signatures exchanged with `remote_hashsig_tool`
+/// (see `compat::binfmt` on the Rust side): magic, format version, log
+/// lifetime, hash_len, rand_len.
+const header_magic = "HSIG";
+const public_key_magic = "HSPK";
+const header_version: u8 = 1;
+/// Set in the version byte when field elements are stored canonically
+/// rather than in Montgomery form.
+const canonical_flag: u8 = 0x80;
+/// Set in the version byte when counts and field elements are big-endian.
+/// Only the Rust tool writes such files; they are read in either order.
+const big_endian_flag: u8 = 0x40;
+
+/// How field elements are stored in a binary file.
+const Repr = enum { montgomery, canonical };
+
+/// Representation and byte order a header declares.
+const Layout = struct {
+    repr: Repr,
+    endian: std.builtin.Endian,
+};
+
+/// Public keys use the same header with magic "HSPK" and parameter_len as
+/// the last byte.
+fn writeHeader(writer: anytype, magic: *const [4]u8, params: hash_zig.signature.LifetimeParams, last_len: usize, repr: Repr) !void {
+    try writer.writeAll(magic);
+    try writer.writeAll(&[_]u8{
+        header_version | (if (repr == .canonical) canonical_flag else 0),
+        @intCast(params.log_lifetime),
+        @intCast(params.hash_len_fe),
+        @intCast(last_len),
+    });
+}
+
+/// Returns the representation and byte order the header declares.
+fn readHeader(reader: anytype, magic: *const [4]u8, params: hash_zig.signature.LifetimeParams, last_len: usize) !Layout {
+    var header: [8]u8 = undefined;
+    try reader.readNoEof(&header);
+    if (!std.mem.eql(u8, header[0..4], magic)) {
+        log.emit("Bad magic {any}, expected \"{s}\"\n", .{ header[0..4], magic });
+        return HeaderError.BadMagic;
+    }
+    const version = header[4] & ~(canonical_flag | big_endian_flag);
+    if (version != header_version) {
+        log.emit("Unsupported binary format version {}, expected {}\n", .{ version, header_version });
+        return HeaderError.UnsupportedVersion;
+    }
+    if (header[5] != params.log_lifetime) {
+        log.emit("File is for lifetime 2^{}, expected 2^{}\n", .{ header[5], params.log_lifetime });
+        return HeaderError.LifetimeMismatch;
+    }
+    if (header[6] != params.hash_len_fe or header[7] != last_len) {
+        log.emit("Header has lengths {} and {}, expected {} and {}\n", .{ header[6], header[7], params.hash_len_fe, last_len });
+        return HeaderError.LengthMismatch;
+    }
+    return .{
+        .repr = if (header[4] & canonical_flag != 0) .canonical else .montgomery,
+        .endian = if (header[4] & big_endian_flag != 0) .big else .little,
+    };
+}
+
+fn writeSignatureHeader(writer: anytype, params: hash_zig.signature.LifetimeParams, repr: Repr) !void {
+    try writeHeader(writer, header_magic, params, params.rand_len_fe, repr);
+}
+
+fn readSignatureHeader(reader: anytype, params: hash_zig.signature.LifetimeParams) !Layout {
+    return readHeader(reader, header_magic, params, params.rand_len_fe);
+}
+
+fn isBinaryPath(path: []const u8) bool {
+    return std.mem.endsWith(u8, path, ".bin");
+}
+
+/// Root and parameter in Montgomery form after an "HSPK" header, matching
+/// `compat::binfmt::write_public_key` on the Rust side.
+fn writePublicKeyBinary(path: []const u8, pk: *const hash_zig.GeneralizedXMSSPublicKey, params: hash_zig.signature.LifetimeParams) !void {
+    var file = try std.fs.cwd().createFile(path, .{ .truncate = true });
+    defer file.close();
+    const writer = file.writer();
+    const parameter = pk.getParameter();
+    try writeHeader(writer, public_key_magic, params, parameter.len, .montgomery);
+    try writeDomain(writer, pk.getRoot(), params.hash_len_fe, .montgomery);
+    for (parameter) |fe| {
+        try writeFieldElement(writer, fe, .montgomery);
+    }
+}
+
+fn readPublicKeyBinary(path: []const u8, params: hash_zig.signature.LifetimeParams) !hash_zig.GeneralizedXMSSPublicKey {
+    var file = try std.fs.cwd().openFile(path, .{});
+    defer file.close();
+    const reader = file.reader();
+    var parameter: [5]FieldElement = undefined;
+    const layout = try readHeader(reader, public_key_magic, params, parameter.len);
+    const root = try readDomain(reader, params.hash_len_fe, layout.repr, layout.endian);
+    for (&parameter) |*fe| {
+        fe.* = try readFieldElement(reader, layout.repr, layout.endian);
+    }
+    return hash_zig.GeneralizedXMSSPublicKey.init(root, parameter, params.hash_len_fe);
+}
 const LifetimeError = error{UnsupportedLifetime};
 
 const FieldElement = hash_zig.core.FieldElement;
@@ -32,35 +134,44 @@ fn writeLength(writer: anytype, value: usize) !void {
     try writer.writeInt(u64, @as(u64, value), .little);
 }
 
-fn readLength(reader: anytype) !usize {
-    const raw = try reader.readInt(u64, .little);
+fn readLength(reader: anytype, endian: std.builtin.Endian) !usize {
+    const raw = try reader.readInt(u64, endian);
     if (raw > std.math.maxInt(usize)) return BincodeError.LengthOverflow;
     return @intCast(raw);
 }
 
-fn writeFieldElement(writer: anytype, value: FieldElement) !void {
-    // Write Montgomery form directly (matching internal representation)
-    // Both libraries use Montgomery internally, so wrappers should use Montgomery too
-    try writer.writeInt(u32, value.toMontgomery(), .little);
+fn encodeFieldElement(value: FieldElement, repr: Repr) u32 {
+    return switch (repr) {
+        .montgomery => value.toMontgomery(),
+        .canonical => value.toCanonical(),
+    };
+}
+
+fn decodeFieldElement(raw: u32, repr: Repr) FieldElement {
+    return switch (repr) {
+        .montgomery => FieldElement.fromMontgomery(raw),
+        .canonical => FieldElement.fromCanonical(raw),
+    };
+}
+
+fn writeFieldElement(writer: anytype, value: FieldElement, repr: Repr) !void {
+    try writer.writeInt(u32, encodeFieldElement(value, repr), .little);
 }
 
-fn readFieldElement(reader: anytype) !FieldElement {
-    // Read Montgomery form directly (matching internal representation)
-    // Both libraries use Montgomery internally, so wrappers should use Montgomery too
-    const mont = try reader.readInt(u32, .little);
-    return FieldElement.fromMontgomery(mont);
+fn readFieldElement(reader: anytype, repr: Repr, endian: std.builtin.Endian) !FieldElement {
+    return decodeFieldElement(try reader.readInt(u32, endian), repr);
 }
 
-fn writeDomain(writer: anytype, domain: [8]FieldElement, active_len: usize) !void {
+fn writeDomain(writer: anytype, domain: [8]FieldElement, active_len: usize, repr: Repr) !void {
     for (domain[0..active_len]) |fe| {
-        try writeFieldElement(writer, fe);
+        try writeFieldElement(writer, fe, repr);
     }
 }
 
-fn readDomain(reader: anytype, active_len: usize) ![8]FieldElement {
+fn readDomain(reader: anytype, active_len: usize, repr: Repr, endian: std.builtin.Endian) ![8]FieldElement {
     var domain: [8]FieldElement = .{ FieldElement.zero(), FieldElement.zero(), FieldElement.zero(), FieldElement.zero(), FieldElement.zero(), FieldElement.zero(), FieldElement.zero(), FieldElement.zero() };
     for (0..active_len) |i| {
-        domain[i] = try readFieldElement(reader);
+        domain[i] = try readFieldElement(reader, repr, endian);
     }
     return domain;
 }
@@ -71,6 +182,9 @@ fn printUsage(writer: anytype) !void {
         \\  zig-remote-hash-tool sign <message> <pk_json_out> <sig_bin_out> [seed_hex] [epoch] [num_active_epochs] [start_epoch] [lifetime]
         \\  zig-remote-hash-tool verify <message> <pk_json_path> <sig_bin_path> [epoch] [lifetime]
         \\
+        \\Public key paths ending in .bin use the binary key layout instead of JSON.
+        \\Binary files are written in Montgomery form; files flagged canonical in their header are converted on read.
+        \\
     , .{});
 }
 
@@ -134,21 +248,17 @@ fn readPublicKeyFromJson(path: []const u8, allocator: std.mem.Allocator) !hash_z
 ///      - 8 u32 values in canonical form
 /// 
 /// This function writes the signature in bincode format matching Rust's serialization.
-/// The output is then padded to exactly 3116 bytes to comply with leanSpec:
-/// https://github.com/leanEthereum/leanSpec/blob/main/src/lean_spec/subspecs/containers/signature.py
-/// 
-/// The leanSpec requires:
-///   1. Signature container: exactly 3116 bytes (Bytes3116)
-///   2. Signature data: bincode format at the beginning
-///   3. Can be sliced to scheme.config.SIGNATURE_LEN_BYTES if needed
-///   4. Format: XmssSignature.from_bytes (bincode deserialization)
+/// It is not the leanSpec wire format; use `writeSignatureWire` for that.
 /// 
 /// Note: Rust's bincode serializes field elements in canonical form, not Montgomery
 pub fn writeSignatureBincode(path: []const u8, signature: *const hash_zig.GeneralizedXMSSSignature, rand_len: usize, hash_len: usize) !void {
     var file = try std.fs.cwd().createFile(path, .{ .truncate = true });
     defer file.close();
+    try writeSignatureBody(file.writer(), signature, rand_len, hash_len, .canonical);
+}
 
-    const writer = file.writer();
+/// Bincode files store canonical values; binary files say which in their header.
+fn writeSignatureBody(writer: anytype, signature: *const hash_zig.GeneralizedXMSSSignature, rand_len: usize, hash_len: usize, repr: Repr) !void {
     const path_nodes = signature.getPath().getNodes();
     
     // Write path_len (u64) - Vec length
@@ -159,7 +269,7 @@ pub fn writeSignatureBincode(path: []const u8, signature: *const hash_zig.Genera
     for (path_nodes) |node| {
         // Write array elements in canonical form (fixed-size array, no length prefix)
         for (node[0..hash_len]) |fe| {
-            try writer.writeInt(u32, fe.toCanonical(), .little);
+            try writeFieldElement(writer, fe, repr);
         }
     }
 
@@ -170,11 +280,11 @@ pub fn writeSignatureBincode(path: []const u8, signature: *const hash_zig.Genera
     if (rand_len > rho.len) return BincodeError.InvalidRandLength;
     // Debug: print rho values as written to file (for cross-language debugging)
     const stderr = std.io.getStdErr().writer();
-    stderr.print("ZIG_WRITE_DEBUG: Writing rho to file (Canonical): ", .{}) catch {};
+    stderr.print("ZIG_WRITE_DEBUG: Writing rho to file ({s}): ", .{@tagName(repr)}) catch {};
     for (rho[0..rand_len]) |fe| {
-        const canonical = fe.toCanonical();
-        try writer.writeInt(u32, canonical, .little);
-        stderr.print("0x{x:0>8} ", .{canonical}) catch {};
+        const raw = encodeFieldElement(fe, repr);
+        try writer.writeInt(u32, raw, .little);
+        stderr.print("0x{x:0>8} ", .{raw}) catch {};
     }
     stderr.print("\n", .{}) catch {};
 
@@ -188,9 +298,153 @@ pub fn writeSignatureBincode(path: []const u8, signature: *const hash_zig.Genera
     for (hashes) |domain| {
         // Write array elements in canonical form (fixed-size array, no length prefix)
         for (domain[0..hash_len]) |fe| {
-            try writer.writeInt(u32, fe.toCanonical(), .little);
+            try writeFieldElement(writer, fe, repr);
+        }
+    }
+}
+
+/// Size of every signature in the leanSignature wire format.
+pub const wire_signature_len: usize = 3116;
+const koalabear_prime: u32 = 0x7f000001;
+
+/// Byte offsets of the wire format fields for one lifetime, matching
+/// `compat::lean_wire::Layout` on the Rust side:
+///   offset(path) u32 | rho | offset(hashes) u32 | offset(siblings) u32 = 4
+///   | log_lifetime co-path nodes | dimension hashes | zero padding
+const WireLayout = struct {
+    rho: usize,
+    hashes_offset: usize,
+    path: usize,
+    nodes: usize,
+    hashes: usize,
+    end: usize,
+
+    fn of(params: hash_zig.signature.LifetimeParams) WireLayout {
+        const node_bytes = params.hash_len_fe * 4;
+        const rho: usize = 4;
+        const hashes_offset = rho + params.rand_len_fe * 4;
+        const path = hashes_offset + 4;
+        const nodes = path + 4;
+        const hashes = nodes + @as(usize, params.log_lifetime) * node_bytes;
+        return .{
+            .rho = rho,
+            .hashes_offset = hashes_offset,
+            .path = path,
+            .nodes = nodes,
+            .hashes = hashes,
+            .end = hashes + params.dimension * node_bytes,
+        };
+    }
+};
+
+fn putWireU32(buf: []u8, at: usize, value: usize) void {
+    std.mem.writeInt(u32, buf[at..][0..4], @intCast(value), .little);
+}
+
+fn getWireU32(buf: []const u8, at: usize) u32 {
+    return std.mem.readInt(u32, buf[at..][0..4], .little);
+}
+
+fn getWireFieldElement(buf: []const u8, at: usize) !FieldElement {
+    const raw = getWireU32(buf, at);
+    if (raw >= koalabear_prime) {
+        log.emit("Wire signature value 0x{x:0>8} at byte {} is not canonical\n", .{ raw, at });
+        return WireError.NonCanonicalValue;
+    }
+    return FieldElement.fromCanonical(raw);
+}
+
+/// Write a signature in the fixed 3116-byte leanSignature wire format, field
+/// by field at the offsets of `WireLayout`, canonical values, zero padded.
+pub fn writeSignatureWire(path: []const u8, signature: *const hash_zig.GeneralizedXMSSSignature, params: hash_zig.signature.LifetimeParams) !void {
+    const layout = WireLayout.of(params);
+    if (layout.end > wire_signature_len) return WireError.SignatureTooLarge;
+    const path_nodes = signature.getPath().getNodes();
+    if (path_nodes.len != params.log_lifetime) return WireError.InvalidPathLength;
+    const hashes = signature.getHashes();
+    if (hashes.len != params.dimension) return WireError.InvalidHashesLength;
+
+    var buf = [_]u8{0} ** wire_signature_len;
+    const node_bytes = params.hash_len_fe * 4;
+    putWireU32(&buf, 0, layout.path);
+    for (signature.getRho()[0..params.rand_len_fe], 0..) |fe, i| {
+        putWireU32(&buf, layout.rho + i * 4, fe.toCanonical());
+    }
+    putWireU32(&buf, layout.hashes_offset, layout.hashes);
+    putWireU32(&buf, layout.path, 4);
+    for (path_nodes, 0..) |node, i| {
+        for (node[0..params.hash_len_fe], 0..) |fe, j| {
+            putWireU32(&buf, layout.nodes + i * node_bytes + j * 4, fe.toCanonical());
         }
     }
+    for (hashes, 0..) |domain, i| {
+        for (domain[0..params.hash_len_fe], 0..) |fe, j| {
+            putWireU32(&buf, layout.hashes + i * node_bytes + j * 4, fe.toCanonical());
+        }
+    }
+
+    var file = try std.fs.cwd().createFile(path, .{ .truncate = true });
+    defer file.close();
+    try file.writeAll(&buf);
+}
+
+/// Read a wire format signature, rejecting any other length, unexpected
+/// offsets, non-canonical values and non-zero padding.
+pub fn readSignatureWire(path: []const u8, allocator: std.mem.Allocator, params: hash_zig.signature.LifetimeParams) !*hash_zig.GeneralizedXMSSSignature {
+    const buf = try std.fs.cwd().readFileAlloc(allocator, path, wire_signature_len + 1);
+    defer allocator.free(buf);
+    if (buf.len != wire_signature_len) {
+        log.emit("Wire signature is {} bytes, expected exactly {}\n", .{ buf.len, wire_signature_len });
+        return WireError.WrongLength;
+    }
+    const layout = WireLayout.of(params);
+    if (layout.end > wire_signature_len) return WireError.SignatureTooLarge;
+    const offsets = [_][2]usize{ .{ 0, layout.path }, .{ layout.hashes_offset, layout.hashes }, .{ layout.path, 4 } };
+    for (offsets) |entry| {
+        const found = getWireU32(buf, entry[0]);
+        if (found != entry[1]) {
+            log.emit("Wire signature offset at byte {} is {}, expected {}\n", .{ entry[0], found, entry[1] });
+            return WireError.BadOffset;
+        }
+    }
+    for (buf[layout.end..], layout.end..) |b, i| {
+        if (b != 0) {
+            log.emit("Wire signature has non-zero padding at byte {}\n", .{i});
+            return WireError.NonZeroPadding;
+        }
+    }
+
+    const node_bytes = params.hash_len_fe * 4;
+    const path_nodes = try allocator.alloc([8]FieldElement, @as(usize, params.log_lifetime));
+    defer allocator.free(path_nodes);
+    for (path_nodes, 0..) |*node, i| {
+        node.* = [_]FieldElement{FieldElement.zero()} ** 8;
+        for (0..params.hash_len_fe) |j| {
+            node[j] = try getWireFieldElement(buf, layout.nodes + i * node_bytes + j * 4);
+        }
+    }
+
+    var rho = [_]FieldElement{FieldElement.zero()} ** 7;
+    if (params.rand_len_fe > rho.len) return BincodeError.InvalidRandLength;
+    for (0..params.rand_len_fe) |i| {
+        rho[i] = try getWireFieldElement(buf, layout.rho + i * 4);
+    }
+
+    const hashes = try allocator.alloc([8]FieldElement, params.dimension);
+    defer allocator.free(hashes);
+    for (hashes, 0..) |*domain, i| {
+        domain.* = [_]FieldElement{FieldElement.zero()} ** 8;
+        for (0..params.hash_len_fe) |j| {
+            domain[j] = try getWireFieldElement(buf, layout.hashes + i * node_bytes + j * 4);
+        }
+    }
+
+    // HashTreeOpening.init and initDeserialized copy their inputs
+    var path_ptr = try HashTreeOpening.init(allocator, path_nodes);
+    return hash_zig.GeneralizedXMSSSignature.initDeserialized(allocator, path_ptr, rho, hashes) catch |err| {
+        path_ptr.deinit();
+        return err;
+    };
 }
 
 /// Read signature from Rust bincode format (matching Rust's bincode::deserialize)
@@ -208,11 +462,12 @@ pub fn writeSignatureBincode(path: []const u8, signature: *const hash_zig.Genera
 pub fn readSignatureBincode(path: []const u8, allocator: std.mem.Allocator, rand_len: usize, max_path_len: usize, hash_len: usize, max_hashes: usize) !*hash_zig.GeneralizedXMSSSignature {
     var file = try std.fs.cwd().openFile(path, .{});
     defer file.close();
+    return readSignatureBody(file.reader(), allocator, rand_len, max_path_len, hash_len, max_hashes, .canonical, .little);
+}
 
-    const reader = file.reader();
-
+fn readSignatureBody(reader: anytype, allocator: std.mem.Allocator, rand_len: usize, max_path_len: usize, hash_len: usize, max_hashes: usize, repr: Repr, endian: std.builtin.Endian) !*hash_zig.GeneralizedXMSSSignature {
     // Read path_len (u64) - Vec length
-    const path_len = try readLength(reader);
+    const path_len = try readLength(reader, endian);
     if (path_len == 0 or path_len > max_path_len) return BincodeError.InvalidPathLength;
     
     // Read path nodes (each has: HASH_LEN u32 values in CANONICAL form, NO length prefix for fixed arrays)
@@ -227,8 +482,7 @@ pub fn readSignatureBincode(path: []const u8, allocator: std.mem.Allocator, rand
         // Read array elements in canonical form (fixed-size array, no length prefix)
         // CRITICAL: Read exactly hash_len elements (matching Rust's FieldArray<HASH_LEN>)
         for (0..hash_len) |j| {
-            const canonical = try reader.readInt(u32, .little);
-            path_nodes[i][j] = FieldElement.fromCanonical(canonical);
+            path_nodes[i][j] = try readFieldElement(reader, repr, endian);
         }
         // Pad remaining with zeros if hash_len < 8
         for (hash_len..8) |j| {
@@ -251,16 +505,16 @@ pub fn readSignatureBincode(path: []const u8, allocator: std.mem.Allocator, rand
     var rho = [_]FieldElement{FieldElement.zero()} ** 7;
     // Debug: print rho values as read from file (for cross-language debugging)
     const stderr = std.io.getStdErr().writer();
-    stderr.print("ZIG_READ_DEBUG: Reading rho from file (Canonical, rand_len={}): ", .{rand_len}) catch {};
+    stderr.print("ZIG_READ_DEBUG: Reading rho from file ({s}, rand_len={}): ", .{ @tagName(repr), rand_len }) catch {};
     for (0..rand_len) |i| {
-        const canonical = try reader.readInt(u32, .little);
-        rho[i] = FieldElement.fromCanonical(canonical);
-        stderr.print("0x{x:0>8} ", .{canonical}) catch {};
+        const raw = try reader.readInt(u32, endian);
+        rho[i] = decodeFieldElement(raw, repr);
+        stderr.print("0x{x:0>8} ", .{raw}) catch {};
     }
     stderr.print("\n", .{}) catch {};
 
     // Read hashes_len (u64) - Vec length
-    const hashes_len = try readLength(reader);
+    const hashes_len = try readLength(reader, endian);
     if (hashes_len == 0 or hashes_len > max_hashes) {
         path_ptr.deinit();
         return BincodeError.InvalidHashesLength;
@@ -278,8 +532,7 @@ pub fn readSignatureBincode(path: []const u8, allocator: std.mem.Allocator, rand
         // Read array elements in canonical form (fixed-size array, no length prefix)
         // CRITICAL: Read exactly hash_len elements (matching Rust's FieldArray<HASH_LEN>)
         for (0..hash_len) |j| {
-            const canonical = try reader.readInt(u32, .little);
-            hashes_tmp[i][j] = FieldElement.fromCanonical(canonical);
+            hashes_tmp[i][j] = try readFieldElement(reader, repr, endian);
         }
         // Pad remaining with zeros if hash_len < 8
         for (hash_len..8) |j| {
@@ -312,6 +565,23 @@ pub fn readSignatureBincode(path: []const u8, allocator: std.mem.Allocator, rand
     return signature_ptr;
 }
 
+/// Header followed by the signature body, as `remote_hashsig_tool` expects.
+fn writeSignatureBinary(path: []const u8, signature: *const hash_zig.GeneralizedXMSSSignature, params: hash_zig.signature.LifetimeParams) !void {
+    var file = try std.fs.cwd().createFile(path, .{ .truncate = true });
+    defer file.close();
+    const writer = file.writer();
+    try writeSignatureHeader(writer, params, .montgomery);
+    try writeSignatureBody(writer, signature, params.rand_len_fe, params.hash_len_fe, .montgomery);
+}
+
+fn readSignatureBinary(path: []const u8, allocator: std.mem.Allocator, params: hash_zig.signature.LifetimeParams) !*hash_zig.GeneralizedXMSSSignature {
+    var file = try std.fs.cwd().openFile(path, .{});
+    defer file.close();
+    const reader = file.reader();
+    const layout = try readSignatureHeader(reader, params);
+    return readSignatureBody(reader, allocator, params.rand_len_fe, params.final_layer, params.hash_len_fe, params.dimension, layout.repr, layout.endian);
+}
+
 fn signCommand(
     allocator: std.mem.Allocator,
     message: []const u8,
@@ -353,8 +623,12 @@ fn signCommand(
     const verify_ok = try scheme.verify(&keypair.public_key, epoch, message_bytes, signature_ptr);
     log.print("ZIG_REMOTE_DEBUG: internal verify result: {}\n", .{verify_ok});
 
-    try writePublicKeyToJson(pk_path, &keypair.public_key);
-    try writeSignatureBincode(sig_path, signature_ptr, scheme.lifetime_params.rand_len_fe, scheme.lifetime_params.hash_len_fe);
+    if (isBinaryPath(pk_path)) {
+        try writePublicKeyBinary(pk_path, &keypair.public_key, scheme.lifetime_params);
+    } else {
+        try writePublicKeyToJson(pk_path, &keypair.public_key);
+    }
+    try writeSignatureBinary(sig_path, signature_ptr, scheme.lifetime_params);
 }
 
 fn verifyCommand(
@@ -374,19 +648,15 @@ fn verifyCommand(
         std.process.exit(1);
     };
 
-    var pk = try readPublicKeyFromJson(pk_path, arena_allocator);
-
     var scheme = try hash_zig.GeneralizedXMSSSignatureScheme.init(arena_allocator, lifetime);
     defer scheme.deinit();
 
-    var signature_ptr = try readSignatureBincode(
-        sig_path,
-        arena_allocator,
-        scheme.lifetime_params.rand_len_fe,
-        scheme.lifetime_params.final_layer,
-        scheme.lifetime_params.hash_len_fe,
-        scheme.lifetime_params.dimension,
-    );
+    var pk = if (isBinaryPath(pk_path))
+        try readPublicKeyBinary(pk_path, scheme.lifetime_params)
+    else
+        try readPublicKeyFromJson(pk_path, arena_allocator);
+
+    var signature_ptr = try readSignatureBinary(sig_path, arena_allocator, scheme.lifetime_params);
     defer signature_ptr.deinit();
 
     const msg_bytes = messageToBytes(message);
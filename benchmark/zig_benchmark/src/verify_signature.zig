@@ -22,38 +22,145 @@ fn parseLifetimeTag(raw: []const u8) hash_zig.KeyLifetimeRustCompat {
     return .lifetime_2_8;
 }
 
+const usage =
+    \\Usage: zig-verify-signature --pk-file <path> --sig-file <path> --message-file <path>
+    \\                            [--epoch <n>] [--lifetime <2^8|2^18|2^32>]
+    \\
+    \\Any one of the files may be `-` for stdin; without --sig-file the signature
+    \\is read from stdin. The PUBLIC_KEY, SIGNATURE, MESSAGE, EPOCH and LIFETIME
+    \\environment variables are still read when the matching argument is absent,
+    \\but are deprecated: a 2^18 signature exceeds some platforms' environment
+    \\size limits.
+    \\
+;
+
+const Options = struct {
+    pk_file: ?[]const u8 = null,
+    sig_file: ?[]const u8 = null,
+    message_file: ?[]const u8 = null,
+    epoch: ?[]const u8 = null,
+    lifetime: ?[]const u8 = null,
+};
+
+fn usageError(comptime fmt: []const u8, args: anytype) noreturn {
+    log.emit(fmt ++ "\n" ++ usage, args);
+    std.process.exit(1);
+}
+
+fn parseArgs(args: []const [:0]u8) Options {
+    var options = Options{};
+    var i: usize = 1;
+    while (i < args.len) : (i += 1) {
+        const arg = args[i];
+        if (std.mem.eql(u8, arg, "-h") or std.mem.eql(u8, arg, "--help")) {
+            std.io.getStdOut().writeAll(usage) catch {};
+            std.process.exit(0);
+        }
+        const slot = if (std.mem.eql(u8, arg, "--pk-file"))
+            &options.pk_file
+        else if (std.mem.eql(u8, arg, "--sig-file"))
+            &options.sig_file
+        else if (std.mem.eql(u8, arg, "--message-file"))
+            &options.message_file
+        else if (std.mem.eql(u8, arg, "--epoch"))
+            &options.epoch
+        else if (std.mem.eql(u8, arg, "--lifetime"))
+            &options.lifetime
+        else
+            usageError("Unknown argument '{s}'", .{arg});
+        i += 1;
+        if (i == args.len) usageError("{s} needs a value", .{arg});
+        slot.* = args[i];
+    }
+    return options;
+}
+
+/// An input's bytes, and whether they came from a deprecated environment variable.
+const Input = struct {
+    data: []const u8,
+    from_env: bool,
+};
+
+fn readStdin(allocator: std.mem.Allocator, stdin_taken: *bool) ![]const u8 {
+    if (stdin_taken.*) usageError("Only one input can be read from stdin", .{});
+    stdin_taken.* = true;
+    return std.io.getStdIn().readToEndAlloc(allocator, std.math.maxInt(usize));
+}
+
+/// Read `path` (`-` for stdin), falling back to the environment variable
+/// `env_name` and then, if `stdin_default`, to stdin.
+fn readInput(
+    allocator: std.mem.Allocator,
+    path: ?[]const u8,
+    flag: []const u8,
+    env_name: []const u8,
+    stdin_default: bool,
+    stdin_taken: *bool,
+) !Input {
+    if (path) |p| {
+        const data = if (std.mem.eql(u8, p, "-"))
+            try readStdin(allocator, stdin_taken)
+        else
+            std.fs.cwd().readFileAlloc(allocator, p, std.math.maxInt(usize)) catch |err| {
+                log.emit("Failed to read {s} {s}: {}\n", .{ flag, p, err });
+                std.process.exit(1);
+            };
+        return .{ .data = data, .from_env = false };
+    }
+    if (std.process.getEnvVarOwned(allocator, env_name)) |value| {
+        log.emit("The {s} environment variable is deprecated; pass {s} instead\n", .{ env_name, flag });
+        return .{ .data = value, .from_env = true };
+    } else |_| {}
+    if (stdin_default) return .{ .data = try readStdin(allocator, stdin_taken), .from_env = false };
+    usageError("Missing {s}", .{flag});
+}
+
+/// `value` if given on the command line, else the deprecated environment variable `env_name`.
+fn scalarOption(allocator: std.mem.Allocator, value: ?[]const u8, flag: []const u8, env_name: []const u8) ?[]const u8 {
+    if (value) |v| return v;
+    const env_value = std.process.getEnvVarOwned(allocator, env_name) catch return null;
+    log.emit("The {s} environment variable is deprecated; pass {s} instead\n", .{ env_name, flag });
+    return env_value;
+}
+
 pub fn main() !void {
     var gpa = std.heap.GeneralPurposeAllocator(.{}){};
     defer _ = gpa.deinit();
     const allocator = gpa.allocator();
+    // Inputs live until exit, so they share one arena
+    var arena_state = std.heap.ArenaAllocator.init(allocator);
+    defer arena_state.deinit();
+    const arena = arena_state.allocator();
 
-    // Get environment variables
-    const public_key_data = std.process.getEnvVarOwned(allocator, "PUBLIC_KEY") catch {
-        log.emit("Missing PUBLIC_KEY environment variable\n", .{});
-        std.process.exit(1);
-    };
-    defer allocator.free(public_key_data);
-
-    const signature_data = std.process.getEnvVarOwned(allocator, "SIGNATURE") catch {
-        log.emit("Missing SIGNATURE environment variable\n", .{});
-        std.process.exit(1);
-    };
-    defer allocator.free(signature_data);
+    const args = try std.process.argsAlloc(arena);
+    const options = parseArgs(args);
 
-    const message = std.process.getEnvVarOwned(allocator, "MESSAGE") catch {
-        log.emit("Missing MESSAGE environment variable\n", .{});
-        std.process.exit(1);
-    };
-    defer allocator.free(message);
+    var stdin_taken = false;
+    const public_key_input = try readInput(arena, options.pk_file, "--pk-file", "PUBLIC_KEY", false, &stdin_taken);
+    const message_input = try readInput(arena, options.message_file, "--message-file", "MESSAGE", false, &stdin_taken);
+    const signature_input = try readInput(arena, options.sig_file, "--sig-file", "SIGNATURE", true, &stdin_taken);
 
-    const lifetime_env = std.process.getEnvVarOwned(allocator, "LIFETIME") catch null;
-    const lifetime_tag = if (lifetime_env) |value| value else "2^8";
-    defer if (lifetime_env) |value| allocator.free(value);
+    const lifetime_tag = scalarOption(arena, options.lifetime, "--lifetime", "LIFETIME") orelse "2^8";
     const lifetime = parseLifetimeTag(lifetime_tag);
 
-    const epoch_str = std.process.getEnvVarOwned(allocator, "EPOCH") catch "0";
-    defer allocator.free(epoch_str);
-    const epoch = std.fmt.parseInt(u32, epoch_str, 10) catch 0;
+    const epoch_str = scalarOption(arena, options.epoch, "--epoch", "EPOCH") orelse "0";
+    const epoch = std.fmt.parseInt(u32, std.mem.trim(u8, epoch_str, " \t\r\n"), 10) catch {
+        usageError("Invalid epoch '{s}'", .{epoch_str});
+    };
+
+    // Files usually end in a newline that is not part of the message
+    const message = if (message_input.from_env)
+        message_input.data
+    else
+        std.mem.trimRight(u8, message_input.data, "\r\n");
+    const public_key_data = std.mem.trim(u8, public_key_input.data, " \t\r\n");
+    // The SIGNATURE: prefix is required in the environment variable and optional in files
+    const maybe_signature_json: ?[]const u8 = if (signature_input.from_env)
+        (if (std.mem.startsWith(u8, signature_input.data, "SIGNATURE:")) signature_input.data[10..] else null)
+    else blk: {
+        const trimmed = std.mem.trim(u8, signature_input.data, " \t\r\n");
+        break :blk if (std.mem.startsWith(u8, trimmed, "SIGNATURE:")) trimmed[10..] else trimmed;
+    };
 
     // Convert message to bytes (truncate/pad to 32 bytes)
     var message_bytes: [32]u8 = [_]u8{0} ** 32;
@@ -61,8 +168,7 @@ pub fn main() !void {
     @memcpy(message_bytes[0..copy_len], message[0..copy_len]);
 
     // Parse the signature and public key data
-    if (std.mem.startsWith(u8, signature_data, "SIGNATURE:")) {
-        const signature_json = signature_data[10..]; // Skip "SIGNATURE:" prefix
+    if (maybe_signature_json) |signature_json| {
 
         // Parse public key
         const public_key_json = if (std.mem.startsWith(u8, public_key_data, "PUBLIC_KEY:"))
@@ -146,6 +252,6 @@ pub fn main() !void {
         try stdout_final.print("VERIFY_RESULT:{}\n", .{is_valid});
     } else {
         const stdout_err = std.io.getStdOut().writer();
-        try stdout_err.print("VERIFY_RESULT:false (signature_data doesn't start with SIGNATURE:)\n", .{});
+        try stdout_err.print("VERIFY_RESULT:false (SIGNATURE doesn't start with SIGNATURE:)\n", .{});
     }
 }
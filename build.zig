@@ -230,6 +230,23 @@ pub fn build(b: *std.Build) void {
     }
     b.installArtifact(cross_lang_zig_tool_exe);
 
+    // C ABI static library linked in-process by the Rust crate's `zig-ffi` feature
+    const c_api_module = b.createModule(.{
+        .root_source_file = b.path("benchmark/zig_benchmark/src/c_api.zig"),
+        .target = target,
+        .optimize = optimize,
+    });
+    c_api_module.addImport("hash-zig", hash_zig_module);
+
+    const c_api_lib = b.addLibrary(.{
+        .name = "hash-zig-c",
+        .linkage = .static,
+        .root_module = c_api_module,
+    });
+    c_api_lib.bundle_compiler_rt = true;
+    const c_api_step = b.step("c-api", "Build the hash-zig-c static library for Rust FFI");
+    c_api_step.dependOn(&b.addInstallArtifact(c_api_lib, .{}).step);
+
     // Rust compatibility test step (for CI)
     const rust_test_step = b.step("test-rust-compat", "Run ONLY Rust compatibility tests");
     rust_test_step.dependOn(&run_rust_compat_tests.step);
@@ -5,3 +5,4 @@ pub const GeneralizedXMSSPublicKey = native.GeneralizedXMSSPublicKey;
 pub const GeneralizedXMSSSecretKey = native.GeneralizedXMSSSecretKey;
 pub const GeneralizedXMSSSignature = native.GeneralizedXMSSSignature;
 pub const HashTreeOpening = native.HashTreeOpening;
+pub const LifetimeParams = native.LifetimeParams;